@@ -0,0 +1,41 @@
+use monitor_core::{
+    audit,
+    db::DatabasePool,
+    events::{Event, EventBus},
+    supervisor::{Backoff, RestartPolicy, Supervisor},
+};
+use serde_json::json;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::warn;
+
+/// Records audit entries for events published on the bus until shutdown.
+pub fn spawn(supervisor: &Supervisor, db: DatabasePool, events: &EventBus) {
+    let events = events.clone();
+    supervisor.spawn("audit_log", RestartPolicy::OnFailure(Backoff::default()), move |mut shutdown| {
+        let db = db.clone();
+        let mut receiver = events.subscribe();
+        async move {
+            loop {
+                let received = tokio::select! {
+                    received = receiver.recv() => received,
+                    _ = shutdown.wait() => return Ok(()),
+                };
+                match received {
+                    Ok(event) => record(&db, &event).await,
+                    Err(RecvError::Lagged(skipped)) => warn!("Audit logger skipped {} events", skipped),
+                    Err(RecvError::Closed) => return Ok(()),
+                }
+            }
+        }
+    });
+}
+
+async fn record(db: &DatabasePool, event: &Event) {
+    let Event::MonitorCreated { monitor, actor_id } = event else {
+        return;
+    };
+    let details = json!({ "name": monitor.name, "endpoint": monitor.endpoint });
+    if let Err(e) = audit::record(db, *actor_id, audit::ACTION_MONITOR_CREATED, Some(monitor.id), details).await {
+        warn!("Failed to record the creation of monitor {}: {}", monitor.id, e);
+    }
+}
@@ -0,0 +1,241 @@
+use axum::{
+    extract::{FromRequestParts, Request, State},
+    http::{HeaderMap, Method, header::AUTHORIZATION, request::Parts},
+    middleware::Next,
+    response::Response,
+};
+use monitor_core::{
+    Error, Result,
+    auth::{PERSONAL_TOKEN_PREFIX, hash_token},
+    models::{PersonalAccessToken, TokenKind, TokenScope, UserRole},
+    repository,
+};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::server::{ApiError, AppState};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthMethod {
+    Jwt,
+    PersonalToken(Uuid),
+    DashboardToken(Uuid),
+}
+
+/// The caller of an authenticated request.
+///
+/// JWT sessions carry every permission of the user; personal access tokens are
+/// limited to their scopes and, when set, to monitors carrying one of their tags.
+/// Dashboard tokens can only read results of their allow-listed monitors and tags.
+#[derive(Debug, Clone)]
+pub struct AuthenticatedUser {
+    pub user_id: Uuid,
+    pub username: String,
+    pub role: UserRole,
+    pub method: AuthMethod,
+    pub scopes: Option<Vec<TokenScope>>,
+    pub tags: Vec<String>,
+    pub monitor_ids: Vec<Uuid>,
+}
+
+impl AuthenticatedUser {
+    pub fn has_scope(&self, scope: TokenScope) -> bool {
+        match &self.scopes {
+            None => true,
+            Some(scopes) => scopes.contains(&scope),
+        }
+    }
+
+    pub fn require_scope(&self, scope: TokenScope) -> Result<()> {
+        if self.has_scope(scope) {
+            Ok(())
+        } else {
+            Err(Error::forbidden(format!("Token is missing the {} scope", scope.as_str())))
+        }
+    }
+
+    pub fn require_any_scope(&self, scopes: &[TokenScope]) -> Result<()> {
+        match scopes.iter().find(|scope| self.has_scope(**scope)) {
+            Some(_) => Ok(()),
+            None => Err(Error::forbidden(format!(
+                "Token is missing one of the {} scopes",
+                scopes.iter().map(|s| s.as_str()).collect::<Vec<_>>().join(", ")
+            ))),
+        }
+    }
+
+    pub fn can_access_tags(&self, monitor_tags: &[String]) -> bool {
+        self.tags.is_empty() || monitor_tags.iter().any(|t| self.tags.contains(t))
+    }
+
+    /// Tag restrictions plus the monitor allow-list of dashboard tokens.
+    pub fn can_access_monitor(&self, monitor_id: Uuid, monitor_tags: &[String]) -> bool {
+        match self.method {
+            AuthMethod::DashboardToken(_) => {
+                self.monitor_ids.contains(&monitor_id) || monitor_tags.iter().any(|t| self.tags.contains(t))
+            }
+            _ => self.can_access_tags(monitor_tags),
+        }
+    }
+
+    pub fn require_admin(&self) -> Result<()> {
+        if matches!(self.method, AuthMethod::DashboardToken(_)) {
+            return Err(Error::forbidden("Dashboard tokens cannot be used for administration"));
+        }
+        if self.role == UserRole::Admin {
+            Ok(())
+        } else {
+            Err(Error::forbidden("Administrator role required"))
+        }
+    }
+
+    pub fn require_jwt(&self) -> Result<()> {
+        match self.method {
+            AuthMethod::Jwt => Ok(()),
+            AuthMethod::PersonalToken(_) | AuthMethod::DashboardToken(_) => Err(Error::forbidden(
+                "This action requires an interactive session, not a personal access token",
+            )),
+        }
+    }
+}
+
+/// `/api` routes reachable without a session. Provisioning checks its own token.
+const PUBLIC_PATHS: &[&str] = &["/api/auth/login", "/api/auth/register"];
+const PUBLIC_PREFIXES: &[&str] = &["/api/provisioning/", "/api/heartbeat/"];
+
+/// The only routes dashboard tokens may call, all read with `GET`. A `*`
+/// segment matches any single path segment.
+const DASHBOARD_PATHS: &[&str] = &[
+    "/api/status",
+    "/api/changes",
+    "/api/compare",
+    "/api/monitors/*/status",
+    "/api/monitors/*/timeseries",
+    "/api/monitors/*/latency",
+    "/api/monitors/*/failures",
+    "/api/monitors/*/availability/calendar",
+    "/api/monitors/*/downtime.ics",
+];
+
+fn bearer_token(headers: &HeaderMap) -> Result<&str> {
+    headers
+        .get(AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(str::trim)
+        .ok_or_else(|| Error::auth("Missing bearer token"))
+}
+
+async fn load_active_user(state: &AppState, user_id: Uuid) -> Result<(String, UserRole)> {
+    repository::get_active_user(&state.db, user_id)
+        .await?
+        .map(|user| (user.username, user.role))
+        .ok_or_else(|| Error::auth("User account is not active"))
+}
+
+async fn authenticate_personal_token(state: &AppState, token: &str) -> Result<AuthenticatedUser> {
+    let record = sqlx::query_as::<_, PersonalAccessToken>(
+        "SELECT * FROM personal_access_tokens WHERE token_hash = $1 AND revoked_at IS NULL",
+    )
+    .bind(hash_token(token))
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| Error::auth("Invalid personal access token"))?;
+
+    if record.expires_at.is_some_and(|expires_at| expires_at <= state.clock.now()) {
+        return Err(Error::auth("Personal access token has expired"));
+    }
+
+    let (username, role) = load_active_user(state, record.user_id).await?;
+
+    sqlx::query("UPDATE personal_access_tokens SET last_used_at = NOW() WHERE id = $1")
+        .bind(record.id)
+        .execute(&state.db)
+        .await?;
+
+    let method = match record.kind {
+        TokenKind::Personal => AuthMethod::PersonalToken(record.id),
+        TokenKind::Dashboard => AuthMethod::DashboardToken(record.id),
+    };
+    Ok(AuthenticatedUser {
+        user_id: record.user_id,
+        username,
+        role,
+        method,
+        scopes: Some(record.scopes.iter().filter_map(|s| TokenScope::parse(s)).collect()),
+        tags: record.tags,
+        monitor_ids: record.monitor_ids,
+    })
+}
+
+async fn authenticate(state: &AppState, headers: &HeaderMap) -> Result<AuthenticatedUser> {
+    let token = bearer_token(headers)?;
+
+    if token.starts_with(PERSONAL_TOKEN_PREFIX) {
+        return authenticate_personal_token(state, token).await;
+    }
+
+    let claims = state.auth.verify_token(token).map_err(|_| Error::auth("Invalid token"))?;
+    let (username, role) = load_active_user(state, claims.user_id).await?;
+    Ok(AuthenticatedUser {
+        user_id: claims.user_id,
+        username,
+        role,
+        method: AuthMethod::Jwt,
+        scopes: None,
+        tags: Vec::new(),
+        monitor_ids: Vec::new(),
+    })
+}
+
+fn is_public(path: &str) -> bool {
+    !path.starts_with("/api/")
+        || PUBLIC_PATHS.contains(&path)
+        || PUBLIC_PREFIXES.iter().any(|prefix| path.starts_with(prefix))
+}
+
+fn is_dashboard_route(method: &Method, path: &str) -> bool {
+    *method == Method::GET
+        && DASHBOARD_PATHS.iter().any(|pattern| {
+            pattern.split('/').count() == path.split('/').count()
+                && pattern
+                    .split('/')
+                    .zip(path.split('/'))
+                    .all(|(expected, actual)| expected == actual || (expected == "*" && !actual.is_empty()))
+        })
+}
+
+/// Rejects `/api` requests without a valid bearer token before they reach a
+/// handler, and dashboard tokens on anything but their read-only routes. The
+/// caller is stored in the request extensions, where the `AuthenticatedUser`
+/// extractor picks it up without authenticating again.
+pub async fn require_auth(
+    State(state): State<Arc<AppState>>,
+    mut request: Request,
+    next: Next,
+) -> std::result::Result<Response, ApiError> {
+    if !is_public(request.uri().path()) {
+        let user = authenticate(&state, request.headers()).await?;
+        if matches!(user.method, AuthMethod::DashboardToken(_))
+            && !is_dashboard_route(request.method(), request.uri().path())
+        {
+            return Err(Error::forbidden("Dashboard tokens can only read monitor status and stats").into());
+        }
+        request.extensions_mut().insert(user);
+    }
+    Ok(next.run(request).await)
+}
+
+impl FromRequestParts<Arc<AppState>> for AuthenticatedUser {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> std::result::Result<Self, Self::Rejection> {
+        if let Some(user) = parts.extensions.get::<AuthenticatedUser>() {
+            return Ok(user.clone());
+        }
+        Ok(authenticate(state, &parts.headers).await?)
+    }
+}
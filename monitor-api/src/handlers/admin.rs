@@ -0,0 +1,213 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::Json,
+};
+use chrono::Duration;
+use monitor_core::{
+    Error,
+    audit::{self, AuditEntry},
+    duplicates::{self, DuplicateGroup},
+    logging,
+    redaction::{self, RedactionRule},
+    repository::{self, MonitorSort},
+    retention::{self, PurgeReport},
+    runtime_settings::{self, SCHEDULER_LOG_FILTER},
+    secrets::{self, KeyUsageReport},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::sync::Arc;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::{
+    auth::AuthenticatedUser,
+    server::{ApiError, AppState},
+};
+
+#[derive(Debug, Deserialize)]
+pub struct PurgeQuery {
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+pub async fn purge_user_data(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Path(user_id): Path<Uuid>,
+    Query(query): Query<PurgeQuery>,
+) -> Result<Json<PurgeReport>, ApiError> {
+    user.require_jwt()?;
+    user.require_admin()?;
+
+    let report = retention::purge_user_data(&state.db, user_id, query.dry_run).await?;
+    if !query.dry_run {
+        warn!(
+            "User data for {} purged by {}: {} rows",
+            user_id,
+            user.username,
+            report.total()
+        );
+    }
+
+    Ok(Json(report))
+}
+
+pub async fn run_result_retention(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Query(query): Query<PurgeQuery>,
+) -> Result<Json<PurgeReport>, ApiError> {
+    user.require_admin()?;
+
+    let cutoff = state.clock.now() - Duration::days(state.config.current().retention.result_days);
+    let report = retention::purge_expired_results(&state.db, cutoff, query.dry_run).await?;
+
+    Ok(Json(report))
+}
+
+/// Reports which encrypted records still use plaintext or a retired key version.
+pub async fn encryption_key_usage(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+) -> Result<Json<KeyUsageReport>, ApiError> {
+    user.require_admin()?;
+
+    let report = secrets::key_usage(&state.db, &state.keys).await?;
+    Ok(Json(report))
+}
+
+/// Monitors that check the same or nearly the same endpoint, with a suggestion
+/// for which one to keep.
+pub async fn monitor_duplicates(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+) -> Result<Json<Vec<DuplicateGroup>>, ApiError> {
+    user.require_admin()?;
+
+    let monitors = repository::all_monitors(&state.db, MonitorSort::CreatedAt).await?;
+    Ok(Json(duplicates::find_duplicates(&monitors)))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AuditLogQuery {
+    pub monitor_id: Option<Uuid>,
+    #[serde(default = "default_audit_limit")]
+    pub limit: i64,
+}
+
+fn default_audit_limit() -> i64 {
+    100
+}
+
+pub async fn audit_log(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Query(query): Query<AuditLogQuery>,
+) -> Result<Json<Vec<AuditEntry>>, ApiError> {
+    user.require_admin()?;
+
+    let limit = query.limit.clamp(1, 1000);
+    Ok(Json(audit::list(&state.db, query.monitor_id, limit).await?))
+}
+
+/// Active configuration with secrets redacted, reflecting any hot-reloaded changes.
+pub async fn config_snapshot(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+) -> Result<Json<Value>, ApiError> {
+    user.require_admin()?;
+
+    Ok(Json(state.config.snapshot()?))
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogComponent {
+    #[default]
+    Api,
+    Scheduler,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LogLevelRequest {
+    #[serde(default)]
+    pub component: LogComponent,
+    /// `EnvFilter` directives such as `info,monitor_scheduler=debug`; `null`
+    /// restores the configured `logging.level`.
+    pub filter: Option<String>,
+}
+
+pub async fn get_log_level(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+) -> Result<Json<Value>, ApiError> {
+    user.require_admin()?;
+
+    let scheduler = runtime_settings::get(&state.db, SCHEDULER_LOG_FILTER).await?;
+    Ok(Json(json!({
+        "api": logging::current_filter(),
+        "scheduler": scheduler,
+        "configured": state.config.current().logging.level,
+    })))
+}
+
+/// Changes the tracing filter at runtime. The API applies it immediately; the
+/// scheduler picks it up from `runtime_settings` within 30 seconds.
+pub async fn set_log_level(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Json(request): Json<LogLevelRequest>,
+) -> Result<Json<Value>, ApiError> {
+    user.require_admin()?;
+
+    if let Some(filter) = &request.filter
+        && filter.trim().is_empty()
+    {
+        return Err(Error::validation("Log filter must not be empty").into());
+    }
+
+    let filter = match (request.component, request.filter) {
+        (LogComponent::Api, filter) => {
+            let filter = filter.unwrap_or_else(|| state.config.current().logging.level.clone());
+            logging::set_filter(&filter)?;
+            Some(filter)
+        }
+        (LogComponent::Scheduler, Some(filter)) => {
+            logging::validate_filter(&filter)?;
+            runtime_settings::set(&state.db, SCHEDULER_LOG_FILTER, &filter).await?;
+            Some(filter)
+        }
+        (LogComponent::Scheduler, None) => {
+            runtime_settings::delete(&state.db, SCHEDULER_LOG_FILTER).await?;
+            None
+        }
+    };
+
+    info!("Log filter for {:?} set to {:?} by {}", request.component, filter, user.username);
+    Ok(Json(json!({ "component": request.component, "filter": filter })))
+}
+
+pub async fn get_redaction_rules(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+) -> Result<Json<Vec<RedactionRule>>, ApiError> {
+    user.require_admin()?;
+
+    Ok(Json(redaction::load_rules(&state.db).await?))
+}
+
+/// Replaces the rules applied to check results before they are stored or
+/// notified. Schedulers pick them up within 30 seconds; stored results are
+/// not rewritten.
+pub async fn set_redaction_rules(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Json(rules): Json<Vec<RedactionRule>>,
+) -> Result<Json<Vec<RedactionRule>>, ApiError> {
+    user.require_admin()?;
+
+    redaction::save_rules(&state.db, &rules).await?;
+    info!("{} result redaction rules set by {}", rules.len(), user.username);
+    Ok(Json(rules))
+}
@@ -0,0 +1,149 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+};
+use monitor_core::{
+    Error,
+    alerts::{self, AlertNotification, AlertRule, AlertState, ChannelConfig},
+    models::{Alert, TokenScope},
+    pagerduty::{self, EventAction},
+    states::{self, MonitorState, ThresholdsRequest},
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    auth::AuthenticatedUser,
+    handlers::monitors::{load_accessible_monitor, load_editable_monitor},
+    server::{ApiError, AppState},
+};
+
+#[derive(Debug, Deserialize)]
+pub struct CreateAlertRequest {
+    /// `down`, `latency` or `expiry`
+    #[serde(rename = "type")]
+    pub alert_type: String,
+    /// Thresholds and notification channels of the rule
+    #[serde(default)]
+    pub config: serde_json::Value,
+}
+
+pub async fn list_alerts(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<Alert>>, ApiError> {
+    user.require_scope(TokenScope::ReadMonitors)?;
+    let monitor = load_accessible_monitor(&state, &user, id).await?;
+    Ok(Json(alerts::list_for_monitor(&state.db, monitor.id).await?))
+}
+
+pub async fn create_alert(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    Json(request): Json<CreateAlertRequest>,
+) -> Result<(StatusCode, Json<Alert>), ApiError> {
+    user.require_scope(TokenScope::WriteMonitors)?;
+    let monitor = load_editable_monitor(&state, &user, id).await?;
+    let config = match request.config {
+        serde_json::Value::Null => serde_json::json!({}),
+        config => config,
+    };
+    let alert = alerts::create(&state.db, monitor.id, &request.alert_type, &config).await?;
+    info!("User {} added a {} alert to {}", user.username, alert.type_, monitor.name);
+    Ok((StatusCode::CREATED, Json(alert)))
+}
+
+pub async fn delete_alert(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    user.require_scope(TokenScope::WriteMonitors)?;
+    let alert = alerts::get(&state.db, id)
+        .await?
+        .ok_or_else(|| Error::not_found(format!("Alert {} not found", id)))?;
+    load_editable_monitor(&state, &user, alert.monitor_id).await?;
+    alerts::delete(&state.db, id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// The monitor's health and the thresholds that move it between states.
+pub async fn get_state(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<MonitorState>, ApiError> {
+    user.require_scope(TokenScope::ReadMonitors)?;
+    let monitor = load_accessible_monitor(&state, &user, id).await?;
+    Ok(Json(states::get(&state.db, monitor.id, state.clock.now()).await?))
+}
+
+pub async fn set_state_thresholds(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    Json(request): Json<ThresholdsRequest>,
+) -> Result<Json<MonitorState>, ApiError> {
+    user.require_scope(TokenScope::WriteMonitors)?;
+    let monitor = load_editable_monitor(&state, &user, id).await?;
+    let updated = states::set_thresholds(&state.db, monitor.id, &request).await?;
+    info!(
+        "User {} set the state thresholds of {} to {}/{}",
+        user.username, monitor.name, updated.failure_threshold, updated.recovery_threshold
+    );
+    Ok(Json(updated))
+}
+
+#[derive(Debug, Serialize)]
+pub struct AcknowledgeOutcome {
+    /// PagerDuty channels the acknowledgement was sent to
+    pub acknowledged: usize,
+}
+
+/// Acknowledges the PagerDuty incidents of a firing rule so on-call is no
+/// longer paged. The incident still resolves when the monitor recovers.
+pub async fn acknowledge_alert(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<AcknowledgeOutcome>, ApiError> {
+    user.require_scope(TokenScope::WriteMonitors)?;
+    let alert = alerts::get(&state.db, id)
+        .await?
+        .ok_or_else(|| Error::not_found(format!("Alert {} not found", id)))?;
+    let monitor = load_editable_monitor(&state, &user, alert.monitor_id).await?;
+    let rule = AlertRule::from_alert(&alert)?;
+    if !rule.firing {
+        return Err(Error::validation(format!("Alert {} is not firing", id)).into());
+    }
+
+    let notification = AlertNotification::new(
+        &rule,
+        &monitor,
+        AlertState::Firing,
+        format!("Acknowledged by {}", user.username),
+        None,
+        state.clock.now(),
+    );
+    let client = reqwest::Client::new();
+    let mut acknowledged = 0;
+    for channel in &rule.channels {
+        if let ChannelConfig::PagerDuty { routing_key, .. } = channel {
+            let event =
+                pagerduty::event(routing_key, EventAction::Acknowledge, pagerduty::DEFAULT_SEVERITY, &notification);
+            pagerduty::enqueue(&client, &event).await?;
+            acknowledged += 1;
+        }
+    }
+    if acknowledged == 0 {
+        return Err(Error::validation(format!("Alert {} has no PagerDuty channels", id)).into());
+    }
+
+    info!("User {} acknowledged the {} alert of {}", user.username, rule.alert_type, monitor.name);
+    Ok(Json(AcknowledgeOutcome { acknowledged }))
+}
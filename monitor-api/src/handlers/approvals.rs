@@ -0,0 +1,114 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::Json,
+};
+use monitor_core::{
+    Error,
+    approvals::{self, ChangeRequest, ChangeRequestStatus},
+    audit,
+    models::{Monitor, TokenScope, UpdateMonitorRequest},
+    repository,
+};
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::Arc;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    auth::AuthenticatedUser,
+    handlers::monitors::{announce_change, load_accessible_monitor, load_editable_monitor},
+    server::{ApiError, AppState},
+};
+
+#[derive(Debug, Deserialize)]
+pub struct ChangeRequestQuery {
+    pub status: Option<ChangeRequestStatus>,
+}
+
+/// Change requests for accessible monitors, oldest first.
+pub async fn list_change_requests(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Query(query): Query<ChangeRequestQuery>,
+) -> Result<Json<Vec<ChangeRequest>>, ApiError> {
+    user.require_scope(TokenScope::ReadMonitors)?;
+
+    let mut visible = Vec::new();
+    for request in approvals::list(&state.db, query.status).await? {
+        if load_accessible_monitor(&state, &user, request.monitor_id).await.is_ok() {
+            visible.push(request);
+        }
+    }
+    Ok(Json(visible))
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ReviewRequest {
+    pub comment: Option<String>,
+}
+
+/// Loads a pending change the caller may decide: they must be able to edit the
+/// monitor and must not have requested the change. Approvals need a session.
+async fn load_reviewable(state: &AppState, user: &AuthenticatedUser, id: Uuid) -> Result<ChangeRequest, ApiError> {
+    user.require_jwt()?;
+    user.require_scope(TokenScope::WriteMonitors)?;
+
+    let change = approvals::get(&state.db, id).await?;
+    load_editable_monitor(state, user, change.monitor_id).await?;
+    change.check_reviewer(user.user_id)?;
+    Ok(change)
+}
+
+/// Claims the pending change and applies it in one transaction, so two
+/// reviewers approving at once cannot both apply it.
+pub async fn approve_change(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    Json(review): Json<ReviewRequest>,
+) -> Result<Json<Monitor>, ApiError> {
+    let change = load_reviewable(&state, &user, id).await?;
+    let update: UpdateMonitorRequest = serde_json::from_value(change.changes.clone()).map_err(Error::from)?;
+
+    let mut tx = state.db.begin().await.map_err(Error::from)?;
+    approvals::decide(&mut *tx, id, user.user_id, ChangeRequestStatus::Approved, review.comment.as_deref()).await?;
+    let monitor = repository::update_monitor(&mut *tx, change.monitor_id, &update, change.credentials.as_deref())
+        .await?
+        .ok_or_else(|| Error::not_found(format!("Monitor {} not found", change.monitor_id)))?;
+    tx.commit().await.map_err(Error::from)?;
+    announce_change(&state, change.monitor_id);
+
+    audit::record(
+        &state.db,
+        Some(user.user_id),
+        audit::ACTION_CHANGE_APPROVED,
+        Some(change.monitor_id),
+        json!({ "change_request_id": id, "requested_by": change.requested_by }),
+    )
+    .await?;
+    info!("User {} approved change {} to monitor {}", user.username, id, monitor.name);
+    Ok(Json(monitor))
+}
+
+pub async fn reject_change(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    Json(review): Json<ReviewRequest>,
+) -> Result<Json<ChangeRequest>, ApiError> {
+    let change = load_reviewable(&state, &user, id).await?;
+
+    let rejected =
+        approvals::decide(&state.db, id, user.user_id, ChangeRequestStatus::Rejected, review.comment.as_deref()).await?;
+    audit::record(
+        &state.db,
+        Some(user.user_id),
+        audit::ACTION_CHANGE_REJECTED,
+        Some(change.monitor_id),
+        json!({ "change_request_id": id, "comment": review.comment }),
+    )
+    .await?;
+    info!("User {} rejected change {}", user.username, id);
+    Ok(Json(rejected))
+}
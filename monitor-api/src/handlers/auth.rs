@@ -0,0 +1,84 @@
+use axum::{extract::State, http::StatusCode, response::Json};
+use monitor_core::{
+    Error, Result,
+    models::{AuthResponse, LoginRequest, RegisterRequest, User},
+    repository,
+};
+use std::sync::Arc;
+use tracing::info;
+
+use crate::{
+    auth::AuthenticatedUser,
+    handlers::provisioning::{validate_email, validate_username},
+    server::{ApiError, AppState},
+};
+
+const MIN_PASSWORD_LENGTH: usize = 8;
+
+/// Creates an account and signs it in. The first account becomes an admin so
+/// a fresh installation can be administered without touching the database.
+pub async fn register(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<RegisterRequest>,
+) -> std::result::Result<(StatusCode, Json<AuthResponse>), ApiError> {
+    validate_username(&request.username)?;
+    validate_email(&request.email)?;
+    if request.password.chars().count() < MIN_PASSWORD_LENGTH {
+        return Err(Error::validation(format!(
+            "Password must be at least {} characters",
+            MIN_PASSWORD_LENGTH
+        ))
+        .into());
+    }
+
+    if repository::username_or_email_taken(&state.db, &request.username, &request.email).await? {
+        return Err(Error::validation("Username or email is already registered").into());
+    }
+
+    let password_hash = state.auth.hash_password(&request.password)?;
+    let user = repository::insert_user(&state.db, &request.username, &request.email, &password_hash, None).await?;
+
+    info!("Registered user {}", user.username);
+    Ok((StatusCode::CREATED, Json(issue_token(&state, user)?)))
+}
+
+pub async fn login(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<LoginRequest>,
+) -> std::result::Result<Json<AuthResponse>, ApiError> {
+    let user = repository::find_active_login(&state.db, &request.username).await?;
+
+    // Same error for unknown users and wrong passwords
+    let Some(user) = user.filter(|user| {
+        state
+            .auth
+            .verify_password(&request.password, &user.password_hash)
+            .unwrap_or(false)
+    }) else {
+        return Err(Error::auth("Invalid username or password").into());
+    };
+
+    Ok(Json(issue_token(&state, user)?))
+}
+
+/// Exchanges a still-valid session token for a new one.
+pub async fn refresh(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+) -> std::result::Result<Json<AuthResponse>, ApiError> {
+    user.require_jwt()?;
+
+    let user = repository::get_active_user(&state.db, user.user_id)
+        .await?
+        .ok_or_else(|| Error::auth("User account is not active"))?;
+
+    Ok(Json(issue_token(&state, user)?))
+}
+
+fn issue_token(state: &AppState, user: User) -> Result<AuthResponse> {
+    Ok(AuthResponse {
+        token: state.auth.generate_token(user.id, &user.username)?,
+        expires_in: state.auth.token_lifetime(),
+        user,
+    })
+}
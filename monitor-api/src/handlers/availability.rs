@@ -0,0 +1,80 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::header,
+    response::{IntoResponse, Json},
+};
+use chrono::{DateTime, Duration, Utc};
+use monitor_core::{availability, models::TokenScope};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    auth::AuthenticatedUser,
+    handlers::monitors::load_accessible_monitor,
+    server::{ApiError, AppState},
+};
+
+#[derive(Debug, Deserialize)]
+pub struct CalendarQuery {
+    /// `YYYY-MM`, defaults to the current month
+    pub month: Option<String>,
+}
+
+/// Month grid (Monday-first weeks) of daily availability with the downtime
+/// events that started on each day.
+pub async fn get_availability_calendar(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    Query(query): Query<CalendarQuery>,
+) -> Result<Json<Value>, ApiError> {
+    user.require_scope(TokenScope::ReadResults)?;
+    let monitor = load_accessible_monitor(&state, &user, id).await?;
+
+    let month = query
+        .month
+        .unwrap_or_else(|| state.clock.now().format("%Y-%m").to_string());
+    let first = availability::parse_month(&month)?;
+
+    let days = availability::month_availability(&state.db, id, first).await?;
+    let (checks, successes) = days.iter().fold((0.0, 0.0), |(checks, successes), day| {
+        let day_checks = day.checks as f64;
+        (checks + day_checks, successes + day.availability.unwrap_or(0.0) * day_checks)
+    });
+
+    Ok(Json(json!({
+        "monitor": { "id": monitor.id, "name": monitor.name },
+        "month": month,
+        "availability": (checks > 0.0).then(|| successes / checks),
+        "downtime_feed": format!("/api/monitors/{}/downtime.ics", monitor.id),
+        "weeks": availability::month_grid(days),
+    })))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DowntimeFeedQuery {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+/// Downtime events as an iCalendar feed; defaults to the last 90 days.
+pub async fn get_downtime_ical(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    Query(query): Query<DowntimeFeedQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    user.require_scope(TokenScope::ReadResults)?;
+    let monitor = load_accessible_monitor(&state, &user, id).await?;
+
+    let to = query.to.unwrap_or_else(|| state.clock.now());
+    let from = query.from.unwrap_or(to - Duration::days(90));
+    let events = availability::downtime_events(&state.db, id, from, to).await?;
+
+    Ok((
+        [(header::CONTENT_TYPE, "text/calendar; charset=utf-8")],
+        availability::to_ical(monitor.id, &monitor.name, &events, state.clock.now()),
+    ))
+}
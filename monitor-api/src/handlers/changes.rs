@@ -0,0 +1,79 @@
+use axum::{
+    extract::{Query, State},
+    response::Json,
+};
+use monitor_core::{
+    changes::{self, StateChange},
+    models::TokenScope,
+};
+use serde::{Deserialize, Serialize};
+use std::{sync::Arc, time::Duration};
+use tokio::time::Instant;
+
+use crate::{
+    auth::AuthenticatedUser,
+    server::{ApiError, AppState},
+};
+
+const MAX_WAIT_SECS: u64 = 60;
+const CHANGE_BATCH: i64 = 200;
+
+#[derive(Debug, Deserialize)]
+pub struct ChangesQuery {
+    /// Cursor from the previous response; omit to get the current cursor
+    pub since: Option<String>,
+    /// Seconds to wait for a change before returning an empty change set
+    #[serde(default = "default_wait")]
+    pub timeout: u64,
+}
+
+fn default_wait() -> u64 {
+    30
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChangeSet {
+    pub cursor: String,
+    pub changes: Vec<StateChange>,
+}
+
+/// Long-polls until an accessible monitor changes status after `since`, or the
+/// timeout passes. Either way the response carries the cursor for the next call.
+pub async fn poll_changes(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Query(query): Query<ChangesQuery>,
+) -> Result<Json<ChangeSet>, ApiError> {
+    user.require_any_scope(&[TokenScope::ReadMonitors, TokenScope::ReadResults])?;
+
+    let Some(since) = query.since else {
+        let cursor = changes::latest_cursor(&state.db).await?;
+        return Ok(Json(ChangeSet { cursor: cursor.to_string(), changes: Vec::new() }));
+    };
+    let mut cursor = changes::parse_cursor(&since)?;
+    let deadline = Instant::now() + Duration::from_secs(query.timeout.clamp(1, MAX_WAIT_SECS));
+
+    // Subscribed before the first query so a change in between still wakes us
+    let mut signal = state.results.change_signal();
+    loop {
+        let scanned = changes::since(&state.db, cursor, CHANGE_BATCH).await?;
+        let full_batch = scanned.len() as i64 == CHANGE_BATCH;
+        cursor = changes::next_cursor(cursor, &scanned);
+
+        let visible: Vec<StateChange> = scanned
+            .into_iter()
+            .filter(|c| user.can_access_monitor(c.monitor_id, &c.tags))
+            .collect();
+        if !visible.is_empty() {
+            return Ok(Json(ChangeSet { cursor: cursor.to_string(), changes: visible }));
+        }
+        if full_batch {
+            continue;
+        }
+
+        match tokio::time::timeout_at(deadline, signal.changed()).await {
+            Ok(Ok(())) => continue,
+            _ => return Ok(Json(ChangeSet { cursor: cursor.to_string(), changes: Vec::new() })),
+        }
+    }
+}
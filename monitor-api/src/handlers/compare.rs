@@ -0,0 +1,95 @@
+use axum::{
+    extract::{Query, State},
+    response::Json,
+};
+use chrono::{DateTime, Duration, Utc};
+use monitor_core::{
+    Error,
+    models::TokenScope,
+    stats::{self, TimeseriesMetric},
+};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    auth::AuthenticatedUser,
+    handlers::monitors::load_accessible_monitor,
+    server::{ApiError, AppState},
+};
+
+const MAX_COMPARED_MONITORS: usize = 10;
+
+#[derive(Debug, Deserialize)]
+pub struct CompareQuery {
+    /// Comma separated monitor ids
+    pub ids: String,
+    pub metric: TimeseriesMetric,
+    #[serde(default = "default_step")]
+    pub step: String,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+fn default_step() -> String {
+    "1h".to_string()
+}
+
+/// Side-by-side series for several monitors on a shared bucket axis, plus
+/// summary statistics per monitor over the whole range.
+pub async fn compare_monitors(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Query(query): Query<CompareQuery>,
+) -> Result<Json<Value>, ApiError> {
+    user.require_scope(TokenScope::ReadResults)?;
+
+    let ids = query
+        .ids
+        .split(',')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(|id| Uuid::parse_str(id).map_err(|_| Error::validation(format!("Invalid monitor id: {}", id))))
+        .collect::<Result<Vec<_>, _>>()?;
+    if ids.is_empty() || ids.len() > MAX_COMPARED_MONITORS {
+        return Err(Error::validation(format!(
+            "Compare between 1 and {} monitors",
+            MAX_COMPARED_MONITORS
+        ))
+        .into());
+    }
+
+    let step = stats::parse_step(&query.step)?;
+    let to = query.to.unwrap_or_else(|| state.clock.now());
+    let from = query.from.unwrap_or(to - Duration::days(1));
+
+    let mut series = Vec::with_capacity(ids.len());
+    let mut monitors = Vec::with_capacity(ids.len());
+    for id in ids {
+        let monitor = load_accessible_monitor(&state, &user, id).await?;
+        let points = stats::timeseries(&state.db, id, query.metric, step, from, to).await?;
+        let summary = stats::latency_summary(&state.db, id, from, to).await?;
+        series.push((id, points));
+        monitors.push(json!({
+            "id": monitor.id,
+            "name": monitor.name,
+            "summary": summary,
+        }));
+    }
+
+    let (buckets, aligned) = stats::align_series(&series);
+    for (monitor, aligned) in monitors.iter_mut().zip(aligned) {
+        monitor["avg"] = json!(aligned.avg);
+        monitor["p95"] = json!(aligned.p95);
+    }
+
+    Ok(Json(json!({
+        "metric": query.metric,
+        "step": query.step,
+        "from": from,
+        "to": to,
+        "buckets": buckets,
+        "monitors": monitors,
+    })))
+}
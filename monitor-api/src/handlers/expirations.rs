@@ -0,0 +1,76 @@
+use axum::{
+    extract::{Query, State},
+    response::Json,
+};
+use monitor_core::{
+    Error,
+    expirations::{self, Expiration},
+    models::{Alert, TokenScope},
+};
+use serde::Deserialize;
+use std::sync::Arc;
+use tracing::info;
+
+use crate::{
+    auth::AuthenticatedUser,
+    server::{ApiError, AppState},
+};
+
+#[derive(Debug, Deserialize)]
+pub struct ExpirationsQuery {
+    /// Only include expirations with fewer days remaining
+    pub within_days: Option<i64>,
+}
+
+/// Upcoming certificate and domain expirations across accessible monitors,
+/// soonest first.
+pub async fn list_expirations(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Query(query): Query<ExpirationsQuery>,
+) -> Result<Json<Vec<Expiration>>, ApiError> {
+    user.require_scope(TokenScope::ReadMonitors)?;
+
+    let expirations = expirations::upcoming(&state.db, query.within_days, state.clock.now()).await?;
+    Ok(Json(
+        expirations
+            .into_iter()
+            .filter(|e| user.can_access_monitor(e.monitor_id, &e.tags))
+            .collect(),
+    ))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExpiryAlertRulesRequest {
+    pub threshold_days: i64,
+}
+
+/// Creates an `expiry` alert for every accessible monitor with a certificate or
+/// domain expiring within `threshold_days`. Monitors that already have one are skipped.
+pub async fn create_expiry_alert_rules(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Json(request): Json<ExpiryAlertRulesRequest>,
+) -> Result<Json<Vec<Alert>>, ApiError> {
+    user.require_scope(TokenScope::WriteMonitors)?;
+
+    if request.threshold_days <= 0 {
+        return Err(Error::validation("threshold_days must be positive").into());
+    }
+
+    let expiring: Vec<Expiration> =
+        expirations::upcoming(&state.db, Some(request.threshold_days), state.clock.now())
+            .await?
+            .into_iter()
+            .filter(|e| user.can_access_monitor(e.monitor_id, &e.tags))
+            .collect();
+    let created = expirations::create_alert_rules(&state.db, &expiring, request.threshold_days).await?;
+
+    info!(
+        "User {} created {} expiry alert rules (threshold {} days)",
+        user.username,
+        created.len(),
+        request.threshold_days
+    );
+    Ok(Json(created))
+}
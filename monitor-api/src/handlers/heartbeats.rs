@@ -0,0 +1,83 @@
+use axum::{
+    extract::{Path, State},
+    response::Json,
+};
+use monitor_core::{
+    Error,
+    heartbeats::{self, HeartbeatCheck},
+    models::{Monitor, TokenScope},
+};
+use serde_json::{Value, json};
+use std::sync::Arc;
+use tracing::{debug, info};
+use uuid::Uuid;
+
+use crate::{
+    auth::AuthenticatedUser,
+    handlers::monitors::{load_accessible_monitor, load_editable_monitor},
+    server::{ApiError, AppState},
+};
+
+fn heartbeat_check(monitor: &Monitor) -> Result<HeartbeatCheck, Error> {
+    let check = monitor
+        .heartbeat_check
+        .as_ref()
+        .ok_or_else(|| Error::validation(format!("Monitor {} is not a heartbeat monitor", monitor.id)))?;
+    serde_json::from_value(check.clone()).map_err(Error::from)
+}
+
+/// Pings received so far and when the next one is due at the latest.
+pub async fn get_heartbeat(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Value>, ApiError> {
+    user.require_scope(TokenScope::ReadMonitors)?;
+    let monitor = load_accessible_monitor(&state, &user, id).await?;
+    let check = heartbeat_check(&monitor)?;
+
+    let heartbeat = heartbeats::get(&state.db, id).await?;
+    let last_ping = heartbeat.as_ref().and_then(|h| h.last_ping_at);
+    let since = heartbeat.as_ref().map(|h| h.created_at).unwrap_or(monitor.created_at);
+    Ok(Json(json!({
+        "monitor_id": id,
+        "token_issued": heartbeat.is_some(),
+        "last_ping_at": last_ping,
+        "pings": heartbeat.as_ref().map(|h| h.pings).unwrap_or(0),
+        "deadline": check.deadline(monitor.interval, last_ping, since),
+    })))
+}
+
+/// Issues the ping token, replacing the previous one. The token is shown
+/// only in this response.
+pub async fn issue_token(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Value>, ApiError> {
+    user.require_scope(TokenScope::WriteMonitors)?;
+    let monitor = load_editable_monitor(&state, &user, id).await?;
+    heartbeat_check(&monitor)?;
+
+    let (token, heartbeat) = heartbeats::issue_token(&state.db, id).await?;
+    info!("User {} issued a heartbeat token for monitor {}", user.username, id);
+    Ok(Json(json!({
+        "token": token,
+        "path": format!("/api/heartbeat/{}", token),
+        "details": heartbeat,
+    })))
+}
+
+/// Where jobs report in. The token is the only credential, so the route is
+/// public.
+pub async fn ping(State(state): State<Arc<AppState>>, Path(token): Path<String>) -> Result<Json<Value>, ApiError> {
+    let received_at = state.clock.now();
+    let monitor_id = heartbeats::record_ping(&state.db, &token, received_at)
+        .await?
+        .ok_or_else(|| Error::not_found("Unknown heartbeat token"))?;
+    debug!("Heartbeat received for monitor {}", monitor_id);
+    Ok(Json(json!({
+        "monitor_id": monitor_id,
+        "received_at": received_at,
+    })))
+}
@@ -0,0 +1,126 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Json,
+};
+use monitor_core::{
+    aws::AwsClient,
+    imports::{self, AcceptProposalRequest, ImportProposal, ProposalStatus},
+    models::{Monitor, TokenScope},
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    auth::AuthenticatedUser,
+    handlers::monitors::insert_monitor,
+    server::{ApiError, AppState},
+};
+
+#[derive(Debug, Deserialize)]
+pub struct AwsScanRequest {
+    /// `route53`, `elb` and/or `cloudfront`; all of them when empty
+    #[serde(default)]
+    pub sources: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScanOutcome {
+    pub found: usize,
+    /// New or refreshed proposals; the rest already have a monitor or were decided
+    pub proposed: usize,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProposalQuery {
+    pub status: Option<ProposalStatus>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AcceptedProposal {
+    pub proposal: ImportProposal,
+    pub monitor: Monitor,
+}
+
+/// Lists the account's Route53 records, load balancers and CloudFront
+/// distributions and stores them as proposals to review.
+pub async fn scan_aws(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Json(request): Json<AwsScanRequest>,
+) -> Result<Json<ScanOutcome>, ApiError> {
+    user.require_admin()?;
+
+    let client = AwsClient::new(&state.config.current().imports.aws)?;
+    let candidates = client.scan(&request.sources).await?;
+    let proposed = imports::propose(&state.db, &candidates).await?;
+    info!(
+        "User {} scanned AWS: {} resources, {} proposals",
+        user.username,
+        candidates.len(),
+        proposed
+    );
+    Ok(Json(ScanOutcome {
+        found: candidates.len(),
+        proposed,
+    }))
+}
+
+pub async fn list_proposals(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Query(query): Query<ProposalQuery>,
+) -> Result<Json<Vec<ImportProposal>>, ApiError> {
+    user.require_scope(TokenScope::ReadMonitors)?;
+    Ok(Json(imports::list(&state.db, query.status).await?))
+}
+
+/// Creates the proposed monitor, owned by the caller.
+pub async fn accept_proposal(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    Json(request): Json<AcceptProposalRequest>,
+) -> Result<(StatusCode, Json<AcceptedProposal>), ApiError> {
+    user.require_scope(TokenScope::WriteMonitors)?;
+
+    let proposal = imports::decide(&state.db, id, user.user_id, ProposalStatus::Accepted).await?;
+    let created = match proposal.monitor_request(&request) {
+        Ok(create) => insert_monitor(&state, &user, &create).await,
+        Err(e) => Err(e),
+    };
+    let monitor = match created {
+        Ok(monitor) => monitor,
+        Err(e) => {
+            imports::reopen(&state.db, id).await?;
+            return Err(e.into());
+        }
+    };
+    imports::set_monitor(&state.db, id, monitor.id).await?;
+    info!("User {} accepted proposal {} as monitor {}", user.username, proposal.resource_id, monitor.name);
+
+    Ok((
+        StatusCode::CREATED,
+        Json(AcceptedProposal {
+            proposal: ImportProposal {
+                monitor_id: Some(monitor.id),
+                ..proposal
+            },
+            monitor,
+        }),
+    ))
+}
+
+/// Rejected resources are not proposed again by later scans.
+pub async fn reject_proposal(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ImportProposal>, ApiError> {
+    user.require_scope(TokenScope::WriteMonitors)?;
+    let proposal = imports::decide(&state.db, id, user.user_id, ProposalStatus::Rejected).await?;
+    info!("User {} rejected proposal {}", user.username, proposal.resource_id);
+    Ok(Json(proposal))
+}
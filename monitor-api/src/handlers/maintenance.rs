@@ -0,0 +1,107 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+};
+use monitor_core::{
+    Error,
+    maintenance::{
+        self, CreateMaintenanceSourceRequest, CreateMaintenanceWindowRequest, MaintenanceSource, MaintenanceWindow,
+    },
+    models::TokenScope,
+};
+use std::sync::Arc;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    auth::AuthenticatedUser,
+    handlers::monitors::{load_accessible_monitor, load_editable_monitor},
+    server::{ApiError, AppState},
+};
+
+const UPCOMING_LIMIT: i64 = 200;
+
+pub async fn list_sources(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+) -> Result<Json<Vec<MaintenanceSource>>, ApiError> {
+    user.require_admin()?;
+    Ok(Json(maintenance::list_sources(&state.db).await?))
+}
+
+/// Adds a calendar; the scheduler fetches it within 15 minutes.
+pub async fn create_source(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Json(request): Json<CreateMaintenanceSourceRequest>,
+) -> Result<(StatusCode, Json<MaintenanceSource>), ApiError> {
+    user.require_admin()?;
+    let source = maintenance::create_source(&state.db, &request, user.user_id).await?;
+    info!("User {} added maintenance calendar {} for tag {}", user.username, source.name, source.tag);
+    Ok((StatusCode::CREATED, Json(source)))
+}
+
+pub async fn delete_source(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    user.require_admin()?;
+    if !maintenance::delete_source(&state.db, id).await? {
+        return Err(Error::not_found(format!("Maintenance calendar {} not found", id)).into());
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Current and future windows of all calendars.
+pub async fn list_windows(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+) -> Result<Json<Vec<MaintenanceWindow>>, ApiError> {
+    user.require_scope(TokenScope::ReadResults)?;
+    Ok(Json(maintenance::upcoming_windows(&state.db, state.clock.now(), UPCOMING_LIMIT).await?))
+}
+
+/// One-off and recurring windows set on the monitor itself.
+pub async fn list_monitor_windows(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<MaintenanceWindow>>, ApiError> {
+    user.require_scope(TokenScope::ReadMonitors)?;
+    let monitor = load_accessible_monitor(&state, &user, id).await?;
+    Ok(Json(maintenance::monitor_windows(&state.db, monitor.id).await?))
+}
+
+pub async fn create_monitor_window(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    Json(request): Json<CreateMaintenanceWindowRequest>,
+) -> Result<(StatusCode, Json<MaintenanceWindow>), ApiError> {
+    user.require_scope(TokenScope::WriteMonitors)?;
+    let monitor = load_editable_monitor(&state, &user, id).await?;
+    let window = maintenance::create_window(&state.db, monitor.id, &request, user.user_id).await?;
+    info!(
+        "User {} added maintenance window {} to {} ({})",
+        user.username,
+        window.id,
+        monitor.name,
+        window.recurrence.as_deref().unwrap_or("once")
+    );
+    Ok((StatusCode::CREATED, Json(window)))
+}
+
+pub async fn delete_monitor_window(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Path((id, window_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, ApiError> {
+    user.require_scope(TokenScope::WriteMonitors)?;
+    let monitor = load_editable_monitor(&state, &user, id).await?;
+    if !maintenance::delete_window(&state.db, monitor.id, window_id).await? {
+        return Err(Error::not_found(format!("Maintenance window {} not found", window_id)).into());
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,20 @@
+pub mod admin;
+pub mod alerts;
+pub mod auth;
+pub mod approvals;
+pub mod availability;
+pub mod changes;
+pub mod compare;
+pub mod expirations;
+pub mod heartbeats;
+pub mod imports;
+pub mod maintenance;
+pub mod monitors;
+pub mod provisioning;
+pub mod replay;
+pub mod scripting;
+pub mod settings;
+pub mod stream;
+pub mod teams;
+pub mod tokens;
+pub mod webhooks;
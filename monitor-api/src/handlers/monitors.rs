@@ -0,0 +1,657 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
+use chrono::{DateTime, Duration, Utc};
+use monitor_core::{
+    Error, approvals, audit, cache,
+    durations::Seconds,
+    events::Event,
+    evidence::{self, IncidentEvidence},
+    failures::{self, FailureCount},
+    faults::{self, FaultPoint},
+    har::{self, Har},
+    http_method::HttpMethod,
+    models::{
+        CreateMonitorRequest, DEFAULT_PER_PAGE, Monitor, MonitorResult, Page, PageRequest, SortOrder, TokenScope,
+        UpdateMonitorRequest, UserRole,
+    },
+    pause::{self, PauseRequest, PauseState},
+    repository::{
+        self, MonitorAccess, MonitorQuery, MonitorSort, ResultBucket, ResultQuery,
+    },
+    stats::{self, LatencySummary, TimeseriesMetric, TimeseriesPoint},
+    teams,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::{collections::HashMap, sync::Arc};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::{
+    auth::{AuthMethod, AuthenticatedUser},
+    handlers::{settings::validate_script_profile, webhooks::authorize_channels},
+    server::{ApiError, AppState},
+};
+
+/// Evidence records returned by `list_evidence`, newest first
+const EVIDENCE_LIMIT: i64 = 50;
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MonitorFilter {
+    /// Monitors owned by the caller
+    Mine,
+    /// Monitors of the caller's teams, or of `team_id` when given
+    Team,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MonitorListQuery {
+    pub filter: Option<MonitorFilter>,
+    pub team_id: Option<Uuid>,
+    #[serde(default = "default_page")]
+    pub page: i64,
+    #[serde(default = "default_per_page")]
+    pub per_page: i64,
+    #[serde(default)]
+    pub sort_by: MonitorSort,
+    #[serde(default)]
+    pub order: SortOrder,
+    pub enabled: Option<bool>,
+    pub search: Option<String>,
+}
+
+fn default_page() -> i64 {
+    1
+}
+
+fn default_per_page() -> i64 {
+    DEFAULT_PER_PAGE
+}
+
+pub async fn get_monitors(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Query(query): Query<MonitorListQuery>,
+) -> Result<Json<Page<Monitor>>, ApiError> {
+    user.require_scope(TokenScope::ReadMonitors)?;
+
+    let team_ids = match (query.filter, query.team_id) {
+        (Some(MonitorFilter::Team), Some(team_id)) => {
+            require_team_member(&state, &user, team_id).await?;
+            Some(vec![team_id])
+        }
+        (Some(MonitorFilter::Team), None) => Some(teams::team_ids_for(&state.db, user.user_id).await?),
+        _ => None,
+    };
+    let access = MonitorAccess {
+        tags: user.tags.clone(),
+        dashboard_monitor_ids: matches!(user.method, AuthMethod::DashboardToken(_)).then(|| user.monitor_ids.clone()),
+    };
+
+    let monitors = repository::list_monitors(
+        &state.db,
+        &MonitorQuery {
+            page: PageRequest {
+                page: query.page,
+                per_page: query.per_page,
+            },
+            sort_by: query.sort_by,
+            order: query.order,
+            enabled: query.enabled,
+            search: query.search,
+            owner_id: (query.filter == Some(MonitorFilter::Mine)).then_some(user.user_id),
+            team_ids,
+            access,
+        },
+    )
+    .await?;
+    Ok(Json(monitors))
+}
+
+pub async fn create_monitor(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Json(request): Json<CreateMonitorRequest>,
+) -> Result<Json<Monitor>, ApiError> {
+    user.require_scope(TokenScope::WriteMonitors)?;
+
+    let monitor = insert_monitor(&state, &user, &request).await?;
+    info!("User {} created monitor {}", user.username, monitor.name);
+    Ok(Json(monitor))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HarImportRequest {
+    pub name: String,
+    pub har: Har,
+    #[serde(default = "default_har_interval")]
+    pub interval: Seconds,
+    /// Covers the whole transaction; inherits the defaults when omitted
+    pub timeout: Option<Seconds>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub team_id: Option<Uuid>,
+    /// Keep scripts, stylesheets, images and fonts as steps
+    #[serde(default)]
+    pub include_static: bool,
+}
+
+fn default_har_interval() -> Seconds {
+    Seconds::new(300)
+}
+
+/// Creates a multi-step transaction monitor from a HAR recording of a browser session.
+pub async fn import_har(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Json(request): Json<HarImportRequest>,
+) -> Result<Json<Monitor>, ApiError> {
+    user.require_scope(TokenScope::WriteMonitors)?;
+
+    let steps = har::steps_from_har(&request.har, request.include_static)?;
+    let first = &steps[0];
+    let create = CreateMonitorRequest::builder(request.name, first.url.clone())
+        .method(HttpMethod::custom(&first.method)?)
+        .expected_status(first.expected_status)
+        .timeout(request.timeout)
+        .interval(request.interval)
+        .tags(request.tags)
+        .team(request.team_id)
+        .steps(steps)
+        .build()?;
+
+    let monitor = insert_monitor(&state, &user, &create).await?;
+    info!(
+        "User {} imported transaction monitor {} from HAR",
+        user.username, monitor.name
+    );
+    Ok(Json(monitor))
+}
+
+/// Validates and stores a new monitor owned by the caller.
+pub async fn insert_monitor(
+    state: &AppState,
+    user: &AuthenticatedUser,
+    request: &CreateMonitorRequest,
+) -> monitor_core::Result<Monitor> {
+    if !user.can_access_tags(&request.tags) {
+        return Err(Error::forbidden("Token may only create monitors with its allowed tags"));
+    }
+    request.validate()?;
+    // Only the command name is allow-listed; its args run on the scheduler host
+    if request.exec_check.is_some() {
+        user.require_admin()?;
+    }
+    if let Some(profile) = &request.script_profile {
+        validate_script_profile(profile)?;
+    }
+    if request.pre_request_script.is_some() && !state.config.current().features.enable_scripting {
+        return Err(Error::validation("Scripting is disabled, pre-request scripts cannot be used"));
+    }
+    if request.script.is_some() && !state.config.current().features.enable_scripting {
+        return Err(Error::validation("Scripting is disabled, validation scripts cannot be used"));
+    }
+    if let Some(team_id) = request.team_id {
+        require_team_member(state, user, team_id).await?;
+    }
+    authorize_channels(state, user, request.notification_channels.as_deref()).await?;
+
+    let credentials = match &request.credentials {
+        Some(credentials) => Some(state.keys.encrypt(&serde_json::to_string(credentials)?)?),
+        None => None,
+    };
+    let monitor = repository::insert_monitor(&state.db, request, user.user_id, credentials.as_deref()).await?;
+    announce_change(state, monitor.id);
+    state.events.publish(Event::MonitorCreated {
+        monitor: Arc::new(monitor.clone()),
+        actor_id: Some(user.user_id),
+    });
+    Ok(monitor)
+}
+
+/// Edits a monitor. Omitted fields stay as they are. With change approval
+/// enabled the edit is stored as a pending change request and returned with
+/// `202 Accepted` instead.
+pub async fn update_monitor(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    Json(request): Json<UpdateMonitorRequest>,
+) -> Result<Response, ApiError> {
+    user.require_scope(TokenScope::WriteMonitors)?;
+    let existing = load_editable_monitor(&state, &user, id).await?;
+    if existing.system_key.is_some() && (request.endpoint.is_some() || request.credentials.is_some()) {
+        return Err(Error::validation("System monitors take their endpoint and credentials from the config").into());
+    }
+    let merged = CreateMonitorRequest::merged(&existing, &request)?;
+    merged.validate()?;
+    // Changing the command or its args of an exec check is for administrators, as creating one is
+    if merged.exec_check.is_some() && (request.exec_check.is_some() || request.endpoint.is_some()) {
+        user.require_admin()?;
+    }
+    validate_update(&state, &user, &request).await?;
+
+    let credentials = match &request.credentials {
+        Some(credentials) => Some(state.keys.encrypt(&serde_json::to_string(credentials).map_err(Error::from)?)?),
+        None => None,
+    };
+
+    if state.config.current().features.enable_change_approval {
+        let changes = serde_json::to_value(UpdateMonitorRequest { credentials: None, ..request }).map_err(Error::from)?;
+        let change = approvals::submit(&state.db, id, user.user_id, &changes, credentials.as_deref()).await?;
+        audit::record(
+            &state.db,
+            Some(user.user_id),
+            audit::ACTION_CHANGE_REQUESTED,
+            Some(id),
+            json!({ "change_request_id": change.id }),
+        )
+        .await?;
+        info!("User {} requested changes to monitor {}", user.username, id);
+        return Ok((StatusCode::ACCEPTED, Json(change)).into_response());
+    }
+
+    let monitor = apply_update(&state, id, &request, credentials.as_deref()).await?;
+    audit::record(&state.db, Some(user.user_id), audit::ACTION_MONITOR_UPDATED, Some(id), json!({})).await?;
+    info!("User {} updated monitor {}", user.username, monitor.name);
+    Ok(Json(monitor).into_response())
+}
+
+/// What `CreateMonitorRequest::validate` leaves to the caller, for the fields an edit sets.
+async fn validate_update(
+    state: &AppState,
+    user: &AuthenticatedUser,
+    request: &UpdateMonitorRequest,
+) -> monitor_core::Result<()> {
+    if let Some(tags) = &request.tags
+        && !user.can_access_tags(tags)
+    {
+        return Err(Error::forbidden("Token may only assign its allowed tags"));
+    }
+    if let Some(profile) = &request.script_profile {
+        validate_script_profile(profile)?;
+    }
+    if request.pre_request_script.is_some() && !state.config.current().features.enable_scripting {
+        return Err(Error::validation("Scripting is disabled, pre-request scripts cannot be used"));
+    }
+    if request.script.is_some() && !state.config.current().features.enable_scripting {
+        return Err(Error::validation("Scripting is disabled, validation scripts cannot be used"));
+    }
+    if let Some(team_id) = request.team_id {
+        require_team_member(state, user, team_id).await?;
+    }
+    authorize_channels(state, user, request.notification_channels.as_deref()).await
+}
+
+/// Writes an edit; `credentials` is already encrypted.
+async fn apply_update(
+    state: &AppState,
+    id: Uuid,
+    request: &UpdateMonitorRequest,
+    credentials: Option<&str>,
+) -> monitor_core::Result<Monitor> {
+    let monitor = repository::update_monitor(&state.db, id, request, credentials)
+        .await?
+        .ok_or_else(|| Error::not_found(format!("Monitor {} not found", id)))?;
+    announce_change(state, id);
+    Ok(monitor)
+}
+
+/// Tells schedulers subscribed over Redis that the monitor changed. They hear
+/// of it through Postgres too, so a failed publish only costs latency.
+pub fn announce_change(state: &AppState, monitor_id: Uuid) {
+    if !state.config.current().features.enable_redis_events {
+        return;
+    }
+    let redis = state.redis.clone();
+    let faults = state.faults.clone();
+    tokio::spawn(async move {
+        let published = async {
+            faults::inject(faults.as_deref(), FaultPoint::Redis).await?;
+            cache::publish_monitor_change(&redis, monitor_id).await
+        };
+        if let Err(e) = published.await {
+            warn!("Failed to publish the change of monitor {}: {}", monitor_id, e);
+        }
+    });
+}
+
+/// Loads a monitor the caller may see, hiding monitors outside a token's tags.
+pub async fn load_accessible_monitor(
+    state: &AppState,
+    user: &AuthenticatedUser,
+    id: Uuid,
+) -> monitor_core::Result<Monitor> {
+    repository::get_monitor(&state.db, id)
+        .await?
+        .filter(|m| user.can_access_monitor(m.id, &m.tags))
+        .ok_or_else(|| Error::not_found(format!("Monitor {} not found", id)))
+}
+
+/// Loads a monitor the caller may change: its owner, members of its team and admins.
+pub async fn load_editable_monitor(
+    state: &AppState,
+    user: &AuthenticatedUser,
+    id: Uuid,
+) -> monitor_core::Result<Monitor> {
+    let monitor = load_accessible_monitor(state, user, id).await?;
+    let team_ids = teams::team_ids_for(&state.db, user.user_id).await?;
+    if !teams::can_edit(&monitor, user.user_id, user.role, &team_ids) {
+        return Err(Error::forbidden(format!(
+            "Only the owner, its team or an administrator may change monitor {}",
+            id
+        )));
+    }
+    Ok(monitor)
+}
+
+async fn require_team_member(state: &AppState, user: &AuthenticatedUser, team_id: Uuid) -> monitor_core::Result<()> {
+    if user.role == UserRole::Admin {
+        return Ok(());
+    }
+    if teams::team_ids_for(&state.db, user.user_id).await?.contains(&team_id) {
+        Ok(())
+    } else {
+        Err(Error::forbidden(format!("Not a member of team {}", team_id)))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OwnershipRequest {
+    pub owner_id: Option<Uuid>,
+    pub team_id: Option<Uuid>,
+}
+
+/// Hands a monitor to another owner and/or team. Non-admins can only assign
+/// teams they belong to.
+pub async fn update_ownership(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    Json(request): Json<OwnershipRequest>,
+) -> Result<Json<Monitor>, ApiError> {
+    user.require_scope(TokenScope::WriteMonitors)?;
+    let monitor = load_editable_monitor(&state, &user, id).await?;
+
+    if let Some(team_id) = request.team_id {
+        require_team_member(&state, &user, team_id).await?;
+    }
+    if let Some(owner_id) = request.owner_id
+        && repository::get_active_user(&state.db, owner_id).await?.is_none()
+    {
+        return Err(Error::validation(format!("User {} not found", owner_id)).into());
+    }
+
+    let updated = repository::set_monitor_ownership(&state.db, id, request.owner_id, request.team_id).await?;
+
+    audit::record(
+        &state.db,
+        Some(user.user_id),
+        audit::ACTION_MONITOR_OWNERSHIP_CHANGED,
+        Some(id),
+        json!({
+            "from": { "owner_id": monitor.owner_id, "team_id": monitor.team_id },
+            "to": { "owner_id": updated.owner_id, "team_id": updated.team_id },
+        }),
+    )
+    .await?;
+    info!("User {} changed ownership of monitor {}", user.username, id);
+    Ok(Json(updated))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TimeseriesQuery {
+    pub metric: TimeseriesMetric,
+    #[serde(default = "default_step")]
+    pub step: String,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+fn default_step() -> String {
+    "5m".to_string()
+}
+
+/// Pre-bucketed chart data; defaults to the last 24 hours.
+pub async fn get_timeseries(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    Query(query): Query<TimeseriesQuery>,
+) -> Result<Json<Vec<TimeseriesPoint>>, ApiError> {
+    user.require_scope(TokenScope::ReadResults)?;
+    load_accessible_monitor(&state, &user, id).await?;
+
+    let step = stats::parse_step(&query.step)?;
+    let to = query.to.unwrap_or_else(|| state.clock.now());
+    let from = query.from.unwrap_or(to - Duration::hours(24));
+
+    let points = stats::timeseries(&state.db, id, query.metric, step, from, to).await?;
+    Ok(Json(points))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResultHistoryQuery {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub status: Option<String>,
+    #[serde(default = "default_page")]
+    pub page: i64,
+    #[serde(default = "default_per_page")]
+    pub per_page: i64,
+    /// Aggregate into buckets of this size, e.g. `1h`
+    pub interval: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum ResultHistory {
+    Results(Page<MonitorResult>),
+    Buckets(Vec<ResultBucket>),
+}
+
+/// Raw check results, or buckets of average response time and uptime when
+/// `interval` is given; defaults to the last 24 hours.
+pub async fn list_results(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    Query(query): Query<ResultHistoryQuery>,
+) -> Result<Json<ResultHistory>, ApiError> {
+    user.require_scope(TokenScope::ReadResults)?;
+    load_accessible_monitor(&state, &user, id).await?;
+
+    let to = query.to.unwrap_or_else(|| state.clock.now());
+    let results = ResultQuery {
+        from: query.from.unwrap_or(to - Duration::hours(24)),
+        to,
+        status: query.status,
+        page: PageRequest {
+            page: query.page,
+            per_page: query.per_page,
+        },
+    };
+
+    let history = match query.interval {
+        Some(interval) => {
+            let step = stats::parse_step(&interval)?;
+            ResultHistory::Buckets(repository::result_buckets(&state.db, id, &results, step).await?)
+        }
+        None => ResultHistory::Results(repository::list_results(&state.db, id, &results).await?),
+    };
+    Ok(Json(history))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RangeQuery {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+/// Latency percentiles and availability over a range; defaults to the last 30 days.
+pub async fn get_latency_summary(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    Query(query): Query<RangeQuery>,
+) -> Result<Json<LatencySummary>, ApiError> {
+    user.require_scope(TokenScope::ReadResults)?;
+    load_accessible_monitor(&state, &user, id).await?;
+
+    let to = query.to.unwrap_or_else(|| state.clock.now());
+    let from = query.from.unwrap_or(to - Duration::days(30));
+
+    Ok(Json(stats::latency_summary(&state.db, id, from, to).await?))
+}
+
+/// Failed checks grouped by cause; defaults to the last 7 days.
+pub async fn get_failure_breakdown(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    Query(query): Query<RangeQuery>,
+) -> Result<Json<Vec<FailureCount>>, ApiError> {
+    user.require_scope(TokenScope::ReadResults)?;
+    load_accessible_monitor(&state, &user, id).await?;
+
+    let to = query.to.unwrap_or_else(|| state.clock.now());
+    let from = query.from.unwrap_or(to - Duration::days(7));
+
+    Ok(Json(failures::breakdown(&state.db, id, from, to).await?))
+}
+
+/// Responses captured when the monitor went from up to down.
+pub async fn list_evidence(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<IncidentEvidence>>, ApiError> {
+    user.require_scope(TokenScope::ReadResults)?;
+    load_accessible_monitor(&state, &user, id).await?;
+
+    Ok(Json(evidence::list(&state.db, id, EVIDENCE_LIMIT).await?))
+}
+
+/// A single evidence record, as linked from state changes by `evidence_id`.
+pub async fn get_evidence(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<IncidentEvidence>, ApiError> {
+    user.require_scope(TokenScope::ReadResults)?;
+    let evidence = evidence::get(&state.db, id)
+        .await?
+        .ok_or_else(|| Error::not_found("Evidence not found"))?;
+    load_accessible_monitor(&state, &user, evidence.monitor_id).await?;
+
+    Ok(Json(evidence))
+}
+
+/// Pauses a monitor, optionally with a reason and a time to resume it automatically.
+pub async fn pause_monitor(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    Json(request): Json<PauseRequest>,
+) -> Result<Json<MonitorStatus>, ApiError> {
+    user.require_scope(TokenScope::WriteMonitors)?;
+    load_editable_monitor(&state, &user, id).await?;
+
+    pause::pause(&state.db, id, user.user_id, &request, state.clock.now()).await?;
+    announce_change(&state, id);
+    info!("User {} paused monitor {}", user.username, id);
+    Ok(Json(monitor_status(&state, &user, id).await?))
+}
+
+pub async fn resume_monitor(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<MonitorStatus>, ApiError> {
+    user.require_scope(TokenScope::WriteMonitors)?;
+    let monitor = load_editable_monitor(&state, &user, id).await?;
+    if monitor.enabled {
+        return Err(Error::validation(format!("Monitor {} is not paused", id)).into());
+    }
+
+    pause::resume(&state.db, id, Some(user.user_id)).await?;
+    announce_change(&state, id);
+    info!("User {} resumed monitor {}", user.username, id);
+    Ok(Json(monitor_status(&state, &user, id).await?))
+}
+
+#[derive(Debug, Serialize)]
+pub struct MonitorStatus {
+    pub monitor_id: Uuid,
+    pub name: String,
+    /// `active` or `paused`
+    pub state: &'static str,
+    pub pause: Option<PauseState>,
+    pub last_result: Option<MonitorResult>,
+}
+
+impl MonitorStatus {
+    fn new(monitor: Monitor, last_result: Option<MonitorResult>) -> Self {
+        let pause = PauseState::of(&monitor);
+        Self {
+            monitor_id: monitor.id,
+            name: monitor.name,
+            state: if pause.is_some() { "paused" } else { "active" },
+            pause,
+            last_result,
+        }
+    }
+}
+
+async fn monitor_status(state: &AppState, user: &AuthenticatedUser, id: Uuid) -> monitor_core::Result<MonitorStatus> {
+    let monitor = load_accessible_monitor(state, user, id).await?;
+    let last_result = repository::latest_result(&state.db, id).await?;
+
+    Ok(MonitorStatus::new(monitor, last_result))
+}
+
+/// Whether a monitor is running, why it is paused, and its latest result.
+/// Readable with dashboard tokens.
+pub async fn get_monitor_status(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<MonitorStatus>, ApiError> {
+    user.require_any_scope(&[TokenScope::ReadMonitors, TokenScope::ReadResults])?;
+    Ok(Json(monitor_status(&state, &user, id).await?))
+}
+
+/// Status of every accessible monitor; the overview for lobby screens.
+pub async fn list_monitor_status(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+) -> Result<Json<Vec<MonitorStatus>>, ApiError> {
+    user.require_any_scope(&[TokenScope::ReadMonitors, TokenScope::ReadResults])?;
+
+    let monitors: Vec<Monitor> = repository::all_monitors(&state.db, MonitorSort::Name)
+        .await?
+        .into_iter()
+        .filter(|m| user.can_access_monitor(m.id, &m.tags))
+        .collect();
+
+    let ids: Vec<Uuid> = monitors.iter().map(|m| m.id).collect();
+    let mut latest: HashMap<Uuid, MonitorResult> = repository::latest_results(&state.db, &ids)
+        .await?
+        .into_iter()
+        .map(|r| (r.monitor_id, r))
+        .collect();
+
+    Ok(Json(
+        monitors
+            .into_iter()
+            .map(|m| {
+                let last_result = latest.remove(&m.id);
+                MonitorStatus::new(m, last_result)
+            })
+            .collect(),
+    ))
+}
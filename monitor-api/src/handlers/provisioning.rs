@@ -0,0 +1,131 @@
+use axum::{
+    extract::State,
+    http::{HeaderMap, header::AUTHORIZATION},
+    response::Json,
+};
+use monitor_core::{
+    Error, Result, auth,
+    models::{BulkProvisioningRequest, ProvisioningOperation, ProvisioningOutcome, User},
+    repository,
+};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::server::{ApiError, AppState};
+
+/// Provisioning endpoints are authenticated with a static bearer token from
+/// `auth.provisioning_token`, the way identity providers are usually configured.
+fn require_provisioning_token(headers: &HeaderMap, state: &AppState) -> Result<()> {
+    let config = state.config.current();
+    let provided = headers
+        .get(AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+
+    auth::verify_provisioning_token(config.auth.provisioning_token.as_deref(), provided)
+}
+
+pub async fn list_users(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> std::result::Result<Json<Vec<User>>, ApiError> {
+    require_provisioning_token(&headers, &state)?;
+
+    let users = repository::list_users(&state.db).await?;
+
+    Ok(Json(users))
+}
+
+pub async fn bulk_provision(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(request): Json<BulkProvisioningRequest>,
+) -> std::result::Result<Json<Vec<ProvisioningOutcome>>, ApiError> {
+    require_provisioning_token(&headers, &state)?;
+
+    let mut outcomes = Vec::with_capacity(request.operations.len());
+    for operation in request.operations {
+        let (username, op) = match &operation {
+            ProvisioningOperation::Create { username, .. } => (username.clone(), "create"),
+            ProvisioningOperation::Update { username, .. } => (username.clone(), "update"),
+            ProvisioningOperation::Deactivate { username } => (username.clone(), "deactivate"),
+            ProvisioningOperation::Reactivate { username } => (username.clone(), "reactivate"),
+        };
+
+        let outcome = match apply_operation(&state, operation).await {
+            Ok(user) => ProvisioningOutcome {
+                username,
+                op: op.to_string(),
+                success: true,
+                user: Some(user),
+                error: None,
+            },
+            Err(e) => ProvisioningOutcome {
+                username,
+                op: op.to_string(),
+                success: false,
+                user: None,
+                error: Some(e.to_string()),
+            },
+        };
+        outcomes.push(outcome);
+    }
+
+    Ok(Json(outcomes))
+}
+
+async fn apply_operation(state: &AppState, operation: ProvisioningOperation) -> Result<User> {
+    match operation {
+        ProvisioningOperation::Create {
+            username,
+            email,
+            password,
+            role,
+        } => {
+            validate_username(&username)?;
+            validate_email(&email)?;
+
+            // SSO-provisioned accounts may have no local password; store an
+            // unguessable one so password login is effectively disabled.
+            let password = password.unwrap_or_else(|| Uuid::new_v4().to_string());
+            let password_hash = state.auth.hash_password(&password)?;
+
+            repository::insert_user(&state.db, &username, &email, &password_hash, Some(role)).await
+        }
+        ProvisioningOperation::Update {
+            username,
+            email,
+            role,
+        } => {
+            if let Some(email) = &email {
+                validate_email(email)?;
+            }
+
+            repository::update_user(&state.db, &username, email.as_deref(), role)
+                .await?
+                .ok_or_else(|| Error::not_found(format!("User {} not found", username)))
+        }
+        ProvisioningOperation::Deactivate { username } => set_active(state, &username, false).await,
+        ProvisioningOperation::Reactivate { username } => set_active(state, &username, true).await,
+    }
+}
+
+async fn set_active(state: &AppState, username: &str, active: bool) -> Result<User> {
+    repository::set_user_active(&state.db, username, active)
+        .await?
+        .ok_or_else(|| Error::not_found(format!("User {} not found", username)))
+}
+
+pub(crate) fn validate_username(username: &str) -> Result<()> {
+    if username.trim().is_empty() || username.len() > 255 {
+        return Err(Error::validation("Username must be between 1 and 255 characters"));
+    }
+    Ok(())
+}
+
+pub(crate) fn validate_email(email: &str) -> Result<()> {
+    match email.split_once('@') {
+        Some((local, domain)) if !local.is_empty() && domain.contains('.') => Ok(()),
+        _ => Err(Error::validation(format!("Invalid email address: {}", email))),
+    }
+}
@@ -0,0 +1,131 @@
+use axum::{
+    extract::{Path, State},
+    response::Json,
+};
+use chrono::{DateTime, Utc};
+use monitor_core::{
+    Error,
+    assertions::{self, Assertion, ReplayReport},
+    models::{MonitorResult, TokenScope},
+    repository,
+    settings::{self, EffectiveSettings},
+};
+use monitor_scripting::{
+    engine::ScriptEngine,
+    models::{SecurityConfig, ValidationContext},
+};
+use serde::Deserialize;
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use uuid::Uuid;
+
+use crate::{
+    auth::AuthenticatedUser,
+    handlers::monitors::load_accessible_monitor,
+    server::{ApiError, AppState},
+};
+
+const MAX_REPLAY_RESULTS: i64 = 1000;
+
+#[derive(Debug, Deserialize)]
+pub struct ReplayRequest {
+    /// Validation script run with `context.status_code`, `context.body` and
+    /// `context.response_time`; stored results have no headers
+    pub script: Option<String>,
+    #[serde(default)]
+    pub assertions: Vec<Assertion>,
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+fn default_limit() -> i64 {
+    200
+}
+
+/// Re-evaluates a monitor's stored responses against new assertions and/or a
+/// validation script, newest first, without changing the monitor.
+pub async fn replay_checks(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    Json(request): Json<ReplayRequest>,
+) -> Result<Json<ReplayReport>, ApiError> {
+    user.require_scope(TokenScope::ReadResults)?;
+    let monitor = load_accessible_monitor(&state, &user, id).await?;
+
+    if request.script.is_none() && request.assertions.is_empty() {
+        return Err(Error::validation("Provide a script or at least one assertion").into());
+    }
+    let config = state.config.current();
+    if request.script.is_some() && !config.features.enable_scripting {
+        return Err(Error::validation("Scripting is disabled, scripts cannot be replayed").into());
+    }
+
+    let to = request.to.unwrap_or_else(|| state.clock.now());
+    let from = request.from.unwrap_or(DateTime::<Utc>::MIN_UTC);
+    let (results, skipped) =
+        repository::results_with_bodies(&state.db, id, from, to, request.limit.clamp(1, MAX_REPLAY_RESULTS)).await?;
+
+    let mut report = ReplayReport {
+        skipped,
+        ..ReplayReport::default()
+    };
+
+    let script_outcomes = match &request.script {
+        Some(script) => {
+            let builtin = EffectiveSettings::builtin(&config.scripting.security_profile);
+            let profile = settings::effective_for(&state.db, &monitor, builtin).await?.script_profile;
+            let timeout = Duration::from_secs(config.scripting.timeout);
+            Some(run_script(script.clone(), profile, timeout, results.clone()).await?)
+        }
+        None => None,
+    };
+
+    for (index, result) in results.iter().enumerate() {
+        let outcome = assertions::evaluate_all(&request.assertions, result).and_then(|()| {
+            match script_outcomes.as_ref().map(|outcomes| &outcomes[index]) {
+                Some(Err(reason)) => Err(reason.clone()),
+                _ => Ok(()),
+            }
+        });
+        report.record(result, outcome);
+    }
+
+    Ok(Json(report))
+}
+
+/// QuickJS runtimes are not `Send`, so every result is validated on one blocking
+/// thread with a single engine.
+async fn run_script(
+    script: String,
+    profile: String,
+    timeout: Duration,
+    results: Vec<MonitorResult>,
+) -> monitor_core::Result<Vec<Result<(), String>>> {
+    tokio::task::spawn_blocking(move || {
+        let security_config = SecurityConfig::from_profile(&profile)
+            .ok_or_else(|| Error::script_execution(format!("Unknown security profile: {}", profile)))?;
+        let engine = ScriptEngine::with_config(timeout, security_config)?;
+        let handle = tokio::runtime::Handle::current();
+
+        Ok(results
+            .iter()
+            .map(|result| {
+                let context = ValidationContext {
+                    status_code: result.response_code.unwrap_or_default() as u16,
+                    headers: HashMap::new(),
+                    body: result.response_body.clone().unwrap_or_default(),
+                    response_time: result.response_time.max(0) as u64,
+                };
+                match handle.block_on(engine.execute_validation_script(&script, &context)) {
+                    Ok(validation) if validation.passed => Ok(()),
+                    Ok(validation) => Err(format!("script: {}", validation.message)),
+                    Err(e) => Err(format!("script: {}", e)),
+                }
+            })
+            .collect())
+    })
+    .await
+    .map_err(|e| Error::script_execution(format!("Replay script task failed: {}", e)))?
+}
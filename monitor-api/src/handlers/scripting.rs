@@ -0,0 +1,36 @@
+use axum::{
+    extract::{Query, State},
+    response::Json,
+};
+use monitor_core::Error;
+use monitor_scripting::{
+    engine::ScriptEngine,
+    models::{ScriptCapabilities, SecurityConfig},
+};
+use serde::Deserialize;
+use std::{sync::Arc, time::Duration};
+
+use crate::server::{ApiError, AppState};
+
+#[derive(Debug, Deserialize)]
+pub struct CapabilitiesQuery {
+    pub profile: Option<String>,
+}
+
+pub async fn get_capabilities(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<CapabilitiesQuery>,
+) -> Result<Json<ScriptCapabilities>, ApiError> {
+    let config = state.config.current();
+    let profile = query
+        .profile
+        .unwrap_or_else(|| config.scripting.security_profile.clone());
+
+    let security_config = SecurityConfig::from_profile(&profile)
+        .ok_or_else(|| Error::validation(format!("Unknown security profile: {}", profile)))?;
+
+    Ok(Json(ScriptEngine::describe_capabilities(
+        &security_config,
+        Duration::from_secs(config.scripting.timeout),
+    )))
+}
@@ -0,0 +1,109 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+};
+use monitor_core::{
+    Error,
+    models::TokenScope,
+    settings::{self, EffectiveSettings, GLOBAL_SCOPE, ScopedDefaults, SettingsOverride},
+};
+use monitor_scripting::models::SecurityConfig;
+use std::sync::Arc;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    auth::AuthenticatedUser,
+    handlers::{monitors::load_accessible_monitor, webhooks::authorize_channels},
+    server::{ApiError, AppState},
+};
+
+pub fn validate_script_profile(profile: &str) -> monitor_core::Result<()> {
+    SecurityConfig::from_profile(profile)
+        .map(|_| ())
+        .ok_or_else(|| Error::validation(format!("Unknown security profile: {}", profile)))
+}
+
+pub async fn list_defaults(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+) -> Result<Json<Vec<ScopedDefaults>>, ApiError> {
+    user.require_scope(TokenScope::ReadMonitors)?;
+    Ok(Json(settings::list_defaults(&state.db).await?))
+}
+
+async fn put_defaults(
+    state: &AppState,
+    user: &AuthenticatedUser,
+    scope: &str,
+    request: &SettingsOverride,
+) -> Result<(), ApiError> {
+    user.require_admin()?;
+    request.validate()?;
+    if let Some(profile) = &request.script_profile {
+        validate_script_profile(profile)?;
+    }
+    authorize_channels(state, user, request.notification_channels.as_deref()).await?;
+
+    settings::set_defaults(&state.db, scope, request).await?;
+    info!("User {} updated {} settings defaults", user.username, scope);
+    Ok(())
+}
+
+async fn remove_defaults(state: &AppState, user: &AuthenticatedUser, scope: &str) -> Result<StatusCode, ApiError> {
+    user.require_admin()?;
+    if !settings::delete_defaults(&state.db, scope).await? {
+        return Err(Error::not_found(format!("No defaults set for {}", scope)).into());
+    }
+
+    info!("User {} removed {} settings defaults", user.username, scope);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn set_global_defaults(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Json(request): Json<SettingsOverride>,
+) -> Result<Json<SettingsOverride>, ApiError> {
+    put_defaults(&state, &user, GLOBAL_SCOPE, &request).await?;
+    Ok(Json(request))
+}
+
+pub async fn delete_global_defaults(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+) -> Result<StatusCode, ApiError> {
+    remove_defaults(&state, &user, GLOBAL_SCOPE).await
+}
+
+pub async fn set_tag_defaults(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Path(tag): Path<String>,
+    Json(request): Json<SettingsOverride>,
+) -> Result<Json<SettingsOverride>, ApiError> {
+    put_defaults(&state, &user, &settings::tag_scope(&tag), &request).await?;
+    Ok(Json(request))
+}
+
+pub async fn delete_tag_defaults(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Path(tag): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    remove_defaults(&state, &user, &settings::tag_scope(&tag)).await
+}
+
+/// The settings a monitor's checks run with and where each value comes from.
+pub async fn get_effective_settings(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<EffectiveSettings>, ApiError> {
+    user.require_scope(TokenScope::ReadMonitors)?;
+
+    let monitor = load_accessible_monitor(&state, &user, id).await?;
+    let builtin = EffectiveSettings::builtin(&state.config.current().scripting.security_profile);
+    Ok(Json(settings::effective_for(&state.db, &monitor, builtin).await?))
+}
@@ -0,0 +1,93 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::HeaderMap,
+    response::sse::{Event, KeepAlive, Sse},
+};
+use monitor_core::{
+    events::Event as MonitorEvent,
+    models::{MonitorResult, TokenScope},
+    repository,
+};
+use serde::Deserialize;
+use std::{collections::HashSet, convert::Infallible, sync::Arc};
+use tokio_stream::{
+    Stream, StreamExt,
+    wrappers::{BroadcastStream, errors::BroadcastStreamRecvError},
+};
+use uuid::Uuid;
+
+use crate::{
+    auth::AuthenticatedUser,
+    handlers::monitors::load_accessible_monitor,
+    server::{ApiError, AppState},
+};
+
+const MAX_REPLAY: i64 = 500;
+
+#[derive(Debug, Deserialize)]
+pub struct StreamQuery {
+    /// Past results sent before live ones
+    #[serde(default = "default_replay")]
+    pub replay: i64,
+}
+
+fn default_replay() -> i64 {
+    20
+}
+
+fn result_event(result: &MonitorResult) -> Event {
+    Event::default()
+        .event("result")
+        .id(result.id.to_string())
+        .json_data(result)
+        .unwrap_or_else(|_| Event::default().comment("unserializable result"))
+}
+
+/// Streams a monitor's results as Server-Sent Events: the last `replay` results
+/// on connect, then each new one. Reconnecting clients that send `Last-Event-ID`
+/// get every result since that event instead.
+pub async fn stream_results(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    Query(query): Query<StreamQuery>,
+    headers: HeaderMap,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, ApiError> {
+    user.require_scope(TokenScope::ReadResults)?;
+    load_accessible_monitor(&state, &user, id).await?;
+
+    // Subscribe before reading history so nothing falls between the two
+    let (receiver, guard) = state.results.subscribe();
+    let live = BroadcastStream::new(receiver);
+
+    let last_event_id = headers
+        .get("last-event-id")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<Uuid>().ok());
+    let limit = match last_event_id {
+        Some(_) => MAX_REPLAY,
+        None => query.replay.clamp(0, MAX_REPLAY),
+    };
+    let mut history = repository::recent_results(&state.db, id, last_event_id, limit).await?;
+    history.reverse();
+
+    let replayed: HashSet<Uuid> = history.iter().map(|r| r.id).collect();
+    let history = tokio_stream::iter(history.into_iter().map(|r| Ok(result_event(&r))));
+    let live = live.filter_map(move |item| {
+        // Keeps results flowing for as long as the stream is open
+        let _ = &guard;
+        match item {
+            Ok(MonitorEvent::CheckCompleted { result })
+                if result.monitor_id == id && !replayed.contains(&result.id) =>
+            {
+                Some(Ok(result_event(&result)))
+            }
+            Ok(_) => None,
+            Err(BroadcastStreamRecvError::Lagged(skipped)) => {
+                Some(Ok(Event::default().comment(format!("skipped {} events", skipped))))
+            }
+        }
+    });
+
+    Ok(Sse::new(history.chain(live)).keep_alive(KeepAlive::default()))
+}
@@ -0,0 +1,93 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+};
+use monitor_core::{
+    Error,
+    models::{TokenScope, UserRole},
+    teams::{self, CreateTeamRequest, Team, TeamMember},
+};
+use std::sync::Arc;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    auth::AuthenticatedUser,
+    server::{ApiError, AppState},
+};
+
+/// Every team for admins, otherwise the caller's own teams.
+pub async fn list_teams(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+) -> Result<Json<Vec<Team>>, ApiError> {
+    user.require_scope(TokenScope::ReadMonitors)?;
+    let member = (user.role != UserRole::Admin).then_some(user.user_id);
+    Ok(Json(teams::list(&state.db, member).await?))
+}
+
+pub async fn create_team(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Json(request): Json<CreateTeamRequest>,
+) -> Result<Json<Team>, ApiError> {
+    user.require_admin()?;
+
+    let team = teams::create(&state.db, &request).await?;
+    info!("User {} created team {}", user.username, team.name);
+    Ok(Json(team))
+}
+
+/// Monitors of a deleted team keep their owner and lose the team.
+pub async fn delete_team(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    user.require_admin()?;
+
+    if !teams::delete(&state.db, id).await? {
+        return Err(Error::not_found(format!("Team {} not found", id)).into());
+    }
+    info!("User {} deleted team {}", user.username, id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn list_members(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<TeamMember>>, ApiError> {
+    user.require_scope(TokenScope::ReadMonitors)?;
+    if user.role != UserRole::Admin && !teams::team_ids_for(&state.db, user.user_id).await?.contains(&id) {
+        return Err(Error::not_found(format!("Team {} not found", id)).into());
+    }
+    Ok(Json(teams::members(&state.db, id).await?))
+}
+
+pub async fn add_member(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Path((id, user_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, ApiError> {
+    user.require_admin()?;
+
+    teams::add_member(&state.db, id, user_id).await?;
+    info!("User {} added {} to team {}", user.username, user_id, id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn remove_member(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Path((id, user_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, ApiError> {
+    user.require_admin()?;
+
+    if !teams::remove_member(&state.db, id, user_id).await? {
+        return Err(Error::not_found(format!("User {} is not in team {}", user_id, id)).into());
+    }
+    info!("User {} removed {} from team {}", user.username, user_id, id);
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,166 @@
+use axum::{
+    extract::{Path, State},
+    response::Json,
+};
+use chrono::Duration;
+use monitor_core::{
+    Error,
+    models::{CreateDashboardTokenRequest, CreatePersonalAccessTokenRequest, PersonalAccessToken, TokenScope},
+};
+use serde_json::{Value, json};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    auth::AuthenticatedUser,
+    handlers::monitors::load_accessible_monitor,
+    server::{ApiError, AppState},
+};
+
+pub async fn list_tokens(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+) -> Result<Json<Vec<PersonalAccessToken>>, ApiError> {
+    user.require_jwt()?;
+
+    let tokens = sqlx::query_as::<_, PersonalAccessToken>(
+        "SELECT * FROM personal_access_tokens WHERE user_id = $1 ORDER BY created_at DESC",
+    )
+    .bind(user.user_id)
+    .fetch_all(&state.db)
+    .await
+    .map_err(Error::from)?;
+
+    Ok(Json(tokens))
+}
+
+pub async fn create_token(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Json(request): Json<CreatePersonalAccessTokenRequest>,
+) -> Result<Json<Value>, ApiError> {
+    // Tokens cannot mint further tokens, so scopes can never be escalated
+    user.require_jwt()?;
+
+    if request.name.trim().is_empty() {
+        return Err(Error::validation("Token name is required").into());
+    }
+    if request.scopes.is_empty() {
+        return Err(Error::validation("At least one scope is required").into());
+    }
+
+    let expires_at = match request.expires_in_days {
+        Some(days) if days <= 0 => {
+            return Err(Error::validation("expires_in_days must be positive").into());
+        }
+        Some(days) => Some(state.clock.now() + Duration::days(days)),
+        None => None,
+    };
+
+    let (token, token_hash) = state.auth.generate_personal_token();
+    let scopes: Vec<String> = request.scopes.iter().map(|s| s.as_str().to_string()).collect();
+
+    let record = sqlx::query_as::<_, PersonalAccessToken>(
+        r#"
+        INSERT INTO personal_access_tokens (id, user_id, name, token_prefix, token_hash, scopes, tags, expires_at, created_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, NOW())
+        RETURNING *
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(user.user_id)
+    .bind(request.name.trim())
+    .bind(&token[..12])
+    .bind(&token_hash)
+    .bind(&scopes)
+    .bind(&request.tags)
+    .bind(expires_at)
+    .fetch_one(&state.db)
+    .await
+    .map_err(Error::from)?;
+
+    Ok(Json(json!({
+        "token": token,
+        "details": record,
+    })))
+}
+
+/// Creates a read-only token for status and stats of an explicit set of monitors
+/// and/or tags, safe to embed in dashboards and lobby screens.
+pub async fn create_dashboard_token(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Json(request): Json<CreateDashboardTokenRequest>,
+) -> Result<Json<Value>, ApiError> {
+    user.require_jwt()?;
+
+    if request.name.trim().is_empty() {
+        return Err(Error::validation("Token name is required").into());
+    }
+    if request.monitor_ids.is_empty() && request.tags.is_empty() {
+        return Err(Error::validation("Dashboard tokens need at least one monitor or tag").into());
+    }
+    let expires_at = match request.expires_in_days {
+        Some(days) if days <= 0 => {
+            return Err(Error::validation("expires_in_days must be positive").into());
+        }
+        Some(days) => Some(state.clock.now() + Duration::days(days)),
+        None => None,
+    };
+
+    // Only monitors the creator can see may be shared
+    for id in &request.monitor_ids {
+        load_accessible_monitor(&state, &user, *id).await?;
+    }
+
+    let (token, token_hash) = state.auth.generate_personal_token();
+    let scopes = vec![TokenScope::ReadResults.as_str().to_string()];
+
+    let record = sqlx::query_as::<_, PersonalAccessToken>(
+        r#"
+        INSERT INTO personal_access_tokens (id, user_id, name, token_prefix, token_hash, kind, scopes, tags, monitor_ids, expires_at, created_at)
+        VALUES ($1, $2, $3, $4, $5, 'dashboard', $6, $7, $8, $9, NOW())
+        RETURNING *
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(user.user_id)
+    .bind(request.name.trim())
+    .bind(&token[..12])
+    .bind(&token_hash)
+    .bind(&scopes)
+    .bind(&request.tags)
+    .bind(&request.monitor_ids)
+    .bind(expires_at)
+    .fetch_one(&state.db)
+    .await
+    .map_err(Error::from)?;
+
+    Ok(Json(json!({
+        "token": token,
+        "details": record,
+    })))
+}
+
+pub async fn revoke_token(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Value>, ApiError> {
+    user.require_jwt()?;
+
+    let revoked = sqlx::query(
+        "UPDATE personal_access_tokens SET revoked_at = NOW() WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL",
+    )
+    .bind(id)
+    .bind(user.user_id)
+    .execute(&state.db)
+    .await
+    .map_err(Error::from)?;
+
+    if revoked.rows_affected() == 0 {
+        return Err(Error::not_found(format!("Token {} not found", id)).into());
+    }
+
+    Ok(Json(json!({ "revoked": id })))
+}
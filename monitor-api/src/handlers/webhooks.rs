@@ -0,0 +1,205 @@
+use axum::{
+    extract::{Path, State},
+    response::Json,
+};
+use monitor_core::{
+    Error,
+    models::{CreateWebhookRequest, TokenScope, UserRole, WebhookEndpoint},
+    repository, teams, webhook,
+};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    auth::{AuthMethod, AuthenticatedUser},
+    handlers::monitors::load_editable_monitor,
+    server::{ApiError, AppState},
+};
+
+#[derive(Debug, Deserialize)]
+pub struct VerifySignatureRequest {
+    pub signature: String,
+    pub payload: String,
+}
+
+/// Endpoints of a monitor are managed by whoever may edit the monitor; global
+/// endpoints receive events of every monitor and are reserved for administrators.
+async fn authorize_endpoint(
+    state: &AppState,
+    user: &AuthenticatedUser,
+    monitor_id: Option<Uuid>,
+) -> monitor_core::Result<()> {
+    user.require_scope(TokenScope::WriteMonitors)?;
+    match monitor_id {
+        Some(monitor_id) => load_editable_monitor(state, user, monitor_id).await.map(|_| ()),
+        None => user.require_admin(),
+    }
+}
+
+async fn find_endpoint(state: &AppState, id: Uuid) -> monitor_core::Result<Option<WebhookEndpoint>> {
+    Ok(sqlx::query_as::<_, WebhookEndpoint>("SELECT * FROM webhook_endpoints WHERE id = $1")
+        .bind(id)
+        .fetch_optional(&state.db)
+        .await?)
+}
+
+async fn load_endpoint(state: &AppState, user: &AuthenticatedUser, id: Uuid) -> monitor_core::Result<WebhookEndpoint> {
+    let endpoint = find_endpoint(state, id)
+        .await?
+        .ok_or_else(|| Error::not_found(format!("Webhook {} not found", id)))?;
+    authorize_endpoint(state, user, endpoint.monitor_id).await?;
+    Ok(endpoint)
+}
+
+/// Notification channels name webhook endpoints that receive a monitor's state
+/// events; each must exist and be one the caller may manage.
+pub async fn authorize_channels(
+    state: &AppState,
+    user: &AuthenticatedUser,
+    channels: Option<&[Uuid]>,
+) -> monitor_core::Result<()> {
+    for &id in channels.unwrap_or_default() {
+        let endpoint = find_endpoint(state, id)
+            .await?
+            .ok_or_else(|| Error::validation(format!("Unknown notification channel: {}", id)))?;
+        authorize_endpoint(state, user, endpoint.monitor_id).await?;
+    }
+    Ok(())
+}
+
+pub async fn list_webhooks(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+) -> Result<Json<Vec<WebhookEndpoint>>, ApiError> {
+    user.require_scope(TokenScope::WriteMonitors)?;
+
+    let endpoints = sqlx::query_as::<_, WebhookEndpoint>(
+        "SELECT * FROM webhook_endpoints ORDER BY created_at",
+    )
+    .fetch_all(&state.db)
+    .await
+    .map_err(Error::from)?;
+
+    let is_admin = user.role == UserRole::Admin && !matches!(user.method, AuthMethod::DashboardToken(_));
+    if is_admin {
+        return Ok(Json(endpoints));
+    }
+
+    let team_ids = teams::team_ids_for(&state.db, user.user_id).await?;
+    let mut visible = Vec::with_capacity(endpoints.len());
+    for endpoint in endpoints {
+        let Some(monitor_id) = endpoint.monitor_id else { continue };
+        let editable = repository::get_monitor(&state.db, monitor_id).await?.is_some_and(|monitor| {
+            user.can_access_monitor(monitor.id, &monitor.tags)
+                && teams::can_edit(&monitor, user.user_id, user.role, &team_ids)
+        });
+        if editable {
+            visible.push(endpoint);
+        }
+    }
+    Ok(Json(visible))
+}
+
+pub async fn create_webhook(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Json(request): Json<CreateWebhookRequest>,
+) -> Result<Json<Value>, ApiError> {
+    authorize_endpoint(&state, &user, request.monitor_id).await?;
+    if !(request.url.starts_with("http://") || request.url.starts_with("https://")) {
+        return Err(Error::validation("Webhook URL must use http or https").into());
+    }
+
+    let secret = webhook::generate_secret();
+    let stored_secret = state.keys.encrypt(&secret)?;
+    let endpoint = sqlx::query_as::<_, WebhookEndpoint>(
+        r#"
+        INSERT INTO webhook_endpoints (id, monitor_id, url, secret, enabled, created_at, updated_at)
+        VALUES ($1, $2, $3, $4, true, NOW(), NOW())
+        RETURNING *
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(request.monitor_id)
+    .bind(&request.url)
+    .bind(&stored_secret)
+    .fetch_one(&state.db)
+    .await
+    .map_err(Error::from)?;
+
+    // The secret is only ever returned at creation or rotation time
+    Ok(Json(json!({
+        "webhook": endpoint,
+        "secret": secret,
+    })))
+}
+
+pub async fn rotate_webhook_secret(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Value>, ApiError> {
+    load_endpoint(&state, &user, id).await?;
+
+    let secret = webhook::generate_secret();
+    let updated = sqlx::query(
+        "UPDATE webhook_endpoints SET secret = $1, updated_at = NOW() WHERE id = $2",
+    )
+    .bind(state.keys.encrypt(&secret)?)
+    .bind(id)
+    .execute(&state.db)
+    .await
+    .map_err(Error::from)?;
+
+    if updated.rows_affected() == 0 {
+        return Err(Error::not_found(format!("Webhook {} not found", id)).into());
+    }
+
+    Ok(Json(json!({ "id": id, "secret": secret })))
+}
+
+pub async fn delete_webhook(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Value>, ApiError> {
+    load_endpoint(&state, &user, id).await?;
+
+    let deleted = sqlx::query("DELETE FROM webhook_endpoints WHERE id = $1")
+        .bind(id)
+        .execute(&state.db)
+        .await
+        .map_err(Error::from)?;
+
+    if deleted.rows_affected() == 0 {
+        return Err(Error::not_found(format!("Webhook {} not found", id)).into());
+    }
+
+    Ok(Json(json!({ "deleted": id })))
+}
+
+/// Lets receivers check their verification code against a captured delivery.
+pub async fn verify_webhook_signature(
+    State(state): State<Arc<AppState>>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    Json(request): Json<VerifySignatureRequest>,
+) -> Result<Json<Value>, ApiError> {
+    let endpoint = load_endpoint(&state, &user, id).await?;
+
+    let secret = state.keys.decrypt(&endpoint.secret)?;
+    let verification = webhook::verify(
+        &secret,
+        &request.signature,
+        request.payload.as_bytes(),
+        state.clock.now().timestamp(),
+        webhook::DEFAULT_TOLERANCE_SECS,
+    );
+
+    Ok(Json(match verification {
+        Ok(()) => json!({ "valid": true }),
+        Err(e) => json!({ "valid": false, "reason": e.to_string() }),
+    }))
+}
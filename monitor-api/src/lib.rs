@@ -0,0 +1,5 @@
+pub mod audit_log;
+pub mod auth;
+pub mod handlers;
+pub mod live;
+pub mod server;
@@ -0,0 +1,111 @@
+use monitor_core::{
+    db::DatabasePool,
+    events::{Event, EventBus},
+    repository,
+    supervisor::{Backoff, RestartPolicy, ShutdownSignal, Supervisor},
+    Result,
+};
+use sqlx::postgres::PgListener;
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::sync::{broadcast, watch};
+use tracing::info;
+use uuid::Uuid;
+
+/// Postgres channel the `monitor_results_notify` trigger publishes result ids on
+const RESULT_CHANNEL: &str = "monitor_results";
+
+/// Postgres channel carrying the seq of each new `monitor_state_changes` row
+const STATE_CHANGE_CHANNEL: &str = "monitor_state_changes";
+
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Publishes results inserted by the scheduler on the event bus as
+/// `CheckCompleted`, and wakes long-polls on state changes, over a single
+/// database listener.
+#[derive(Clone, Debug)]
+pub struct ResultFeed {
+    events: EventBus,
+    /// Open result streams; results are only loaded while there are some
+    streams: Arc<AtomicUsize>,
+    changes: watch::Sender<i64>,
+}
+
+/// Counts an open stream until dropped.
+pub struct StreamGuard(Arc<AtomicUsize>);
+
+impl Drop for StreamGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl ResultFeed {
+    pub fn new(events: EventBus) -> Self {
+        let (changes, _) = watch::channel(0);
+        Self {
+            events,
+            streams: Arc::new(AtomicUsize::new(0)),
+            changes,
+        }
+    }
+
+    /// Events from the bus, with results included while the guard is held.
+    pub fn subscribe(&self) -> (broadcast::Receiver<Event>, StreamGuard) {
+        self.streams.fetch_add(1, Ordering::Relaxed);
+        (self.events.subscribe(), StreamGuard(self.streams.clone()))
+    }
+
+    /// Updated with the seq of every new state change.
+    pub fn change_signal(&self) -> watch::Receiver<i64> {
+        self.changes.subscribe()
+    }
+
+    /// Listens for result notifications until shutdown, reconnecting when
+    /// the connection drops.
+    pub fn spawn_listener(&self, supervisor: &Supervisor, db: DatabasePool) {
+        let feed = self.clone();
+        let policy = RestartPolicy::Always(Backoff::fixed(RECONNECT_DELAY));
+        supervisor.spawn("result_listener", policy, move |shutdown| {
+            let db = db.clone();
+            let feed = feed.clone();
+            async move { listen(&db, &feed, shutdown).await }
+        });
+    }
+}
+
+async fn listen(db: &DatabasePool, feed: &ResultFeed, mut shutdown: ShutdownSignal) -> Result<()> {
+    let mut listener = PgListener::connect_with(db).await?;
+    listener.listen_all([RESULT_CHANNEL, STATE_CHANGE_CHANNEL]).await?;
+    info!("Listening for new monitor results");
+
+    loop {
+        let notification = tokio::select! {
+            notification = listener.recv() => notification?,
+            _ = shutdown.wait() => return Ok(()),
+        };
+        if notification.channel() == STATE_CHANGE_CHANNEL {
+            if let Ok(seq) = notification.payload().parse::<i64>() {
+                feed.changes.send_replace(seq);
+            }
+            continue;
+        }
+
+        // Nobody is streaming, skip the lookup
+        if feed.streams.load(Ordering::Relaxed) == 0 {
+            continue;
+        }
+        let Ok(id) = notification.payload().parse::<Uuid>() else {
+            continue;
+        };
+
+        if let Some(result) = repository::get_result(db, id).await? {
+            feed.events.publish(Event::CheckCompleted { result: Arc::new(result) });
+        }
+    }
+}
@@ -2,22 +2,40 @@ use monitor_core::{
     Result,
     auth::AuthService,
     cache::create_redis_pool,
+    clock,
     config::Config,
+    crypto::KeyRing,
     db::{create_pool, run_migrations},
+    doctor, error_reporting,
+    events::EventBus,
+    faults::FaultInjector,
     logging,
+    reload::{self, LiveConfig},
+    runtime,
+    supervisor::{Backoff, RestartPolicy, Supervisor},
 };
+use monitor_api::{audit_log, live, server};
 use std::sync::Arc;
 use tokio::net::TcpListener;
-use tracing::info;
+use tracing::{info, warn};
 
-mod server;
+fn main() -> Result<()> {
+    runtime::build("monitor-api", &runtime::from_env())?.block_on(run())
+}
 
-#[tokio::main]
-async fn main() -> Result<()> {
+async fn run() -> Result<()> {
     logging::init_logging();
 
+    if doctor::requested() {
+        let report = doctor::run(&doctor::DoctorOptions::default()).await;
+        report.print("monitor-api");
+        std::process::exit(if report.passed() { 0 } else { 1 });
+    }
+
     let config = Config::from_env()?;
     info!("Starting Monitor API server with config: {:?}", config);
+    logging::set_filter(&config.logging.level)?;
+    let _reporting = error_reporting::init(&config.error_reporting, "monitor-api", env!("CARGO_PKG_VERSION"));
 
     let db_pool = create_pool(&config.database).await?;
     info!("Database connection established");
@@ -30,12 +48,27 @@ async fn main() -> Result<()> {
 
     let auth_service = AuthService::new(config.auth.jwt_secret.clone(), config.auth.jwt_expiration);
 
+    let keys = KeyRing::from_config(&config.encryption, &config.auth.jwt_secret)?;
+
+    let events = EventBus::default();
+    let supervisor = Supervisor::default();
     let state = Arc::new(server::AppState {
         db: db_pool,
         redis: redis_pool,
         auth: auth_service,
-        config: config.clone(),
+        keys,
+        config: LiveConfig::new(config.clone()),
+        results: live::ResultFeed::new(events.clone()),
+        events,
+        supervisor: supervisor.clone(),
+        clock: clock::system(),
+        faults: FaultInjector::from_config(&config),
     });
+    state.results.spawn_listener(&supervisor, state.db.clone());
+    audit_log::spawn(&supervisor, state.db.clone(), &state.events);
+
+    reload::spawn_watcher(state.config.clone())?;
+    spawn_clock_checks(&supervisor, state.clone());
 
     let app = server::create_app(state).await;
 
@@ -48,7 +81,32 @@ async fn main() -> Result<()> {
         config.server.host, config.server.port
     );
 
-    axum::serve(listener, app).await?;
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async {
+            let _ = tokio::signal::ctrl_c().await;
+            info!("Shutdown signal received");
+        })
+        .await?;
+    supervisor.shutdown().await;
 
     Ok(())
 }
+
+/// Re-measures clock skew against the database every five minutes for /health.
+fn spawn_clock_checks(supervisor: &Supervisor, state: Arc<server::AppState>) {
+    supervisor.spawn("clock_checks", RestartPolicy::Always(Backoff::default()), move |mut shutdown| {
+        let state = state.clone();
+        async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(300));
+            loop {
+                tokio::select! {
+                    _ = interval.tick() => {}
+                    _ = shutdown.wait() => return Ok(()),
+                }
+                if let Err(e) = clock::check(&state.db, &state.config.current().clock).await {
+                    warn!("Clock skew check failed: {}", e);
+                }
+            }
+        }
+    });
+}
@@ -1,22 +1,42 @@
 use axum::{
     Router,
-    extract::State,
+    extract::{DefaultBodyLimit, State},
     http::StatusCode,
+    middleware,
     response::{Json, Response},
-    routing::{get, post},
+    routing::{delete, get, post, put},
+};
+use monitor_core::{
+    Error, auth::AuthService, build_info::{self, BuildInfo}, cache::RedisPool, clock::{self, SharedClock},
+    crypto::KeyRing, db::DatabasePool, error_reporting, events::EventBus, faults::FaultInjector, reload::LiveConfig,
+    runtime_settings, supervisor::Supervisor,
 };
-use monitor_core::{Error, auth::AuthService, cache::RedisPool, config::Config, db::DatabasePool};
 use serde_json::json;
 use std::sync::Arc;
 use tower::ServiceBuilder;
 use tower_http::cors::CorsLayer;
 
+use crate::{auth, handlers, live::ResultFeed};
+
+/// Browser recordings are far larger than the default 2 MB JSON body limit
+const HAR_UPLOAD_LIMIT: usize = 32 * 1024 * 1024;
+
 #[derive(Clone, Debug)]
+#[allow(dead_code)]
 pub struct AppState {
     pub db: DatabasePool,
     pub redis: RedisPool,
     pub auth: AuthService,
-    pub config: Config,
+    pub keys: KeyRing,
+    pub config: LiveConfig,
+    pub results: ResultFeed,
+    pub events: EventBus,
+    /// Background tasks, reported on /health
+    pub supervisor: Supervisor,
+    /// Read instead of `Utc::now()` so tests can control time
+    pub clock: SharedClock,
+    /// Fails a share of Redis publishes when fault injection is enabled
+    pub faults: Option<Arc<FaultInjector>>,
 }
 
 #[derive(Debug)]
@@ -30,10 +50,13 @@ impl From<Error> for ApiError {
 
 impl axum::response::IntoResponse for ApiError {
     fn into_response(self) -> Response {
+        error_reporting::capture_error(&self.0, "api");
+
         let (status, error_message) = match self.0 {
             Error::Validation(msg) => (StatusCode::BAD_REQUEST, msg),
             Error::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
             Error::Auth(msg) => (StatusCode::UNAUTHORIZED, msg),
+            Error::Forbidden(msg) => (StatusCode::FORBIDDEN, msg),
             _ => (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 "Internal server error".to_string(),
@@ -49,50 +72,258 @@ impl axum::response::IntoResponse for ApiError {
 }
 
 pub async fn create_app(state: Arc<AppState>) -> Router {
-    Router::new()
+    let features = state.config.current().features.clone();
+
+    let mut router = Router::new()
         .route("/health", get(health_check))
-        .route("/api/auth/login", post(login))
-        .route("/api/auth/register", post(register))
-        .route("/api/monitors", get(get_monitors))
-        .route("/api/monitors", post(create_monitor))
+        .route("/metrics", get(metrics))
+        .route("/api/version", get(version))
+        .route("/api/auth/login", post(handlers::auth::login))
+        .route("/api/auth/register", post(handlers::auth::register))
+        .route("/api/auth/refresh", post(handlers::auth::refresh))
+        .route(
+            "/api/monitors",
+            get(handlers::monitors::get_monitors).post(handlers::monitors::create_monitor),
+        )
+        .route(
+            "/api/monitors/import/har",
+            post(handlers::monitors::import_har).layer(DefaultBodyLimit::max(HAR_UPLOAD_LIMIT)),
+        )
+        .route("/api/monitors/{id}/replay", post(handlers::replay::replay_checks))
+        .route("/api/monitors/{id}/results", get(handlers::monitors::list_results))
+        .route(
+            "/api/monitors/{id}/results/stream",
+            get(handlers::stream::stream_results),
+        )
+        .route(
+            "/api/monitors/{id}/timeseries",
+            get(handlers::monitors::get_timeseries),
+        )
+        .route(
+            "/api/monitors/{id}/availability/calendar",
+            get(handlers::availability::get_availability_calendar),
+        )
+        .route(
+            "/api/monitors/{id}/downtime.ics",
+            get(handlers::availability::get_downtime_ical),
+        )
+        .route(
+            "/api/monitors/{id}/latency",
+            get(handlers::monitors::get_latency_summary),
+        )
+        .route(
+            "/api/monitors/{id}/failures",
+            get(handlers::monitors::get_failure_breakdown),
+        )
+        .route(
+            "/api/monitors/{id}/evidence",
+            get(handlers::monitors::list_evidence),
+        )
+        .route("/api/evidence/{id}", get(handlers::monitors::get_evidence))
+        .route(
+            "/api/monitors/{id}/alerts",
+            get(handlers::alerts::list_alerts).post(handlers::alerts::create_alert),
+        )
+        .route(
+            "/api/monitors/{id}/state",
+            get(handlers::alerts::get_state).put(handlers::alerts::set_state_thresholds),
+        )
+        .route(
+            "/api/monitors/{id}/maintenance",
+            get(handlers::maintenance::list_monitor_windows).post(handlers::maintenance::create_monitor_window),
+        )
+        .route(
+            "/api/monitors/{id}/maintenance/{window_id}",
+            delete(handlers::maintenance::delete_monitor_window),
+        )
+        .route("/api/alerts/{id}", delete(handlers::alerts::delete_alert))
+        .route("/api/alerts/{id}/acknowledge", post(handlers::alerts::acknowledge_alert))
+        .route("/api/monitors/{id}", put(handlers::monitors::update_monitor))
+        .route("/api/monitor-changes", get(handlers::approvals::list_change_requests))
+        .route(
+            "/api/monitor-changes/{id}/approve",
+            post(handlers::approvals::approve_change),
+        )
+        .route(
+            "/api/monitor-changes/{id}/reject",
+            post(handlers::approvals::reject_change),
+        )
+        .route("/api/status", get(handlers::monitors::list_monitor_status))
+        .route("/api/changes", get(handlers::changes::poll_changes))
+        .route(
+            "/api/monitors/{id}/status",
+            get(handlers::monitors::get_monitor_status),
+        )
+        .route(
+            "/api/monitors/{id}/ownership",
+            put(handlers::monitors::update_ownership),
+        )
+        .route("/api/monitors/{id}/pause", post(handlers::monitors::pause_monitor))
+        .route("/api/monitors/{id}/resume", post(handlers::monitors::resume_monitor))
+        .route("/api/monitors/{id}/heartbeat", get(handlers::heartbeats::get_heartbeat))
+        .route(
+            "/api/monitors/{id}/heartbeat/token",
+            post(handlers::heartbeats::issue_token),
+        )
+        .route("/api/heartbeat/{token}", post(handlers::heartbeats::ping))
+        .route(
+            "/api/monitors/{id}/settings",
+            get(handlers::settings::get_effective_settings),
+        )
+        .route("/api/settings/defaults", get(handlers::settings::list_defaults))
+        .route(
+            "/api/settings/defaults/global",
+            put(handlers::settings::set_global_defaults).delete(handlers::settings::delete_global_defaults),
+        )
+        .route(
+            "/api/settings/defaults/tags/{tag}",
+            put(handlers::settings::set_tag_defaults).delete(handlers::settings::delete_tag_defaults),
+        )
+        .route(
+            "/api/teams",
+            get(handlers::teams::list_teams).post(handlers::teams::create_team),
+        )
+        .route("/api/teams/{id}", delete(handlers::teams::delete_team))
+        .route("/api/teams/{id}/members", get(handlers::teams::list_members))
+        .route(
+            "/api/teams/{id}/members/{user_id}",
+            put(handlers::teams::add_member).delete(handlers::teams::remove_member),
+        )
+        .route("/api/compare", get(handlers::compare::compare_monitors))
+        .route("/api/expirations", get(handlers::expirations::list_expirations))
+        .route(
+            "/api/expirations/alert-rules",
+            post(handlers::expirations::create_expiry_alert_rules),
+        )
+        .route("/api/imports/aws", post(handlers::imports::scan_aws))
+        .route("/api/imports/proposals", get(handlers::imports::list_proposals))
+        .route(
+            "/api/imports/proposals/{id}/accept",
+            post(handlers::imports::accept_proposal),
+        )
+        .route(
+            "/api/imports/proposals/{id}/reject",
+            post(handlers::imports::reject_proposal),
+        )
+        .route(
+            "/api/maintenance/sources",
+            get(handlers::maintenance::list_sources).post(handlers::maintenance::create_source),
+        )
+        .route(
+            "/api/maintenance/sources/{id}",
+            delete(handlers::maintenance::delete_source),
+        )
+        .route("/api/maintenance/windows", get(handlers::maintenance::list_windows))
+        .route(
+            "/api/admin/users/{id}/data",
+            delete(handlers::admin::purge_user_data),
+        )
+        .route("/api/admin/config", get(handlers::admin::config_snapshot))
+        .route(
+            "/api/admin/log-level",
+            get(handlers::admin::get_log_level).put(handlers::admin::set_log_level),
+        )
+        .route(
+            "/api/admin/redaction-rules",
+            get(handlers::admin::get_redaction_rules).put(handlers::admin::set_redaction_rules),
+        )
+        .route(
+            "/api/admin/encryption/keys",
+            get(handlers::admin::encryption_key_usage),
+        )
+        .route("/api/admin/audit-log", get(handlers::admin::audit_log))
+        .route(
+            "/api/admin/monitors/duplicates",
+            get(handlers::admin::monitor_duplicates),
+        )
+        .route(
+            "/api/admin/retention/run",
+            post(handlers::admin::run_result_retention),
+        )
+        .route(
+            "/api/tokens",
+            get(handlers::tokens::list_tokens).post(handlers::tokens::create_token),
+        )
+        .route("/api/tokens/dashboard", post(handlers::tokens::create_dashboard_token))
+        .route("/api/tokens/{id}", delete(handlers::tokens::revoke_token));
+
+    if features.enable_scripting {
+        router = router.route(
+            "/api/scripting/capabilities",
+            get(handlers::scripting::get_capabilities),
+        );
+    }
+
+    if features.enable_provisioning {
+        router = router
+            .route(
+                "/api/provisioning/users",
+                get(handlers::provisioning::list_users),
+            )
+            .route(
+                "/api/provisioning/users/bulk",
+                post(handlers::provisioning::bulk_provision),
+            );
+    }
+
+    if features.enable_webhooks {
+        router = router
+            .route(
+                "/api/webhooks",
+                get(handlers::webhooks::list_webhooks).post(handlers::webhooks::create_webhook),
+            )
+            .route("/api/webhooks/{id}", delete(handlers::webhooks::delete_webhook))
+            .route(
+                "/api/webhooks/{id}/rotate-secret",
+                post(handlers::webhooks::rotate_webhook_secret),
+            )
+            .route(
+                "/api/webhooks/{id}/verify",
+                post(handlers::webhooks::verify_webhook_signature),
+            );
+    }
+
+    router
+        .layer(middleware::from_fn_with_state(state.clone(), auth::require_auth))
         .layer(ServiceBuilder::new().layer(CorsLayer::permissive()))
         .with_state(state)
 }
 
-async fn health_check() -> Json<serde_json::Value> {
+async fn health_check(State(state): State<Arc<AppState>>) -> Json<serde_json::Value> {
+    let scheduler_clock = runtime_settings::get(&state.db, runtime_settings::SCHEDULER_CLOCK_STATUS)
+        .await
+        .ok()
+        .flatten()
+        .and_then(|json| serde_json::from_str::<clock::ClockStatus>(&json).ok());
+    let api_clock = clock::current();
+    let skewed = api_clock.iter().chain(&scheduler_clock).any(|c| c.skewed);
+    let degraded = skewed || !state.supervisor.healthy();
+
     Json(json!({
-        "status": "healthy",
-        "timestamp": chrono::Utc::now()
+        "status": if degraded { "degraded" } else { "healthy" },
+        "timestamp": state.clock.now(),
+        "features": state.config.current().features,
+        "clock": {
+            "api": api_clock,
+            "scheduler": scheduler_clock,
+        },
+        "tasks": state.supervisor.health(),
     }))
 }
 
-async fn login(State(_state): State<Arc<AppState>>) -> Result<Json<serde_json::Value>, ApiError> {
-    Ok(Json(json!({
-        "message": "Login endpoint - TODO: implement"
-    })))
-}
-
-async fn register(
-    State(_state): State<Arc<AppState>>,
-) -> Result<Json<serde_json::Value>, ApiError> {
+/// What the API and the schedulers are running, for confirming a rollout
+/// during an incident. Schedulers report through `runtime_settings`.
+async fn version(State(state): State<Arc<AppState>>) -> Result<Json<serde_json::Value>, ApiError> {
+    let api = BuildInfo::current("api", &state.config.current());
+    let schedulers = build_info::reported(&state.db, state.clock.now()).await?;
+    let migrations = build_info::migration_level(&state.db).await?;
     Ok(Json(json!({
-        "message": "Register endpoint - TODO: implement"
+        "api": api,
+        "schedulers": schedulers,
+        "migrations": migrations,
     })))
 }
 
-async fn get_monitors(
-    State(_state): State<Arc<AppState>>,
-) -> Result<Json<serde_json::Value>, ApiError> {
-    Ok(Json(json!({
-        "monitors": [],
-        "message": "Get monitors endpoint - TODO: implement"
-    })))
-}
-
-async fn create_monitor(
-    State(_state): State<Arc<AppState>>,
-) -> Result<Json<serde_json::Value>, ApiError> {
-    Ok(Json(json!({
-        "message": "Create monitor endpoint - TODO: implement"
-    })))
+async fn metrics() -> String {
+    monitor_core::metrics::global().render()
 }
@@ -0,0 +1,31 @@
+use std::{
+    env,
+    process::Command,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Bakes the git commit and build time into the binaries for `/api/version`.
+/// `GIT_COMMIT` and `SOURCE_DATE_EPOCH` take precedence, for builds outside a
+/// git checkout (e.g. in Docker) and reproducible builds.
+fn main() {
+    println!("cargo:rerun-if-env-changed=GIT_COMMIT");
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+    println!("cargo:rerun-if-changed=../.git/HEAD");
+    println!("cargo:rerun-if-changed=../.git/refs");
+
+    let commit = env::var("GIT_COMMIT")
+        .ok()
+        .or_else(|| {
+            let output = Command::new("git").args(["rev-parse", "--short=12", "HEAD"]).output().ok()?;
+            output.status.success().then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        })
+        .filter(|commit| !commit.is_empty())
+        .unwrap_or_else(|| "unknown".to_string());
+    let built_at = env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|epoch| epoch.parse::<u64>().ok())
+        .unwrap_or_else(|| SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default());
+
+    println!("cargo:rustc-env=MONITOR_GIT_COMMIT={}", commit);
+    println!("cargo:rustc-env=MONITOR_BUILT_AT={}", built_at);
+}
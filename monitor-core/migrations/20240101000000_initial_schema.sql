@@ -0,0 +1,48 @@
+CREATE TABLE IF NOT EXISTS users (
+    id UUID PRIMARY KEY,
+    username VARCHAR(255) NOT NULL UNIQUE,
+    email VARCHAR(255) NOT NULL UNIQUE,
+    password_hash TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS monitors (
+    id UUID PRIMARY KEY,
+    name VARCHAR(255) NOT NULL,
+    endpoint TEXT NOT NULL,
+    method VARCHAR(16) NOT NULL DEFAULT 'GET',
+    headers JSONB,
+    body TEXT,
+    expected_status INTEGER NOT NULL DEFAULT 200,
+    timeout INTEGER NOT NULL DEFAULT 30,
+    interval INTEGER NOT NULL DEFAULT 60,
+    script TEXT,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS monitor_results (
+    id UUID PRIMARY KEY,
+    monitor_id UUID NOT NULL REFERENCES monitors(id) ON DELETE CASCADE,
+    status VARCHAR(32) NOT NULL,
+    response_time INTEGER NOT NULL,
+    response_code INTEGER,
+    response_body TEXT,
+    error_message TEXT,
+    checked_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_monitor_results_monitor_id_checked_at
+    ON monitor_results (monitor_id, checked_at DESC);
+
+CREATE TABLE IF NOT EXISTS alerts (
+    id UUID PRIMARY KEY,
+    monitor_id UUID NOT NULL REFERENCES monitors(id) ON DELETE CASCADE,
+    type_ VARCHAR(64) NOT NULL,
+    config JSONB NOT NULL DEFAULT '{}',
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
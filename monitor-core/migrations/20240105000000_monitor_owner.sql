@@ -0,0 +1,3 @@
+ALTER TABLE monitors ADD COLUMN IF NOT EXISTS owner_id UUID REFERENCES users(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_monitors_owner_id ON monitors (owner_id);
@@ -0,0 +1,2 @@
+-- Secret request headers (e.g. Authorization), encrypted with the configured key ring
+ALTER TABLE monitors ADD COLUMN IF NOT EXISTS credentials TEXT;
@@ -0,0 +1,2 @@
+-- Worker clock offset when the result was recorded, for judging checked_at accuracy
+ALTER TABLE monitor_results ADD COLUMN IF NOT EXISTS clock_skew_ms INTEGER;
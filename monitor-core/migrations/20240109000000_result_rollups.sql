@@ -0,0 +1,14 @@
+-- Hourly per-monitor aggregates kept after raw results expire. The latency
+-- histogram is a serialized HdrHistogram so percentiles can be merged exactly
+-- across hours instead of averaging averages.
+CREATE TABLE IF NOT EXISTS monitor_result_rollups (
+    monitor_id UUID NOT NULL REFERENCES monitors(id) ON DELETE CASCADE,
+    bucket_start TIMESTAMPTZ NOT NULL,
+    count BIGINT NOT NULL,
+    success_count BIGINT NOT NULL,
+    latency_sum BIGINT NOT NULL,
+    latency_max INTEGER NOT NULL,
+    latency_histogram BYTEA NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (monitor_id, bucket_start)
+);
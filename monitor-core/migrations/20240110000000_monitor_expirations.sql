@@ -0,0 +1,11 @@
+-- Latest known certificate / domain registration expiry per monitor
+CREATE TABLE IF NOT EXISTS monitor_expirations (
+    monitor_id UUID NOT NULL REFERENCES monitors(id) ON DELETE CASCADE,
+    kind VARCHAR(32) NOT NULL,
+    subject TEXT NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    checked_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (monitor_id, kind)
+);
+
+CREATE INDEX IF NOT EXISTS idx_monitor_expirations_expires_at ON monitor_expirations(expires_at);
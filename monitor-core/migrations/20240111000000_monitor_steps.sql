@@ -0,0 +1,2 @@
+-- Ordered requests of a multi-step transaction monitor; NULL for single-request monitors
+ALTER TABLE monitors ADD COLUMN IF NOT EXISTS steps JSONB;
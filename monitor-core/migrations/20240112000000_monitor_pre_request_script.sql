@@ -0,0 +1,2 @@
+-- Optional script run before each request to compute headers, signatures or query params
+ALTER TABLE monitors ADD COLUMN IF NOT EXISTS pre_request_script TEXT;
@@ -0,0 +1,19 @@
+-- Default monitor settings per scope: 'global' or 'tag:<name>'. NULL inherits.
+CREATE TABLE IF NOT EXISTS settings_defaults (
+    scope VARCHAR(255) PRIMARY KEY,
+    timeout INTEGER,
+    retries INTEGER,
+    notification_channels UUID[],
+    script_profile VARCHAR(64),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Monitor-level values; NULL falls back to tag and global defaults
+ALTER TABLE monitors ALTER COLUMN timeout DROP NOT NULL;
+ALTER TABLE monitors ALTER COLUMN timeout DROP DEFAULT;
+ALTER TABLE monitors ADD COLUMN IF NOT EXISTS retries INTEGER;
+ALTER TABLE monitors ADD COLUMN IF NOT EXISTS notification_channels UUID[];
+ALTER TABLE monitors ADD COLUMN IF NOT EXISTS script_profile VARCHAR(64);
+
+-- 30 was the column default, so treat it as inherited rather than chosen
+UPDATE monitors SET timeout = NULL WHERE timeout = 30;
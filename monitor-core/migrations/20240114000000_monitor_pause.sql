@@ -0,0 +1,20 @@
+-- Pausing disables a monitor; resume_at lets the scheduler re-enable it
+ALTER TABLE monitors ADD COLUMN IF NOT EXISTS paused_reason TEXT;
+ALTER TABLE monitors ADD COLUMN IF NOT EXISTS paused_by UUID REFERENCES users(id) ON DELETE SET NULL;
+ALTER TABLE monitors ADD COLUMN IF NOT EXISTS paused_at TIMESTAMPTZ;
+ALTER TABLE monitors ADD COLUMN IF NOT EXISTS resume_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_monitors_resume_at ON monitors (resume_at) WHERE resume_at IS NOT NULL;
+
+-- Who changed what; actor_id is NULL for actions taken by the scheduler
+CREATE TABLE IF NOT EXISTS audit_log (
+    id UUID PRIMARY KEY,
+    actor_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    action VARCHAR(64) NOT NULL,
+    monitor_id UUID REFERENCES monitors(id) ON DELETE CASCADE,
+    details JSONB NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_log_created_at ON audit_log (created_at);
+CREATE INDEX IF NOT EXISTS idx_audit_log_monitor_id ON audit_log (monitor_id);
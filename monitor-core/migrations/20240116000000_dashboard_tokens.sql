@@ -0,0 +1,3 @@
+-- Dashboard tokens are read-only and limited to an allow-list of monitors and/or tags
+ALTER TABLE personal_access_tokens ADD COLUMN IF NOT EXISTS kind VARCHAR(16) NOT NULL DEFAULT 'personal';
+ALTER TABLE personal_access_tokens ADD COLUMN IF NOT EXISTS monitor_ids UUID[] NOT NULL DEFAULT '{}';
@@ -0,0 +1,12 @@
+-- Lets the API stream new results without polling; the payload is the result id
+CREATE OR REPLACE FUNCTION notify_monitor_result() RETURNS trigger AS $$
+BEGIN
+    PERFORM pg_notify('monitor_results', NEW.id::text);
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS monitor_results_notify ON monitor_results;
+CREATE TRIGGER monitor_results_notify
+    AFTER INSERT ON monitor_results
+    FOR EACH ROW EXECUTE FUNCTION notify_monitor_result();
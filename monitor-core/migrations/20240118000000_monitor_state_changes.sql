@@ -0,0 +1,37 @@
+-- One row each time a monitor's result status differs from its previous result;
+-- seq is the cursor for GET /api/changes
+CREATE TABLE IF NOT EXISTS monitor_state_changes (
+    seq BIGSERIAL PRIMARY KEY,
+    monitor_id UUID NOT NULL REFERENCES monitors(id) ON DELETE CASCADE,
+    previous_status VARCHAR(32),
+    status VARCHAR(32) NOT NULL,
+    result_id UUID NOT NULL,
+    changed_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_monitor_state_changes_changed_at ON monitor_state_changes (changed_at);
+
+CREATE OR REPLACE FUNCTION record_monitor_state_change() RETURNS trigger AS $$
+DECLARE
+    previous VARCHAR(32);
+    change_seq BIGINT;
+BEGIN
+    SELECT status INTO previous FROM monitor_results
+    WHERE monitor_id = NEW.monitor_id AND id <> NEW.id AND checked_at <= NEW.checked_at
+    ORDER BY checked_at DESC
+    LIMIT 1;
+
+    IF previous IS DISTINCT FROM NEW.status THEN
+        INSERT INTO monitor_state_changes (monitor_id, previous_status, status, result_id, changed_at)
+        VALUES (NEW.monitor_id, previous, NEW.status, NEW.id, NEW.checked_at)
+        RETURNING seq INTO change_seq;
+        PERFORM pg_notify('monitor_state_changes', change_seq::text);
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS monitor_results_state_change ON monitor_results;
+CREATE TRIGGER monitor_results_state_change
+    AFTER INSERT ON monitor_results
+    FOR EACH ROW EXECUTE FUNCTION record_monitor_state_change();
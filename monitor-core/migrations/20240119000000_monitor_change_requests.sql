@@ -0,0 +1,16 @@
+-- Monitor edits awaiting a second user's approval when change approval is enabled
+CREATE TABLE IF NOT EXISTS monitor_change_requests (
+    id UUID PRIMARY KEY,
+    monitor_id UUID NOT NULL REFERENCES monitors(id) ON DELETE CASCADE,
+    requested_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    changes JSONB NOT NULL,
+    -- New credentials, encrypted like monitors.credentials
+    credentials TEXT,
+    status VARCHAR(16) NOT NULL DEFAULT 'pending',
+    reviewed_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    review_comment TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    reviewed_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_monitor_change_requests_status ON monitor_change_requests (status, created_at);
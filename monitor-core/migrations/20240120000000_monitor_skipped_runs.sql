@@ -0,0 +1,10 @@
+-- Scheduled runs that were skipped because the previous check of the same
+-- monitor was still running, on this worker or another
+CREATE TABLE IF NOT EXISTS monitor_skipped_runs (
+    id UUID PRIMARY KEY,
+    monitor_id UUID NOT NULL REFERENCES monitors(id) ON DELETE CASCADE,
+    reason VARCHAR(32) NOT NULL,
+    skipped_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_monitor_skipped_runs_monitor ON monitor_skipped_runs (monitor_id, skipped_at DESC);
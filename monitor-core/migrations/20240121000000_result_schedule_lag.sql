@@ -0,0 +1,2 @@
+-- How long after its scheduled tick the check started
+ALTER TABLE monitor_results ADD COLUMN IF NOT EXISTS schedule_lag_ms INTEGER;
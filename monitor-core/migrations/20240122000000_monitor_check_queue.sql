@@ -0,0 +1,11 @@
+-- Durable schedule: one row per scheduled monitor holding its next due time.
+-- Workers claim due rows with FOR UPDATE SKIP LOCKED; a claim expires after
+-- its lease so checks held by a crashed worker are picked up again.
+CREATE TABLE IF NOT EXISTS monitor_check_queue (
+    monitor_id UUID PRIMARY KEY REFERENCES monitors(id) ON DELETE CASCADE,
+    due_at TIMESTAMPTZ NOT NULL,
+    claimed_by VARCHAR(64),
+    claimed_until TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_monitor_check_queue_due ON monitor_check_queue (due_at);
@@ -0,0 +1,2 @@
+-- Resolve the host afresh on every check instead of using the shared DNS cache
+ALTER TABLE monitors ADD COLUMN IF NOT EXISTS bypass_dns_cache BOOLEAN NOT NULL DEFAULT false;
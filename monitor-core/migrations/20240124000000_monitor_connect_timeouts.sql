@@ -0,0 +1,4 @@
+-- Per-monitor connection phase limits; NULL uses the scheduler's http_client defaults.
+-- The existing timeout column remains the limit for the whole request.
+ALTER TABLE monitors ADD COLUMN IF NOT EXISTS connect_timeout_ms INTEGER;
+ALTER TABLE monitors ADD COLUMN IF NOT EXISTS tls_timeout_ms INTEGER;
@@ -0,0 +1,6 @@
+-- Structured failure cause alongside the free-form error_message
+ALTER TABLE monitor_results ADD COLUMN IF NOT EXISTS error_category VARCHAR(32);
+
+CREATE INDEX IF NOT EXISTS idx_monitor_results_error_category
+    ON monitor_results (monitor_id, checked_at)
+    WHERE error_category IS NOT NULL;
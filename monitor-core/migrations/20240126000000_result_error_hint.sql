@@ -0,0 +1,2 @@
+-- Suggested cause for common failures, see failures::hint
+ALTER TABLE monitor_results ADD COLUMN IF NOT EXISTS error_hint TEXT;
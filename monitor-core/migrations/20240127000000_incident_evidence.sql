@@ -0,0 +1,17 @@
+-- Full response captured when a monitor goes from up to down; kept independently
+-- of result retention so the evidence outlives the raw results
+CREATE TABLE IF NOT EXISTS incident_evidence (
+    id UUID PRIMARY KEY,
+    monitor_id UUID NOT NULL REFERENCES monitors(id) ON DELETE CASCADE,
+    result_id UUID NOT NULL UNIQUE,
+    previous_status VARCHAR(32) NOT NULL,
+    status VARCHAR(32) NOT NULL,
+    response_code INTEGER,
+    response_headers JSONB,
+    response_body TEXT,
+    body_truncated BOOLEAN NOT NULL DEFAULT false,
+    error_message TEXT,
+    captured_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_incident_evidence_monitor ON incident_evidence (monitor_id, captured_at DESC);
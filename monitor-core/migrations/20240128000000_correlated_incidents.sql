@@ -0,0 +1,14 @@
+-- Monitors that went down together, grouped by a shared tag or network block
+-- so they page once instead of once per monitor
+CREATE TABLE IF NOT EXISTS correlated_incidents (
+    id UUID PRIMARY KEY,
+    kind VARCHAR(16) NOT NULL,
+    group_key VARCHAR(255) NOT NULL,
+    monitor_ids UUID[] NOT NULL,
+    started_at TIMESTAMPTZ NOT NULL,
+    last_seen_at TIMESTAMPTZ NOT NULL,
+    resolved_at TIMESTAMPTZ
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_correlated_incidents_open
+    ON correlated_incidents (kind, group_key) WHERE resolved_at IS NULL;
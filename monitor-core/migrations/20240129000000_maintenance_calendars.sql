@@ -0,0 +1,25 @@
+-- External maintenance calendars (iCal or Statuspage feeds). Monitors tagged
+-- with a source's tag get no alerts while one of its windows is in progress.
+CREATE TABLE IF NOT EXISTS maintenance_sources (
+    id UUID PRIMARY KEY,
+    name VARCHAR(255) NOT NULL,
+    url TEXT NOT NULL,
+    tag VARCHAR(255) NOT NULL,
+    enabled BOOLEAN NOT NULL DEFAULT true,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    last_synced_at TIMESTAMPTZ,
+    last_error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS maintenance_windows (
+    id UUID PRIMARY KEY,
+    source_id UUID NOT NULL REFERENCES maintenance_sources(id) ON DELETE CASCADE,
+    uid TEXT NOT NULL,
+    summary TEXT NOT NULL,
+    starts_at TIMESTAMPTZ NOT NULL,
+    ends_at TIMESTAMPTZ NOT NULL,
+    UNIQUE (source_id, uid)
+);
+
+CREATE INDEX IF NOT EXISTS idx_maintenance_windows_time ON maintenance_windows (starts_at, ends_at);
@@ -0,0 +1,5 @@
+-- Set while an alert rule's condition holds, so each rule notifies once when
+-- it starts firing and once when it resolves.
+ALTER TABLE alerts ADD COLUMN IF NOT EXISTS firing_since TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_alerts_monitor_id ON alerts (monitor_id) WHERE enabled = true;
@@ -0,0 +1,18 @@
+-- Monitors proposed from cloud provider resources, created only once a user
+-- accepts them. Decided proposals stay so a later scan does not propose
+-- a rejected resource again.
+CREATE TABLE IF NOT EXISTS import_proposals (
+    id UUID PRIMARY KEY,
+    source VARCHAR(50) NOT NULL,
+    resource_id TEXT NOT NULL UNIQUE,
+    name VARCHAR(255) NOT NULL,
+    endpoint TEXT NOT NULL,
+    tags TEXT[] NOT NULL DEFAULT '{}',
+    status VARCHAR(20) NOT NULL DEFAULT 'pending',
+    monitor_id UUID REFERENCES monitors(id) ON DELETE SET NULL,
+    decided_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    proposed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    decided_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_import_proposals_status ON import_proposals (status, proposed_at);
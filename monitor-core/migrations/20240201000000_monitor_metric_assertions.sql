@@ -0,0 +1,3 @@
+-- Conditions on a scraped Prometheus/OpenMetrics endpoint, e.g. ["up == 1"].
+-- NULL checks only the status code.
+ALTER TABLE monitors ADD COLUMN IF NOT EXISTS metric_assertions JSONB;
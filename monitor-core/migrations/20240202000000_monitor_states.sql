@@ -0,0 +1,12 @@
+-- Per-monitor health derived from consecutive check results. Notifications
+-- are sent when `state` changes, not for every failed check.
+CREATE TABLE IF NOT EXISTS monitor_states (
+    monitor_id UUID PRIMARY KEY REFERENCES monitors(id) ON DELETE CASCADE,
+    state VARCHAR(20) NOT NULL DEFAULT 'ok',
+    failure_threshold INTEGER NOT NULL DEFAULT 3 CHECK (failure_threshold > 0),
+    recovery_threshold INTEGER NOT NULL DEFAULT 2 CHECK (recovery_threshold > 0),
+    consecutive_failures INTEGER NOT NULL DEFAULT 0,
+    consecutive_successes INTEGER NOT NULL DEFAULT 0,
+    changed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,7 @@
+-- A monitor whose state changes more than `flap_threshold` times within
+-- `flap_window_secs` is flapping: one notification is sent when that starts
+-- and one when a whole window passes without a change.
+ALTER TABLE monitor_states ADD COLUMN IF NOT EXISTS flap_threshold INTEGER NOT NULL DEFAULT 5 CHECK (flap_threshold > 0);
+ALTER TABLE monitor_states ADD COLUMN IF NOT EXISTS flap_window_secs INTEGER NOT NULL DEFAULT 1800 CHECK (flap_window_secs > 0);
+ALTER TABLE monitor_states ADD COLUMN IF NOT EXISTS recent_transitions TIMESTAMPTZ[] NOT NULL DEFAULT '{}';
+ALTER TABLE monitor_states ADD COLUMN IF NOT EXISTS flapping_since TIMESTAMPTZ;
@@ -0,0 +1,3 @@
+-- NATS probe or Kafka/NATS consumer lag check run instead of the HTTP
+-- request, e.g. {"type": "nats_probe"}. NULL is an HTTP check.
+ALTER TABLE monitors ADD COLUMN IF NOT EXISTS broker_check JSONB;
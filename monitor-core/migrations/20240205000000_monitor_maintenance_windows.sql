@@ -0,0 +1,14 @@
+-- Windows set on one monitor rather than imported from a calendar. They can
+-- repeat by a cron expression or an RRULE, in which case starts_at/ends_at
+-- is the first occurrence and gives the length of every later one.
+ALTER TABLE maintenance_windows ALTER COLUMN source_id DROP NOT NULL;
+ALTER TABLE maintenance_windows ADD COLUMN IF NOT EXISTS monitor_id UUID REFERENCES monitors(id) ON DELETE CASCADE;
+ALTER TABLE maintenance_windows ADD COLUMN IF NOT EXISTS recurrence TEXT;
+-- Calendar windows only silence alerts
+ALTER TABLE maintenance_windows ADD COLUMN IF NOT EXISTS skip_checks BOOLEAN NOT NULL DEFAULT false;
+ALTER TABLE maintenance_windows ADD COLUMN IF NOT EXISTS created_by UUID REFERENCES users(id) ON DELETE SET NULL;
+ALTER TABLE maintenance_windows ADD CONSTRAINT maintenance_windows_owner
+    CHECK ((source_id IS NULL) <> (monitor_id IS NULL));
+
+CREATE INDEX IF NOT EXISTS idx_maintenance_windows_monitor ON maintenance_windows (monitor_id)
+    WHERE monitor_id IS NOT NULL;
@@ -0,0 +1,3 @@
+-- HEAD, GET or write-read-delete round trip against an S3-compatible bucket
+-- run instead of the HTTP request, e.g. {"type": "head", "key": "health.txt"}.
+ALTER TABLE monitors ADD COLUMN IF NOT EXISTS storage_check JSONB;
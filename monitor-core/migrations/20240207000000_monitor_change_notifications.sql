@@ -0,0 +1,14 @@
+-- Lets schedulers queue, reschedule or drop a monitor as soon as it is created
+-- or edited instead of on their next periodic sync; the payload is the
+-- monitor id. Deleted monitors leave the queue through its foreign key.
+CREATE OR REPLACE FUNCTION notify_monitor_change() RETURNS trigger AS $$
+BEGIN
+    PERFORM pg_notify('monitor_changes', NEW.id::text);
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS monitors_notify_change ON monitors;
+CREATE TRIGGER monitors_notify_change
+    AFTER INSERT OR UPDATE OF enabled, resume_at, interval ON monitors
+    FOR EACH ROW EXECUTE FUNCTION notify_monitor_change();
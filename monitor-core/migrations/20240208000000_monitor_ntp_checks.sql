@@ -0,0 +1,3 @@
+-- NTP server check run instead of the HTTP request, e.g.
+-- {"max_stratum": 3, "max_offset_ms": 100}. NULL is an HTTP check.
+ALTER TABLE monitors ADD COLUMN IF NOT EXISTS ntp_check JSONB;
@@ -0,0 +1,4 @@
+-- SNMP polling check run instead of the HTTP request, e.g.
+-- {"version": "v2c", "oids": {"if_oper_status": "1.3.6.1.2.1.2.2.1.8.1"},
+--  "assertions": ["if_oper_status == 1"]}. NULL is an HTTP check.
+ALTER TABLE monitors ADD COLUMN IF NOT EXISTS snmp_check JSONB;
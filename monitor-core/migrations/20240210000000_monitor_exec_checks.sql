@@ -0,0 +1,3 @@
+-- Allow-listed local command run instead of the HTTP request, e.g.
+-- {"args": ["-w", "20%", "-c", "10%"], "output": "nagios"}. NULL is an HTTP check.
+ALTER TABLE monitors ADD COLUMN IF NOT EXISTS exec_check JSONB;
@@ -0,0 +1,4 @@
+-- Pause between retries of a transient failure; NULL uses the built-in 2s.
+ALTER TABLE monitors ADD COLUMN IF NOT EXISTS retry_delay_ms INTEGER;
+-- Attempts the check took; results stored before retries were counted took one.
+ALTER TABLE monitor_results ADD COLUMN IF NOT EXISTS attempts INTEGER NOT NULL DEFAULT 1;
@@ -0,0 +1,9 @@
+-- Cron expression (UTC) the monitor runs on instead of every `interval`
+-- seconds, e.g. '*/5 9-17 * * 1-5'. NULL runs on the interval.
+ALTER TABLE monitors ADD COLUMN IF NOT EXISTS schedule_cron TEXT;
+
+-- Schedulers move a monitor's next run when its schedule changes as well
+DROP TRIGGER IF EXISTS monitors_notify_change ON monitors;
+CREATE TRIGGER monitors_notify_change
+    AFTER INSERT OR UPDATE OF enabled, resume_at, interval, schedule_cron ON monitors
+    FOR EACH ROW EXECUTE FUNCTION notify_monitor_change();
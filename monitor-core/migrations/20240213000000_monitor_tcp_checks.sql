@@ -0,0 +1,3 @@
+-- TCP port check run instead of the HTTP request, e.g.
+-- {"payload": "PING\r\n", "banner": "+PONG"}. NULL is an HTTP check.
+ALTER TABLE monitors ADD COLUMN IF NOT EXISTS tcp_check JSONB;
@@ -0,0 +1,26 @@
+-- Headers are now validated as an object of header names to strings. The
+-- scheduler used to ignore every header of a monitor whose headers had any
+-- other shape; bring stored rows in line so they are sent from now on.
+
+-- Anything but an object could never be sent
+UPDATE monitors SET headers = NULL
+WHERE headers IS NOT NULL AND jsonb_typeof(headers) <> 'object';
+
+-- Numbers and booleans become their text; nulls, arrays, objects and
+-- entries whose name is not a valid header name are dropped
+UPDATE monitors m SET headers = (
+    SELECT NULLIF(COALESCE(jsonb_object_agg(key, to_jsonb(value #>> '{}')), '{}'::jsonb), '{}'::jsonb)
+    FROM jsonb_each(m.headers)
+    WHERE jsonb_typeof(value) IN ('string', 'number', 'boolean')
+      AND key ~ '^[!#$%&''*+.^_`|~0-9A-Za-z-]+$'
+      AND position(E'\n' IN value #>> '{}') = 0
+      AND position(E'\r' IN value #>> '{}') = 0
+)
+WHERE jsonb_typeof(headers) = 'object'
+  AND EXISTS (
+      SELECT 1 FROM jsonb_each(m.headers)
+      WHERE jsonb_typeof(value) <> 'string'
+         OR key !~ '^[!#$%&''*+.^_`|~0-9A-Za-z-]+$'
+         OR position(E'\n' IN value #>> '{}') > 0
+         OR position(E'\r' IN value #>> '{}') > 0
+  );
@@ -0,0 +1,4 @@
+-- DNS record check run instead of the HTTP request, e.g.
+-- {"record_type": "MX", "expected": ["10 mail.example.com"], "min_ttl": 300}.
+-- NULL is an HTTP check.
+ALTER TABLE monitors ADD COLUMN IF NOT EXISTS dns_check JSONB;
@@ -0,0 +1,8 @@
+-- Monitor methods are now validated and standard ones stored upper case.
+-- Methods that were not valid tokens were sent as GET, so store that.
+UPDATE monitors SET method = UPPER(method)
+WHERE UPPER(method) IN ('GET', 'HEAD', 'POST', 'PUT', 'PATCH', 'DELETE', 'OPTIONS')
+  AND method <> UPPER(method);
+
+UPDATE monitors SET method = 'GET'
+WHERE method !~ '^[!#$%&''*+.^_`|~0-9A-Za-z-]+$';
@@ -0,0 +1,3 @@
+-- Certificate expiry check run instead of the HTTP request, e.g.
+-- {"min_days": 21, "server_name": "www.example.com"}. NULL is an HTTP check.
+ALTER TABLE monitors ADD COLUMN IF NOT EXISTS tls_check JSONB;
@@ -0,0 +1,4 @@
+-- Keyword and regular expression the HTTP response body must match on top
+-- of the expected status. NULL skips the match.
+ALTER TABLE monitors ADD COLUMN IF NOT EXISTS expected_body_contains TEXT;
+ALTER TABLE monitors ADD COLUMN IF NOT EXISTS expected_body_regex TEXT;
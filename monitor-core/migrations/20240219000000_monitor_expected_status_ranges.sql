@@ -0,0 +1,4 @@
+-- expected_status now holds codes and ranges such as '200-299,304'.
+ALTER TABLE monitors ALTER COLUMN expected_status DROP DEFAULT;
+ALTER TABLE monitors ALTER COLUMN expected_status TYPE TEXT USING expected_status::text;
+ALTER TABLE monitors ALTER COLUMN expected_status SET DEFAULT '200';
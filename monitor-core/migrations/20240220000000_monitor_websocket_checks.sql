@@ -0,0 +1,3 @@
+-- WebSocket check run instead of the HTTP request, e.g.
+-- {"message": "ping", "expected_reply": "pong"}. NULL is an HTTP check.
+ALTER TABLE monitors ADD COLUMN IF NOT EXISTS websocket_check JSONB;
@@ -0,0 +1,3 @@
+-- SMTP or IMAP check run instead of the HTTP request, e.g.
+-- {"starttls": true, "authenticate": true}. NULL is an HTTP check.
+ALTER TABLE monitors ADD COLUMN IF NOT EXISTS mail_check JSONB;
@@ -0,0 +1,4 @@
+-- Connect-and-query check of a PostgreSQL, MySQL or Redis server run
+-- instead of the HTTP request, e.g. {"query": "SELECT 1"}. NULL is an
+-- HTTP check.
+ALTER TABLE monitors ADD COLUMN IF NOT EXISTS database_check JSONB;
@@ -0,0 +1,14 @@
+-- Passive heartbeat check: the monitored job pings the API and the check
+-- fails once no ping arrived within the interval plus the grace period,
+-- e.g. {"grace_secs": 300}. NULL is an HTTP check.
+ALTER TABLE monitors ADD COLUMN IF NOT EXISTS heartbeat_check JSONB;
+
+-- One ping token per heartbeat monitor. Only the SHA-256 hash of the token
+-- is stored.
+CREATE TABLE IF NOT EXISTS monitor_heartbeats (
+    monitor_id UUID PRIMARY KEY REFERENCES monitors(id) ON DELETE CASCADE,
+    token_hash TEXT NOT NULL UNIQUE,
+    last_ping_at TIMESTAMPTZ,
+    pings BIGINT NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,16 @@
+-- Monitors the scheduler keeps for the monitoring stack itself: the API,
+-- the scheduler, Postgres and Redis. system_key names the part each one
+-- watches; NULL is an ordinary monitor.
+ALTER TABLE monitors ADD COLUMN IF NOT EXISTS system_key TEXT UNIQUE;
+
+CREATE OR REPLACE FUNCTION protect_system_monitors() RETURNS trigger AS $$
+BEGIN
+    RAISE EXCEPTION 'System monitor "%" cannot be deleted', OLD.name;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS monitors_protect_system ON monitors;
+CREATE TRIGGER monitors_protect_system
+    BEFORE DELETE ON monitors
+    FOR EACH ROW WHEN (OLD.system_key IS NOT NULL)
+    EXECUTE FUNCTION protect_system_monitors();
@@ -0,0 +1,10 @@
+-- Which worker is running a monitor's check. A row is claimed before the
+-- check starts and deleted when it ends. An expired row belongs to a worker
+-- that stopped mid-check and may be claimed by any worker; the same worker
+-- may always claim its own row again.
+CREATE TABLE IF NOT EXISTS monitor_check_leases (
+    monitor_id UUID PRIMARY KEY REFERENCES monitors(id) ON DELETE CASCADE,
+    worker TEXT NOT NULL,
+    token UUID NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL
+);
@@ -0,0 +1,28 @@
+-- Edits are validated together with the stored monitor, so rows saved before
+-- these rules existed are brought in line once instead of blocking every edit.
+
+-- A timeout must be shorter than the interval; overlong ones are cut to just
+-- under it, or left to the defaults when the interval is a single second.
+UPDATE monitors SET timeout = NULLIF(interval - 1, 0) WHERE timeout >= interval;
+
+-- A monitor runs one kind of check. Where several are set, keep the one the
+-- scheduler has been running, the first in `Monitor::monitor_type` order, and
+-- clear the rest.
+UPDATE monitors SET
+    storage_check = CASE WHEN broker_check IS NULL THEN storage_check END,
+    ntp_check = CASE WHEN COALESCE(broker_check, storage_check) IS NULL THEN ntp_check END,
+    snmp_check = CASE WHEN COALESCE(broker_check, storage_check, ntp_check) IS NULL THEN snmp_check END,
+    exec_check = CASE WHEN COALESCE(broker_check, storage_check, ntp_check, snmp_check) IS NULL THEN exec_check END,
+    tcp_check = CASE WHEN COALESCE(broker_check, storage_check, ntp_check, snmp_check, exec_check) IS NULL THEN tcp_check END,
+    dns_check = CASE WHEN COALESCE(broker_check, storage_check, ntp_check, snmp_check, exec_check, tcp_check) IS NULL THEN dns_check END,
+    tls_check = CASE WHEN COALESCE(broker_check, storage_check, ntp_check, snmp_check, exec_check, tcp_check, dns_check) IS NULL THEN tls_check END,
+    websocket_check = CASE WHEN COALESCE(broker_check, storage_check, ntp_check, snmp_check, exec_check, tcp_check, dns_check, tls_check) IS NULL THEN websocket_check END,
+    mail_check = CASE WHEN COALESCE(broker_check, storage_check, ntp_check, snmp_check, exec_check, tcp_check, dns_check, tls_check, websocket_check) IS NULL THEN mail_check END,
+    database_check = CASE WHEN COALESCE(broker_check, storage_check, ntp_check, snmp_check, exec_check, tcp_check, dns_check, tls_check, websocket_check, mail_check) IS NULL THEN database_check END,
+    heartbeat_check = CASE WHEN COALESCE(broker_check, storage_check, ntp_check, snmp_check, exec_check, tcp_check, dns_check, tls_check, websocket_check, mail_check, database_check) IS NULL THEN heartbeat_check END,
+    steps = CASE WHEN COALESCE(broker_check, storage_check, ntp_check, snmp_check, exec_check, tcp_check, dns_check, tls_check, websocket_check, mail_check, database_check, heartbeat_check) IS NULL THEN steps END,
+    metric_assertions = CASE WHEN COALESCE(broker_check, storage_check, ntp_check, snmp_check, exec_check, tcp_check, dns_check, tls_check, websocket_check, mail_check, database_check, heartbeat_check, steps) IS NULL THEN metric_assertions END
+WHERE num_nonnulls(
+    broker_check, storage_check, ntp_check, snmp_check, exec_check, tcp_check, dns_check,
+    tls_check, websocket_check, mail_check, database_check, heartbeat_check, steps, metric_assertions
+) > 1;
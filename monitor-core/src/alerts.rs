@@ -0,0 +1,490 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use crate::{
+    db::DatabasePool,
+    error::Result,
+    expirations::EXPIRY_ALERT_TYPE,
+    models::{Alert, Monitor, MonitorResult},
+    pagerduty, Error,
+};
+
+/// Fires after `consecutive` failed checks in a row.
+pub const DOWN_ALERT_TYPE: &str = "down";
+/// Fires after `consecutive` successful checks slower than `threshold_ms`.
+pub const LATENCY_ALERT_TYPE: &str = "latency";
+
+/// A `down` alert sent to the Telegram chat in its config.
+pub const TELEGRAM_ALERT_TYPE: &str = "telegram";
+
+/// Alert types evaluated after every check result.
+pub const RESULT_ALERT_TYPES: &[&str] = &[DOWN_ALERT_TYPE, LATENCY_ALERT_TYPE, TELEGRAM_ALERT_TYPE];
+
+/// Message of Slack and Discord channels without a `template`.
+pub const DEFAULT_CHAT_TEMPLATE: &str = "{subject}\n{summary}\n{endpoint}";
+
+/// Discord rejects longer messages.
+const DISCORD_MAX_CONTENT: usize = 2000;
+
+/// Where a rule's notifications go. Rules without channels notify the
+/// monitor's webhook endpoints instead.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ChannelConfig {
+    Webhook { url: String },
+    Email { to: Vec<String> },
+    /// Incoming webhook. `channel` only takes effect on legacy webhooks that
+    /// may post anywhere.
+    Slack {
+        webhook_url: String,
+        channel: Option<String>,
+        /// User or user group IDs, or `here` / `channel`
+        #[serde(default)]
+        mentions: Vec<String>,
+        /// See `render` for the placeholders
+        template: Option<String>,
+    },
+    Discord {
+        webhook_url: String,
+        /// User IDs, role IDs prefixed with `&`, or `here` / `everyone`
+        #[serde(default)]
+        mentions: Vec<String>,
+        template: Option<String>,
+    },
+    /// Bot API `sendMessage` to a chat, group or channel the bot is in
+    Telegram { bot_token: String, chat_id: String },
+    /// Events v2 integration; firing triggers and resolving resolves the incident
+    PagerDuty {
+        routing_key: String,
+        /// `critical` (default), `error`, `warning` or `info`
+        severity: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AlertCondition {
+    Down { consecutive: usize },
+    Latency { threshold_ms: i32, consecutive: usize },
+    Expiry { threshold_days: i64 },
+}
+
+/// An `alerts` row with its config read.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlertRule {
+    pub id: Uuid,
+    pub monitor_id: Uuid,
+    pub alert_type: String,
+    pub condition: AlertCondition,
+    pub channels: Vec<ChannelConfig>,
+    pub firing: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct RuleConfig {
+    #[serde(default = "default_consecutive")]
+    consecutive: usize,
+    threshold_ms: Option<i32>,
+    threshold_days: Option<i64>,
+    #[serde(default)]
+    channels: Vec<ChannelConfig>,
+    /// Only for `telegram` alerts
+    bot_token: Option<String>,
+    chat_id: Option<String>,
+}
+
+fn default_consecutive() -> usize {
+    1
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AlertState {
+    Firing,
+    Resolved,
+}
+
+/// What is sent through every channel of a rule when it fires or resolves.
+#[derive(Debug, Clone, Serialize)]
+pub struct AlertNotification {
+    pub alert_id: Uuid,
+    pub alert_type: String,
+    pub state: AlertState,
+    pub monitor_id: Uuid,
+    pub monitor_name: String,
+    pub endpoint: String,
+    pub summary: String,
+    /// Of the result that fired or resolved the alert; unset for expiry alerts
+    pub status: Option<String>,
+    pub response_time: Option<i32>,
+    pub error_message: Option<String>,
+    pub at: DateTime<Utc>,
+}
+
+impl AlertRule {
+    pub fn from_alert(alert: &Alert) -> Result<Self> {
+        let (condition, channels) = parse(&alert.type_, &alert.config)?;
+        Ok(Self {
+            id: alert.id,
+            monitor_id: alert.monitor_id,
+            alert_type: alert.type_.clone(),
+            condition,
+            channels,
+            firing: alert.firing_since.is_some(),
+        })
+    }
+}
+
+/// Reads and validates an alert's type and config.
+pub fn parse(alert_type: &str, config: &serde_json::Value) -> Result<(AlertCondition, Vec<ChannelConfig>)> {
+    let config: RuleConfig = serde_json::from_value(config.clone())
+        .map_err(|e| Error::validation(format!("Invalid alert config: {}", e)))?;
+    if config.consecutive == 0 {
+        return Err(Error::validation("consecutive must be at least 1"));
+    }
+    let mut channels = config.channels;
+    let condition = match alert_type {
+        DOWN_ALERT_TYPE => AlertCondition::Down {
+            consecutive: config.consecutive,
+        },
+        TELEGRAM_ALERT_TYPE => {
+            channels.push(ChannelConfig::Telegram {
+                bot_token: config.bot_token.ok_or_else(|| Error::validation("Telegram alerts need a bot_token"))?,
+                chat_id: config.chat_id.ok_or_else(|| Error::validation("Telegram alerts need a chat_id"))?,
+            });
+            AlertCondition::Down {
+                consecutive: config.consecutive,
+            }
+        }
+        LATENCY_ALERT_TYPE => AlertCondition::Latency {
+            threshold_ms: config
+                .threshold_ms
+                .filter(|ms| *ms > 0)
+                .ok_or_else(|| Error::validation("Latency alerts need a positive threshold_ms"))?,
+            consecutive: config.consecutive,
+        },
+        EXPIRY_ALERT_TYPE => AlertCondition::Expiry {
+            threshold_days: config
+                .threshold_days
+                .filter(|days| *days > 0)
+                .ok_or_else(|| Error::validation("Expiry alerts need a positive threshold_days"))?,
+        },
+        other => return Err(Error::validation(format!("Unknown alert type: {}", other))),
+    };
+    for channel in &channels {
+        match channel {
+            ChannelConfig::Webhook { url } if !(url.starts_with("http://") || url.starts_with("https://")) => {
+                return Err(Error::validation("Webhook channels need an http or https url"));
+            }
+            ChannelConfig::Email { to } if to.is_empty() || !to.iter().all(|address| is_email_address(address)) => {
+                return Err(Error::validation("Email channels need at least one valid address"));
+            }
+            ChannelConfig::Slack { webhook_url, .. } | ChannelConfig::Discord { webhook_url, .. }
+                if !webhook_url.starts_with("https://") =>
+            {
+                return Err(Error::validation("Slack and Discord channels need an https webhook_url"));
+            }
+            ChannelConfig::Telegram { bot_token, chat_id }
+                if bot_token.trim().is_empty() || chat_id.trim().is_empty() =>
+            {
+                return Err(Error::validation("Telegram channels need a bot_token and chat_id"));
+            }
+            ChannelConfig::PagerDuty { routing_key, .. } if routing_key.trim().is_empty() => {
+                return Err(Error::validation("PagerDuty channels need a routing_key"));
+            }
+            ChannelConfig::PagerDuty { severity: Some(severity), .. }
+                if !pagerduty::SEVERITIES.contains(&severity.as_str()) =>
+            {
+                return Err(Error::validation(format!(
+                    "PagerDuty severity must be one of {}",
+                    pagerduty::SEVERITIES.join(", ")
+                )));
+            }
+            ChannelConfig::Slack { template: Some(template), .. } | ChannelConfig::Discord { template: Some(template), .. }
+                if template.trim().is_empty() =>
+            {
+                return Err(Error::validation("template must not be empty"));
+            }
+            _ => {}
+        }
+    }
+
+    Ok((condition, channels))
+}
+
+impl AlertCondition {
+    /// How many of the latest results `evaluate` looks at.
+    pub fn results_needed(&self) -> usize {
+        match self {
+            AlertCondition::Down { consecutive } | AlertCondition::Latency { consecutive, .. } => *consecutive,
+            AlertCondition::Expiry { .. } => 0,
+        }
+    }
+
+    /// `Some(true)` when the condition holds, `Some(false)` when it clearly
+    /// does not, and `None` when the results are not conclusive yet, e.g.
+    /// fewer failures in a row than required. `recent` is newest first.
+    pub fn evaluate(&self, recent: &[MonitorResult], days_remaining: Option<i64>) -> Option<bool> {
+        match *self {
+            AlertCondition::Down { consecutive } => {
+                let latest = recent.first()?;
+                if latest.status == "success" {
+                    return Some(false);
+                }
+                let failing = recent.iter().take_while(|r| r.status != "success").count();
+                (failing >= consecutive).then_some(true)
+            }
+            AlertCondition::Latency { threshold_ms, consecutive } => {
+                let slow = |r: &MonitorResult| r.status == "success" && r.response_time > threshold_ms;
+                let latest = recent.first()?;
+                if latest.status == "success" && !slow(latest) {
+                    return Some(false);
+                }
+                (recent.iter().take_while(|r| slow(r)).count() >= consecutive).then_some(true)
+            }
+            AlertCondition::Expiry { threshold_days } => days_remaining.map(|days| days < threshold_days),
+        }
+    }
+
+    /// One line describing why the alert fired.
+    pub fn describe(&self, latest: Option<&MonitorResult>, days_remaining: Option<i64>) -> String {
+        match *self {
+            AlertCondition::Down { consecutive } => {
+                let error = latest.and_then(|r| r.error_message.as_deref()).unwrap_or("check failed");
+                format!("Down for {} consecutive checks: {}", consecutive, error)
+            }
+            AlertCondition::Latency { threshold_ms, consecutive } => format!(
+                "Responded in {}ms, above {}ms for {} consecutive checks",
+                latest.map(|r| r.response_time).unwrap_or_default(),
+                threshold_ms,
+                consecutive
+            ),
+            AlertCondition::Expiry { .. } => format!(
+                "Certificate or domain expires in {} days",
+                days_remaining.unwrap_or_default()
+            ),
+        }
+    }
+}
+
+impl AlertNotification {
+    pub fn new(
+        rule: &AlertRule,
+        monitor: &Monitor,
+        state: AlertState,
+        summary: String,
+        latest: Option<&MonitorResult>,
+        at: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            alert_id: rule.id,
+            alert_type: rule.alert_type.clone(),
+            state,
+            monitor_id: monitor.id,
+            monitor_name: monitor.name.clone(),
+            endpoint: monitor.endpoint.clone(),
+            summary,
+            status: latest.map(|r| r.status.clone()),
+            response_time: latest.map(|r| r.response_time),
+            error_message: latest.and_then(|r| r.error_message.clone()),
+            at,
+        }
+    }
+
+    pub fn subject(&self) -> String {
+        match self.state {
+            AlertState::Firing => format!("[FIRING] {}: {}", self.monitor_name, self.alert_type),
+            AlertState::Resolved => format!("[RESOLVED] {}: {}", self.monitor_name, self.alert_type),
+        }
+    }
+
+    pub fn text(&self) -> String {
+        format!(
+            "{}\n\nMonitor: {}\nEndpoint: {}\nAt: {}\n",
+            self.summary,
+            self.monitor_name,
+            self.endpoint,
+            self.at.to_rfc3339()
+        )
+    }
+}
+
+/// Fills `{subject}`, `{summary}`, `{monitor}`, `{endpoint}`, `{alert_type}`,
+/// `{state}`, `{status}`, `{response_time}`, `{error}` and `{at}`.
+/// Placeholders without a value, such as `{error}` after a success, are empty.
+pub fn render(template: &str, notification: &AlertNotification) -> String {
+    let state = match notification.state {
+        AlertState::Firing => "firing",
+        AlertState::Resolved => "resolved",
+    };
+    [
+        ("{subject}", notification.subject()),
+        ("{summary}", notification.summary.clone()),
+        ("{monitor}", notification.monitor_name.clone()),
+        ("{endpoint}", notification.endpoint.clone()),
+        ("{alert_type}", notification.alert_type.clone()),
+        ("{state}", state.to_string()),
+        ("{status}", notification.status.clone().unwrap_or_default()),
+        ("{response_time}", notification.response_time.map(|ms| ms.to_string()).unwrap_or_default()),
+        ("{error}", notification.error_message.clone().unwrap_or_default()),
+        ("{at}", notification.at.to_rfc3339()),
+    ]
+    .iter()
+    .fold(template.to_string(), |text, (placeholder, value)| text.replace(placeholder, value))
+}
+
+fn slack_mention(target: &str) -> String {
+    match target {
+        "here" | "channel" | "everyone" => format!("<!{}>", target),
+        group if group.starts_with('S') => format!("<!subteam^{}>", group),
+        user => format!("<@{}>", user),
+    }
+}
+
+fn discord_mention(target: &str) -> String {
+    match target {
+        "here" | "everyone" => format!("@{}", target),
+        _ => match target.strip_prefix('&') {
+            Some(role) => format!("<@&{}>", role),
+            None => format!("<@{}>", target),
+        },
+    }
+}
+
+fn with_mentions(mentions: Vec<String>, text: String) -> String {
+    if mentions.is_empty() {
+        text
+    } else {
+        format!("{} {}", mentions.join(" "), text)
+    }
+}
+
+/// Body of a Slack incoming webhook post.
+pub fn slack_payload(
+    channel: Option<&str>,
+    mentions: &[String],
+    template: Option<&str>,
+    notification: &AlertNotification,
+) -> serde_json::Value {
+    let text = render(template.unwrap_or(DEFAULT_CHAT_TEMPLATE), notification);
+    let text = with_mentions(mentions.iter().map(|m| slack_mention(m)).collect(), text);
+    let mut payload = serde_json::json!({ "text": text });
+    if let Some(channel) = channel {
+        payload["channel"] = channel.into();
+    }
+    payload
+}
+
+/// Body of a Discord webhook post, cut to Discord's length limit.
+pub fn discord_payload(mentions: &[String], template: Option<&str>, notification: &AlertNotification) -> serde_json::Value {
+    let text = render(template.unwrap_or(DEFAULT_CHAT_TEMPLATE), notification);
+    let content: String = with_mentions(mentions.iter().map(|m| discord_mention(m)).collect(), text)
+        .chars()
+        .take(DISCORD_MAX_CONTENT)
+        .collect();
+    serde_json::json!({ "content": content })
+}
+
+/// A bare `local@domain` address; anything that could add SMTP commands or
+/// headers is refused.
+fn is_email_address(address: &str) -> bool {
+    match address.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty()
+                && domain.contains('.')
+                && !address.chars().any(|c| c.is_whitespace() || c.is_control() || "<>,;:\"".contains(c))
+        }
+        None => false,
+    }
+}
+
+/// A plain text email ready for the SMTP `DATA` command: CRLF line endings,
+/// lines starting with a dot escaped, and the terminating `.` line.
+pub fn email_message(from: &str, to: &[String], notification: &AlertNotification) -> String {
+    let headers = [
+        format!("From: {}", from),
+        format!("To: {}", to.join(", ")),
+        format!("Subject: {}", notification.subject().replace(['\r', '\n'], " ")),
+        format!("Date: {}", notification.at.to_rfc2822()),
+        format!("Message-ID: <{}.{}@monitor>", notification.alert_id, notification.at.timestamp()),
+        "MIME-Version: 1.0".to_string(),
+        "Content-Type: text/plain; charset=utf-8".to_string(),
+    ];
+    let body = notification
+        .text()
+        .lines()
+        .map(|line| if line.starts_with('.') { format!(".{}", line) } else { line.to_string() })
+        .collect::<Vec<_>>();
+    format!("{}\r\n\r\n{}\r\n.\r\n", headers.join("\r\n"), body.join("\r\n"))
+}
+
+pub async fn create(db: &DatabasePool, monitor_id: Uuid, alert_type: &str, config: &serde_json::Value) -> Result<Alert> {
+    parse(alert_type, config)?;
+    let alert = sqlx::query_as::<_, Alert>(
+        r#"
+        INSERT INTO alerts (id, monitor_id, type_, config, enabled, created_at, updated_at)
+        VALUES ($1, $2, $3, $4, true, NOW(), NOW())
+        RETURNING *
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(monitor_id)
+    .bind(alert_type)
+    .bind(config)
+    .fetch_one(db)
+    .await?;
+    Ok(alert)
+}
+
+pub async fn get(db: &DatabasePool, id: Uuid) -> Result<Option<Alert>> {
+    let alert = sqlx::query_as::<_, Alert>("SELECT * FROM alerts WHERE id = $1")
+        .bind(id)
+        .fetch_optional(db)
+        .await?;
+    Ok(alert)
+}
+
+pub async fn list_for_monitor(db: &DatabasePool, monitor_id: Uuid) -> Result<Vec<Alert>> {
+    let alerts = sqlx::query_as::<_, Alert>("SELECT * FROM alerts WHERE monitor_id = $1 ORDER BY created_at")
+        .bind(monitor_id)
+        .fetch_all(db)
+        .await?;
+    Ok(alerts)
+}
+
+pub async fn delete(db: &DatabasePool, id: Uuid) -> Result<bool> {
+    let deleted = sqlx::query("DELETE FROM alerts WHERE id = $1").bind(id).execute(db).await?;
+    Ok(deleted.rows_affected() > 0)
+}
+
+/// Enabled rules of the given types for one monitor.
+pub async fn enabled_rules(db: &DatabasePool, monitor_id: Uuid, types: &[&str]) -> Result<Vec<Alert>> {
+    let types: Vec<String> = types.iter().map(|t| t.to_string()).collect();
+    let alerts = sqlx::query_as::<_, Alert>(
+        "SELECT * FROM alerts WHERE monitor_id = $1 AND enabled = true AND type_ = ANY($2)",
+    )
+    .bind(monitor_id)
+    .bind(&types)
+    .fetch_all(db)
+    .await?;
+    Ok(alerts)
+}
+
+/// `false` when the rule was already firing, e.g. marked by another worker,
+/// so only one of them notifies.
+pub async fn mark_firing(db: &DatabasePool, id: Uuid, at: DateTime<Utc>) -> Result<bool> {
+    let updated = sqlx::query("UPDATE alerts SET firing_since = $2 WHERE id = $1 AND firing_since IS NULL")
+        .bind(id)
+        .bind(at)
+        .execute(db)
+        .await?;
+    Ok(updated.rows_affected() > 0)
+}
+
+/// `false` when the rule was not firing.
+pub async fn mark_resolved(db: &DatabasePool, id: Uuid) -> Result<bool> {
+    let updated = sqlx::query("UPDATE alerts SET firing_since = NULL WHERE id = $1 AND firing_since IS NOT NULL")
+        .bind(id)
+        .execute(db)
+        .await?;
+    Ok(updated.rows_affected() > 0)
+}
@@ -0,0 +1,202 @@
+#[cfg(test)]
+mod alerts_tests {
+    use crate::{alerts::*, models::MonitorResult};
+    use chrono::{TimeZone, Utc};
+    use serde_json::json;
+    use uuid::Uuid;
+
+    fn result(status: &str, response_time: i32) -> MonitorResult {
+        MonitorResult {
+            id: Uuid::new_v4(),
+            monitor_id: Uuid::nil(),
+            status: status.to_string(),
+            response_time,
+            response_code: None,
+            response_body: None,
+            response_headers: None,
+            error_message: None,
+            error_category: None,
+            error_hint: None,
+            checked_at: Utc::now(),
+            clock_skew_ms: None,
+            schedule_lag_ms: None,
+            attempts: 1,
+        }
+    }
+
+    fn notification() -> AlertNotification {
+        AlertNotification {
+            alert_id: Uuid::nil(),
+            alert_type: DOWN_ALERT_TYPE.to_string(),
+            state: AlertState::Firing,
+            monitor_id: Uuid::nil(),
+            monitor_name: "Checkout".to_string(),
+            endpoint: "https://shop.example.com".to_string(),
+            summary: "Down for 3 consecutive checks: connection refused".to_string(),
+            status: Some("failure".to_string()),
+            response_time: Some(120),
+            error_message: Some("connection refused".to_string()),
+            at: Utc.with_ymd_and_hms(2024, 1, 30, 12, 0, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_parse_rules() {
+        let (condition, channels) = parse(
+            DOWN_ALERT_TYPE,
+            &json!({ "consecutive": 3, "channels": [{ "type": "email", "to": ["ops@example.com"] }] }),
+        )
+        .unwrap();
+        assert_eq!(condition, AlertCondition::Down { consecutive: 3 });
+        assert_eq!(channels, vec![ChannelConfig::Email { to: vec!["ops@example.com".to_string()] }]);
+
+        assert_eq!(parse(DOWN_ALERT_TYPE, &json!({})).unwrap().0, AlertCondition::Down { consecutive: 1 });
+        assert!(parse(LATENCY_ALERT_TYPE, &json!({})).is_err());
+        assert!(parse(DOWN_ALERT_TYPE, &json!({ "consecutive": 0 })).is_err());
+        assert!(parse("sms", &json!({})).is_err());
+        assert!(parse(DOWN_ALERT_TYPE, &json!({ "channels": [{ "type": "webhook", "url": "ftp://x" }] })).is_err());
+        assert!(parse(
+            DOWN_ALERT_TYPE,
+            &json!({ "channels": [{ "type": "email", "to": ["ops@example.com\r\nRCPT TO:<x@y.z>"] }] })
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_down_needs_consecutive_failures() {
+        let down = AlertCondition::Down { consecutive: 2 };
+        assert_eq!(down.evaluate(&[result("failure", 0)], None), None);
+        assert_eq!(down.evaluate(&[result("timeout", 0), result("failure", 0)], None), Some(true));
+        assert_eq!(down.evaluate(&[result("error", 0), result("success", 10)], None), None);
+        assert_eq!(down.evaluate(&[result("success", 10), result("failure", 0)], None), Some(false));
+        assert_eq!(down.evaluate(&[], None), None);
+    }
+
+    #[test]
+    fn test_latency_ignores_failures() {
+        let slow = AlertCondition::Latency {
+            threshold_ms: 500,
+            consecutive: 2,
+        };
+        assert_eq!(slow.evaluate(&[result("success", 900), result("success", 800)], None), Some(true));
+        assert_eq!(slow.evaluate(&[result("success", 900), result("success", 100)], None), None);
+        assert_eq!(slow.evaluate(&[result("success", 100), result("success", 900)], None), Some(false));
+        // A failed check says nothing about latency
+        assert_eq!(slow.evaluate(&[result("failure", 900), result("success", 900)], None), None);
+    }
+
+    #[test]
+    fn test_expiry_uses_days_remaining() {
+        let expiry = AlertCondition::Expiry { threshold_days: 14 };
+        assert_eq!(expiry.evaluate(&[], Some(3)), Some(true));
+        assert_eq!(expiry.evaluate(&[], Some(30)), Some(false));
+        assert_eq!(expiry.evaluate(&[], None), None);
+    }
+
+    #[test]
+    fn test_email_message() {
+        let mut notification = notification();
+        notification.summary = ".hidden line".to_string();
+        let message = email_message("monitor@example.com", &["ops@example.com".to_string()], &notification);
+
+        assert!(message.starts_with("From: monitor@example.com\r\nTo: ops@example.com\r\nSubject: [FIRING] Checkout: down\r\n"));
+        assert!(message.contains("\r\n\r\n..hidden line\r\n"));
+        assert!(message.ends_with("\r\n.\r\n"));
+        assert!(!message.replace("\r\n", "").contains('\n'));
+    }
+
+    #[test]
+    fn test_chat_channels_render_templates_and_mentions() {
+        let notification = notification();
+        assert_eq!(
+            render("{monitor} is {status} after {response_time}ms: {error} ({state})", &notification),
+            "Checkout is failure after 120ms: connection refused (firing)"
+        );
+
+        let slack = slack_payload(
+            Some("#ops"),
+            &["here".to_string(), "U123".to_string(), "S456".to_string()],
+            None,
+            &notification,
+        );
+        assert_eq!(slack["channel"], "#ops");
+        assert_eq!(
+            slack["text"],
+            "<!here> <@U123> <!subteam^S456> [FIRING] Checkout: down\n\
+             Down for 3 consecutive checks: connection refused\nhttps://shop.example.com"
+        );
+
+        let discord = discord_payload(&["&789".to_string(), "42".to_string()], Some("{monitor}: {summary}"), &notification);
+        assert_eq!(
+            discord["content"],
+            "<@&789> <@42> Checkout: Down for 3 consecutive checks: connection refused"
+        );
+
+        let (_, channels) = parse(
+            DOWN_ALERT_TYPE,
+            &json!({ "channels": [{ "type": "discord", "webhook_url": "https://discord.com/api/webhooks/1/x" }] }),
+        )
+        .unwrap();
+        assert!(matches!(&channels[0], ChannelConfig::Discord { mentions, template: None, .. } if mentions.is_empty()));
+        assert!(parse(DOWN_ALERT_TYPE, &json!({ "channels": [{ "type": "slack", "webhook_url": "http://x" }] })).is_err());
+    }
+
+    #[test]
+    fn test_pagerduty_events_share_a_dedup_key() {
+        use crate::pagerduty::{self, EventAction};
+
+        let notification = notification();
+        let trigger = pagerduty::event("key", EventAction::Trigger, "warning", &notification);
+        assert_eq!(trigger["event_action"], "trigger");
+        assert_eq!(trigger["dedup_key"], format!("monitor/{}/down", Uuid::nil()));
+        assert_eq!(trigger["payload"]["severity"], "warning");
+        assert_eq!(trigger["payload"]["source"], "https://shop.example.com");
+        assert_eq!(trigger["payload"]["custom_details"]["response_time"], 120);
+
+        let resolve = pagerduty::event("key", EventAction::Resolve, "warning", &notification);
+        assert_eq!(resolve["event_action"], "resolve");
+        assert_eq!(resolve["dedup_key"], trigger["dedup_key"]);
+        assert!(resolve.get("payload").is_none());
+
+        assert!(parse(
+            DOWN_ALERT_TYPE,
+            &json!({ "channels": [{ "type": "pagerduty", "routing_key": "abc", "severity": "info" }] })
+        )
+        .is_ok());
+        assert!(parse(
+            DOWN_ALERT_TYPE,
+            &json!({ "channels": [{ "type": "pagerduty", "routing_key": "abc", "severity": "urgent" }] })
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_telegram_alerts_escape_markdown() {
+        use crate::telegram;
+
+        let (condition, channels) = parse(
+            TELEGRAM_ALERT_TYPE,
+            &json!({ "consecutive": 2, "bot_token": "123:abc", "chat_id": "-100200" }),
+        )
+        .unwrap();
+        assert_eq!(condition, AlertCondition::Down { consecutive: 2 });
+        assert_eq!(
+            channels,
+            vec![ChannelConfig::Telegram {
+                bot_token: "123:abc".to_string(),
+                chat_id: "-100200".to_string()
+            }]
+        );
+        assert!(parse(TELEGRAM_ALERT_TYPE, &json!({ "chat_id": "1" })).is_err());
+
+        assert_eq!(telegram::escape("a_b*c (1.5) [x] #!"), "a\\_b\\*c \\(1\\.5\\) \\[x\\] \\#\\!");
+        let message = telegram::message(&notification());
+        assert!(message.starts_with("🔴 *\\[FIRING\\] Checkout: down*\n"));
+        assert!(message.contains("https://shop\\.example\\.com"));
+        assert!(message.contains("Response time: 120 ms"));
+        assert!(message.ends_with("Error: _connection refused_"));
+
+        assert_eq!(telegram::retry_after(&json!({ "ok": false, "parameters": { "retry_after": 7 } })), Some(7));
+        assert_eq!(telegram::retry_after(&json!({ "ok": false })), None);
+    }
+}
@@ -0,0 +1,113 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use crate::{db::DatabasePool, error::Result, Error};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+pub enum ChangeRequestStatus {
+    Pending,
+    Approved,
+    Rejected,
+}
+
+/// A monitor edit held back until another user approves it.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct ChangeRequest {
+    pub id: Uuid,
+    pub monitor_id: Uuid,
+    pub requested_by: Option<Uuid>,
+    /// The `UpdateMonitorRequest` without credentials
+    pub changes: serde_json::Value,
+    #[serde(skip_serializing)]
+    pub credentials: Option<String>,
+    pub status: ChangeRequestStatus,
+    pub reviewed_by: Option<Uuid>,
+    pub review_comment: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+}
+
+impl ChangeRequest {
+    /// Only pending requests can be decided, and never by the user who made them.
+    pub fn check_reviewer(&self, reviewer: Uuid) -> Result<()> {
+        if self.status != ChangeRequestStatus::Pending {
+            return Err(Error::validation(format!("Change request {} is no longer pending", self.id)));
+        }
+        if self.requested_by == Some(reviewer) {
+            return Err(Error::forbidden("Changes must be approved by a different user"));
+        }
+        Ok(())
+    }
+}
+
+pub async fn submit(
+    db: &DatabasePool,
+    monitor_id: Uuid,
+    requested_by: Uuid,
+    changes: &serde_json::Value,
+    credentials: Option<&str>,
+) -> Result<ChangeRequest> {
+    let request = sqlx::query_as::<_, ChangeRequest>(
+        r#"
+        INSERT INTO monitor_change_requests (id, monitor_id, requested_by, changes, credentials, status, created_at)
+        VALUES ($1, $2, $3, $4, $5, 'pending', NOW())
+        RETURNING *
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(monitor_id)
+    .bind(requested_by)
+    .bind(changes)
+    .bind(credentials)
+    .fetch_one(db)
+    .await?;
+    Ok(request)
+}
+
+/// Oldest first, optionally only requests in one status.
+pub async fn list(db: &DatabasePool, status: Option<ChangeRequestStatus>) -> Result<Vec<ChangeRequest>> {
+    let requests = sqlx::query_as::<_, ChangeRequest>(
+        "SELECT * FROM monitor_change_requests WHERE $1::text IS NULL OR status = $1 ORDER BY created_at",
+    )
+    .bind(status)
+    .fetch_all(db)
+    .await?;
+    Ok(requests)
+}
+
+pub async fn get(db: &DatabasePool, id: Uuid) -> Result<ChangeRequest> {
+    sqlx::query_as::<_, ChangeRequest>("SELECT * FROM monitor_change_requests WHERE id = $1")
+        .bind(id)
+        .fetch_optional(db)
+        .await?
+        .ok_or_else(|| Error::not_found(format!("Change request {} not found", id)))
+}
+
+/// Records the decision; fails if another reviewer decided first. Pass an open
+/// transaction as `db` to claim the request before acting on it.
+pub async fn decide<'e>(
+    db: impl sqlx::PgExecutor<'e>,
+    id: Uuid,
+    reviewer: Uuid,
+    status: ChangeRequestStatus,
+    comment: Option<&str>,
+) -> Result<ChangeRequest> {
+    sqlx::query_as::<_, ChangeRequest>(
+        r#"
+        UPDATE monitor_change_requests
+        SET status = $3, reviewed_by = $2, review_comment = $4, reviewed_at = NOW()
+        WHERE id = $1 AND status = 'pending'
+        RETURNING *
+        "#,
+    )
+    .bind(id)
+    .bind(reviewer)
+    .bind(status)
+    .bind(comment)
+    .fetch_optional(db)
+    .await?
+    .ok_or_else(|| Error::validation(format!("Change request {} is no longer pending", id)))
+}
@@ -0,0 +1,44 @@
+#[cfg(test)]
+mod approvals_tests {
+    use crate::approvals::*;
+    use chrono::Utc;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    fn request(requested_by: Uuid, status: ChangeRequestStatus) -> ChangeRequest {
+        ChangeRequest {
+            id: Uuid::new_v4(),
+            monitor_id: Uuid::new_v4(),
+            requested_by: Some(requested_by),
+            changes: json!({ "interval": 30 }),
+            credentials: None,
+            status,
+            reviewed_by: None,
+            review_comment: None,
+            created_at: Utc::now(),
+            reviewed_at: None,
+        }
+    }
+
+    #[test]
+    fn test_second_user_must_review() {
+        let author = Uuid::new_v4();
+        let pending = request(author, ChangeRequestStatus::Pending);
+
+        assert!(pending.check_reviewer(author).is_err());
+        assert!(pending.check_reviewer(Uuid::new_v4()).is_ok());
+    }
+
+    #[test]
+    fn test_decided_requests_cannot_be_reviewed() {
+        let approved = request(Uuid::new_v4(), ChangeRequestStatus::Approved);
+        assert!(approved.check_reviewer(Uuid::new_v4()).is_err());
+    }
+
+    #[test]
+    fn test_status_serialization() {
+        assert_eq!(serde_json::to_value(ChangeRequestStatus::Rejected).unwrap(), json!("rejected"));
+        let status: ChangeRequestStatus = serde_json::from_value(json!("pending")).unwrap();
+        assert_eq!(status, ChangeRequestStatus::Pending);
+    }
+}
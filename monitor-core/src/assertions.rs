@@ -0,0 +1,117 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use crate::models::MonitorResult;
+
+/// Failures listed in a replay report; the counts still cover every result
+const MAX_REPORTED_FAILURES: usize = 50;
+
+/// Declarative check on a stored response.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Assertion {
+    Status { equals: i32 },
+    StatusRange { min: i32, max: i32 },
+    BodyContains { value: String },
+    BodyNotContains { value: String },
+    /// Compares the value at a JSON pointer such as `/data/status`
+    JsonPointer { pointer: String, equals: serde_json::Value },
+    ResponseTimeBelow { ms: i32 },
+}
+
+impl Assertion {
+    /// `Err` carries a short reason for the report.
+    pub fn evaluate(&self, result: &MonitorResult) -> Result<(), String> {
+        let body = result.response_body.as_deref().unwrap_or_default();
+        match self {
+            Assertion::Status { equals } => match result.response_code {
+                Some(code) if code == *equals => Ok(()),
+                code => Err(format!("expected status {}, got {}", equals, describe_code(code))),
+            },
+            Assertion::StatusRange { min, max } => match result.response_code {
+                Some(code) if (*min..=*max).contains(&code) => Ok(()),
+                code => Err(format!("expected status {}-{}, got {}", min, max, describe_code(code))),
+            },
+            Assertion::BodyContains { value } if body.contains(value.as_str()) => Ok(()),
+            Assertion::BodyContains { value } => Err(format!("body does not contain '{}'", value)),
+            Assertion::BodyNotContains { value } if body.contains(value.as_str()) => {
+                Err(format!("body contains '{}'", value))
+            }
+            Assertion::BodyNotContains { .. } => Ok(()),
+            Assertion::JsonPointer { pointer, equals } => {
+                let json: serde_json::Value =
+                    serde_json::from_str(body).map_err(|_| "body is not valid JSON".to_string())?;
+                match json.pointer(pointer) {
+                    Some(actual) if actual == equals => Ok(()),
+                    Some(actual) => Err(format!("{} is {}, expected {}", pointer, actual, equals)),
+                    None => Err(format!("{} is missing", pointer)),
+                }
+            }
+            Assertion::ResponseTimeBelow { ms } if result.response_time < *ms => Ok(()),
+            Assertion::ResponseTimeBelow { ms } => {
+                Err(format!("response time {}ms is not below {}ms", result.response_time, ms))
+            }
+        }
+    }
+}
+
+fn describe_code(code: Option<i32>) -> String {
+    code.map(|c| c.to_string()).unwrap_or_else(|| "no response".to_string())
+}
+
+/// Runs every assertion, stopping at the first failure.
+pub fn evaluate_all(assertions: &[Assertion], result: &MonitorResult) -> Result<(), String> {
+    assertions.iter().try_for_each(|a| a.evaluate(result))
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayFailure {
+    pub result_id: Uuid,
+    pub checked_at: DateTime<Utc>,
+    pub original_status: String,
+    pub reason: String,
+}
+
+/// How stored results fare against new rules compared with how they were recorded.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReplayReport {
+    pub evaluated: usize,
+    pub passed: usize,
+    pub failed: usize,
+    /// Results without a stored body, which cannot be re-evaluated
+    pub skipped: i64,
+    /// Originally successful checks that would now fail
+    pub newly_failing: usize,
+    /// Originally failed checks that would now pass
+    pub newly_passing: usize,
+    pub failures: Vec<ReplayFailure>,
+}
+
+impl ReplayReport {
+    pub fn record(&mut self, result: &MonitorResult, outcome: Result<(), String>) {
+        self.evaluated += 1;
+        let was_success = result.status == "success";
+        match outcome {
+            Ok(()) => {
+                self.passed += 1;
+                if !was_success {
+                    self.newly_passing += 1;
+                }
+            }
+            Err(reason) => {
+                self.failed += 1;
+                if was_success {
+                    self.newly_failing += 1;
+                }
+                if self.failures.len() < MAX_REPORTED_FAILURES {
+                    self.failures.push(ReplayFailure {
+                        result_id: result.id,
+                        checked_at: result.checked_at,
+                        original_status: result.status.clone(),
+                        reason,
+                    });
+                }
+            }
+        }
+    }
+}
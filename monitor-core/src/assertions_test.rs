@@ -0,0 +1,82 @@
+#[cfg(test)]
+mod assertions_tests {
+    use crate::{assertions::*, models::MonitorResult};
+    use chrono::Utc;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    fn result(status: &str, code: Option<i32>, body: &str, response_time: i32) -> MonitorResult {
+        MonitorResult {
+            id: Uuid::new_v4(),
+            monitor_id: Uuid::new_v4(),
+            status: status.to_string(),
+            response_time,
+            response_code: code,
+            response_body: Some(body.to_string()),
+            response_headers: None,
+            error_message: None,
+            error_category: None,
+            error_hint: None,
+            checked_at: Utc::now(),
+            clock_skew_ms: None,
+            schedule_lag_ms: None,
+            attempts: 1,
+        }
+    }
+
+    #[test]
+    fn test_assertions_parse_from_json() {
+        let assertions: Vec<Assertion> = serde_json::from_value(json!([
+            { "type": "status_range", "min": 200, "max": 299 },
+            { "type": "json_pointer", "pointer": "/status", "equals": "ok" },
+        ]))
+        .unwrap();
+        assert_eq!(assertions[0], Assertion::StatusRange { min: 200, max: 299 });
+    }
+
+    #[test]
+    fn test_evaluate_assertions() {
+        let ok = result("success", Some(200), r#"{"status":"ok","items":3}"#, 120);
+
+        assert!(Assertion::Status { equals: 200 }.evaluate(&ok).is_ok());
+        assert!(Assertion::StatusRange { min: 500, max: 599 }.evaluate(&ok).is_err());
+        assert!(Assertion::BodyContains { value: "items".to_string() }.evaluate(&ok).is_ok());
+        assert!(Assertion::BodyNotContains { value: "error".to_string() }.evaluate(&ok).is_ok());
+        assert!(Assertion::ResponseTimeBelow { ms: 100 }.evaluate(&ok).is_err());
+
+        let pointer = Assertion::JsonPointer { pointer: "/items".to_string(), equals: json!(3) };
+        assert!(pointer.evaluate(&ok).is_ok());
+        let missing = Assertion::JsonPointer { pointer: "/missing".to_string(), equals: json!(3) };
+        assert_eq!(missing.evaluate(&ok).unwrap_err(), "/missing is missing");
+        let not_json = result("success", Some(200), "<html>", 10);
+        assert!(pointer.evaluate(&not_json).is_err());
+    }
+
+    #[test]
+    fn test_evaluate_all_reports_first_failure() {
+        let failed = result("failure", None, "", 5000);
+        let assertions = vec![
+            Assertion::Status { equals: 200 },
+            Assertion::ResponseTimeBelow { ms: 1000 },
+        ];
+        assert_eq!(
+            evaluate_all(&assertions, &failed).unwrap_err(),
+            "expected status 200, got no response"
+        );
+    }
+
+    #[test]
+    fn test_replay_report_counts_changed_outcomes() {
+        let mut report = ReplayReport::default();
+        report.record(&result("success", Some(200), "", 10), Ok(()));
+        report.record(&result("success", Some(200), "", 10), Err("nope".to_string()));
+        report.record(&result("failure", Some(500), "", 10), Ok(()));
+
+        assert_eq!(report.evaluated, 3);
+        assert_eq!(report.passed, 2);
+        assert_eq!(report.failed, 1);
+        assert_eq!(report.newly_failing, 1);
+        assert_eq!(report.newly_passing, 1);
+        assert_eq!(report.failures.len(), 1);
+    }
+}
@@ -0,0 +1,65 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::FromRow;
+use uuid::Uuid;
+use crate::{db::DatabasePool, error::Result};
+
+pub const ACTION_MONITOR_CREATED: &str = "monitor.created";
+pub const ACTION_MONITOR_PAUSED: &str = "monitor.paused";
+pub const ACTION_MONITOR_RESUMED: &str = "monitor.resumed";
+pub const ACTION_MONITOR_OWNERSHIP_CHANGED: &str = "monitor.ownership_changed";
+pub const ACTION_MONITOR_UPDATED: &str = "monitor.updated";
+pub const ACTION_CHANGE_REQUESTED: &str = "monitor.change_requested";
+pub const ACTION_CHANGE_APPROVED: &str = "monitor.change_approved";
+pub const ACTION_CHANGE_REJECTED: &str = "monitor.change_rejected";
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct AuditEntry {
+    pub id: Uuid,
+    /// `None` when the scheduler took the action
+    pub actor_id: Option<Uuid>,
+    pub actor: Option<String>,
+    pub action: String,
+    pub monitor_id: Option<Uuid>,
+    pub details: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+}
+
+pub async fn record(
+    db: &DatabasePool,
+    actor_id: Option<Uuid>,
+    action: &str,
+    monitor_id: Option<Uuid>,
+    details: serde_json::Value,
+) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO audit_log (id, actor_id, action, monitor_id, details, created_at) VALUES ($1, $2, $3, $4, $5, NOW())",
+    )
+    .bind(Uuid::new_v4())
+    .bind(actor_id)
+    .bind(action)
+    .bind(monitor_id)
+    .bind(details)
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/// Newest entries first, optionally limited to one monitor.
+pub async fn list(db: &DatabasePool, monitor_id: Option<Uuid>, limit: i64) -> Result<Vec<AuditEntry>> {
+    let entries = sqlx::query_as::<_, AuditEntry>(
+        r#"
+        SELECT a.id, a.actor_id, u.username AS actor, a.action, a.monitor_id, a.details, a.created_at
+        FROM audit_log a
+        LEFT JOIN users u ON u.id = a.actor_id
+        WHERE $1::uuid IS NULL OR a.monitor_id = $1
+        ORDER BY a.created_at DESC
+        LIMIT $2
+        "#,
+    )
+    .bind(monitor_id)
+    .bind(limit)
+    .fetch_all(db)
+    .await?;
+    Ok(entries)
+}
@@ -4,8 +4,12 @@ use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation}
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use chrono::{Utc, Duration};
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
 use crate::{error::Result, Error};
 
+pub const PERSONAL_TOKEN_PREFIX: &str = "mpat_";
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Claims {
     pub sub: String,
@@ -29,6 +33,11 @@ impl AuthService {
         }
     }
 
+    /// Seconds a token from `generate_token` stays valid.
+    pub fn token_lifetime(&self) -> i64 {
+        self.jwt_expiration
+    }
+
     pub fn hash_password(&self, password: &str) -> Result<String> {
         let salt = SaltString::generate(&mut OsRng);
         let argon2 = Argon2::default();
@@ -79,4 +88,37 @@ impl AuthService {
 
         Ok(token_data.claims)
     }
-}
\ No newline at end of file
+
+    /// Returns the plaintext token (shown to the user once) and the hash that gets stored.
+    pub fn generate_personal_token(&self) -> (String, String) {
+        let token = format!(
+            "{}{}{}",
+            PERSONAL_TOKEN_PREFIX,
+            Uuid::new_v4().simple(),
+            Uuid::new_v4().simple()
+        );
+        let hash = hash_token(&token);
+        (token, hash)
+    }
+}
+
+pub fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+/// Checks the bearer token of a provisioning request against `auth.provisioning_token`.
+/// An unset, empty or blank configured token disables provisioning; the comparison
+/// runs in constant time so the token cannot be guessed byte by byte.
+pub fn verify_provisioning_token(configured: Option<&str>, provided: Option<&str>) -> Result<()> {
+    let expected = configured
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+        .ok_or_else(|| Error::auth("User provisioning is not enabled"))?;
+    let provided = provided.ok_or_else(|| Error::auth("Missing provisioning token"))?;
+
+    if bool::from(provided.as_bytes().ct_eq(expected.as_bytes())) {
+        Ok(())
+    } else {
+        Err(Error::auth("Invalid provisioning token"))
+    }
+}
@@ -0,0 +1,38 @@
+#[cfg(test)]
+mod auth_tests {
+    use crate::{Error, auth::*};
+
+    fn message(result: crate::Result<()>) -> String {
+        match result {
+            Err(Error::Auth(message)) => message,
+            other => panic!("expected an auth error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_provisioning_token_must_match() {
+        assert!(verify_provisioning_token(Some("s3cret"), Some("s3cret")).is_ok());
+        assert_eq!(message(verify_provisioning_token(Some("s3cret"), Some("s3cre"))), "Invalid provisioning token");
+        assert_eq!(message(verify_provisioning_token(Some("s3cret"), Some("S3CRET"))), "Invalid provisioning token");
+        assert_eq!(message(verify_provisioning_token(Some("s3cret"), Some(""))), "Invalid provisioning token");
+    }
+
+    #[test]
+    fn test_missing_provisioning_token_is_rejected() {
+        assert_eq!(message(verify_provisioning_token(Some("s3cret"), None)), "Missing provisioning token");
+    }
+
+    #[test]
+    fn test_empty_configured_token_disables_provisioning() {
+        for configured in [None, Some(""), Some("   ")] {
+            assert_eq!(
+                message(verify_provisioning_token(configured, Some(""))),
+                "User provisioning is not enabled"
+            );
+            assert_eq!(
+                message(verify_provisioning_token(configured, Some("   "))),
+                "User provisioning is not enabled"
+            );
+        }
+    }
+}
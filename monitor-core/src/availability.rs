@@ -0,0 +1,190 @@
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use serde::Serialize;
+use sqlx::Row;
+use std::{collections::BTreeMap, fmt::Write};
+use uuid::Uuid;
+use crate::{db::DatabasePool, error::Result, rollups, Error};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DowntimeEvent {
+    pub start: DateTime<Utc>,
+    /// Time of the first successful check afterwards, or the last failed one if still down
+    pub end: DateTime<Utc>,
+    pub failed_checks: i64,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DayAvailability {
+    pub date: NaiveDate,
+    pub checks: i64,
+    pub availability: Option<f64>,
+    pub downtime: Vec<DowntimeEvent>,
+}
+
+/// Parses `YYYY-MM` into the first day of that month.
+pub fn parse_month(month: &str) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(&format!("{}-01", month), "%Y-%m-%d")
+        .map_err(|_| Error::validation(format!("Invalid month '{}', expected YYYY-MM", month)))
+}
+
+fn next_month(first: NaiveDate) -> NaiveDate {
+    let (year, month) = if first.month() == 12 {
+        (first.year() + 1, 1)
+    } else {
+        (first.year(), first.month() + 1)
+    };
+    NaiveDate::from_ymd_opt(year, month, 1).unwrap_or(first)
+}
+
+fn day_start(date: NaiveDate) -> DateTime<Utc> {
+    date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc()
+}
+
+/// Consecutive runs of non-successful checks in `[from, to)`.
+pub async fn downtime_events(
+    db: &DatabasePool,
+    monitor_id: Uuid,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<DowntimeEvent>> {
+    let rows = sqlx::query(
+        r#"
+        SELECT status, error_message, checked_at FROM monitor_results
+        WHERE monitor_id = $1 AND checked_at >= $2 AND checked_at < $3
+        ORDER BY checked_at
+        "#,
+    )
+    .bind(monitor_id)
+    .bind(from)
+    .bind(to)
+    .fetch_all(db)
+    .await?;
+
+    let mut events = Vec::new();
+    let mut current: Option<DowntimeEvent> = None;
+    for row in rows {
+        let status: String = row.get("status");
+        let checked_at: DateTime<Utc> = row.get("checked_at");
+
+        if status == "success" {
+            if let Some(mut event) = current.take() {
+                event.end = checked_at;
+                events.push(event);
+            }
+            continue;
+        }
+
+        let event = current.get_or_insert(DowntimeEvent {
+            start: checked_at,
+            end: checked_at,
+            failed_checks: 0,
+            last_error: None,
+        });
+        event.end = checked_at;
+        event.failed_checks += 1;
+        event.last_error = row.get::<Option<String>, _>("error_message").or(Some(status));
+    }
+    events.extend(current);
+
+    Ok(events)
+}
+
+/// Daily availability for every day of the month starting at `first`, with the
+/// downtime events that began on each day.
+pub async fn month_availability(
+    db: &DatabasePool,
+    monitor_id: Uuid,
+    first: NaiveDate,
+) -> Result<Vec<DayAvailability>> {
+    let from = day_start(first);
+    let to = day_start(next_month(first));
+
+    let mut days: BTreeMap<NaiveDate, (i64, i64)> = BTreeMap::new();
+    for (hour, aggregate) in rollups::hourly_aggregates(db, monitor_id, from, to).await? {
+        let day = days.entry(hour.date_naive()).or_default();
+        day.0 += aggregate.count;
+        day.1 += aggregate.success_count;
+    }
+
+    let mut downtime: BTreeMap<NaiveDate, Vec<DowntimeEvent>> = BTreeMap::new();
+    for event in downtime_events(db, monitor_id, from, to).await? {
+        downtime.entry(event.start.date_naive()).or_default().push(event);
+    }
+
+    Ok(first
+        .iter_days()
+        .take_while(|date| *date < next_month(first))
+        .map(|date| {
+            let (checks, successes) = days.get(&date).copied().unwrap_or_default();
+            DayAvailability {
+                date,
+                checks,
+                availability: (checks > 0).then(|| successes as f64 / checks as f64),
+                downtime: downtime.remove(&date).unwrap_or_default(),
+            }
+        })
+        .collect())
+}
+
+/// Arranges days into Monday-first weeks, padding with `None` outside the month.
+pub fn month_grid(days: Vec<DayAvailability>) -> Vec<Vec<Option<DayAvailability>>> {
+    let Some(first) = days.first() else {
+        return Vec::new();
+    };
+
+    let mut cells: Vec<Option<DayAvailability>> = (0..first.date.weekday().num_days_from_monday())
+        .map(|_| None)
+        .collect();
+    cells.extend(days.into_iter().map(Some));
+    while !cells.len().is_multiple_of(7) {
+        cells.push(None);
+    }
+
+    cells.chunks(7).map(|week| week.to_vec()).collect()
+}
+
+fn ical_time(at: DateTime<Utc>) -> String {
+    at.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+fn ical_escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Renders downtime events as an iCalendar (RFC 5545) feed, stamped `now`.
+pub fn to_ical(monitor_id: Uuid, monitor_name: &str, events: &[DowntimeEvent], now: DateTime<Utc>) -> String {
+    let mut out = String::new();
+    let _ = write!(
+        out,
+        "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nPRODID:-//monitor//availability//EN\r\nX-WR-CALNAME:{}\r\n",
+        ical_escape(&format!("{} downtime", monitor_name))
+    );
+
+    let now = ical_time(now);
+    for event in events {
+        // Zero-length events do not render in most calendar clients
+        let end = event.end.max(event.start + Duration::minutes(1));
+        let _ = write!(
+            out,
+            "BEGIN:VEVENT\r\nUID:{}-{}@monitor\r\nDTSTAMP:{}\r\nDTSTART:{}\r\nDTEND:{}\r\nSUMMARY:{}\r\nDESCRIPTION:{}\r\nEND:VEVENT\r\n",
+            monitor_id,
+            event.start.timestamp(),
+            now,
+            ical_time(event.start),
+            ical_time(end),
+            ical_escape(&format!("{} down", monitor_name)),
+            ical_escape(&format!(
+                "{} failed checks. Last error: {}",
+                event.failed_checks,
+                event.last_error.as_deref().unwrap_or("unknown")
+            )),
+        );
+    }
+
+    out.push_str("END:VCALENDAR\r\n");
+    out
+}
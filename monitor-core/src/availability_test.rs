@@ -0,0 +1,52 @@
+#[cfg(test)]
+mod availability_tests {
+    use crate::availability::*;
+    use chrono::{DateTime, NaiveDate};
+    use uuid::Uuid;
+
+    fn day(date: NaiveDate) -> DayAvailability {
+        DayAvailability {
+            date,
+            checks: 0,
+            availability: None,
+            downtime: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_month_grid_starts_on_monday() {
+        // May 2024 starts on a Wednesday and has 31 days
+        let first = parse_month("2024-05").unwrap();
+        let days: Vec<_> = first.iter_days().take(31).map(day).collect();
+
+        let grid = month_grid(days);
+        assert_eq!(grid.len(), 5);
+        assert!(grid.iter().all(|week| week.len() == 7));
+        assert!(grid[0][0].is_none() && grid[0][1].is_none());
+        assert_eq!(grid[0][2].as_ref().unwrap().date, first);
+        assert_eq!(grid[4][4].as_ref().unwrap().date, NaiveDate::from_ymd_opt(2024, 5, 31).unwrap());
+        assert!(grid[4][5].is_none());
+
+        assert!(parse_month("2024-13").is_err());
+    }
+
+    #[test]
+    fn test_ical_export() {
+        let event = DowntimeEvent {
+            start: DateTime::from_timestamp(1_714_564_800, 0).unwrap(),
+            end: DateTime::from_timestamp(1_714_565_400, 0).unwrap(),
+            failed_checks: 3,
+            last_error: Some("connection refused, retrying".to_string()),
+        };
+
+        let exported_at = DateTime::from_timestamp(1_714_568_400, 0).unwrap();
+        let ics = to_ical(Uuid::nil(), "API; EU", &[event], exported_at);
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(ics.contains("DTSTAMP:20240501T130000Z\r\n"));
+        assert!(ics.contains("DTSTART:20240501T120000Z\r\n"));
+        assert!(ics.contains("DTEND:20240501T121000Z\r\n"));
+        assert!(ics.contains("SUMMARY:API\\; EU down\r\n"));
+        assert!(ics.contains("connection refused\\, retrying"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+    }
+}
@@ -0,0 +1,494 @@
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use reqwest::Client;
+use roxmltree::{Document, Node};
+use sha2::{Digest, Sha256};
+use std::time::Duration;
+use crate::{config::AwsImportConfig, error::Result, imports::ImportCandidate, Error};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// One page of a listing and where the next one starts.
+pub type Page<T, M = String> = (Vec<T>, Option<M>);
+
+pub const ROUTE53_SOURCE: &str = "route53";
+pub const LOAD_BALANCER_SOURCE: &str = "elb";
+pub const CLOUDFRONT_SOURCE: &str = "cloudfront";
+
+/// Every source `scan` knows, in the order it runs them.
+pub const SOURCES: &[&str] = &[ROUTE53_SOURCE, LOAD_BALANCER_SOURCE, CLOUDFRONT_SOURCE];
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+/// Route53 and CloudFront are global and signed for us-east-1.
+const GLOBAL_REGION: &str = "us-east-1";
+
+#[derive(Debug, Clone)]
+pub struct Credentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub session_token: Option<String>,
+}
+
+impl Credentials {
+    pub fn from_config(config: &AwsImportConfig) -> Result<Self> {
+        match (&config.access_key_id, &config.secret_access_key) {
+            (Some(access_key_id), Some(secret_access_key)) => Ok(Self {
+                access_key_id: access_key_id.clone(),
+                secret_access_key: secret_access_key.clone(),
+                session_token: config.session_token.clone(),
+            }),
+            _ => Err(Error::validation(
+                "AWS credentials are not configured; set AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY",
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HostedZone {
+    /// Without the `/hostedzone/` prefix
+    pub id: String,
+    pub name: String,
+    pub private: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordSet {
+    pub name: String,
+    pub record_type: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoadBalancer {
+    pub arn: String,
+    pub name: String,
+    pub dns_name: String,
+    pub scheme: String,
+    pub lb_type: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Distribution {
+    pub id: String,
+    pub domain_name: String,
+    pub aliases: Vec<String>,
+    pub enabled: bool,
+}
+
+/// Encodes everything except the unreserved characters, as SigV4 requires.
+pub fn uri_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// Sorted and encoded, usable both in the URL and the canonical request.
+pub fn canonical_query(params: &[(&str, &str)]) -> String {
+    let mut pairs: Vec<(String, String)> = params.iter().map(|(k, v)| (uri_encode(k), uri_encode(v))).collect();
+    pairs.sort();
+    pairs.iter().map(|(k, v)| format!("{}={}", k, v)).collect::<Vec<_>>().join("&")
+}
+
+fn hmac(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// The parts of a request Signature Version 4 signs.
+#[derive(Debug, Clone)]
+pub struct CanonicalRequest<'a> {
+    pub method: &'a str,
+    pub host: &'a str,
+    /// Already URI-encoded
+    pub path: &'a str,
+    /// From [`canonical_query`]
+    pub query: &'a str,
+    /// Signed besides `host` and `x-amz-date`; lower-case names
+    pub headers: Vec<(&'a str, &'a str)>,
+    /// Hex SHA-256 of the body
+    pub payload_hash: String,
+}
+
+/// The `Authorization` header of a Signature Version 4 signed GET request
+/// without a body. `query` must come from [`canonical_query`]; the signed
+/// headers are `host`, `x-amz-date` and, with temporary credentials,
+/// `x-amz-security-token`.
+pub fn authorization(
+    credentials: &Credentials,
+    service: &str,
+    region: &str,
+    host: &str,
+    path: &str,
+    query: &str,
+    at: DateTime<Utc>,
+) -> String {
+    let request = CanonicalRequest {
+        method: "GET",
+        host,
+        path,
+        query,
+        headers: credentials
+            .session_token
+            .iter()
+            .map(|token| ("x-amz-security-token", token.as_str()))
+            .collect(),
+        payload_hash: hex::encode(Sha256::digest(b"")),
+    };
+    sign(credentials, service, region, &request, at)
+}
+
+/// The `Authorization` header for `request`, which must also be sent with
+/// an `x-amz-date` header for `at`.
+pub fn sign(
+    credentials: &Credentials,
+    service: &str,
+    region: &str,
+    request: &CanonicalRequest,
+    at: DateTime<Utc>,
+) -> String {
+    let amz_date = at.format("%Y%m%dT%H%M%SZ").to_string();
+    let date = at.format("%Y%m%d").to_string();
+
+    let mut headers = vec![("host", request.host), ("x-amz-date", amz_date.as_str())];
+    headers.extend(request.headers.iter().copied());
+    headers.sort_by_key(|(name, _)| *name);
+    let canonical_headers: String =
+        headers.iter().map(|(name, value)| format!("{}:{}\n", name, value.trim())).collect();
+    let signed_headers = headers.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(";");
+    let canonical_request = format!(
+        "{}\n{}\n{}\n{}\n{}\n{}",
+        request.method, request.path, request.query, canonical_headers, signed_headers, request.payload_hash
+    );
+
+    let scope = format!("{}/{}/{}/aws4_request", date, region, service);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        hex::encode(Sha256::digest(canonical_request.as_bytes()))
+    );
+    let key = [date.as_str(), region, service, "aws4_request"]
+        .iter()
+        .fold(format!("AWS4{}", credentials.secret_access_key).into_bytes(), |key, part| hmac(&key, part));
+    let signature = hex::encode(hmac(&key, &string_to_sign));
+
+    format!(
+        "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+        credentials.access_key_id, scope, signed_headers, signature
+    )
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|n| n.tag_name().name() == name)
+}
+
+fn child_text<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    child(node, name).and_then(|n| n.text()).map(str::trim)
+}
+
+fn elements<'a, 'input>(node: Node<'a, 'input>, name: &'a str) -> impl Iterator<Item = Node<'a, 'input>> + 'a {
+    node.descendants().filter(move |n| n.tag_name().name() == name)
+}
+
+fn parse(xml: &str) -> Result<Document<'_>> {
+    Document::parse(xml).map_err(|e| Error::internal(format!("Invalid AWS response: {}", e)))
+}
+
+/// Route53 and CloudFront say `IsTruncated`; load balancers only leave
+/// out `NextMarker` on the last page.
+fn next_marker(doc: &Document) -> Option<String> {
+    let root = doc.root_element();
+    if elements(root, "IsTruncated").any(|n| n.text() == Some("false")) {
+        return None;
+    }
+    elements(root, "NextMarker").find_map(|n| n.text()).map(str::to_string)
+}
+
+/// `ListHostedZones`; the marker is set when there are more pages.
+pub fn parse_hosted_zones(xml: &str) -> Result<Page<HostedZone>> {
+    let doc = parse(xml)?;
+    let zones = elements(doc.root_element(), "HostedZone")
+        .filter_map(|zone| {
+            Some(HostedZone {
+                id: child_text(zone, "Id")?.trim_start_matches("/hostedzone/").to_string(),
+                name: child_text(zone, "Name")?.trim_end_matches('.').to_string(),
+                private: child(zone, "Config").and_then(|c| child_text(c, "PrivateZone")) == Some("true"),
+            })
+        })
+        .collect();
+    Ok((zones, next_marker(&doc)))
+}
+
+/// `ListResourceRecordSets`; the next record name and type are set when
+/// there are more pages.
+pub fn parse_record_sets(xml: &str) -> Result<Page<RecordSet, (String, String)>> {
+    let doc = parse(xml)?;
+    let root = doc.root_element();
+    let records = elements(root, "ResourceRecordSet")
+        .filter_map(|record| {
+            Some(RecordSet {
+                name: child_text(record, "Name")?.trim_end_matches('.').to_string(),
+                record_type: child_text(record, "Type")?.to_string(),
+            })
+        })
+        .collect();
+    let next = match (
+        child_text(root, "IsTruncated"),
+        child_text(root, "NextRecordName"),
+        child_text(root, "NextRecordType"),
+    ) {
+        (Some("true"), Some(name), Some(record_type)) => Some((name.to_string(), record_type.to_string())),
+        _ => None,
+    };
+    Ok((records, next))
+}
+
+/// `DescribeLoadBalancers` of Elastic Load Balancing v2.
+pub fn parse_load_balancers(xml: &str) -> Result<Page<LoadBalancer>> {
+    let doc = parse(xml)?;
+    let balancers = elements(doc.root_element(), "LoadBalancers")
+        .flat_map(|list| list.children().filter(|n| n.tag_name().name() == "member"))
+        .filter_map(|lb| {
+            Some(LoadBalancer {
+                arn: child_text(lb, "LoadBalancerArn")?.to_string(),
+                name: child_text(lb, "LoadBalancerName")?.to_string(),
+                dns_name: child_text(lb, "DNSName")?.to_string(),
+                scheme: child_text(lb, "Scheme").unwrap_or_default().to_string(),
+                lb_type: child_text(lb, "Type").unwrap_or_default().to_string(),
+            })
+        })
+        .collect();
+    Ok((balancers, next_marker(&doc)))
+}
+
+/// CloudFront `ListDistributions`.
+pub fn parse_distributions(xml: &str) -> Result<Page<Distribution>> {
+    let doc = parse(xml)?;
+    let distributions = elements(doc.root_element(), "DistributionSummary")
+        .filter_map(|summary| {
+            Some(Distribution {
+                id: child_text(summary, "Id")?.to_string(),
+                domain_name: child_text(summary, "DomainName")?.to_string(),
+                aliases: child(summary, "Aliases")
+                    .map(|aliases| elements(aliases, "CNAME").filter_map(|n| n.text()).map(str::to_string).collect())
+                    .unwrap_or_default(),
+                enabled: child_text(summary, "Enabled") != Some("false"),
+            })
+        })
+        .collect();
+    Ok((distributions, next_marker(&doc)))
+}
+
+/// One proposal per public host name with an A, AAAA or CNAME record.
+/// Wildcards and names with other escaped characters are skipped.
+pub fn record_candidates(zone: &HostedZone, records: &[RecordSet]) -> Vec<ImportCandidate> {
+    if zone.private {
+        return Vec::new();
+    }
+    let mut names: Vec<&str> = records
+        .iter()
+        .filter(|r| matches!(r.record_type.as_str(), "A" | "AAAA" | "CNAME"))
+        .map(|r| r.name.as_str())
+        .filter(|name| !name.contains('\\') && !name.starts_with('*'))
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+        .into_iter()
+        .map(|name| ImportCandidate {
+            source: ROUTE53_SOURCE.to_string(),
+            resource_id: format!("route53:{}/{}", zone.id, name),
+            name: name.to_string(),
+            endpoint: format!("https://{}/", name),
+            tags: vec!["aws".to_string(), ROUTE53_SOURCE.to_string(), format!("zone:{}", zone.name)],
+        })
+        .collect()
+}
+
+/// Internet-facing application load balancers; network and internal ones
+/// cannot be checked over HTTP from outside.
+pub fn load_balancer_candidates(balancers: &[LoadBalancer], region: &str) -> Vec<ImportCandidate> {
+    balancers
+        .iter()
+        .filter(|lb| lb.lb_type == "application" && lb.scheme == "internet-facing")
+        .map(|lb| ImportCandidate {
+            source: LOAD_BALANCER_SOURCE.to_string(),
+            resource_id: format!("elb:{}", lb.arn),
+            name: lb.name.clone(),
+            endpoint: format!("http://{}/", lb.dns_name),
+            tags: vec!["aws".to_string(), LOAD_BALANCER_SOURCE.to_string(), format!("region:{}", region)],
+        })
+        .collect()
+}
+
+/// One proposal per alias of an enabled distribution, or its
+/// `cloudfront.net` name when it has none.
+pub fn distribution_candidates(distributions: &[Distribution]) -> Vec<ImportCandidate> {
+    distributions
+        .iter()
+        .filter(|d| d.enabled)
+        .flat_map(|d| {
+            let hosts = if d.aliases.is_empty() { vec![d.domain_name.clone()] } else { d.aliases.clone() };
+            hosts.into_iter().map(move |host| ImportCandidate {
+                source: CLOUDFRONT_SOURCE.to_string(),
+                resource_id: format!("cloudfront:{}/{}", d.id, host),
+                name: host.clone(),
+                endpoint: format!("https://{}/", host),
+                tags: vec!["aws".to_string(), CLOUDFRONT_SOURCE.to_string()],
+            })
+        })
+        .collect()
+}
+
+/// Read-only client for the few AWS APIs the importer calls.
+pub struct AwsClient {
+    client: Client,
+    credentials: Credentials,
+    region: String,
+}
+
+impl AwsClient {
+    pub fn new(config: &AwsImportConfig) -> Result<Self> {
+        Ok(Self {
+            client: Client::new(),
+            credentials: Credentials::from_config(config)?,
+            region: config.region.clone(),
+        })
+    }
+
+    async fn get(&self, service: &str, region: &str, host: &str, path: &str, params: &[(&str, &str)]) -> Result<String> {
+        let query = canonical_query(params);
+        let now = Utc::now();
+        let mut request = self
+            .client
+            .get(format!("https://{}{}?{}", host, path, query))
+            .header("x-amz-date", now.format("%Y%m%dT%H%M%SZ").to_string())
+            .header(
+                reqwest::header::AUTHORIZATION,
+                authorization(&self.credentials, service, region, host, path, &query, now),
+            )
+            .timeout(REQUEST_TIMEOUT);
+        if let Some(token) = &self.credentials.session_token {
+            request = request.header("x-amz-security-token", token);
+        }
+
+        let response = request.send().await?;
+        let status = response.status();
+        let body = response.text().await?;
+        if !status.is_success() {
+            let message = Document::parse(&body)
+                .ok()
+                .and_then(|doc| elements(doc.root_element(), "Message").find_map(|n| n.text()).map(str::to_string))
+                .unwrap_or_else(|| status.to_string());
+            return Err(Error::internal(format!("AWS {} request failed: {}", service, message)));
+        }
+        Ok(body)
+    }
+
+    pub async fn route53_candidates(&self) -> Result<Vec<ImportCandidate>> {
+        let host = "route53.amazonaws.com";
+        let mut zones = Vec::new();
+        let mut marker = None;
+        loop {
+            let params: Vec<(&str, &str)> = marker.as_deref().map(|m| vec![("marker", m)]).unwrap_or_default();
+            let xml = self.get("route53", GLOBAL_REGION, host, "/2013-04-01/hostedzone", &params).await?;
+            let (page, next) = parse_hosted_zones(&xml)?;
+            zones.extend(page);
+            match next {
+                Some(next) => marker = Some(next),
+                None => break,
+            }
+        }
+
+        let mut candidates = Vec::new();
+        for zone in zones.iter().filter(|z| !z.private) {
+            let path = format!("/2013-04-01/hostedzone/{}/rrset", zone.id);
+            let mut records = Vec::new();
+            let mut start: Option<(String, String)> = None;
+            loop {
+                let params: Vec<(&str, &str)> = match &start {
+                    Some((name, record_type)) => vec![("name", name.as_str()), ("type", record_type.as_str())],
+                    None => Vec::new(),
+                };
+                let xml = self.get("route53", GLOBAL_REGION, host, &path, &params).await?;
+                let (page, next) = parse_record_sets(&xml)?;
+                records.extend(page);
+                match next {
+                    Some(next) => start = Some(next),
+                    None => break,
+                }
+            }
+            candidates.extend(record_candidates(zone, &records));
+        }
+        Ok(candidates)
+    }
+
+    pub async fn load_balancer_candidates(&self) -> Result<Vec<ImportCandidate>> {
+        let host = format!("elasticloadbalancing.{}.amazonaws.com", self.region);
+        let mut balancers = Vec::new();
+        let mut marker = None;
+        loop {
+            let mut params = vec![("Action", "DescribeLoadBalancers"), ("Version", "2015-12-01")];
+            if let Some(marker) = marker.as_deref() {
+                params.push(("Marker", marker));
+            }
+            let xml = self.get("elasticloadbalancing", &self.region, &host, "/", &params).await?;
+            let (page, next) = parse_load_balancers(&xml)?;
+            balancers.extend(page);
+            match next {
+                Some(next) => marker = Some(next),
+                None => break,
+            }
+        }
+        Ok(load_balancer_candidates(&balancers, &self.region))
+    }
+
+    pub async fn cloudfront_candidates(&self) -> Result<Vec<ImportCandidate>> {
+        let mut distributions = Vec::new();
+        let mut marker = None;
+        loop {
+            let params: Vec<(&str, &str)> = marker.as_deref().map(|m| vec![("Marker", m)]).unwrap_or_default();
+            let xml = self
+                .get("cloudfront", GLOBAL_REGION, "cloudfront.amazonaws.com", "/2020-05-31/distribution", &params)
+                .await?;
+            let (page, next) = parse_distributions(&xml)?;
+            distributions.extend(page);
+            match next {
+                Some(next) => marker = Some(next),
+                None => break,
+            }
+        }
+        Ok(distribution_candidates(&distributions))
+    }
+
+    /// Candidates from the given sources, or all of them when empty.
+    pub async fn scan(&self, sources: &[String]) -> Result<Vec<ImportCandidate>> {
+        for source in sources {
+            if !SOURCES.contains(&source.as_str()) {
+                return Err(Error::validation(format!(
+                    "Unknown AWS source {}; expected one of {}",
+                    source,
+                    SOURCES.join(", ")
+                )));
+            }
+        }
+        let wanted = |source: &str| sources.is_empty() || sources.iter().any(|s| s == source);
+
+        let mut candidates = Vec::new();
+        if wanted(ROUTE53_SOURCE) {
+            candidates.extend(self.route53_candidates().await?);
+        }
+        if wanted(LOAD_BALANCER_SOURCE) {
+            candidates.extend(self.load_balancer_candidates().await?);
+        }
+        if wanted(CLOUDFRONT_SOURCE) {
+            candidates.extend(self.cloudfront_candidates().await?);
+        }
+        Ok(candidates)
+    }
+}
@@ -0,0 +1,130 @@
+#[cfg(test)]
+mod aws_tests {
+    use crate::aws::*;
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn test_authorization_matches_sigv4_test_suite() {
+        // "get-vanilla" from the AWS Signature Version 4 test suite
+        let credentials = Credentials {
+            access_key_id: "AKIDEXAMPLE".to_string(),
+            secret_access_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".to_string(),
+            session_token: None,
+        };
+        let at = Utc.with_ymd_and_hms(2015, 8, 30, 12, 36, 0).unwrap();
+
+        let header = authorization(&credentials, "service", "us-east-1", "example.amazonaws.com", "/", "", at);
+
+        assert_eq!(
+            header,
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, \
+             SignedHeaders=host;x-amz-date, \
+             Signature=5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31"
+        );
+    }
+
+    #[test]
+    fn test_canonical_query_sorts_and_encodes() {
+        assert_eq!(
+            canonical_query(&[("type", "A"), ("name", "api.example.com."), ("Marker", "a b/c")]),
+            "Marker=a%20b%2Fc&name=api.example.com.&type=A"
+        );
+    }
+
+    #[test]
+    fn test_route53_records_become_public_https_candidates() {
+        let zones = r#"<?xml version="1.0"?>
+            <ListHostedZonesResponse xmlns="https://route53.amazonaws.com/doc/2013-04-01/">
+              <HostedZones>
+                <HostedZone><Id>/hostedzone/Z1</Id><Name>example.com.</Name><Config><PrivateZone>false</PrivateZone></Config></HostedZone>
+                <HostedZone><Id>/hostedzone/Z2</Id><Name>internal.</Name><Config><PrivateZone>true</PrivateZone></Config></HostedZone>
+              </HostedZones>
+              <IsTruncated>true</IsTruncated><NextMarker>Z3</NextMarker>
+            </ListHostedZonesResponse>"#;
+        let (zones, marker) = parse_hosted_zones(zones).unwrap();
+        assert_eq!(zones.len(), 2);
+        assert_eq!(zones[0].id, "Z1");
+        assert!(zones[1].private);
+        assert_eq!(marker.as_deref(), Some("Z3"));
+
+        let records = r#"<ListResourceRecordSetsResponse xmlns="https://route53.amazonaws.com/doc/2013-04-01/">
+              <ResourceRecordSets>
+                <ResourceRecordSet><Name>example.com.</Name><Type>MX</Type></ResourceRecordSet>
+                <ResourceRecordSet><Name>api.example.com.</Name><Type>A</Type></ResourceRecordSet>
+                <ResourceRecordSet><Name>api.example.com.</Name><Type>AAAA</Type></ResourceRecordSet>
+                <ResourceRecordSet><Name>\052.example.com.</Name><Type>CNAME</Type></ResourceRecordSet>
+              </ResourceRecordSets>
+              <IsTruncated>true</IsTruncated><NextRecordName>www.example.com.</NextRecordName><NextRecordType>A</NextRecordType>
+            </ListResourceRecordSetsResponse>"#;
+        let (records, next) = parse_record_sets(records).unwrap();
+        assert_eq!(next, Some(("www.example.com.".to_string(), "A".to_string())));
+
+        let candidates = record_candidates(&zones[0], &records);
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].endpoint, "https://api.example.com/");
+        assert_eq!(candidates[0].resource_id, "route53:Z1/api.example.com");
+        assert!(record_candidates(&zones[1], &records).is_empty());
+    }
+
+    #[test]
+    fn test_only_internet_facing_application_load_balancers() {
+        let xml = r#"<DescribeLoadBalancersResponse xmlns="http://elasticloadbalancing.amazonaws.com/doc/2015-12-01/">
+              <DescribeLoadBalancersResult>
+                <LoadBalancers>
+                  <member>
+                    <LoadBalancerArn>arn:aws:elasticloadbalancing:eu-west-1:1:loadbalancer/app/web/1</LoadBalancerArn>
+                    <LoadBalancerName>web</LoadBalancerName>
+                    <DNSName>web-1.eu-west-1.elb.amazonaws.com</DNSName>
+                    <Scheme>internet-facing</Scheme><Type>application</Type>
+                    <AvailabilityZones><member><ZoneName>eu-west-1a</ZoneName></member></AvailabilityZones>
+                  </member>
+                  <member>
+                    <LoadBalancerArn>arn:2</LoadBalancerArn><LoadBalancerName>db</LoadBalancerName>
+                    <DNSName>db.elb.amazonaws.com</DNSName><Scheme>internal</Scheme><Type>application</Type>
+                  </member>
+                  <member>
+                    <LoadBalancerArn>arn:3</LoadBalancerArn><LoadBalancerName>tcp</LoadBalancerName>
+                    <DNSName>tcp.elb.amazonaws.com</DNSName><Scheme>internet-facing</Scheme><Type>network</Type>
+                  </member>
+                </LoadBalancers>
+              </DescribeLoadBalancersResult>
+            </DescribeLoadBalancersResponse>"#;
+        let (balancers, marker) = parse_load_balancers(xml).unwrap();
+        assert_eq!(balancers.len(), 3);
+        assert_eq!(marker, None);
+
+        let candidates = load_balancer_candidates(&balancers, "eu-west-1");
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].name, "web");
+        assert_eq!(candidates[0].endpoint, "http://web-1.eu-west-1.elb.amazonaws.com/");
+        assert!(candidates[0].tags.contains(&"region:eu-west-1".to_string()));
+    }
+
+    #[test]
+    fn test_cloudfront_aliases_preferred_over_distribution_domain() {
+        let xml = r#"<DistributionList xmlns="http://cloudfront.amazonaws.com/doc/2020-05-31/">
+              <IsTruncated>false</IsTruncated><NextMarker>ignored</NextMarker>
+              <Items>
+                <DistributionSummary>
+                  <Id>E1</Id><DomainName>d1.cloudfront.net</DomainName><Enabled>true</Enabled>
+                  <Aliases><Quantity>2</Quantity><Items><CNAME>www.example.com</CNAME><CNAME>cdn.example.com</CNAME></Items></Aliases>
+                </DistributionSummary>
+                <DistributionSummary>
+                  <Id>E2</Id><DomainName>d2.cloudfront.net</DomainName><Enabled>true</Enabled>
+                  <Aliases><Quantity>0</Quantity></Aliases>
+                </DistributionSummary>
+                <DistributionSummary>
+                  <Id>E3</Id><DomainName>d3.cloudfront.net</DomainName><Enabled>false</Enabled>
+                </DistributionSummary>
+              </Items>
+            </DistributionList>"#;
+        let (distributions, marker) = parse_distributions(xml).unwrap();
+        assert_eq!(marker, None);
+
+        let endpoints: Vec<String> = distribution_candidates(&distributions).into_iter().map(|c| c.endpoint).collect();
+        assert_eq!(
+            endpoints,
+            vec!["https://www.example.com/", "https://cdn.example.com/", "https://d2.cloudfront.net/"]
+        );
+    }
+}
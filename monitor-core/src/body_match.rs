@@ -0,0 +1,37 @@
+use regex::Regex;
+use crate::{error::Result, Error};
+
+/// Longest keyword or pattern a monitor may store
+pub const MAX_PATTERN_LEN: usize = 1024;
+
+/// Rejects keywords and patterns that are too long and patterns that do not
+/// compile. Empty values clear the match.
+pub fn validate(contains: Option<&str>, regex: Option<&str>) -> Result<()> {
+    for (field, value) in [("expected_body_contains", contains), ("expected_body_regex", regex)] {
+        if value.is_some_and(|value| value.len() > MAX_PATTERN_LEN) {
+            return Err(Error::validation(format!("{} must be at most {} bytes", field, MAX_PATTERN_LEN)));
+        }
+    }
+    if let Some(pattern) = regex.filter(|pattern| !pattern.is_empty()) {
+        Regex::new(pattern)
+            .map_err(|e| Error::validation(format!("Invalid expected_body_regex '{}': {}", pattern, e)))?;
+    }
+    Ok(())
+}
+
+/// `Err` carries the result's error message when `body` lacks the keyword
+/// or does not match the pattern.
+pub fn check(body: &str, contains: Option<&str>, regex: Option<&str>) -> std::result::Result<(), String> {
+    if let Some(keyword) = contains.filter(|keyword| !keyword.is_empty())
+        && !body.contains(keyword)
+    {
+        return Err(format!("Response body does not contain '{}'", keyword));
+    }
+    if let Some(pattern) = regex.filter(|pattern| !pattern.is_empty()) {
+        let regex = Regex::new(pattern).map_err(|e| format!("Invalid expected_body_regex '{}': {}", pattern, e))?;
+        if !regex.is_match(body) {
+            return Err(format!("Response body does not match /{}/", pattern));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,34 @@
+#[cfg(test)]
+mod body_match_tests {
+    use crate::body_match::*;
+
+    #[test]
+    fn keyword_must_appear_in_the_body() {
+        assert!(check("status: ok", Some("ok"), None).is_ok());
+        let error = check("status: degraded", Some("ok"), None).unwrap_err();
+        assert_eq!(error, "Response body does not contain 'ok'");
+    }
+
+    #[test]
+    fn pattern_must_match_the_body() {
+        assert!(check(r#"{"version":"1.4.2"}"#, None, Some(r#""version":"1\.\d+"#)).is_ok());
+        let error = check(r#"{"version":"2.0.0"}"#, None, Some(r#""version":"1\.\d+"#)).unwrap_err();
+        assert!(error.contains("does not match"));
+    }
+
+    #[test]
+    fn both_are_checked_and_empty_values_are_ignored() {
+        assert!(check("ok", Some("ok"), Some("^ok$")).is_ok());
+        assert!(check("ok", Some("ok"), Some("^fine$")).is_err());
+        assert!(check("anything", Some(""), Some("")).is_ok());
+        assert!(check("anything", None, None).is_ok());
+    }
+
+    #[test]
+    fn invalid_or_oversized_patterns_are_rejected() {
+        assert!(validate(Some("ok"), Some(r"\d+")).is_ok());
+        assert!(validate(None, Some("(unclosed")).is_err());
+        assert!(validate(Some(&"x".repeat(MAX_PATTERN_LEN + 1)), None).is_err());
+        assert!(validate(Some(""), Some("")).is_ok());
+    }
+}
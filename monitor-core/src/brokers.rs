@@ -0,0 +1,185 @@
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use uuid::Uuid;
+use crate::{error::Result, Error};
+
+pub const NATS_DEFAULT_PORT: u16 = 4222;
+
+/// A message broker check run instead of the HTTP request. The monitor's
+/// `endpoint` is the broker, `nats://[user:pass@]host[:port]`, or the base
+/// URL of a Kafka REST Proxy.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BrokerCheck {
+    /// Publishes a probe message and waits for it to be delivered back to
+    /// a subscription on the same subject.
+    NatsProbe { subject: Option<String> },
+    /// Pending messages of a JetStream consumer, from the JetStream API.
+    NatsConsumerLag {
+        stream: String,
+        consumer: String,
+        max_lag: u64,
+    },
+    /// Total lag of a consumer group, from the REST Proxy v3 admin API.
+    KafkaConsumerLag {
+        cluster_id: String,
+        group: String,
+        max_lag: u64,
+    },
+}
+
+impl BrokerCheck {
+    pub fn is_nats(&self) -> bool {
+        !matches!(self, BrokerCheck::KafkaConsumerLag { .. })
+    }
+
+    pub fn validate(&self, endpoint: &str) -> Result<()> {
+        let url = Url::parse(endpoint).map_err(|e| Error::validation(format!("Invalid broker endpoint: {}", e)))?;
+        let expected: &[&str] = if self.is_nats() { &["nats"] } else { &["http", "https"] };
+        if !expected.contains(&url.scheme()) || url.host_str().is_none() {
+            return Err(Error::validation(format!(
+                "broker_check endpoints must be {} URLs",
+                expected.join(" or ")
+            )));
+        }
+        match self {
+            BrokerCheck::NatsProbe { subject: Some(subject) } if !valid_subject(subject) => Err(Error::validation(
+                "subject must be dot-separated tokens without spaces or wildcards",
+            )),
+            BrokerCheck::NatsConsumerLag { stream, consumer, .. }
+                if !valid_name(stream) || !valid_name(consumer) =>
+            {
+                Err(Error::validation("stream and consumer must be non-empty names without spaces, dots or wildcards"))
+            }
+            BrokerCheck::KafkaConsumerLag { cluster_id, group, .. }
+                if cluster_id.trim().is_empty() || group.trim().is_empty() =>
+            {
+                Err(Error::validation("cluster_id and group must not be empty"))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+fn valid_name(name: &str) -> bool {
+    !name.is_empty() && !name.contains(|c: char| c.is_whitespace() || ".*>".contains(c))
+}
+
+fn valid_subject(subject: &str) -> bool {
+    subject.split('.').all(valid_name)
+}
+
+/// The subject a probe is published on; each monitor gets its own by default.
+pub fn probe_subject(subject: Option<&str>, monitor_id: Uuid) -> String {
+    subject.map(str::to_string).unwrap_or_else(|| format!("monitor.probe.{}", monitor_id))
+}
+
+pub fn consumer_info_subject(stream: &str, consumer: &str) -> String {
+    format!("$JS.API.CONSUMER.INFO.{}.{}", stream, consumer)
+}
+
+/// The `CONNECT` options for a server URL. A username without a password
+/// is sent as a token.
+pub fn connect_options(url: &Url) -> serde_json::Value {
+    let mut options = json!({
+        "verbose": false,
+        "pedantic": false,
+        "name": "monitor",
+        "lang": "rust",
+        "version": env!("CARGO_PKG_VERSION"),
+        "protocol": 1,
+    });
+    let user = percent_decode(url.username());
+    match url.password().map(percent_decode) {
+        Some(pass) => {
+            options["user"] = json!(user);
+            options["pass"] = json!(pass);
+        }
+        None if !user.is_empty() => options["auth_token"] = json!(user),
+        None => {}
+    }
+    options
+}
+
+/// Userinfo stays percent-encoded in a parsed URL.
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%'
+            && let Some(byte) = value.get(i + 1..i + 3).and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        {
+            decoded.push(byte);
+            i += 3;
+            continue;
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// A `MSG <subject> <sid> [reply-to] <#bytes>` line, without the payload.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MessageHeader {
+    pub subject: String,
+    pub sid: String,
+    pub reply_to: Option<String>,
+    pub size: usize,
+}
+
+pub fn parse_message_header(line: &str) -> Option<MessageHeader> {
+    let mut parts = line.split_whitespace();
+    if parts.next()? != "MSG" {
+        return None;
+    }
+    let fields: Vec<&str> = parts.collect();
+    let (subject, sid, reply_to, size) = match fields.as_slice() {
+        [subject, sid, size] => (subject, sid, None, size),
+        [subject, sid, reply_to, size] => (subject, sid, Some(reply_to.to_string()), size),
+        _ => return None,
+    };
+    Some(MessageHeader {
+        subject: subject.to_string(),
+        sid: sid.to_string(),
+        reply_to,
+        size: size.parse().ok()?,
+    })
+}
+
+/// Pending messages from a JetStream consumer info response.
+pub fn parse_consumer_lag(body: &serde_json::Value) -> std::result::Result<u64, String> {
+    if let Some(error) = body.get("error") {
+        let description = error.get("description").and_then(|d| d.as_str()).unwrap_or("unknown error");
+        return Err(format!("JetStream API error: {}", description));
+    }
+    body.get("num_pending")
+        .and_then(|n| n.as_u64())
+        .ok_or_else(|| "consumer info has no num_pending".to_string())
+}
+
+pub fn kafka_lag_url(base: &str, cluster_id: &str, group: &str) -> Result<Url> {
+    let mut url = Url::parse(base).map_err(|e| Error::validation(format!("Invalid broker endpoint: {}", e)))?;
+    url.path_segments_mut()
+        .map_err(|_| Error::validation("Invalid broker endpoint"))?
+        .pop_if_empty()
+        .extend(["v3", "clusters", cluster_id, "consumer-groups", group, "lag-summary"]);
+    Ok(url)
+}
+
+/// Total lag from a REST Proxy `lag-summary` response.
+pub fn parse_kafka_lag(body: &serde_json::Value) -> std::result::Result<u64, String> {
+    body.get("total_lag")
+        .and_then(|n| n.as_u64())
+        .ok_or_else(|| "lag summary has no total_lag".to_string())
+}
+
+/// `Err` carries the result's error message when the lag is over the limit.
+pub fn check_lag(lag: u64, max_lag: u64) -> std::result::Result<(), String> {
+    if lag > max_lag {
+        return Err(format!("consumer lag is {}, expected at most {}", lag, max_lag));
+    }
+    Ok(())
+}
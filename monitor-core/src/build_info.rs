@@ -0,0 +1,111 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+use crate::{
+    config::{Config, FeaturesConfig},
+    db::DatabasePool,
+    error::Result,
+    runtime_settings,
+};
+
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+/// Short commit hash, or `unknown` for builds outside a git checkout
+pub const GIT_COMMIT: &str = env!("MONITOR_GIT_COMMIT");
+const BUILT_AT: &str = env!("MONITOR_BUILT_AT");
+/// Schedulers republish every five minutes; older reports are from
+/// instances that have gone away
+const REPORT_MAX_AGE_MINUTES: i64 = 60;
+
+/// What a process is running, for `GET /api/version`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BuildInfo {
+    /// `api` or `scheduler`
+    pub component: String,
+    pub version: String,
+    pub git_commit: String,
+    pub built_at: Option<DateTime<Utc>>,
+    /// Feature flags enabled in the config, without the `enable_` prefix
+    pub features: Vec<String>,
+    /// `HOSTNAME`, which is the pod or container name in most deployments
+    pub host: String,
+}
+
+/// A scheduler's build info as last published.
+#[derive(Debug, Clone, Serialize)]
+pub struct Reported {
+    #[serde(flatten)]
+    pub info: BuildInfo,
+    pub reported_at: DateTime<Utc>,
+}
+
+/// Latest migration applied to the database against the latest one this
+/// build knows about.
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationLevel {
+    pub applied: Option<i64>,
+    pub latest: Option<i64>,
+    pub pending: usize,
+}
+
+impl BuildInfo {
+    pub fn current(component: &str, config: &Config) -> Self {
+        Self {
+            component: component.to_string(),
+            version: VERSION.to_string(),
+            git_commit: GIT_COMMIT.to_string(),
+            built_at: built_at(),
+            features: enabled_features(&config.features),
+            host: std::env::var("HOSTNAME").unwrap_or_else(|_| "unknown".to_string()),
+        }
+    }
+}
+
+pub fn built_at() -> Option<DateTime<Utc>> {
+    BUILT_AT.parse().ok().and_then(|secs| DateTime::from_timestamp(secs, 0))
+}
+
+pub fn enabled_features(features: &FeaturesConfig) -> Vec<String> {
+    let Ok(serde_json::Value::Object(flags)) = serde_json::to_value(features) else {
+        return Vec::new();
+    };
+    flags
+        .into_iter()
+        .filter(|(_, enabled)| enabled.as_bool() == Some(true))
+        .map(|(name, _)| name.strip_prefix("enable_").unwrap_or(&name).to_string())
+        .collect()
+}
+
+/// Shares a scheduler's build info with the API, keyed by host so each
+/// instance shows up.
+pub async fn publish(db: &DatabasePool, info: &BuildInfo) -> Result<()> {
+    let key = format!("{}{}", runtime_settings::SCHEDULER_BUILD_INFO_PREFIX, info.host);
+    runtime_settings::set(db, &key, &serde_json::to_string(info)?).await
+}
+
+/// Build info of the schedulers that reported within the last hour.
+pub async fn reported(db: &DatabasePool, now: DateTime<Utc>) -> Result<Vec<Reported>> {
+    let since = now - Duration::minutes(REPORT_MAX_AGE_MINUTES);
+    let mut reports = Vec::new();
+    for (key, value, reported_at) in runtime_settings::list(db, runtime_settings::SCHEDULER_BUILD_INFO_PREFIX).await? {
+        if reported_at < since {
+            continue;
+        }
+        match serde_json::from_str(&value) {
+            Ok(info) => reports.push(Reported { info, reported_at }),
+            Err(e) => warn!("Ignoring unreadable build info in {}: {}", key, e),
+        }
+    }
+    Ok(reports)
+}
+
+pub async fn migration_level(db: &DatabasePool) -> Result<MigrationLevel> {
+    let migrator = sqlx::migrate!("../monitor-core/migrations");
+    let applied: Vec<i64> = sqlx::query_scalar("SELECT version FROM _sqlx_migrations WHERE success = true")
+        .fetch_all(db)
+        .await?;
+    Ok(MigrationLevel {
+        applied: applied.iter().max().copied(),
+        latest: migrator.iter().map(|m| m.version).max(),
+        pending: migrator.iter().filter(|m| !applied.contains(&m.version)).count(),
+    })
+}
@@ -0,0 +1,27 @@
+#[cfg(test)]
+mod build_info_tests {
+    use crate::build_info::*;
+    use crate::config::Config;
+
+    #[test]
+    fn build_info_names_the_enabled_features() {
+        let mut config = Config::from_env().unwrap();
+        config.features.enable_agents = true;
+        config.features.enable_status_pages = false;
+        let info = BuildInfo::current("api", &config);
+
+        assert_eq!(info.component, "api");
+        assert_eq!(info.version, env!("CARGO_PKG_VERSION"));
+        assert!(!info.git_commit.is_empty());
+        assert!(built_at().is_some());
+        assert!(info.features.contains(&"agents".to_string()));
+        assert!(!info.features.contains(&"status_pages".to_string()));
+    }
+
+    #[test]
+    fn build_info_round_trips_through_json() {
+        let info = BuildInfo::current("scheduler", &Config::from_env().unwrap());
+        let json = serde_json::to_string(&info).unwrap();
+        assert_eq!(serde_json::from_str::<BuildInfo>(&json).unwrap(), info);
+    }
+}
@@ -1,9 +1,20 @@
-use redis::Client;
+use redis::{AsyncCommands, Client};
+use uuid::Uuid;
 use crate::{config::RedisConfig, error::Result};
 
 pub type RedisPool = Client;
 
+/// Redis channel the API publishes the ids of created and edited monitors on,
+/// alongside the `monitor_changes` Postgres notification
+pub const MONITOR_CHANGES_CHANNEL: &str = "monitor:changes";
+
 pub async fn create_redis_pool(config: &RedisConfig) -> Result<RedisPool> {
     let client = Client::open(config.url.as_str())?;
     Ok(client)
-}
\ No newline at end of file
+}
+
+pub async fn publish_monitor_change(redis: &RedisPool, monitor_id: Uuid) -> Result<()> {
+    let mut connection = redis.get_multiplexed_async_connection().await?;
+    let _receivers: i64 = connection.publish(MONITOR_CHANGES_CHANNEL, monitor_id.to_string()).await?;
+    Ok(())
+}
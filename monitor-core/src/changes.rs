@@ -0,0 +1,65 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::FromRow;
+use uuid::Uuid;
+use crate::{db::DatabasePool, error::Result, Error};
+
+/// A monitor whose latest result status differs from the one before it.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct StateChange {
+    pub seq: i64,
+    pub monitor_id: Uuid,
+    pub monitor_name: String,
+    #[serde(skip_serializing)]
+    pub tags: Vec<String>,
+    /// `None` for a monitor's first result
+    pub previous_status: Option<String>,
+    pub status: String,
+    pub result_id: Uuid,
+    /// Captured response for up-to-down changes, see `evidence`
+    pub evidence_id: Option<Uuid>,
+    pub changed_at: DateTime<Utc>,
+}
+
+/// Cursors are opaque to clients but are the `seq` of the last change seen.
+pub fn parse_cursor(cursor: &str) -> Result<i64> {
+    cursor
+        .parse::<i64>()
+        .ok()
+        .filter(|seq| *seq >= 0)
+        .ok_or_else(|| Error::validation(format!("Invalid cursor: {}", cursor)))
+}
+
+/// The cursor to hand back: the newest change scanned, or `since` when there was none.
+pub fn next_cursor(since: i64, scanned: &[StateChange]) -> i64 {
+    scanned.iter().map(|c| c.seq).max().unwrap_or(since).max(since)
+}
+
+/// Seq of the newest change, where a client without a cursor starts.
+pub async fn latest_cursor(db: &DatabasePool) -> Result<i64> {
+    let seq: Option<i64> = sqlx::query_scalar("SELECT MAX(seq) FROM monitor_state_changes")
+        .fetch_one(db)
+        .await?;
+    Ok(seq.unwrap_or(0))
+}
+
+/// Changes after `since`, oldest first.
+pub async fn since(db: &DatabasePool, since: i64, limit: i64) -> Result<Vec<StateChange>> {
+    let changes = sqlx::query_as::<_, StateChange>(
+        r#"
+        SELECT c.seq, c.monitor_id, m.name AS monitor_name, m.tags, c.previous_status, c.status,
+               c.result_id, e.id AS evidence_id, c.changed_at
+        FROM monitor_state_changes c
+        JOIN monitors m ON m.id = c.monitor_id
+        LEFT JOIN incident_evidence e ON e.result_id = c.result_id
+        WHERE c.seq > $1
+        ORDER BY c.seq
+        LIMIT $2
+        "#,
+    )
+    .bind(since)
+    .bind(limit)
+    .fetch_all(db)
+    .await?;
+    Ok(changes)
+}
@@ -0,0 +1,34 @@
+#[cfg(test)]
+mod changes_tests {
+    use crate::changes::*;
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    fn change(seq: i64) -> StateChange {
+        StateChange {
+            seq,
+            monitor_id: Uuid::new_v4(),
+            monitor_name: "api".to_string(),
+            tags: vec![],
+            previous_status: Some("success".to_string()),
+            status: "failure".to_string(),
+            result_id: Uuid::new_v4(),
+            evidence_id: None,
+            changed_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_parse_cursor() {
+        assert_eq!(parse_cursor("0").unwrap(), 0);
+        assert_eq!(parse_cursor("42").unwrap(), 42);
+        assert!(parse_cursor("-1").is_err());
+        assert!(parse_cursor("abc").is_err());
+    }
+
+    #[test]
+    fn test_next_cursor() {
+        assert_eq!(next_cursor(7, &[]), 7);
+        assert_eq!(next_cursor(7, &[change(8), change(11), change(9)]), 11);
+    }
+}
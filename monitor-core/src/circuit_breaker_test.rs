@@ -0,0 +1,45 @@
+#[cfg(test)]
+mod circuit_breaker_tests {
+    use crate::circuit_breaker::*;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_opens_after_threshold_and_recovers() {
+        let mut breaker = CircuitBreaker::new(3, Duration::from_secs(30));
+        let start = Instant::now();
+
+        for _ in 0..2 {
+            assert!(breaker.allow_request(start));
+            breaker.record_failure(start);
+        }
+        assert_eq!(breaker.state(), BreakerState::Closed);
+
+        breaker.record_failure(start);
+        assert_eq!(breaker.state(), BreakerState::Open);
+        assert!(!breaker.allow_request(start + Duration::from_secs(10)));
+
+        // One trial call once the open period has elapsed
+        assert!(breaker.allow_request(start + Duration::from_secs(30)));
+        assert_eq!(breaker.state(), BreakerState::HalfOpen);
+        assert!(!breaker.allow_request(start + Duration::from_secs(30)));
+
+        breaker.record_success();
+        assert_eq!(breaker.state(), BreakerState::Closed);
+        assert!(breaker.allow_request(start + Duration::from_secs(31)));
+    }
+
+    #[test]
+    fn test_failed_trial_reopens() {
+        let mut breaker = CircuitBreaker::new(1, Duration::from_secs(5));
+        let start = Instant::now();
+
+        breaker.record_failure(start);
+        let retry = start + Duration::from_secs(5);
+        assert!(breaker.allow_request(retry));
+        breaker.record_failure(retry);
+
+        assert_eq!(breaker.state(), BreakerState::Open);
+        assert!(!breaker.allow_request(retry + Duration::from_secs(4)));
+        assert!(breaker.allow_request(retry + Duration::from_secs(5)));
+    }
+}
@@ -0,0 +1,146 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::Debug,
+    sync::{Arc, Mutex, RwLock},
+    time::Duration,
+};
+use tracing::{debug, warn};
+use crate::{config::ClockConfig, db::DatabasePool, error::Result, ntp};
+
+const NTP_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Last measured difference between this process's clock and the reference clocks.
+/// Offsets are positive when the reference is ahead of the local clock.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClockStatus {
+    pub db_offset_ms: i64,
+    pub ntp_offset_ms: Option<i64>,
+    pub max_skew_ms: i64,
+    pub skewed: bool,
+    pub checked_at: DateTime<Utc>,
+}
+
+impl ClockStatus {
+    /// The larger of the measured offsets, which is what gets annotated on results.
+    pub fn offset_ms(&self) -> i64 {
+        match self.ntp_offset_ms {
+            Some(ntp) if ntp.abs() > self.db_offset_ms.abs() => ntp,
+            _ => self.db_offset_ms,
+        }
+    }
+}
+
+static CURRENT: RwLock<Option<ClockStatus>> = RwLock::new(None);
+
+pub fn current() -> Option<ClockStatus> {
+    CURRENT.read().unwrap().clone()
+}
+
+/// Offset to store with check results, if the clock has been measured.
+pub fn current_offset_ms() -> Option<i32> {
+    current().map(|status| status.offset_ms().clamp(i32::MIN as i64, i32::MAX as i64) as i32)
+}
+
+async fn db_offset_ms(db: &DatabasePool) -> Result<i64> {
+    let before = Utc::now();
+    let db_now: DateTime<Utc> = sqlx::query_scalar("SELECT NOW()").fetch_one(db).await?;
+    let after = Utc::now();
+
+    // Assume the database read its clock half way through the round trip
+    let midpoint = before + (after - before) / 2;
+    Ok((db_now - midpoint).num_milliseconds())
+}
+
+/// Measures skew against the database (and NTP, if configured), logs a warning
+/// when it exceeds `max_skew_ms`, and makes the result available via [`current`].
+pub async fn check(db: &DatabasePool, config: &ClockConfig) -> Result<ClockStatus> {
+    let db_offset_ms = db_offset_ms(db).await?;
+
+    let ntp_offset_ms = match config.ntp_server.as_deref().filter(|s| !s.is_empty()) {
+        Some(server) => match ntp::query_offset_ms(server, NTP_TIMEOUT).await {
+            Ok(offset) => Some(offset),
+            Err(e) => {
+                warn!("NTP clock check against {} failed: {}", server, e);
+                None
+            }
+        },
+        None => None,
+    };
+
+    let skewed = db_offset_ms.abs() > config.max_skew_ms
+        || ntp_offset_ms.is_some_and(|o| o.abs() > config.max_skew_ms);
+    let status = ClockStatus {
+        db_offset_ms,
+        ntp_offset_ms,
+        max_skew_ms: config.max_skew_ms,
+        skewed,
+        checked_at: Utc::now(),
+    };
+
+    if skewed {
+        warn!(
+            "Clock skew exceeds {}ms (database: {}ms, ntp: {:?}ms); checked_at timestamps will be inaccurate",
+            config.max_skew_ms, db_offset_ms, ntp_offset_ms
+        );
+    } else {
+        debug!("Clock offset: database {}ms, ntp {:?}ms", db_offset_ms, ntp_offset_ms);
+    }
+
+    *CURRENT.write().unwrap() = Some(status.clone());
+    Ok(status)
+}
+
+/// Where time-dependent logic reads the current time. Services hold a
+/// [`SharedClock`] so tests can swap in a [`ManualClock`] and run weeks of
+/// checks without waiting.
+pub trait Clock: Debug + Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+pub type SharedClock = Arc<dyn Clock>;
+
+/// The system's wall clock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+pub fn system() -> SharedClock {
+    Arc::new(SystemClock)
+}
+
+/// A clock that stands still until it is moved. Clones share the time.
+#[derive(Debug, Clone)]
+pub struct ManualClock(Arc<Mutex<DateTime<Utc>>>);
+
+impl ManualClock {
+    pub fn new(at: DateTime<Utc>) -> Self {
+        Self(Arc::new(Mutex::new(at)))
+    }
+
+    pub fn set(&self, at: DateTime<Utc>) {
+        *self.0.lock().unwrap() = at;
+    }
+
+    /// Moves the clock forward by `by` and returns the new time.
+    pub fn advance(&self, by: chrono::Duration) -> DateTime<Utc> {
+        let mut now = self.0.lock().unwrap();
+        *now += by;
+        *now
+    }
+
+    pub fn shared(&self) -> SharedClock {
+        Arc::new(self.clone())
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.0.lock().unwrap()
+    }
+}
@@ -0,0 +1,114 @@
+#[cfg(test)]
+mod clock_tests {
+    use crate::ntp::*;
+    use chrono::{DateTime, Duration};
+
+    #[test]
+    fn test_parse_ntp_timestamp() {
+        // 2024-01-01T00:00:00.5Z
+        let seconds: u32 = (1_704_067_200i64 + 2_208_988_800) as u32;
+        let mut bytes = seconds.to_be_bytes().to_vec();
+        bytes.extend_from_slice(&(1u32 << 31).to_be_bytes());
+
+        let parsed = parse_timestamp(&bytes).unwrap();
+        assert_eq!(parsed.timestamp(), 1_704_067_200);
+        assert_eq!(parsed.timestamp_subsec_millis(), 500);
+        assert!(parse_timestamp(&bytes[..6]).is_none());
+    }
+
+    #[test]
+    fn test_clock_offset() {
+        let t1 = DateTime::from_timestamp(1_000, 0).unwrap();
+        // Server is 2s ahead, 100ms each way on the network
+        let t2 = t1 + Duration::milliseconds(2_100);
+        let t3 = t2 + Duration::milliseconds(10);
+        let t4 = t1 + Duration::milliseconds(210);
+
+        assert_eq!(clock_offset_ms(t1, t2, t3, t4), 2_000);
+        assert_eq!(clock_offset_ms(t1, t1, t1, t1), 0);
+    }
+
+    fn response(first_byte: u8, stratum: u8, reference: [u8; 4], server_time: i64) -> Vec<u8> {
+        let mut packet = vec![0u8; 48];
+        packet[0] = first_byte;
+        packet[1] = stratum;
+        packet[12..16].copy_from_slice(&reference);
+        let seconds = ((server_time + 2_208_988_800) as u32).to_be_bytes();
+        packet[32..36].copy_from_slice(&seconds);
+        packet[40..44].copy_from_slice(&seconds);
+        packet
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let sent = DateTime::from_timestamp(1_000, 0).unwrap();
+        let received = sent + Duration::milliseconds(40);
+        let sample = parse_response(&response(0x24, 2, [10, 0, 0, 1], 1_003), sent, received).unwrap();
+        assert_eq!(sample.stratum, 2);
+        assert_eq!(sample.leap_indicator, 0);
+        assert_eq!(sample.reference, "10.0.0.1");
+        assert_eq!(sample.round_trip_ms, 40);
+        assert_eq!(sample.offset_ms, 2_980);
+
+        let gps = parse_response(&response(0x24, 1, *b"GPS\0", 1_000), sent, received).unwrap();
+        assert_eq!(gps.reference, "GPS");
+        assert!(parse_response(&[0u8; 20], sent, received).is_err());
+    }
+
+    #[test]
+    fn test_ntp_check_bounds() {
+        let check = NtpCheck {
+            max_stratum: Some(2),
+            max_offset_ms: Some(100),
+        };
+        let sample = NtpSample {
+            offset_ms: -50,
+            round_trip_ms: 10,
+            stratum: 2,
+            leap_indicator: 0,
+            reference: "10.0.0.1".to_string(),
+        };
+        assert!(check.evaluate(&sample).is_ok());
+        assert!(check.evaluate(&NtpSample { offset_ms: -150, ..sample.clone() }).unwrap_err().contains("-150 ms"));
+        assert!(check.evaluate(&NtpSample { stratum: 3, ..sample.clone() }).unwrap_err().contains("stratum is 3"));
+        let kiss = NtpSample {
+            stratum: 0,
+            reference: "RATE".to_string(),
+            ..sample.clone()
+        };
+        assert_eq!(check.evaluate(&kiss).unwrap_err(), "server clock is not synchronized (kiss code RATE)");
+        assert!(check.evaluate(&NtpSample { leap_indicator: 3, ..sample }).is_err());
+    }
+
+    #[test]
+    fn test_ntp_check_validation() {
+        let check = NtpCheck {
+            max_stratum: Some(3),
+            max_offset_ms: None,
+        };
+        assert!(check.validate("ntp://time.example.com").is_ok());
+        assert_eq!(server_address("ntp://time.example.com:1123").unwrap(), "time.example.com:1123");
+        assert_eq!(server_address("ntp://time.example.com").unwrap(), "time.example.com:123");
+        assert!(check.validate("https://time.example.com").is_err());
+        let unbounded = NtpCheck {
+            max_stratum: Some(16),
+            max_offset_ms: None,
+        };
+        assert!(unbounded.validate("ntp://time.example.com").is_err());
+    }
+
+    #[test]
+    fn test_manual_clock_moves_only_when_told() {
+        use crate::clock::ManualClock;
+
+        let start = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let clock = ManualClock::new(start);
+        let shared = clock.shared();
+        assert_eq!(shared.now(), start);
+
+        assert_eq!(clock.advance(Duration::days(7)), start + Duration::days(7));
+        assert_eq!(shared.now(), start + Duration::days(7));
+        clock.set(start);
+        assert_eq!(shared.now(), start);
+    }
+}
@@ -1,6 +1,9 @@
 use serde::{Deserialize, Serialize};
 use std::env;
 
+/// Placeholder `auth.jwt_secret` used when `JWT_SECRET` is not set
+pub const DEFAULT_JWT_SECRET: &str = "your-secret-key";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DatabaseConfig {
     pub host: String,
@@ -27,6 +30,270 @@ pub struct ServerConfig {
 pub struct AuthConfig {
     pub jwt_secret: String,
     pub jwt_expiration: i64,
+    pub provisioning_token: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScriptingConfig {
+    pub security_profile: String,
+    pub timeout: u64,
+    /// Script engines the scheduler keeps; at most this many scripts run at once
+    pub pool_size: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionConfig {
+    pub result_days: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncryptionConfig {
+    /// Comma separated `<version>:<base64 32-byte key>` entries
+    pub keys: String,
+    pub active_key: u32,
+    pub rotation_batch_size: usize,
+}
+
+/// Subsystems operators can switch off; disabled subsystems expose no routes or jobs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeaturesConfig {
+    pub enable_scripting: bool,
+    pub enable_webhooks: bool,
+    pub enable_provisioning: bool,
+    pub enable_status_pages: bool,
+    pub enable_agents: bool,
+    /// Monitor edits wait for a second user's approval before they apply
+    pub enable_change_approval: bool,
+    /// The API also publishes monitor changes on Redis, for schedulers that
+    /// cannot LISTEN to Postgres (e.g. behind a transaction-mode pooler)
+    pub enable_redis_events: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoggingConfig {
+    /// tracing `EnvFilter` directives, e.g. `info,monitor_scheduler=debug`
+    pub level: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorReportingConfig {
+    pub sentry_dsn: Option<String>,
+    /// Generic alternative to Sentry: reports are POSTed here as JSON
+    pub webhook_url: Option<String>,
+    pub environment: String,
+    /// Defaults to `<binary>@<crate version>`
+    pub release: Option<String>,
+}
+
+/// Where the scheduler keeps results while the database is unreachable.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResultBufferConfig {
+    pub spill_path: String,
+    /// Results beyond this many are dropped until the buffer drains
+    pub capacity: usize,
+    pub failure_threshold: u32,
+    pub open_secs: u64,
+    /// Buffered results written per statement when replaying the spill file
+    pub batch_size: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClockConfig {
+    pub max_skew_ms: i64,
+    /// Optional SNTP server (`host` or `host:port`) checked in addition to the database clock
+    pub ntp_server: Option<String>,
+}
+
+/// Tokio runtime of the API and scheduler processes. 0 keeps tokio's own
+/// default: a worker per CPU core and up to 512 blocking threads.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RuntimeConfig {
+    pub worker_threads: usize,
+    /// Threads for blocking work such as script execution and file I/O
+    pub max_blocking_threads: usize,
+}
+
+/// Connection reuse for the per-host HTTP clients the scheduler checks with.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpClientConfig {
+    pub pool_max_idle_per_host: usize,
+    pub pool_idle_timeout_secs: u64,
+    pub tcp_keepalive_secs: u64,
+    /// Clients kept at once; the least recently used host is dropped beyond this
+    pub max_hosts: usize,
+    /// Defaults for monitors without their own connect or TLS handshake limit
+    pub connect_timeout_ms: u64,
+    pub tls_timeout_ms: u64,
+}
+
+/// Shared DNS cache for checks. Record TTLs are honoured within these bounds.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DnsConfig {
+    pub cache_size: usize,
+    pub min_ttl_secs: u64,
+    pub max_ttl_secs: u64,
+}
+
+/// Check queue capacity and when the scheduler warns that checks are
+/// consistently starting late.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchedulerConfig {
+    pub drift_warn_ms: i64,
+    /// Number of recent checks whose median lag is compared to `drift_warn_ms`
+    pub drift_window: usize,
+    /// Checks this worker runs at once; it claims no more from the queue than that.
+    /// Capped by `Config::check_concurrency` to half the database pool
+    pub max_concurrent_checks: usize,
+    /// How long a claimed check stays reserved before another worker may take it
+    pub claim_lease_secs: i64,
+    /// Monitors queued by the periodic sync start at a random point within
+    /// this many seconds (or their interval, if shorter); 0 starts them at once
+    pub start_spread_secs: i64,
+    /// Each next run moves by up to this share of the interval either way,
+    /// so monitors that started together drift apart; 0 keeps them in step
+    pub jitter_percent: u32,
+}
+
+/// When failures of several monitors are grouped into one correlated incident.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorrelationConfig {
+    pub enabled: bool,
+    /// Monitors sharing a tag or network block that must go down together
+    pub min_monitors: usize,
+    /// How close together their failures must start
+    pub window_secs: i64,
+}
+
+/// `monitor-discovery`: where monitors are discovered and the monitor API
+/// they are written to.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiscoveryConfig {
+    /// Base URL of `monitor-api`
+    pub api_url: String,
+    /// Personal access token with `read:monitors` and `write:monitors`
+    pub api_token: Option<String>,
+    pub interval_secs: u64,
+    pub kubernetes: KubernetesDiscoveryConfig,
+    pub docker: DockerDiscoveryConfig,
+    pub consul: ConsulDiscoveryConfig,
+}
+
+/// Annotated Ingresses and Services.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KubernetesDiscoveryConfig {
+    pub enabled: bool,
+    /// Kubernetes API server; defaults to the in-cluster service account
+    pub cluster_url: Option<String>,
+    /// Only this namespace; all namespaces when unset
+    pub namespace: Option<String>,
+}
+
+/// Labelled running containers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DockerDiscoveryConfig {
+    pub enabled: bool,
+    /// Path of the Docker socket, or `tcp://host:port`
+    pub host: String,
+    /// Host name monitors use to reach published ports
+    pub published_host: String,
+}
+
+/// Consul catalog services carrying `tag`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsulDiscoveryConfig {
+    pub enabled: bool,
+    pub address: String,
+    /// ACL token with read access to the catalog
+    pub token: Option<String>,
+    pub datacenter: Option<String>,
+    /// Catalog tag that opts a service in
+    pub tag: String,
+    /// Base URL of each instance; see `consul::render` for the placeholders
+    pub endpoint_template: String,
+    /// Extra monitor tags, with the same placeholders
+    pub tag_templates: Vec<String>,
+}
+
+/// Outgoing mail for email alert channels.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SmtpConfig {
+    /// Email channels are skipped when unset
+    pub host: Option<String>,
+    pub port: u16,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub from: String,
+    pub tls: SmtpTls,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpTls {
+    /// Plain connection upgraded with `STARTTLS`, usually port 587
+    StartTls,
+    /// TLS from the start, usually port 465
+    Tls,
+    /// Unencrypted, for a relay on the same host or network
+    None,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertingConfig {
+    pub smtp: SmtpConfig,
+}
+
+/// Read-only AWS access for proposing monitors from Route53, load balancers
+/// and CloudFront.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AwsImportConfig {
+    pub access_key_id: Option<String>,
+    pub secret_access_key: Option<String>,
+    /// Only for temporary credentials
+    pub session_token: Option<String>,
+    /// Region of the load balancers; Route53 and CloudFront are global
+    pub region: String,
+}
+
+/// Local commands exec checks may run on the scheduler host.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecConfig {
+    /// Comma separated `name=/absolute/path` entries; exec checks fail when
+    /// their command is not listed
+    pub commands: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportConfig {
+    pub aws: AwsImportConfig,
+}
+
+/// How often one kind of operation is made to fail or stall, in percent
+/// of calls.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FaultRule {
+    pub fail_percent: u32,
+    pub stall_percent: u32,
+    pub stall_ms: u64,
+}
+
+/// Fault injection for resilience testing, see `faults`. Ignored when
+/// `error_reporting.environment` is `production`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FaultsConfig {
+    pub enabled: bool,
+    /// Result writes, which spill to the buffer behind the circuit breaker
+    pub db_writes: FaultRule,
+    pub redis: FaultRule,
+    /// Outbound checks, which are retried like real failures
+    pub checks: FaultRule,
+}
+
+/// Monitors for the monitoring stack itself, see `self_monitoring`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SelfMonitoringConfig {
+    pub enabled: bool,
+    /// Base URL under which the scheduler reaches `monitor-api`'s /health
+    pub api_url: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -35,33 +302,261 @@ pub struct Config {
     pub redis: RedisConfig,
     pub server: ServerConfig,
     pub auth: AuthConfig,
+    pub scripting: ScriptingConfig,
+    pub retention: RetentionConfig,
+    pub encryption: EncryptionConfig,
+    pub features: FeaturesConfig,
+    pub logging: LoggingConfig,
+    pub error_reporting: ErrorReportingConfig,
+    pub result_buffer: ResultBufferConfig,
+    pub runtime: RuntimeConfig,
+    pub clock: ClockConfig,
+    pub scheduler: SchedulerConfig,
+    pub http_client: HttpClientConfig,
+    pub dns: DnsConfig,
+    pub correlation: CorrelationConfig,
+    pub discovery: DiscoveryConfig,
+    pub alerting: AlertingConfig,
+    pub imports: ImportConfig,
+    pub exec: ExecConfig,
+    pub faults: FaultsConfig,
+    pub self_monitoring: SelfMonitoringConfig,
 }
 
 impl Config {
+    /// Checks the scheduler runs at once: `scheduler.max_concurrent_checks`,
+    /// but no more than half of `database.max_connections`, so queue claims,
+    /// result writes and alerting always find a free connection.
+    pub fn check_concurrency(&self) -> usize {
+        let pool_share = (self.database.max_connections / 2).max(1) as usize;
+        self.scheduler.max_concurrent_checks.clamp(1, pool_share)
+    }
+
     pub fn from_env() -> Result<Self, config::ConfigError> {
         let mut cfg = config::Config::builder();
         
         cfg = cfg
             .set_default("database.host", "localhost")?
             .set_default("database.port", 5432)?
-            .set_default("database.max_connections", 10)?
+            .set_default("database.max_connections", 20)?
             .set_default("redis.max_connections", 10)?
             .set_default("server.host", "0.0.0.0")?
             .set_default("server.port", 8080)?
-            .set_default("auth.jwt_expiration", 86400)?;
+            .set_default("auth.jwt_expiration", 86400)?
+            .set_default("scripting.security_profile", "default")?
+            .set_default("scripting.timeout", 30)?
+            .set_default("scripting.pool_size", 4)?
+            .set_default("retention.result_days", 90)?
+            .set_default("encryption.keys", "")?
+            .set_default("encryption.active_key", 1)?
+            .set_default("encryption.rotation_batch_size", 500)?
+            .set_default("features.enable_scripting", true)?
+            .set_default("features.enable_webhooks", true)?
+            .set_default("features.enable_provisioning", true)?
+            .set_default("features.enable_status_pages", false)?
+            .set_default("features.enable_agents", false)?
+            .set_default("features.enable_change_approval", false)?
+            .set_default("features.enable_redis_events", false)?
+            .set_default("logging.level", env::var("RUST_LOG").unwrap_or_else(|_| "info".to_string()))?
+            .set_default("error_reporting.environment", "development")?
+            .set_default("result_buffer.spill_path", "data/result-spill.jsonl")?
+            .set_default("result_buffer.capacity", 10_000)?
+            .set_default("result_buffer.failure_threshold", 3)?
+            .set_default("result_buffer.open_secs", 30)?
+            .set_default("result_buffer.batch_size", 100)?
+            .set_default("runtime.worker_threads", 0)?
+            .set_default("runtime.max_blocking_threads", 0)?
+            .set_default("clock.max_skew_ms", 1000)?
+            .set_default("scheduler.drift_warn_ms", 2000)?
+            .set_default("scheduler.drift_window", 50)?
+            .set_default("scheduler.max_concurrent_checks", 100)?
+            .set_default("scheduler.claim_lease_secs", 300)?
+            .set_default("scheduler.start_spread_secs", 60)?
+            .set_default("scheduler.jitter_percent", 10)?
+            .set_default("http_client.pool_max_idle_per_host", 4)?
+            .set_default("http_client.pool_idle_timeout_secs", 90)?
+            .set_default("http_client.tcp_keepalive_secs", 60)?
+            .set_default("http_client.max_hosts", 1000)?
+            .set_default("http_client.connect_timeout_ms", 5000)?
+            .set_default("http_client.tls_timeout_ms", 5000)?
+            .set_default("dns.cache_size", 1000)?
+            .set_default("dns.min_ttl_secs", 0)?
+            .set_default("dns.max_ttl_secs", 300)?
+            .set_default("correlation.enabled", true)?
+            .set_default("correlation.min_monitors", 3)?
+            .set_default("correlation.window_secs", 120)?
+            .set_default("discovery.api_url", "http://localhost:8080")?
+            .set_default("discovery.interval_secs", 60)?
+            .set_default("discovery.kubernetes.enabled", false)?
+            .set_default("discovery.docker.enabled", false)?
+            .set_default("discovery.docker.host", "/var/run/docker.sock")?
+            .set_default("discovery.docker.published_host", "localhost")?
+            .set_default("discovery.consul.enabled", false)?
+            .set_default("discovery.consul.address", "http://localhost:8500")?
+            .set_default("discovery.consul.tag", "monitor")?
+            .set_default("discovery.consul.endpoint_template", "http://{address}:{port}")?
+            .set_default("discovery.consul.tag_templates", Vec::<String>::new())?
+            .set_default("alerting.smtp.port", 587)?
+            .set_default("alerting.smtp.from", "monitor@localhost")?
+            .set_default("alerting.smtp.tls", "starttls")?
+            .set_default("imports.aws.region", "us-east-1")?
+            .set_default("exec.commands", "")?
+            .set_default("faults.enabled", false)?
+            .set_default("self_monitoring.enabled", true)?
+            .set_default("self_monitoring.api_url", "http://localhost:8080")?
+            .set_default("database.username", "monitor")?
+            .set_default("database.password", "password")?
+            .set_default("database.database", "monitor")?
+            .set_default("redis.url", "redis://localhost:6379")?
+            .set_default("auth.jwt_secret", DEFAULT_JWT_SECRET)?;
+        for target in ["db_writes", "redis", "checks"] {
+            for field in ["fail_percent", "stall_percent", "stall_ms"] {
+                cfg = cfg.set_default(format!("faults.{}.{}", target, field), 0)?;
+            }
+        }
+
+        // Optional TOML/YAML/JSON file; environment variables still take precedence
+        if let Some(path) = Self::file_path() {
+            cfg = cfg.add_source(config::File::from(path));
+        }
 
         if let Ok(database_url) = env::var("DATABASE_URL") {
             cfg = cfg.set_override("database.url", database_url)?;
         } else {
-            cfg = cfg
-                .set_override("database.username", env::var("DATABASE_USERNAME").unwrap_or_else(|_| "monitor".to_string()))?
-                .set_override("database.password", env::var("DATABASE_PASSWORD").unwrap_or_else(|_| "password".to_string()))?
-                .set_override("database.database", env::var("DATABASE_NAME").unwrap_or_else(|_| "monitor".to_string()))?;
+            for (var, key) in [
+                ("DATABASE_USERNAME", "database.username"),
+                ("DATABASE_PASSWORD", "database.password"),
+                ("DATABASE_NAME", "database.database"),
+            ] {
+                if let Ok(value) = env::var(var) {
+                    cfg = cfg.set_override(key, value)?;
+                }
+            }
         }
 
-        cfg = cfg
-            .set_override("redis.url", env::var("REDIS_URL").unwrap_or_else(|_| "redis://localhost:6379".to_string()))?
-            .set_override("auth.jwt_secret", env::var("JWT_SECRET").unwrap_or_else(|_| "your-secret-key".to_string()))?;
+        if let Ok(url) = env::var("REDIS_URL") {
+            cfg = cfg.set_override("redis.url", url)?;
+        }
+
+        if let Ok(secret) = env::var("JWT_SECRET") {
+            cfg = cfg.set_override("auth.jwt_secret", secret)?;
+        }
+
+        for (var, key) in [
+            ("SENTRY_DSN", "error_reporting.sentry_dsn"),
+            ("ERROR_REPORT_WEBHOOK_URL", "error_reporting.webhook_url"),
+            ("ENVIRONMENT", "error_reporting.environment"),
+            ("RELEASE", "error_reporting.release"),
+        ] {
+            if let Ok(value) = env::var(var) {
+                cfg = cfg.set_override(key, value)?;
+            }
+        }
+
+        if let Ok(server) = env::var("NTP_SERVER") {
+            cfg = cfg.set_override("clock.ntp_server", server)?;
+        }
+
+        if let Ok(path) = env::var("RESULT_SPILL_PATH") {
+            cfg = cfg.set_override("result_buffer.spill_path", path)?;
+        }
+
+        if let Ok(level) = env::var("LOG_LEVEL") {
+            cfg = cfg.set_override("logging.level", level)?;
+        }
+
+        if let Ok(token) = env::var("PROVISIONING_TOKEN") {
+            cfg = cfg.set_override("auth.provisioning_token", token)?;
+        }
+
+        if let Ok(profile) = env::var("SCRIPT_SECURITY_PROFILE") {
+            cfg = cfg.set_override("scripting.security_profile", profile)?;
+        }
+
+        if let Ok(days) = env::var("RESULT_RETENTION_DAYS") {
+            cfg = cfg.set_override("retention.result_days", days.parse::<i64>().unwrap_or(90))?;
+        }
+
+        if let Ok(keys) = env::var("ENCRYPTION_KEYS") {
+            cfg = cfg.set_override("encryption.keys", keys)?;
+        }
+
+        if let Ok(active) = env::var("ENCRYPTION_ACTIVE_KEY") {
+            cfg = cfg.set_override("encryption.active_key", active.parse::<u32>().unwrap_or(1))?;
+        }
+
+        for flag in [
+            "enable_scripting",
+            "enable_webhooks",
+            "enable_provisioning",
+            "enable_status_pages",
+            "enable_agents",
+            "enable_change_approval",
+            "enable_redis_events",
+        ] {
+            if let Ok(value) = env::var(flag.to_uppercase()) {
+                let enabled = matches!(value.to_lowercase().as_str(), "1" | "true" | "yes" | "on");
+                cfg = cfg.set_override(format!("features.{}", flag), enabled)?;
+            }
+        }
+
+        for (var, key) in [
+            ("MONITOR_API_URL", "discovery.api_url"),
+            ("MONITOR_API_TOKEN", "discovery.api_token"),
+            ("K8S_API_URL", "discovery.kubernetes.cluster_url"),
+            ("K8S_NAMESPACE", "discovery.kubernetes.namespace"),
+            ("DOCKER_HOST", "discovery.docker.host"),
+            ("DOCKER_PUBLISHED_HOST", "discovery.docker.published_host"),
+            ("CONSUL_HTTP_ADDR", "discovery.consul.address"),
+            ("CONSUL_HTTP_TOKEN", "discovery.consul.token"),
+            ("SMTP_HOST", "alerting.smtp.host"),
+            ("SMTP_USERNAME", "alerting.smtp.username"),
+            ("SMTP_PASSWORD", "alerting.smtp.password"),
+            ("SMTP_FROM", "alerting.smtp.from"),
+            ("SMTP_TLS", "alerting.smtp.tls"),
+            ("AWS_ACCESS_KEY_ID", "imports.aws.access_key_id"),
+            ("AWS_SECRET_ACCESS_KEY", "imports.aws.secret_access_key"),
+            ("AWS_SESSION_TOKEN", "imports.aws.session_token"),
+            ("AWS_REGION", "imports.aws.region"),
+            ("EXEC_COMMANDS", "exec.commands"),
+            ("SELF_MONITORING_API_URL", "self_monitoring.api_url"),
+        ] {
+            if let Ok(value) = env::var(var) {
+                cfg = cfg.set_override(key, value)?;
+            }
+        }
+
+        for (var, key) in [
+            ("DISCOVER_KUBERNETES", "discovery.kubernetes.enabled"),
+            ("DISCOVER_DOCKER", "discovery.docker.enabled"),
+            ("DISCOVER_CONSUL", "discovery.consul.enabled"),
+            ("FAULT_INJECTION", "faults.enabled"),
+            ("SELF_MONITORING", "self_monitoring.enabled"),
+        ] {
+            if let Ok(value) = env::var(var) {
+                let enabled = matches!(value.to_lowercase().as_str(), "1" | "true" | "yes" | "on");
+                cfg = cfg.set_override(key, enabled)?;
+            }
+        }
+
+        if let Ok(port) = env::var("SMTP_PORT") {
+            cfg = cfg.set_override("alerting.smtp.port", port.parse::<u16>().unwrap_or(587))?;
+        }
+
+        for (var, key) in [
+            ("WORKER_THREADS", "runtime.worker_threads"),
+            ("MAX_BLOCKING_THREADS", "runtime.max_blocking_threads"),
+            ("MAX_CONCURRENT_CHECKS", "scheduler.max_concurrent_checks"),
+            ("RESULT_BATCH_SIZE", "result_buffer.batch_size"),
+            ("SCRIPT_POOL_SIZE", "scripting.pool_size"),
+        ] {
+            if let Ok(value) = env::var(var) {
+                let value = value
+                    .parse::<u64>()
+                    .map_err(|_| config::ConfigError::Message(format!("{} must be a whole number", var)))?;
+                cfg = cfg.set_override(key, value)?;
+            }
+        }
 
         if let Ok(port) = env::var("PORT") {
             cfg = cfg.set_override("server.port", port.parse::<u16>().unwrap_or(8080))?;
@@ -69,4 +564,9 @@ impl Config {
 
         cfg.build()?.try_deserialize()
     }
+
+    /// Config file named by `CONFIG_FILE`, if any.
+    pub fn file_path() -> Option<std::path::PathBuf> {
+        env::var("CONFIG_FILE").ok().filter(|p| !p.is_empty()).map(Into::into)
+    }
 }
\ No newline at end of file
@@ -0,0 +1,26 @@
+#[cfg(test)]
+mod config_tests {
+    use crate::config::Config;
+
+    #[test]
+    fn check_concurrency_is_capped_at_half_the_pool() {
+        let mut config = Config::from_env().unwrap();
+        config.database.max_connections = 20;
+        config.scheduler.max_concurrent_checks = 100;
+        assert_eq!(config.check_concurrency(), 10);
+
+        config.scheduler.max_concurrent_checks = 4;
+        assert_eq!(config.check_concurrency(), 4);
+    }
+
+    #[test]
+    fn check_concurrency_is_at_least_one() {
+        let mut config = Config::from_env().unwrap();
+        config.database.max_connections = 1;
+        assert_eq!(config.check_concurrency(), 1);
+
+        config.database.max_connections = 20;
+        config.scheduler.max_concurrent_checks = 0;
+        assert_eq!(config.check_concurrency(), 1);
+    }
+}
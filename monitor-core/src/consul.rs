@@ -0,0 +1,101 @@
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use crate::{
+    config::ConsulDiscoveryConfig,
+    discovery::{self, DesiredMonitor, Discovered, CONSUL, LABEL_PREFIX},
+    error::Result,
+};
+
+/// Service meta keys may not contain `.` or `/`, so the options Kubernetes and
+/// Docker read from `monitor.yeheng.io/<key>` are read from `monitor_<key>`.
+pub const META_PREFIX: &str = "monitor_";
+
+const META_KEYS: &[&str] = &["name", "path", "url", "interval", "expected-status", "tags"];
+
+/// One instance of a service, as listed by `GET /v1/catalog/service/<name>`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct CatalogService {
+    pub node: String,
+    /// Address of the node, used when the service has none of its own
+    pub address: String,
+    #[serde(rename = "ServiceID")]
+    pub service_id: String,
+    pub service_name: String,
+    #[serde(default)]
+    pub service_address: String,
+    pub service_port: u16,
+    #[serde(default)]
+    pub service_tags: Vec<String>,
+    #[serde(default)]
+    pub service_meta: BTreeMap<String, String>,
+}
+
+impl CatalogService {
+    pub fn address(&self) -> &str {
+        if self.service_address.is_empty() {
+            &self.address
+        } else {
+            &self.service_address
+        }
+    }
+}
+
+/// `service/<name>/<id>`, also used in the source tag.
+pub fn source_name(instance: &CatalogService) -> String {
+    format!("service/{}/{}", instance.service_name, instance.service_id)
+}
+
+/// Replaces `{service}`, `{id}`, `{node}`, `{address}` and `{port}`. IPv6
+/// addresses are bracketed so they can be used in URLs.
+pub fn render(template: &str, instance: &CatalogService) -> String {
+    let address = match instance.address() {
+        address if address.contains(':') => format!("[{}]", address),
+        address => address.to_string(),
+    };
+    template
+        .replace("{service}", &instance.service_name)
+        .replace("{id}", &instance.service_id)
+        .replace("{node}", &instance.node)
+        .replace("{address}", &address)
+        .replace("{port}", &instance.service_port.to_string())
+}
+
+/// `None` unless the instance carries the configured opt-in tag. The
+/// endpoint is the rendered `endpoint_template` followed by `monitor_path`;
+/// monitor tags are the instance's other Consul tags, the rendered
+/// `tag_templates` and `monitor_tags`.
+pub fn from_instance(instance: &CatalogService, config: &ConsulDiscoveryConfig) -> Result<Option<DesiredMonitor>> {
+    if !instance.service_tags.contains(&config.tag) {
+        return Ok(None);
+    }
+
+    let mut labels: BTreeMap<String, String> = META_KEYS
+        .iter()
+        .filter_map(|key| {
+            let value = instance.service_meta.get(&format!("{}{}", META_PREFIX, key.replace('-', "_")))?;
+            Some((format!("{}{}", LABEL_PREFIX, key), value.clone()))
+        })
+        .collect();
+    labels.insert(format!("{}enabled", LABEL_PREFIX), "true".to_string());
+
+    let tags: Vec<String> = instance
+        .service_tags
+        .iter()
+        .filter(|tag| **tag != config.tag)
+        .cloned()
+        .chain(config.tag_templates.iter().map(|template| render(template, instance)))
+        .chain(labels.get(&format!("{}tags", LABEL_PREFIX)).cloned())
+        .collect();
+    labels.insert(format!("{}tags", LABEL_PREFIX), tags.join(","));
+
+    let default_name = format!("{}/{}", instance.service_name, instance.service_id);
+    let base_url = render(&config.endpoint_template, instance);
+    CONSUL.desired(source_name(instance), default_name, &labels, Some(base_url))
+}
+
+/// Monitors asked for by tagged instances, and the instances whose meta
+/// could not be read, with why.
+pub fn desired_monitors(instances: &[CatalogService], config: &ConsulDiscoveryConfig) -> Discovered {
+    discovery::collect(instances.iter().map(|i| (source_name(i), from_instance(i, config))))
+}
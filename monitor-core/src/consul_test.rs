@@ -0,0 +1,73 @@
+#[cfg(test)]
+mod consul_tests {
+    use crate::{config::ConsulDiscoveryConfig, consul::*};
+    use serde_json::json;
+
+    fn config() -> ConsulDiscoveryConfig {
+        ConsulDiscoveryConfig {
+            enabled: true,
+            address: "http://localhost:8500".to_string(),
+            token: None,
+            datacenter: None,
+            tag: "monitor".to_string(),
+            endpoint_template: "http://{address}:{port}".to_string(),
+            tag_templates: vec!["service:{service}".to_string()],
+        }
+    }
+
+    fn instance(tags: serde_json::Value, meta: serde_json::Value) -> CatalogService {
+        serde_json::from_value(json!({
+            "Node": "node-1",
+            "Address": "10.0.0.5",
+            "ServiceID": "billing-1",
+            "ServiceName": "billing",
+            "ServiceAddress": "",
+            "ServicePort": 9000,
+            "ServiceTags": tags,
+            "ServiceMeta": meta
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn tagged_instances_build_a_monitor() {
+        let desired = from_instance(
+            &instance(json!(["monitor", "payments"]), json!({ "monitor_path": "/health", "monitor_expected_status": "204" })),
+            &config(),
+        )
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(desired.source, "service/billing/billing-1");
+        assert_eq!(desired.name, "billing/billing-1");
+        assert_eq!(desired.endpoint, "http://10.0.0.5:9000/health");
+        assert_eq!(desired.expected_status, 204.into());
+        assert_eq!(
+            desired.tags,
+            vec!["consul", "consul:service/billing/billing-1", "payments", "service:billing"]
+        );
+    }
+
+    #[test]
+    fn untagged_instances_are_ignored() {
+        assert!(from_instance(&instance(json!(["payments"]), json!({})), &config()).unwrap().is_none());
+    }
+
+    #[test]
+    fn invalid_meta_is_an_error() {
+        let broken = instance(json!(["monitor"]), json!({ "monitor_interval": "often" }));
+        let (desired, invalid) = desired_monitors(&[broken], &config());
+        assert!(desired.is_empty());
+        assert_eq!(invalid[0].0, "service/billing/billing-1");
+    }
+
+    #[test]
+    fn templates_use_the_service_address() {
+        let mut instance = instance(json!(["monitor"]), json!({}));
+        instance.service_address = "fd00::7".to_string();
+        assert_eq!(
+            render("https://{address}:{port}/{service}/{id}@{node}", &instance),
+            "https://[fd00::7]:9000/billing/billing-1@node-1"
+        );
+    }
+}
@@ -0,0 +1,188 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::FromRow;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    net::IpAddr,
+};
+use uuid::Uuid;
+use crate::{db::DatabasePool, error::Result};
+
+/// Monitors sharing a tag
+pub const KIND_TAG: &str = "tag";
+/// Monitors whose targets resolve into the same /24 (IPv4) or /64 (IPv6)
+pub const KIND_NETWORK: &str = "network";
+
+/// Advisory lock key so only one scheduler process runs the analyzer at a time
+pub const ANALYZER_LOCK_KEY: i64 = 0x636f_7272_656c_6174;
+
+/// An enabled monitor whose latest status change was into a failing status.
+#[derive(Debug, Clone, FromRow)]
+pub struct DownMonitor {
+    pub monitor_id: Uuid,
+    pub name: String,
+    pub endpoint: String,
+    pub tags: Vec<String>,
+    pub went_down_at: DateTime<Utc>,
+    /// Network block of the target, filled in by the caller after resolving it
+    #[sqlx(skip)]
+    pub network: Option<String>,
+}
+
+impl DownMonitor {
+    fn keys(&self) -> impl Iterator<Item = (&'static str, &str)> {
+        self.tags
+            .iter()
+            .map(|tag| (KIND_TAG, tag.as_str()))
+            .chain(self.network.as_deref().map(|network| (KIND_NETWORK, network)))
+    }
+
+    pub fn belongs_to(&self, kind: &str, key: &str) -> bool {
+        self.keys().any(|(k, v)| k == kind && v == key)
+    }
+}
+
+/// A group of monitors that went down together.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Correlation {
+    pub kind: &'static str,
+    pub key: String,
+    pub monitor_ids: BTreeSet<Uuid>,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct CorrelatedIncident {
+    pub id: Uuid,
+    pub kind: String,
+    pub group_key: String,
+    pub monitor_ids: Vec<Uuid>,
+    pub started_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
+/// The network block `ip` is grouped under.
+pub fn network_block(ip: IpAddr) -> String {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, c, _] = ip.octets();
+            format!("{}.{}.{}.0/24", a, b, c)
+        }
+        IpAddr::V6(ip) => {
+            let s = ip.segments();
+            format!("{:x}:{:x}:{:x}:{:x}::/64", s[0], s[1], s[2], s[3])
+        }
+    }
+}
+
+/// Groups monitors that went down at or after `since` by tag and network
+/// block. Groups smaller than `min_monitors` are dropped, as are groups whose
+/// monitors are all part of a larger group, so one outage yields one group.
+pub fn correlate(down: &[DownMonitor], since: DateTime<Utc>, min_monitors: usize) -> Vec<Correlation> {
+    let mut groups: BTreeMap<(&'static str, &str), BTreeSet<Uuid>> = BTreeMap::new();
+    for monitor in down.iter().filter(|m| m.went_down_at >= since) {
+        for key in monitor.keys() {
+            groups.entry(key).or_default().insert(monitor.monitor_id);
+        }
+    }
+
+    let mut candidates: Vec<Correlation> = groups
+        .into_iter()
+        .filter(|(_, ids)| ids.len() >= min_monitors.max(2))
+        .map(|((kind, key), monitor_ids)| Correlation { kind, key: key.to_string(), monitor_ids })
+        .collect();
+    // Largest first; ties keep key order
+    candidates.sort_by_key(|c| std::cmp::Reverse(c.monitor_ids.len()));
+
+    let mut kept: Vec<Correlation> = Vec::new();
+    for candidate in candidates {
+        if !kept.iter().any(|k| candidate.monitor_ids.is_subset(&k.monitor_ids)) {
+            kept.push(candidate);
+        }
+    }
+    kept
+}
+
+pub async fn down_monitors(db: &DatabasePool) -> Result<Vec<DownMonitor>> {
+    let monitors = sqlx::query_as::<_, DownMonitor>(
+        r#"
+        SELECT latest.monitor_id, m.name, m.endpoint, m.tags, latest.changed_at AS went_down_at
+        FROM (
+            SELECT DISTINCT ON (monitor_id) monitor_id, status, changed_at
+            FROM monitor_state_changes
+            ORDER BY monitor_id, seq DESC
+        ) latest
+        JOIN monitors m ON m.id = latest.monitor_id
+        WHERE m.enabled = true AND latest.status <> 'success'
+        "#,
+    )
+    .fetch_all(db)
+    .await?;
+    Ok(monitors)
+}
+
+pub async fn open_incidents(db: &DatabasePool) -> Result<Vec<CorrelatedIncident>> {
+    let incidents = sqlx::query_as::<_, CorrelatedIncident>(
+        "SELECT * FROM correlated_incidents WHERE resolved_at IS NULL ORDER BY started_at",
+    )
+    .fetch_all(db)
+    .await?;
+    Ok(incidents)
+}
+
+pub async fn open(db: &DatabasePool, correlation: &Correlation, now: DateTime<Utc>) -> Result<CorrelatedIncident> {
+    let incident = sqlx::query_as::<_, CorrelatedIncident>(
+        r#"
+        INSERT INTO correlated_incidents (id, kind, group_key, monitor_ids, started_at, last_seen_at)
+        VALUES ($1, $2, $3, $4, $5, $5)
+        RETURNING *
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(correlation.kind)
+    .bind(&correlation.key)
+    .bind(correlation.monitor_ids.iter().copied().collect::<Vec<_>>())
+    .bind(now)
+    .fetch_one(db)
+    .await?;
+    Ok(incident)
+}
+
+/// Records the monitors currently down in an open incident; monitors that
+/// recovered stay listed.
+pub async fn touch(db: &DatabasePool, id: Uuid, monitor_ids: &[Uuid], now: DateTime<Utc>) -> Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE correlated_incidents
+        SET monitor_ids = ARRAY(SELECT DISTINCT unnest(monitor_ids || $2::uuid[])), last_seen_at = $3
+        WHERE id = $1
+        "#,
+    )
+    .bind(id)
+    .bind(monitor_ids)
+    .bind(now)
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+pub async fn resolve(db: &DatabasePool, id: Uuid, now: DateTime<Utc>) -> Result<()> {
+    sqlx::query("UPDATE correlated_incidents SET resolved_at = $2 WHERE id = $1")
+        .bind(id)
+        .bind(now)
+        .execute(db)
+        .await?;
+    Ok(())
+}
+
+/// Whether the monitor is part of an open incident, whose notification stands
+/// in for the monitor's own failure notifications.
+pub async fn is_correlated(db: &DatabasePool, monitor_id: Uuid) -> Result<bool> {
+    let correlated = sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM correlated_incidents WHERE resolved_at IS NULL AND $1 = ANY(monitor_ids))",
+    )
+    .bind(monitor_id)
+    .fetch_one(db)
+    .await?;
+    Ok(correlated)
+}
@@ -0,0 +1,62 @@
+#[cfg(test)]
+mod correlation_tests {
+    use crate::correlation::*;
+    use chrono::{Duration, Utc};
+    use uuid::Uuid;
+
+    fn down(tags: &[&str], network: Option<&str>, minutes_ago: i64) -> DownMonitor {
+        DownMonitor {
+            monitor_id: Uuid::new_v4(),
+            name: "api".to_string(),
+            endpoint: "https://example.com".to_string(),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            went_down_at: Utc::now() - Duration::minutes(minutes_ago),
+            network: network.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_network_block() {
+        assert_eq!(network_block("203.0.113.42".parse().unwrap()), "203.0.113.0/24");
+        assert_eq!(network_block("2001:db8:1:2:3::1".parse().unwrap()), "2001:db8:1:2::/64");
+    }
+
+    #[test]
+    fn groups_by_shared_tag_above_threshold() {
+        let monitors = vec![
+            down(&["eu"], None, 0),
+            down(&["eu"], None, 0),
+            down(&["eu"], None, 0),
+            down(&["us"], None, 0),
+        ];
+        let groups = correlate(&monitors, Utc::now() - Duration::minutes(2), 3);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].kind, KIND_TAG);
+        assert_eq!(groups[0].key, "eu");
+        assert_eq!(groups[0].monitor_ids.len(), 3);
+    }
+
+    #[test]
+    fn ignores_monitors_that_went_down_before_the_window() {
+        let monitors = vec![
+            down(&["eu"], None, 0),
+            down(&["eu"], None, 0),
+            down(&["eu"], None, 30),
+        ];
+        assert!(correlate(&monitors, Utc::now() - Duration::minutes(2), 3).is_empty());
+    }
+
+    #[test]
+    fn overlapping_groups_collapse_into_the_largest() {
+        let block = Some("203.0.113.0/24");
+        let monitors = vec![
+            down(&["eu", "db"], block, 0),
+            down(&["eu", "db"], block, 0),
+            down(&["eu"], block, 0),
+        ];
+        let groups = correlate(&monitors, Utc::now() - Duration::minutes(2), 2);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].monitor_ids.len(), 3);
+        assert!(monitors[0].belongs_to(KIND_NETWORK, "203.0.113.0/24"));
+    }
+}
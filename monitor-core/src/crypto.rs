@@ -0,0 +1,147 @@
+use aes_gcm::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    Aes256Gcm, Key, Nonce,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use crate::{config::{EncryptionConfig, DEFAULT_JWT_SECRET}, error::Result, Error};
+
+const PREFIX: &str = "enc:v";
+const NONCE_LEN: usize = 12;
+/// Version of the key derived from the JWT secret when no keys are configured
+const DERIVED_KEY_VERSION: u32 = 1;
+
+/// Versioned AES-256-GCM keys. New values are always encrypted with the active
+/// key; older versions are kept so existing ciphertexts stay readable until the
+/// re-encryption job has rotated them.
+///
+/// Ciphertexts are stored as `enc:v<version>:<base64(nonce || ciphertext)>`.
+/// Values without that prefix are treated as legacy plaintext.
+#[derive(Clone)]
+pub struct KeyRing {
+    keys: BTreeMap<u32, Key<Aes256Gcm>>,
+    active: u32,
+}
+
+impl std::fmt::Debug for KeyRing {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KeyRing")
+            .field("versions", &self.keys.keys().collect::<Vec<_>>())
+            .field("active", &self.active)
+            .finish()
+    }
+}
+
+impl KeyRing {
+    pub fn new(keys: BTreeMap<u32, [u8; 32]>, active: u32) -> Result<Self> {
+        if !keys.contains_key(&active) {
+            return Err(Error::validation(format!("Active encryption key v{} is not configured", active)));
+        }
+        Ok(Self {
+            keys: keys.into_iter().map(|(v, k)| (v, k.into())).collect(),
+            active,
+        })
+    }
+
+    /// Builds the key ring from `encryption.keys` (`"1:<base64>,2:<base64>"`).
+    /// Without configured keys a single v1 key is derived from `fallback_secret`,
+    /// which must then not be the built-in default. Once keys are configured the
+    /// derived key stays readable as v1, unless that version is taken, so the
+    /// re-encryption job can move existing secrets to the active key.
+    pub fn from_config(config: &EncryptionConfig, fallback_secret: &str) -> Result<Self> {
+        let mut keys = BTreeMap::new();
+
+        for entry in config.keys.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let (version, encoded) = entry
+                .split_once(':')
+                .ok_or_else(|| Error::validation("Encryption keys must be formatted as <version>:<base64>"))?;
+            let version: u32 = version
+                .parse()
+                .map_err(|_| Error::validation(format!("Invalid encryption key version: {}", version)))?;
+            let bytes = STANDARD
+                .decode(encoded)
+                .map_err(|e| Error::validation(format!("Invalid encryption key v{}: {}", version, e)))?;
+            let key: [u8; 32] = bytes
+                .try_into()
+                .map_err(|_| Error::validation(format!("Encryption key v{} must be 32 bytes", version)))?;
+            keys.insert(version, key);
+        }
+
+        let derived: [u8; 32] = Sha256::digest(fallback_secret.as_bytes()).into();
+        if keys.is_empty() {
+            if fallback_secret == DEFAULT_JWT_SECRET || fallback_secret.trim().is_empty() {
+                return Err(Error::validation(
+                    "No encryption keys configured and JWT_SECRET is the default; set ENCRYPTION_KEYS or JWT_SECRET",
+                ));
+            }
+            tracing::warn!("No encryption keys configured, deriving one from the JWT secret");
+            keys.insert(DERIVED_KEY_VERSION, derived);
+            return Self::new(keys, DERIVED_KEY_VERSION);
+        }
+
+        let mut ring = Self::new(keys, config.active_key)?;
+        ring.keys.entry(DERIVED_KEY_VERSION).or_insert_with(|| derived.into());
+        Ok(ring)
+    }
+
+    pub fn active_version(&self) -> u32 {
+        self.active
+    }
+
+    pub fn versions(&self) -> Vec<u32> {
+        self.keys.keys().copied().collect()
+    }
+
+    pub fn encrypt(&self, plaintext: &str) -> Result<String> {
+        let cipher = Aes256Gcm::new(&self.keys[&self.active]);
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = cipher
+            .encrypt(&nonce, plaintext.as_bytes())
+            .map_err(|e| Error::internal(format!("Encryption failed: {}", e)))?;
+
+        let mut payload = nonce.to_vec();
+        payload.extend_from_slice(&ciphertext);
+        Ok(format!("{}{}:{}", PREFIX, self.active, STANDARD.encode(payload)))
+    }
+
+    pub fn decrypt(&self, value: &str) -> Result<String> {
+        let Some((version, encoded)) = parse(value) else {
+            return Ok(value.to_string());
+        };
+
+        let key = self
+            .keys
+            .get(&version)
+            .ok_or_else(|| Error::internal(format!("Encryption key v{} is no longer configured", version)))?;
+        let payload = STANDARD
+            .decode(encoded)
+            .map_err(|e| Error::internal(format!("Corrupt ciphertext: {}", e)))?;
+        if payload.len() < NONCE_LEN {
+            return Err(Error::internal("Corrupt ciphertext: too short"));
+        }
+
+        let (nonce, ciphertext) = payload.split_at(NONCE_LEN);
+        let plaintext = Aes256Gcm::new(key)
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| Error::internal(format!("Decryption with key v{} failed", version)))?;
+
+        String::from_utf8(plaintext).map_err(|e| Error::internal(format!("Decrypted value is not UTF-8: {}", e)))
+    }
+
+    /// True when a stored value is plaintext or encrypted with a non-active key.
+    pub fn needs_rotation(&self, value: &str) -> bool {
+        key_version(value) != Some(self.active)
+    }
+}
+
+fn parse(value: &str) -> Option<(u32, &str)> {
+    let rest = value.strip_prefix(PREFIX)?;
+    let (version, encoded) = rest.split_once(':')?;
+    Some((version.parse().ok()?, encoded))
+}
+
+/// Returns the key version of a stored value, or `None` for legacy plaintext.
+pub fn key_version(value: &str) -> Option<u32> {
+    parse(value).map(|(version, _)| version)
+}
@@ -0,0 +1,95 @@
+#[cfg(test)]
+mod crypto_tests {
+    use crate::{
+        config::{DEFAULT_JWT_SECRET, EncryptionConfig},
+        crypto::*,
+    };
+    use base64::{Engine, engine::general_purpose::STANDARD};
+    use std::collections::BTreeMap;
+
+    const JWT_SECRET: &str = "a-long-random-jwt-secret-for-tests";
+
+    fn config(keys: &[u32], active_key: u32) -> EncryptionConfig {
+        EncryptionConfig {
+            keys: keys
+                .iter()
+                .map(|v| format!("{}:{}", v, STANDARD.encode([*v as u8; 32])))
+                .collect::<Vec<_>>()
+                .join(","),
+            active_key,
+            rotation_batch_size: 500,
+        }
+    }
+
+    fn ring(versions: &[u32], active: u32) -> KeyRing {
+        let keys: BTreeMap<u32, [u8; 32]> = versions.iter().map(|v| (*v, [*v as u8; 32])).collect();
+        KeyRing::new(keys, active).unwrap()
+    }
+
+    #[test]
+    fn test_encrypt_decrypt_roundtrip() {
+        let keys = ring(&[1], 1);
+        let ciphertext = keys.encrypt("Bearer secret-token").unwrap();
+        assert!(ciphertext.starts_with("enc:v1:"));
+        assert_ne!(keys.encrypt("Bearer secret-token").unwrap(), ciphertext);
+        assert_eq!(keys.decrypt(&ciphertext).unwrap(), "Bearer secret-token");
+    }
+
+    #[test]
+    fn test_rotation_keeps_old_versions_readable() {
+        let old = ring(&[1], 1).encrypt("value").unwrap();
+        let rotated = ring(&[1, 2], 2);
+
+        assert_eq!(key_version(&old), Some(1));
+        assert!(rotated.needs_rotation(&old));
+        assert_eq!(rotated.decrypt(&old).unwrap(), "value");
+
+        let fresh = rotated.encrypt("value").unwrap();
+        assert_eq!(key_version(&fresh), Some(2));
+        assert!(!rotated.needs_rotation(&fresh));
+    }
+
+    #[test]
+    fn test_plaintext_is_passed_through_and_flagged() {
+        let keys = ring(&[1], 1);
+        assert_eq!(keys.decrypt("legacy").unwrap(), "legacy");
+        assert!(keys.needs_rotation("legacy"));
+        assert_eq!(key_version("legacy"), None);
+    }
+
+    #[test]
+    fn test_missing_or_wrong_key_fails() {
+        let ciphertext = ring(&[1], 1).encrypt("value").unwrap();
+        assert!(ring(&[2], 2).decrypt(&ciphertext).is_err());
+
+        let tampered = ciphertext.replace("enc:v1:", "enc:v1:AAAA");
+        assert!(ring(&[1], 1).decrypt(&tampered).is_err());
+        assert!(KeyRing::new(BTreeMap::new(), 1).is_err());
+    }
+
+    #[test]
+    fn test_default_jwt_secret_is_refused_without_keys() {
+        assert!(KeyRing::from_config(&config(&[], 1), DEFAULT_JWT_SECRET).is_err());
+        assert!(KeyRing::from_config(&config(&[], 1), "  ").is_err());
+
+        let derived = KeyRing::from_config(&config(&[], 1), JWT_SECRET).unwrap();
+        assert_eq!(derived.versions(), vec![1]);
+        assert!(KeyRing::from_config(&config(&[2], 2), DEFAULT_JWT_SECRET).is_ok());
+    }
+
+    #[test]
+    fn test_derived_key_stays_readable_once_keys_are_configured() {
+        let old = KeyRing::from_config(&config(&[], 1), JWT_SECRET).unwrap().encrypt("value").unwrap();
+        let configured = KeyRing::from_config(&config(&[2], 2), JWT_SECRET).unwrap();
+        assert_eq!(configured.versions(), vec![1, 2]);
+        assert_eq!(configured.active_version(), 2);
+        assert_eq!(configured.decrypt(&old).unwrap(), "value");
+        assert!(configured.needs_rotation(&old));
+
+        // A configured v1 takes precedence over the derived key
+        let replaced = KeyRing::from_config(&config(&[1], 1), JWT_SECRET).unwrap();
+        assert!(replaced.decrypt(&old).is_err());
+        // The derived key never becomes the active one by accident
+        assert!(KeyRing::from_config(&config(&[2], 1), JWT_SECRET).is_err());
+    }
+}
@@ -0,0 +1,93 @@
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use crate::{error::Result, Error};
+
+const MAX_QUERY_LEN: usize = 1024;
+/// Statement run on SQL servers when the check sets none
+pub const DEFAULT_QUERY: &str = "SELECT 1";
+/// Keys read from the monitor's decrypted credentials
+pub const USERNAME: &str = "username";
+pub const PASSWORD: &str = "password";
+
+/// A database check run instead of the HTTP request. The monitor's
+/// `endpoint` is the DSN, `postgres://`, `mysql://`, `redis://` or
+/// `rediss://`, without a password; the password comes from the monitor's
+/// credentials, which are stored encrypted.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DatabaseCheck {
+    /// Statement run after connecting on SQL servers, `SELECT 1` by default.
+    /// Redis servers are sent `PING`.
+    #[serde(default)]
+    pub query: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Engine {
+    Postgres,
+    Mysql,
+    Redis,
+}
+
+impl Engine {
+    pub fn is_sql(&self) -> bool {
+        !matches!(self, Engine::Redis)
+    }
+}
+
+impl DatabaseCheck {
+    pub fn validate(&self, endpoint: &str) -> Result<()> {
+        let url = parse(endpoint)?;
+        let engine = engine(&url)?;
+        if url.host_str().is_none_or(str::is_empty) {
+            return Err(Error::validation("database_check endpoints must name a host"));
+        }
+        if url.password().is_some() {
+            return Err(Error::validation(
+                "database_check endpoints must not contain a password; set it in the monitor's credentials",
+            ));
+        }
+        if let Some(query) = &self.query {
+            if !engine.is_sql() {
+                return Err(Error::validation("query applies to postgres:// and mysql:// endpoints only"));
+            }
+            let statement = query.trim().trim_end_matches(';');
+            if statement.trim().is_empty() || query.len() > MAX_QUERY_LEN {
+                return Err(Error::validation(format!("query must be 1 to {} bytes", MAX_QUERY_LEN)));
+            }
+            if statement.contains(';') {
+                return Err(Error::validation("query must be a single statement"));
+            }
+        }
+        Ok(())
+    }
+
+    /// The statement sent to SQL servers.
+    pub fn query(&self) -> &str {
+        self.query.as_deref().map(str::trim).unwrap_or(DEFAULT_QUERY)
+    }
+}
+
+/// The database kind an endpoint connects to, from its scheme.
+pub fn engine(url: &Url) -> Result<Engine> {
+    match url.scheme() {
+        "postgres" | "postgresql" => Ok(Engine::Postgres),
+        "mysql" | "mariadb" => Ok(Engine::Mysql),
+        "redis" | "rediss" => Ok(Engine::Redis),
+        _ => Err(Error::validation(
+            "database_check endpoints must be postgres://, mysql://, redis:// or rediss:// URLs",
+        )),
+    }
+}
+
+pub fn parse(endpoint: &str) -> Result<Url> {
+    Url::parse(endpoint).map_err(|e| Error::validation(format!("Invalid database endpoint: {}", e)))
+}
+
+/// Username and password from the monitor's decrypted credentials. Either
+/// may be left out: the username then comes from the endpoint, and servers
+/// without authentication need no password.
+pub fn credentials(values: &HashMap<String, String>) -> (Option<String>, Option<String>) {
+    (values.get(USERNAME).cloned(), values.get(PASSWORD).cloned())
+}
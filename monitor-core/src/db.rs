@@ -1,4 +1,4 @@
-use sqlx::{PgPool, Pool, Postgres};
+use sqlx::{postgres::PgPoolOptions, Pool, Postgres};
 use crate::{config::DatabaseConfig, error::Result};
 
 pub type DatabasePool = Pool<Postgres>;
@@ -13,7 +13,10 @@ pub async fn create_pool(config: &DatabaseConfig) -> Result<DatabasePool> {
         config.database
     );
 
-    let pool = PgPool::connect(&connection_string).await?;
+    let pool = PgPoolOptions::new()
+        .max_connections(config.max_connections)
+        .connect(&connection_string)
+        .await?;
     
     Ok(pool)
 }
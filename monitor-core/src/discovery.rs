@@ -0,0 +1,258 @@
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+use crate::{
+    durations::Seconds,
+    error::Result,
+    models::{CreateMonitorRequest, Monitor, UpdateMonitorRequest},
+    status_match::ExpectedStatus,
+    Error,
+};
+
+/// Annotations (Kubernetes) or labels (Docker) that opt an object in and
+/// configure its monitor: `enabled`, `name`, `path`, `url`, `interval`,
+/// `expected-status` (a code or ranges such as `200-299,304`), `tags` and
+/// `port`
+pub const LABEL_PREFIX: &str = "monitor.yeheng.io/";
+
+const DEFAULT_INTERVAL: Seconds = Seconds::new(60);
+
+/// A system monitors are discovered from. Each source only manages monitors
+/// carrying its own tags.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Source {
+    /// Every monitor created from this source carries this tag
+    pub managed_tag: &'static str,
+    /// Followed by the object's source name, identifies the object
+    pub tag_prefix: &'static str,
+    /// Pause reason for monitors whose object went away or opted out
+    pub removed_reason: &'static str,
+}
+
+pub const KUBERNETES: Source = Source {
+    managed_tag: "k8s",
+    tag_prefix: "k8s:",
+    removed_reason: "Removed from Kubernetes",
+};
+
+pub const DOCKER: Source = Source {
+    managed_tag: "docker",
+    tag_prefix: "docker:",
+    removed_reason: "Container stopped or removed",
+};
+
+pub const CONSUL: Source = Source {
+    managed_tag: "consul",
+    tag_prefix: "consul:",
+    removed_reason: "Deregistered from Consul",
+};
+
+/// The monitor a discovered object asks for.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DesiredMonitor {
+    /// Identifies the object within its source, e.g. `ingress/<namespace>/<name>`
+    pub source: String,
+    pub name: String,
+    pub endpoint: String,
+    pub interval: Seconds,
+    pub expected_status: ExpectedStatus,
+    /// Includes the managed and source tags
+    pub tags: Vec<String>,
+}
+
+/// Monitors asked for by a source, and the objects whose labels could not
+/// be read, with why.
+pub type Discovered = (Vec<DesiredMonitor>, Vec<(String, Error)>);
+
+/// What a sync does to bring monitors in line with the source.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SyncAction {
+    Create(DesiredMonitor),
+    Update(Uuid, DesiredMonitor),
+    /// Paused by an earlier sync and back in the source
+    Resume(Uuid),
+    /// The object is gone or no longer opted in; monitors are paused rather
+    /// than deleted so their history stays
+    Pause(Uuid),
+}
+
+impl DesiredMonitor {
+    pub fn create_request(&self) -> Result<CreateMonitorRequest> {
+        CreateMonitorRequest::builder(self.name.clone(), self.endpoint.clone())
+            .expected_status(self.expected_status.clone())
+            .interval(self.interval)
+            .tags(self.tags.clone())
+            .build()
+    }
+
+    pub fn update_request(&self) -> UpdateMonitorRequest {
+        UpdateMonitorRequest {
+            name: Some(self.name.clone()),
+            endpoint: Some(self.endpoint.clone()),
+            method: None,
+            headers: None,
+            body: None,
+            expected_status: Some(self.expected_status.clone()),
+            timeout: None,
+            interval: Some(self.interval),
+            script: None,
+            pre_request_script: None,
+            enabled: None,
+            tags: Some(self.tags.clone()),
+            team_id: None,
+            retries: None,
+            retry_delay_ms: None,
+            schedule_cron: None,
+            notification_channels: None,
+            script_profile: None,
+            credentials: None,
+            steps: None,
+            metric_assertions: None,
+            broker_check: None,
+            storage_check: None,
+            ntp_check: None,
+            snmp_check: None,
+            exec_check: None,
+            tcp_check: None,
+            dns_check: None,
+            tls_check: None,
+            websocket_check: None,
+            mail_check: None,
+            database_check: None,
+            heartbeat_check: None,
+            expected_body_contains: None,
+            expected_body_regex: None,
+            bypass_dns_cache: None,
+            connect_timeout_ms: None,
+            tls_timeout_ms: None,
+        }
+    }
+
+    fn matches(&self, monitor: &Monitor) -> bool {
+        let mut tags = monitor.tags.clone();
+        tags.sort();
+        self.name == monitor.name
+            && self.endpoint == monitor.endpoint
+            && self.interval == monitor.interval
+            && self.expected_status == monitor.expected_status
+            && self.tags == tags
+    }
+}
+
+/// Value of `monitor.yeheng.io/<key>`, ignoring blank values.
+pub fn label<'a>(labels: &'a BTreeMap<String, String>, key: &str) -> Option<&'a str> {
+    labels
+        .get(&format!("{}{}", LABEL_PREFIX, key))
+        .map(|value| value.trim())
+        .filter(|value| !value.is_empty())
+}
+
+impl Source {
+    /// `None` unless `labels` has `monitor.yeheng.io/enabled: "true"`. The
+    /// endpoint is the `url` label, or `base_url` followed by the `path` label.
+    pub fn desired(
+        &self,
+        source: String,
+        default_name: String,
+        labels: &BTreeMap<String, String>,
+        base_url: Option<String>,
+    ) -> Result<Option<DesiredMonitor>> {
+        if label(labels, "enabled") != Some("true") {
+            return Ok(None);
+        }
+        let invalid = |key: &str, value: &str| Error::validation(format!("{}: invalid {}{}: {}", source, LABEL_PREFIX, key, value));
+
+        let endpoint = match (label(labels, "url"), base_url) {
+            (Some(url), _) => url.to_string(),
+            (None, Some(base)) => format!("{}{}", base, label(labels, "path").unwrap_or("/")),
+            (None, None) => return Err(Error::validation(format!("{}: no host or port to monitor", source))),
+        };
+        let interval = match label(labels, "interval") {
+            Some(value) => value
+                .parse::<Seconds>()
+                .ok()
+                .filter(|i| i.is_positive())
+                .ok_or_else(|| invalid("interval", value))?,
+            None => DEFAULT_INTERVAL,
+        };
+        let expected_status = match label(labels, "expected-status") {
+            Some(value) => value.parse::<ExpectedStatus>().map_err(|_| invalid("expected-status", value))?,
+            None => ExpectedStatus::default(),
+        };
+
+        let mut tags: Vec<String> = label(labels, "tags")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|tag| !tag.is_empty())
+            .map(str::to_string)
+            .collect();
+        tags.push(self.managed_tag.to_string());
+        tags.push(format!("{}{}", self.tag_prefix, source));
+        tags.sort();
+        tags.dedup();
+
+        Ok(Some(DesiredMonitor {
+            name: label(labels, "name").map(str::to_string).unwrap_or(default_name),
+            source,
+            endpoint,
+            interval,
+            expected_status,
+            tags,
+        }))
+    }
+
+    /// The object a monitor managed by this source was created for.
+    pub fn source_of<'a>(&self, monitor: &'a Monitor) -> Option<&'a str> {
+        monitor.tags.iter().find_map(|tag| tag.strip_prefix(self.tag_prefix))
+    }
+
+    /// Actions that make this source's monitors match `desired`. Monitors of
+    /// other sources are never touched, monitors paused by hand stay paused,
+    /// and monitors of `invalid` objects are left as they are until the
+    /// labels are fixed.
+    pub fn plan(&self, desired: &[DesiredMonitor], invalid: &[String], existing: &[Monitor]) -> Vec<SyncAction> {
+        let managed: HashMap<&str, &Monitor> = existing.iter().filter_map(|m| Some((self.source_of(m)?, m))).collect();
+        let mut actions = Vec::new();
+
+        for want in desired {
+            match managed.get(want.source.as_str()) {
+                None => actions.push(SyncAction::Create(want.clone())),
+                Some(monitor) => {
+                    if !want.matches(monitor) {
+                        actions.push(SyncAction::Update(monitor.id, want.clone()));
+                    }
+                    if !monitor.enabled && monitor.paused_reason.as_deref() == Some(self.removed_reason) {
+                        actions.push(SyncAction::Resume(monitor.id));
+                    }
+                }
+            }
+        }
+
+        for (source, monitor) in &managed {
+            if monitor.enabled
+                && !desired.iter().any(|want| want.source == *source)
+                && !invalid.iter().any(|i| i == source)
+            {
+                actions.push(SyncAction::Pause(monitor.id));
+            }
+        }
+        actions
+    }
+}
+
+/// Splits per-object results into desired monitors and the objects whose
+/// labels could not be read, with why.
+pub fn collect(
+    candidates: impl IntoIterator<Item = (String, Result<Option<DesiredMonitor>>)>,
+) -> Discovered {
+    let mut desired = Vec::new();
+    let mut invalid = Vec::new();
+    for (source, candidate) in candidates {
+        match candidate {
+            Ok(Some(want)) => desired.push(want),
+            Ok(None) => {}
+            Err(e) => invalid.push((source, e)),
+        }
+    }
+    (desired, invalid)
+}
@@ -0,0 +1,208 @@
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+    time::{Duration, Instant},
+};
+use crate::{error::Result, Error};
+
+const MAX_EXPECTED: usize = 32;
+const DEFAULT_DNS_PORT: u16 = 53;
+
+/// Resolved addresses by host name, each kept for its record TTL.
+#[derive(Debug, Clone)]
+pub struct DnsCache {
+    entries: HashMap<String, (Vec<IpAddr>, Instant)>,
+    capacity: usize,
+    min_ttl: Duration,
+    max_ttl: Duration,
+}
+
+impl DnsCache {
+    pub fn new(capacity: usize, min_ttl: Duration, max_ttl: Duration) -> Self {
+        Self {
+            entries: HashMap::new(),
+            capacity: capacity.max(1),
+            min_ttl,
+            max_ttl: max_ttl.max(min_ttl),
+        }
+    }
+
+    /// Cached addresses for `host`, unless they have expired.
+    pub fn get(&mut self, host: &str, now: Instant) -> Option<Vec<IpAddr>> {
+        let key = host.to_ascii_lowercase();
+        match self.entries.get(&key) {
+            Some((addrs, expires)) if *expires > now => Some(addrs.clone()),
+            Some(_) => {
+                self.entries.remove(&key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Stores a lookup valid for `ttl`, clamped to the configured bounds.
+    pub fn insert(&mut self, host: &str, addrs: Vec<IpAddr>, ttl: Duration, now: Instant) {
+        let ttl = ttl.clamp(self.min_ttl, self.max_ttl);
+        if ttl.is_zero() || addrs.is_empty() {
+            return;
+        }
+        if self.entries.len() >= self.capacity {
+            self.entries.retain(|_, (_, expires)| *expires > now);
+        }
+        if self.entries.len() >= self.capacity
+            && let Some(soonest) = self.entries.iter().min_by_key(|(_, (_, expires))| *expires).map(|(k, _)| k.clone())
+        {
+            self.entries.remove(&soonest);
+        }
+        self.entries.insert(host.to_ascii_lowercase(), (addrs, now + ttl));
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum RecordType {
+    #[default]
+    A,
+    Aaaa,
+    Cname,
+    Mx,
+    Txt,
+}
+
+impl RecordType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::A => "A",
+            Self::Aaaa => "AAAA",
+            Self::Cname => "CNAME",
+            Self::Mx => "MX",
+            Self::Txt => "TXT",
+        }
+    }
+}
+
+/// A DNS check run instead of the HTTP request. The monitor's `endpoint` is
+/// `dns://name`; the check passes when the name has records of the type and
+/// the expectations hold.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DnsCheck {
+    #[serde(default)]
+    pub record_type: RecordType,
+    /// Name server asked instead of the system's, as `ip` or `ip:port`
+    #[serde(default)]
+    pub resolver: Option<String>,
+    /// Values the answer must all contain: addresses, names, MX exchanges
+    /// (optionally with their preference, e.g. `10 mail.example.com`) or TXT
+    /// strings
+    #[serde(default)]
+    pub expected: Vec<String>,
+    /// Bounds on the lowest TTL in the answer, in seconds
+    #[serde(default)]
+    pub min_ttl: Option<u32>,
+    #[serde(default)]
+    pub max_ttl: Option<u32>,
+}
+
+impl DnsCheck {
+    pub fn validate(&self, endpoint: &str) -> Result<()> {
+        query_name(endpoint)?;
+        self.resolver_addr()?;
+        if self.expected.len() > MAX_EXPECTED {
+            return Err(Error::validation(format!("At most {} expected values are allowed", MAX_EXPECTED)));
+        }
+        for value in &self.expected {
+            let valid = match self.record_type {
+                RecordType::A => value.parse::<std::net::Ipv4Addr>().is_ok(),
+                RecordType::Aaaa => value.parse::<std::net::Ipv6Addr>().is_ok(),
+                _ => !value.is_empty(),
+            };
+            if !valid {
+                return Err(Error::validation(format!(
+                    "'{}' is not a valid {} value",
+                    value,
+                    self.record_type.as_str()
+                )));
+            }
+        }
+        if let (Some(min), Some(max)) = (self.min_ttl, self.max_ttl)
+            && min > max
+        {
+            return Err(Error::validation("min_ttl must not be greater than max_ttl"));
+        }
+        Ok(())
+    }
+
+    /// The configured name server, with the DNS port filled in.
+    pub fn resolver_addr(&self) -> Result<Option<SocketAddr>> {
+        let Some(resolver) = &self.resolver else {
+            return Ok(None);
+        };
+        resolver
+            .parse::<SocketAddr>()
+            .or_else(|_| resolver.parse::<IpAddr>().map(|ip| SocketAddr::new(ip, DEFAULT_DNS_PORT)))
+            .map(Some)
+            .map_err(|_| {
+                Error::validation(format!("resolver '{}' must be an IP address with an optional port", resolver))
+            })
+    }
+
+    /// `Err` carries the result's error message. `answers` are the record
+    /// values as the scheduler formats them and `ttl` the lowest of their TTLs.
+    pub fn evaluate(&self, answers: &[String], ttl: u32) -> std::result::Result<(), String> {
+        for expected in &self.expected {
+            if !answers.iter().any(|answer| same_value(self.record_type, expected, answer)) {
+                return Err(format!(
+                    "expected '{}' in the {} answer, got '{}'",
+                    expected,
+                    self.record_type.as_str(),
+                    answers.join("', '")
+                ));
+            }
+        }
+        if let Some(min) = self.min_ttl
+            && ttl < min
+        {
+            return Err(format!("TTL is {}s, expected at least {}s", ttl, min));
+        }
+        if let Some(max) = self.max_ttl
+            && ttl > max
+        {
+            return Err(format!("TTL is {}s, expected at most {}s", ttl, max));
+        }
+        Ok(())
+    }
+}
+
+/// The name a `dns://name` endpoint queries.
+pub fn query_name(endpoint: &str) -> Result<String> {
+    let url = Url::parse(endpoint).map_err(|e| Error::validation(format!("Invalid DNS endpoint: {}", e)))?;
+    match (url.scheme(), url.host_str(), url.port(), url.path()) {
+        ("dns", Some(name), None, "" | "/") if !name.is_empty() => Ok(name.to_string()),
+        _ => Err(Error::validation("dns_check endpoints must be dns://name URLs")),
+    }
+}
+
+fn same_value(record_type: RecordType, expected: &str, answer: &str) -> bool {
+    let same_name = |a: &str, b: &str| a.trim_end_matches('.').eq_ignore_ascii_case(b.trim_end_matches('.'));
+    match record_type {
+        RecordType::A | RecordType::Aaaa => {
+            expected.parse::<IpAddr>().is_ok_and(|expected| answer.parse::<IpAddr>() == Ok(expected))
+        }
+        RecordType::Cname => same_name(expected, answer),
+        RecordType::Mx => {
+            same_name(expected, answer)
+                || answer.split_once(' ').is_some_and(|(_, exchange)| same_name(expected, exchange))
+        }
+        RecordType::Txt => expected == answer,
+    }
+}
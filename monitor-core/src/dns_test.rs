@@ -0,0 +1,116 @@
+#[cfg(test)]
+mod dns_tests {
+    use crate::dns::*;
+    use std::{
+        net::IpAddr,
+        time::{Duration, Instant},
+    };
+
+    fn addr(last: u8) -> Vec<IpAddr> {
+        vec![IpAddr::from([192, 0, 2, last])]
+    }
+
+    #[test]
+    fn entries_expire_after_their_ttl() {
+        let now = Instant::now();
+        let mut cache = DnsCache::new(10, Duration::ZERO, Duration::from_secs(300));
+        cache.insert("example.com", addr(1), Duration::from_secs(30), now);
+        assert_eq!(cache.get("example.com", now + Duration::from_secs(29)), Some(addr(1)));
+        assert_eq!(cache.get("example.com", now + Duration::from_secs(30)), None);
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn ttl_is_clamped_to_configured_bounds() {
+        let now = Instant::now();
+        let mut cache = DnsCache::new(10, Duration::from_secs(5), Duration::from_secs(60));
+        cache.insert("short.example.com", addr(1), Duration::from_secs(1), now);
+        cache.insert("long.example.com", addr(2), Duration::from_secs(3600), now);
+        assert_eq!(cache.get("short.example.com", now + Duration::from_secs(4)), Some(addr(1)));
+        assert_eq!(cache.get("long.example.com", now + Duration::from_secs(61)), None);
+    }
+
+    #[test]
+    fn zero_ttl_is_not_cached() {
+        let now = Instant::now();
+        let mut cache = DnsCache::new(10, Duration::ZERO, Duration::from_secs(60));
+        cache.insert("example.com", addr(1), Duration::ZERO, now);
+        assert_eq!(cache.get("example.com", now), None);
+    }
+
+    #[test]
+    fn host_names_are_case_insensitive() {
+        let now = Instant::now();
+        let mut cache = DnsCache::new(10, Duration::ZERO, Duration::from_secs(60));
+        cache.insert("Example.COM", addr(1), Duration::from_secs(30), now);
+        assert_eq!(cache.get("example.com", now), Some(addr(1)));
+    }
+
+    #[test]
+    fn full_cache_evicts_the_entry_expiring_first() {
+        let now = Instant::now();
+        let mut cache = DnsCache::new(2, Duration::ZERO, Duration::from_secs(300));
+        cache.insert("a.example.com", addr(1), Duration::from_secs(10), now);
+        cache.insert("b.example.com", addr(2), Duration::from_secs(100), now);
+        cache.insert("c.example.com", addr(3), Duration::from_secs(50), now);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get("a.example.com", now), None);
+        assert_eq!(cache.get("b.example.com", now), Some(addr(2)));
+    }
+
+    fn check(value: serde_json::Value) -> DnsCheck {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn checks_query_the_endpoint_name() {
+        assert_eq!(query_name("dns://example.com").unwrap(), "example.com");
+        assert_eq!(query_name("dns://_dmarc.example.com/").unwrap(), "_dmarc.example.com");
+        assert!(query_name("dns://example.com:53").is_err());
+        assert!(query_name("https://example.com").is_err());
+
+        let endpoint = "dns://example.com";
+        assert!(DnsCheck::default().validate(endpoint).is_ok());
+        assert!(check(serde_json::json!({ "record_type": "AAAA", "expected": ["2001:db8::1"] }))
+            .validate(endpoint)
+            .is_ok());
+        assert!(check(serde_json::json!({ "expected": ["2001:db8::1"] })).validate(endpoint).is_err());
+        assert!(check(serde_json::json!({ "min_ttl": 600, "max_ttl": 60 })).validate(endpoint).is_err());
+        assert!(check(serde_json::json!({ "resolver": "dns.google" })).validate(endpoint).is_err());
+
+        let resolver = |value: &str| check(serde_json::json!({ "resolver": value })).resolver_addr().unwrap();
+        assert_eq!(resolver("192.0.2.53"), Some("192.0.2.53:53".parse().unwrap()));
+        assert_eq!(resolver("[2001:db8::53]:5353"), Some("[2001:db8::53]:5353".parse().unwrap()));
+    }
+
+    #[test]
+    fn answers_must_hold_every_expected_value() {
+        let a = check(serde_json::json!({ "expected": ["192.0.2.1", "192.0.2.2"] }));
+        let answers = ["192.0.2.2".to_string(), "192.0.2.1".to_string()];
+        assert!(a.evaluate(&answers, 300).is_ok());
+        assert_eq!(
+            a.evaluate(&answers[..1], 300).unwrap_err(),
+            "expected '192.0.2.1' in the A answer, got '192.0.2.2'"
+        );
+
+        let mx = check(serde_json::json!({ "record_type": "MX", "expected": ["Mail.example.com."] }));
+        assert!(mx.evaluate(&["10 mail.example.com".to_string()], 300).is_ok());
+        let preference = check(serde_json::json!({ "record_type": "MX", "expected": ["20 mail.example.com"] }));
+        assert!(preference.evaluate(&["10 mail.example.com".to_string()], 300).is_err());
+
+        let txt = check(serde_json::json!({ "record_type": "TXT", "expected": ["v=spf1 -all"] }));
+        assert!(txt.evaluate(&["v=spf1 -all".to_string()], 300).is_ok());
+        assert!(txt.evaluate(&["V=SPF1 -all".to_string()], 300).is_err());
+
+        // Without expectations any answer passes
+        assert!(DnsCheck::default().evaluate(&answers, 0).is_ok());
+    }
+
+    #[test]
+    fn ttl_bounds_apply_to_the_lowest_ttl() {
+        let bounded = check(serde_json::json!({ "min_ttl": 60, "max_ttl": 3600 }));
+        assert!(bounded.evaluate(&[], 60).is_ok());
+        assert_eq!(bounded.evaluate(&[], 30).unwrap_err(), "TTL is 30s, expected at least 60s");
+        assert_eq!(bounded.evaluate(&[], 86400).unwrap_err(), "TTL is 86400s, expected at most 3600s");
+    }
+}
@@ -0,0 +1,81 @@
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use crate::{
+    discovery::{self, DesiredMonitor, Discovered, DOCKER, LABEL_PREFIX},
+    error::{Error, Result},
+};
+
+/// A running container as listed by `GET /containers/json`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Container {
+    pub id: String,
+    #[serde(default)]
+    pub names: Vec<String>,
+    #[serde(default)]
+    pub labels: BTreeMap<String, String>,
+    #[serde(default)]
+    pub ports: Vec<ContainerPort>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ContainerPort {
+    pub private_port: u16,
+    /// Absent unless the port is published on the host
+    pub public_port: Option<u16>,
+    #[serde(rename = "Type")]
+    pub protocol: String,
+}
+
+/// The label filter that limits the listing to opted-in containers.
+pub fn label_filter() -> String {
+    format!(r#"{{"label":["{}enabled=true"]}}"#, LABEL_PREFIX)
+}
+
+impl Container {
+    /// The container name without Docker's leading slash, or its short id.
+    pub fn name(&self) -> String {
+        self.names
+            .first()
+            .map(|name| name.trim_start_matches('/').to_string())
+            .unwrap_or_else(|| self.id.chars().take(12).collect())
+    }
+}
+
+/// `container/<name>`, also used in the source tag.
+pub fn source_name(container: &Container) -> String {
+    format!("container/{}", container.name())
+}
+
+/// `None` unless the container is labelled `monitor.yeheng.io/enabled=true`.
+/// The URL uses `published_host` and the host port published for the
+/// container port in `monitor.yeheng.io/port`, or the first published TCP port.
+pub fn from_container(container: &Container, published_host: &str) -> Result<Option<DesiredMonitor>> {
+    let published = container
+        .ports
+        .iter()
+        .filter(|p| p.protocol == "tcp")
+        .filter_map(|p| Some((p.private_port, p.public_port?)));
+    let port = match discovery::label(&container.labels, "port") {
+        Some(wanted) => {
+            let wanted: u16 = wanted
+                .parse()
+                .map_err(|_| Error::validation(format!("{}: invalid {}port: {}", source_name(container), LABEL_PREFIX, wanted)))?;
+            published.filter(|(private, _)| *private == wanted).map(|(_, public)| public).next()
+        }
+        None => published.map(|(_, public)| public).next(),
+    };
+    let url = port.map(|port| format!("http://{}:{}", published_host, port));
+    DOCKER.desired(source_name(container), container.name(), &container.labels, url)
+}
+
+/// Monitors asked for by labelled containers, and the containers whose
+/// labels could not be read, with why.
+pub fn desired_monitors(containers: &[Container], published_host: &str) -> Discovered {
+    discovery::collect(
+        containers
+            .iter()
+            .map(|c| (source_name(c), from_container(c, published_host))),
+    )
+}
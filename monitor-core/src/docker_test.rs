@@ -0,0 +1,130 @@
+#[cfg(test)]
+mod docker_tests {
+    use crate::{
+        discovery::{SyncAction, DOCKER, KUBERNETES},
+        docker::*,
+        http_method::HttpMethod,
+        models::Monitor,
+    };
+    use chrono::Utc;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    fn container(labels: serde_json::Value) -> Container {
+        serde_json::from_value(json!({
+            "Id": "4f66ad9a0b2e1c3d",
+            "Names": ["/shop-web"],
+            "Labels": labels,
+            "Ports": [
+                { "IP": "0.0.0.0", "PrivatePort": 9090, "Type": "tcp" },
+                { "IP": "0.0.0.0", "PrivatePort": 80, "PublicPort": 8080, "Type": "tcp" },
+                { "IP": "0.0.0.0", "PrivatePort": 8443, "PublicPort": 8443, "Type": "tcp" }
+            ]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn labelled_containers_use_their_published_port() {
+        let desired = from_container(
+            &container(json!({
+                "monitor.yeheng.io/enabled": "true",
+                "monitor.yeheng.io/path": "/healthz",
+                "monitor.yeheng.io/tags": "shop"
+            })),
+            "docker01",
+        )
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(desired.source, "container/shop-web");
+        assert_eq!(desired.name, "shop-web");
+        assert_eq!(desired.endpoint, "http://docker01:8080/healthz");
+        assert_eq!(desired.tags, vec!["docker", "docker:container/shop-web", "shop"]);
+    }
+
+    #[test]
+    fn the_port_label_picks_the_container_port() {
+        let labels = json!({ "monitor.yeheng.io/enabled": "true", "monitor.yeheng.io/port": "8443" });
+        let desired = from_container(&container(labels), "localhost").unwrap().unwrap();
+        assert_eq!(desired.endpoint, "http://localhost:8443/");
+
+        // An unpublished port cannot be reached from the host
+        let labels = json!({ "monitor.yeheng.io/enabled": "true", "monitor.yeheng.io/port": "9090" });
+        assert!(from_container(&container(labels), "localhost").is_err());
+    }
+
+    #[test]
+    fn containers_without_the_opt_in_are_ignored() {
+        assert!(from_container(&container(json!({})), "localhost").unwrap().is_none());
+    }
+
+    #[test]
+    fn docker_sync_leaves_kubernetes_monitors_alone() {
+        let labels = json!({ "monitor.yeheng.io/enabled": "true" });
+        let (desired, _) = desired_monitors(&[container(labels.clone())], "localhost");
+        let k8s = KUBERNETES
+            .desired(
+                "service/prod/shop".to_string(),
+                "prod/shop".to_string(),
+                &serde_json::from_value(labels).unwrap(),
+                Some("http://shop.prod.svc:80".to_string()),
+            )
+            .unwrap()
+            .unwrap();
+
+        let now = Utc::now();
+        let existing = Monitor {
+            id: Uuid::new_v4(),
+            name: k8s.name.clone(),
+            endpoint: k8s.endpoint.clone(),
+            method: HttpMethod::Get,
+            headers: None,
+            body: None,
+            expected_status: 200.into(),
+            timeout: None,
+            interval: k8s.interval,
+            script: None,
+            pre_request_script: None,
+            enabled: true,
+            paused_reason: None,
+            paused_by: None,
+            paused_at: None,
+            resume_at: None,
+            tags: k8s.tags.clone(),
+            owner_id: None,
+            team_id: None,
+            system_key: None,
+            retries: None,
+            retry_delay_ms: None,
+            schedule_cron: None,
+            notification_channels: None,
+            script_profile: None,
+            credentials: None,
+            steps: None,
+            metric_assertions: None,
+            broker_check: None,
+            storage_check: None,
+            ntp_check: None,
+            snmp_check: None,
+            exec_check: None,
+            tcp_check: None,
+            dns_check: None,
+            tls_check: None,
+            websocket_check: None,
+            mail_check: None,
+            database_check: None,
+            heartbeat_check: None,
+            expected_body_contains: None,
+            expected_body_regex: None,
+            bypass_dns_cache: false,
+            connect_timeout_ms: None,
+            tls_timeout_ms: None,
+            created_at: now,
+            updated_at: now,
+        };
+
+        assert_eq!(DOCKER.plan(&desired, &[], std::slice::from_ref(&existing)), vec![SyncAction::Create(desired[0].clone())]);
+        assert_eq!(DOCKER.plan(&[], &[], &[existing]), vec![]);
+    }
+}
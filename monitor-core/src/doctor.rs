@@ -0,0 +1,271 @@
+use redis::Client;
+use sqlx::PgPool;
+use std::{fmt, time::Duration};
+use crate::{config::{Config, DEFAULT_JWT_SECRET}, crypto::KeyRing, db};
+
+const CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+const MIN_JWT_SECRET_LEN: usize = 32;
+/// Endpoint used to confirm outbound HTTP(S) access; override with `DOCTOR_PROBE_URL`.
+pub const DEFAULT_PROBE_URL: &str = "https://example.com";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    Ok,
+    Warn,
+    Fail,
+}
+
+impl fmt::Display for CheckStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckStatus::Ok => write!(f, " OK "),
+            CheckStatus::Warn => write!(f, "WARN"),
+            CheckStatus::Fail => write!(f, "FAIL"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CheckResult {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub message: String,
+    /// What the operator should do about a warning or failure.
+    pub hint: Option<String>,
+}
+
+impl CheckResult {
+    fn ok(name: &'static str, message: impl Into<String>) -> Self {
+        Self { name, status: CheckStatus::Ok, message: message.into(), hint: None }
+    }
+
+    fn warn(name: &'static str, message: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self { name, status: CheckStatus::Warn, message: message.into(), hint: Some(hint.into()) }
+    }
+
+    fn fail(name: &'static str, message: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self { name, status: CheckStatus::Fail, message: message.into(), hint: Some(hint.into()) }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DoctorOptions {
+    pub check_redis: bool,
+    pub probe_url: String,
+}
+
+impl Default for DoctorOptions {
+    fn default() -> Self {
+        Self {
+            check_redis: true,
+            probe_url: std::env::var("DOCTOR_PROBE_URL").unwrap_or_else(|_| DEFAULT_PROBE_URL.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DoctorReport {
+    pub checks: Vec<CheckResult>,
+}
+
+impl DoctorReport {
+    pub fn passed(&self) -> bool {
+        self.checks.iter().all(|c| c.status != CheckStatus::Fail)
+    }
+
+    pub fn print(&self, component: &str) {
+        println!("{} doctor", component);
+        for check in &self.checks {
+            println!("[{}] {}: {}", check.status, check.name, check.message);
+            if let Some(hint) = &check.hint {
+                println!("       -> {}", hint);
+            }
+        }
+        if self.passed() {
+            println!("All required checks passed");
+        } else {
+            println!("One or more checks failed; fix them before starting {}", component);
+        }
+    }
+}
+
+/// Returns true when the process was started with `--doctor`.
+pub fn requested() -> bool {
+    std::env::args().any(|arg| arg == "--doctor")
+}
+
+/// Runs every startup check, continuing past failures so all problems are reported at once.
+pub async fn run(options: &DoctorOptions) -> DoctorReport {
+    let mut report = DoctorReport::default();
+
+    let config = match Config::from_env() {
+        Ok(config) => {
+            report.checks.push(CheckResult::ok("configuration", "loaded from environment"));
+            config
+        }
+        Err(e) => {
+            report.checks.push(CheckResult::fail(
+                "configuration",
+                e.to_string(),
+                "Check DATABASE_*, REDIS_URL and JWT_SECRET environment variables",
+            ));
+            return report;
+        }
+    };
+
+    report.checks.extend(check_config(&config));
+    report.checks.push(check_jwt_secret(&config.auth.jwt_secret));
+
+    match tokio::time::timeout(CHECK_TIMEOUT, db::create_pool(&config.database)).await {
+        Ok(Ok(pool)) => {
+            report.checks.push(CheckResult::ok(
+                "postgres",
+                format!("connected to {}:{}/{}", config.database.host, config.database.port, config.database.database),
+            ));
+            report.checks.push(check_migrations(&pool).await);
+        }
+        Ok(Err(e)) => report.checks.push(CheckResult::fail(
+            "postgres",
+            e.to_string(),
+            "Verify DATABASE_HOST/PORT/USER/PASSWORD/NAME and that Postgres accepts connections",
+        )),
+        Err(_) => report.checks.push(CheckResult::fail(
+            "postgres",
+            format!("no connection within {}s", CHECK_TIMEOUT.as_secs()),
+            "Check that the database host is reachable from this machine",
+        )),
+    }
+
+    if options.check_redis {
+        report.checks.push(check_redis(&config.redis.url).await);
+    }
+
+    report.checks.push(check_outbound(&options.probe_url).await);
+    report
+}
+
+fn check_config(config: &Config) -> Vec<CheckResult> {
+    let mut checks = Vec::new();
+
+    if config.retention.result_days <= 0 {
+        checks.push(CheckResult::fail(
+            "retention",
+            format!("result_days is {}", config.retention.result_days),
+            "Set RESULT_RETENTION_DAYS to a positive number of days",
+        ));
+    }
+
+    if config.faults.enabled {
+        checks.push(CheckResult::warn(
+            "fault injection",
+            format!("enabled in the {} environment", config.error_reporting.environment),
+            "Turn fault injection off outside resilience tests; it is ignored in production",
+        ));
+    }
+
+    match KeyRing::from_config(&config.encryption, &config.auth.jwt_secret) {
+        Ok(keys) if config.encryption.keys.trim().is_empty() => checks.push(CheckResult::warn(
+            "encryption keys",
+            format!("no keys configured, using v{} derived from the JWT secret", keys.active_version()),
+            "Set ENCRYPTION_KEYS so rotating JWT_SECRET does not make stored secrets unreadable",
+        )),
+        Ok(keys) => checks.push(CheckResult::ok(
+            "encryption keys",
+            format!("versions {:?}, active v{}", keys.versions(), keys.active_version()),
+        )),
+        Err(e) => checks.push(CheckResult::fail(
+            "encryption keys",
+            e.to_string(),
+            "ENCRYPTION_KEYS must be <version>:<base64 32 bytes> entries including ENCRYPTION_ACTIVE_KEY",
+        )),
+    }
+
+    checks
+}
+
+pub fn check_jwt_secret(secret: &str) -> CheckResult {
+    if secret == DEFAULT_JWT_SECRET || secret.trim().is_empty() {
+        return CheckResult::fail(
+            "jwt secret",
+            "using the built-in default secret",
+            "Set JWT_SECRET to a random value, e.g. `openssl rand -base64 48`",
+        );
+    }
+
+    if secret.len() < MIN_JWT_SECRET_LEN {
+        return CheckResult::warn(
+            "jwt secret",
+            format!("only {} characters long", secret.len()),
+            format!("Use at least {} random characters", MIN_JWT_SECRET_LEN),
+        );
+    }
+
+    let distinct = secret.chars().collect::<std::collections::HashSet<_>>().len();
+    if distinct < 10 {
+        return CheckResult::warn(
+            "jwt secret",
+            format!("low entropy ({} distinct characters)", distinct),
+            "Generate the secret randomly instead of choosing it by hand",
+        );
+    }
+
+    CheckResult::ok("jwt secret", format!("{} characters", secret.len()))
+}
+
+async fn check_migrations(pool: &PgPool) -> CheckResult {
+    let migrator = sqlx::migrate!("../monitor-core/migrations");
+    let applied: Vec<i64> = sqlx::query_scalar("SELECT version FROM _sqlx_migrations WHERE success = true")
+        .fetch_all(pool)
+        .await
+        .unwrap_or_default();
+
+    let pending: Vec<String> = migrator
+        .iter()
+        .filter(|m| !applied.contains(&m.version))
+        .map(|m| format!("{}_{}", m.version, m.description))
+        .collect();
+
+    if pending.is_empty() {
+        CheckResult::ok("migrations", format!("{} applied, none pending", applied.len()))
+    } else {
+        CheckResult::warn(
+            "migrations",
+            format!("{} pending: {}", pending.len(), pending.join(", ")),
+            "They are applied automatically at startup; make sure the database user may run DDL",
+        )
+    }
+}
+
+async fn check_redis(url: &str) -> CheckResult {
+    let ping = async {
+        let client = Client::open(url)?;
+        let mut conn = client.get_multiplexed_async_connection().await?;
+        redis::cmd("PING").query_async::<String>(&mut conn).await
+    };
+
+    match tokio::time::timeout(CHECK_TIMEOUT, ping).await {
+        Ok(Ok(_)) => CheckResult::ok("redis", format!("PING succeeded at {}", url)),
+        Ok(Err(e)) => CheckResult::fail("redis", e.to_string(), "Verify REDIS_URL and that Redis is running"),
+        Err(_) => CheckResult::fail(
+            "redis",
+            format!("no response within {}s", CHECK_TIMEOUT.as_secs()),
+            "Check that the Redis host is reachable from this machine",
+        ),
+    }
+}
+
+async fn check_outbound(probe_url: &str) -> CheckResult {
+    let client = match reqwest::Client::builder().timeout(CHECK_TIMEOUT).build() {
+        Ok(client) => client,
+        Err(e) => return CheckResult::fail("outbound network", e.to_string(), "Check the TLS setup of this host"),
+    };
+
+    match client.head(probe_url).send().await {
+        Ok(response) => CheckResult::ok("outbound network", format!("{} answered HTTP {}", probe_url, response.status())),
+        Err(e) => CheckResult::fail(
+            "outbound network",
+            format!("{}: {}", probe_url, e),
+            "Monitors cannot reach external endpoints; check DNS, firewall and HTTPS_PROXY settings",
+        ),
+    }
+}
@@ -0,0 +1,26 @@
+#[cfg(test)]
+mod doctor_tests {
+    use crate::doctor::*;
+
+    #[test]
+    fn test_jwt_secret_strength() {
+        assert_eq!(check_jwt_secret("your-secret-key").status, CheckStatus::Fail);
+        assert_eq!(check_jwt_secret("").status, CheckStatus::Fail);
+        assert_eq!(check_jwt_secret("short-but-custom").status, CheckStatus::Warn);
+        assert_eq!(check_jwt_secret(&"ab".repeat(20)).status, CheckStatus::Warn);
+        assert_eq!(
+            check_jwt_secret("b1Q9xk2LmZ7pR4tW8vN0cF3hJ6sD5gYeA").status,
+            CheckStatus::Ok
+        );
+    }
+
+    #[test]
+    fn test_report_fails_only_on_failures() {
+        let mut report = DoctorReport::default();
+        report.checks.push(check_jwt_secret("short-but-custom"));
+        assert!(report.passed());
+
+        report.checks.push(check_jwt_secret("your-secret-key"));
+        assert!(!report.passed());
+    }
+}
@@ -0,0 +1,58 @@
+use std::collections::VecDeque;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DriftAlert {
+    /// The median lag over a full window rose above the threshold
+    Sustained { median_ms: i64 },
+    Recovered { median_ms: i64 },
+}
+
+/// Rolling window of check start lag on one worker. Single late starts are
+/// normal; a median above the threshold means the worker is falling behind.
+#[derive(Debug, Clone)]
+pub struct DriftTracker {
+    samples: VecDeque<i64>,
+    window: usize,
+    threshold_ms: i64,
+    alerting: bool,
+}
+
+impl DriftTracker {
+    pub fn new(window: usize, threshold_ms: i64) -> Self {
+        Self {
+            samples: VecDeque::with_capacity(window),
+            window: window.max(1),
+            threshold_ms,
+            alerting: false,
+        }
+    }
+
+    /// Adds a sample and reports when sustained drift starts or ends.
+    pub fn record(&mut self, lag_ms: i64) -> Option<DriftAlert> {
+        if self.samples.len() == self.window {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(lag_ms);
+        if self.samples.len() < self.window {
+            return None;
+        }
+
+        let median_ms = self.median_ms()?;
+        let over = median_ms > self.threshold_ms;
+        if over == self.alerting {
+            return None;
+        }
+        self.alerting = over;
+        Some(if over {
+            DriftAlert::Sustained { median_ms }
+        } else {
+            DriftAlert::Recovered { median_ms }
+        })
+    }
+
+    pub fn median_ms(&self) -> Option<i64> {
+        let mut sorted: Vec<i64> = self.samples.iter().copied().collect();
+        sorted.sort_unstable();
+        sorted.get(sorted.len() / 2).copied()
+    }
+}
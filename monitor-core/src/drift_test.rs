@@ -0,0 +1,32 @@
+#[cfg(test)]
+mod drift_tests {
+    use crate::drift::*;
+
+    #[test]
+    fn tracker_waits_for_a_full_window() {
+        let mut tracker = DriftTracker::new(3, 100);
+        assert_eq!(tracker.record(500), None);
+        assert_eq!(tracker.record(500), None);
+        assert_eq!(tracker.record(500), Some(DriftAlert::Sustained { median_ms: 500 }));
+        assert_eq!(tracker.record(500), None);
+    }
+
+    #[test]
+    fn tracker_ignores_isolated_spikes() {
+        let mut tracker = DriftTracker::new(3, 100);
+        for lag in [5, 5000, 5, 5, 4000, 10] {
+            assert_eq!(tracker.record(lag), None);
+        }
+    }
+
+    #[test]
+    fn tracker_reports_recovery_once() {
+        let mut tracker = DriftTracker::new(3, 100);
+        for lag in [300, 300, 300] {
+            tracker.record(lag);
+        }
+        assert_eq!(tracker.record(10), None);
+        assert_eq!(tracker.record(10), Some(DriftAlert::Recovered { median_ms: 10 }));
+        assert_eq!(tracker.record(10), None);
+    }
+}
@@ -0,0 +1,173 @@
+use reqwest::Url;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use uuid::Uuid;
+use crate::{durations::Seconds, http_method::HttpMethod, models::Monitor};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DuplicateKind {
+    /// Same request: method, normalized URL, body and expected status
+    Identical,
+    /// Same host and path, differing in scheme, query, method or expectations
+    Similar,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateMonitor {
+    pub id: Uuid,
+    pub name: String,
+    pub method: HttpMethod,
+    pub endpoint: String,
+    pub interval: Seconds,
+    pub enabled: bool,
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConsolidationSuggestion {
+    pub keep: Uuid,
+    pub remove: Vec<Uuid>,
+    /// Shortest interval in the group, so coverage does not get worse
+    pub interval: Seconds,
+    /// Union of the group's tags, so tag-scoped access and defaults still apply
+    pub tags: Vec<String>,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateGroup {
+    pub kind: DuplicateKind,
+    pub key: String,
+    pub monitors: Vec<DuplicateMonitor>,
+    pub suggestion: ConsolidationSuggestion,
+}
+
+/// Canonical form of an endpoint: lowercase host without default port, no
+/// fragment, no trailing slash and sorted query parameters.
+pub fn normalize_endpoint(endpoint: &str) -> String {
+    let Ok(mut url) = Url::parse(endpoint.trim()) else {
+        return endpoint.trim().to_lowercase();
+    };
+    url.set_fragment(None);
+
+    let mut pairs: Vec<(String, String)> = url.query_pairs().into_owned().collect();
+    pairs.sort();
+    if pairs.is_empty() {
+        url.set_query(None);
+    } else {
+        url.query_pairs_mut().clear().extend_pairs(pairs);
+    }
+
+    let path = url.path().trim_end_matches('/').to_string();
+    url.set_path(if path.is_empty() { "/" } else { &path });
+    url.to_string()
+}
+
+/// Host and path only, ignoring scheme, `www.`, port and query.
+pub fn similarity_key(endpoint: &str) -> String {
+    match Url::parse(endpoint.trim()) {
+        Ok(url) => {
+            let host = url.host_str().unwrap_or_default().trim_start_matches("www.");
+            format!("{}{}", host, url.path().trim_end_matches('/'))
+        }
+        Err(_) => endpoint.trim().to_lowercase(),
+    }
+}
+
+fn identical_key(monitor: &Monitor) -> String {
+    format!(
+        "{} {} {} {}",
+        monitor.method,
+        normalize_endpoint(&monitor.endpoint),
+        monitor.expected_status,
+        monitor.body.as_deref().unwrap_or_default()
+    )
+}
+
+fn summary(monitor: &Monitor) -> DuplicateMonitor {
+    DuplicateMonitor {
+        id: monitor.id,
+        name: monitor.name.clone(),
+        method: monitor.method.clone(),
+        endpoint: monitor.endpoint.clone(),
+        interval: monitor.interval,
+        enabled: monitor.enabled,
+        tags: monitor.tags.clone(),
+    }
+}
+
+fn suggest(kind: DuplicateKind, group: &[&Monitor]) -> ConsolidationSuggestion {
+    // Prefer an enabled monitor with the shortest interval, then the oldest
+    let keep = group
+        .iter()
+        .min_by_key(|m| (!m.enabled, m.interval, m.created_at))
+        .expect("duplicate groups have at least two monitors");
+    let interval = group.iter().map(|m| m.interval).min().unwrap_or(keep.interval);
+    let mut tags: Vec<String> = group.iter().flat_map(|m| m.tags.iter().cloned()).collect();
+    tags.sort();
+    tags.dedup();
+
+    let reason = match kind {
+        DuplicateKind::Identical => format!(
+            "{} monitors send the same request; keep '{}' and delete the rest",
+            group.len(),
+            keep.name
+        ),
+        DuplicateKind::Similar => format!(
+            "{} monitors check the same host and path; review whether '{}' can cover them",
+            group.len(),
+            keep.name
+        ),
+    };
+
+    ConsolidationSuggestion {
+        keep: keep.id,
+        remove: group.iter().filter(|m| m.id != keep.id).map(|m| m.id).collect(),
+        interval,
+        tags,
+        reason,
+    }
+}
+
+/// Groups monitors that duplicate each other. A similar group is only reported
+/// when it holds more than one distinct request, so it never just repeats an
+/// identical group. Transaction monitors are skipped since their endpoint only
+/// reflects the first step.
+pub fn find_duplicates(monitors: &[Monitor]) -> Vec<DuplicateGroup> {
+    let candidates: Vec<&Monitor> = monitors.iter().filter(|m| m.steps.is_none()).collect();
+
+    let mut identical: BTreeMap<String, Vec<&Monitor>> = BTreeMap::new();
+    for monitor in &candidates {
+        identical.entry(identical_key(monitor)).or_default().push(monitor);
+    }
+
+    let mut groups = Vec::new();
+    for (key, group) in identical.into_iter().filter(|(_, g)| g.len() > 1) {
+        groups.push(DuplicateGroup {
+            kind: DuplicateKind::Identical,
+            key,
+            monitors: group.iter().map(|m| summary(m)).collect(),
+            suggestion: suggest(DuplicateKind::Identical, &group),
+        });
+    }
+
+    let mut similar: BTreeMap<String, Vec<&Monitor>> = BTreeMap::new();
+    for monitor in &candidates {
+        similar.entry(similarity_key(&monitor.endpoint)).or_default().push(monitor);
+    }
+    for (key, group) in similar {
+        let distinct: HashSet<String> = group.iter().map(|m| identical_key(m)).collect();
+        if distinct.len() < 2 {
+            continue;
+        }
+        groups.push(DuplicateGroup {
+            kind: DuplicateKind::Similar,
+            key,
+            monitors: group.iter().map(|m| summary(m)).collect(),
+            suggestion: suggest(DuplicateKind::Similar, &group),
+        });
+    }
+
+    groups
+}
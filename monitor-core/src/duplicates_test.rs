@@ -0,0 +1,111 @@
+#[cfg(test)]
+mod duplicates_tests {
+    use crate::{duplicates::*, durations::Seconds, http_method::HttpMethod, models::Monitor};
+    use chrono::{Duration, Utc};
+    use uuid::Uuid;
+
+    fn monitor(name: &str, method: &str, endpoint: &str, interval: i32, age_days: i64) -> Monitor {
+        let created_at = Utc::now() - Duration::days(age_days);
+        Monitor {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            endpoint: endpoint.to_string(),
+            method: HttpMethod::custom(method).unwrap(),
+            headers: None,
+            body: None,
+            expected_status: 200.into(),
+            timeout: None,
+            interval: Seconds::new(interval),
+            script: None,
+            pre_request_script: None,
+            enabled: true,
+            paused_reason: None,
+            paused_by: None,
+            paused_at: None,
+            resume_at: None,
+            tags: vec![name.to_string()],
+            owner_id: None,
+            team_id: None,
+            system_key: None,
+            retries: None,
+            retry_delay_ms: None,
+            schedule_cron: None,
+            notification_channels: None,
+            script_profile: None,
+            credentials: None,
+            steps: None,
+            metric_assertions: None,
+            broker_check: None,
+            storage_check: None,
+            ntp_check: None,
+            snmp_check: None,
+            exec_check: None,
+            tcp_check: None,
+            dns_check: None,
+            tls_check: None,
+            websocket_check: None,
+            mail_check: None,
+            database_check: None,
+            heartbeat_check: None,
+            expected_body_contains: None,
+            expected_body_regex: None,
+            bypass_dns_cache: false,
+            connect_timeout_ms: None,
+            tls_timeout_ms: None,
+            created_at,
+            updated_at: created_at,
+        }
+    }
+
+    #[test]
+    fn test_normalize_endpoint() {
+        assert_eq!(
+            normalize_endpoint("HTTPS://Example.com:443/api/health/?b=2&a=1#top"),
+            "https://example.com/api/health?a=1&b=2"
+        );
+        assert_eq!(normalize_endpoint("https://example.com"), "https://example.com/");
+        assert_eq!(similarity_key("http://www.example.com/api/health?x=1"), "example.com/api/health");
+    }
+
+    #[test]
+    fn test_identical_monitors_are_grouped() {
+        let monitors = vec![
+            monitor("a", "GET", "https://example.com/health", 60, 10),
+            monitor("b", "get", "https://EXAMPLE.com/health/", 30, 5),
+            monitor("c", "GET", "https://example.com/other", 60, 1),
+        ];
+
+        let groups = find_duplicates(&monitors);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].kind, DuplicateKind::Identical);
+        assert_eq!(groups[0].monitors.len(), 2);
+
+        let suggestion = &groups[0].suggestion;
+        assert_eq!(suggestion.keep, monitors[1].id);
+        assert_eq!(suggestion.remove, vec![monitors[0].id]);
+        assert_eq!(suggestion.interval, Seconds::new(30));
+        assert_eq!(suggestion.tags, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_similar_monitors_are_grouped_once() {
+        let mut disabled = monitor("d", "GET", "http://www.example.com/health", 10, 30);
+        disabled.enabled = false;
+        let monitors = vec![
+            monitor("a", "GET", "https://example.com/health", 60, 10),
+            monitor("b", "GET", "https://example.com/health", 60, 5),
+            monitor("c", "HEAD", "https://example.com/health?verbose=1", 120, 1),
+            disabled,
+        ];
+
+        let groups = find_duplicates(&monitors);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].kind, DuplicateKind::Identical);
+        assert_eq!(groups[1].kind, DuplicateKind::Similar);
+        assert_eq!(groups[1].key, "example.com/health");
+        assert_eq!(groups[1].monitors.len(), 4);
+        // Enabled monitors are preferred, then the shortest interval, then the oldest
+        assert_eq!(groups[1].suggestion.keep, monitors[0].id);
+        assert_eq!(groups[1].suggestion.interval, Seconds::new(10));
+    }
+}
@@ -0,0 +1,95 @@
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr, time::Duration};
+use crate::{error::Result, Error};
+
+/// A whole number of seconds, as monitors store their `timeout` and
+/// `interval`. JSON takes a number of seconds or a string with a unit such as
+/// `"30s"`, `"5m"`, `"1h"` or `"1d"`, and always gets a number back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, sqlx::Type)]
+#[serde(try_from = "SecondsRepr", into = "i32")]
+#[sqlx(transparent)]
+pub struct Seconds(i32);
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SecondsRepr {
+    Number(i32),
+    Text(String),
+}
+
+impl Seconds {
+    pub const fn new(secs: i32) -> Self {
+        Self(secs)
+    }
+
+    pub const fn get(self) -> i32 {
+        self.0
+    }
+
+    pub fn is_positive(self) -> bool {
+        self.0 > 0
+    }
+
+    /// Negative values count as zero.
+    pub fn as_std(self) -> Duration {
+        Duration::from_secs(self.0.max(0) as u64)
+    }
+
+    pub fn as_chrono(self) -> chrono::Duration {
+        chrono::Duration::seconds(self.0 as i64)
+    }
+}
+
+impl fmt::Display for Seconds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}s", self.0)
+    }
+}
+
+impl FromStr for Seconds {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        let value = value.trim();
+        let invalid = || {
+            Error::validation(format!(
+                "Invalid duration '{}': expected seconds or a number with s, m, h or d",
+                value
+            ))
+        };
+        let (number, unit) = match value.find(|c: char| !c.is_ascii_digit()) {
+            Some(at) => value.split_at(at),
+            None => (value, "s"),
+        };
+        let multiplier = match unit {
+            "s" => 1,
+            "m" => 60,
+            "h" => 3600,
+            "d" => 86400,
+            _ => return Err(invalid()),
+        };
+        number
+            .parse::<i32>()
+            .ok()
+            .and_then(|number| number.checked_mul(multiplier))
+            .map(Self)
+            .ok_or_else(invalid)
+    }
+}
+
+impl TryFrom<SecondsRepr> for Seconds {
+    type Error = Error;
+
+    fn try_from(repr: SecondsRepr) -> Result<Self> {
+        match repr {
+            SecondsRepr::Number(secs) => Ok(Self(secs)),
+            SecondsRepr::Text(text) => text.parse(),
+        }
+    }
+}
+
+impl From<Seconds> for i32 {
+    fn from(seconds: Seconds) -> Self {
+        seconds.0
+    }
+}
@@ -0,0 +1,39 @@
+#[cfg(test)]
+mod durations_tests {
+    use crate::durations::*;
+    use std::time::Duration;
+
+    fn parse(value: serde_json::Value) -> Result<Seconds, serde_json::Error> {
+        serde_json::from_value(value)
+    }
+
+    #[test]
+    fn numbers_and_units_parse_to_seconds() {
+        assert_eq!(parse(serde_json::json!(45)).unwrap(), Seconds::new(45));
+        assert_eq!(parse(serde_json::json!("30s")).unwrap(), Seconds::new(30));
+        assert_eq!(parse(serde_json::json!("5m")).unwrap(), Seconds::new(300));
+        assert_eq!(parse(serde_json::json!("2h")).unwrap(), Seconds::new(7200));
+        assert_eq!(parse(serde_json::json!("1d")).unwrap(), Seconds::new(86_400));
+        assert_eq!(parse(serde_json::json!(" 90 ")).unwrap(), Seconds::new(90));
+        assert_eq!("10m".parse::<Seconds>().unwrap(), Seconds::new(600));
+    }
+
+    #[test]
+    fn invalid_durations_are_rejected() {
+        for value in ["", "m", "5 m", "1.5m", "-5m", "5w", "1m30s", "99999999d"] {
+            assert!(value.parse::<Seconds>().is_err(), "{}", value);
+        }
+        assert!(parse(serde_json::json!(1.5)).is_err());
+    }
+
+    #[test]
+    fn conversions_share_the_unit() {
+        let interval = Seconds::new(90);
+        assert_eq!(serde_json::to_value(interval).unwrap(), 90);
+        assert_eq!(interval.to_string(), "90s");
+        assert_eq!(interval.as_std(), Duration::from_secs(90));
+        assert_eq!(interval.as_chrono(), chrono::Duration::seconds(90));
+        assert_eq!(Seconds::new(-5).as_std(), Duration::ZERO);
+        assert!(!Seconds::new(0).is_positive());
+    }
+}
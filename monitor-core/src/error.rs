@@ -25,6 +25,9 @@ pub enum Error {
     #[error("Authentication error: {0}")]
     Auth(String),
     
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+    
     #[error("Validation error: {0}")]
     Validation(String),
     
@@ -67,6 +70,10 @@ impl Error {
         Self::Auth(msg.into())
     }
     
+    pub fn forbidden(msg: impl Into<String>) -> Self {
+        Self::Forbidden(msg.into())
+    }
+    
     pub fn script_execution(msg: impl Into<String>) -> Self {
         Self::ScriptExecution(msg.into())
     }
@@ -78,4 +85,15 @@ impl Error {
     pub fn scheduler(msg: impl Into<String>) -> Self {
         Self::Scheduler(msg.into())
     }
+
+    /// The database rejected the data itself (SQLSTATE class 22, data exception,
+    /// or 23, integrity constraint violation), so the same write can never succeed.
+    pub fn is_rejected_data(&self) -> bool {
+        match self {
+            Self::Database(sqlx::Error::Database(e)) => {
+                e.code().is_some_and(|code| code.starts_with("22") || code.starts_with("23"))
+            }
+            _ => false,
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,122 @@
+use serde_json::json;
+use std::sync::OnceLock;
+use crate::{config::ErrorReportingConfig, Error};
+
+struct Reporter {
+    component: &'static str,
+    environment: String,
+    release: String,
+    sentry: bool,
+    webhook_url: Option<String>,
+    client: reqwest::Client,
+}
+
+static REPORTER: OnceLock<Reporter> = OnceLock::new();
+
+/// Keeps the Sentry client alive; dropping it flushes pending events.
+pub struct ReportingGuard {
+    _sentry: Option<sentry::ClientInitGuard>,
+}
+
+/// Sets up Sentry and/or the error-report webhook for this process and installs a
+/// panic hook. `version` is the binary's crate version, used when no release is configured.
+pub fn init(config: &ErrorReportingConfig, component: &'static str, version: &str) -> ReportingGuard {
+    let release = config
+        .release
+        .clone()
+        .unwrap_or_else(|| format!("{}@{}", component, version));
+
+    let sentry = config.sentry_dsn.as_deref().filter(|dsn| !dsn.is_empty()).map(|dsn| {
+        let guard = sentry::init((
+            dsn,
+            sentry::ClientOptions {
+                release: Some(release.clone().into()),
+                environment: Some(config.environment.clone().into()),
+                ..Default::default()
+            },
+        ));
+        sentry::configure_scope(|scope| scope.set_tag("component", component));
+        guard
+    });
+
+    let webhook_url = config.webhook_url.clone().filter(|url| !url.is_empty());
+    if sentry.is_none() && webhook_url.is_none() {
+        return ReportingGuard { _sentry: None };
+    }
+
+    let _ = REPORTER.set(Reporter {
+        component,
+        environment: config.environment.clone(),
+        release,
+        sentry: sentry.is_some(),
+        webhook_url: webhook_url.clone(),
+        client: reqwest::Client::new(),
+    });
+
+    // Sentry's own integration already captures panics
+    if webhook_url.is_some() {
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            send_webhook("panic", &info.to_string(), "panic");
+            previous(info);
+        }));
+    }
+
+    tracing::info!("Error reporting enabled for {}", component);
+    ReportingGuard { _sentry: sentry }
+}
+
+/// Reports every error the API answers with a 500; the caller's mistakes
+/// (validation, auth, forbidden, not found) are ignored.
+pub fn capture_error(error: &Error, source: &str) {
+    if is_reportable(error) {
+        capture_message(&error.to_string(), source);
+    }
+}
+
+pub fn is_reportable(error: &Error) -> bool {
+    !matches!(error, Error::Validation(_) | Error::NotFound(_) | Error::Auth(_) | Error::Forbidden(_))
+}
+
+pub fn capture_message(message: &str, source: &str) {
+    let Some(reporter) = REPORTER.get() else {
+        return;
+    };
+
+    if reporter.sentry {
+        sentry::with_scope(
+            |scope| scope.set_tag("source", source),
+            || sentry::capture_message(message, sentry::Level::Error),
+        );
+    }
+    send_webhook("error", message, source);
+}
+
+fn send_webhook(kind: &str, message: &str, source: &str) {
+    let Some(reporter) = REPORTER.get() else {
+        return;
+    };
+    let Some(url) = reporter.webhook_url.clone() else {
+        return;
+    };
+    // Reports are best effort and only sent from within the Tokio runtime
+    let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+        return;
+    };
+
+    let payload = json!({
+        "kind": kind,
+        "component": reporter.component,
+        "environment": reporter.environment,
+        "release": reporter.release,
+        "source": source,
+        "message": message,
+        "timestamp": chrono::Utc::now(),
+    });
+    let request = reporter.client.post(url).json(&payload);
+    runtime.spawn(async move {
+        if let Err(e) = request.send().await {
+            tracing::warn!("Failed to send error report: {}", e);
+        }
+    });
+}
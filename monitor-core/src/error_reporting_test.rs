@@ -0,0 +1,20 @@
+#[cfg(test)]
+mod error_reporting_tests {
+    use crate::{Error, error_reporting::*};
+
+    #[test]
+    fn test_server_errors_are_reported() {
+        assert!(is_reportable(&Error::internal("boom")));
+        assert!(is_reportable(&Error::Database(sqlx::Error::PoolTimedOut)));
+        assert!(is_reportable(&Error::scheduler("queue stalled")));
+        assert!(is_reportable(&Error::Io(std::io::Error::other("disk full"))));
+    }
+
+    #[test]
+    fn test_caller_errors_are_not_reported() {
+        assert!(!is_reportable(&Error::validation("bad")));
+        assert!(!is_reportable(&Error::not_found("missing")));
+        assert!(!is_reportable(&Error::auth("who")));
+        assert!(!is_reportable(&Error::forbidden("no")));
+    }
+}
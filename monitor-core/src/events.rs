@@ -0,0 +1,89 @@
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+use crate::{
+    models::{Monitor, MonitorResult},
+    states::StateEvent,
+};
+
+/// Events buffered per subscriber before a slow one starts skipping
+pub const DEFAULT_CAPACITY: usize = 1024;
+
+/// Something that happened to a monitor within this process.
+#[derive(Debug, Clone)]
+pub enum Event {
+    /// Stored through the API; `actor_id` is the user who created it
+    MonitorCreated {
+        monitor: Arc<Monitor>,
+        actor_id: Option<Uuid>,
+    },
+    /// A check finished and its result was handed to storage
+    CheckCompleted { result: Arc<MonitorResult> },
+    /// The check counted towards the monitor's health and changed what to notify
+    StateChanged { monitor_id: Uuid, event: StateEvent },
+    /// An alert rule started firing
+    IncidentOpened {
+        monitor_id: Uuid,
+        alert_id: Uuid,
+        alert_type: String,
+        summary: String,
+    },
+    /// A notification went out, or failed to, on one channel
+    NotificationSent {
+        monitor_id: Uuid,
+        channel: String,
+        error: Option<String>,
+    },
+}
+
+impl Event {
+    pub fn monitor_id(&self) -> Uuid {
+        match self {
+            Event::MonitorCreated { monitor, .. } => monitor.id,
+            Event::CheckCompleted { result } => result.monitor_id,
+            Event::StateChanged { monitor_id, .. }
+            | Event::IncidentOpened { monitor_id, .. }
+            | Event::NotificationSent { monitor_id, .. } => *monitor_id,
+        }
+    }
+
+    /// Name used in logs and metric labels.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Event::MonitorCreated { .. } => "monitor_created",
+            Event::CheckCompleted { .. } => "check_completed",
+            Event::StateChanged { .. } => "state_changed",
+            Event::IncidentOpened { .. } => "incident_opened",
+            Event::NotificationSent { .. } => "notification_sent",
+        }
+    }
+}
+
+/// In-process broadcast of [`Event`]s. Publishing never blocks or fails;
+/// events published while nobody subscribes are dropped, and a subscriber
+/// that falls more than the capacity behind skips the oldest ones.
+#[derive(Debug, Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<Event>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl EventBus {
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
+        Self { sender }
+    }
+
+    pub fn publish(&self, event: Event) {
+        let _ = self.sender.send(event);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Event> {
+        self.sender.subscribe()
+    }
+}
@@ -0,0 +1,54 @@
+#[cfg(test)]
+mod events_tests {
+    use crate::events::*;
+    use crate::states::{HealthState, StateEvent, Transition};
+    use uuid::Uuid;
+
+    fn state_changed(monitor_id: Uuid) -> Event {
+        Event::StateChanged {
+            monitor_id,
+            event: StateEvent::Changed(Transition {
+                from: HealthState::Ok,
+                to: HealthState::Degraded,
+            }),
+        }
+    }
+
+    #[test]
+    fn every_subscriber_receives_published_events() {
+        let bus = EventBus::default();
+        let mut first = bus.subscribe();
+        let mut second = bus.subscribe();
+        let monitor_id = Uuid::new_v4();
+        bus.publish(state_changed(monitor_id));
+
+        for receiver in [&mut first, &mut second] {
+            let event = receiver.try_recv().unwrap();
+            assert_eq!(event.kind(), "state_changed");
+            assert_eq!(event.monitor_id(), monitor_id);
+        }
+    }
+
+    #[test]
+    fn publishing_without_subscribers_is_dropped() {
+        let bus = EventBus::default();
+        bus.publish(state_changed(Uuid::new_v4()));
+        let mut late = bus.subscribe();
+        assert!(late.try_recv().is_err());
+    }
+
+    #[test]
+    fn slow_subscribers_skip_the_oldest_events() {
+        let bus = EventBus::new(2);
+        let mut receiver = bus.subscribe();
+        for _ in 0..3 {
+            bus.publish(Event::NotificationSent {
+                monitor_id: Uuid::new_v4(),
+                channel: "webhook endpoints".to_string(),
+                error: None,
+            });
+        }
+        assert!(matches!(receiver.try_recv(), Err(tokio::sync::broadcast::error::TryRecvError::Lagged(1))));
+        assert_eq!(receiver.try_recv().unwrap().kind(), "notification_sent");
+    }
+}
@@ -0,0 +1,123 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::FromRow;
+use uuid::Uuid;
+use crate::{db::DatabasePool, error::Result, models::MonitorResult};
+
+/// Bodies larger than this are cut when stored as evidence
+pub const MAX_BODY_BYTES: usize = 64 * 1024;
+
+/// The full response of the first failed check after a success.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct IncidentEvidence {
+    pub id: Uuid,
+    pub monitor_id: Uuid,
+    pub result_id: Uuid,
+    pub previous_status: String,
+    pub status: String,
+    pub response_code: Option<i32>,
+    pub response_headers: Option<serde_json::Value>,
+    pub response_body: Option<String>,
+    pub body_truncated: bool,
+    pub error_message: Option<String>,
+    pub captured_at: DateTime<Utc>,
+}
+
+/// Evidence is captured when a monitor goes from up to down, not for its first
+/// result or while it stays down.
+pub fn is_first_failure(previous_status: Option<&str>, status: &str) -> bool {
+    previous_status == Some("success") && status != "success"
+}
+
+/// Cuts `body` to at most `max_bytes` on a character boundary.
+pub fn truncate_body(body: &str, max_bytes: usize) -> (String, bool) {
+    if body.len() <= max_bytes {
+        return (body.to_string(), false);
+    }
+    let mut end = max_bytes;
+    while !body.is_char_boundary(end) {
+        end -= 1;
+    }
+    (body[..end].to_string(), true)
+}
+
+impl IncidentEvidence {
+    pub fn from_result(result: &MonitorResult, previous_status: &str) -> Self {
+        let (response_body, body_truncated) = match result.response_body.as_deref() {
+            Some(body) => {
+                let (body, truncated) = truncate_body(body, MAX_BODY_BYTES);
+                (Some(body), truncated)
+            }
+            None => (None, false),
+        };
+        Self {
+            id: Uuid::new_v4(),
+            monitor_id: result.monitor_id,
+            result_id: result.id,
+            previous_status: previous_status.to_string(),
+            status: result.status.clone(),
+            response_code: result.response_code,
+            response_headers: result.response_headers.as_ref().map(|headers| serde_json::json!(headers)),
+            response_body,
+            body_truncated,
+            error_message: result.error_message.clone(),
+            captured_at: result.checked_at,
+        }
+    }
+}
+
+/// Status of the monitor's latest stored result.
+pub async fn previous_status(db: &DatabasePool, monitor_id: Uuid) -> Result<Option<String>> {
+    let status = sqlx::query_scalar(
+        "SELECT status FROM monitor_results WHERE monitor_id = $1 ORDER BY checked_at DESC LIMIT 1",
+    )
+    .bind(monitor_id)
+    .fetch_optional(db)
+    .await?;
+    Ok(status)
+}
+
+pub async fn record(db: &DatabasePool, evidence: &IncidentEvidence) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO incident_evidence
+            (id, monitor_id, result_id, previous_status, status, response_code, response_headers, response_body, body_truncated, error_message, captured_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+        ON CONFLICT (result_id) DO NOTHING
+        "#,
+    )
+    .bind(evidence.id)
+    .bind(evidence.monitor_id)
+    .bind(evidence.result_id)
+    .bind(&evidence.previous_status)
+    .bind(&evidence.status)
+    .bind(evidence.response_code)
+    .bind(&evidence.response_headers)
+    .bind(&evidence.response_body)
+    .bind(evidence.body_truncated)
+    .bind(&evidence.error_message)
+    .bind(evidence.captured_at)
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/// Newest first.
+pub async fn list(db: &DatabasePool, monitor_id: Uuid, limit: i64) -> Result<Vec<IncidentEvidence>> {
+    let evidence = sqlx::query_as::<_, IncidentEvidence>(
+        "SELECT * FROM incident_evidence WHERE monitor_id = $1 ORDER BY captured_at DESC LIMIT $2",
+    )
+    .bind(monitor_id)
+    .bind(limit)
+    .fetch_all(db)
+    .await?;
+    Ok(evidence)
+}
+
+pub async fn get(db: &DatabasePool, id: Uuid) -> Result<Option<IncidentEvidence>> {
+    let evidence = sqlx::query_as::<_, IncidentEvidence>("SELECT * FROM incident_evidence WHERE id = $1")
+        .bind(id)
+        .fetch_optional(db)
+        .await?;
+    Ok(evidence)
+}
@@ -0,0 +1,52 @@
+#[cfg(test)]
+mod evidence_tests {
+    use crate::{evidence::*, models::MonitorResult};
+    use chrono::Utc;
+    use std::collections::BTreeMap;
+    use uuid::Uuid;
+
+    #[test]
+    fn only_up_to_down_transitions_capture() {
+        assert!(is_first_failure(Some("success"), "failure"));
+        assert!(is_first_failure(Some("success"), "timeout"));
+        assert!(!is_first_failure(Some("failure"), "failure"));
+        assert!(!is_first_failure(Some("success"), "success"));
+        assert!(!is_first_failure(None, "failure"));
+    }
+
+    #[test]
+    fn truncation_respects_char_boundaries() {
+        assert_eq!(truncate_body("short", 10), ("short".to_string(), false));
+        let (body, truncated) = truncate_body("aé", 2);
+        assert_eq!(body, "a");
+        assert!(truncated);
+    }
+
+    #[test]
+    fn evidence_keeps_headers_and_caps_body() {
+        let mut headers = BTreeMap::new();
+        headers.insert("content-type".to_string(), "text/html".to_string());
+        let result = MonitorResult {
+            id: Uuid::new_v4(),
+            monitor_id: Uuid::new_v4(),
+            status: "failure".to_string(),
+            response_time: 120,
+            response_code: Some(503),
+            response_body: Some("x".repeat(MAX_BODY_BYTES + 10)),
+            response_headers: Some(headers),
+            error_message: None,
+            error_category: Some("http_status".to_string()),
+            error_hint: None,
+            checked_at: Utc::now(),
+            clock_skew_ms: None,
+            schedule_lag_ms: None,
+            attempts: 1,
+        };
+
+        let evidence = IncidentEvidence::from_result(&result, "success");
+        assert_eq!(evidence.result_id, result.id);
+        assert_eq!(evidence.response_body.as_ref().unwrap().len(), MAX_BODY_BYTES);
+        assert!(evidence.body_truncated);
+        assert_eq!(evidence.response_headers.unwrap()["content-type"], "text/html");
+    }
+}
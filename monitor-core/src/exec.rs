@@ -0,0 +1,281 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::BTreeMap;
+use crate::{
+    error::Result,
+    openmetrics::{MetricAssertion, Sample},
+    Error,
+};
+
+const MAX_ARGS: usize = 32;
+const MAX_ARG_LEN: usize = 1024;
+/// Stdout past this many bytes is not read
+pub const MAX_OUTPUT_BYTES: usize = 64 * 1024;
+/// Longest plugin text kept in messages
+const MAX_TEXT_LEN: usize = 500;
+
+/// How the command reports values besides its exit code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// `TEXT | label=value[UOM];warn;crit;min;max ...` as Nagios plugins print it
+    #[default]
+    Nagios,
+    /// A JSON object; its top-level numbers and booleans can be asserted on
+    Json,
+}
+
+/// A Nagios-style plugin check run instead of the HTTP request. The monitor's
+/// `endpoint` is `exec://<name>`, where `name` is a command the scheduler's
+/// operator allow-listed; it runs without a shell, with `args` as given.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExecCheck {
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub output: OutputFormat,
+    /// Conditions on performance data labels or top-level JSON fields, in
+    /// the `metric_assertions` syntax without labels
+    #[serde(default)]
+    pub assertions: Vec<MetricAssertion>,
+    /// A WARNING exit fails the check instead of passing it
+    #[serde(default)]
+    pub fail_on_warning: bool,
+}
+
+/// The Nagios plugin return codes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PluginState {
+    Ok,
+    Warning,
+    Critical,
+    Unknown,
+}
+
+impl PluginState {
+    /// Codes outside 0-3 are UNKNOWN, as the plugin guidelines ask.
+    pub fn from_exit_code(code: i32) -> Self {
+        match code {
+            0 => PluginState::Ok,
+            1 => PluginState::Warning,
+            2 => PluginState::Critical,
+            _ => PluginState::Unknown,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PluginState::Ok => "OK",
+            PluginState::Warning => "WARNING",
+            PluginState::Critical => "CRITICAL",
+            PluginState::Unknown => "UNKNOWN",
+        }
+    }
+}
+
+/// What a finished run means for the check.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Verdict {
+    Pass,
+    /// CRITICAL, a failing WARNING, or an assertion that does not hold
+    Fail(String),
+    /// UNKNOWN, killed by a signal, or stdout that could not be read
+    Error(String),
+}
+
+impl ExecCheck {
+    pub fn validate(&self, endpoint: &str) -> Result<()> {
+        command_name(endpoint)?;
+        if self.args.len() > MAX_ARGS {
+            return Err(Error::validation(format!("exec checks take at most {} args", MAX_ARGS)));
+        }
+        if self.args.iter().any(|arg| arg.len() > MAX_ARG_LEN || arg.contains('\0')) {
+            return Err(Error::validation(format!(
+                "args must be at most {} bytes and contain no NUL characters",
+                MAX_ARG_LEN
+            )));
+        }
+        if let Some(assertion) = self.assertions.iter().find(|a| !a.labels.is_empty()) {
+            return Err(Error::validation(format!("assertion '{}' must not have labels", assertion)));
+        }
+        Ok(())
+    }
+
+    /// The result body and verdict of a run that exited with `exit_code`,
+    /// `None` when a signal ended it.
+    pub fn evaluate(&self, exit_code: Option<i32>, stdout: &str) -> (serde_json::Value, Verdict) {
+        let Some(code) = exit_code else {
+            return (json!({ "exit_code": null }), Verdict::Error("the command was killed by a signal".to_string()));
+        };
+        let state = PluginState::from_exit_code(code);
+        let (text, values, mut body) = match self.output {
+            OutputFormat::Nagios => {
+                let output = parse_plugin_output(stdout);
+                let body = json!({ "output": output.text, "perfdata": output.perfdata });
+                (output.text, output.perfdata, body)
+            }
+            OutputFormat::Json => match serde_json::from_str::<serde_json::Value>(stdout) {
+                Ok(serde_json::Value::Object(object)) => {
+                    let values = json_values(&object);
+                    (String::new(), values, json!({ "output": object }))
+                }
+                _ => {
+                    let body = json!({ "exit_code": code, "state": state.as_str() });
+                    return (body, Verdict::Error("stdout is not a JSON object".to_string()));
+                }
+            },
+        };
+        body["exit_code"] = json!(code);
+        body["state"] = json!(state.as_str());
+
+        // Plugin text conventionally starts with the service and state already
+        let message = || match text.as_str() {
+            "" => format!("{} (exit code {})", state.as_str(), code),
+            text => text.to_string(),
+        };
+        let verdict = match state {
+            PluginState::Unknown => Verdict::Error(message()),
+            PluginState::Critical => Verdict::Fail(message()),
+            PluginState::Warning if self.fail_on_warning => Verdict::Fail(message()),
+            PluginState::Ok | PluginState::Warning => {
+                let samples: Vec<Sample> = values
+                    .into_iter()
+                    .map(|(name, value)| Sample {
+                        name,
+                        labels: BTreeMap::new(),
+                        value,
+                    })
+                    .collect();
+                match self.assertions.iter().try_for_each(|assertion| assertion.evaluate(&samples)) {
+                    Ok(()) => Verdict::Pass,
+                    Err(message) => Verdict::Fail(message),
+                }
+            }
+        };
+        (body, verdict)
+    }
+}
+
+/// The allow-list name of an `exec://<name>` endpoint.
+pub fn command_name(endpoint: &str) -> Result<&str> {
+    match endpoint.strip_prefix("exec://") {
+        Some(name) if valid_name(name) => Ok(name),
+        _ => Err(Error::validation(
+            "exec_check endpoints must be exec://<name> with a command name of letters, digits, '_' and '-'",
+        )),
+    }
+}
+
+fn valid_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Path of the allow-listed command `name`. `commands` holds comma separated
+/// `name=/absolute/path` entries.
+pub fn resolve(commands: &str, name: &str) -> Result<String> {
+    for entry in commands.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+        let (entry_name, path) = entry
+            .split_once('=')
+            .ok_or_else(|| Error::validation(format!("Invalid exec command entry '{}': expected name=/path", entry)))?;
+        if entry_name.trim() != name {
+            continue;
+        }
+        let path = path.trim();
+        if !path.starts_with('/') {
+            return Err(Error::validation(format!("exec command '{}' must be an absolute path", name)));
+        }
+        return Ok(path.to_string());
+    }
+    Err(Error::forbidden(format!("exec command '{}' is not allow-listed on this scheduler", name)))
+}
+
+/// Text and performance data of a plugin's stdout.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PluginOutput {
+    /// The first line without its performance data
+    pub text: String,
+    /// Values by label, with characters other than letters, digits, `_` and
+    /// `:` replaced by `_` so assertions can name them
+    pub perfdata: BTreeMap<String, f64>,
+}
+
+/// Reads the first line's text and the performance data after `|` on it
+/// and in the long output that follows.
+pub fn parse_plugin_output(stdout: &str) -> PluginOutput {
+    let mut lines = stdout.lines();
+    let first = lines.next().unwrap_or_default();
+    let (text, first_perfdata) = first.split_once('|').unwrap_or((first, ""));
+    let mut perfdata = BTreeMap::new();
+    let long_perfdata = lines.filter_map(|line| line.split_once('|').map(|(_, data)| data));
+    for data in std::iter::once(first_perfdata).chain(long_perfdata) {
+        for (label, value) in parse_perfdata(data) {
+            perfdata.insert(label, value);
+        }
+    }
+    let mut text = text.trim().to_string();
+    if text.len() > MAX_TEXT_LEN {
+        let end = (0..=MAX_TEXT_LEN).rev().find(|i| text.is_char_boundary(*i)).unwrap_or(0);
+        text.truncate(end);
+    }
+    PluginOutput { text, perfdata }
+}
+
+/// `'label'=value[UOM];warn;crit;min;max` items separated by spaces. Values
+/// of `U` (unknown) and items that do not parse are skipped.
+fn parse_perfdata(data: &str) -> Vec<(String, f64)> {
+    let mut items = Vec::new();
+    let mut rest = data.trim_start();
+    while !rest.is_empty() {
+        let (label, after) = match rest.strip_prefix('\'') {
+            Some(quoted) => match quoted.split_once("'=") {
+                Some((label, after)) => (label, after),
+                None => break,
+            },
+            None => match rest.split_once('=') {
+                Some((label, after)) if !label.contains(' ') => (label, after),
+                _ => {
+                    // Not an item; skip to the next space
+                    rest = rest.split_once(' ').map(|(_, next)| next.trim_start()).unwrap_or_default();
+                    continue;
+                }
+            },
+        };
+        let (item, next) = after.split_once(' ').unwrap_or((after, ""));
+        let value = item.split(';').next().unwrap_or_default();
+        let number_end = value
+            .find(|c: char| !(c.is_ascii_digit() || "+-.eE".contains(c)))
+            .unwrap_or(value.len());
+        if let Ok(value) = value[..number_end].parse::<f64>() {
+            items.push((metric_name(label), value));
+        }
+        rest = next.trim_start();
+    }
+    items
+}
+
+fn metric_name(label: &str) -> String {
+    let name: String = label
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' || c == ':' { c } else { '_' })
+        .collect();
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{}", name)
+    } else {
+        name
+    }
+}
+
+/// Top-level numbers, and booleans as 1 or 0.
+fn json_values(object: &serde_json::Map<String, serde_json::Value>) -> BTreeMap<String, f64> {
+    object
+        .iter()
+        .filter_map(|(key, value)| {
+            let value = match value {
+                serde_json::Value::Number(number) => number.as_f64()?,
+                serde_json::Value::Bool(flag) => f64::from(u8::from(*flag)),
+                _ => return None,
+            };
+            Some((key.clone(), value))
+        })
+        .collect()
+}
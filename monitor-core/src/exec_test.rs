@@ -0,0 +1,82 @@
+#[cfg(test)]
+mod exec_tests {
+    use crate::exec::*;
+
+    fn check(value: serde_json::Value) -> ExecCheck {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_parse_plugin_output() {
+        let output = parse_plugin_output(
+            "DISK OK - free space: / 3326 MB (56%); | /=2643MB;5948;5958;0;5968 'inode ratio'=12.5%;;\n\
+             / 15272 MB (77%);\n\
+             /boot 68 MB (69%); | /boot=68MB;88;93;0;98 load1=U",
+        );
+        assert_eq!(output.text, "DISK OK - free space: / 3326 MB (56%);");
+        assert_eq!(output.perfdata.len(), 3);
+        assert_eq!(output.perfdata["_"], 2643.0);
+        assert_eq!(output.perfdata["inode_ratio"], 12.5);
+        assert_eq!(output.perfdata["_boot"], 68.0);
+
+        let plain = parse_plugin_output("PING OK\n");
+        assert_eq!(plain.text, "PING OK");
+        assert!(plain.perfdata.is_empty());
+    }
+
+    #[test]
+    fn test_exit_codes_map_to_verdicts() {
+        let plugin = check(serde_json::json!({}));
+        let (body, verdict) = plugin.evaluate(Some(0), "OK - all good | time=0.12s;1;2");
+        assert_eq!(verdict, Verdict::Pass);
+        assert_eq!(body["state"], "OK");
+        assert_eq!(body["perfdata"]["time"], 0.12);
+
+        assert_eq!(plugin.evaluate(Some(1), "WARNING - slow").1, Verdict::Pass);
+        assert_eq!(
+            plugin.evaluate(Some(2), "CRITICAL - down").1,
+            Verdict::Fail("CRITICAL - down".to_string())
+        );
+        assert_eq!(plugin.evaluate(Some(3), "").1, Verdict::Error("UNKNOWN (exit code 3)".to_string()));
+        assert!(matches!(plugin.evaluate(Some(127), "").1, Verdict::Error(_)));
+        assert!(matches!(plugin.evaluate(None, "").1, Verdict::Error(_)));
+
+        let strict = check(serde_json::json!({ "fail_on_warning": true }));
+        assert!(matches!(strict.evaluate(Some(1), "WARNING - slow").1, Verdict::Fail(_)));
+    }
+
+    #[test]
+    fn test_assertions_on_perfdata_and_json() {
+        let plugin = check(serde_json::json!({ "assertions": ["time < 0.5"] }));
+        assert_eq!(plugin.evaluate(Some(0), "OK | time=0.12s").1, Verdict::Pass);
+        assert_eq!(
+            plugin.evaluate(Some(0), "OK | time=0.8s").1,
+            Verdict::Fail("time is 0.8, expected < 0.5".to_string())
+        );
+
+        let json = check(serde_json::json!({ "output": "json", "assertions": ["healthy == 1", "queue < 100"] }));
+        let (body, verdict) = json.evaluate(Some(0), r#"{"healthy": true, "queue": 12, "version": "1.2"}"#);
+        assert_eq!(verdict, Verdict::Pass);
+        assert_eq!(body["output"]["version"], "1.2");
+        assert!(matches!(json.evaluate(Some(0), r#"{"healthy": false, "queue": 12}"#).1, Verdict::Fail(_)));
+        assert!(matches!(json.evaluate(Some(0), "not json").1, Verdict::Error(_)));
+    }
+
+    #[test]
+    fn test_validate_and_resolve() {
+        let plugin = check(serde_json::json!({ "args": ["-w", "20%", "-c", "10%"] }));
+        assert!(plugin.validate("exec://check_disk").is_ok());
+        assert!(plugin.validate("exec://../bin/sh").is_err());
+        assert!(plugin.validate("http://check_disk").is_err());
+        assert!(check(serde_json::json!({ "assertions": ["time{host=\"a\"} < 1"] }))
+            .validate("exec://check_ping")
+            .is_err());
+
+        let commands = "check_disk=/usr/lib/nagios/plugins/check_disk, health = /opt/health.sh";
+        assert_eq!(resolve(commands, "check_disk").unwrap(), "/usr/lib/nagios/plugins/check_disk");
+        assert_eq!(resolve(commands, "health").unwrap(), "/opt/health.sh");
+        assert!(resolve(commands, "check_load").is_err());
+        assert!(resolve("", "check_disk").is_err());
+        assert!(resolve("check_disk=check_disk", "check_disk").is_err());
+    }
+}
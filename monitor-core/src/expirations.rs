@@ -0,0 +1,257 @@
+use chrono::{DateTime, Utc};
+use reqwest::{Client, Url};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use std::time::Duration;
+use tracing::warn;
+use uuid::Uuid;
+use crate::{db::DatabasePool, error::Result, models::{Alert, Monitor}, settings, Error};
+
+const RDAP_BOOTSTRAP: &str = "https://rdap.org/domain";
+
+/// Alert type created for monitors whose certificate or domain is about to expire.
+pub const EXPIRY_ALERT_TYPE: &str = "expiry";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+pub enum ExpiryKind {
+    Certificate,
+    Domain,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct Expiration {
+    pub monitor_id: Uuid,
+    pub monitor_name: String,
+    #[serde(skip_serializing)]
+    pub tags: Vec<String>,
+    pub kind: ExpiryKind,
+    /// Certificate common name or registered domain
+    pub subject: String,
+    pub expires_at: DateTime<Utc>,
+    pub checked_at: DateTime<Utc>,
+    #[sqlx(skip)]
+    pub days_remaining: i64,
+}
+
+/// Whole days left until `expires_at`, negative once it has passed.
+pub fn days_remaining(expires_at: DateTime<Utc>, now: DateTime<Utc>) -> i64 {
+    (expires_at - now).num_seconds().div_euclid(86_400)
+}
+
+/// Host name of an `https://` endpoint; plain HTTP and IP literals have nothing to track.
+pub fn https_host(endpoint: &str) -> Option<String> {
+    let url = Url::parse(endpoint).ok()?;
+    if url.scheme() != "https" {
+        return None;
+    }
+    let host = url.host_str()?;
+    if host.starts_with('[') || host.parse::<std::net::IpAddr>().is_ok() {
+        return None;
+    }
+    Some(host.trim_end_matches('.').to_lowercase())
+}
+
+/// Best-effort registered domain for RDAP lookups: the last two labels, or three
+/// when the suffix looks like a second-level registry such as `co.uk`.
+pub fn registrable_domain(host: &str) -> String {
+    let labels: Vec<&str> = host.split('.').filter(|l| !l.is_empty()).collect();
+    let keep = match labels.as_slice() {
+        [.., second, tld] if tld.len() == 2 && second.len() <= 3 && labels.len() > 2 => 3,
+        _ => 2,
+    };
+    labels[labels.len().saturating_sub(keep)..].join(".")
+}
+
+/// Common name (or full subject) and `notAfter` of a DER certificate.
+pub fn parse_certificate(der: &[u8]) -> Result<(String, DateTime<Utc>)> {
+    let (_, cert) = x509_parser::parse_x509_certificate(der)
+        .map_err(|e| Error::validation(format!("Invalid certificate: {}", e)))?;
+
+    let subject = cert
+        .subject()
+        .iter_common_name()
+        .next()
+        .and_then(|cn| cn.as_str().ok())
+        .map(str::to_string)
+        .unwrap_or_else(|| cert.subject().to_string());
+    let expires_at = DateTime::from_timestamp(cert.validity().not_after.timestamp(), 0)
+        .ok_or_else(|| Error::validation("Certificate expiry out of range"))?;
+
+    Ok((subject, expires_at))
+}
+
+/// The `expiration` event of an RDAP domain response.
+pub fn parse_rdap_expiry(response: &serde_json::Value) -> Option<DateTime<Utc>> {
+    response["events"]
+        .as_array()?
+        .iter()
+        .find(|event| event["eventAction"] == "expiration")
+        .and_then(|event| event["eventDate"].as_str())
+        .and_then(|date| DateTime::parse_from_rfc3339(date).ok())
+        .map(|date| date.with_timezone(&Utc))
+}
+
+/// Connects to the endpoint and reads the leaf certificate it presents.
+pub async fn certificate_expiry(endpoint: &str, timeout: Duration) -> Result<(String, DateTime<Utc>)> {
+    let client = Client::builder().tls_info(true).timeout(timeout).build()?;
+    let response = client.head(endpoint).send().await?;
+    let der = response
+        .extensions()
+        .get::<reqwest::tls::TlsInfo>()
+        .and_then(|info| info.peer_certificate())
+        .ok_or_else(|| Error::validation(format!("No peer certificate for {}", endpoint)))?;
+
+    parse_certificate(der)
+}
+
+pub async fn domain_expiry(client: &Client, domain: &str) -> Result<DateTime<Utc>> {
+    let response: serde_json::Value = client
+        .get(format!("{}/{}", RDAP_BOOTSTRAP, domain))
+        .header("Accept", "application/rdap+json")
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    parse_rdap_expiry(&response)
+        .ok_or_else(|| Error::not_found(format!("No expiration date published for {}", domain)))
+}
+
+pub async fn record(
+    db: &DatabasePool,
+    monitor_id: Uuid,
+    kind: ExpiryKind,
+    subject: &str,
+    expires_at: DateTime<Utc>,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO monitor_expirations (monitor_id, kind, subject, expires_at, checked_at)
+        VALUES ($1, $2, $3, $4, NOW())
+        ON CONFLICT (monitor_id, kind) DO UPDATE
+        SET subject = EXCLUDED.subject, expires_at = EXCLUDED.expires_at, checked_at = NOW()
+        "#,
+    )
+    .bind(monitor_id)
+    .bind(kind)
+    .bind(subject)
+    .bind(expires_at)
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+/// Refreshes certificate and domain expiry for an HTTPS monitor. Lookups that fail
+/// are logged and keep the previously recorded value. Returns the number updated.
+pub async fn refresh_monitor(db: &DatabasePool, client: &Client, monitor: &Monitor) -> Result<usize> {
+    let Some(host) = https_host(&monitor.endpoint) else {
+        return Ok(0);
+    };
+    let mut updated = 0;
+
+    let timeout = monitor.timeout.unwrap_or(settings::DEFAULT_TIMEOUT).as_std().max(Duration::from_secs(1));
+    match certificate_expiry(&monitor.endpoint, timeout).await {
+        Ok((subject, expires_at)) => {
+            record(db, monitor.id, ExpiryKind::Certificate, &subject, expires_at).await?;
+            updated += 1;
+        }
+        Err(e) => warn!("Certificate lookup for {} failed: {}", monitor.name, e),
+    }
+
+    let domain = registrable_domain(&host);
+    match domain_expiry(client, &domain).await {
+        Ok(expires_at) => {
+            record(db, monitor.id, ExpiryKind::Domain, &domain, expires_at).await?;
+            updated += 1;
+        }
+        Err(e) => warn!("Domain lookup for {} ({}) failed: {}", monitor.name, domain, e),
+    }
+
+    Ok(updated)
+}
+
+/// Last recorded expiry of a monitor's TLS certificate.
+pub async fn certificate_expiry_for(db: &DatabasePool, monitor_id: Uuid) -> Result<Option<DateTime<Utc>>> {
+    let expires_at = sqlx::query_scalar(
+        "SELECT expires_at FROM monitor_expirations WHERE monitor_id = $1 AND kind = 'certificate'",
+    )
+    .bind(monitor_id)
+    .fetch_optional(db)
+    .await?;
+    Ok(expires_at)
+}
+
+/// The soonest recorded certificate or domain expiry of a monitor.
+pub async fn soonest_expiry_for(db: &DatabasePool, monitor_id: Uuid) -> Result<Option<DateTime<Utc>>> {
+    let expires_at = sqlx::query_scalar("SELECT MIN(expires_at) FROM monitor_expirations WHERE monitor_id = $1")
+        .bind(monitor_id)
+        .fetch_one(db)
+        .await?;
+    Ok(expires_at)
+}
+
+/// Recorded expirations soonest first, optionally limited to those within `within_days`.
+pub async fn upcoming(
+    db: &DatabasePool,
+    within_days: Option<i64>,
+    now: DateTime<Utc>,
+) -> Result<Vec<Expiration>> {
+    let mut expirations = sqlx::query_as::<_, Expiration>(
+        r#"
+        SELECT e.monitor_id, m.name AS monitor_name, m.tags, e.kind, e.subject, e.expires_at, e.checked_at
+        FROM monitor_expirations e
+        JOIN monitors m ON m.id = e.monitor_id
+        ORDER BY e.expires_at, m.name
+        "#,
+    )
+    .fetch_all(db)
+    .await?;
+
+    for expiration in &mut expirations {
+        expiration.days_remaining = days_remaining(expiration.expires_at, now);
+    }
+    if let Some(days) = within_days {
+        expirations.retain(|e| e.days_remaining < days);
+    }
+
+    Ok(expirations)
+}
+
+/// Creates one `expiry` alert per monitor in `expirations` that does not already
+/// have one. Returns only the newly created alerts.
+pub async fn create_alert_rules(
+    db: &DatabasePool,
+    expirations: &[Expiration],
+    threshold_days: i64,
+) -> Result<Vec<Alert>> {
+    let mut monitor_ids: Vec<Uuid> = expirations.iter().map(|e| e.monitor_id).collect();
+    monitor_ids.sort();
+    monitor_ids.dedup();
+
+    let config = serde_json::json!({ "threshold_days": threshold_days });
+    let mut created = Vec::new();
+    for monitor_id in monitor_ids {
+        let alert = sqlx::query_as::<_, Alert>(
+            r#"
+            INSERT INTO alerts (id, monitor_id, type_, config, enabled, created_at, updated_at)
+            SELECT $1, $2, $3, $4, true, NOW(), NOW()
+            WHERE NOT EXISTS (SELECT 1 FROM alerts WHERE monitor_id = $2 AND type_ = $3)
+            RETURNING *
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(monitor_id)
+        .bind(EXPIRY_ALERT_TYPE)
+        .bind(&config)
+        .fetch_optional(db)
+        .await?;
+
+        created.extend(alert);
+    }
+
+    Ok(created)
+}
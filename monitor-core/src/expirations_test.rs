@@ -0,0 +1,51 @@
+#[cfg(test)]
+mod expirations_tests {
+    use crate::expirations::*;
+    use chrono::{DateTime, Duration};
+
+    #[test]
+    fn test_days_remaining_rounds_down() {
+        let now = DateTime::parse_from_rfc3339("2024-05-01T12:00:00Z").unwrap().to_utc();
+        assert_eq!(days_remaining(now + Duration::days(30), now), 30);
+        assert_eq!(days_remaining(now + Duration::hours(47), now), 1);
+        assert_eq!(days_remaining(now + Duration::hours(1), now), 0);
+        assert_eq!(days_remaining(now - Duration::hours(1), now), -1);
+    }
+
+    #[test]
+    fn test_https_host() {
+        assert_eq!(https_host("https://API.Example.com/health").as_deref(), Some("api.example.com"));
+        assert_eq!(https_host("http://example.com"), None);
+        assert_eq!(https_host("https://10.0.0.1/health"), None);
+        assert_eq!(https_host("https://[::1]:8443/"), None);
+        assert_eq!(https_host("not a url"), None);
+    }
+
+    #[test]
+    fn test_registrable_domain() {
+        assert_eq!(registrable_domain("api.eu.example.com"), "example.com");
+        assert_eq!(registrable_domain("example.com"), "example.com");
+        assert_eq!(registrable_domain("www.example.co.uk"), "example.co.uk");
+        assert_eq!(registrable_domain("example.io"), "example.io");
+    }
+
+    #[test]
+    fn test_parse_rdap_expiry() {
+        let response = serde_json::json!({
+            "events": [
+                { "eventAction": "registration", "eventDate": "1995-08-14T04:00:00Z" },
+                { "eventAction": "expiration", "eventDate": "2025-08-13T04:00:00Z" }
+            ]
+        });
+        assert_eq!(
+            parse_rdap_expiry(&response),
+            Some(DateTime::parse_from_rfc3339("2025-08-13T04:00:00Z").unwrap().to_utc())
+        );
+        assert_eq!(parse_rdap_expiry(&serde_json::json!({ "events": [] })), None);
+    }
+
+    #[test]
+    fn test_parse_certificate_rejects_garbage() {
+        assert!(parse_certificate(b"not a certificate").is_err());
+    }
+}
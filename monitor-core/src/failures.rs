@@ -0,0 +1,182 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use crate::{db::DatabasePool, error::Result, models::MonitorResult, Error};
+
+/// Why a check failed, stored in `monitor_results.error_category`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailureCategory {
+    DnsError,
+    ConnectTimeout,
+    /// Refused, reset or unreachable before a response
+    ConnectError,
+    TlsError,
+    /// The whole request exceeded the monitor's timeout
+    RequestTimeout,
+    /// A response arrived with an unexpected status code
+    HttpStatus,
+    AssertionFailed,
+    ScriptError,
+    TooManyRedirects,
+    /// The check itself panicked
+    Crashed,
+    Other,
+}
+
+impl FailureCategory {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::DnsError => "dns_error",
+            Self::ConnectTimeout => "connect_timeout",
+            Self::ConnectError => "connect_error",
+            Self::TlsError => "tls_error",
+            Self::RequestTimeout => "request_timeout",
+            Self::HttpStatus => "http_status",
+            Self::AssertionFailed => "assertion_failed",
+            Self::ScriptError => "script_error",
+            Self::TooManyRedirects => "too_many_redirects",
+            Self::Crashed => "crashed",
+            Self::Other => "other",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        serde_json::from_value(serde_json::Value::String(value.to_string())).ok()
+    }
+
+    /// Narrows a connection failure using the messages of its error chain, since
+    /// DNS and TLS errors reach the HTTP client as opaque connect errors.
+    pub fn for_connect_error<S: AsRef<str>>(chain: &[S]) -> Self {
+        let mentions = |needles: &[&str]| {
+            chain.iter().any(|message| {
+                let message = message.as_ref().to_ascii_lowercase();
+                needles.iter().any(|needle| message.contains(needle))
+            })
+        };
+        if mentions(&["dns error", "failed to lookup address", "no record found", "nxdomain", "name or service not known"]) {
+            Self::DnsError
+        } else if mentions(&["certificate", "tls", "ssl", "handshake"]) {
+            Self::TlsError
+        } else {
+            Self::ConnectError
+        }
+    }
+}
+
+/// Whether a failed result may pass on another attempt: timeouts, lost
+/// connections and 5xx responses. DNS, TLS and assertion failures are not
+/// retried since they rarely clear up within seconds.
+pub fn is_transient(result: &MonitorResult) -> bool {
+    if result.status == "timeout" {
+        return true;
+    }
+    match result.error_category.as_deref().and_then(FailureCategory::parse) {
+        Some(FailureCategory::ConnectTimeout | FailureCategory::ConnectError | FailureCategory::RequestTimeout) => {
+            true
+        }
+        Some(FailureCategory::HttpStatus) => result.response_code.is_some_and(|code| (500..600).contains(&code)),
+        _ => false,
+    }
+}
+
+/// What is known about a failed check when suggesting a cause.
+#[derive(Debug, Clone, Copy)]
+pub struct HintContext<'a> {
+    pub category: FailureCategory,
+    pub message: &'a str,
+    pub response_code: Option<i32>,
+    /// Last recorded expiry of the endpoint's certificate, see `expirations`
+    pub certificate_expires_at: Option<DateTime<Utc>>,
+    pub now: DateTime<Utc>,
+}
+
+/// An actionable suggestion for common failures, in the spirit of the script
+/// engine's error suggestions. `None` when nothing useful can be said.
+pub fn hint(context: &HintContext) -> Option<String> {
+    let message = context.message.to_ascii_lowercase();
+    let mentions = |needles: &[&str]| needles.iter().any(|needle| message.contains(needle));
+
+    let hint = match context.category {
+        FailureCategory::DnsError if mentions(&["no record found", "nxdomain", "name or service not known"]) => {
+            "DNS NXDOMAIN — check that the record exists and is spelled correctly".to_string()
+        }
+        FailureCategory::DnsError => "DNS lookup failed — check the resolver and the record's name servers".to_string(),
+        FailureCategory::ConnectError if mentions(&["connection refused"]) => {
+            "connection refused — port closed or nothing listening on it".to_string()
+        }
+        FailureCategory::ConnectError if mentions(&["connection reset"]) => {
+            "connection reset — a firewall or load balancer may be dropping the connection".to_string()
+        }
+        FailureCategory::ConnectError if mentions(&["unreachable", "no route to host"]) => {
+            "host unreachable — check routing, and IPv6 connectivity if the host has AAAA records".to_string()
+        }
+        FailureCategory::ConnectTimeout => {
+            "connect timeout — host down or a firewall silently dropping packets".to_string()
+        }
+        FailureCategory::TlsError if mentions(&["expired"]) => match context.certificate_expires_at {
+            Some(expires_at) if expires_at <= context.now => {
+                let days = (context.now - expires_at).num_days();
+                format!("certificate expired {} day{} ago — renew it", days, if days == 1 { "" } else { "s" })
+            }
+            _ => "certificate expired — renew it".to_string(),
+        },
+        FailureCategory::TlsError if mentions(&["unknownissuer", "unknown issuer", "self signed", "self-signed"]) => {
+            "certificate not trusted — self-signed or the intermediate chain is missing".to_string()
+        }
+        FailureCategory::TlsError if mentions(&["notvalidforname", "not valid for"]) => {
+            "certificate does not cover this host name".to_string()
+        }
+        FailureCategory::RequestTimeout => {
+            "no complete response within the timeout — the endpoint is slow or hanging".to_string()
+        }
+        FailureCategory::TooManyRedirects => {
+            "redirect loop — check the URL's scheme, host and trailing slash".to_string()
+        }
+        FailureCategory::HttpStatus => match context.response_code? {
+            401 | 403 => "authentication rejected — check the monitor's credentials".to_string(),
+            404 => "not found — check the endpoint path".to_string(),
+            429 => "rate limited — lower the check frequency or allow the monitor's source".to_string(),
+            502..=504 => "gateway error — the proxy is up but the upstream service is not responding".to_string(),
+            500..=599 => "server error — check the application's logs".to_string(),
+            _ => return None,
+        },
+        _ => return None,
+    };
+    Some(hint)
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct FailureCount {
+    pub category: String,
+    pub count: i64,
+}
+
+/// Failed checks in `[from, to)` grouped by cause, most frequent first.
+/// Results recorded before categories existed are counted as `other`.
+pub async fn breakdown(
+    db: &DatabasePool,
+    monitor_id: Uuid,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<FailureCount>> {
+    if to <= from {
+        return Err(Error::validation("'to' must be after 'from'"));
+    }
+    let counts = sqlx::query_as::<_, FailureCount>(
+        r#"
+        SELECT COALESCE(error_category, 'other') AS category, COUNT(*) AS count
+        FROM monitor_results
+        WHERE monitor_id = $1 AND checked_at >= $2 AND checked_at < $3 AND status <> 'success'
+        GROUP BY 1
+        ORDER BY count DESC, category
+        "#,
+    )
+    .bind(monitor_id)
+    .bind(from)
+    .bind(to)
+    .fetch_all(db)
+    .await?;
+    Ok(counts)
+}
@@ -0,0 +1,124 @@
+#[cfg(test)]
+mod failures_tests {
+    use crate::failures::*;
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn categories_serialize_as_stored() {
+        for category in [
+            FailureCategory::DnsError,
+            FailureCategory::ConnectTimeout,
+            FailureCategory::TlsError,
+            FailureCategory::HttpStatus,
+            FailureCategory::TooManyRedirects,
+        ] {
+            let json = serde_json::to_value(category).unwrap();
+            assert_eq!(json, category.as_str());
+        }
+    }
+
+    #[test]
+    fn dns_failures_are_recognised() {
+        let chain = ["error sending request for url (https://missing.example/)", "client error (Connect)", "dns error: no record found for Query { name: Name(\"missing.example.\") }"];
+        assert_eq!(FailureCategory::for_connect_error(&chain), FailureCategory::DnsError);
+    }
+
+    #[test]
+    fn tls_failures_are_recognised() {
+        let chain = ["client error (Connect)", "invalid peer certificate: Expired"];
+        assert_eq!(FailureCategory::for_connect_error(&chain), FailureCategory::TlsError);
+    }
+
+    #[test]
+    fn other_connect_failures_stay_generic() {
+        let chain = ["client error (Connect)", "tcp connect error", "Connection refused (os error 111)"];
+        assert_eq!(FailureCategory::for_connect_error(&chain), FailureCategory::ConnectError);
+    }
+
+    fn context(category: FailureCategory, message: &str) -> HintContext<'_> {
+        HintContext {
+            category,
+            message,
+            response_code: None,
+            certificate_expires_at: None,
+            now: Utc.with_ymd_and_hms(2024, 3, 10, 12, 0, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn categories_parse_from_stored_strings() {
+        assert_eq!(FailureCategory::parse("tls_error"), Some(FailureCategory::TlsError));
+        assert_eq!(FailureCategory::parse("bogus"), None);
+    }
+
+    #[test]
+    fn expired_certificate_hint_counts_days() {
+        let mut context = context(FailureCategory::TlsError, "invalid peer certificate: Expired");
+        context.certificate_expires_at = Some(Utc.with_ymd_and_hms(2024, 3, 7, 0, 0, 0).unwrap());
+        assert_eq!(hint(&context).unwrap(), "certificate expired 3 days ago — renew it");
+
+        context.certificate_expires_at = None;
+        assert_eq!(hint(&context).unwrap(), "certificate expired — renew it");
+    }
+
+    #[test]
+    fn connection_refused_points_at_the_port() {
+        let context = context(FailureCategory::ConnectError, "tcp connect error: Connection refused (os error 111)");
+        assert!(hint(&context).unwrap().starts_with("connection refused"));
+    }
+
+    #[test]
+    fn nxdomain_hint() {
+        let context = context(FailureCategory::DnsError, "dns error: no record found for Query");
+        assert!(hint(&context).unwrap().starts_with("DNS NXDOMAIN"));
+    }
+
+    #[test]
+    fn status_hints_depend_on_the_code() {
+        let mut context = context(FailureCategory::HttpStatus, "");
+        context.response_code = Some(503);
+        assert!(hint(&context).unwrap().starts_with("gateway error"));
+        context.response_code = Some(418);
+        assert_eq!(hint(&context), None);
+        context.response_code = None;
+        assert_eq!(hint(&context), None);
+    }
+
+    #[test]
+    fn unknown_failures_have_no_hint() {
+        assert_eq!(hint(&context(FailureCategory::Other, "something odd")), None);
+        assert_eq!(hint(&context(FailureCategory::ConnectError, "tcp connect error")), None);
+    }
+
+    fn failed(status: &str, category: FailureCategory, response_code: Option<i32>) -> crate::models::MonitorResult {
+        crate::models::MonitorResult {
+            id: uuid::Uuid::new_v4(),
+            monitor_id: uuid::Uuid::new_v4(),
+            status: status.to_string(),
+            response_time: 0,
+            response_code,
+            response_body: None,
+            response_headers: None,
+            error_message: None,
+            error_category: Some(category.as_str().to_string()),
+            error_hint: None,
+            checked_at: Utc::now(),
+            clock_skew_ms: None,
+            schedule_lag_ms: None,
+            attempts: 1,
+        }
+    }
+
+    #[test]
+    fn only_transient_failures_are_retried() {
+        assert!(is_transient(&failed("timeout", FailureCategory::Other, None)));
+        assert!(is_transient(&failed("error", FailureCategory::ConnectError, None)));
+        assert!(is_transient(&failed("timeout", FailureCategory::RequestTimeout, None)));
+        assert!(is_transient(&failed("failure", FailureCategory::HttpStatus, Some(503))));
+
+        assert!(!is_transient(&failed("failure", FailureCategory::HttpStatus, Some(404))));
+        assert!(!is_transient(&failed("failure", FailureCategory::AssertionFailed, Some(500))));
+        assert!(!is_transient(&failed("error", FailureCategory::DnsError, None)));
+        assert!(!is_transient(&failed("error", FailureCategory::TlsError, None)));
+    }
+}
@@ -0,0 +1,119 @@
+use std::{fmt, sync::Arc, time::Duration};
+use tracing::warn;
+use crate::{
+    config::{Config, FaultRule, FaultsConfig},
+    error::Result,
+    metrics, random, Error,
+};
+
+const METRIC_INJECTED: &str = "monitor_faults_injected_total";
+/// Environment in which faults are never injected, whatever the config says
+const PRODUCTION: &str = "production";
+
+/// Operations the injector can make fail or stall.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FaultPoint {
+    DbWrite,
+    Redis,
+    Check,
+}
+
+impl FaultPoint {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FaultPoint::DbWrite => "db_write",
+            FaultPoint::Redis => "redis",
+            FaultPoint::Check => "check",
+        }
+    }
+}
+
+impl fmt::Display for FaultPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+    Fail,
+    Stall(Duration),
+}
+
+impl FaultRule {
+    /// The fault for a uniform `draw` in `[0, 1)`: the first `fail_percent`
+    /// fail and the next `stall_percent` stall.
+    pub fn pick(&self, draw: f64) -> Option<Fault> {
+        let roll = draw * 100.0;
+        let fail = self.fail_percent.min(100) as f64;
+        let stall = fail + self.stall_percent.min(100) as f64;
+        if roll < fail {
+            Some(Fault::Fail)
+        } else if roll < stall && self.stall_ms > 0 {
+            Some(Fault::Stall(Duration::from_millis(self.stall_ms)))
+        } else {
+            None
+        }
+    }
+}
+
+/// Makes a share of database writes, Redis calls and outbound checks fail
+/// or stall, so the circuit breaker, retries and the spill buffer can be
+/// exercised end to end. Only exists when the config enables it.
+#[derive(Debug, Clone)]
+pub struct FaultInjector {
+    config: FaultsConfig,
+}
+
+impl FaultInjector {
+    /// The injector the config asks for, if any. Faults are never injected
+    /// in production.
+    pub fn from_config(config: &Config) -> Option<Arc<Self>> {
+        if !config.faults.enabled {
+            return None;
+        }
+        if config.error_reporting.environment == PRODUCTION {
+            warn!("Fault injection is enabled but ignored in production");
+            return None;
+        }
+        metrics::global().describe(METRIC_INJECTED, "Failures and stalls injected for resilience testing");
+        warn!("Fault injection is enabled: {:?}", config.faults);
+        Some(Arc::new(Self {
+            config: config.faults.clone(),
+        }))
+    }
+
+    pub fn rule(&self, point: FaultPoint) -> &FaultRule {
+        match point {
+            FaultPoint::DbWrite => &self.config.db_writes,
+            FaultPoint::Redis => &self.config.redis,
+            FaultPoint::Check => &self.config.checks,
+        }
+    }
+
+    /// Stalls or fails this call at `point` if the dice say so.
+    pub async fn inject(&self, point: FaultPoint) -> Result<()> {
+        let fault = self.rule(point).pick(random::uniform());
+        let label = match fault {
+            None => return Ok(()),
+            Some(Fault::Fail) => "fail",
+            Some(Fault::Stall(_)) => "stall",
+        };
+        metrics::global().increment_counter(METRIC_INJECTED, &[("point", point.as_str()), ("fault", label)]);
+        match fault {
+            Some(Fault::Stall(duration)) => {
+                tokio::time::sleep(duration).await;
+                Ok(())
+            }
+            _ => Err(Error::internal(format!("Injected {} failure", point))),
+        }
+    }
+}
+
+/// `FaultInjector::inject` for call sites whose injector is optional.
+pub async fn inject(faults: Option<&FaultInjector>, point: FaultPoint) -> Result<()> {
+    match faults {
+        Some(faults) => faults.inject(point).await,
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,52 @@
+#[cfg(test)]
+mod faults_tests {
+    use crate::{
+        config::{Config, FaultRule},
+        faults::*,
+    };
+    use std::time::Duration;
+
+    #[test]
+    fn rules_split_the_draw_into_failures_stalls_and_the_rest() {
+        let rule = FaultRule {
+            fail_percent: 10,
+            stall_percent: 20,
+            stall_ms: 1500,
+        };
+        assert_eq!(rule.pick(0.0), Some(Fault::Fail));
+        assert_eq!(rule.pick(0.099), Some(Fault::Fail));
+        assert_eq!(rule.pick(0.1), Some(Fault::Stall(Duration::from_millis(1500))));
+        assert_eq!(rule.pick(0.299), Some(Fault::Stall(Duration::from_millis(1500))));
+        assert_eq!(rule.pick(0.3), None);
+        assert_eq!(FaultRule::default().pick(0.0), None);
+
+        // A stall without a duration is no fault, and percentages cap at 100
+        let rule = FaultRule {
+            fail_percent: 0,
+            stall_percent: 50,
+            stall_ms: 0,
+        };
+        assert_eq!(rule.pick(0.2), None);
+        let always = FaultRule {
+            fail_percent: 250,
+            ..Default::default()
+        };
+        assert_eq!(always.pick(0.999), Some(Fault::Fail));
+    }
+
+    #[test]
+    fn injection_needs_the_flag_and_stays_out_of_production() {
+        let mut config = Config::from_env().unwrap();
+        config.error_reporting.environment = "test".to_string();
+        config.faults.checks.fail_percent = 30;
+        assert!(FaultInjector::from_config(&config).is_none());
+
+        config.faults.enabled = true;
+        let faults = FaultInjector::from_config(&config).unwrap();
+        assert_eq!(faults.rule(FaultPoint::Check).fail_percent, 30);
+        assert_eq!(faults.rule(FaultPoint::DbWrite), &FaultRule::default());
+
+        config.error_reporting.environment = "production".to_string();
+        assert!(FaultInjector::from_config(&config).is_none());
+    }
+}
@@ -0,0 +1,261 @@
+use reqwest::Url;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashSet};
+use crate::{error::Result, transaction::TransactionStep, Error};
+
+#[derive(Debug, Deserialize)]
+pub struct Har {
+    pub log: HarLog,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HarLog {
+    pub entries: Vec<HarEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HarEntry {
+    pub request: HarRequest,
+    pub response: HarResponse,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HarHeader {
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HarRequest {
+    pub method: String,
+    pub url: String,
+    #[serde(default)]
+    pub headers: Vec<HarHeader>,
+    #[serde(rename = "postData")]
+    pub post_data: Option<HarPostData>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HarPostData {
+    pub text: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HarResponse {
+    pub status: i32,
+    #[serde(default)]
+    pub headers: Vec<HarHeader>,
+    #[serde(default)]
+    pub content: HarContent,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct HarContent {
+    #[serde(rename = "mimeType", default)]
+    pub mime_type: String,
+    pub text: Option<String>,
+    pub encoding: Option<String>,
+}
+
+const STATIC_EXTENSIONS: &[&str] = &[
+    "js", "mjs", "css", "map", "png", "jpg", "jpeg", "gif", "webp", "avif", "svg", "ico",
+    "woff", "woff2", "ttf", "otf", "eot", "mp4", "webm", "mp3",
+];
+
+const STATIC_MIME_PREFIXES: &[&str] = &[
+    "image/", "font/", "video/", "audio/", "text/css", "text/javascript",
+    "application/javascript", "application/x-javascript", "application/font",
+];
+
+/// Request headers the browser or the scheduler manages itself. Cookies are
+/// replayed from `Set-Cookie` responses at run time instead.
+const DROPPED_HEADERS: &[&str] = &[
+    "cookie", "host", "content-length", "connection", "accept-encoding",
+    "if-none-match", "if-modified-since", "upgrade-insecure-requests", "priority",
+];
+
+/// Response fields or headers whose values are worth turning into variables.
+const TOKEN_HINTS: &[&str] = &["token", "csrf", "xsrf", "session", "nonce", "jwt"];
+
+/// Values shorter than this are too likely to collide with unrelated text.
+const MIN_TOKEN_LEN: usize = 8;
+
+pub fn is_static_asset(entry: &HarEntry) -> bool {
+    let extension = Url::parse(&entry.request.url).ok().and_then(|url| {
+        url.path()
+            .rsplit('/')
+            .next()
+            .and_then(|file| file.rsplit_once('.'))
+            .map(|(_, ext)| ext.to_lowercase())
+    });
+    if extension.is_some_and(|ext| STATIC_EXTENSIONS.contains(&ext.as_str())) {
+        return true;
+    }
+
+    let mime = entry.response.content.mime_type.to_lowercase();
+    STATIC_MIME_PREFIXES.iter().any(|prefix| mime.starts_with(prefix))
+}
+
+/// A token seen in a response, and the step variable that captures it.
+struct Candidate {
+    value: String,
+    var: String,
+    step: usize,
+    source: String,
+}
+
+fn is_token_name(name: &str) -> bool {
+    let name = name.to_lowercase();
+    TOKEN_HINTS.iter().any(|hint| name.contains(hint))
+}
+
+fn variable_name(key: &str, taken: &HashSet<String>) -> String {
+    let base: String = key
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+        .collect();
+    let base = base.trim_matches('_').to_string();
+    let mut name = base.clone();
+    let mut n = 2;
+    while taken.contains(&name) {
+        name = format!("{}_{}", base, n);
+        n += 1;
+    }
+    name
+}
+
+fn escape_pointer(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+/// Collects token-like string fields of a JSON document with their pointers.
+fn json_tokens(value: &serde_json::Value, pointer: &str, out: &mut Vec<(String, String, String)>) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, child) in map {
+                let child_pointer = format!("{}/{}", pointer, escape_pointer(key));
+                match child {
+                    serde_json::Value::String(s) if is_token_name(key) && s.len() >= MIN_TOKEN_LEN => {
+                        out.push((key.clone(), child_pointer, s.clone()));
+                    }
+                    _ => json_tokens(child, &child_pointer, out),
+                }
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for (index, child) in items.iter().enumerate() {
+                json_tokens(child, &format!("{}/{}", pointer, index), out);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn response_tokens(response: &HarResponse) -> Vec<(String, String, String)> {
+    let mut tokens: Vec<(String, String, String)> = response
+        .headers
+        .iter()
+        .filter(|h| !h.name.eq_ignore_ascii_case("set-cookie") && is_token_name(&h.name))
+        .filter(|h| h.value.len() >= MIN_TOKEN_LEN)
+        .map(|h| (h.name.clone(), format!("header:{}", h.name.to_lowercase()), h.value.clone()))
+        .collect();
+
+    let content = &response.content;
+    if content.mime_type.contains("json")
+        && content.encoding.as_deref() != Some("base64")
+        && let Some(json) = content.text.as_deref().and_then(|t| serde_json::from_str(t).ok())
+    {
+        json_tokens(&json, "", &mut tokens);
+    }
+    tokens
+}
+
+fn step_name(method: &str, url: &str) -> String {
+    match Url::parse(url) {
+        Ok(url) => format!("{} {}", method, url.path()),
+        Err(_) => format!("{} {}", method, url),
+    }
+}
+
+/// Builds transaction steps from a recorded browser session.
+///
+/// Static assets (unless `include_static`), CORS preflights and aborted requests
+/// are dropped. Tokens returned by one response and sent in a later request are
+/// replaced with `{{variables}}` captured from that response at run time.
+pub fn steps_from_har(har: &Har, include_static: bool) -> Result<Vec<TransactionStep>> {
+    let mut steps: Vec<TransactionStep> = Vec::new();
+    let mut candidates: Vec<Candidate> = Vec::new();
+    let mut taken: HashSet<String> = HashSet::new();
+
+    for entry in &har.log.entries {
+        let request = &entry.request;
+        if !(request.url.starts_with("http://") || request.url.starts_with("https://"))
+            || request.method.eq_ignore_ascii_case("OPTIONS")
+            || entry.response.status == 0
+            || (!include_static && is_static_asset(entry))
+        {
+            continue;
+        }
+
+        let mut step = TransactionStep {
+            name: step_name(&request.method, &request.url),
+            method: request.method.to_uppercase(),
+            url: request.url.clone(),
+            headers: request
+                .headers
+                .iter()
+                .filter(|h| {
+                    let name = h.name.to_lowercase();
+                    !name.starts_with(':')
+                        && !name.starts_with("sec-")
+                        && !DROPPED_HEADERS.contains(&name.as_str())
+                })
+                .map(|h| (h.name.to_lowercase(), h.value.clone()))
+                .collect(),
+            body: request.post_data.as_ref().and_then(|p| p.text.clone()),
+            expected_status: entry.response.status,
+            extract: BTreeMap::new(),
+            assertions: Vec::new(),
+        };
+
+        for candidate in &candidates {
+            let placeholder = format!("{{{{{}}}}}", candidate.var);
+            let mut used = false;
+            let mut replace = |text: &mut String| {
+                if text.contains(&candidate.value) {
+                    *text = text.replace(&candidate.value, &placeholder);
+                    used = true;
+                }
+            };
+            replace(&mut step.url);
+            step.headers.values_mut().for_each(&mut replace);
+            if let Some(body) = step.body.as_mut() {
+                replace(body);
+            }
+            if used {
+                steps[candidate.step]
+                    .extract
+                    .insert(candidate.var.clone(), candidate.source.clone());
+            }
+        }
+
+        let index = steps.len();
+        for (key, source, value) in response_tokens(&entry.response) {
+            if candidates.iter().any(|c| c.value == value) {
+                continue;
+            }
+            let var = variable_name(&key, &taken);
+            taken.insert(var.clone());
+            candidates.push(Candidate { value, var, step: index, source });
+        }
+        // Longest first so a token is never partially replaced by one it contains
+        candidates.sort_by_key(|c| std::cmp::Reverse(c.value.len()));
+
+        steps.push(step);
+    }
+
+    if steps.is_empty() {
+        return Err(Error::validation("HAR file contains no replayable requests"));
+    }
+    Ok(steps)
+}
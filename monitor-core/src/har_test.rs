@@ -0,0 +1,95 @@
+#[cfg(test)]
+mod har_tests {
+    use crate::har::*;
+    use serde_json::json;
+
+    fn entry(method: &str, url: &str, status: i32, mime: &str) -> serde_json::Value {
+        json!({
+            "request": { "method": method, "url": url, "headers": [] },
+            "response": { "status": status, "headers": [], "content": { "mimeType": mime } }
+        })
+    }
+
+    #[test]
+    fn test_static_assets_and_preflights_are_dropped() {
+        let har: Har = serde_json::from_value(json!({ "log": { "entries": [
+            entry("GET", "https://example.com/", 200, "text/html"),
+            entry("GET", "https://example.com/app.js?v=3", 200, "application/javascript"),
+            entry("GET", "https://cdn.example.com/logo", 200, "image/png"),
+            entry("OPTIONS", "https://api.example.com/items", 204, ""),
+            entry("GET", "https://api.example.com/items", 200, "application/json"),
+            entry("GET", "data:image/png;base64,AAAA", 200, "image/png"),
+        ] } }))
+        .unwrap();
+
+        let steps = steps_from_har(&har, false).unwrap();
+        assert_eq!(steps.len(), 2);
+        assert_eq!(steps[0].name, "GET /");
+        assert_eq!(steps[1].url, "https://api.example.com/items");
+
+        assert_eq!(steps_from_har(&har, true).unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_tokens_are_parameterized() {
+        let har: Har = serde_json::from_value(json!({ "log": { "entries": [
+            {
+                "request": {
+                    "method": "POST",
+                    "url": "https://example.com/api/login",
+                    "headers": [
+                        { "name": "Content-Type", "value": "application/json" },
+                        { "name": "Cookie", "value": "sid=old" },
+                        { "name": ":authority", "value": "example.com" }
+                    ],
+                    "postData": { "mimeType": "application/json", "text": "{\"user\":\"demo\"}" }
+                },
+                "response": {
+                    "status": 200,
+                    "headers": [{ "name": "X-CSRF-Token", "value": "csrf-123456" }],
+                    "content": {
+                        "mimeType": "application/json",
+                        "text": "{\"auth\":{\"access_token\":\"eyJhbGciOi.payload.sig\",\"expires\":3600}}"
+                    }
+                }
+            },
+            {
+                "request": {
+                    "method": "POST",
+                    "url": "https://example.com/api/orders",
+                    "headers": [
+                        { "name": "Authorization", "value": "Bearer eyJhbGciOi.payload.sig" },
+                        { "name": "X-CSRF-Token", "value": "csrf-123456" }
+                    ],
+                    "postData": { "text": "{\"item\":1}" }
+                },
+                "response": { "status": 201, "content": { "mimeType": "application/json", "text": "{}" } }
+            }
+        ] } }))
+        .unwrap();
+
+        let steps = steps_from_har(&har, false).unwrap();
+        assert_eq!(steps.len(), 2);
+
+        let login = &steps[0];
+        assert_eq!(login.headers.len(), 1);
+        assert_eq!(login.headers["content-type"], "application/json");
+        assert_eq!(login.extract["access_token"], "/auth/access_token");
+        assert_eq!(login.extract["x_csrf_token"], "header:x-csrf-token");
+
+        let order = &steps[1];
+        assert_eq!(order.expected_status, 201);
+        assert_eq!(order.headers["authorization"], "Bearer {{access_token}}");
+        assert_eq!(order.headers["x-csrf-token"], "{{x_csrf_token}}");
+        assert!(order.extract.is_empty());
+    }
+
+    #[test]
+    fn test_empty_har_is_rejected() {
+        let har: Har = serde_json::from_value(json!({ "log": { "entries": [
+            entry("GET", "https://example.com/style.css", 200, "text/css"),
+        ] } }))
+        .unwrap();
+        assert!(steps_from_har(&har, false).is_err());
+    }
+}
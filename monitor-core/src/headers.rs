@@ -0,0 +1,40 @@
+use std::collections::BTreeMap;
+use crate::{error::Result, Error};
+
+const MAX_HEADERS: usize = 100;
+const MAX_VALUE_LEN: usize = 8192;
+
+/// Request headers a monitor sends, by name.
+pub type Headers = BTreeMap<String, String>;
+
+/// Names must be RFC 9110 tokens and values must not be able to end the
+/// header line early.
+pub fn validate(headers: &Headers) -> Result<()> {
+    if headers.len() > MAX_HEADERS {
+        return Err(Error::validation(format!("At most {} headers are allowed", MAX_HEADERS)));
+    }
+    for (name, value) in headers {
+        if !is_token(name) {
+            return Err(Error::validation(format!("Invalid header name '{}'", name)));
+        }
+        if value.len() > MAX_VALUE_LEN {
+            return Err(Error::validation(format!("Header '{}' is longer than {} bytes", name, MAX_VALUE_LEN)));
+        }
+        if value.contains(['\r', '\n', '\0']) {
+            return Err(Error::validation(format!("Header '{}' must not contain line breaks or NUL", name)));
+        }
+    }
+    Ok(())
+}
+
+/// Headers as stored in `monitors.headers`.
+pub fn from_value(value: &serde_json::Value) -> Result<Headers> {
+    let headers: Headers = serde_json::from_value(value.clone())
+        .map_err(|e| Error::validation(format!("headers must be an object of strings: {}", e)))?;
+    validate(&headers)?;
+    Ok(headers)
+}
+
+fn is_token(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c))
+}
@@ -0,0 +1,32 @@
+#[cfg(test)]
+mod headers_tests {
+    use crate::headers::*;
+
+    fn headers(pairs: &[(&str, &str)]) -> Headers {
+        pairs.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect()
+    }
+
+    #[test]
+    fn valid_headers_pass() {
+        assert!(validate(&headers(&[("Authorization", "Bearer abc"), ("X-Request-Id", "42")])).is_ok());
+        assert!(validate(&Headers::new()).is_ok());
+    }
+
+    #[test]
+    fn invalid_names_and_values_are_rejected() {
+        assert!(validate(&headers(&[("", "x")])).is_err());
+        assert!(validate(&headers(&[("X Header", "x")])).is_err());
+        assert!(validate(&headers(&[("X-Header:", "x")])).is_err());
+        assert!(validate(&headers(&[("X-Header", "a\r\nInjected: yes")])).is_err());
+        assert!(validate(&headers(&[("X-Header", &"x".repeat(9000))])).is_err());
+    }
+
+    #[test]
+    fn stored_headers_must_be_an_object_of_strings() {
+        let parsed = from_value(&serde_json::json!({ "Accept": "application/json" })).unwrap();
+        assert_eq!(parsed["Accept"], "application/json");
+        assert!(from_value(&serde_json::json!(["Accept: application/json"])).is_err());
+        assert!(from_value(&serde_json::json!({ "X-Retries": 3 })).is_err());
+        assert!(from_value(&serde_json::json!({ "Bad Name": "x" })).is_err());
+    }
+}
@@ -0,0 +1,141 @@
+use chrono::{DateTime, Duration, Utc};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use crate::{auth::hash_token, db::DatabasePool, durations::Seconds, error::Result, Error};
+
+pub const DEFAULT_GRACE_SECS: i64 = 300;
+const MAX_GRACE_SECS: i64 = 7 * 86_400;
+const TOKEN_PREFIX: &str = "hb_";
+
+/// A passive check: instead of the scheduler reaching out, the monitored job
+/// pings `POST /api/heartbeat/{token}`, and the check fails once no ping came
+/// within the interval plus `grace_secs`. The monitor's `endpoint` is
+/// `heartbeat://<name>`, naming the job.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HeartbeatCheck {
+    /// How late a ping may be, 300 seconds by default
+    #[serde(default)]
+    pub grace_secs: Option<i64>,
+}
+
+/// Pings received for a heartbeat monitor. The token itself is only shown
+/// when it is issued.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Heartbeat {
+    pub monitor_id: Uuid,
+    #[serde(skip_serializing)]
+    pub token_hash: String,
+    pub last_ping_at: Option<DateTime<Utc>>,
+    pub pings: i64,
+    /// When the current token was issued
+    pub created_at: DateTime<Utc>,
+}
+
+impl HeartbeatCheck {
+    pub fn validate(&self, endpoint: &str) -> Result<()> {
+        let url = Url::parse(endpoint).map_err(|e| Error::validation(format!("Invalid heartbeat endpoint: {}", e)))?;
+        if url.scheme() != "heartbeat" || url.host_str().is_none_or(str::is_empty) {
+            return Err(Error::validation("heartbeat_check endpoints must be heartbeat://<name> URLs"));
+        }
+        if self.grace_secs.is_some_and(|secs| !(0..=MAX_GRACE_SECS).contains(&secs)) {
+            return Err(Error::validation(format!("grace_secs must be between 0 and {}", MAX_GRACE_SECS)));
+        }
+        Ok(())
+    }
+
+    pub fn grace(&self) -> Duration {
+        Duration::seconds(self.grace_secs.unwrap_or(DEFAULT_GRACE_SECS))
+    }
+
+    /// When the next ping is due at the latest, counting from the last ping
+    /// or, before the first one, from `since`.
+    pub fn deadline(&self, interval: Seconds, last_ping: Option<DateTime<Utc>>, since: DateTime<Utc>) -> DateTime<Utc> {
+        last_ping.unwrap_or(since) + interval.as_chrono() + self.grace()
+    }
+
+    /// `Err` carries the result's error message once the deadline passed.
+    pub fn evaluate(
+        &self,
+        interval: Seconds,
+        last_ping: Option<DateTime<Utc>>,
+        since: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> std::result::Result<(), String> {
+        let deadline = self.deadline(interval, last_ping, since);
+        if now <= deadline {
+            return Ok(());
+        }
+        let overdue = (now - deadline).num_seconds();
+        Err(match last_ping {
+            Some(at) => format!("no heartbeat since {}, {}s overdue", at.to_rfc3339(), overdue),
+            None => format!("no heartbeat received yet, {}s overdue", overdue),
+        })
+    }
+}
+
+/// A new ping token and the hash that gets stored.
+pub fn generate_token() -> (String, String) {
+    let token = format!("{}{}{}", TOKEN_PREFIX, Uuid::new_v4().simple(), Uuid::new_v4().simple());
+    let hash = hash_token(&token);
+    (token, hash)
+}
+
+pub async fn get(db: &DatabasePool, monitor_id: Uuid) -> Result<Option<Heartbeat>> {
+    let heartbeat = sqlx::query_as::<_, Heartbeat>("SELECT * FROM monitor_heartbeats WHERE monitor_id = $1")
+        .bind(monitor_id)
+        .fetch_optional(db)
+        .await?;
+    Ok(heartbeat)
+}
+
+/// Issues the monitor a new token, replacing any earlier one. Pings already
+/// received still count.
+pub async fn issue_token(db: &DatabasePool, monitor_id: Uuid) -> Result<(String, Heartbeat)> {
+    let (token, token_hash) = generate_token();
+    let heartbeat = sqlx::query_as::<_, Heartbeat>(
+        r#"
+        INSERT INTO monitor_heartbeats (monitor_id, token_hash, created_at)
+        VALUES ($1, $2, NOW())
+        ON CONFLICT (monitor_id) DO UPDATE SET token_hash = $2, created_at = NOW()
+        RETURNING *
+        "#,
+    )
+    .bind(monitor_id)
+    .bind(&token_hash)
+    .fetch_one(db)
+    .await?;
+    Ok((token, heartbeat))
+}
+
+/// Records a ping for the monitor `token` belongs to, if any.
+pub async fn record_ping(db: &DatabasePool, token: &str, at: DateTime<Utc>) -> Result<Option<Uuid>> {
+    let monitor_id = sqlx::query_scalar(
+        "UPDATE monitor_heartbeats SET last_ping_at = $2, pings = pings + 1 WHERE token_hash = $1 RETURNING monitor_id",
+    )
+    .bind(hash_token(token))
+    .bind(at)
+    .fetch_optional(db)
+    .await?;
+    Ok(monitor_id)
+}
+
+/// Records a ping from inside the monitoring stack, which holds no token.
+/// Monitors that never had one get a token nobody knows.
+pub async fn touch(db: &DatabasePool, monitor_id: Uuid, at: DateTime<Utc>) -> Result<()> {
+    let (_, token_hash) = generate_token();
+    sqlx::query(
+        r#"
+        INSERT INTO monitor_heartbeats (monitor_id, token_hash, last_ping_at, pings, created_at)
+        VALUES ($1, $2, $3, 1, $3)
+        ON CONFLICT (monitor_id) DO UPDATE SET last_ping_at = $3, pings = monitor_heartbeats.pings + 1
+        "#,
+    )
+    .bind(monitor_id)
+    .bind(&token_hash)
+    .bind(at)
+    .execute(db)
+    .await?;
+    Ok(())
+}
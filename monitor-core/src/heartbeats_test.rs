@@ -0,0 +1,42 @@
+#[cfg(test)]
+mod heartbeats_tests {
+    use crate::durations::Seconds;
+    use crate::heartbeats::*;
+    use chrono::{Duration, TimeZone, Utc};
+
+    #[test]
+    fn endpoints_name_the_job() {
+        let check = HeartbeatCheck::default();
+        assert!(check.validate("heartbeat://nightly-backup").is_ok());
+        assert!(check.validate("https://example.com").is_err());
+        assert!(check.validate("heartbeat://").is_err());
+        assert!(HeartbeatCheck { grace_secs: Some(-1) }.validate("heartbeat://backup").is_err());
+        assert!(HeartbeatCheck { grace_secs: Some(30 * 86_400) }.validate("heartbeat://backup").is_err());
+    }
+
+    #[test]
+    fn late_pings_fail_after_the_grace_period() {
+        let check = HeartbeatCheck { grace_secs: Some(60) };
+        let interval = Seconds::new(3600);
+        let created = Utc.with_ymd_and_hms(2024, 2, 1, 0, 0, 0).unwrap();
+
+        // Before the first ping the clock starts when the token was issued
+        assert!(check.evaluate(interval, None, created, created + Duration::seconds(3660)).is_ok());
+        let error = check.evaluate(interval, None, created, created + Duration::seconds(3700)).unwrap_err();
+        assert_eq!(error, "no heartbeat received yet, 40s overdue");
+
+        let pinged = created + Duration::hours(5);
+        assert!(check.evaluate(interval, Some(pinged), created, pinged + Duration::minutes(30)).is_ok());
+        let error = check.evaluate(interval, Some(pinged), created, pinged + Duration::hours(2)).unwrap_err();
+        assert!(error.starts_with("no heartbeat since 2024-02-01T05:00:00"), "{}", error);
+        assert!(error.ends_with("3540s overdue"), "{}", error);
+    }
+
+    #[test]
+    fn tokens_are_stored_hashed() {
+        let (token, hash) = generate_token();
+        assert!(token.starts_with("hb_"));
+        assert_eq!(hash, crate::auth::hash_token(&token));
+        assert_ne!(generate_token().0, token);
+    }
+}
@@ -0,0 +1,126 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{
+    encode::IsNull,
+    error::BoxDynError,
+    postgres::{PgArgumentBuffer, PgTypeInfo, PgValueRef},
+    Decode, Encode, Postgres, Type,
+};
+use std::fmt;
+use crate::{error::Result, Error};
+
+/// Method of a monitor's request, stored upper case in `monitors.method`.
+///
+/// In JSON the standard methods are strings in any case, e.g. `"post"`, so a
+/// misspelled one is rejected instead of being sent. Other methods have to be
+/// asked for explicitly as `{ "custom": "PURGE" }`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "MethodRepr", into = "MethodRepr")]
+pub enum HttpMethod {
+    #[default]
+    Get,
+    Head,
+    Post,
+    Put,
+    Patch,
+    Delete,
+    Options,
+    /// A method outside the standard set, sent exactly as given
+    Custom(String),
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum MethodRepr {
+    Standard(String),
+    Custom { custom: String },
+}
+
+impl HttpMethod {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Get => "GET",
+            Self::Head => "HEAD",
+            Self::Post => "POST",
+            Self::Put => "PUT",
+            Self::Patch => "PATCH",
+            Self::Delete => "DELETE",
+            Self::Options => "OPTIONS",
+            Self::Custom(method) => method,
+        }
+    }
+
+    /// One of the standard methods, ignoring case.
+    pub fn standard(method: &str) -> Option<Self> {
+        [Self::Get, Self::Head, Self::Post, Self::Put, Self::Patch, Self::Delete, Self::Options]
+            .into_iter()
+            .find(|standard| standard.as_str().eq_ignore_ascii_case(method))
+    }
+
+    /// A method outside the standard set; it must be an RFC 9110 token.
+    /// Standard names are still mapped to their variants.
+    pub fn custom(method: &str) -> Result<Self> {
+        if let Some(standard) = Self::standard(method) {
+            return Ok(standard);
+        }
+        if method.is_empty() || !method.chars().all(|c| c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c)) {
+            return Err(Error::validation(format!("Invalid HTTP method '{}'", method)));
+        }
+        Ok(Self::Custom(method.to_string()))
+    }
+}
+
+impl fmt::Display for HttpMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl TryFrom<MethodRepr> for HttpMethod {
+    type Error = Error;
+
+    fn try_from(repr: MethodRepr) -> Result<Self> {
+        match repr {
+            MethodRepr::Standard(method) => Self::standard(&method).ok_or_else(|| {
+                Error::validation(format!(
+                    "Unknown HTTP method '{}'; send {{ \"custom\": \"{}\" }} for methods outside the standard set",
+                    method, method
+                ))
+            }),
+            MethodRepr::Custom { custom } => Self::custom(&custom),
+        }
+    }
+}
+
+impl From<HttpMethod> for MethodRepr {
+    fn from(method: HttpMethod) -> Self {
+        match method {
+            HttpMethod::Custom(custom) => Self::Custom { custom },
+            standard => Self::Standard(standard.as_str().to_string()),
+        }
+    }
+}
+
+impl Type<Postgres> for HttpMethod {
+    fn type_info() -> PgTypeInfo {
+        <String as Type<Postgres>>::type_info()
+    }
+
+    fn compatible(ty: &PgTypeInfo) -> bool {
+        <String as Type<Postgres>>::compatible(ty)
+    }
+}
+
+impl Encode<'_, Postgres> for HttpMethod {
+    fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> std::result::Result<IsNull, BoxDynError> {
+        <&str as Encode<Postgres>>::encode(self.as_str(), buf)
+    }
+}
+
+/// Stored methods were validated when they were written; anything outside
+/// the standard set is custom.
+impl<'r> Decode<'r, Postgres> for HttpMethod {
+    fn decode(value: PgValueRef<'r>) -> std::result::Result<Self, BoxDynError> {
+        let method = <String as Decode<Postgres>>::decode(value)?;
+        Ok(Self::standard(&method).unwrap_or(Self::Custom(method)))
+    }
+}
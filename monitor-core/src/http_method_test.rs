@@ -0,0 +1,35 @@
+#[cfg(test)]
+mod http_method_tests {
+    use crate::http_method::*;
+
+    fn parse(value: serde_json::Value) -> Result<HttpMethod, serde_json::Error> {
+        serde_json::from_value(value)
+    }
+
+    #[test]
+    fn standard_methods_are_canonical_upper_case() {
+        assert_eq!(parse(serde_json::json!("post")).unwrap(), HttpMethod::Post);
+        assert_eq!(parse(serde_json::json!("Options")).unwrap(), HttpMethod::Options);
+        assert_eq!(serde_json::to_value(HttpMethod::Delete).unwrap(), "DELETE");
+        assert_eq!(HttpMethod::Patch.to_string(), "PATCH");
+    }
+
+    #[test]
+    fn misspelled_methods_are_rejected() {
+        let error = parse(serde_json::json!("GTE")).unwrap_err().to_string();
+        assert!(error.contains("Unknown HTTP method 'GTE'"), "{}", error);
+        assert!(parse(serde_json::json!("")).is_err());
+    }
+
+    #[test]
+    fn custom_methods_are_explicit() {
+        let purge = parse(serde_json::json!({ "custom": "PURGE" })).unwrap();
+        assert_eq!(purge, HttpMethod::Custom("PURGE".to_string()));
+        assert_eq!(serde_json::to_value(&purge).unwrap(), serde_json::json!({ "custom": "PURGE" }));
+
+        // Standard names keep their variant
+        assert_eq!(parse(serde_json::json!({ "custom": "get" })).unwrap(), HttpMethod::Get);
+        assert!(parse(serde_json::json!({ "custom": "BAD METHOD" })).is_err());
+        assert!(HttpMethod::custom("PURGE\r\n").is_err());
+    }
+}
@@ -0,0 +1,160 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use crate::{
+    db::DatabasePool, durations::Seconds, error::Result, models::CreateMonitorRequest, status_match::ExpectedStatus, Error,
+};
+
+/// Interval of monitors created from accepted proposals unless the reviewer
+/// picks another.
+pub const DEFAULT_INTERVAL: Seconds = Seconds::new(300);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+pub enum ProposalStatus {
+    Pending,
+    Accepted,
+    Rejected,
+}
+
+/// A monitor an importer would create for one cloud resource.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ImportCandidate {
+    pub source: String,
+    /// Stable across scans, e.g. `route53:<zone>/<name>`
+    pub resource_id: String,
+    pub name: String,
+    pub endpoint: String,
+    pub tags: Vec<String>,
+}
+
+/// A candidate waiting for a user to accept or reject it.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct ImportProposal {
+    pub id: Uuid,
+    pub source: String,
+    pub resource_id: String,
+    pub name: String,
+    pub endpoint: String,
+    pub tags: Vec<String>,
+    pub status: ProposalStatus,
+    /// The monitor created when it was accepted
+    pub monitor_id: Option<Uuid>,
+    pub decided_by: Option<Uuid>,
+    pub proposed_at: DateTime<Utc>,
+    pub decided_at: Option<DateTime<Utc>>,
+}
+
+/// Adjustments made while accepting; omitted fields keep the proposal's values.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AcceptProposalRequest {
+    pub name: Option<String>,
+    pub endpoint: Option<String>,
+    pub interval: Option<Seconds>,
+    pub expected_status: Option<ExpectedStatus>,
+    pub tags: Option<Vec<String>>,
+    pub team_id: Option<Uuid>,
+}
+
+impl ImportProposal {
+    pub fn monitor_request(&self, request: &AcceptProposalRequest) -> Result<CreateMonitorRequest> {
+        CreateMonitorRequest::builder(
+            request.name.clone().unwrap_or_else(|| self.name.clone()),
+            request.endpoint.clone().unwrap_or_else(|| self.endpoint.clone()),
+        )
+        .expected_status(request.expected_status.clone().unwrap_or_default())
+        .interval(request.interval.unwrap_or(DEFAULT_INTERVAL))
+        .tags(request.tags.clone().unwrap_or_else(|| self.tags.clone()))
+        .team(request.team_id)
+        .build()
+    }
+}
+
+/// Stores new candidates as pending proposals and refreshes pending ones.
+/// Decided resources and endpoints that already have a monitor are left
+/// alone. Returns how many candidates were stored.
+pub async fn propose(db: &DatabasePool, candidates: &[ImportCandidate]) -> Result<usize> {
+    let mut stored = 0;
+    for candidate in candidates {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO import_proposals (id, source, resource_id, name, endpoint, tags, status, proposed_at)
+            SELECT $1, $2, $3, $4, $5, $6, 'pending', NOW()
+            WHERE NOT EXISTS (SELECT 1 FROM monitors WHERE endpoint = $5)
+            ON CONFLICT (resource_id) DO UPDATE
+            SET name = EXCLUDED.name, endpoint = EXCLUDED.endpoint, tags = EXCLUDED.tags, proposed_at = NOW()
+            WHERE import_proposals.status = 'pending'
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(&candidate.source)
+        .bind(&candidate.resource_id)
+        .bind(&candidate.name)
+        .bind(&candidate.endpoint)
+        .bind(&candidate.tags)
+        .execute(db)
+        .await?;
+        stored += result.rows_affected() as usize;
+    }
+    Ok(stored)
+}
+
+/// Oldest first, optionally only proposals in one status.
+pub async fn list(db: &DatabasePool, status: Option<ProposalStatus>) -> Result<Vec<ImportProposal>> {
+    let proposals = sqlx::query_as::<_, ImportProposal>(
+        "SELECT * FROM import_proposals WHERE $1::text IS NULL OR status = $1 ORDER BY proposed_at, name",
+    )
+    .bind(status)
+    .fetch_all(db)
+    .await?;
+    Ok(proposals)
+}
+
+/// Records the decision; fails if the proposal was already decided.
+pub async fn decide(db: &DatabasePool, id: Uuid, decided_by: Uuid, status: ProposalStatus) -> Result<ImportProposal> {
+    let proposal = sqlx::query_as::<_, ImportProposal>(
+        r#"
+        UPDATE import_proposals SET status = $3, decided_by = $2, decided_at = NOW()
+        WHERE id = $1 AND status = 'pending'
+        RETURNING *
+        "#,
+    )
+    .bind(id)
+    .bind(decided_by)
+    .bind(status)
+    .fetch_optional(db)
+    .await?;
+    match proposal {
+        Some(proposal) => Ok(proposal),
+        None if exists(db, id).await? => Err(Error::validation(format!("Proposal {} is no longer pending", id))),
+        None => Err(Error::not_found(format!("Proposal {} not found", id))),
+    }
+}
+
+/// Puts an accepted proposal back when its monitor could not be created.
+pub async fn reopen(db: &DatabasePool, id: Uuid) -> Result<()> {
+    sqlx::query("UPDATE import_proposals SET status = 'pending', decided_by = NULL, decided_at = NULL WHERE id = $1")
+        .bind(id)
+        .execute(db)
+        .await?;
+    Ok(())
+}
+
+pub async fn set_monitor(db: &DatabasePool, id: Uuid, monitor_id: Uuid) -> Result<()> {
+    sqlx::query("UPDATE import_proposals SET monitor_id = $2 WHERE id = $1")
+        .bind(id)
+        .bind(monitor_id)
+        .execute(db)
+        .await?;
+    Ok(())
+}
+
+async fn exists(db: &DatabasePool, id: Uuid) -> Result<bool> {
+    let exists = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM import_proposals WHERE id = $1)")
+        .bind(id)
+        .fetch_one(db)
+        .await?;
+    Ok(exists)
+}
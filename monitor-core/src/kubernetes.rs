@@ -0,0 +1,114 @@
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use crate::{
+    discovery::{self, DesiredMonitor, Discovered, KUBERNETES},
+    error::Result,
+};
+
+#[derive(Debug, Deserialize)]
+pub struct ObjectList<T> {
+    pub items: Vec<T>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ObjectMeta {
+    pub name: String,
+    #[serde(default)]
+    pub namespace: String,
+    #[serde(default)]
+    pub annotations: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Ingress {
+    pub metadata: ObjectMeta,
+    #[serde(default)]
+    pub spec: IngressSpec,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct IngressSpec {
+    #[serde(default)]
+    pub rules: Vec<IngressRule>,
+    #[serde(default)]
+    pub tls: Vec<IngressTls>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct IngressRule {
+    pub host: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct IngressTls {
+    #[serde(default)]
+    pub hosts: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Service {
+    pub metadata: ObjectMeta,
+    #[serde(default)]
+    pub spec: ServiceSpec,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ServiceSpec {
+    #[serde(default)]
+    pub ports: Vec<ServicePort>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ServicePort {
+    pub port: u16,
+    pub name: Option<String>,
+}
+
+/// `None` unless the Ingress is annotated with `monitor.yeheng.io/enabled: "true"`.
+/// The URL is built from the first rule's host, over HTTPS when the host is
+/// listed under `tls`.
+pub fn from_ingress(ingress: &Ingress) -> Result<Option<DesiredMonitor>> {
+    let host = ingress.spec.rules.iter().find_map(|rule| rule.host.clone());
+    let url = host.map(|host| {
+        let https = ingress.spec.tls.iter().any(|tls| tls.hosts.contains(&host));
+        format!("{}://{}", if https { "https" } else { "http" }, host)
+    });
+    desired("ingress", &ingress.metadata, url)
+}
+
+/// `None` unless annotated. The URL uses the cluster DNS name and the port
+/// named by `monitor.yeheng.io/port`, or the first port.
+pub fn from_service(service: &Service) -> Result<Option<DesiredMonitor>> {
+    let meta = &service.metadata;
+    let port = match discovery::label(&meta.annotations, "port") {
+        Some(wanted) => service
+            .spec
+            .ports
+            .iter()
+            .find(|p| p.name.as_deref() == Some(wanted) || p.port.to_string() == wanted)
+            .map(|p| p.port),
+        None => service.spec.ports.first().map(|p| p.port),
+    };
+    let url = port.map(|port| format!("http://{}.{}.svc:{}", meta.name, meta.namespace, port));
+    desired("service", meta, url)
+}
+
+/// `<kind>/<namespace>/<name>`, also used in the source tag.
+pub fn source_name(kind: &str, meta: &ObjectMeta) -> String {
+    format!("{}/{}/{}", kind, meta.namespace, meta.name)
+}
+
+fn desired(kind: &str, meta: &ObjectMeta, base_url: Option<String>) -> Result<Option<DesiredMonitor>> {
+    let default_name = format!("{}/{}", meta.namespace, meta.name);
+    KUBERNETES.desired(source_name(kind, meta), default_name, &meta.annotations, base_url)
+}
+
+/// Monitors asked for by annotated objects, and the objects whose
+/// annotations could not be read, with why.
+pub fn desired_monitors(ingresses: &[Ingress], services: &[Service]) -> Discovered {
+    let candidates = ingresses
+        .iter()
+        .map(|i| (source_name("ingress", &i.metadata), from_ingress(i)))
+        .chain(services.iter().map(|s| (source_name("service", &s.metadata), from_service(s))));
+    discovery::collect(candidates)
+}
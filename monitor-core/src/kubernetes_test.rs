@@ -0,0 +1,179 @@
+#[cfg(test)]
+mod kubernetes_tests {
+    use crate::{
+        discovery::{DesiredMonitor, SyncAction, KUBERNETES},
+        durations::Seconds,
+        http_method::HttpMethod,
+        kubernetes::*,
+        models::Monitor,
+    };
+    use chrono::Utc;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    fn ingress(annotations: serde_json::Value) -> Ingress {
+        serde_json::from_value(json!({
+            "metadata": { "name": "shop", "namespace": "prod", "annotations": annotations },
+            "spec": {
+                "rules": [{ "host": "shop.example.com" }],
+                "tls": [{ "hosts": ["shop.example.com"] }]
+            }
+        }))
+        .unwrap()
+    }
+
+    fn monitor_for(desired: &DesiredMonitor) -> Monitor {
+        let now = Utc::now();
+        Monitor {
+            id: Uuid::new_v4(),
+            name: desired.name.clone(),
+            endpoint: desired.endpoint.clone(),
+            method: HttpMethod::Get,
+            headers: None,
+            body: None,
+            expected_status: desired.expected_status.clone(),
+            timeout: None,
+            interval: desired.interval,
+            script: None,
+            pre_request_script: None,
+            enabled: true,
+            paused_reason: None,
+            paused_by: None,
+            paused_at: None,
+            resume_at: None,
+            tags: desired.tags.clone(),
+            owner_id: None,
+            team_id: None,
+            system_key: None,
+            retries: None,
+            retry_delay_ms: None,
+            schedule_cron: None,
+            notification_channels: None,
+            script_profile: None,
+            credentials: None,
+            steps: None,
+            metric_assertions: None,
+            broker_check: None,
+            storage_check: None,
+            ntp_check: None,
+            snmp_check: None,
+            exec_check: None,
+            tcp_check: None,
+            dns_check: None,
+            tls_check: None,
+            websocket_check: None,
+            mail_check: None,
+            database_check: None,
+            heartbeat_check: None,
+            expected_body_contains: None,
+            expected_body_regex: None,
+            bypass_dns_cache: false,
+            connect_timeout_ms: None,
+            tls_timeout_ms: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn ingress_annotations_build_a_monitor() {
+        let desired = from_ingress(&ingress(json!({
+            "monitor.yeheng.io/enabled": "true",
+            "monitor.yeheng.io/path": "/healthz",
+            "monitor.yeheng.io/interval": "30",
+            "monitor.yeheng.io/tags": "shop, eu"
+        })))
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(desired.source, "ingress/prod/shop");
+        assert_eq!(desired.name, "prod/shop");
+        assert_eq!(desired.endpoint, "https://shop.example.com/healthz");
+        assert_eq!(desired.interval, Seconds::new(30));
+        assert_eq!(desired.tags, vec!["eu", "k8s", "k8s:ingress/prod/shop", "shop"]);
+    }
+
+    #[test]
+    fn objects_without_the_opt_in_are_ignored() {
+        assert!(from_ingress(&ingress(json!({}))).unwrap().is_none());
+        assert!(from_ingress(&ingress(json!({ "monitor.yeheng.io/interval": "abc" }))).unwrap().is_none());
+    }
+
+    #[test]
+    fn invalid_annotations_are_errors() {
+        let result = from_ingress(&ingress(json!({
+            "monitor.yeheng.io/enabled": "true",
+            "monitor.yeheng.io/expected-status": "abc"
+        })));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn services_use_the_named_port() {
+        let service: Service = serde_json::from_value(json!({
+            "metadata": {
+                "name": "api",
+                "namespace": "prod",
+                "annotations": { "monitor.yeheng.io/enabled": "true", "monitor.yeheng.io/port": "http" }
+            },
+            "spec": { "ports": [{ "name": "grpc", "port": 9090 }, { "name": "http", "port": 8080 }] }
+        }))
+        .unwrap();
+        let desired = from_service(&service).unwrap().unwrap();
+        assert_eq!(desired.endpoint, "http://api.prod.svc:8080/");
+    }
+
+    #[test]
+    fn plan_creates_updates_and_pauses() {
+        let annotations = json!({ "monitor.yeheng.io/enabled": "true" });
+        let desired = from_ingress(&ingress(annotations)).unwrap().unwrap();
+
+        assert_eq!(KUBERNETES.plan(std::slice::from_ref(&desired), &[], &[]), vec![SyncAction::Create(desired.clone())]);
+
+        let mut existing = monitor_for(&desired);
+        assert!(KUBERNETES.plan(std::slice::from_ref(&desired), &[], std::slice::from_ref(&existing)).is_empty());
+
+        existing.interval = Seconds::new(300);
+        assert_eq!(
+            KUBERNETES.plan(std::slice::from_ref(&desired), &[], std::slice::from_ref(&existing)),
+            vec![SyncAction::Update(existing.id, desired.clone())]
+        );
+
+        assert_eq!(KUBERNETES.plan(&[], &[], std::slice::from_ref(&existing)), vec![SyncAction::Pause(existing.id)]);
+    }
+
+    #[test]
+    fn plan_only_resumes_monitors_it_paused() {
+        let annotations = json!({ "monitor.yeheng.io/enabled": "true" });
+        let desired = from_ingress(&ingress(annotations)).unwrap().unwrap();
+        let mut existing = monitor_for(&desired);
+        existing.enabled = false;
+        existing.paused_reason = Some("Planned maintenance".to_string());
+        assert!(KUBERNETES.plan(std::slice::from_ref(&desired), &[], std::slice::from_ref(&existing)).is_empty());
+
+        existing.paused_reason = Some(KUBERNETES.removed_reason.to_string());
+        assert_eq!(
+            KUBERNETES.plan(std::slice::from_ref(&desired), &[], std::slice::from_ref(&existing)),
+            vec![SyncAction::Resume(existing.id)]
+        );
+
+        let unmanaged = Monitor { tags: vec![], ..existing };
+        assert_eq!(KUBERNETES.plan(&[], &[], &[unmanaged]), vec![]);
+    }
+
+    #[test]
+    fn invalid_annotations_keep_the_existing_monitor() {
+        let broken = ingress(json!({
+            "monitor.yeheng.io/enabled": "true",
+            "monitor.yeheng.io/interval": "often"
+        }));
+        let valid = from_ingress(&ingress(json!({ "monitor.yeheng.io/enabled": "true" }))).unwrap().unwrap();
+        let existing = monitor_for(&valid);
+
+        let (desired, invalid) = desired_monitors(&[broken], &[]);
+        assert!(desired.is_empty());
+        let invalid: Vec<String> = invalid.into_iter().map(|(source, _)| source).collect();
+        assert_eq!(invalid, vec!["ingress/prod/shop"]);
+        assert!(KUBERNETES.plan(&desired, &invalid, &[existing]).is_empty());
+    }
+}
@@ -1,10 +1,268 @@
+// Without a TLS backend every https:// check would fail at runtime
+#[cfg(not(any(feature = "native-tls", feature = "rustls")))]
+compile_error!("monitor-core needs a TLS backend: enable the `native-tls` or `rustls` feature");
+
 pub mod models;
 pub mod config;
+pub mod consul;
 pub mod error;
+pub mod error_reporting;
+pub mod databases;
 pub mod db;
 pub mod cache;
+pub mod changes;
+pub mod circuit_breaker;
+pub mod clock;
+pub mod alerts;
+pub mod approvals;
+pub mod assertions;
+pub mod audit;
 pub mod auth;
+pub mod body_match;
+pub mod aws;
+pub mod brokers;
+pub mod build_info;
+pub mod availability;
+pub mod correlation;
+pub mod crypto;
+pub mod discovery;
+pub mod dns;
+pub mod doctor;
+pub mod drift;
+pub mod durations;
+pub mod events;
+pub mod duplicates;
+pub mod evidence;
+pub mod exec;
+pub mod expirations;
+pub mod failures;
+pub mod faults;
+pub mod har;
+pub mod headers;
+pub mod heartbeats;
+pub mod http_method;
+pub mod imports;
+pub mod docker;
+pub mod kubernetes;
+pub mod locks;
 pub mod logging;
+pub mod mail;
+pub mod maintenance;
+pub mod metrics;
+pub mod monitor_builder;
+pub mod ntp;
+pub mod openmetrics;
+pub mod pagerduty;
+pub mod pause;
+pub mod queue;
+pub mod random;
+pub mod redaction;
+pub mod reload;
+pub mod repository;
+pub mod retention;
+pub mod rollups;
+pub mod runtime;
+pub mod runtime_settings;
+pub mod secrets;
+pub mod self_monitoring;
+pub mod settings;
+pub mod sketch;
+pub mod snmp;
+pub mod states;
+pub mod stats;
+pub mod status_match;
+pub mod storage;
+pub mod supervisor;
+pub mod tcp;
+pub mod teams;
+pub mod telegram;
+pub mod tls;
+pub mod transaction;
+pub mod webhook;
+pub mod websocket;
 
 pub use config::Config;
-pub use error::{Error, Result};
\ No newline at end of file
+pub use error::{Error, Result};
+
+#[cfg(test)]
+pub mod auth_test;
+
+#[cfg(test)]
+pub mod webhook_test;
+
+#[cfg(test)]
+pub mod crypto_test;
+
+#[cfg(test)]
+pub mod doctor_test;
+
+#[cfg(test)]
+pub mod reload_test;
+
+#[cfg(test)]
+pub mod circuit_breaker_test;
+
+#[cfg(test)]
+pub mod clock_test;
+
+#[cfg(test)]
+pub mod stats_test;
+
+#[cfg(test)]
+pub mod sketch_test;
+
+#[cfg(test)]
+pub mod availability_test;
+
+
+#[cfg(test)]
+pub mod expirations_test;
+
+#[cfg(test)]
+pub mod transaction_test;
+
+#[cfg(test)]
+pub mod har_test;
+
+#[cfg(test)]
+pub mod settings_test;
+
+#[cfg(test)]
+pub mod duplicates_test;
+
+#[cfg(test)]
+pub mod pause_test;
+
+#[cfg(test)]
+pub mod teams_test;
+
+#[cfg(test)]
+pub mod changes_test;
+
+#[cfg(test)]
+pub mod assertions_test;
+
+#[cfg(test)]
+pub mod approvals_test;
+#[cfg(test)]
+pub mod config_test;
+
+#[cfg(test)]
+pub mod drift_test;
+
+#[cfg(test)]
+pub mod queue_test;
+
+#[cfg(test)]
+pub mod dns_test;
+
+#[cfg(test)]
+pub mod failures_test;
+
+#[cfg(test)]
+pub mod evidence_test;
+
+#[cfg(test)]
+pub mod correlation_test;
+
+#[cfg(test)]
+pub mod maintenance_test;
+
+#[cfg(test)]
+pub mod kubernetes_test;
+
+#[cfg(test)]
+pub mod repository_test;
+
+#[cfg(test)]
+pub mod docker_test;
+
+#[cfg(test)]
+pub mod consul_test;
+
+#[cfg(test)]
+pub mod alerts_test;
+
+#[cfg(test)]
+pub mod aws_test;
+
+#[cfg(test)]
+pub mod openmetrics_test;
+
+#[cfg(test)]
+pub mod states_test;
+
+#[cfg(test)]
+pub mod brokers_test;
+
+#[cfg(test)]
+pub mod storage_test;
+
+#[cfg(test)]
+pub mod snmp_test;
+
+#[cfg(test)]
+pub mod exec_test;
+
+#[cfg(test)]
+pub mod redaction_test;
+
+#[cfg(test)]
+pub mod tcp_test;
+
+#[cfg(test)]
+pub mod headers_test;
+
+#[cfg(test)]
+pub mod http_method_test;
+
+#[cfg(test)]
+pub mod durations_test;
+
+#[cfg(test)]
+pub mod tls_test;
+
+#[cfg(test)]
+pub mod monitor_builder_test;
+
+#[cfg(test)]
+pub mod body_match_test;
+
+#[cfg(test)]
+pub mod status_match_test;
+
+#[cfg(test)]
+pub mod events_test;
+
+#[cfg(test)]
+pub mod supervisor_test;
+
+#[cfg(test)]
+pub mod websocket_test;
+
+#[cfg(test)]
+pub mod mail_test;
+
+#[cfg(test)]
+pub mod databases_test;
+
+#[cfg(test)]
+pub mod faults_test;
+
+#[cfg(test)]
+pub mod heartbeats_test;
+
+#[cfg(test)]
+pub mod build_info_test;
+
+#[cfg(test)]
+pub mod self_monitoring_test;
+
+#[cfg(test)]
+pub mod runtime_test;
+
+#[cfg(test)]
+pub mod error_reporting_test;
+
+#[cfg(test)]
+pub mod random_test;
@@ -0,0 +1,81 @@
+use chrono::{DateTime, Utc};
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+use crate::{db::DatabasePool, error::Result};
+
+/// The previous run is still in progress on this worker
+pub const SKIP_LOCAL: &str = "running_locally";
+/// Another worker holds the monitor's check lease
+pub const SKIP_REMOTE: &str = "running_elsewhere";
+/// The monitor is in a maintenance window that skips checks
+pub const SKIP_MAINTENANCE: &str = "maintenance";
+
+/// Holds a transaction-scoped advisory lock; dropping it rolls the transaction
+/// back, which releases the lock. Pins one pooled connection while held.
+pub struct AdvisoryLock {
+    _tx: Transaction<'static, Postgres>,
+}
+
+/// A claimed `monitor_check_leases` row. Holds no connection; the lease
+/// expires on its own if the worker stops before releasing it.
+pub struct CheckLease {
+    pub monitor_id: Uuid,
+    token: Uuid,
+}
+
+/// `None` when another worker holds an unexpired lease on the monitor. A lease
+/// `worker` failed to release is taken over, since its own runs are already
+/// serialized in memory.
+pub async fn claim_check(
+    db: &DatabasePool,
+    monitor_id: Uuid,
+    worker: &str,
+    expires_at: DateTime<Utc>,
+) -> Result<Option<CheckLease>> {
+    let token = Uuid::new_v4();
+    let claimed = sqlx::query(
+        "INSERT INTO monitor_check_leases (monitor_id, worker, token, expires_at) VALUES ($1, $2, $3, $4) \
+         ON CONFLICT (monitor_id) DO UPDATE \
+         SET worker = EXCLUDED.worker, token = EXCLUDED.token, expires_at = EXCLUDED.expires_at \
+         WHERE monitor_check_leases.expires_at < NOW() OR monitor_check_leases.worker = EXCLUDED.worker",
+    )
+    .bind(monitor_id)
+    .bind(worker)
+    .bind(token)
+    .bind(expires_at)
+    .execute(db)
+    .await?
+    .rows_affected();
+    Ok((claimed == 1).then_some(CheckLease { monitor_id, token }))
+}
+
+/// Deletes the lease unless it already expired and another worker took it.
+pub async fn release_check(db: &DatabasePool, lease: &CheckLease) -> Result<()> {
+    sqlx::query("DELETE FROM monitor_check_leases WHERE monitor_id = $1 AND token = $2")
+        .bind(lease.monitor_id)
+        .bind(lease.token)
+        .execute(db)
+        .await?;
+    Ok(())
+}
+
+/// `None` when another session already holds `key`.
+pub async fn try_lock(db: &DatabasePool, key: i64) -> Result<Option<AdvisoryLock>> {
+    let mut tx = db.begin().await?;
+    let acquired = sqlx::query_scalar::<_, bool>("SELECT pg_try_advisory_xact_lock($1)")
+        .bind(key)
+        .fetch_one(&mut *tx)
+        .await?;
+    Ok(acquired.then_some(AdvisoryLock { _tx: tx }))
+}
+
+pub async fn record_skipped(db: &DatabasePool, monitor_id: Uuid, reason: &str, at: DateTime<Utc>) -> Result<()> {
+    sqlx::query("INSERT INTO monitor_skipped_runs (id, monitor_id, reason, skipped_at) VALUES ($1, $2, $3, $4)")
+        .bind(Uuid::new_v4())
+        .bind(monitor_id)
+        .bind(reason)
+        .bind(at)
+        .execute(db)
+        .await?;
+    Ok(())
+}
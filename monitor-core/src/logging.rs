@@ -1,11 +1,44 @@
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+use std::sync::OnceLock;
+use tracing_subscriber::{layer::SubscriberExt, reload, util::SubscriberInitExt, EnvFilter, Registry};
+use crate::{error::Result, Error};
+
+static FILTER_HANDLE: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
 
 pub fn init_logging() {
     let env_filter = EnvFilter::try_from_default_env()
         .unwrap_or_else(|_| EnvFilter::new("info"));
+    let (filter, handle) = reload::Layer::new(env_filter);
 
     tracing_subscriber::registry()
-        .with(env_filter)
+        .with(filter)
         .with(tracing_subscriber::fmt::layer())
         .init();
-}
\ No newline at end of file
+
+    let _ = FILTER_HANDLE.set(handle);
+}
+
+fn parse_filter(directives: &str) -> Result<EnvFilter> {
+    EnvFilter::try_new(directives)
+        .map_err(|e| Error::validation(format!("Invalid log filter '{}': {}", directives, e)))
+}
+
+/// Checks filter directives without applying them, e.g. before handing them to another process.
+pub fn validate_filter(directives: &str) -> Result<()> {
+    parse_filter(directives).map(|_| ())
+}
+
+/// Replaces the active `EnvFilter` directives without restarting the process.
+pub fn set_filter(directives: &str) -> Result<()> {
+    let filter = parse_filter(directives)?;
+    let handle = FILTER_HANDLE
+        .get()
+        .ok_or_else(|| Error::internal("Logging has not been initialised"))?;
+
+    handle
+        .reload(filter)
+        .map_err(|e| Error::internal(format!("Failed to reload log filter: {}", e)))
+}
+
+pub fn current_filter() -> Option<String> {
+    FILTER_HANDLE.get()?.with_current(|filter| filter.to_string()).ok()
+}
@@ -0,0 +1,200 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use crate::{error::Result, Error};
+
+const MAX_BANNER_LEN: usize = 1024;
+const MAX_HELO_NAME_LEN: usize = 255;
+/// Longest line read from the server
+pub const MAX_LINE_BYTES: usize = 8 * 1024;
+/// Lines read for one multi-line reply before giving up
+pub const MAX_REPLY_LINES: usize = 100;
+/// What a failure message quotes of the server's reply
+const MAX_QUOTED_CHARS: usize = 200;
+const DEFAULT_HELO_NAME: &str = "monitor";
+/// Keys read from the monitor's decrypted credentials when authenticating
+pub const USERNAME: &str = "username";
+pub const PASSWORD: &str = "password";
+
+/// A mail server check run instead of the HTTP request. The monitor's
+/// `endpoint` is `smtp://`, `smtps://`, `imap://` or `imaps://` with an
+/// optional port; the greeting and `EHLO` or `CAPABILITY` are enough to
+/// pass unless more is asked for.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MailCheck {
+    /// Upgrade a plain `smtp://` or `imap://` connection with STARTTLS
+    #[serde(default)]
+    pub starttls: bool,
+    /// Log in with `username` and `password` from the monitor's credentials
+    #[serde(default)]
+    pub authenticate: bool,
+    /// Name sent with `EHLO`, `monitor` by default
+    #[serde(default)]
+    pub helo_name: Option<String>,
+    /// Text the greeting must contain, e.g. the server's host name
+    #[serde(default)]
+    pub expected_banner: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Protocol {
+    Smtp,
+    Imap,
+}
+
+/// Where a mail endpoint connects.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Target {
+    pub protocol: Protocol,
+    pub host: String,
+    pub port: u16,
+    /// `smtps://` or `imaps://`, which speak TLS from the start
+    pub implicit_tls: bool,
+}
+
+/// One SMTP reply line: its code, and whether more lines follow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReplyLine {
+    pub code: u16,
+    pub more: bool,
+}
+
+impl MailCheck {
+    pub fn validate(&self, endpoint: &str) -> Result<()> {
+        let target = target(endpoint)?;
+        if self.starttls && target.implicit_tls {
+            return Err(Error::validation("starttls applies to smtp:// and imap:// endpoints only"));
+        }
+        if let Some(name) = &self.helo_name {
+            if target.protocol != Protocol::Smtp {
+                return Err(Error::validation("helo_name applies to SMTP endpoints only"));
+            }
+            if name.is_empty()
+                || name.len() > MAX_HELO_NAME_LEN
+                || !name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '[' | ']' | ':'))
+            {
+                return Err(Error::validation("helo_name must be a host name or address literal"));
+            }
+        }
+        if self.expected_banner.as_ref().is_some_and(|banner| banner.is_empty() || banner.len() > MAX_BANNER_LEN) {
+            return Err(Error::validation(format!("expected_banner must be 1 to {} bytes", MAX_BANNER_LEN)));
+        }
+        Ok(())
+    }
+
+    pub fn helo_name(&self) -> &str {
+        self.helo_name.as_deref().unwrap_or(DEFAULT_HELO_NAME)
+    }
+
+    /// `Err` carries the result's error message when the greeting lacks the
+    /// expected text.
+    pub fn evaluate(&self, banner: &str) -> std::result::Result<(), String> {
+        match &self.expected_banner {
+            Some(expected) if !banner.contains(expected.as_str()) => {
+                Err(format!("expected '{}' in the greeting, got '{}'", expected, quote(banner)))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+pub fn target(endpoint: &str) -> Result<Target> {
+    let url = Url::parse(endpoint).map_err(|e| Error::validation(format!("Invalid mail endpoint: {}", e)))?;
+    let (protocol, implicit_tls, default_port) = match url.scheme() {
+        "smtp" => (Protocol::Smtp, false, 25),
+        "smtps" => (Protocol::Smtp, true, 465),
+        "imap" => (Protocol::Imap, false, 143),
+        "imaps" => (Protocol::Imap, true, 993),
+        _ => return Err(Error::validation("mail_check endpoints must be smtp://, smtps://, imap:// or imaps:// URLs")),
+    };
+    let Some(host) = url.host_str() else {
+        return Err(Error::validation("mail_check endpoints must name a host"));
+    };
+    Ok(Target {
+        protocol,
+        // IPv6 hosts come bracketed
+        host: host.trim_matches(['[', ']']).to_string(),
+        port: url.port().unwrap_or(default_port),
+        implicit_tls,
+    })
+}
+
+/// Username and password from the monitor's decrypted credentials.
+pub fn credentials(values: &HashMap<String, String>) -> Result<(String, String)> {
+    match (values.get(USERNAME), values.get(PASSWORD)) {
+        // Both end up on a single protocol line
+        (Some(username), Some(password)) if !format!("{}{}", username, password).contains(['\r', '\n', '\0']) => {
+            Ok((username.clone(), password.clone()))
+        }
+        (Some(_), Some(_)) => Err(Error::validation("mail credentials must not contain line breaks or NUL")),
+        _ => Err(Error::validation(format!(
+            "authenticating mail checks need {} and {} in the monitor's credentials",
+            USERNAME, PASSWORD
+        ))),
+    }
+}
+
+/// The code of an SMTP reply line (`250-...` continues, `250 ...` ends).
+pub fn reply_line(line: &str) -> Option<ReplyLine> {
+    let code = line.get(..3)?.parse().ok().filter(|code| (200..600).contains(code))?;
+    match line.as_bytes().get(3) {
+        Some(b'-') => Some(ReplyLine { code, more: true }),
+        Some(b' ' | b'\r' | b'\n') | None => Some(ReplyLine { code, more: false }),
+        _ => None,
+    }
+}
+
+/// Extension keywords from the lines of an `EHLO` reply, upper-cased. The
+/// first line is the server's greeting, not an extension.
+pub fn smtp_extensions(lines: &[String]) -> Vec<String> {
+    lines
+        .iter()
+        .skip(1)
+        .filter_map(|line| line.get(4..)?.split_whitespace().next())
+        .map(str::to_ascii_uppercase)
+        .collect()
+}
+
+/// The argument to `AUTH PLAIN` (RFC 4616).
+pub fn auth_plain(username: &str, password: &str) -> String {
+    STANDARD.encode(format!("\0{}\0{}", username, password))
+}
+
+/// Whether an IMAP greeting lets the session go on.
+pub fn imap_greeting_ok(line: &str) -> bool {
+    let mut words = line.split_whitespace();
+    words.next() == Some("*") && words.next().is_some_and(|status| ["OK", "PREAUTH"].contains(&status))
+}
+
+/// Capabilities from an untagged `* CAPABILITY` line, upper-cased.
+pub fn imap_capabilities(line: &str) -> Option<Vec<String>> {
+    let rest = line.strip_prefix("* ")?;
+    let (keyword, capabilities) = rest.split_once(' ').unwrap_or((rest, ""));
+    keyword
+        .eq_ignore_ascii_case("CAPABILITY")
+        .then(|| capabilities.split_whitespace().map(str::to_ascii_uppercase).collect())
+}
+
+/// The outcome of a tagged IMAP response, or `None` for untagged and other
+/// commands' lines. `Err` carries the server's text for `NO` and `BAD`.
+pub fn imap_completion(line: &str, tag: &str) -> Option<std::result::Result<(), String>> {
+    let rest = line.strip_prefix(tag)?.strip_prefix(' ')?;
+    let (status, _) = rest.split_once(' ').unwrap_or((rest.trim_end(), ""));
+    if status.eq_ignore_ascii_case("OK") {
+        Some(Ok(()))
+    } else {
+        Some(Err(quote(rest)))
+    }
+}
+
+/// An IMAP quoted string (RFC 3501 section 4.3).
+pub fn imap_quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// The start of a server reply as it is quoted in results.
+pub fn quote(reply: &str) -> String {
+    reply.trim_end().chars().take(MAX_QUOTED_CHARS).collect()
+}
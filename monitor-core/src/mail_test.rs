@@ -0,0 +1,107 @@
+#[cfg(test)]
+mod mail_tests {
+    use crate::mail::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn endpoints_pick_the_protocol_and_default_port() {
+        let submission = target("smtp://mail.example.com:587").unwrap();
+        assert_eq!(submission.protocol, Protocol::Smtp);
+        assert_eq!(submission.host, "mail.example.com");
+        assert_eq!((submission.port, submission.implicit_tls), (587, false));
+
+        assert_eq!(target("smtps://mail.example.com").unwrap().port, 465);
+        assert_eq!(target("smtp://[::1]").unwrap().host, "::1");
+        let imaps = target("imaps://imap.example.com").unwrap();
+        assert_eq!((imaps.protocol, imaps.port, imaps.implicit_tls), (Protocol::Imap, 993, true));
+        assert_eq!(target("imap://imap.example.com").unwrap().port, 143);
+
+        assert!(target("pop3://mail.example.com").is_err());
+        assert!(target("https://mail.example.com").is_err());
+    }
+
+    #[test]
+    fn options_are_validated_against_the_endpoint() {
+        let starttls = MailCheck {
+            starttls: true,
+            ..Default::default()
+        };
+        assert!(starttls.validate("smtp://mail.example.com:587").is_ok());
+        assert!(starttls.validate("imaps://imap.example.com").is_err());
+
+        let helo = |name: &str| MailCheck {
+            helo_name: Some(name.to_string()),
+            ..Default::default()
+        };
+        assert!(helo("probe.example.com").validate("smtp://h").is_ok());
+        assert!(helo("[192.0.2.1]").validate("smtp://h").is_ok());
+        assert!(helo("bad name\r\nRSET").validate("smtp://h").is_err());
+        assert!(helo("probe.example.com").validate("imap://h").is_err());
+        assert_eq!(MailCheck::default().helo_name(), "monitor");
+
+        let banner = MailCheck {
+            expected_banner: Some(String::new()),
+            ..Default::default()
+        };
+        assert!(banner.validate("smtp://h").is_err());
+    }
+
+    #[test]
+    fn credentials_need_a_username_and_password_on_one_line() {
+        let values = |pairs: &[(&str, &str)]| -> HashMap<String, String> {
+            pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+        };
+        let (username, password) = credentials(&values(&[("username", "probe"), ("password", "s3cret")])).unwrap();
+        assert_eq!((username.as_str(), password.as_str()), ("probe", "s3cret"));
+        assert!(credentials(&values(&[("username", "probe")])).is_err());
+        assert!(credentials(&values(&[("username", "probe"), ("password", "x\r\nQUIT")])).is_err());
+
+        // Example from RFC 4616 section 4, without an authorization identity
+        assert_eq!(auth_plain("tim", "tanstaaftanstaaf"), "AHRpbQB0YW5zdGFhZnRhbnN0YWFm");
+    }
+
+    #[test]
+    fn smtp_replies_and_extensions() {
+        assert_eq!(reply_line("250-mail.example.com\r\n"), Some(ReplyLine { code: 250, more: true }));
+        assert_eq!(reply_line("220 mail.example.com ESMTP\r\n"), Some(ReplyLine { code: 220, more: false }));
+        assert_eq!(reply_line("250\r\n"), Some(ReplyLine { code: 250, more: false }));
+        assert_eq!(reply_line("HTTP/1.1 400 Bad Request"), None);
+        assert_eq!(reply_line("2500 too long"), None);
+
+        let ehlo = ["250-mail.example.com", "250-PIPELINING", "250-starttls", "250 AUTH PLAIN LOGIN"]
+            .map(str::to_string);
+        assert_eq!(smtp_extensions(&ehlo), ["PIPELINING", "STARTTLS", "AUTH"]);
+    }
+
+    #[test]
+    fn imap_greetings_capabilities_and_completions() {
+        assert!(imap_greeting_ok("* OK [CAPABILITY IMAP4rev1] Dovecot ready.\r\n"));
+        assert!(imap_greeting_ok("* PREAUTH ready"));
+        assert!(!imap_greeting_ok("* BYE too many connections"));
+
+        let capabilities = imap_capabilities("* CAPABILITY IMAP4rev1 STARTTLS AUTH=PLAIN\r\n").unwrap();
+        assert_eq!(capabilities, ["IMAP4REV1", "STARTTLS", "AUTH=PLAIN"]);
+        assert_eq!(imap_capabilities("* 3 EXISTS"), None);
+
+        assert_eq!(imap_completion("a1 OK LOGIN completed\r\n", "a1"), Some(Ok(())));
+        assert_eq!(
+            imap_completion("a1 NO [AUTHENTICATIONFAILED] Invalid credentials\r\n", "a1"),
+            Some(Err("NO [AUTHENTICATIONFAILED] Invalid credentials".to_string()))
+        );
+        assert_eq!(imap_completion("a10 OK done", "a1"), None);
+        assert_eq!(imap_completion("* OK still here", "a1"), None);
+        assert_eq!(imap_quote(r#"pa"ss\word"#), r#""pa\"ss\\word""#);
+    }
+
+    #[test]
+    fn greetings_must_contain_the_expected_banner() {
+        let check = MailCheck {
+            expected_banner: Some("ESMTP Postfix".to_string()),
+            ..Default::default()
+        };
+        assert!(check.evaluate("220 mail.example.com ESMTP Postfix").is_ok());
+        let error = check.evaluate("220 mail.example.com ESMTP Exim\r\n").unwrap_err();
+        assert!(error.ends_with("got '220 mail.example.com ESMTP Exim'"));
+        assert!(MailCheck::default().evaluate("anything").is_ok());
+    }
+}
@@ -1,8 +1,14 @@
-use monitor_core::{logging, Config, Result};
+use monitor_core::{doctor, logging, Config, Result};
 
 #[tokio::main]
 async fn main() -> Result<()> {
     logging::init_logging();
+
+    if doctor::requested() {
+        let report = doctor::run(&doctor::DoctorOptions::default()).await;
+        report.print("monitor-core");
+        std::process::exit(if report.passed() { 0 } else { 1 });
+    }
     
     let config = Config::from_env()?;
     tracing::info!("Monitor Core started with config: {:?}", config);
@@ -0,0 +1,600 @@
+use chrono::{DateTime, Datelike, Duration, Months, NaiveDate, NaiveDateTime, Utc, Weekday};
+use croner::Cron;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use crate::{db::DatabasePool, error::Result, Error};
+
+/// A calendar or status feed whose maintenance windows silence monitors
+/// tagged with `tag`.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct MaintenanceSource {
+    pub id: Uuid,
+    pub name: String,
+    pub url: String,
+    pub tag: String,
+    pub enabled: bool,
+    pub created_by: Option<Uuid>,
+    pub last_synced_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A window imported from a calendar (`source_id`) or set on one monitor
+/// (`monitor_id`).
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct MaintenanceWindow {
+    pub id: Uuid,
+    pub source_id: Option<Uuid>,
+    pub uid: String,
+    pub summary: String,
+    /// The first occurrence when `recurrence` is set
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub monitor_id: Option<Uuid>,
+    /// Cron expression (UTC) or RRULE the window repeats by
+    pub recurrence: Option<String>,
+    /// Checks are skipped rather than only their alerts
+    pub skip_checks: bool,
+    pub created_by: Option<Uuid>,
+}
+
+impl MaintenanceWindow {
+    pub fn is_active(&self, at: DateTime<Utc>) -> bool {
+        match &self.recurrence {
+            None => self.starts_at <= at && at < self.ends_at,
+            Some(recurrence) => Recurrence::parse(recurrence)
+                .is_ok_and(|r| r.covers(self.starts_at, self.ends_at - self.starts_at, at)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateMaintenanceWindowRequest {
+    pub summary: Option<String>,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    /// e.g. `0 2 * * SUN` or `RRULE:FREQ=WEEKLY;BYDAY=SA,SU`; without one the
+    /// window happens once
+    pub recurrence: Option<String>,
+    #[serde(default = "default_skip_checks")]
+    pub skip_checks: bool,
+}
+
+fn default_skip_checks() -> bool {
+    true
+}
+
+impl CreateMaintenanceWindowRequest {
+    pub fn validate(&self) -> Result<()> {
+        if self.ends_at <= self.starts_at {
+            return Err(Error::validation("ends_at must be after starts_at"));
+        }
+        if self.summary.as_ref().is_some_and(|s| s.len() > 255) {
+            return Err(Error::validation("summary must be at most 255 characters"));
+        }
+        if let Some(recurrence) = &self.recurrence {
+            Recurrence::parse(recurrence)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+/// The part of an RFC 5545 RRULE that maintenance schedules need: `FREQ`,
+/// `INTERVAL`, `BYDAY` (weekly rules, without ordinals), `COUNT` and `UNTIL`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecurrenceRule {
+    pub frequency: Frequency,
+    pub interval: u32,
+    pub by_day: Vec<Weekday>,
+    pub count: Option<u32>,
+    pub until: Option<DateTime<Utc>>,
+}
+
+const MAX_RULE_INTERVAL: u32 = 1000;
+/// Bounds the search for the latest occurrence of a rule
+const MAX_RULE_PERIODS: i64 = 100_000;
+
+impl RecurrenceRule {
+    pub fn parse(value: &str) -> Result<Self> {
+        let invalid = |reason: String| Error::validation(format!("Invalid RRULE '{}': {}", value, reason));
+        let mut frequency = None;
+        let mut rule = Self {
+            frequency: Frequency::Daily,
+            interval: 1,
+            by_day: Vec::new(),
+            count: None,
+            until: None,
+        };
+        for part in value.trim().trim_start_matches("RRULE:").split(';').filter(|p| !p.is_empty()) {
+            let (key, val) = part.split_once('=').ok_or_else(|| invalid(format!("expected KEY=VALUE, got {}", part)))?;
+            match key.to_ascii_uppercase().as_str() {
+                "FREQ" => {
+                    frequency = Some(match val.to_ascii_uppercase().as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        other => return Err(invalid(format!("unsupported FREQ {}", other))),
+                    })
+                }
+                "INTERVAL" => {
+                    rule.interval = val
+                        .parse()
+                        .ok()
+                        .filter(|i| (1..=MAX_RULE_INTERVAL).contains(i))
+                        .ok_or_else(|| invalid(format!("INTERVAL must be between 1 and {}", MAX_RULE_INTERVAL)))?
+                }
+                "COUNT" => {
+                    rule.count = Some(val.parse().ok().filter(|c| *c > 0).ok_or_else(|| invalid("bad COUNT".into()))?)
+                }
+                "UNTIL" => rule.until = Some(ical_time(val).ok_or_else(|| invalid("bad UNTIL".into()))?),
+                "BYDAY" => {
+                    rule.by_day = val
+                        .split(',')
+                        .map(|day| weekday(day).ok_or_else(|| invalid(format!("unsupported BYDAY {}", day))))
+                        .collect::<Result<_>>()?
+                }
+                other => return Err(invalid(format!("unsupported part {}", other))),
+            }
+        }
+        rule.frequency = frequency.ok_or_else(|| invalid("FREQ is required".into()))?;
+        if !rule.by_day.is_empty() && rule.frequency != Frequency::Weekly {
+            return Err(invalid("BYDAY is only supported with FREQ=WEEKLY".into()));
+        }
+        rule.by_day.sort_by_key(|day| day.num_days_from_monday());
+        rule.by_day.dedup();
+        Ok(rule)
+    }
+
+    /// Starts of the occurrences in the `period`th day, week or month after `first`.
+    fn period_starts(&self, first: DateTime<Utc>, period: i64) -> Vec<DateTime<Utc>> {
+        let step = period * self.interval as i64;
+        match self.frequency {
+            Frequency::Daily => vec![first + Duration::days(step)],
+            Frequency::Weekly if self.by_day.is_empty() => vec![first + Duration::weeks(step)],
+            Frequency::Weekly => {
+                let monday = first - Duration::days(first.weekday().num_days_from_monday() as i64);
+                let week = monday + Duration::weeks(step);
+                self.by_day
+                    .iter()
+                    .map(|day| week + Duration::days(day.num_days_from_monday() as i64))
+                    .filter(|start| *start >= first)
+                    .collect()
+            }
+            // Months without the first occurrence's day are skipped
+            Frequency::Monthly => u32::try_from(step)
+                .ok()
+                .and_then(|months| first.checked_add_months(Months::new(months)))
+                .filter(|start| start.day() == first.day())
+                .into_iter()
+                .collect(),
+        }
+    }
+
+    /// Whole periods between `first` and `at`, used to skip ahead when the
+    /// rule has no `COUNT` to keep track of.
+    fn periods_before(&self, first: DateTime<Utc>, at: DateTime<Utc>) -> i64 {
+        let elapsed = match self.frequency {
+            Frequency::Daily => (at - first).num_days(),
+            Frequency::Weekly => (at - first).num_weeks(),
+            Frequency::Monthly => {
+                (at.year() - first.year()) as i64 * 12 + at.month() as i64 - first.month() as i64
+            }
+        };
+        (elapsed / self.interval as i64 - 1).max(0)
+    }
+
+    /// The start of the latest occurrence at or before `at`.
+    pub fn latest_start(&self, first: DateTime<Utc>, at: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        if at < first {
+            return None;
+        }
+        let start_period = if self.count.is_some() { 0 } else { self.periods_before(first, at) };
+        let mut latest = None;
+        let mut seen = 0;
+        for period in start_period..start_period + MAX_RULE_PERIODS {
+            for start in self.period_starts(first, period) {
+                seen += 1;
+                if start > at || self.until.is_some_and(|until| start > until) || self.count.is_some_and(|c| seen > c) {
+                    return latest;
+                }
+                latest = Some(start);
+            }
+        }
+        latest
+    }
+}
+
+fn weekday(value: &str) -> Option<Weekday> {
+    Some(match value.trim().to_ascii_uppercase().as_str() {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return None,
+    })
+}
+
+#[derive(Debug, Clone)]
+pub enum Recurrence {
+    Cron(Cron),
+    Rule(RecurrenceRule),
+}
+
+impl Recurrence {
+    /// An `RRULE:` or `FREQ=...` rule, otherwise a cron expression in UTC.
+    pub fn parse(value: &str) -> Result<Self> {
+        let upper = value.trim().to_ascii_uppercase();
+        if upper.starts_with("RRULE:") || upper.starts_with("FREQ=") {
+            return Ok(Self::Rule(RecurrenceRule::parse(value)?));
+        }
+        Cron::new(value.trim())
+            .parse()
+            .map(Self::Cron)
+            .map_err(|e| Error::validation(format!("Invalid cron expression '{}': {}", value, e)))
+    }
+
+    /// Whether an occurrence lasting `length`, and starting no earlier than
+    /// `first`, is in progress at `at`.
+    pub fn covers(&self, first: DateTime<Utc>, length: Duration, at: DateTime<Utc>) -> bool {
+        match self {
+            Recurrence::Rule(rule) => rule.latest_start(first, at).is_some_and(|start| at < start + length),
+            Recurrence::Cron(cron) => {
+                // Occurrences starting at `at - length` have just ended
+                let from = (at - length + Duration::seconds(1)).max(first);
+                cron.find_next_occurrence(&from, true).is_ok_and(|start| start <= at)
+            }
+        }
+    }
+}
+
+/// A window as read from a feed, before it is stored.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MaintenanceEvent {
+    pub uid: String,
+    pub summary: String,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateMaintenanceSourceRequest {
+    pub name: String,
+    /// iCal (`.ics`) URL or a Statuspage `scheduled-maintenances.json` URL
+    pub url: String,
+    /// Tag of the monitors that depend on this provider
+    pub tag: String,
+}
+
+impl CreateMaintenanceSourceRequest {
+    pub fn validate(&self) -> Result<()> {
+        if self.name.trim().is_empty() {
+            return Err(Error::validation("name is required"));
+        }
+        if !(self.url.starts_with("http://") || self.url.starts_with("https://")) {
+            return Err(Error::validation("url must use http or https"));
+        }
+        if self.tag.trim().is_empty() {
+            return Err(Error::validation("tag is required"));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct StatuspageFeed {
+    scheduled_maintenances: Vec<StatuspageMaintenance>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StatuspageMaintenance {
+    id: String,
+    name: String,
+    scheduled_for: DateTime<Utc>,
+    scheduled_until: DateTime<Utc>,
+}
+
+/// Parses an iCalendar document or a Statuspage scheduled maintenance feed.
+pub fn parse_feed(body: &str) -> Result<Vec<MaintenanceEvent>> {
+    if body.trim_start().starts_with("BEGIN:VCALENDAR") {
+        return Ok(parse_ical(body));
+    }
+    let feed: StatuspageFeed = serde_json::from_str(body)
+        .map_err(|_| Error::validation("Feed is neither iCalendar nor a Statuspage maintenance feed"))?;
+    Ok(feed
+        .scheduled_maintenances
+        .into_iter()
+        .map(|m| MaintenanceEvent {
+            uid: m.id,
+            summary: m.name,
+            starts_at: m.scheduled_for,
+            ends_at: m.scheduled_until,
+        })
+        .collect())
+}
+
+/// Events with a start and an end (or an all-day start) that are not
+/// cancelled. Times with a `TZID` are read as UTC.
+pub fn parse_ical(body: &str) -> Vec<MaintenanceEvent> {
+    let mut events = Vec::new();
+    let mut current: Option<Vec<(String, String)>> = None;
+
+    for line in unfold(body) {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let name = name.split(';').next().unwrap_or_default().to_ascii_uppercase();
+        match (name.as_str(), value) {
+            ("BEGIN", "VEVENT") => current = Some(Vec::new()),
+            ("END", "VEVENT") => {
+                if let Some(event) = current.take().and_then(|props| ical_event(&props)) {
+                    events.push(event);
+                }
+            }
+            _ => {
+                if let Some(props) = current.as_mut() {
+                    props.push((name, value.to_string()));
+                }
+            }
+        }
+    }
+    events
+}
+
+fn ical_event(props: &[(String, String)]) -> Option<MaintenanceEvent> {
+    let get = |key: &str| props.iter().find(|(name, _)| name == key).map(|(_, value)| value.as_str());
+    if get("STATUS").is_some_and(|status| status.eq_ignore_ascii_case("CANCELLED")) {
+        return None;
+    }
+
+    let start = get("DTSTART")?;
+    let starts_at = ical_time(start)?;
+    let ends_at = match get("DTEND") {
+        Some(end) => ical_time(end)?,
+        // An all-day event without an end lasts the day
+        None if start.len() == 8 => starts_at + chrono::Duration::days(1),
+        None => return None,
+    };
+    Some(MaintenanceEvent {
+        uid: get("UID").map(str::to_string).unwrap_or_else(|| format!("{}", starts_at.timestamp())),
+        summary: unescape(get("SUMMARY").unwrap_or("Maintenance")),
+        starts_at,
+        ends_at,
+    })
+}
+
+fn ical_time(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if value.len() == 8 {
+        let date = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+        return Some(date.and_hms_opt(0, 0, 0)?.and_utc());
+    }
+    NaiveDateTime::parse_from_str(value.trim_end_matches('Z'), "%Y%m%dT%H%M%S")
+        .ok()
+        .map(|time| time.and_utc())
+}
+
+/// Joins folded lines (continuations start with a space or tab).
+fn unfold(body: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in body.lines() {
+        match (line.strip_prefix(' ').or_else(|| line.strip_prefix('\t')), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+fn unescape(value: &str) -> String {
+    value
+        .replace("\\n", " ")
+        .replace("\\N", " ")
+        .replace("\\,", ",")
+        .replace("\\;", ";")
+        .replace("\\\\", "\\")
+}
+
+pub async fn create_source(
+    db: &DatabasePool,
+    request: &CreateMaintenanceSourceRequest,
+    created_by: Uuid,
+) -> Result<MaintenanceSource> {
+    request.validate()?;
+    let source = sqlx::query_as::<_, MaintenanceSource>(
+        r#"
+        INSERT INTO maintenance_sources (id, name, url, tag, enabled, created_by, created_at)
+        VALUES ($1, $2, $3, $4, true, $5, NOW())
+        RETURNING *
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(request.name.trim())
+    .bind(&request.url)
+    .bind(request.tag.trim())
+    .bind(created_by)
+    .fetch_one(db)
+    .await?;
+    Ok(source)
+}
+
+pub async fn list_sources(db: &DatabasePool) -> Result<Vec<MaintenanceSource>> {
+    let sources = sqlx::query_as::<_, MaintenanceSource>("SELECT * FROM maintenance_sources ORDER BY name")
+        .fetch_all(db)
+        .await?;
+    Ok(sources)
+}
+
+/// `false` when there was no such source.
+pub async fn delete_source(db: &DatabasePool, id: Uuid) -> Result<bool> {
+    let deleted = sqlx::query("DELETE FROM maintenance_sources WHERE id = $1")
+        .bind(id)
+        .execute(db)
+        .await?;
+    Ok(deleted.rows_affected() > 0)
+}
+
+/// Replaces the source's windows with the ones currently in its feed.
+pub async fn store_windows(db: &DatabasePool, source_id: Uuid, events: &[MaintenanceEvent], now: DateTime<Utc>) -> Result<()> {
+    let mut tx = db.begin().await?;
+    sqlx::query("DELETE FROM maintenance_windows WHERE source_id = $1")
+        .bind(source_id)
+        .execute(&mut *tx)
+        .await?;
+    for event in events.iter().filter(|e| e.ends_at > e.starts_at) {
+        sqlx::query(
+            r#"
+            INSERT INTO maintenance_windows (id, source_id, uid, summary, starts_at, ends_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (source_id, uid) DO NOTHING
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(source_id)
+        .bind(&event.uid)
+        .bind(&event.summary)
+        .bind(event.starts_at)
+        .bind(event.ends_at)
+        .execute(&mut *tx)
+        .await?;
+    }
+    sqlx::query("UPDATE maintenance_sources SET last_synced_at = $2, last_error = NULL WHERE id = $1")
+        .bind(source_id)
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Keeps the previous windows; only the error is recorded.
+pub async fn record_sync_error(db: &DatabasePool, source_id: Uuid, error: &str) -> Result<()> {
+    sqlx::query("UPDATE maintenance_sources SET last_error = $2 WHERE id = $1")
+        .bind(source_id)
+        .bind(error)
+        .execute(db)
+        .await?;
+    Ok(())
+}
+
+/// Calendar windows that have not ended yet, soonest first.
+pub async fn upcoming_windows(db: &DatabasePool, now: DateTime<Utc>, limit: i64) -> Result<Vec<MaintenanceWindow>> {
+    let windows = sqlx::query_as::<_, MaintenanceWindow>(
+        "SELECT * FROM maintenance_windows WHERE source_id IS NOT NULL AND ends_at > $1 ORDER BY starts_at LIMIT $2",
+    )
+    .bind(now)
+    .bind(limit)
+    .fetch_all(db)
+    .await?;
+    Ok(windows)
+}
+
+pub async fn create_window(
+    db: &DatabasePool,
+    monitor_id: Uuid,
+    request: &CreateMaintenanceWindowRequest,
+    created_by: Uuid,
+) -> Result<MaintenanceWindow> {
+    request.validate()?;
+    let id = Uuid::new_v4();
+    let window = sqlx::query_as::<_, MaintenanceWindow>(
+        r#"
+        INSERT INTO maintenance_windows
+            (id, uid, summary, starts_at, ends_at, monitor_id, recurrence, skip_checks, created_by)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        RETURNING *
+        "#,
+    )
+    .bind(id)
+    .bind(id.to_string())
+    .bind(request.summary.as_deref().map(str::trim).unwrap_or("Maintenance"))
+    .bind(request.starts_at)
+    .bind(request.ends_at)
+    .bind(monitor_id)
+    .bind(request.recurrence.as_deref().map(str::trim))
+    .bind(request.skip_checks)
+    .bind(created_by)
+    .fetch_one(db)
+    .await?;
+    Ok(window)
+}
+
+pub async fn monitor_windows(db: &DatabasePool, monitor_id: Uuid) -> Result<Vec<MaintenanceWindow>> {
+    let windows = sqlx::query_as::<_, MaintenanceWindow>(
+        "SELECT * FROM maintenance_windows WHERE monitor_id = $1 ORDER BY starts_at",
+    )
+    .bind(monitor_id)
+    .fetch_all(db)
+    .await?;
+    Ok(windows)
+}
+
+/// `false` when the monitor has no such window.
+pub async fn delete_window(db: &DatabasePool, monitor_id: Uuid, id: Uuid) -> Result<bool> {
+    let deleted = sqlx::query("DELETE FROM maintenance_windows WHERE id = $1 AND monitor_id = $2")
+        .bind(id)
+        .bind(monitor_id)
+        .execute(db)
+        .await?;
+    Ok(deleted.rows_affected() > 0)
+}
+
+/// The monitor's own window in progress at `at`, preferring one that skips checks.
+pub async fn active_monitor_window(
+    db: &DatabasePool,
+    monitor_id: Uuid,
+    at: DateTime<Utc>,
+) -> Result<Option<MaintenanceWindow>> {
+    // Recurring windows are evaluated here rather than in SQL
+    let windows = sqlx::query_as::<_, MaintenanceWindow>(
+        "SELECT * FROM maintenance_windows WHERE monitor_id = $1 AND (recurrence IS NOT NULL OR ends_at > $2)",
+    )
+    .bind(monitor_id)
+    .bind(at)
+    .fetch_all(db)
+    .await?;
+    Ok(windows
+        .into_iter()
+        .filter(|window| window.is_active(at))
+        .max_by_key(|window| window.skip_checks))
+}
+
+/// Whether one of the monitor's own windows, or a window of an enabled
+/// source tagged like the monitor, is in progress.
+pub async fn in_maintenance(
+    db: &DatabasePool,
+    monitor_id: Uuid,
+    tags: &[String],
+    at: DateTime<Utc>,
+) -> Result<bool> {
+    if active_monitor_window(db, monitor_id, at).await?.is_some() {
+        return Ok(true);
+    }
+    if tags.is_empty() {
+        return Ok(false);
+    }
+    let active = sqlx::query_scalar(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM maintenance_windows w
+            JOIN maintenance_sources s ON s.id = w.source_id
+            WHERE s.enabled = true AND s.tag = ANY($1) AND w.starts_at <= $2 AND w.ends_at > $2
+        )
+        "#,
+    )
+    .bind(tags)
+    .bind(at)
+    .fetch_one(db)
+    .await?;
+    Ok(active)
+}
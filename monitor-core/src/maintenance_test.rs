@@ -0,0 +1,147 @@
+#[cfg(test)]
+mod maintenance_tests {
+    use crate::maintenance::*;
+    use chrono::{TimeZone, Utc};
+
+    const CALENDAR: &str = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\n\
+BEGIN:VEVENT\r\nUID:db-upgrade\r\nDTSTART:20240301T020000Z\r\nDTEND:20240301T040000Z\r\n\
+SUMMARY:Database upgrade\\, eu-west\r\nEND:VEVENT\r\n\
+BEGIN:VEVENT\r\nUID:network\r\nDTSTART;VALUE=DATE:20240305\r\nSUMMARY:Network\r\n  work\r\nEND:VEVENT\r\n\
+BEGIN:VEVENT\r\nUID:cancelled\r\nSTATUS:CANCELLED\r\nDTSTART:20240306T000000Z\r\nDTEND:20240306T010000Z\r\nEND:VEVENT\r\n\
+END:VCALENDAR\r\n";
+
+    #[test]
+    fn parses_ical_events() {
+        let events = parse_feed(CALENDAR).unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].uid, "db-upgrade");
+        assert_eq!(events[0].summary, "Database upgrade, eu-west");
+        assert_eq!(events[0].starts_at, Utc.with_ymd_and_hms(2024, 3, 1, 2, 0, 0).unwrap());
+        assert_eq!(events[0].ends_at, Utc.with_ymd_and_hms(2024, 3, 1, 4, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn all_day_events_and_folded_lines() {
+        let events = parse_ical(CALENDAR);
+        assert_eq!(events[1].summary, "Network work");
+        assert_eq!(events[1].starts_at, Utc.with_ymd_and_hms(2024, 3, 5, 0, 0, 0).unwrap());
+        assert_eq!(events[1].ends_at, Utc.with_ymd_and_hms(2024, 3, 6, 0, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn parses_statuspage_feed() {
+        let body = r#"{"page": {"id": "x"}, "scheduled_maintenances": [
+            {"id": "abc", "name": "Storage maintenance", "status": "scheduled",
+             "scheduled_for": "2024-03-01T02:00:00Z", "scheduled_until": "2024-03-01T03:00:00Z"}
+        ]}"#;
+        let events = parse_feed(body).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].uid, "abc");
+        assert_eq!(events[0].ends_at, Utc.with_ymd_and_hms(2024, 3, 1, 3, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn rejects_unknown_feeds() {
+        assert!(parse_feed("<html></html>").is_err());
+    }
+
+    #[test]
+    fn source_validation() {
+        let request = CreateMaintenanceSourceRequest {
+            name: "AWS".to_string(),
+            url: "ftp://example.com/cal.ics".to_string(),
+            tag: "aws".to_string(),
+        };
+        assert!(request.validate().is_err());
+        let request = CreateMaintenanceSourceRequest { url: "https://example.com/cal.ics".to_string(), ..request };
+        assert!(request.validate().is_ok());
+    }
+
+    fn window(starts_at: (u32, u32), ends_at: (u32, u32), recurrence: Option<&str>) -> MaintenanceWindow {
+        MaintenanceWindow {
+            id: uuid::Uuid::nil(),
+            source_id: None,
+            uid: "window".to_string(),
+            summary: "Maintenance".to_string(),
+            starts_at: Utc.with_ymd_and_hms(2024, 3, starts_at.0, starts_at.1, 0, 0).unwrap(),
+            ends_at: Utc.with_ymd_and_hms(2024, 3, ends_at.0, ends_at.1, 0, 0).unwrap(),
+            monitor_id: Some(uuid::Uuid::nil()),
+            recurrence: recurrence.map(str::to_string),
+            skip_checks: true,
+            created_by: None,
+        }
+    }
+
+    fn at(month: u32, day: u32, hour: u32, min: u32) -> chrono::DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, month, day, hour, min, 0).unwrap()
+    }
+
+    #[test]
+    fn one_off_windows() {
+        let window = window((1, 2), (1, 4), None);
+        assert!(window.is_active(at(3, 1, 2, 0)));
+        assert!(window.is_active(at(3, 1, 3, 59)));
+        assert!(!window.is_active(at(3, 1, 4, 0)));
+        assert!(!window.is_active(at(3, 1, 1, 59)));
+    }
+
+    #[test]
+    fn cron_windows() {
+        // Sundays 02:00-04:00, from 3 March
+        let window = window((3, 2), (3, 4), Some("0 2 * * SUN"));
+        assert!(window.is_active(at(3, 10, 3, 0)));
+        assert!(window.is_active(at(3, 3, 2, 0)));
+        assert!(!window.is_active(at(3, 10, 4, 0)));
+        assert!(!window.is_active(at(3, 9, 3, 0)));
+        assert!(!window.is_active(at(2, 25, 3, 0)));
+    }
+
+    #[test]
+    fn weekly_rules() {
+        // Weekends 22:00-23:00, from Saturday 2 March
+        let weekends = window((2, 22), (2, 23), Some("RRULE:FREQ=WEEKLY;BYDAY=SA,SU"));
+        assert!(weekends.is_active(at(3, 3, 22, 30)));
+        assert!(weekends.is_active(at(3, 17, 22, 30)));
+        assert!(weekends.is_active(at(11, 30, 22, 0)));
+        assert!(!weekends.is_active(at(3, 13, 22, 30)));
+        assert!(!weekends.is_active(at(3, 16, 23, 0)));
+
+        let fortnightly = window((2, 22), (2, 23), Some("FREQ=WEEKLY;INTERVAL=2"));
+        assert!(fortnightly.is_active(at(3, 16, 22, 30)));
+        assert!(!fortnightly.is_active(at(3, 9, 22, 30)));
+    }
+
+    #[test]
+    fn rules_end_after_count_or_until() {
+        let counted = window((2, 22), (2, 23), Some("RRULE:FREQ=DAILY;COUNT=3"));
+        assert!(counted.is_active(at(3, 4, 22, 30)));
+        assert!(!counted.is_active(at(3, 5, 22, 30)));
+
+        let until = window((2, 22), (2, 23), Some("FREQ=DAILY;UNTIL=20240310T000000Z"));
+        assert!(until.is_active(at(3, 9, 22, 30)));
+        assert!(!until.is_active(at(3, 10, 22, 30)));
+
+        // Months without a 31st are skipped
+        let monthly = window((31, 1), (31, 2), Some("FREQ=MONTHLY"));
+        assert!(monthly.is_active(at(5, 31, 1, 30)));
+        assert!(!monthly.is_active(at(4, 30, 1, 30)));
+    }
+
+    #[test]
+    fn window_validation() {
+        let request = |ends_at, recurrence: Option<&str>| CreateMaintenanceWindowRequest {
+            summary: None,
+            starts_at: at(3, 1, 2, 0),
+            ends_at,
+            recurrence: recurrence.map(str::to_string),
+            skip_checks: true,
+        };
+        assert!(request(at(3, 1, 4, 0), Some("0 2 * * SUN")).validate().is_ok());
+        assert!(request(at(3, 1, 4, 0), Some("RRULE:FREQ=WEEKLY;BYDAY=MO,TH")).validate().is_ok());
+        assert!(request(at(3, 1, 1, 0), None).validate().is_err());
+        assert!(request(at(3, 1, 4, 0), Some("not a schedule")).validate().is_err());
+        assert!(request(at(3, 1, 4, 0), Some("FREQ=DAILY;BYDAY=MO")).validate().is_err());
+        assert!(request(at(3, 1, 4, 0), Some("FREQ=YEARLY")).validate().is_err());
+        assert!(request(at(3, 1, 4, 0), Some("FREQ=WEEKLY;BYDAY=1MO")).validate().is_err());
+    }
+}
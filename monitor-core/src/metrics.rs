@@ -0,0 +1,215 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{Mutex, OnceLock};
+
+/// Default histogram buckets, in seconds.
+pub const DEFAULT_BUCKETS: &[f64] = &[0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+type MetricKey = (String, Vec<(String, String)>);
+
+#[derive(Debug, Clone)]
+struct Histogram {
+    buckets: Vec<f64>,
+    counts: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    fn new(buckets: &[f64]) -> Self {
+        Self {
+            buckets: buckets.to_vec(),
+            counts: vec![0; buckets.len()],
+            sum: 0.0,
+            count: 0,
+        }
+    }
+
+    fn observe(&mut self, value: f64) {
+        for (bound, count) in self.buckets.iter().zip(self.counts.iter_mut()) {
+            if value <= *bound {
+                *count += 1;
+            }
+        }
+        self.sum += value;
+        self.count += 1;
+    }
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    counters: BTreeMap<MetricKey, f64>,
+    gauges: BTreeMap<MetricKey, f64>,
+    histograms: BTreeMap<MetricKey, Histogram>,
+    buckets: BTreeMap<String, Vec<f64>>,
+    help: BTreeMap<String, String>,
+}
+
+/// In-process metrics registry rendered in the Prometheus text exposition format.
+#[derive(Debug, Default)]
+pub struct MetricsRegistry {
+    inner: Mutex<Inner>,
+}
+
+fn key(name: &str, labels: &[(&str, &str)]) -> MetricKey {
+    let mut labels: Vec<(String, String)> = labels
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+    labels.sort();
+    (name.to_string(), labels)
+}
+
+impl MetricsRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn describe(&self, name: &str, help: &str) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.help.insert(name.to_string(), help.to_string());
+    }
+
+    pub fn register_buckets(&self, name: &str, buckets: &[f64]) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.buckets.insert(name.to_string(), buckets.to_vec());
+    }
+
+    pub fn increment_counter(&self, name: &str, labels: &[(&str, &str)]) {
+        self.add_counter(name, labels, 1.0);
+    }
+
+    pub fn add_counter(&self, name: &str, labels: &[(&str, &str)], value: f64) {
+        let mut inner = self.inner.lock().unwrap();
+        *inner.counters.entry(key(name, labels)).or_insert(0.0) += value;
+    }
+
+    pub fn set_gauge(&self, name: &str, labels: &[(&str, &str)], value: f64) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.gauges.insert(key(name, labels), value);
+    }
+
+    /// Sets the gauge only if `value` exceeds the current value, tracking a peak.
+    pub fn set_gauge_max(&self, name: &str, labels: &[(&str, &str)], value: f64) {
+        let mut inner = self.inner.lock().unwrap();
+        let gauge = inner.gauges.entry(key(name, labels)).or_insert(value);
+        if value > *gauge {
+            *gauge = value;
+        }
+    }
+
+    pub fn observe_histogram(&self, name: &str, labels: &[(&str, &str)], value: f64) {
+        let mut inner = self.inner.lock().unwrap();
+        let buckets = inner
+            .buckets
+            .get(name)
+            .cloned()
+            .unwrap_or_else(|| DEFAULT_BUCKETS.to_vec());
+        inner
+            .histograms
+            .entry(key(name, labels))
+            .or_insert_with(|| Histogram::new(&buckets))
+            .observe(value);
+    }
+
+    pub fn counter_value(&self, name: &str, labels: &[(&str, &str)]) -> f64 {
+        let inner = self.inner.lock().unwrap();
+        inner.counters.get(&key(name, labels)).copied().unwrap_or(0.0)
+    }
+
+    pub fn gauge_value(&self, name: &str, labels: &[(&str, &str)]) -> Option<f64> {
+        let inner = self.inner.lock().unwrap();
+        inner.gauges.get(&key(name, labels)).copied()
+    }
+
+    pub fn histogram_count(&self, name: &str, labels: &[(&str, &str)]) -> u64 {
+        let inner = self.inner.lock().unwrap();
+        inner
+            .histograms
+            .get(&key(name, labels))
+            .map(|h| h.count)
+            .unwrap_or(0)
+    }
+
+    pub fn render(&self) -> String {
+        let inner = self.inner.lock().unwrap();
+        let mut out = String::new();
+
+        let mut last_name = None;
+        for ((name, labels), value) in &inner.counters {
+            write_header(&mut out, &inner.help, name, "counter", &mut last_name);
+            let _ = writeln!(out, "{}{} {}", name, format_labels(labels, None), value);
+        }
+
+        last_name = None;
+        for ((name, labels), value) in &inner.gauges {
+            write_header(&mut out, &inner.help, name, "gauge", &mut last_name);
+            let _ = writeln!(out, "{}{} {}", name, format_labels(labels, None), value);
+        }
+
+        last_name = None;
+        for ((name, labels), histogram) in &inner.histograms {
+            write_header(&mut out, &inner.help, name, "histogram", &mut last_name);
+            for (bound, count) in histogram.buckets.iter().zip(&histogram.counts) {
+                let le = bound.to_string();
+                let _ = writeln!(
+                    out,
+                    "{}_bucket{} {}",
+                    name,
+                    format_labels(labels, Some(&le)),
+                    count
+                );
+            }
+            let _ = writeln!(
+                out,
+                "{}_bucket{} {}",
+                name,
+                format_labels(labels, Some("+Inf")),
+                histogram.count
+            );
+            let _ = writeln!(out, "{}_sum{} {}", name, format_labels(labels, None), histogram.sum);
+            let _ = writeln!(out, "{}_count{} {}", name, format_labels(labels, None), histogram.count);
+        }
+
+        out
+    }
+}
+
+fn write_header(
+    out: &mut String,
+    help: &BTreeMap<String, String>,
+    name: &str,
+    kind: &str,
+    last_name: &mut Option<String>,
+) {
+    if last_name.as_deref() == Some(name) {
+        return;
+    }
+    if let Some(text) = help.get(name) {
+        let _ = writeln!(out, "# HELP {} {}", name, text);
+    }
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+    *last_name = Some(name.to_string());
+}
+
+fn format_labels(labels: &[(String, String)], le: Option<&str>) -> String {
+    let mut parts: Vec<String> = labels
+        .iter()
+        .map(|(k, v)| format!("{}=\"{}\"", k, v.replace('\\', "\\\\").replace('"', "\\\"")))
+        .collect();
+    if let Some(le) = le {
+        parts.push(format!("le=\"{}\"", le));
+    }
+    if parts.is_empty() {
+        String::new()
+    } else {
+        format!("{{{}}}", parts.join(","))
+    }
+}
+
+static REGISTRY: OnceLock<MetricsRegistry> = OnceLock::new();
+
+/// Process-wide registry shared by the API, scheduler and scripting engine.
+pub fn global() -> &'static MetricsRegistry {
+    REGISTRY.get_or_init(MetricsRegistry::new)
+}
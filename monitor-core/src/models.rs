@@ -8,18 +8,178 @@ pub struct Monitor {
     pub id: Uuid,
     pub name: String,
     pub endpoint: String,
-    pub method: String,
+    pub method: crate::http_method::HttpMethod,
     pub headers: Option<serde_json::Value>,
     pub body: Option<String>,
-    pub expected_status: i32,
-    pub timeout: i32,
-    pub interval: i32,
+    pub expected_status: crate::status_match::ExpectedStatus,
+    /// `None` inherits from tag and global defaults, see `settings`
+    pub timeout: Option<crate::durations::Seconds>,
+    pub interval: crate::durations::Seconds,
     pub script: Option<String>,
+    /// Runs before each request and may add headers, query params or replace the body
+    pub pre_request_script: Option<String>,
     pub enabled: bool,
+    /// Set while paused, see `pause`
+    pub paused_reason: Option<String>,
+    pub paused_by: Option<Uuid>,
+    pub paused_at: Option<DateTime<Utc>>,
+    pub resume_at: Option<DateTime<Utc>>,
+    pub tags: Vec<String>,
+    pub owner_id: Option<Uuid>,
+    /// Members of this team may edit the monitor, see `teams::can_edit`
+    pub team_id: Option<Uuid>,
+    /// Set on the monitors watching the monitoring stack itself, which
+    /// cannot be deleted, see `self_monitoring`
+    pub system_key: Option<String>,
+    pub retries: Option<i32>,
+    /// Pause between attempts; `settings::DEFAULT_RETRY_DELAY_MS` when unset
+    pub retry_delay_ms: Option<i32>,
+    /// Runs the check at the times this cron expression names (UTC) instead
+    /// of every `interval` seconds
+    pub schedule_cron: Option<String>,
+    pub notification_channels: Option<Vec<Uuid>>,
+    pub script_profile: Option<String>,
+    #[serde(skip_serializing)]
+    pub credentials: Option<String>,
+    /// Multi-step transaction; `endpoint` and `method` mirror the first step
+    pub steps: Option<serde_json::Value>,
+    /// Parse the response as Prometheus/OpenMetrics and check these, see `openmetrics`
+    pub metric_assertions: Option<serde_json::Value>,
+    /// Message broker check run instead of the HTTP request, see `brokers`
+    pub broker_check: Option<serde_json::Value>,
+    /// Object storage check run instead of the HTTP request, see `storage`
+    pub storage_check: Option<serde_json::Value>,
+    /// NTP server check run instead of the HTTP request, see `ntp`
+    pub ntp_check: Option<serde_json::Value>,
+    /// SNMP polling check run instead of the HTTP request, see `snmp`
+    pub snmp_check: Option<serde_json::Value>,
+    /// Allow-listed local command run instead of the HTTP request, see `exec`
+    pub exec_check: Option<serde_json::Value>,
+    /// TCP port check run instead of the HTTP request, see `tcp`
+    pub tcp_check: Option<serde_json::Value>,
+    /// DNS record check run instead of the HTTP request, see `dns`
+    pub dns_check: Option<serde_json::Value>,
+    /// Certificate expiry check run instead of the HTTP request, see `tls`
+    pub tls_check: Option<serde_json::Value>,
+    /// WebSocket handshake and echo check run instead of the HTTP request, see `websocket`
+    pub websocket_check: Option<serde_json::Value>,
+    /// SMTP or IMAP handshake check run instead of the HTTP request, see `mail`
+    pub mail_check: Option<serde_json::Value>,
+    /// Connect-and-query check of a database server run instead of the HTTP request, see `databases`
+    pub database_check: Option<serde_json::Value>,
+    /// Passive check that expects pings at `/api/heartbeat/{token}`, see `heartbeats`
+    pub heartbeat_check: Option<serde_json::Value>,
+    /// The HTTP response body must contain this text, see `body_match`
+    pub expected_body_contains: Option<String>,
+    /// The HTTP response body must match this regular expression
+    pub expected_body_regex: Option<String>,
+    /// Resolve the host on every check, for detecting DNS changes
+    pub bypass_dns_cache: bool,
+    /// TCP connect limit; `None` uses `http_client.connect_timeout_ms`
+    pub connect_timeout_ms: Option<i32>,
+    /// TLS handshake limit; `None` uses `http_client.tls_timeout_ms`
+    pub tls_timeout_ms: Option<i32>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+impl Monitor {
+    /// The check the scheduler runs for this monitor. Validation allows one
+    /// kind per monitor; for older rows that set several, the first in
+    /// `check_kinds` order wins.
+    pub fn monitor_type(&self) -> MonitorType {
+        self.check_kinds()
+            .into_iter()
+            .find(|(_, set)| *set)
+            .map_or(MonitorType::Http, |(kind, _)| kind)
+    }
+
+    /// Settings of the check `monitor_type` names; `None` for the HTTP request.
+    pub fn check_config(&self) -> Option<&serde_json::Value> {
+        match self.monitor_type() {
+            MonitorType::Http | MonitorType::Metrics => None,
+            MonitorType::Transaction => self.steps.as_ref(),
+            MonitorType::Broker => self.broker_check.as_ref(),
+            MonitorType::Storage => self.storage_check.as_ref(),
+            MonitorType::Ntp => self.ntp_check.as_ref(),
+            MonitorType::Snmp => self.snmp_check.as_ref(),
+            MonitorType::Exec => self.exec_check.as_ref(),
+            MonitorType::Tcp => self.tcp_check.as_ref(),
+            MonitorType::Dns => self.dns_check.as_ref(),
+            MonitorType::Tls => self.tls_check.as_ref(),
+            MonitorType::WebSocket => self.websocket_check.as_ref(),
+            MonitorType::Mail => self.mail_check.as_ref(),
+            MonitorType::Database => self.database_check.as_ref(),
+            MonitorType::Heartbeat => self.heartbeat_check.as_ref(),
+        }
+    }
+
+    fn check_kinds(&self) -> [(MonitorType, bool); 14] {
+        [
+            (MonitorType::Broker, self.broker_check.is_some()),
+            (MonitorType::Storage, self.storage_check.is_some()),
+            (MonitorType::Ntp, self.ntp_check.is_some()),
+            (MonitorType::Snmp, self.snmp_check.is_some()),
+            (MonitorType::Exec, self.exec_check.is_some()),
+            (MonitorType::Tcp, self.tcp_check.is_some()),
+            (MonitorType::Dns, self.dns_check.is_some()),
+            (MonitorType::Tls, self.tls_check.is_some()),
+            (MonitorType::WebSocket, self.websocket_check.is_some()),
+            (MonitorType::Mail, self.mail_check.is_some()),
+            (MonitorType::Database, self.database_check.is_some()),
+            (MonitorType::Heartbeat, self.heartbeat_check.is_some()),
+            (MonitorType::Transaction, self.steps.is_some()),
+            (MonitorType::Metrics, self.metric_assertions.is_some()),
+        ]
+    }
+}
+
+/// What a monitor checks. Each kind but `Http` is selected by setting one
+/// field of the monitor, and replaces the plain HTTP request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MonitorType {
+    Http,
+    /// HTTP request whose response is parsed as metrics
+    Metrics,
+    Transaction,
+    Broker,
+    Storage,
+    Ntp,
+    Snmp,
+    Exec,
+    Tcp,
+    Dns,
+    Tls,
+    WebSocket,
+    Mail,
+    Database,
+    Heartbeat,
+}
+
+impl MonitorType {
+    /// The monitor field that selects this kind; `None` for `Http`.
+    pub fn field(&self) -> Option<&'static str> {
+        match self {
+            MonitorType::Http => None,
+            MonitorType::Metrics => Some("metric_assertions"),
+            MonitorType::Transaction => Some("steps"),
+            MonitorType::Broker => Some("broker_check"),
+            MonitorType::Storage => Some("storage_check"),
+            MonitorType::Ntp => Some("ntp_check"),
+            MonitorType::Snmp => Some("snmp_check"),
+            MonitorType::Exec => Some("exec_check"),
+            MonitorType::Tcp => Some("tcp_check"),
+            MonitorType::Dns => Some("dns_check"),
+            MonitorType::Tls => Some("tls_check"),
+            MonitorType::WebSocket => Some("websocket_check"),
+            MonitorType::Mail => Some("mail_check"),
+            MonitorType::Database => Some("database_check"),
+            MonitorType::Heartbeat => Some("heartbeat_check"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct MonitorResult {
     pub id: Uuid,
@@ -28,8 +188,38 @@ pub struct MonitorResult {
     pub response_time: i32,
     pub response_code: Option<i32>,
     pub response_body: Option<String>,
+    /// Only held in memory, for incident evidence; see `evidence`
+    #[serde(skip)]
+    #[sqlx(skip)]
+    pub response_headers: Option<std::collections::BTreeMap<String, String>>,
     pub error_message: Option<String>,
+    /// Failure cause, see `failures::FailureCategory`
+    pub error_category: Option<String>,
+    /// Suggested cause for common failures
+    pub error_hint: Option<String>,
     pub checked_at: DateTime<Utc>,
+    /// Worker clock offset from the reference clock when the check ran
+    pub clock_skew_ms: Option<i32>,
+    /// Delay between the scheduled tick and the start of the check
+    pub schedule_lag_ms: Option<i32>,
+    /// Attempts the check took, retries included
+    #[serde(default = "first_attempt")]
+    pub attempts: i32,
+}
+
+/// Results spilled before attempts were recorded took one.
+fn first_attempt() -> i32 {
+    1
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+pub enum UserRole {
+    Admin,
+    #[default]
+    Member,
+    Viewer,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -37,11 +227,36 @@ pub struct User {
     pub id: Uuid,
     pub username: String,
     pub email: String,
+    #[serde(skip_serializing)]
     pub password_hash: String,
+    pub role: UserRole,
+    pub active: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegisterRequest {
+    pub username: String,
+    pub email: String,
+    pub password: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoginRequest {
+    /// Username or email address
+    pub username: String,
+    pub password: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AuthResponse {
+    pub token: String,
+    /// Seconds until the token expires
+    pub expires_in: i64,
+    pub user: User,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Alert {
     pub id: Uuid,
@@ -49,6 +264,8 @@ pub struct Alert {
     pub type_: String,
     pub config: serde_json::Value,
     pub enabled: bool,
+    /// Set while the rule's condition holds
+    pub firing_since: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -57,25 +274,305 @@ pub struct Alert {
 pub struct CreateMonitorRequest {
     pub name: String,
     pub endpoint: String,
-    pub method: String,
-    pub headers: Option<serde_json::Value>,
+    pub method: crate::http_method::HttpMethod,
+    pub headers: Option<crate::headers::Headers>,
     pub body: Option<String>,
-    pub expected_status: i32,
-    pub timeout: i32,
-    pub interval: i32,
+    pub expected_status: crate::status_match::ExpectedStatus,
+    pub timeout: Option<crate::durations::Seconds>,
+    pub interval: crate::durations::Seconds,
     pub script: Option<String>,
+    pub pre_request_script: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub team_id: Option<Uuid>,
+    pub retries: Option<i32>,
+    pub retry_delay_ms: Option<i32>,
+    pub schedule_cron: Option<String>,
+    pub notification_channels: Option<Vec<Uuid>>,
+    pub script_profile: Option<String>,
+    /// Secret headers, stored encrypted and merged into the request at check time
+    pub credentials: Option<std::collections::HashMap<String, String>>,
+    pub steps: Option<Vec<crate::transaction::TransactionStep>>,
+    /// Conditions such as `up == 1` on the scraped metrics
+    pub metric_assertions: Option<Vec<crate::openmetrics::MetricAssertion>>,
+    /// Probe or consumer lag check against the broker at `endpoint`
+    pub broker_check: Option<crate::brokers::BrokerCheck>,
+    /// HEAD, GET or write-read-delete round trip against the bucket at `endpoint`
+    pub storage_check: Option<crate::storage::StorageCheck>,
+    /// Stratum and offset bounds for the NTP server at `endpoint`
+    pub ntp_check: Option<crate::ntp::NtpCheck>,
+    /// OIDs to poll and thresholds for the SNMP agent at `endpoint`
+    pub snmp_check: Option<crate::snmp::SnmpCheck>,
+    /// Arguments and output handling for the command named by `endpoint`
+    pub exec_check: Option<crate::exec::ExecCheck>,
+    /// Payload and banner for the port at `endpoint`
+    pub tcp_check: Option<crate::tcp::TcpCheck>,
+    /// Record type, resolver and expectations for the name at `endpoint`
+    pub dns_check: Option<crate::dns::DnsCheck>,
+    /// Expiry threshold for the certificate served at `endpoint`
+    pub tls_check: Option<crate::tls::TlsCheck>,
+    /// Message and expected reply for the WebSocket at `endpoint`
+    pub websocket_check: Option<crate::websocket::WebSocketCheck>,
+    /// STARTTLS, login and greeting options for the mail server at `endpoint`
+    pub mail_check: Option<crate::mail::MailCheck>,
+    /// Query for the database server whose DSN is `endpoint`
+    pub database_check: Option<crate::databases::DatabaseCheck>,
+    /// How late pings from the job named by `endpoint` may be
+    pub heartbeat_check: Option<crate::heartbeats::HeartbeatCheck>,
+    /// Text the response body must contain, on top of the status code
+    pub expected_body_contains: Option<String>,
+    /// Regular expression the response body must match
+    pub expected_body_regex: Option<String>,
+    #[serde(default)]
+    pub bypass_dns_cache: bool,
+    pub connect_timeout_ms: Option<i32>,
+    pub tls_timeout_ms: Option<i32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpdateMonitorRequest {
     pub name: Option<String>,
     pub endpoint: Option<String>,
-    pub method: Option<String>,
-    pub headers: Option<serde_json::Value>,
+    pub method: Option<crate::http_method::HttpMethod>,
+    pub headers: Option<crate::headers::Headers>,
     pub body: Option<String>,
-    pub expected_status: Option<i32>,
-    pub timeout: Option<i32>,
-    pub interval: Option<i32>,
+    pub expected_status: Option<crate::status_match::ExpectedStatus>,
+    pub timeout: Option<crate::durations::Seconds>,
+    pub interval: Option<crate::durations::Seconds>,
     pub script: Option<String>,
+    pub pre_request_script: Option<String>,
     pub enabled: Option<bool>,
-}
\ No newline at end of file
+    pub tags: Option<Vec<String>>,
+    pub team_id: Option<Uuid>,
+    pub retries: Option<i32>,
+    pub retry_delay_ms: Option<i32>,
+    /// An empty string clears the schedule and returns to `interval`
+    pub schedule_cron: Option<String>,
+    pub notification_channels: Option<Vec<Uuid>>,
+    pub script_profile: Option<String>,
+    pub credentials: Option<std::collections::HashMap<String, String>>,
+    pub steps: Option<Vec<crate::transaction::TransactionStep>>,
+    pub metric_assertions: Option<Vec<crate::openmetrics::MetricAssertion>>,
+    pub broker_check: Option<crate::brokers::BrokerCheck>,
+    pub storage_check: Option<crate::storage::StorageCheck>,
+    pub ntp_check: Option<crate::ntp::NtpCheck>,
+    pub snmp_check: Option<crate::snmp::SnmpCheck>,
+    pub exec_check: Option<crate::exec::ExecCheck>,
+    pub tcp_check: Option<crate::tcp::TcpCheck>,
+    pub dns_check: Option<crate::dns::DnsCheck>,
+    pub tls_check: Option<crate::tls::TlsCheck>,
+    pub websocket_check: Option<crate::websocket::WebSocketCheck>,
+    pub mail_check: Option<crate::mail::MailCheck>,
+    pub database_check: Option<crate::databases::DatabaseCheck>,
+    pub heartbeat_check: Option<crate::heartbeats::HeartbeatCheck>,
+    /// An empty string clears the keyword
+    pub expected_body_contains: Option<String>,
+    /// An empty string clears the pattern
+    pub expected_body_regex: Option<String>,
+    pub bypass_dns_cache: Option<bool>,
+    pub connect_timeout_ms: Option<i32>,
+    pub tls_timeout_ms: Option<i32>,
+}
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct WebhookEndpoint {
+    pub id: Uuid,
+    pub monitor_id: Option<Uuid>,
+    pub url: String,
+    #[serde(skip_serializing)]
+    pub secret: String,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateWebhookRequest {
+    pub url: String,
+    pub monitor_id: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum ProvisioningOperation {
+    Create {
+        username: String,
+        email: String,
+        password: Option<String>,
+        #[serde(default)]
+        role: UserRole,
+    },
+    Update {
+        username: String,
+        email: Option<String>,
+        role: Option<UserRole>,
+    },
+    Deactivate {
+        username: String,
+    },
+    Reactivate {
+        username: String,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkProvisioningRequest {
+    pub operations: Vec<ProvisioningOperation>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProvisioningOutcome {
+    pub username: String,
+    pub op: String,
+    pub success: bool,
+    pub user: Option<User>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TokenScope {
+    #[serde(rename = "read:monitors")]
+    ReadMonitors,
+    #[serde(rename = "write:monitors")]
+    WriteMonitors,
+    #[serde(rename = "read:results")]
+    ReadResults,
+}
+
+impl TokenScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TokenScope::ReadMonitors => "read:monitors",
+            TokenScope::WriteMonitors => "write:monitors",
+            TokenScope::ReadResults => "read:results",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "read:monitors" => Some(TokenScope::ReadMonitors),
+            "write:monitors" => Some(TokenScope::WriteMonitors),
+            "read:results" => Some(TokenScope::ReadResults),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+pub enum TokenKind {
+    #[default]
+    Personal,
+    /// Read-only status and stats for allow-listed monitors, for lobby screens and embeds
+    Dashboard,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct PersonalAccessToken {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub token_prefix: String,
+    #[serde(skip_serializing)]
+    pub token_hash: String,
+    pub kind: TokenKind,
+    pub scopes: Vec<String>,
+    pub tags: Vec<String>,
+    /// Dashboard tokens only; empty for personal tokens
+    pub monitor_ids: Vec<Uuid>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatePersonalAccessTokenRequest {
+    pub name: String,
+    pub scopes: Vec<TokenScope>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub expires_in_days: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateDashboardTokenRequest {
+    pub name: String,
+    #[serde(default)]
+    pub monitor_ids: Vec<Uuid>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub expires_in_days: Option<i64>,
+}
+
+pub const DEFAULT_PER_PAGE: i64 = 50;
+pub const MAX_PER_PAGE: i64 = 200;
+
+/// Which page of a listing to return. Pages start at 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageRequest {
+    pub page: i64,
+    pub per_page: i64,
+}
+
+impl Default for PageRequest {
+    fn default() -> Self {
+        Self {
+            page: 1,
+            per_page: DEFAULT_PER_PAGE,
+        }
+    }
+}
+
+impl PageRequest {
+    pub fn validate(&self) -> crate::Result<()> {
+        if self.page < 1 {
+            return Err(crate::Error::validation("page must be at least 1"));
+        }
+        if !(1..=MAX_PER_PAGE).contains(&self.per_page) {
+            return Err(crate::Error::validation(format!("per_page must be between 1 and {}", MAX_PER_PAGE)));
+        }
+        Ok(())
+    }
+
+    pub fn offset(&self) -> i64 {
+        (self.page - 1) * self.per_page
+    }
+}
+
+/// One page of a listing, with the number of items across all pages.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Page<T> {
+    pub total: i64,
+    pub page: i64,
+    pub per_page: i64,
+    pub items: Vec<T>,
+}
+
+impl<T> Page<T> {
+    pub fn new(request: PageRequest, total: i64, items: Vec<T>) -> Self {
+        Self {
+            total,
+            page: request.page,
+            per_page: request.per_page,
+            items,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+impl SortOrder {
+    pub fn as_sql(self) -> &'static str {
+        match self {
+            SortOrder::Asc => "ASC",
+            SortOrder::Desc => "DESC",
+        }
+    }
+}
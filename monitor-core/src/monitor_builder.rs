@@ -0,0 +1,463 @@
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use uuid::Uuid;
+use crate::{
+    body_match,
+    brokers::BrokerCheck,
+    dns::DnsCheck,
+    durations::Seconds,
+    error::Result,
+    databases::DatabaseCheck,
+    exec::ExecCheck,
+    headers::{self, Headers},
+    heartbeats::HeartbeatCheck,
+    http_method::HttpMethod,
+    mail::MailCheck,
+    models::{CreateMonitorRequest, Monitor, MonitorType, UpdateMonitorRequest},
+    ntp::NtpCheck,
+    openmetrics::{self, MetricAssertion},
+    queue,
+    settings::SettingsOverride,
+    snmp::SnmpCheck,
+    status_match::ExpectedStatus,
+    storage::StorageCheck,
+    tcp::TcpCheck,
+    tls::TlsCheck,
+    transaction::{self, TransactionStep},
+    websocket::WebSocketCheck,
+    Error,
+};
+
+/// Interval of built monitors unless one is set
+pub const DEFAULT_INTERVAL: Seconds = Seconds::new(60);
+
+/// Upper bound for `connect_timeout_ms` and `tls_timeout_ms`
+pub const MAX_PHASE_TIMEOUT_MS: i32 = 60_000;
+
+/// Upper bound for `retry_delay_ms`
+pub const MAX_RETRY_DELAY_MS: i32 = 60_000;
+
+impl CreateMonitorRequest {
+    /// A GET expecting `200` every minute, with nothing else set.
+    pub fn builder(name: impl Into<String>, endpoint: impl Into<String>) -> MonitorBuilder {
+        MonitorBuilder {
+            request: CreateMonitorRequest {
+                name: name.into(),
+                endpoint: endpoint.into(),
+                method: HttpMethod::Get,
+                headers: None,
+                body: None,
+                expected_status: ExpectedStatus::default(),
+                timeout: None,
+                interval: DEFAULT_INTERVAL,
+                script: None,
+                pre_request_script: None,
+                tags: Vec::new(),
+                team_id: None,
+                retries: None,
+                retry_delay_ms: None,
+                schedule_cron: None,
+                notification_channels: None,
+                script_profile: None,
+                credentials: None,
+                steps: None,
+                metric_assertions: None,
+                broker_check: None,
+                storage_check: None,
+                ntp_check: None,
+                snmp_check: None,
+                exec_check: None,
+                tcp_check: None,
+                dns_check: None,
+                tls_check: None,
+                websocket_check: None,
+                mail_check: None,
+                database_check: None,
+                heartbeat_check: None,
+                expected_body_contains: None,
+                expected_body_regex: None,
+                bypass_dns_cache: false,
+                connect_timeout_ms: None,
+                tls_timeout_ms: None,
+            },
+        }
+    }
+
+    /// `existing` as it will be once `update` is applied, so that an edit gets
+    /// the same checks as a new monitor: check kinds that would end up combined,
+    /// `timeout` against `interval` and stored checks against a new endpoint.
+    pub fn merged(existing: &Monitor, update: &UpdateMonitorRequest) -> Result<Self> {
+        Ok(CreateMonitorRequest {
+            name: update.name.clone().unwrap_or_else(|| existing.name.clone()),
+            endpoint: update.endpoint.clone().unwrap_or_else(|| existing.endpoint.clone()),
+            method: update.method.clone().unwrap_or_else(|| existing.method.clone()),
+            headers: or_stored(&update.headers, &existing.headers)?,
+            body: update.body.clone().or_else(|| existing.body.clone()),
+            expected_status: update.expected_status.clone().unwrap_or_else(|| existing.expected_status.clone()),
+            timeout: update.timeout.or(existing.timeout),
+            interval: update.interval.unwrap_or(existing.interval),
+            script: update.script.clone().or_else(|| existing.script.clone()),
+            pre_request_script: update.pre_request_script.clone().or_else(|| existing.pre_request_script.clone()),
+            tags: update.tags.clone().unwrap_or_else(|| existing.tags.clone()),
+            team_id: update.team_id.or(existing.team_id),
+            retries: update.retries.or(existing.retries),
+            retry_delay_ms: update.retry_delay_ms.or(existing.retry_delay_ms),
+            schedule_cron: update.schedule_cron.clone().or_else(|| existing.schedule_cron.clone()),
+            notification_channels: update
+                .notification_channels
+                .clone()
+                .or_else(|| existing.notification_channels.clone()),
+            script_profile: update.script_profile.clone().or_else(|| existing.script_profile.clone()),
+            // Stored encrypted; they have no rules of their own to check
+            credentials: update.credentials.clone(),
+            steps: or_stored(&update.steps, &existing.steps)?,
+            metric_assertions: or_stored(&update.metric_assertions, &existing.metric_assertions)?,
+            broker_check: or_stored(&update.broker_check, &existing.broker_check)?,
+            storage_check: or_stored(&update.storage_check, &existing.storage_check)?,
+            ntp_check: or_stored(&update.ntp_check, &existing.ntp_check)?,
+            snmp_check: or_stored(&update.snmp_check, &existing.snmp_check)?,
+            exec_check: or_stored(&update.exec_check, &existing.exec_check)?,
+            tcp_check: or_stored(&update.tcp_check, &existing.tcp_check)?,
+            dns_check: or_stored(&update.dns_check, &existing.dns_check)?,
+            tls_check: or_stored(&update.tls_check, &existing.tls_check)?,
+            websocket_check: or_stored(&update.websocket_check, &existing.websocket_check)?,
+            mail_check: or_stored(&update.mail_check, &existing.mail_check)?,
+            database_check: or_stored(&update.database_check, &existing.database_check)?,
+            heartbeat_check: or_stored(&update.heartbeat_check, &existing.heartbeat_check)?,
+            expected_body_contains: update
+                .expected_body_contains
+                .clone()
+                .or_else(|| existing.expected_body_contains.clone()),
+            expected_body_regex: update.expected_body_regex.clone().or_else(|| existing.expected_body_regex.clone()),
+            bypass_dns_cache: update.bypass_dns_cache.unwrap_or(existing.bypass_dns_cache),
+            connect_timeout_ms: update.connect_timeout_ms.or(existing.connect_timeout_ms),
+            tls_timeout_ms: update.tls_timeout_ms.or(existing.tls_timeout_ms),
+        })
+    }
+
+    /// Checks that need nothing but the request itself. Tag access, team
+    /// membership, script profiles and feature flags are up to the caller.
+    pub fn validate(&self) -> Result<()> {
+        if self.name.trim().is_empty() {
+            return Err(Error::validation("Monitor name is required"));
+        }
+        if !self.interval.is_positive() {
+            return Err(Error::validation("interval must be positive"));
+        }
+        if let Some(timeout) = self.timeout
+            && timeout >= self.interval
+        {
+            return Err(Error::validation(format!(
+                "timeout ({}) must be shorter than interval ({})",
+                timeout, self.interval
+            )));
+        }
+        validate_connect_timeouts(self.connect_timeout_ms, self.tls_timeout_ms)?;
+        validate_retry_delay(self.retry_delay_ms)?;
+        if let Some(expression) = self.schedule_cron.as_deref().filter(|expression| !expression.is_empty()) {
+            queue::validate_cron(expression)?;
+        }
+        if let Some(headers) = &self.headers {
+            headers::validate(headers)?;
+        }
+        body_match::validate(self.expected_body_contains.as_deref(), self.expected_body_regex.as_deref())?;
+        SettingsOverride {
+            timeout: self.timeout,
+            retries: self.retries,
+            notification_channels: self.notification_channels.clone(),
+            script_profile: self.script_profile.clone(),
+        }
+        .validate()?;
+        if let Some(steps) = &self.steps {
+            transaction::validate_steps(steps)?;
+        }
+        validate_check_kind(&[
+            (MonitorType::Broker, self.broker_check.is_some()),
+            (MonitorType::Storage, self.storage_check.is_some()),
+            (MonitorType::Ntp, self.ntp_check.is_some()),
+            (MonitorType::Snmp, self.snmp_check.is_some()),
+            (MonitorType::Exec, self.exec_check.is_some()),
+            (MonitorType::Tcp, self.tcp_check.is_some()),
+            (MonitorType::Dns, self.dns_check.is_some()),
+            (MonitorType::Tls, self.tls_check.is_some()),
+            (MonitorType::WebSocket, self.websocket_check.is_some()),
+            (MonitorType::Mail, self.mail_check.is_some()),
+            (MonitorType::Database, self.database_check.is_some()),
+            (MonitorType::Heartbeat, self.heartbeat_check.is_some()),
+            (MonitorType::Transaction, self.steps.is_some()),
+            (MonitorType::Metrics, self.metric_assertions.is_some()),
+        ])?;
+        if let Some(assertions) = &self.metric_assertions {
+            openmetrics::validate_assertions(assertions)?;
+        }
+        if let Some(check) = &self.broker_check {
+            check.validate(&self.endpoint)?;
+        }
+        if let Some(check) = &self.storage_check {
+            check.validate(&self.endpoint)?;
+        }
+        if let Some(check) = &self.ntp_check {
+            check.validate(&self.endpoint)?;
+        }
+        if let Some(check) = &self.snmp_check {
+            check.validate(&self.endpoint)?;
+        }
+        if let Some(check) = &self.exec_check {
+            check.validate(&self.endpoint)?;
+        }
+        if let Some(check) = &self.tcp_check {
+            check.validate(&self.endpoint)?;
+        }
+        if let Some(check) = &self.dns_check {
+            check.validate(&self.endpoint)?;
+        }
+        if let Some(check) = &self.tls_check {
+            check.validate(&self.endpoint)?;
+        }
+        if let Some(check) = &self.websocket_check {
+            check.validate(&self.endpoint)?;
+        }
+        if let Some(check) = &self.mail_check {
+            check.validate(&self.endpoint)?;
+        }
+        if let Some(check) = &self.database_check {
+            check.validate(&self.endpoint)?;
+        }
+        if let Some(check) = &self.heartbeat_check {
+            check.validate(&self.endpoint)?;
+        }
+        Ok(())
+    }
+}
+
+/// Builds a [`CreateMonitorRequest`] for code that creates monitors, such as
+/// importers and discovery, with the same checks the API applies.
+#[derive(Debug, Clone)]
+pub struct MonitorBuilder {
+    request: CreateMonitorRequest,
+}
+
+impl MonitorBuilder {
+    pub fn method(mut self, method: HttpMethod) -> Self {
+        self.request.method = method;
+        self
+    }
+
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.request.headers.get_or_insert_with(Headers::new).insert(name.into(), value.into());
+        self
+    }
+
+    pub fn body(mut self, body: impl Into<String>) -> Self {
+        self.request.body = Some(body.into());
+        self
+    }
+
+    pub fn expected_status(mut self, status: impl Into<ExpectedStatus>) -> Self {
+        self.request.expected_status = status.into();
+        self
+    }
+
+    pub fn timeout(mut self, timeout: impl Into<Option<Seconds>>) -> Self {
+        self.request.timeout = timeout.into();
+        self
+    }
+
+    pub fn interval(mut self, interval: Seconds) -> Self {
+        self.request.interval = interval;
+        self
+    }
+
+    pub fn script(mut self, script: impl Into<String>) -> Self {
+        self.request.script = Some(script.into());
+        self
+    }
+
+    pub fn pre_request_script(mut self, script: impl Into<String>) -> Self {
+        self.request.pre_request_script = Some(script.into());
+        self
+    }
+
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.request.tags.push(tag.into());
+        self
+    }
+
+    pub fn tags(mut self, tags: Vec<String>) -> Self {
+        self.request.tags = tags;
+        self
+    }
+
+    pub fn team(mut self, team_id: impl Into<Option<Uuid>>) -> Self {
+        self.request.team_id = team_id.into();
+        self
+    }
+
+    pub fn retries(mut self, retries: i32, delay_ms: Option<i32>) -> Self {
+        self.request.retries = Some(retries);
+        self.request.retry_delay_ms = delay_ms;
+        self
+    }
+
+    pub fn schedule_cron(mut self, expression: impl Into<String>) -> Self {
+        self.request.schedule_cron = Some(expression.into());
+        self
+    }
+
+    pub fn notification_channels(mut self, channels: Vec<Uuid>) -> Self {
+        self.request.notification_channels = Some(channels);
+        self
+    }
+
+    pub fn script_profile(mut self, profile: impl Into<String>) -> Self {
+        self.request.script_profile = Some(profile.into());
+        self
+    }
+
+    pub fn credential(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.request.credentials.get_or_insert_with(HashMap::new).insert(name.into(), value.into());
+        self
+    }
+
+    pub fn steps(mut self, steps: Vec<TransactionStep>) -> Self {
+        self.request.steps = Some(steps);
+        self
+    }
+
+    pub fn metric_assertions(mut self, assertions: Vec<MetricAssertion>) -> Self {
+        self.request.metric_assertions = Some(assertions);
+        self
+    }
+
+    pub fn broker_check(mut self, check: BrokerCheck) -> Self {
+        self.request.broker_check = Some(check);
+        self
+    }
+
+    pub fn storage_check(mut self, check: StorageCheck) -> Self {
+        self.request.storage_check = Some(check);
+        self
+    }
+
+    pub fn ntp_check(mut self, check: NtpCheck) -> Self {
+        self.request.ntp_check = Some(check);
+        self
+    }
+
+    pub fn snmp_check(mut self, check: SnmpCheck) -> Self {
+        self.request.snmp_check = Some(check);
+        self
+    }
+
+    pub fn exec_check(mut self, check: ExecCheck) -> Self {
+        self.request.exec_check = Some(check);
+        self
+    }
+
+    pub fn tcp_check(mut self, check: TcpCheck) -> Self {
+        self.request.tcp_check = Some(check);
+        self
+    }
+
+    pub fn dns_check(mut self, check: DnsCheck) -> Self {
+        self.request.dns_check = Some(check);
+        self
+    }
+
+    pub fn tls_check(mut self, check: TlsCheck) -> Self {
+        self.request.tls_check = Some(check);
+        self
+    }
+
+    pub fn websocket_check(mut self, check: WebSocketCheck) -> Self {
+        self.request.websocket_check = Some(check);
+        self
+    }
+
+    pub fn mail_check(mut self, check: MailCheck) -> Self {
+        self.request.mail_check = Some(check);
+        self
+    }
+
+    pub fn database_check(mut self, check: DatabaseCheck) -> Self {
+        self.request.database_check = Some(check);
+        self
+    }
+
+    pub fn heartbeat_check(mut self, check: HeartbeatCheck) -> Self {
+        self.request.heartbeat_check = Some(check);
+        self
+    }
+
+    pub fn expected_body_contains(mut self, keyword: impl Into<String>) -> Self {
+        self.request.expected_body_contains = Some(keyword.into());
+        self
+    }
+
+    pub fn expected_body_regex(mut self, pattern: impl Into<String>) -> Self {
+        self.request.expected_body_regex = Some(pattern.into());
+        self
+    }
+
+    pub fn bypass_dns_cache(mut self, bypass: bool) -> Self {
+        self.request.bypass_dns_cache = bypass;
+        self
+    }
+
+    pub fn connect_timeout_ms(mut self, ms: i32) -> Self {
+        self.request.connect_timeout_ms = Some(ms);
+        self
+    }
+
+    pub fn tls_timeout_ms(mut self, ms: i32) -> Self {
+        self.request.tls_timeout_ms = Some(ms);
+        self
+    }
+
+    pub fn build(self) -> Result<CreateMonitorRequest> {
+        self.request.validate()?;
+        Ok(self.request)
+    }
+}
+
+/// The updated value, or else the one stored as JSON on the monitor.
+fn or_stored<T: Clone + DeserializeOwned>(update: &Option<T>, stored: &Option<serde_json::Value>) -> Result<Option<T>> {
+    match update {
+        Some(value) => Ok(Some(value.clone())),
+        None => Ok(stored.clone().map(serde_json::from_value).transpose()?),
+    }
+}
+
+/// Steps, metric assertions and the non-HTTP checks each replace the plain
+/// request, so a monitor may use at most one of them. Returns the kind the
+/// set fields select.
+pub fn validate_check_kind(kinds: &[(MonitorType, bool)]) -> Result<MonitorType> {
+    let chosen: Vec<MonitorType> = kinds.iter().filter(|(_, set)| *set).map(|(kind, _)| *kind).collect();
+    match chosen[..] {
+        [] => Ok(MonitorType::Http),
+        [kind] => Ok(kind),
+        _ => {
+            let fields: Vec<&str> = chosen.iter().filter_map(MonitorType::field).collect();
+            Err(Error::validation(format!("{} cannot be combined", fields.join(" and "))))
+        }
+    }
+}
+
+pub fn validate_retry_delay(retry_delay_ms: Option<i32>) -> Result<()> {
+    if retry_delay_ms.is_some_and(|ms| !(0..=MAX_RETRY_DELAY_MS).contains(&ms)) {
+        return Err(Error::validation(format!("retry_delay_ms must be between 0 and {}", MAX_RETRY_DELAY_MS)));
+    }
+    Ok(())
+}
+
+pub fn validate_connect_timeouts(connect_timeout_ms: Option<i32>, tls_timeout_ms: Option<i32>) -> Result<()> {
+    for (field, value) in [("connect_timeout_ms", connect_timeout_ms), ("tls_timeout_ms", tls_timeout_ms)] {
+        if value.is_some_and(|ms| !(1..=MAX_PHASE_TIMEOUT_MS).contains(&ms)) {
+            return Err(Error::validation(format!(
+                "{} must be between 1 and {}",
+                field, MAX_PHASE_TIMEOUT_MS
+            )));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,181 @@
+#[cfg(test)]
+mod monitor_builder_tests {
+    use crate::durations::Seconds;
+    use crate::http_method::HttpMethod;
+    use crate::models::{CreateMonitorRequest, Monitor, MonitorType, UpdateMonitorRequest};
+    use crate::monitor_builder::*;
+    use crate::tcp::TcpCheck;
+    use crate::tls::TlsCheck;
+    use chrono::Utc;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    fn tcp_monitor() -> Monitor {
+        let now = Utc::now();
+        Monitor {
+            id: Uuid::new_v4(),
+            name: "db".to_string(),
+            endpoint: "tcp://db.example.com:5432".to_string(),
+            method: HttpMethod::Get,
+            headers: None,
+            body: None,
+            expected_status: 200.into(),
+            timeout: Some(Seconds::new(10)),
+            interval: Seconds::new(60),
+            script: None,
+            pre_request_script: None,
+            enabled: true,
+            paused_reason: None,
+            paused_by: None,
+            paused_at: None,
+            resume_at: None,
+            tags: vec![],
+            owner_id: None,
+            team_id: None,
+            system_key: None,
+            retries: None,
+            retry_delay_ms: None,
+            schedule_cron: None,
+            notification_channels: None,
+            script_profile: None,
+            credentials: None,
+            steps: None,
+            metric_assertions: None,
+            broker_check: None,
+            storage_check: None,
+            ntp_check: None,
+            snmp_check: None,
+            exec_check: None,
+            tcp_check: Some(json!({ "banner": "PONG" })),
+            dns_check: None,
+            tls_check: None,
+            websocket_check: None,
+            mail_check: None,
+            database_check: None,
+            heartbeat_check: None,
+            expected_body_contains: None,
+            expected_body_regex: None,
+            bypass_dns_cache: false,
+            connect_timeout_ms: None,
+            tls_timeout_ms: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    fn update(fields: serde_json::Value) -> UpdateMonitorRequest {
+        serde_json::from_value(fields).unwrap()
+    }
+
+    #[test]
+    fn defaults_to_a_get_expecting_200_every_minute() {
+        let request = CreateMonitorRequest::builder("API", "https://example.com/health").build().unwrap();
+        assert_eq!(request.method, HttpMethod::Get);
+        assert_eq!(request.expected_status, 200.into());
+        assert_eq!(request.interval, DEFAULT_INTERVAL);
+        assert_eq!(request.timeout, None);
+        assert!(request.tags.is_empty());
+        assert!(request.headers.is_none());
+    }
+
+    #[test]
+    fn setters_fill_in_the_request() {
+        let request = CreateMonitorRequest::builder("API", "https://example.com/orders")
+            .method(HttpMethod::Post)
+            .header("Content-Type", "application/json")
+            .body("{}")
+            .expected_status(201)
+            .timeout(Seconds::new(10))
+            .interval(Seconds::new(300))
+            .tag("shop")
+            .tag("orders")
+            .retries(2, Some(500))
+            .build()
+            .unwrap();
+        assert_eq!(request.method, HttpMethod::Post);
+        assert_eq!(request.headers.unwrap()["Content-Type"], "application/json");
+        assert_eq!(request.expected_status, 201.into());
+        assert_eq!(request.timeout, Some(Seconds::new(10)));
+        assert_eq!(request.tags, vec!["shop", "orders"]);
+        assert_eq!((request.retries, request.retry_delay_ms), (Some(2), Some(500)));
+    }
+
+    #[test]
+    fn timeout_must_be_shorter_than_interval() {
+        let builder = CreateMonitorRequest::builder("API", "https://example.com").interval(Seconds::new(30));
+        assert!(builder.clone().timeout(Seconds::new(29)).build().is_ok());
+        assert!(builder.clone().timeout(Seconds::new(30)).build().is_err());
+        assert!(builder.timeout(Seconds::new(60)).build().is_err());
+    }
+
+    #[test]
+    fn single_field_checks_still_apply() {
+        assert!(CreateMonitorRequest::builder(" ", "https://example.com").build().is_err());
+        let builder = CreateMonitorRequest::builder("API", "https://example.com");
+        assert!(builder.clone().interval(Seconds::new(0)).build().is_err());
+        assert!(builder.clone().retries(11, None).build().is_err());
+        assert!(builder.clone().retries(1, Some(MAX_RETRY_DELAY_MS + 1)).build().is_err());
+        assert!(builder.clone().connect_timeout_ms(0).build().is_err());
+        assert!(builder.clone().tls_timeout_ms(MAX_PHASE_TIMEOUT_MS + 1).build().is_err());
+        assert!(builder.clone().header("Bad Name", "x").build().is_err());
+        assert!(builder.clone().expected_body_regex("(unclosed").build().is_err());
+        assert!(builder.schedule_cron("not a schedule").build().is_err());
+    }
+
+    #[test]
+    fn checks_are_validated_against_the_endpoint_and_each_other() {
+        let cert = |endpoint| CreateMonitorRequest::builder("Cert", endpoint).tls_check(TlsCheck::default());
+        assert!(cert("tls://example.com").build().is_ok());
+        assert!(cert("ftp://example.com").build().is_err());
+        let error = CreateMonitorRequest::builder("Both", "tcp://example.com:443")
+            .tcp_check(TcpCheck::default())
+            .tls_check(TlsCheck::default())
+            .build()
+            .unwrap_err();
+        assert!(error.to_string().contains("tcp_check and tls_check cannot be combined"));
+    }
+
+    #[test]
+    fn only_one_check_kind_may_be_set() {
+        let kind = validate_check_kind(&[(MonitorType::Transaction, true), (MonitorType::Tcp, false)]).unwrap();
+        assert_eq!(kind, MonitorType::Transaction);
+        assert_eq!(validate_check_kind(&[(MonitorType::Tcp, false)]).unwrap(), MonitorType::Http);
+        assert!(validate_check_kind(&[(MonitorType::Transaction, true), (MonitorType::Tcp, true)]).is_err());
+    }
+
+    #[test]
+    fn monitor_type_follows_the_check_field() {
+        let monitor = tcp_monitor();
+        assert_eq!(monitor.monitor_type(), MonitorType::Tcp);
+        assert_eq!(monitor.check_config(), monitor.tcp_check.as_ref());
+
+        let plain = Monitor { tcp_check: None, ..tcp_monitor() };
+        assert_eq!(plain.monitor_type(), MonitorType::Http);
+        assert_eq!(plain.check_config(), None);
+    }
+
+    #[test]
+    fn edits_are_validated_together_with_the_stored_monitor() {
+        let existing = tcp_monitor();
+        let merged = CreateMonitorRequest::merged(&existing, &update(json!({ "name": "primary db" }))).unwrap();
+        assert_eq!(merged.name, "primary db");
+        assert_eq!(merged.tcp_check.as_ref().and_then(|check| check.banner.as_deref()), Some("PONG"));
+        assert!(merged.validate().is_ok());
+
+        let combined = CreateMonitorRequest::merged(&existing, &update(json!({ "tls_check": {} }))).unwrap();
+        let error = combined.validate().unwrap_err();
+        assert!(error.to_string().contains("tcp_check and tls_check cannot be combined"));
+
+        let shorter = CreateMonitorRequest::merged(&existing, &update(json!({ "interval": 10 }))).unwrap();
+        assert!(shorter.validate().is_err());
+    }
+
+    #[test]
+    fn stored_checks_are_validated_against_a_new_endpoint() {
+        let existing = tcp_monitor();
+        let moved = update(json!({ "endpoint": "tcp://replica.example.com:5432" }));
+        assert!(CreateMonitorRequest::merged(&existing, &moved).unwrap().validate().is_ok());
+        let http = update(json!({ "endpoint": "https://example.com/health" }));
+        assert!(CreateMonitorRequest::merged(&existing, &http).unwrap().validate().is_err());
+    }
+}
@@ -0,0 +1,161 @@
+use chrono::{DateTime, Utc};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tokio::net::UdpSocket;
+use crate::{error::Result, Error};
+
+/// Seconds between the NTP epoch (1900) and the Unix epoch (1970).
+const NTP_UNIX_OFFSET: i64 = 2_208_988_800;
+const PACKET_LEN: usize = 48;
+/// Highest stratum of a synchronized server
+const MAX_STRATUM: u8 = 15;
+
+/// Converts a 64-bit NTP timestamp (seconds + 2^-32 fractions since 1900).
+pub fn parse_timestamp(bytes: &[u8]) -> Option<DateTime<Utc>> {
+    let seconds = u32::from_be_bytes(bytes.get(0..4)?.try_into().ok()?) as i64;
+    let fraction = u32::from_be_bytes(bytes.get(4..8)?.try_into().ok()?) as i64;
+    let nanos = (fraction * 1_000_000_000) >> 32;
+    DateTime::from_timestamp(seconds - NTP_UNIX_OFFSET, nanos as u32)
+}
+
+/// Standard SNTP clock offset: ((t2 - t1) + (t3 - t4)) / 2, positive when the
+/// server is ahead of the local clock.
+pub fn clock_offset_ms(
+    sent: DateTime<Utc>,
+    server_received: DateTime<Utc>,
+    server_sent: DateTime<Utc>,
+    received: DateTime<Utc>,
+) -> i64 {
+    ((server_received - sent) + (server_sent - received)).num_milliseconds() / 2
+}
+
+/// One exchange with a server.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NtpSample {
+    /// Positive when the server is ahead of the local clock
+    pub offset_ms: i64,
+    pub round_trip_ms: i64,
+    pub stratum: u8,
+    /// 3 means the server's clock is not synchronized
+    pub leap_indicator: u8,
+    /// Reference clock code for stratum 1, upstream address otherwise
+    pub reference: String,
+}
+
+/// Reads a server response to a request sent at `sent` and received at `received`.
+pub fn parse_response(response: &[u8], sent: DateTime<Utc>, received: DateTime<Utc>) -> Result<NtpSample> {
+    if response.len() < PACKET_LEN {
+        return Err(Error::internal("Short NTP response"));
+    }
+    let server_received = parse_timestamp(&response[32..40])
+        .ok_or_else(|| Error::internal("Invalid NTP receive timestamp"))?;
+    let server_sent = parse_timestamp(&response[40..48])
+        .ok_or_else(|| Error::internal("Invalid NTP transmit timestamp"))?;
+    let stratum = response[1];
+    let reference = &response[12..16];
+    let reference = if stratum <= 1 {
+        // Kiss codes and reference clocks are up to four ASCII characters
+        String::from_utf8_lossy(reference).trim_end_matches('\0').to_string()
+    } else {
+        reference.iter().map(u8::to_string).collect::<Vec<_>>().join(".")
+    };
+    Ok(NtpSample {
+        offset_ms: clock_offset_ms(sent, server_received, server_sent, received),
+        round_trip_ms: ((received - sent) - (server_sent - server_received)).num_milliseconds().max(0),
+        stratum,
+        leap_indicator: response[0] >> 6,
+        reference,
+    })
+}
+
+/// Queries an SNTP server (`host` or `host:port`).
+pub async fn query(server: &str, timeout: Duration) -> Result<NtpSample> {
+    let address = if server.contains(':') {
+        server.to_string()
+    } else {
+        format!("{}:123", server)
+    };
+
+    let exchange = async {
+        let socket = UdpSocket::bind("0.0.0.0:0").await?;
+        socket.connect(&address).await?;
+
+        // LI = 0, version 3, mode 3 (client)
+        let mut request = [0u8; PACKET_LEN];
+        request[0] = 0x1B;
+        let sent = Utc::now();
+        socket.send(&request).await?;
+
+        let mut response = [0u8; PACKET_LEN];
+        let len = socket.recv(&mut response).await?;
+        Ok::<_, std::io::Error>((sent, Utc::now(), response, len))
+    };
+
+    let (sent, received, response, len) = tokio::time::timeout(timeout, exchange)
+        .await
+        .map_err(|_| Error::internal(format!("NTP server {} did not respond", server)))??;
+    parse_response(&response[..len], sent, received)
+        .map_err(|e| Error::internal(format!("{} from {}", e, server)))
+}
+
+/// Queries an SNTP server (`host` or `host:port`) and returns the local clock
+/// offset in milliseconds.
+pub async fn query_offset_ms(server: &str, timeout: Duration) -> Result<i64> {
+    Ok(query(server, timeout).await?.offset_ms)
+}
+
+/// Bounds an NTP server check holds the server to. The monitor's `endpoint`
+/// is `ntp://host[:port]`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NtpCheck {
+    /// Highest acceptable stratum, 1 being a server with a reference clock
+    pub max_stratum: Option<u8>,
+    /// Largest acceptable offset from the scheduler's clock, either way
+    pub max_offset_ms: Option<i64>,
+}
+
+impl NtpCheck {
+    pub fn validate(&self, endpoint: &str) -> Result<()> {
+        server_address(endpoint)?;
+        if self.max_stratum.is_some_and(|stratum| !(1..=MAX_STRATUM).contains(&stratum)) {
+            return Err(Error::validation(format!("max_stratum must be between 1 and {}", MAX_STRATUM)));
+        }
+        if self.max_offset_ms.is_some_and(|offset| offset <= 0) {
+            return Err(Error::validation("max_offset_ms must be positive"));
+        }
+        Ok(())
+    }
+
+    /// `Err` carries the result's error message when the server is out of bounds.
+    pub fn evaluate(&self, sample: &NtpSample) -> std::result::Result<(), String> {
+        if sample.leap_indicator == 3 || sample.stratum == 0 || sample.stratum > MAX_STRATUM {
+            let detail = if sample.stratum == 0 && !sample.reference.is_empty() {
+                format!(" (kiss code {})", sample.reference)
+            } else {
+                String::new()
+            };
+            return Err(format!("server clock is not synchronized{}", detail));
+        }
+        if let Some(max) = self.max_stratum
+            && sample.stratum > max
+        {
+            return Err(format!("stratum is {}, expected at most {}", sample.stratum, max));
+        }
+        if let Some(max) = self.max_offset_ms
+            && sample.offset_ms.abs() > max
+        {
+            return Err(format!("offset is {} ms, expected at most {} ms either way", sample.offset_ms, max));
+        }
+        Ok(())
+    }
+}
+
+/// `host:port` of an `ntp://` endpoint.
+pub fn server_address(endpoint: &str) -> Result<String> {
+    let url = Url::parse(endpoint).map_err(|e| Error::validation(format!("Invalid NTP endpoint: {}", e)))?;
+    match (url.scheme(), url.host_str()) {
+        ("ntp", Some(host)) => Ok(format!("{}:{}", host, url.port().unwrap_or(123))),
+        _ => Err(Error::validation("ntp_check endpoints must be ntp://host[:port] URLs")),
+    }
+}
@@ -0,0 +1,233 @@
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt, str::FromStr};
+use crate::{error::Result, Error};
+
+/// Sent when the monitor has metric assertions and no `Accept` header of its own.
+pub const ACCEPT: &str = "application/openmetrics-text;version=1.0.0,text/plain;version=0.0.4;q=0.5";
+
+/// One line of a Prometheus text or OpenMetrics exposition.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sample {
+    pub name: String,
+    pub labels: BTreeMap<String, String>,
+    pub value: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Comparison {
+    /// Two-character operators first so `<=` is not read as `<`.
+    const OPERATORS: [(&'static str, Comparison); 6] = [
+        ("==", Comparison::Eq),
+        ("!=", Comparison::Ne),
+        ("<=", Comparison::Le),
+        (">=", Comparison::Ge),
+        ("<", Comparison::Lt),
+        (">", Comparison::Gt),
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        Self::OPERATORS.iter().find(|(_, op)| op == self).map(|(s, _)| *s).unwrap_or_default()
+    }
+
+    pub fn holds(&self, actual: f64, expected: f64) -> bool {
+        match self {
+            Comparison::Eq => actual == expected,
+            Comparison::Ne => actual != expected,
+            Comparison::Lt => actual < expected,
+            Comparison::Le => actual <= expected,
+            Comparison::Gt => actual > expected,
+            Comparison::Ge => actual >= expected,
+        }
+    }
+}
+
+/// A condition on a scraped metric, written like `up == 1` or
+/// `queue_depth{queue="jobs"} < 1000`. Every series matching the name and
+/// labels must satisfy it, and at least one must exist.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct MetricAssertion {
+    pub metric: String,
+    /// Series may carry more labels than these
+    pub labels: BTreeMap<String, String>,
+    pub op: Comparison,
+    pub value: f64,
+}
+
+impl FromStr for MetricAssertion {
+    type Err = Error;
+
+    fn from_str(expression: &str) -> Result<Self> {
+        let invalid = |reason: &str| Error::validation(format!("Invalid metric assertion '{}': {}", expression, reason));
+        let (metric, labels, rest) = parse_series(expression.trim()).map_err(|e| invalid(&e))?;
+        let rest = rest.trim_start();
+        let (symbol, op) = Comparison::OPERATORS
+            .iter()
+            .find(|(symbol, _)| rest.starts_with(symbol))
+            .ok_or_else(|| invalid("expected one of == != < <= > >="))?;
+        let value = parse_value(rest[symbol.len()..].trim()).ok_or_else(|| invalid("expected a number"))?;
+        Ok(Self {
+            metric,
+            labels,
+            op: *op,
+            value,
+        })
+    }
+}
+
+impl TryFrom<String> for MetricAssertion {
+    type Error = Error;
+
+    fn try_from(expression: String) -> Result<Self> {
+        expression.parse()
+    }
+}
+
+impl From<MetricAssertion> for String {
+    fn from(assertion: MetricAssertion) -> Self {
+        assertion.to_string()
+    }
+}
+
+impl fmt::Display for MetricAssertion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", series(&self.metric, &self.labels), self.op.as_str(), self.value)
+    }
+}
+
+impl MetricAssertion {
+    fn matches(&self, sample: &Sample) -> bool {
+        sample.name == self.metric && self.labels.iter().all(|(k, v)| sample.labels.get(k) == Some(v))
+    }
+
+    /// `Err` carries a short reason for the result's error message.
+    pub fn evaluate(&self, samples: &[Sample]) -> std::result::Result<(), String> {
+        let mut matched = false;
+        for sample in samples.iter().filter(|s| self.matches(s)) {
+            matched = true;
+            if !self.op.holds(sample.value, self.value) {
+                return Err(format!(
+                    "{} is {}, expected {} {}",
+                    series(&sample.name, &sample.labels),
+                    sample.value,
+                    self.op.as_str(),
+                    self.value
+                ));
+            }
+        }
+        if matched {
+            Ok(())
+        } else {
+            Err(format!("{} not found", series(&self.metric, &self.labels)))
+        }
+    }
+}
+
+fn series(name: &str, labels: &BTreeMap<String, String>) -> String {
+    if labels.is_empty() {
+        return name.to_string();
+    }
+    let labels: Vec<String> = labels
+        .iter()
+        .map(|(k, v)| format!("{}=\"{}\"", k, v.replace('\\', "\\\\").replace('"', "\\\"")))
+        .collect();
+    format!("{}{{{}}}", name, labels.join(","))
+}
+
+/// Accepts `NaN`, `+Inf` and `-Inf` as the exposition formats write them.
+fn parse_value(value: &str) -> Option<f64> {
+    match value {
+        "+Inf" | "Inf" => Some(f64::INFINITY),
+        "-Inf" => Some(f64::NEG_INFINITY),
+        "NaN" => Some(f64::NAN),
+        _ => value.parse().ok(),
+    }
+}
+
+/// Reads `name{label="value",...}` from the start of `input` and returns the rest.
+fn parse_series(input: &str) -> std::result::Result<(String, BTreeMap<String, String>, &str), String> {
+    let end = input
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == ':'))
+        .unwrap_or(input.len());
+    let name = &input[..end];
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        return Err("expected a metric name".to_string());
+    }
+
+    let mut labels = BTreeMap::new();
+    let Some(mut rest) = input[end..].strip_prefix('{') else {
+        return Ok((name.to_string(), labels, &input[end..]));
+    };
+    loop {
+        rest = rest.trim_start_matches([' ', ',']);
+        if let Some(after) = rest.strip_prefix('}') {
+            return Ok((name.to_string(), labels, after));
+        }
+        let (key, after) = rest.split_once('=').ok_or("expected label=\"value\"")?;
+        let after = after.trim_start().strip_prefix('"').ok_or("label values must be quoted")?;
+
+        let mut value = String::new();
+        let mut chars = after.char_indices();
+        let close = loop {
+            match chars.next() {
+                Some((i, '"')) => break i,
+                Some((_, '\\')) => match chars.next() {
+                    Some((_, 'n')) => value.push('\n'),
+                    Some((_, c)) => value.push(c),
+                    None => return Err("unterminated label value".to_string()),
+                },
+                Some((_, c)) => value.push(c),
+                None => return Err("unterminated label value".to_string()),
+            }
+        };
+        labels.insert(key.trim().to_string(), value);
+        rest = &after[close + 1..];
+    }
+}
+
+/// Parses a Prometheus text (0.0.4) or OpenMetrics exposition. Comments,
+/// timestamps and exemplars are ignored.
+pub fn parse(body: &str) -> std::result::Result<Vec<Sample>, String> {
+    let mut samples = Vec::new();
+    for (number, line) in body.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let invalid = |reason: String| format!("line {} is not a metric sample: {}", number + 1, reason);
+        let (name, labels, rest) = parse_series(line).map_err(invalid)?;
+        // OpenMetrics exemplars follow a `#`
+        let rest = rest.split('#').next().unwrap_or_default();
+        let value = rest
+            .split_whitespace()
+            .next()
+            .and_then(parse_value)
+            .ok_or_else(|| invalid("missing or invalid value".to_string()))?;
+        samples.push(Sample { name, labels, value });
+    }
+    Ok(samples)
+}
+
+/// Checks a scraped body against a monitor's stored `metric_assertions`.
+pub fn check(assertions: &serde_json::Value, body: &str) -> std::result::Result<(), String> {
+    let assertions: Vec<MetricAssertion> =
+        serde_json::from_value(assertions.clone()).map_err(|e| format!("invalid metric assertions: {}", e))?;
+    let samples = parse(body)?;
+    assertions.iter().try_for_each(|a| a.evaluate(&samples))
+}
+
+pub fn validate_assertions(assertions: &[MetricAssertion]) -> Result<()> {
+    if assertions.is_empty() {
+        return Err(Error::validation("metric_assertions needs at least one assertion"));
+    }
+    Ok(())
+}
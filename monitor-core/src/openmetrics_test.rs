@@ -0,0 +1,73 @@
+#[cfg(test)]
+mod openmetrics_tests {
+    use crate::openmetrics::*;
+    use serde_json::json;
+
+    const EXPOSITION: &str = r#"# HELP up Whether the target is up.
+# TYPE up gauge
+up 1
+# TYPE queue_depth gauge
+queue_depth{queue="jobs"} 42
+queue_depth{queue="mail",region="eu"} 1500 1700000000000
+http_requests_total{path="/a \"b\"",code="200"} 17 # {trace_id="abc"} 1.0
+temperature NaN
+latency_bucket{le="+Inf"} +Inf
+# EOF
+"#;
+
+    #[test]
+    fn test_parses_samples_ignoring_comments_timestamps_and_exemplars() {
+        let samples = parse(EXPOSITION).unwrap();
+        assert_eq!(samples.len(), 6);
+        assert_eq!(samples[0].name, "up");
+        assert_eq!(samples[0].value, 1.0);
+        assert_eq!(samples[2].value, 1500.0);
+        assert_eq!(samples[2].labels.get("region").map(String::as_str), Some("eu"));
+        assert_eq!(samples[3].labels.get("path").map(String::as_str), Some("/a \"b\""));
+        assert_eq!(samples[3].value, 17.0);
+        assert!(samples[4].value.is_nan());
+        assert_eq!(samples[5].value, f64::INFINITY);
+
+        assert!(parse("up").is_err());
+        assert!(parse("up{job=\"x} 1").is_err());
+    }
+
+    #[test]
+    fn test_assertion_expressions_round_trip() {
+        let assertion: MetricAssertion = "queue_depth{queue=\"jobs\"} <= 1000".parse().unwrap();
+        assert_eq!(assertion.metric, "queue_depth");
+        assert_eq!(assertion.labels.get("queue").map(String::as_str), Some("jobs"));
+        assert_eq!(assertion.op, Comparison::Le);
+        assert_eq!(assertion.value, 1000.0);
+        assert_eq!(assertion.to_string(), "queue_depth{queue=\"jobs\"} <= 1000");
+
+        let parsed: Vec<MetricAssertion> = serde_json::from_value(json!(["up == 1"])).unwrap();
+        assert_eq!(serde_json::to_value(&parsed).unwrap(), json!(["up == 1"]));
+
+        assert!("up = 1".parse::<MetricAssertion>().is_err());
+        assert!("up == one".parse::<MetricAssertion>().is_err());
+        assert!("== 1".parse::<MetricAssertion>().is_err());
+    }
+
+    #[test]
+    fn test_every_matching_series_must_hold() {
+        let samples = parse(EXPOSITION).unwrap();
+        let check = |expression: &str| expression.parse::<MetricAssertion>().unwrap().evaluate(&samples);
+
+        assert!(check("up == 1").is_ok());
+        assert!(check("queue_depth{queue=\"jobs\"} < 1000").is_ok());
+        assert_eq!(
+            check("queue_depth < 1000").unwrap_err(),
+            "queue_depth{queue=\"mail\",region=\"eu\"} is 1500, expected < 1000"
+        );
+        assert_eq!(check("missing_metric > 0").unwrap_err(), "missing_metric not found");
+    }
+
+    #[test]
+    fn test_check_reports_the_first_failure() {
+        assert!(check(&json!(["up == 1", "queue_depth{queue=\"jobs\"} > 0"]), EXPOSITION).is_ok());
+        let error = check(&json!(["up == 1", "up != 1"]), EXPOSITION).unwrap_err();
+        assert_eq!(error, "up is 1, expected != 1");
+        assert!(check(&json!(["up == 1"]), "<html>not metrics</html>").is_err());
+    }
+}
@@ -0,0 +1,71 @@
+use reqwest::Client;
+use serde::Serialize;
+use serde_json::json;
+use std::time::Duration;
+use uuid::Uuid;
+use crate::{alerts::AlertNotification, error::Result, Error};
+
+pub const EVENTS_URL: &str = "https://events.pagerduty.com/v2/enqueue";
+
+pub const SEVERITIES: &[&str] = &["critical", "error", "warning", "info"];
+pub const DEFAULT_SEVERITY: &str = "critical";
+
+/// PagerDuty caps the summary at 1024 characters.
+const MAX_SUMMARY: usize = 1024;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EventAction {
+    Trigger,
+    Acknowledge,
+    Resolve,
+}
+
+/// One incident per monitor and alert type, so a monitor's recovery
+/// resolves the incident its failure opened.
+pub fn dedup_key(monitor_id: Uuid, alert_type: &str) -> String {
+    format!("monitor/{}/{}", monitor_id, alert_type)
+}
+
+/// An Events v2 body. Acknowledge and resolve events only need the key.
+pub fn event(
+    routing_key: &str,
+    action: EventAction,
+    severity: &str,
+    notification: &AlertNotification,
+) -> serde_json::Value {
+    let mut event = json!({
+        "routing_key": routing_key,
+        "event_action": action,
+        "dedup_key": dedup_key(notification.monitor_id, &notification.alert_type),
+    });
+    if action == EventAction::Trigger {
+        let summary = format!("{}: {}", notification.subject(), notification.summary);
+        event["payload"] = json!({
+            "summary": summary.chars().take(MAX_SUMMARY).collect::<String>(),
+            "source": notification.endpoint,
+            "severity": severity,
+            "timestamp": notification.at.to_rfc3339(),
+            "component": notification.monitor_name,
+            "group": notification.alert_type,
+            "custom_details": {
+                "monitor_id": notification.monitor_id,
+                "status": notification.status,
+                "response_time": notification.response_time,
+                "error_message": notification.error_message,
+            },
+        });
+    }
+    event
+}
+
+pub async fn enqueue(client: &Client, event: &serde_json::Value) -> Result<()> {
+    let response = client.post(EVENTS_URL).json(event).timeout(REQUEST_TIMEOUT).send().await?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(Error::internal(format!("PagerDuty rejected the event: HTTP {} {}", status, body)));
+    }
+    Ok(())
+}
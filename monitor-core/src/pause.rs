@@ -0,0 +1,125 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use uuid::Uuid;
+use crate::{audit, db::DatabasePool, error::Result, models::Monitor, Error};
+
+const MAX_REASON_LEN: usize = 500;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PauseRequest {
+    pub reason: Option<String>,
+    /// Re-enable the monitor automatically at this time
+    pub resume_at: Option<DateTime<Utc>>,
+}
+
+impl PauseRequest {
+    pub fn validate(&self, now: DateTime<Utc>) -> Result<()> {
+        if self.reason.as_ref().is_some_and(|r| r.chars().count() > MAX_REASON_LEN) {
+            return Err(Error::validation(format!(
+                "reason must be at most {} characters",
+                MAX_REASON_LEN
+            )));
+        }
+        if self.resume_at.is_some_and(|at| at <= now) {
+            return Err(Error::validation("resume_at must be in the future"));
+        }
+        Ok(())
+    }
+
+    /// Blank reasons are stored as no reason.
+    pub fn reason(&self) -> Option<&str> {
+        self.reason.as_deref().map(str::trim).filter(|r| !r.is_empty())
+    }
+}
+
+/// Why and until when a monitor is paused, as shown by the status API.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PauseState {
+    pub reason: Option<String>,
+    pub paused_by: Option<Uuid>,
+    pub paused_at: Option<DateTime<Utc>>,
+    pub resume_at: Option<DateTime<Utc>>,
+}
+
+impl PauseState {
+    /// `None` while the monitor is enabled.
+    pub fn of(monitor: &Monitor) -> Option<Self> {
+        (!monitor.enabled).then(|| Self {
+            reason: monitor.paused_reason.clone(),
+            paused_by: monitor.paused_by,
+            paused_at: monitor.paused_at,
+            resume_at: monitor.resume_at,
+        })
+    }
+}
+
+/// Disables a monitor and records who paused it. `now` is what `resume_at`
+/// must be after.
+pub async fn pause(
+    db: &DatabasePool,
+    monitor_id: Uuid,
+    actor_id: Uuid,
+    request: &PauseRequest,
+    now: DateTime<Utc>,
+) -> Result<()> {
+    request.validate(now)?;
+    sqlx::query(
+        r#"
+        UPDATE monitors
+        SET enabled = false, paused_reason = $2, paused_by = $3, paused_at = NOW(), resume_at = $4, updated_at = NOW()
+        WHERE id = $1
+        "#,
+    )
+    .bind(monitor_id)
+    .bind(request.reason())
+    .bind(actor_id)
+    .bind(request.resume_at)
+    .execute(db)
+    .await?;
+
+    audit::record(
+        db,
+        Some(actor_id),
+        audit::ACTION_MONITOR_PAUSED,
+        Some(monitor_id),
+        json!({ "reason": request.reason(), "resume_at": request.resume_at }),
+    )
+    .await
+}
+
+/// Re-enables a monitor and clears its pause details. `actor_id` is `None`
+/// for automatic resumes.
+pub async fn resume(db: &DatabasePool, monitor_id: Uuid, actor_id: Option<Uuid>) -> Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE monitors
+        SET enabled = true, paused_reason = NULL, paused_by = NULL, paused_at = NULL, resume_at = NULL, updated_at = NOW()
+        WHERE id = $1
+        "#,
+    )
+    .bind(monitor_id)
+    .execute(db)
+    .await?;
+
+    audit::record(
+        db,
+        actor_id,
+        audit::ACTION_MONITOR_RESUMED,
+        Some(monitor_id),
+        json!({ "automatic": actor_id.is_none() }),
+    )
+    .await
+}
+
+/// Resumes every paused monitor whose `resume_at` has passed and returns their ids.
+pub async fn resume_due(db: &DatabasePool, now: DateTime<Utc>) -> Result<Vec<Uuid>> {
+    let due: Vec<Uuid> = sqlx::query_scalar("SELECT id FROM monitors WHERE enabled = false AND resume_at <= $1")
+        .bind(now)
+        .fetch_all(db)
+        .await?;
+    for monitor_id in &due {
+        resume(db, *monitor_id, None).await?;
+    }
+    Ok(due)
+}
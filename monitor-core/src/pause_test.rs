@@ -0,0 +1,96 @@
+#[cfg(test)]
+mod pause_tests {
+    use crate::{durations::Seconds, http_method::HttpMethod, models::Monitor, pause::*};
+    use chrono::{Duration, TimeZone, Utc};
+    use uuid::Uuid;
+
+    fn monitor(enabled: bool) -> Monitor {
+        let now = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        Monitor {
+            id: Uuid::new_v4(),
+            name: "api".to_string(),
+            endpoint: "https://example.com/health".to_string(),
+            method: HttpMethod::Get,
+            headers: None,
+            body: None,
+            expected_status: 200.into(),
+            timeout: None,
+            interval: Seconds::new(60),
+            script: None,
+            pre_request_script: None,
+            enabled,
+            paused_reason: Some("Planned maintenance".to_string()),
+            paused_by: Some(Uuid::nil()),
+            paused_at: Some(now),
+            resume_at: Some(now + Duration::hours(2)),
+            tags: vec![],
+            owner_id: None,
+            team_id: None,
+            system_key: None,
+            retries: None,
+            retry_delay_ms: None,
+            schedule_cron: None,
+            notification_channels: None,
+            script_profile: None,
+            credentials: None,
+            steps: None,
+            metric_assertions: None,
+            broker_check: None,
+            storage_check: None,
+            ntp_check: None,
+            snmp_check: None,
+            exec_check: None,
+            tcp_check: None,
+            dns_check: None,
+            tls_check: None,
+            websocket_check: None,
+            mail_check: None,
+            database_check: None,
+            heartbeat_check: None,
+            expected_body_contains: None,
+            expected_body_regex: None,
+            bypass_dns_cache: false,
+            connect_timeout_ms: None,
+            tls_timeout_ms: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn test_validate_pause_request() {
+        let now = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+
+        assert!(PauseRequest::default().validate(now).is_ok());
+        let request = PauseRequest {
+            reason: Some("Deploy window".to_string()),
+            resume_at: Some(now + Duration::minutes(30)),
+        };
+        assert!(request.validate(now).is_ok());
+
+        let past = PauseRequest { reason: None, resume_at: Some(now) };
+        assert!(past.validate(now).is_err());
+
+        let long = PauseRequest { reason: Some("x".repeat(501)), resume_at: None };
+        assert!(long.validate(now).is_err());
+    }
+
+    #[test]
+    fn test_blank_reason_is_dropped() {
+        let request = PauseRequest { reason: Some("   ".to_string()), resume_at: None };
+        assert_eq!(request.reason(), None);
+
+        let request = PauseRequest { reason: Some(" Vendor outage ".to_string()), resume_at: None };
+        assert_eq!(request.reason(), Some("Vendor outage"));
+    }
+
+    #[test]
+    fn test_pause_state_only_for_disabled_monitors() {
+        assert_eq!(PauseState::of(&monitor(true)), None);
+
+        let state = PauseState::of(&monitor(false)).unwrap();
+        assert_eq!(state.reason.as_deref(), Some("Planned maintenance"));
+        assert_eq!(state.paused_by, Some(Uuid::nil()));
+        assert!(state.resume_at.is_some());
+    }
+}
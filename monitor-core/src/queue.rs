@@ -0,0 +1,210 @@
+use chrono::{DateTime, Duration, Utc};
+use croner::Cron;
+use sqlx::FromRow;
+use uuid::Uuid;
+use crate::{db::DatabasePool, durations::Seconds, error::Result, Error};
+
+#[derive(Debug, Clone, FromRow)]
+pub struct QueuedCheck {
+    pub monitor_id: Uuid,
+    pub due_at: DateTime<Utc>,
+}
+
+impl QueuedCheck {
+    /// How late the check is starting relative to its due time.
+    pub fn lag_ms(&self, started: DateTime<Utc>) -> i64 {
+        (started - self.due_at).num_milliseconds().max(0)
+    }
+}
+
+/// The first tick after `now` on the monitor's schedule. Ticks missed while no
+/// worker was running collapse into the check that has just run.
+pub fn next_due(due_at: DateTime<Utc>, interval: Seconds, now: DateTime<Utc>) -> DateTime<Utc> {
+    let step = interval.max(Seconds::new(1)).as_chrono();
+    let next = due_at + step;
+    if next > now {
+        return next;
+    }
+    let missed = (now - next).num_milliseconds() / step.num_milliseconds() + 1;
+    next + step * missed as i32
+}
+
+/// Jitter beyond this share of the interval could reorder consecutive runs
+pub const MAX_JITTER_PERCENT: u32 = 50;
+
+/// `next` moved by `draw` (in `[-1, 1]`) times `percent` of the interval.
+/// Falls back to `next` when the move would put the run in the past.
+pub fn jittered(next: DateTime<Utc>, interval: Seconds, percent: u32, draw: f64, now: DateTime<Utc>) -> DateTime<Utc> {
+    let spread_ms = interval.get().max(1) as f64 * 10.0 * percent.min(MAX_JITTER_PERCENT) as f64;
+    let moved = next + Duration::milliseconds((spread_ms * draw.clamp(-1.0, 1.0)) as i64);
+    if moved > now { moved } else { next }
+}
+
+/// Checks a monitor's `schedule_cron`: five fields, or six with seconds
+/// first, that name at least one future time.
+pub fn validate_cron(expression: &str) -> Result<()> {
+    let cron = parse_cron(expression)
+        .map_err(|e| Error::validation(format!("Invalid schedule_cron '{}': {}", expression, e)))?;
+    if cron.find_next_occurrence(&Utc::now(), false).is_err() {
+        return Err(Error::validation(format!("schedule_cron '{}' never runs", expression)));
+    }
+    Ok(())
+}
+
+/// The first time after `now` that `expression` names, in UTC. `None` when
+/// it does not parse or never runs again.
+pub fn next_cron_due(expression: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    parse_cron(expression).ok()?.find_next_occurrence(&now, false).ok()
+}
+
+/// Whether `at` is one of the times `expression` names, to the second.
+/// Expressions that do not parse match every time.
+pub fn cron_matches(expression: &str, at: DateTime<Utc>) -> bool {
+    parse_cron(expression)
+        .and_then(|cron| cron.is_time_matching(&at))
+        .unwrap_or(true)
+}
+
+fn parse_cron(expression: &str) -> std::result::Result<Cron, croner::errors::CronError> {
+    Cron::new(expression).with_seconds_optional().parse()
+}
+
+/// Postgres channel the `monitors_notify_change` trigger publishes the ids of
+/// created and edited monitors on
+pub const CHANGE_CHANNEL: &str = "monitor_changes";
+
+/// Queues monitors that are enabled (or paused with a resume time) and drops
+/// rows for the rest. Newly queued monitors are due at a random point within
+/// `spread_secs` (or their interval, if shorter) so a full queue does not
+/// come due at once, and ones whose interval was shortened are due within
+/// the new interval.
+pub async fn sync(db: &DatabasePool, spread_secs: i64) -> Result<u64> {
+    reconcile(db, None, spread_secs).await
+}
+
+/// `sync` for one monitor, right after it was created or edited. A new
+/// monitor is due immediately, or at the next time its `schedule_cron`
+/// names.
+pub async fn sync_monitor(db: &DatabasePool, monitor_id: Uuid) -> Result<u64> {
+    let added = reconcile(db, Some(monitor_id), 0).await?;
+    let schedule_cron: Option<String> = sqlx::query_scalar("SELECT schedule_cron FROM monitors WHERE id = $1")
+        .bind(monitor_id)
+        .fetch_optional(db)
+        .await?
+        .flatten();
+    if let Some(next_due) = schedule_cron.and_then(|expression| next_cron_due(&expression, Utc::now())) {
+        sqlx::query(
+            r#"
+            UPDATE monitor_check_queue SET due_at = $2
+            WHERE monitor_id = $1 AND (claimed_until IS NULL OR claimed_until < NOW())
+            "#,
+        )
+        .bind(monitor_id)
+        .bind(next_due)
+        .execute(db)
+        .await?;
+    }
+    Ok(added)
+}
+
+async fn reconcile(db: &DatabasePool, monitor_id: Option<Uuid>, spread_secs: i64) -> Result<u64> {
+    let added = sqlx::query(
+        r#"
+        INSERT INTO monitor_check_queue (monitor_id, due_at)
+        SELECT id, NOW() + random() * make_interval(secs => LEAST(interval, $2)) FROM monitors
+        WHERE (enabled = true OR resume_at IS NOT NULL) AND ($1::uuid IS NULL OR id = $1)
+        ON CONFLICT (monitor_id) DO NOTHING
+        "#,
+    )
+    .bind(monitor_id)
+    .bind(spread_secs.max(0))
+    .execute(db)
+    .await?
+    .rows_affected();
+
+    // Claimed rows get their next due time when the running check completes
+    sqlx::query(
+        r#"
+        UPDATE monitor_check_queue q
+        SET due_at = NOW() + make_interval(secs => m.interval)
+        FROM monitors m
+        WHERE m.id = q.monitor_id AND ($1::uuid IS NULL OR m.id = $1) AND m.schedule_cron IS NULL
+          AND (q.claimed_until IS NULL OR q.claimed_until < NOW())
+          AND q.due_at > NOW() + make_interval(secs => m.interval)
+        "#,
+    )
+    .bind(monitor_id)
+    .execute(db)
+    .await?;
+
+    sqlx::query(
+        r#"
+        DELETE FROM monitor_check_queue q
+        USING monitors m
+        WHERE m.id = q.monitor_id AND m.enabled = false AND m.resume_at IS NULL
+          AND ($1::uuid IS NULL OR m.id = $1)
+        "#,
+    )
+    .bind(monitor_id)
+    .execute(db)
+    .await?;
+
+    Ok(added)
+}
+
+/// Claims up to `limit` due checks for `worker`, most overdue first. Rows
+/// locked or claimed by other workers are skipped.
+pub async fn claim(db: &DatabasePool, worker: &str, limit: i64, lease: Duration) -> Result<Vec<QueuedCheck>> {
+    let checks = sqlx::query_as::<_, QueuedCheck>(
+        r#"
+        WITH due AS (
+            SELECT monitor_id FROM monitor_check_queue
+            WHERE due_at <= NOW() AND (claimed_until IS NULL OR claimed_until < NOW())
+            ORDER BY due_at
+            LIMIT $1
+            FOR UPDATE SKIP LOCKED
+        )
+        UPDATE monitor_check_queue q
+        SET claimed_by = $2, claimed_until = NOW() + $3
+        FROM due
+        WHERE q.monitor_id = due.monitor_id
+        RETURNING q.monitor_id, q.due_at
+        "#,
+    )
+    .bind(limit)
+    .bind(worker)
+    .bind(lease)
+    .fetch_all(db)
+    .await?;
+    Ok(checks)
+}
+
+/// Releases a claim and schedules the monitor's next run.
+pub async fn complete(db: &DatabasePool, monitor_id: Uuid, worker: &str, next_due: DateTime<Utc>) -> Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE monitor_check_queue
+        SET due_at = $3, claimed_by = NULL, claimed_until = NULL
+        WHERE monitor_id = $1 AND claimed_by = $2
+        "#,
+    )
+    .bind(monitor_id)
+    .bind(worker)
+    .bind(next_due)
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/// Hands back every claim held by `worker`, e.g. on shutdown, so the checks
+/// run on the next available worker without waiting for the lease.
+pub async fn release_all(db: &DatabasePool, worker: &str) -> Result<u64> {
+    let released = sqlx::query(
+        "UPDATE monitor_check_queue SET claimed_by = NULL, claimed_until = NULL WHERE claimed_by = $1",
+    )
+    .bind(worker)
+    .execute(db)
+    .await?
+    .rows_affected();
+    Ok(released)
+}
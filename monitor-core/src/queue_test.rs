@@ -0,0 +1,84 @@
+#[cfg(test)]
+mod queue_tests {
+    use crate::{durations::Seconds, queue::*};
+    use chrono::{Duration, TimeZone, Utc};
+    use uuid::Uuid;
+
+    #[test]
+    fn next_due_advances_by_interval() {
+        let due = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let now = due + Duration::seconds(2);
+        assert_eq!(next_due(due, Seconds::new(30), now), due + Duration::seconds(30));
+    }
+
+    #[test]
+    fn next_due_skips_ticks_missed_during_downtime() {
+        let due = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let now = due + Duration::seconds(95);
+        assert_eq!(next_due(due, Seconds::new(30), now), due + Duration::seconds(120));
+    }
+
+    #[test]
+    fn next_due_is_strictly_after_now() {
+        let due = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let now = due + Duration::seconds(60);
+        assert_eq!(next_due(due, Seconds::new(30), now), due + Duration::seconds(90));
+    }
+
+    #[test]
+    fn next_due_handles_intervals_of_a_minute_or_more() {
+        let due = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let now = due + Duration::seconds(1);
+        assert_eq!(next_due(due, Seconds::new(60), now), due + Duration::minutes(1));
+        assert_eq!(next_due(due, Seconds::new(90), now), due + Duration::seconds(90));
+        assert_eq!(next_due(due, Seconds::new(3600), now), due + Duration::hours(1));
+        assert_eq!(next_due(due, Seconds::new(86_400), due + Duration::hours(30)), due + Duration::days(2));
+    }
+
+    #[test]
+    fn lag_is_measured_from_due_time() {
+        let due = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let check = QueuedCheck { monitor_id: Uuid::new_v4(), due_at: due };
+        assert_eq!(check.lag_ms(due + Duration::milliseconds(1500)), 1500);
+        assert_eq!(check.lag_ms(due - Duration::seconds(1)), 0);
+    }
+
+    #[test]
+    fn jitter_moves_within_the_share_of_the_interval() {
+        let next = Utc.with_ymd_and_hms(2024, 3, 1, 12, 1, 0).unwrap();
+        let now = next - Duration::seconds(50);
+        assert_eq!(jittered(next, Seconds::new(60), 10, 1.0, now), next + Duration::seconds(6));
+        assert_eq!(jittered(next, Seconds::new(60), 10, -0.5, now), next - Duration::seconds(3));
+        assert_eq!(jittered(next, Seconds::new(60), 0, 1.0, now), next);
+        // Capped at half the interval
+        assert_eq!(jittered(next, Seconds::new(60), 200, 1.0, now), next + Duration::seconds(30));
+    }
+
+    #[test]
+    fn jitter_never_moves_a_run_into_the_past() {
+        let next = Utc.with_ymd_and_hms(2024, 3, 1, 12, 1, 0).unwrap();
+        let now = next - Duration::seconds(2);
+        assert_eq!(jittered(next, Seconds::new(60), 10, -1.0, now), next);
+    }
+
+    #[test]
+    fn cron_schedules_name_the_next_run() {
+        let now = Utc.with_ymd_and_hms(2024, 3, 1, 17, 58, 30).unwrap(); // a Friday
+        let business_hours = "*/5 9-17 * * 1-5";
+        assert!(validate_cron(business_hours).is_ok());
+        assert_eq!(next_cron_due(business_hours, now), Some(Utc.with_ymd_and_hms(2024, 3, 4, 9, 0, 0).unwrap()));
+        assert!(cron_matches(business_hours, Utc.with_ymd_and_hms(2024, 3, 1, 9, 5, 0).unwrap()));
+        assert!(!cron_matches(business_hours, now));
+
+        // Six fields start with seconds
+        assert_eq!(next_cron_due("30 * * * * *", now), Some(now + Duration::minutes(1)));
+    }
+
+    #[test]
+    fn invalid_cron_schedules_are_rejected() {
+        assert!(validate_cron("every five minutes").is_err());
+        assert!(validate_cron("61 * * * *").is_err());
+        assert!(validate_cron("").is_err());
+        assert_eq!(next_cron_due("not cron", Utc::now()), None);
+    }
+}
@@ -0,0 +1,8 @@
+use uuid::Uuid;
+
+/// A uniform draw in `[0, 1)` from the last 48 bits of a v4 UUID, which are
+/// all random. Good enough for jitter and sampling, not for secrets.
+pub fn uniform() -> f64 {
+    let bits = Uuid::new_v4().as_u128() & 0xFFFF_FFFF_FFFF;
+    bits as f64 / (1u64 << 48) as f64
+}
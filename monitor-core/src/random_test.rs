@@ -0,0 +1,12 @@
+#[cfg(test)]
+mod random_tests {
+    use crate::random::*;
+
+    #[test]
+    fn test_uniform_stays_in_range_and_varies() {
+        let draws: Vec<f64> = (0..1000).map(|_| uniform()).collect();
+        assert!(draws.iter().all(|draw| (0.0..1.0).contains(draw)));
+        assert!(draws.iter().any(|draw| *draw < 0.5));
+        assert!(draws.iter().any(|draw| *draw >= 0.5));
+    }
+}
@@ -0,0 +1,184 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::{Arc, RwLock};
+use crate::{db::DatabasePool, error::Result, models::MonitorResult, runtime_settings, Error};
+
+/// Redaction rules applied by the scheduler, set through the API as a JSON array.
+pub const RESULT_REDACTION_RULES: &str = "results.redaction_rules";
+
+/// Replaces redacted text unless a rule names its own replacement
+pub const REDACTED: &str = "[REDACTED]";
+
+const MAX_RULES: usize = 100;
+
+/// Hides matching parts of check results before they are stored or sent in
+/// notifications. Exactly one of `pattern` and `json_pointer` is set.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RedactionRule {
+    /// Regular expression replaced in response bodies, response header
+    /// values and error messages; `replacement` may refer to its groups as `$1`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    /// Value replaced in JSON response bodies, e.g. `/user/email`; a `*`
+    /// segment matches every key or array index
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub json_pointer: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replacement: Option<String>,
+}
+
+#[derive(Debug)]
+enum Matcher {
+    Pattern(Regex),
+    Pointer(Vec<String>),
+}
+
+/// Compiled redaction rules.
+#[derive(Debug, Default)]
+pub struct Redactor {
+    rules: Vec<(Matcher, String)>,
+}
+
+impl Redactor {
+    pub fn new(rules: &[RedactionRule]) -> Result<Self> {
+        if rules.len() > MAX_RULES {
+            return Err(Error::validation(format!("At most {} redaction rules are allowed", MAX_RULES)));
+        }
+        let mut compiled = Vec::with_capacity(rules.len());
+        for rule in rules {
+            let matcher = match (&rule.pattern, &rule.json_pointer) {
+                (Some(pattern), None) => Regex::new(pattern)
+                    .map(Matcher::Pattern)
+                    .map_err(|e| Error::validation(format!("Invalid redaction pattern '{}': {}", pattern, e)))?,
+                (None, Some(pointer)) => Matcher::Pointer(parse_pointer(pointer)?),
+                _ => return Err(Error::validation("Each redaction rule needs either a pattern or a json_pointer")),
+            };
+            let replacement = rule.replacement.clone().unwrap_or_else(|| REDACTED.to_string());
+            compiled.push((matcher, replacement));
+        }
+        Ok(Self { rules: compiled })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// `text` with every pattern's matches replaced.
+    pub fn redact_text(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        for (matcher, replacement) in &self.rules {
+            if let Matcher::Pattern(regex) = matcher {
+                text = regex.replace_all(&text, replacement.as_str()).into_owned();
+            }
+        }
+        text
+    }
+
+    /// A response body with the JSON pointers replaced, when it is JSON, and
+    /// then the patterns. Bodies are only re-serialized when a pointer matched.
+    pub fn redact_body(&self, body: &str) -> String {
+        let pointers: Vec<_> = self
+            .rules
+            .iter()
+            .filter_map(|(matcher, replacement)| match matcher {
+                Matcher::Pointer(segments) => Some((segments, replacement)),
+                Matcher::Pattern(_) => None,
+            })
+            .collect();
+        if !pointers.is_empty()
+            && let Ok(mut json) = serde_json::from_str::<Value>(body)
+        {
+            let mut changed = false;
+            for (segments, replacement) in pointers {
+                changed |= replace_at(&mut json, segments, replacement);
+            }
+            if changed {
+                return self.redact_text(&json.to_string());
+            }
+        }
+        self.redact_text(body)
+    }
+
+    /// Redacts the parts of a result that can carry response data.
+    pub fn apply(&self, result: &mut MonitorResult) {
+        if self.is_empty() {
+            return;
+        }
+        if let Some(body) = &result.response_body {
+            result.response_body = Some(self.redact_body(body));
+        }
+        if let Some(headers) = &mut result.response_headers {
+            for value in headers.values_mut() {
+                *value = self.redact_text(value);
+            }
+        }
+        for text in [&mut result.error_message, &mut result.error_hint].into_iter().flatten() {
+            *text = self.redact_text(text);
+        }
+    }
+}
+
+/// RFC 6901 segments of `pointer`, with `~1` and `~0` unescaped.
+fn parse_pointer(pointer: &str) -> Result<Vec<String>> {
+    let Some(path) = pointer.strip_prefix('/') else {
+        return Err(Error::validation(format!("json_pointer '{}' must start with '/'", pointer)));
+    };
+    Ok(path.split('/').map(|segment| segment.replace("~1", "/").replace("~0", "~")).collect())
+}
+
+fn replace_at(value: &mut Value, segments: &[String], replacement: &str) -> bool {
+    let Some((segment, rest)) = segments.split_first() else {
+        *value = Value::String(replacement.to_string());
+        return true;
+    };
+    match value {
+        Value::Object(map) if segment == "*" => {
+            map.values_mut().fold(false, |changed, child| replace_at(child, rest, replacement) | changed)
+        }
+        Value::Object(map) => map.get_mut(segment).is_some_and(|child| replace_at(child, rest, replacement)),
+        Value::Array(items) if segment == "*" => {
+            items.iter_mut().fold(false, |changed, child| replace_at(child, rest, replacement) | changed)
+        }
+        Value::Array(items) => segment
+            .parse::<usize>()
+            .ok()
+            .and_then(|index| items.get_mut(index))
+            .is_some_and(|child| replace_at(child, rest, replacement)),
+        _ => false,
+    }
+}
+
+pub async fn load_rules(db: &DatabasePool) -> Result<Vec<RedactionRule>> {
+    match runtime_settings::get(db, RESULT_REDACTION_RULES).await? {
+        Some(json) => Ok(serde_json::from_str(&json)?),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Stores `rules` after checking they compile; an empty list removes them.
+pub async fn save_rules(db: &DatabasePool, rules: &[RedactionRule]) -> Result<()> {
+    Redactor::new(rules)?;
+    if rules.is_empty() {
+        return runtime_settings::delete(db, RESULT_REDACTION_RULES).await;
+    }
+    runtime_settings::set(db, RESULT_REDACTION_RULES, &serde_json::to_string(rules)?).await
+}
+
+/// The rules a process applies, reloaded from the database as they change.
+#[derive(Debug, Clone, Default)]
+pub struct LiveRedactor(Arc<RwLock<Arc<Redactor>>>);
+
+impl LiveRedactor {
+    pub fn current(&self) -> Arc<Redactor> {
+        self.0.read().unwrap().clone()
+    }
+
+    /// Loads the stored rules. The current ones stay in place when the
+    /// stored ones cannot be read or compiled.
+    pub async fn refresh(&self, db: &DatabasePool) -> Result<()> {
+        let redactor = Redactor::new(&load_rules(db).await?)?;
+        *self.0.write().unwrap() = Arc::new(redactor);
+        Ok(())
+    }
+}
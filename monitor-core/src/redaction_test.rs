@@ -0,0 +1,76 @@
+#[cfg(test)]
+mod redaction_tests {
+    use crate::{models::MonitorResult, redaction::*};
+    use chrono::Utc;
+    use std::collections::BTreeMap;
+    use uuid::Uuid;
+
+    fn redactor(rules: serde_json::Value) -> Redactor {
+        Redactor::new(&serde_json::from_value::<Vec<RedactionRule>>(rules).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn patterns_replace_matches_in_text() {
+        let redactor = redactor(serde_json::json!([
+            { "pattern": r"[\w.+-]+@[\w-]+\.[\w.]+" },
+            { "pattern": r"(token=)\w+", "replacement": "${1}***" }
+        ]));
+        assert_eq!(
+            redactor.redact_text("sent to jane.doe@example.com with token=abc123"),
+            "sent to [REDACTED] with token=***"
+        );
+        assert_eq!(redactor.redact_text("nothing here"), "nothing here");
+    }
+
+    #[test]
+    fn pointers_replace_json_values() {
+        let redactor = redactor(serde_json::json!([
+            { "json_pointer": "/user/email" },
+            { "json_pointer": "/sessions/*/token", "replacement": "hidden" }
+        ]));
+        let body = r#"{"user": {"email": "a@b.c", "id": 7}, "sessions": [{"token": "x"}, {"token": "y"}]}"#;
+        let redacted: serde_json::Value = serde_json::from_str(&redactor.redact_body(body)).unwrap();
+        assert_eq!(redacted["user"]["email"], REDACTED);
+        assert_eq!(redacted["user"]["id"], 7);
+        assert_eq!(redacted["sessions"][1]["token"], "hidden");
+
+        // Bodies the pointers do not reach are kept as they were
+        assert_eq!(redactor.redact_body("{ \"other\": 1 }"), "{ \"other\": 1 }");
+        assert_eq!(redactor.redact_body("<html>a@b.c</html>"), "<html>a@b.c</html>");
+    }
+
+    #[test]
+    fn results_are_redacted_where_response_data_lands() {
+        let redactor = redactor(serde_json::json!([{ "pattern": "secret-\\d+" }]));
+        let mut result = MonitorResult {
+            id: Uuid::new_v4(),
+            monitor_id: Uuid::new_v4(),
+            status: "failure".to_string(),
+            response_time: 12,
+            response_code: Some(500),
+            response_body: Some("failed for secret-42".to_string()),
+            response_headers: Some(BTreeMap::from([("x-api-key".to_string(), "secret-7".to_string())])),
+            error_message: Some("Body mentions secret-42".to_string()),
+            error_category: Some("http_status".to_string()),
+            error_hint: None,
+            checked_at: Utc::now(),
+            clock_skew_ms: None,
+            schedule_lag_ms: None,
+            attempts: 1,
+        };
+        redactor.apply(&mut result);
+        assert_eq!(result.response_body.as_deref(), Some("failed for [REDACTED]"));
+        assert_eq!(result.response_headers.unwrap()["x-api-key"], REDACTED);
+        assert_eq!(result.error_message.as_deref(), Some("Body mentions [REDACTED]"));
+    }
+
+    #[test]
+    fn invalid_rules_are_rejected() {
+        let rules = |value: serde_json::Value| serde_json::from_value::<Vec<RedactionRule>>(value).unwrap();
+        assert!(Redactor::new(&rules(serde_json::json!([{ "pattern": "(" }]))).is_err());
+        assert!(Redactor::new(&rules(serde_json::json!([{ "json_pointer": "user/email" }]))).is_err());
+        assert!(Redactor::new(&rules(serde_json::json!([{}]))).is_err());
+        assert!(Redactor::new(&rules(serde_json::json!([{ "pattern": "a", "json_pointer": "/a" }]))).is_err());
+        assert!(Redactor::new(&[]).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,217 @@
+use notify::{RecursiveMode, Watcher};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::{
+    path::Path,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+use tracing::{error, info, warn};
+use crate::{config::Config, error::Result, logging, Error};
+
+/// Settings that are read on every use and can therefore change without a restart.
+/// Everything else (listen address, database, keys, feature flags) is fixed at startup.
+pub const RELOADABLE_KEYS: &[&str] = &[
+    "logging.level",
+    "retention.result_days",
+    "scripting.security_profile",
+    "scripting.timeout",
+    "encryption.rotation_batch_size",
+];
+
+/// Keys whose values are replaced with `***` in config snapshots.
+pub const SECRET_KEYS: &[&str] = &[
+    "database.password",
+    "auth.jwt_secret",
+    "auth.provisioning_token",
+    "encryption.keys",
+    "error_reporting.sentry_dsn",
+    "error_reporting.webhook_url",
+    "discovery.api_token",
+    "discovery.consul.token",
+    "alerting.smtp.password",
+    "imports.aws.secret_access_key",
+    "imports.aws.session_token",
+];
+
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReloadOutcome {
+    pub applied: Vec<String>,
+    /// Changed keys that only take effect after a restart and were ignored.
+    pub rejected: Vec<String>,
+}
+
+/// Shared, swappable configuration. Readers take a cheap snapshot with [`LiveConfig::current`].
+#[derive(Debug, Clone)]
+pub struct LiveConfig {
+    inner: Arc<RwLock<Arc<Config>>>,
+}
+
+impl LiveConfig {
+    pub fn new(config: Config) -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(Arc::new(config))),
+        }
+    }
+
+    pub fn current(&self) -> Arc<Config> {
+        self.inner.read().unwrap().clone()
+    }
+
+    /// Applies the reloadable subset of `new` and reports what changed.
+    pub fn apply(&self, new: &Config) -> Result<ReloadOutcome> {
+        let current = self.current();
+        let (merged, outcome) = merge_reloadable(&current, new)?;
+
+        if outcome.applied.iter().any(|k| k == "logging.level") {
+            logging::set_filter(&merged.logging.level)?;
+        }
+        if !outcome.applied.is_empty() {
+            *self.inner.write().unwrap() = Arc::new(merged);
+        }
+
+        Ok(outcome)
+    }
+
+    /// Current configuration as JSON with secrets redacted.
+    pub fn snapshot(&self) -> Result<Value> {
+        redacted(&self.current())
+    }
+}
+
+fn flatten(prefix: &str, value: &Value, out: &mut Map<String, Value>) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                let key = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+                flatten(&key, value, out);
+            }
+        }
+        other => {
+            out.insert(prefix.to_string(), other.clone());
+        }
+    }
+}
+
+fn set_path(root: &mut Value, path: &str, value: Value) {
+    let mut target = root;
+    let mut parts = path.split('.').peekable();
+    while let Some(part) = parts.next() {
+        if parts.peek().is_none() {
+            target[part] = value;
+            return;
+        }
+        target = &mut target[part];
+    }
+}
+
+/// Returns `current` with the reloadable keys taken from `new`, plus which keys
+/// were applied and which changed but require a restart.
+pub fn merge_reloadable(current: &Config, new: &Config) -> Result<(Config, ReloadOutcome)> {
+    let mut merged = serde_json::to_value(current)?;
+    let mut old_flat = Map::new();
+    let mut new_flat = Map::new();
+    flatten("", &merged, &mut old_flat);
+    flatten("", &serde_json::to_value(new)?, &mut new_flat);
+
+    let mut outcome = ReloadOutcome::default();
+    for (key, value) in new_flat {
+        if old_flat.get(&key) == Some(&value) {
+            continue;
+        }
+        if RELOADABLE_KEYS.contains(&key.as_str()) {
+            set_path(&mut merged, &key, value);
+            outcome.applied.push(key);
+        } else {
+            outcome.rejected.push(key);
+        }
+    }
+
+    Ok((serde_json::from_value(merged)?, outcome))
+}
+
+pub fn redacted(config: &Config) -> Result<Value> {
+    let mut value = serde_json::to_value(config)?;
+    for key in SECRET_KEYS {
+        let mut target = &mut value;
+        for part in key.split('.') {
+            target = &mut target[part];
+        }
+        if !target.is_null() {
+            *target = Value::String("***".to_string());
+        }
+    }
+
+    // Redis URLs may embed credentials
+    if let Some(url) = value["redis"]["url"].as_str()
+        && let Some((scheme, rest)) = url.split_once("://")
+        && let Some((_, host)) = rest.rsplit_once('@')
+    {
+        value["redis"]["url"] = Value::String(format!("{}://***@{}", scheme, host));
+    }
+
+    Ok(value)
+}
+
+/// Watches `CONFIG_FILE` and applies reloadable changes as the file is edited.
+/// Does nothing when no config file is configured.
+pub fn spawn_watcher(live: LiveConfig) -> Result<()> {
+    let Some(path) = Config::file_path() else {
+        return Ok(());
+    };
+    let file_name = path.file_name().map(|n| n.to_os_string());
+    let dir = path
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."))
+        .to_path_buf();
+
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        // Editors often replace the file, so watch the directory and filter by name
+        if let Ok(event) = event
+            && event.paths.iter().any(|p| p.file_name().map(|n| n.to_os_string()) == file_name)
+        {
+            let _ = tx.send(());
+        }
+    })
+    .map_err(|e| Error::internal(format!("Failed to create config watcher: {}", e)))?;
+    watcher
+        .watch(&dir, RecursiveMode::NonRecursive)
+        .map_err(|e| Error::internal(format!("Failed to watch {}: {}", dir.display(), e)))?;
+
+    info!("Watching {} for configuration changes", path.display());
+    tokio::spawn(async move {
+        let _watcher = watcher;
+        while rx.recv().await.is_some() {
+            tokio::time::sleep(DEBOUNCE).await;
+            while rx.try_recv().is_ok() {}
+
+            let new = match Config::from_env() {
+                Ok(config) => config,
+                Err(e) => {
+                    error!("Ignoring invalid configuration change: {}", e);
+                    continue;
+                }
+            };
+            match live.apply(&new) {
+                Ok(outcome) => {
+                    if !outcome.applied.is_empty() {
+                        info!("Configuration reloaded: {}", outcome.applied.join(", "));
+                    }
+                    if !outcome.rejected.is_empty() {
+                        warn!(
+                            "Configuration changes require a restart and were not applied: {}",
+                            outcome.rejected.join(", ")
+                        );
+                    }
+                }
+                Err(e) => error!("Failed to apply configuration change: {}", e),
+            }
+        }
+    });
+
+    Ok(())
+}
@@ -0,0 +1,690 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, Postgres, QueryBuilder};
+use uuid::Uuid;
+use crate::{
+    db::DatabasePool,
+    error::Result,
+    models::{
+        CreateMonitorRequest, Monitor, MonitorResult, Page, PageRequest, SortOrder, UpdateMonitorRequest, User, UserRole,
+    },
+    stats::MAX_BUCKETS,
+    Error,
+};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MonitorSort {
+    Name,
+    #[default]
+    CreatedAt,
+    UpdatedAt,
+    Interval,
+}
+
+impl MonitorSort {
+    fn column(self) -> &'static str {
+        match self {
+            MonitorSort::Name => "name",
+            MonitorSort::CreatedAt => "created_at",
+            MonitorSort::UpdatedAt => "updated_at",
+            MonitorSort::Interval => "interval",
+        }
+    }
+}
+
+/// Which monitors the caller may see, mirroring the API's access rules.
+#[derive(Debug, Clone, Default)]
+pub struct MonitorAccess {
+    /// Empty means any tag
+    pub tags: Vec<String>,
+    /// Dashboard tokens see these monitors plus monitors with one of `tags`
+    pub dashboard_monitor_ids: Option<Vec<Uuid>>,
+}
+
+/// One page of a monitor listing.
+#[derive(Debug, Clone, Default)]
+pub struct MonitorQuery {
+    pub page: PageRequest,
+    pub sort_by: MonitorSort,
+    pub order: SortOrder,
+    pub enabled: Option<bool>,
+    /// Case-insensitive substring of the name or endpoint
+    pub search: Option<String>,
+    pub owner_id: Option<Uuid>,
+    pub team_ids: Option<Vec<Uuid>>,
+    pub access: MonitorAccess,
+}
+
+impl MonitorQuery {
+    fn push_filters<'a>(&'a self, sql: &mut QueryBuilder<'a, Postgres>) {
+        sql.push(" WHERE true");
+        if let Some(enabled) = self.enabled {
+            sql.push(" AND enabled = ").push_bind(enabled);
+        }
+        if let Some(search) = self.search.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+            let pattern = format!("%{}%", escape_like(search));
+            sql.push(" AND (name ILIKE ").push_bind(pattern.clone());
+            sql.push(" OR endpoint ILIKE ").push_bind(pattern).push(")");
+        }
+        if let Some(owner_id) = self.owner_id {
+            sql.push(" AND owner_id = ").push_bind(owner_id);
+        }
+        if let Some(team_ids) = &self.team_ids {
+            sql.push(" AND team_id = ANY(").push_bind(team_ids).push(")");
+        }
+        match &self.access.dashboard_monitor_ids {
+            Some(ids) => {
+                sql.push(" AND (id = ANY(").push_bind(ids);
+                sql.push(") OR tags && ").push_bind(&self.access.tags).push(")");
+            }
+            None if !self.access.tags.is_empty() => {
+                sql.push(" AND tags && ").push_bind(&self.access.tags);
+            }
+            None => {}
+        }
+    }
+}
+
+/// Escapes `LIKE` wildcards so user input matches literally.
+pub fn escape_like(input: &str) -> String {
+    input.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
+
+pub async fn list_monitors(db: &DatabasePool, query: &MonitorQuery) -> Result<Page<Monitor>> {
+    query.page.validate()?;
+
+    let mut count = QueryBuilder::new("SELECT COUNT(*) FROM monitors");
+    query.push_filters(&mut count);
+    let total: i64 = count.build_query_scalar().fetch_one(db).await?;
+
+    let mut select = QueryBuilder::new("SELECT * FROM monitors");
+    query.push_filters(&mut select);
+    // Column and direction come from enums, never from the request text
+    select.push(format!(" ORDER BY {} {}, id", query.sort_by.column(), query.order.as_sql()));
+    select.push(" LIMIT ").push_bind(query.page.per_page);
+    select.push(" OFFSET ").push_bind(query.page.offset());
+    let items = select.build_query_as::<Monitor>().fetch_all(db).await?;
+
+    Ok(Page::new(query.page, total, items))
+}
+
+pub async fn get_monitor(db: &DatabasePool, id: Uuid) -> Result<Option<Monitor>> {
+    let monitor = sqlx::query_as::<_, Monitor>("SELECT * FROM monitors WHERE id = $1")
+        .bind(id)
+        .fetch_optional(db)
+        .await?;
+    Ok(monitor)
+}
+
+/// Every monitor, for callers that filter in memory.
+pub async fn all_monitors(db: &DatabasePool, sort_by: MonitorSort) -> Result<Vec<Monitor>> {
+    let monitors = sqlx::query_as::<_, Monitor>(&format!("SELECT * FROM monitors ORDER BY {}, id", sort_by.column()))
+        .fetch_all(db)
+        .await?;
+    Ok(monitors)
+}
+
+pub async fn enabled_monitors(db: &DatabasePool) -> Result<Vec<Monitor>> {
+    let monitors = sqlx::query_as::<_, Monitor>("SELECT * FROM monitors WHERE enabled = true")
+        .fetch_all(db)
+        .await?;
+    Ok(monitors)
+}
+
+/// Inserts a validated monitor; `credentials` is already encrypted.
+pub async fn insert_monitor(
+    db: &DatabasePool,
+    request: &CreateMonitorRequest,
+    owner_id: Uuid,
+    credentials: Option<&str>,
+) -> Result<Monitor> {
+    let steps = request.steps.as_ref().map(serde_json::to_value).transpose()?;
+    let metric_assertions = request.metric_assertions.as_ref().map(serde_json::to_value).transpose()?;
+    let broker_check = request.broker_check.as_ref().map(serde_json::to_value).transpose()?;
+    let storage_check = request.storage_check.as_ref().map(serde_json::to_value).transpose()?;
+    let ntp_check = request.ntp_check.as_ref().map(serde_json::to_value).transpose()?;
+    let snmp_check = request.snmp_check.as_ref().map(serde_json::to_value).transpose()?;
+    let exec_check = request.exec_check.as_ref().map(serde_json::to_value).transpose()?;
+    let headers = request.headers.as_ref().map(serde_json::to_value).transpose()?;
+    let tcp_check = request.tcp_check.as_ref().map(serde_json::to_value).transpose()?;
+    let dns_check = request.dns_check.as_ref().map(serde_json::to_value).transpose()?;
+    let tls_check = request.tls_check.as_ref().map(serde_json::to_value).transpose()?;
+    let websocket_check = request.websocket_check.as_ref().map(serde_json::to_value).transpose()?;
+    let mail_check = request.mail_check.as_ref().map(serde_json::to_value).transpose()?;
+    let database_check = request.database_check.as_ref().map(serde_json::to_value).transpose()?;
+    let heartbeat_check = request.heartbeat_check.as_ref().map(serde_json::to_value).transpose()?;
+    let monitor = sqlx::query_as::<_, Monitor>(
+        r#"
+        INSERT INTO monitors (id, name, endpoint, method, headers, body, expected_status, timeout, interval, script, pre_request_script, enabled, tags, owner_id, team_id, credentials, steps, retries, notification_channels, script_profile, bypass_dns_cache, connect_timeout_ms, tls_timeout_ms, metric_assertions, broker_check, storage_check, ntp_check, snmp_check, exec_check, retry_delay_ms, schedule_cron, tcp_check, dns_check, tls_check, expected_body_contains, expected_body_regex, websocket_check, mail_check, database_check, heartbeat_check, created_at, updated_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, true, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21, $22, $23, $24, $25, $26, $27, $28, $29, NULLIF($30, ''), $31, $32, $33, NULLIF($34, ''), NULLIF($35, ''), $36, $37, $38, $39, NOW(), NOW())
+        RETURNING *
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(&request.name)
+    .bind(&request.endpoint)
+    .bind(&request.method)
+    .bind(&headers)
+    .bind(&request.body)
+    .bind(&request.expected_status)
+    .bind(request.timeout)
+    .bind(request.interval)
+    .bind(&request.script)
+    .bind(&request.pre_request_script)
+    .bind(&request.tags)
+    .bind(owner_id)
+    .bind(request.team_id)
+    .bind(credentials)
+    .bind(&steps)
+    .bind(request.retries)
+    .bind(&request.notification_channels)
+    .bind(&request.script_profile)
+    .bind(request.bypass_dns_cache)
+    .bind(request.connect_timeout_ms)
+    .bind(request.tls_timeout_ms)
+    .bind(&metric_assertions)
+    .bind(&broker_check)
+    .bind(&storage_check)
+    .bind(&ntp_check)
+    .bind(&snmp_check)
+    .bind(&exec_check)
+    .bind(request.retry_delay_ms)
+    .bind(&request.schedule_cron)
+    .bind(&tcp_check)
+    .bind(&dns_check)
+    .bind(&tls_check)
+    .bind(&request.expected_body_contains)
+    .bind(&request.expected_body_regex)
+    .bind(&websocket_check)
+    .bind(&mail_check)
+    .bind(&database_check)
+    .bind(&heartbeat_check)
+    .fetch_one(db)
+    .await?;
+    Ok(monitor)
+}
+
+/// Writes a validated edit through `db`, a pool or an open transaction;
+/// omitted fields stay as they are and `credentials` is already encrypted.
+/// `None` when there is no such monitor.
+pub async fn update_monitor<'e>(
+    db: impl sqlx::PgExecutor<'e>,
+    id: Uuid,
+    request: &UpdateMonitorRequest,
+    credentials: Option<&str>,
+) -> Result<Option<Monitor>> {
+    let steps = request.steps.as_ref().map(serde_json::to_value).transpose()?;
+    let metric_assertions = request.metric_assertions.as_ref().map(serde_json::to_value).transpose()?;
+    let broker_check = request.broker_check.as_ref().map(serde_json::to_value).transpose()?;
+    let storage_check = request.storage_check.as_ref().map(serde_json::to_value).transpose()?;
+    let ntp_check = request.ntp_check.as_ref().map(serde_json::to_value).transpose()?;
+    let snmp_check = request.snmp_check.as_ref().map(serde_json::to_value).transpose()?;
+    let exec_check = request.exec_check.as_ref().map(serde_json::to_value).transpose()?;
+    let headers = request.headers.as_ref().map(serde_json::to_value).transpose()?;
+    let tcp_check = request.tcp_check.as_ref().map(serde_json::to_value).transpose()?;
+    let dns_check = request.dns_check.as_ref().map(serde_json::to_value).transpose()?;
+    let tls_check = request.tls_check.as_ref().map(serde_json::to_value).transpose()?;
+    let websocket_check = request.websocket_check.as_ref().map(serde_json::to_value).transpose()?;
+    let mail_check = request.mail_check.as_ref().map(serde_json::to_value).transpose()?;
+    let database_check = request.database_check.as_ref().map(serde_json::to_value).transpose()?;
+    let heartbeat_check = request.heartbeat_check.as_ref().map(serde_json::to_value).transpose()?;
+    let monitor = sqlx::query_as::<_, Monitor>(
+        r#"
+        UPDATE monitors SET
+            name = COALESCE($2, name),
+            endpoint = COALESCE($3, endpoint),
+            method = COALESCE($4, method),
+            headers = COALESCE($5, headers),
+            body = COALESCE($6, body),
+            expected_status = COALESCE($7, expected_status),
+            timeout = COALESCE($8, timeout),
+            interval = COALESCE($9, interval),
+            script = COALESCE($10, script),
+            pre_request_script = COALESCE($11, pre_request_script),
+            enabled = COALESCE($12, enabled),
+            tags = COALESCE($13, tags),
+            team_id = COALESCE($14, team_id),
+            retries = COALESCE($15, retries),
+            notification_channels = COALESCE($16, notification_channels),
+            script_profile = COALESCE($17, script_profile),
+            credentials = COALESCE($18, credentials),
+            steps = COALESCE($19, steps),
+            bypass_dns_cache = COALESCE($20, bypass_dns_cache),
+            connect_timeout_ms = COALESCE($21, connect_timeout_ms),
+            tls_timeout_ms = COALESCE($22, tls_timeout_ms),
+            metric_assertions = COALESCE($23, metric_assertions),
+            broker_check = COALESCE($24, broker_check),
+            storage_check = COALESCE($25, storage_check),
+            ntp_check = COALESCE($26, ntp_check),
+            snmp_check = COALESCE($27, snmp_check),
+            exec_check = COALESCE($28, exec_check),
+            retry_delay_ms = COALESCE($29, retry_delay_ms),
+            schedule_cron = NULLIF(COALESCE($30, schedule_cron), ''),
+            tcp_check = COALESCE($31, tcp_check),
+            dns_check = COALESCE($32, dns_check),
+            tls_check = COALESCE($33, tls_check),
+            expected_body_contains = NULLIF(COALESCE($34, expected_body_contains), ''),
+            expected_body_regex = NULLIF(COALESCE($35, expected_body_regex), ''),
+            websocket_check = COALESCE($36, websocket_check),
+            mail_check = COALESCE($37, mail_check),
+            database_check = COALESCE($38, database_check),
+            heartbeat_check = COALESCE($39, heartbeat_check),
+            updated_at = NOW()
+        WHERE id = $1
+        RETURNING *
+        "#,
+    )
+    .bind(id)
+    .bind(&request.name)
+    .bind(&request.endpoint)
+    .bind(&request.method)
+    .bind(&headers)
+    .bind(&request.body)
+    .bind(&request.expected_status)
+    .bind(request.timeout)
+    .bind(request.interval)
+    .bind(&request.script)
+    .bind(&request.pre_request_script)
+    .bind(request.enabled)
+    .bind(&request.tags)
+    .bind(request.team_id)
+    .bind(request.retries)
+    .bind(&request.notification_channels)
+    .bind(&request.script_profile)
+    .bind(credentials)
+    .bind(&steps)
+    .bind(request.bypass_dns_cache)
+    .bind(request.connect_timeout_ms)
+    .bind(request.tls_timeout_ms)
+    .bind(&metric_assertions)
+    .bind(&broker_check)
+    .bind(&storage_check)
+    .bind(&ntp_check)
+    .bind(&snmp_check)
+    .bind(&exec_check)
+    .bind(request.retry_delay_ms)
+    .bind(&request.schedule_cron)
+    .bind(&tcp_check)
+    .bind(&dns_check)
+    .bind(&tls_check)
+    .bind(&request.expected_body_contains)
+    .bind(&request.expected_body_regex)
+    .bind(&websocket_check)
+    .bind(&mail_check)
+    .bind(&database_check)
+    .bind(&heartbeat_check)
+    .fetch_optional(db)
+    .await?;
+    Ok(monitor)
+}
+
+pub async fn set_monitor_ownership(
+    db: &DatabasePool,
+    id: Uuid,
+    owner_id: Option<Uuid>,
+    team_id: Option<Uuid>,
+) -> Result<Monitor> {
+    let monitor = sqlx::query_as::<_, Monitor>(
+        "UPDATE monitors SET owner_id = $2, team_id = $3, updated_at = NOW() WHERE id = $1 RETURNING *",
+    )
+    .bind(id)
+    .bind(owner_id)
+    .bind(team_id)
+    .fetch_one(db)
+    .await?;
+    Ok(monitor)
+}
+
+/// Statuses a check result can have.
+pub const RESULT_STATUSES: &[&str] = &["success", "failure", "timeout", "error", "crashed"];
+
+/// Results of one monitor within `[from, to)`, newest first.
+#[derive(Debug, Clone)]
+pub struct ResultQuery {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub status: Option<String>,
+    pub page: PageRequest,
+}
+
+/// Results aggregated over one interval; empty intervals are omitted.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct ResultBucket {
+    pub bucket: DateTime<Utc>,
+    pub count: i64,
+    pub avg_response_time: Option<f64>,
+    /// Share of successful checks in percent
+    pub uptime: Option<f64>,
+}
+
+impl ResultQuery {
+    pub fn validate(&self) -> Result<()> {
+        if self.to <= self.from {
+            return Err(Error::validation("'to' must be after 'from'"));
+        }
+        if let Some(status) = &self.status
+            && !RESULT_STATUSES.contains(&status.as_str())
+        {
+            return Err(Error::validation(format!(
+                "Unknown status '{}', use one of {}",
+                status,
+                RESULT_STATUSES.join(", ")
+            )));
+        }
+        self.page.validate()
+    }
+
+    fn push_filters<'a>(&'a self, sql: &mut QueryBuilder<'a, Postgres>, monitor_id: Uuid) {
+        sql.push(" WHERE monitor_id = ").push_bind(monitor_id);
+        sql.push(" AND checked_at >= ").push_bind(self.from);
+        sql.push(" AND checked_at < ").push_bind(self.to);
+        if let Some(status) = &self.status {
+            sql.push(" AND status = ").push_bind(status);
+        }
+    }
+}
+
+pub async fn list_results(db: &DatabasePool, monitor_id: Uuid, query: &ResultQuery) -> Result<Page<MonitorResult>> {
+    query.validate()?;
+
+    let mut count = QueryBuilder::new("SELECT COUNT(*) FROM monitor_results");
+    query.push_filters(&mut count, monitor_id);
+    let total: i64 = count.build_query_scalar().fetch_one(db).await?;
+
+    let mut select = QueryBuilder::new("SELECT * FROM monitor_results");
+    query.push_filters(&mut select, monitor_id);
+    select.push(" ORDER BY checked_at DESC, id");
+    select.push(" LIMIT ").push_bind(query.page.per_page);
+    select.push(" OFFSET ").push_bind(query.page.offset());
+    let items = select.build_query_as::<MonitorResult>().fetch_all(db).await?;
+
+    Ok(Page::new(query.page, total, items))
+}
+
+/// Buckets aligned to multiples of `step_secs` since the Unix epoch, oldest
+/// first. Pagination does not apply.
+pub async fn result_buckets(
+    db: &DatabasePool,
+    monitor_id: Uuid,
+    query: &ResultQuery,
+    step_secs: i64,
+) -> Result<Vec<ResultBucket>> {
+    query.validate()?;
+    if (query.to - query.from).num_seconds() / step_secs > MAX_BUCKETS {
+        return Err(Error::validation(format!(
+            "Range and interval would produce more than {} buckets; use a larger interval",
+            MAX_BUCKETS
+        )));
+    }
+
+    let mut select = QueryBuilder::new("SELECT TO_TIMESTAMP(FLOOR(EXTRACT(EPOCH FROM checked_at) / ");
+    select.push_bind(step_secs as f64).push(") * ").push_bind(step_secs as f64);
+    select.push(
+        r#") AS bucket,
+           COUNT(*) AS count,
+           AVG(response_time)::FLOAT8 AS avg_response_time,
+           (100.0 * AVG(CASE WHEN status = 'success' THEN 1.0 ELSE 0.0 END))::FLOAT8 AS uptime
+        FROM monitor_results"#,
+    );
+    query.push_filters(&mut select, monitor_id);
+    select.push(" GROUP BY bucket ORDER BY bucket");
+    Ok(select.build_query_as::<ResultBucket>().fetch_all(db).await?)
+}
+
+/// Writes a check result. Replays may re-send results that were written just
+/// before a failure, so existing ids are skipped and `false` is returned.
+pub async fn insert_result(db: &DatabasePool, result: &MonitorResult) -> Result<bool> {
+    let inserted = sqlx::query(
+        r#"
+        INSERT INTO monitor_results (id, monitor_id, status, response_time, response_code, response_body, error_message, error_category, error_hint, checked_at, clock_skew_ms, schedule_lag_ms, attempts)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+        ON CONFLICT (id) DO NOTHING
+        "#,
+    )
+    .bind(result.id)
+    .bind(result.monitor_id)
+    .bind(&result.status)
+    .bind(result.response_time)
+    .bind(result.response_code)
+    .bind(&result.response_body)
+    .bind(&result.error_message)
+    .bind(&result.error_category)
+    .bind(&result.error_hint)
+    .bind(result.checked_at)
+    .bind(result.clock_skew_ms)
+    .bind(result.schedule_lag_ms)
+    .bind(result.attempts)
+    .execute(db)
+    .await?
+    .rows_affected();
+    Ok(inserted > 0)
+}
+
+/// Writes several results in one statement, skipping ids that already exist
+/// like `insert_result`. Returns how many were new.
+pub async fn insert_results(db: &DatabasePool, results: &[MonitorResult]) -> Result<u64> {
+    if results.is_empty() {
+        return Ok(0);
+    }
+    let mut insert = QueryBuilder::<Postgres>::new(
+        "INSERT INTO monitor_results (id, monitor_id, status, response_time, response_code, response_body, error_message, error_category, error_hint, checked_at, clock_skew_ms, schedule_lag_ms, attempts) ",
+    );
+    insert.push_values(results, |mut row, result| {
+        row.push_bind(result.id)
+            .push_bind(result.monitor_id)
+            .push_bind(&result.status)
+            .push_bind(result.response_time)
+            .push_bind(result.response_code)
+            .push_bind(&result.response_body)
+            .push_bind(&result.error_message)
+            .push_bind(&result.error_category)
+            .push_bind(&result.error_hint)
+            .push_bind(result.checked_at)
+            .push_bind(result.clock_skew_ms)
+            .push_bind(result.schedule_lag_ms)
+            .push_bind(result.attempts);
+    });
+    insert.push(" ON CONFLICT (id) DO NOTHING");
+    Ok(insert.build().execute(db).await?.rows_affected())
+}
+
+pub async fn get_result(db: &DatabasePool, id: Uuid) -> Result<Option<MonitorResult>> {
+    let result = sqlx::query_as::<_, MonitorResult>("SELECT * FROM monitor_results WHERE id = $1")
+        .bind(id)
+        .fetch_optional(db)
+        .await?;
+    Ok(result)
+}
+
+pub async fn latest_result(db: &DatabasePool, monitor_id: Uuid) -> Result<Option<MonitorResult>> {
+    let result = sqlx::query_as::<_, MonitorResult>(
+        "SELECT * FROM monitor_results WHERE monitor_id = $1 ORDER BY checked_at DESC LIMIT 1",
+    )
+    .bind(monitor_id)
+    .fetch_optional(db)
+    .await?;
+    Ok(result)
+}
+
+/// The latest result of each of `monitor_ids` that has one.
+pub async fn latest_results(db: &DatabasePool, monitor_ids: &[Uuid]) -> Result<Vec<MonitorResult>> {
+    let results = sqlx::query_as::<_, MonitorResult>(
+        r#"
+        SELECT DISTINCT ON (monitor_id) * FROM monitor_results
+        WHERE monitor_id = ANY($1)
+        ORDER BY monitor_id, checked_at DESC
+        "#,
+    )
+    .bind(monitor_ids)
+    .fetch_all(db)
+    .await?;
+    Ok(results)
+}
+
+/// Up to `limit` results, newest first; only those checked after the result
+/// `after` when given.
+pub async fn recent_results(
+    db: &DatabasePool,
+    monitor_id: Uuid,
+    after: Option<Uuid>,
+    limit: i64,
+) -> Result<Vec<MonitorResult>> {
+    let results = sqlx::query_as::<_, MonitorResult>(
+        r#"
+        SELECT * FROM monitor_results
+        WHERE monitor_id = $1
+          AND ($2::UUID IS NULL OR checked_at > (SELECT checked_at FROM monitor_results WHERE id = $2))
+        ORDER BY checked_at DESC
+        LIMIT $3
+        "#,
+    )
+    .bind(monitor_id)
+    .bind(after)
+    .bind(limit)
+    .fetch_all(db)
+    .await?;
+    Ok(results)
+}
+
+/// Results within `[from, to]` that kept their response body, newest first,
+/// and how many in the range did not.
+pub async fn results_with_bodies(
+    db: &DatabasePool,
+    monitor_id: Uuid,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    limit: i64,
+) -> Result<(Vec<MonitorResult>, i64)> {
+    let results = sqlx::query_as::<_, MonitorResult>(
+        r#"
+        SELECT * FROM monitor_results
+        WHERE monitor_id = $1 AND checked_at >= $2 AND checked_at <= $3 AND response_body IS NOT NULL
+        ORDER BY checked_at DESC
+        LIMIT $4
+        "#,
+    )
+    .bind(monitor_id)
+    .bind(from)
+    .bind(to)
+    .bind(limit)
+    .fetch_all(db)
+    .await?;
+    let without_body = sqlx::query_scalar(
+        r#"
+        SELECT COUNT(*) FROM monitor_results
+        WHERE monitor_id = $1 AND checked_at >= $2 AND checked_at <= $3 AND response_body IS NULL
+        "#,
+    )
+    .bind(monitor_id)
+    .bind(from)
+    .bind(to)
+    .fetch_one(db)
+    .await?;
+    Ok((results, without_body))
+}
+
+pub async fn get_active_user(db: &DatabasePool, id: Uuid) -> Result<Option<User>> {
+    let user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = $1 AND active = true")
+        .bind(id)
+        .fetch_optional(db)
+        .await?;
+    Ok(user)
+}
+
+/// An active user by username or email, for password login.
+pub async fn find_active_login(db: &DatabasePool, login: &str) -> Result<Option<User>> {
+    let user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE (username = $1 OR email = $1) AND active = true")
+        .bind(login)
+        .fetch_optional(db)
+        .await?;
+    Ok(user)
+}
+
+pub async fn username_or_email_taken(db: &DatabasePool, username: &str, email: &str) -> Result<bool> {
+    let taken = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM users WHERE username = $1 OR email = $2)")
+        .bind(username)
+        .bind(email)
+        .fetch_one(db)
+        .await?;
+    Ok(taken)
+}
+
+pub async fn list_users(db: &DatabasePool) -> Result<Vec<User>> {
+    let users = sqlx::query_as::<_, User>("SELECT * FROM users ORDER BY username")
+        .fetch_all(db)
+        .await?;
+    Ok(users)
+}
+
+/// Advisory lock key that serializes registrations deciding the first admin
+const FIRST_USER_LOCK_KEY: i64 = 0x6669_7273_7475_7365;
+
+/// Creates an active user. Without a `role` the first user becomes an admin
+/// and everyone after a member.
+pub async fn insert_user(
+    db: &DatabasePool,
+    username: &str,
+    email: &str,
+    password_hash: &str,
+    role: Option<UserRole>,
+) -> Result<User> {
+    let mut tx = db.begin().await?;
+    // Two first registrations would otherwise both find no users and both
+    // become admin; the second waits here and then sees the first
+    if role.is_none() {
+        sqlx::query("SELECT pg_advisory_xact_lock($1)")
+            .bind(FIRST_USER_LOCK_KEY)
+            .execute(&mut *tx)
+            .await?;
+    }
+    let user = sqlx::query_as::<_, User>(
+        r#"
+        INSERT INTO users (id, username, email, password_hash, role, active, created_at, updated_at)
+        VALUES ($1, $2, $3, $4, COALESCE($5, CASE WHEN EXISTS (SELECT 1 FROM users) THEN 'member' ELSE 'admin' END), true, NOW(), NOW())
+        RETURNING *
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(username)
+    .bind(email)
+    .bind(password_hash)
+    .bind(role)
+    .fetch_one(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    Ok(user)
+}
+
+/// `None` when there is no such user.
+pub async fn update_user(
+    db: &DatabasePool,
+    username: &str,
+    email: Option<&str>,
+    role: Option<UserRole>,
+) -> Result<Option<User>> {
+    let user = sqlx::query_as::<_, User>(
+        r#"
+        UPDATE users
+        SET email = COALESCE($2, email), role = COALESCE($3, role), updated_at = NOW()
+        WHERE username = $1
+        RETURNING *
+        "#,
+    )
+    .bind(username)
+    .bind(email)
+    .bind(role)
+    .fetch_optional(db)
+    .await?;
+    Ok(user)
+}
+
+/// `None` when there is no such user.
+pub async fn set_user_active(db: &DatabasePool, username: &str, active: bool) -> Result<Option<User>> {
+    let user = sqlx::query_as::<_, User>("UPDATE users SET active = $2, updated_at = NOW() WHERE username = $1 RETURNING *")
+        .bind(username)
+        .bind(active)
+        .fetch_optional(db)
+        .await?;
+    Ok(user)
+}
@@ -0,0 +1,75 @@
+#[cfg(test)]
+mod repository_tests {
+    use crate::models::{PageRequest, SortOrder, DEFAULT_PER_PAGE, MAX_PER_PAGE};
+    use crate::repository::*;
+
+    fn query(page: i64, per_page: i64) -> MonitorQuery {
+        MonitorQuery {
+            page: PageRequest { page, per_page },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_page_bounds() {
+        assert!(query(1, DEFAULT_PER_PAGE).page.validate().is_ok());
+        assert!(query(0, DEFAULT_PER_PAGE).page.validate().is_err());
+        assert!(query(1, 0).page.validate().is_err());
+        assert!(query(1, MAX_PER_PAGE + 1).page.validate().is_err());
+    }
+
+    #[test]
+    fn test_offset() {
+        assert_eq!(query(1, 50).page.offset(), 0);
+        assert_eq!(query(3, 20).page.offset(), 40);
+    }
+
+    #[test]
+    fn test_page_echoes_the_request() {
+        let page = crate::models::Page::new(PageRequest { page: 2, per_page: 10 }, 25, vec!["a", "b"]);
+        assert_eq!((page.total, page.page, page.per_page, page.items.len()), (25, 2, 10, 2));
+        assert_eq!(SortOrder::Desc.as_sql(), "DESC");
+    }
+
+    #[test]
+    fn test_escape_like() {
+        assert_eq!(escape_like("api"), "api");
+        assert_eq!(escape_like("100%_done\\"), "100\\%\\_done\\\\");
+    }
+
+    #[test]
+    fn test_sort_params() {
+        let sort: MonitorSort = serde_json::from_str("\"updated_at\"").unwrap();
+        assert_eq!(sort, MonitorSort::UpdatedAt);
+        let order: SortOrder = serde_json::from_str("\"desc\"").unwrap();
+        assert_eq!(order, SortOrder::Desc);
+        assert!(serde_json::from_str::<MonitorSort>("\"endpoint; DROP TABLE\"").is_err());
+    }
+
+    fn results(status: Option<&str>) -> ResultQuery {
+        let to = chrono::Utc::now();
+        ResultQuery {
+            from: to - chrono::Duration::hours(1),
+            to,
+            status: status.map(str::to_string),
+            page: PageRequest::default(),
+        }
+    }
+
+    #[test]
+    fn test_result_query_validation() {
+        assert!(results(None).validate().is_ok());
+        assert!(results(Some("timeout")).validate().is_ok());
+        assert!(results(Some("down")).validate().is_err());
+
+        let mut reversed = results(None);
+        reversed.from = reversed.to + chrono::Duration::seconds(1);
+        assert!(reversed.validate().is_err());
+
+        let mut paged = results(None);
+        paged.page = PageRequest { page: 4, per_page: 25 };
+        assert_eq!(paged.page.offset(), 75);
+        paged.page.per_page = MAX_PER_PAGE + 1;
+        assert!(paged.validate().is_err());
+    }
+}
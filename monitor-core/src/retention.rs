@@ -0,0 +1,139 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use uuid::Uuid;
+use crate::{db::DatabasePool, error::Result};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PurgeReport {
+    pub dry_run: bool,
+    /// Rows matched (dry run) or deleted, keyed by table.
+    pub rows: BTreeMap<String, i64>,
+}
+
+impl PurgeReport {
+    fn new(dry_run: bool) -> Self {
+        Self {
+            dry_run,
+            rows: BTreeMap::new(),
+        }
+    }
+
+    pub fn total(&self) -> i64 {
+        self.rows.values().sum()
+    }
+}
+
+/// Per-table predicates identifying rows that belong to a user, ordered so that
+/// children are deleted before their parents. Each takes the user id as `$1`.
+const USER_DATA: &[(&str, &str)] = &[
+    (
+        "monitor_results",
+        "monitor_id IN (SELECT id FROM monitors WHERE owner_id = $1)",
+    ),
+    (
+        "monitor_result_rollups",
+        "monitor_id IN (SELECT id FROM monitors WHERE owner_id = $1)",
+    ),
+    (
+        "monitor_expirations",
+        "monitor_id IN (SELECT id FROM monitors WHERE owner_id = $1)",
+    ),
+    (
+        "webhook_endpoints",
+        "monitor_id IN (SELECT id FROM monitors WHERE owner_id = $1)",
+    ),
+    (
+        "alerts",
+        "monitor_id IN (SELECT id FROM monitors WHERE owner_id = $1)",
+    ),
+    ("monitors", "owner_id = $1"),
+    ("personal_access_tokens", "user_id = $1"),
+    // Entries keep their actor's id, and their details may name the user
+    ("audit_log", "actor_id = $1"),
+    ("users", "id = $1"),
+];
+
+/// Removes every row owned by a user. With `dry_run` nothing is deleted and the
+/// report holds the number of rows that would be.
+pub async fn purge_user_data(db: &DatabasePool, user_id: Uuid, dry_run: bool) -> Result<PurgeReport> {
+    let mut report = PurgeReport::new(dry_run);
+    let mut tx = db.begin().await?;
+
+    for (table, predicate) in USER_DATA {
+        let rows = if dry_run {
+            sqlx::query_scalar::<_, i64>(&format!("SELECT COUNT(*) FROM {} WHERE {}", table, predicate))
+                .bind(user_id)
+                .fetch_one(&mut *tx)
+                .await?
+        } else {
+            sqlx::query(&format!("DELETE FROM {} WHERE {}", table, predicate))
+                .bind(user_id)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected() as i64
+        };
+        report.rows.insert(table.to_string(), rows);
+    }
+
+    if dry_run {
+        tx.rollback().await?;
+    } else {
+        tx.commit().await?;
+    }
+
+    Ok(report)
+}
+
+/// Deletes monitor results checked before `cutoff`, and state changes from the same period.
+pub async fn purge_expired_results(
+    db: &DatabasePool,
+    cutoff: DateTime<Utc>,
+    dry_run: bool,
+) -> Result<PurgeReport> {
+    let mut report = PurgeReport::new(dry_run);
+
+    let rows = if dry_run {
+        sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM monitor_results WHERE checked_at < $1")
+            .bind(cutoff)
+            .fetch_one(db)
+            .await?
+    } else {
+        sqlx::query("DELETE FROM monitor_results WHERE checked_at < $1")
+            .bind(cutoff)
+            .execute(db)
+            .await?
+            .rows_affected() as i64
+    };
+    report.rows.insert("monitor_results".to_string(), rows);
+
+    let rows = if dry_run {
+        sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM monitor_state_changes WHERE changed_at < $1")
+            .bind(cutoff)
+            .fetch_one(db)
+            .await?
+    } else {
+        sqlx::query("DELETE FROM monitor_state_changes WHERE changed_at < $1")
+            .bind(cutoff)
+            .execute(db)
+            .await?
+            .rows_affected() as i64
+    };
+    report.rows.insert("monitor_state_changes".to_string(), rows);
+
+    let rows = if dry_run {
+        sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM monitor_skipped_runs WHERE skipped_at < $1")
+            .bind(cutoff)
+            .fetch_one(db)
+            .await?
+    } else {
+        sqlx::query("DELETE FROM monitor_skipped_runs WHERE skipped_at < $1")
+            .bind(cutoff)
+            .execute(db)
+            .await?
+            .rows_affected() as i64
+    };
+    report.rows.insert("monitor_skipped_runs".to_string(), rows);
+
+    Ok(report)
+}
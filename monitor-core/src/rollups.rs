@@ -0,0 +1,188 @@
+use chrono::{DateTime, Duration, Utc};
+use sqlx::Row;
+use std::collections::BTreeMap;
+use uuid::Uuid;
+use crate::{db::DatabasePool, error::Result, sketch::LatencySketch};
+
+pub const ROLLUP_SECS: i64 = 3_600;
+/// Recent hours are rolled up again so results replayed late are included.
+const LOOKBACK_HOURS: i64 = 3;
+const MAX_HOURS_PER_RUN: i64 = 48;
+
+/// Aggregated results for one monitor-hour.
+#[derive(Debug, Clone, Default)]
+pub struct HourlyAggregate {
+    pub count: i64,
+    pub success_count: i64,
+    pub latency_sum: i64,
+    pub latency_max: i32,
+    pub latency: LatencySketch,
+}
+
+impl HourlyAggregate {
+    pub fn record(&mut self, success: bool, latency_ms: i32) {
+        self.count += 1;
+        if success {
+            self.success_count += 1;
+        }
+        self.latency_sum += latency_ms as i64;
+        self.latency_max = self.latency_max.max(latency_ms);
+        self.latency.record(latency_ms as i64);
+    }
+
+    pub fn merge(&mut self, other: &HourlyAggregate) {
+        self.count += other.count;
+        self.success_count += other.success_count;
+        self.latency_sum += other.latency_sum;
+        self.latency_max = self.latency_max.max(other.latency_max);
+        self.latency.merge(&other.latency);
+    }
+}
+
+pub fn hour_start(at: DateTime<Utc>) -> DateTime<Utc> {
+    let ts = at.timestamp();
+    DateTime::from_timestamp(ts - ts.rem_euclid(ROLLUP_SECS), 0).unwrap_or(at)
+}
+
+/// Rolls up raw results for completed hours, catching up at most 48 hours per run.
+/// Returns the number of monitor-hour rows written.
+pub async fn rollup_completed_hours(db: &DatabasePool, now: DateTime<Utc>) -> Result<u64> {
+    let current_hour = hour_start(now);
+    let latest: Option<DateTime<Utc>> =
+        sqlx::query_scalar("SELECT MAX(bucket_start) FROM monitor_result_rollups")
+            .fetch_one(db)
+            .await?;
+
+    let start = match latest {
+        Some(latest) => (latest + Duration::hours(1)).min(current_hour - Duration::hours(LOOKBACK_HOURS)),
+        None => {
+            let oldest: Option<DateTime<Utc>> = sqlx::query_scalar("SELECT MIN(checked_at) FROM monitor_results")
+                .fetch_one(db)
+                .await?;
+            match oldest {
+                Some(oldest) => hour_start(oldest),
+                None => return Ok(0),
+            }
+        }
+    };
+    let end = (start + Duration::hours(MAX_HOURS_PER_RUN)).min(current_hour);
+
+    let mut written = 0;
+    let mut hour = start;
+    while hour < end {
+        written += rollup_hour(db, hour).await?;
+        hour += Duration::hours(1);
+    }
+    Ok(written)
+}
+
+async fn rollup_hour(db: &DatabasePool, hour: DateTime<Utc>) -> Result<u64> {
+    let rows = sqlx::query(
+        "SELECT monitor_id, status, response_time FROM monitor_results WHERE checked_at >= $1 AND checked_at < $2",
+    )
+    .bind(hour)
+    .bind(hour + Duration::hours(1))
+    .fetch_all(db)
+    .await?;
+
+    let mut aggregates: BTreeMap<Uuid, HourlyAggregate> = BTreeMap::new();
+    for row in rows {
+        let status: String = row.get("status");
+        aggregates
+            .entry(row.get("monitor_id"))
+            .or_default()
+            .record(status == "success", row.get("response_time"));
+    }
+
+    let mut written = 0;
+    for (monitor_id, aggregate) in aggregates {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO monitor_result_rollups
+                (monitor_id, bucket_start, count, success_count, latency_sum, latency_max, latency_histogram, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, NOW())
+            ON CONFLICT (monitor_id, bucket_start) DO UPDATE SET
+                count = EXCLUDED.count,
+                success_count = EXCLUDED.success_count,
+                latency_sum = EXCLUDED.latency_sum,
+                latency_max = EXCLUDED.latency_max,
+                latency_histogram = EXCLUDED.latency_histogram,
+                updated_at = NOW()
+            "#,
+        )
+        .bind(monitor_id)
+        .bind(hour)
+        .bind(aggregate.count)
+        .bind(aggregate.success_count)
+        .bind(aggregate.latency_sum)
+        .bind(aggregate.latency_max)
+        .bind(aggregate.latency.to_bytes()?)
+        .execute(db)
+        .await?;
+        written += result.rows_affected();
+    }
+    Ok(written)
+}
+
+/// Per-hour aggregates for `[from, to)`: stored rollups for hours fully inside the
+/// range, and raw results for everything else (partial or not yet rolled up hours).
+pub async fn hourly_aggregates(
+    db: &DatabasePool,
+    monitor_id: Uuid,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<BTreeMap<DateTime<Utc>, HourlyAggregate>> {
+    let mut hours = BTreeMap::new();
+
+    let rollups = sqlx::query(
+        r#"
+        SELECT bucket_start, count, success_count, latency_sum, latency_max, latency_histogram
+        FROM monitor_result_rollups
+        WHERE monitor_id = $1 AND bucket_start >= $2 AND bucket_start + INTERVAL '1 hour' <= $3
+        "#,
+    )
+    .bind(monitor_id)
+    .bind(from)
+    .bind(to)
+    .fetch_all(db)
+    .await?;
+
+    for row in rollups {
+        let histogram: Vec<u8> = row.get("latency_histogram");
+        hours.insert(
+            row.get::<DateTime<Utc>, _>("bucket_start"),
+            HourlyAggregate {
+                count: row.get("count"),
+                success_count: row.get("success_count"),
+                latency_sum: row.get("latency_sum"),
+                latency_max: row.get("latency_max"),
+                latency: LatencySketch::from_bytes(&histogram)?,
+            },
+        );
+    }
+
+    let covered: Vec<DateTime<Utc>> = hours.keys().copied().collect();
+    let raw = sqlx::query(
+        r#"
+        SELECT checked_at, status, response_time FROM monitor_results
+        WHERE monitor_id = $1 AND checked_at >= $2 AND checked_at < $3
+          AND NOT (TO_TIMESTAMP(FLOOR(EXTRACT(EPOCH FROM checked_at) / 3600) * 3600) = ANY($4))
+        "#,
+    )
+    .bind(monitor_id)
+    .bind(from)
+    .bind(to)
+    .bind(&covered)
+    .fetch_all(db)
+    .await?;
+
+    for row in raw {
+        let status: String = row.get("status");
+        hours
+            .entry(hour_start(row.get("checked_at")))
+            .or_insert_with(HourlyAggregate::default)
+            .record(status == "success", row.get("response_time"));
+    }
+
+    Ok(hours)
+}
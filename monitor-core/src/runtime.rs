@@ -0,0 +1,27 @@
+use tokio::runtime::{Builder, Runtime};
+use crate::{
+    config::{Config, RuntimeConfig},
+    error::Result,
+};
+
+/// The multi-threaded runtime a service runs on, tuned by `config`. Worker
+/// threads are named after the service so they are easy to tell apart in
+/// `top -H` and stack dumps.
+pub fn build(service: &str, config: &RuntimeConfig) -> Result<Runtime> {
+    let mut builder = Builder::new_multi_thread();
+    builder.enable_all().thread_name(service);
+    if config.worker_threads > 0 {
+        builder.worker_threads(config.worker_threads);
+    }
+    if config.max_blocking_threads > 0 {
+        builder.max_blocking_threads(config.max_blocking_threads);
+    }
+    Ok(builder.build()?)
+}
+
+/// Runtime settings from the environment. They are needed before the
+/// runtime exists, so a config that does not load falls back to tokio's
+/// defaults and the service reports the error once it runs.
+pub fn from_env() -> RuntimeConfig {
+    Config::from_env().map(|config| config.runtime).unwrap_or_default()
+}
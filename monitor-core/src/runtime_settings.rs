@@ -0,0 +1,52 @@
+use chrono::{DateTime, Utc};
+use crate::{db::DatabasePool, error::Result};
+
+/// Log filter override for the scheduler, set through the API and polled by the scheduler.
+pub const SCHEDULER_LOG_FILTER: &str = "scheduler.log_filter";
+
+/// Latest clock skew measurement published by the scheduler, as JSON.
+pub const SCHEDULER_CLOCK_STATUS: &str = "scheduler.clock_status";
+
+/// Build info published by each scheduler, as JSON, keyed by host after the prefix.
+pub const SCHEDULER_BUILD_INFO_PREFIX: &str = "scheduler.build_info.";
+
+pub async fn get(db: &DatabasePool, key: &str) -> Result<Option<String>> {
+    let value = sqlx::query_scalar("SELECT value FROM runtime_settings WHERE key = $1")
+        .bind(key)
+        .fetch_optional(db)
+        .await?;
+    Ok(value)
+}
+
+/// Keys starting with `prefix`, with their values and when they were last set.
+pub async fn list(db: &DatabasePool, prefix: &str) -> Result<Vec<(String, String, DateTime<Utc>)>> {
+    let rows = sqlx::query_as(
+        "SELECT key, value, updated_at FROM runtime_settings WHERE starts_with(key, $1) ORDER BY key",
+    )
+    .bind(prefix)
+    .fetch_all(db)
+    .await?;
+    Ok(rows)
+}
+
+pub async fn set(db: &DatabasePool, key: &str, value: &str) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO runtime_settings (key, value, updated_at) VALUES ($1, $2, NOW())
+        ON CONFLICT (key) DO UPDATE SET value = EXCLUDED.value, updated_at = NOW()
+        "#,
+    )
+    .bind(key)
+    .bind(value)
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+pub async fn delete(db: &DatabasePool, key: &str) -> Result<()> {
+    sqlx::query("DELETE FROM runtime_settings WHERE key = $1")
+        .bind(key)
+        .execute(db)
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,28 @@
+#[cfg(test)]
+mod runtime_tests {
+    use crate::config::RuntimeConfig;
+    use crate::runtime::*;
+
+    #[test]
+    fn runtime_uses_the_configured_workers() {
+        let config = RuntimeConfig {
+            worker_threads: 3,
+            max_blocking_threads: 4,
+        };
+        let runtime = build("monitor-test", &config).unwrap();
+        assert_eq!(runtime.metrics().num_workers(), 3);
+
+        let name = runtime.block_on(async {
+            tokio::task::spawn_blocking(|| std::thread::current().name().map(str::to_string))
+                .await
+                .unwrap()
+        });
+        assert_eq!(name.as_deref(), Some("monitor-test"));
+    }
+
+    #[test]
+    fn zero_keeps_tokio_defaults() {
+        let runtime = build("monitor-test", &RuntimeConfig::default()).unwrap();
+        assert!(runtime.metrics().num_workers() >= 1);
+    }
+}
@@ -0,0 +1,102 @@
+use serde::Serialize;
+use sqlx::Row;
+use std::collections::BTreeMap;
+use uuid::Uuid;
+use crate::{crypto::{key_version, KeyRing}, db::DatabasePool, error::Result};
+
+/// Every column holding values encrypted with the key ring, as (table, column).
+pub const ENCRYPTED_COLUMNS: &[(&str, &str)] = &[
+    ("monitors", "credentials"),
+    ("monitor_change_requests", "credentials"),
+    ("webhook_endpoints", "secret"),
+];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StaleRecord {
+    pub table: String,
+    pub id: Uuid,
+    /// Key version the record is encrypted with; `None` for plaintext.
+    pub key_version: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct KeyUsageReport {
+    pub active_version: u32,
+    pub configured_versions: Vec<u32>,
+    /// Record counts per table, keyed by `v<version>` or `plaintext`.
+    pub usage: BTreeMap<String, BTreeMap<String, i64>>,
+    pub stale_records: Vec<StaleRecord>,
+}
+
+async fn load_values(db: &DatabasePool, table: &str, column: &str) -> Result<Vec<(Uuid, String)>> {
+    let rows = sqlx::query(&format!(
+        "SELECT id, {column} AS value FROM {table} WHERE {column} IS NOT NULL"
+    ))
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| (row.get("id"), row.get("value")))
+        .collect())
+}
+
+pub async fn key_usage(db: &DatabasePool, keys: &KeyRing) -> Result<KeyUsageReport> {
+    let mut usage = BTreeMap::new();
+    let mut stale_records = Vec::new();
+
+    for (table, column) in ENCRYPTED_COLUMNS {
+        let counts: &mut BTreeMap<String, i64> = usage.entry(table.to_string()).or_default();
+
+        for (id, value) in load_values(db, table, column).await? {
+            let version = key_version(&value);
+            let label = version.map_or_else(|| "plaintext".to_string(), |v| format!("v{}", v));
+            *counts.entry(label).or_insert(0) += 1;
+
+            if keys.needs_rotation(&value) {
+                stale_records.push(StaleRecord {
+                    table: table.to_string(),
+                    id,
+                    key_version: version,
+                });
+            }
+        }
+    }
+
+    Ok(KeyUsageReport {
+        active_version: keys.active_version(),
+        configured_versions: keys.versions(),
+        usage,
+        stale_records,
+    })
+}
+
+/// Re-encrypts up to `batch_size` values per column that use a plaintext or
+/// non-active key, returning how many records were rotated.
+pub async fn reencrypt_stale(db: &DatabasePool, keys: &KeyRing, batch_size: usize) -> Result<u64> {
+    let mut rotated = 0;
+
+    for (table, column) in ENCRYPTED_COLUMNS {
+        let stale = load_values(db, table, column)
+            .await?
+            .into_iter()
+            .filter(|(_, value)| keys.needs_rotation(value))
+            .take(batch_size);
+
+        for (id, value) in stale {
+            let reencrypted = keys.encrypt(&keys.decrypt(&value)?)?;
+            // Only overwrite if the value was not changed concurrently
+            let result = sqlx::query(&format!(
+                "UPDATE {table} SET {column} = $1 WHERE id = $2 AND {column} = $3"
+            ))
+            .bind(&reencrypted)
+            .bind(id)
+            .bind(&value)
+            .execute(db)
+            .await?;
+            rotated += result.rows_affected();
+        }
+    }
+
+    Ok(rotated)
+}
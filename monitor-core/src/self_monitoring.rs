@@ -0,0 +1,156 @@
+use chrono::{DateTime, Utc};
+use reqwest::Url;
+use tracing::info;
+use uuid::Uuid;
+use crate::{
+    config::Config,
+    crypto::KeyRing,
+    databases::{self, DatabaseCheck},
+    db::DatabasePool,
+    durations::Seconds,
+    error::Result,
+    heartbeats::{self, HeartbeatCheck},
+    models::CreateMonitorRequest,
+    Error,
+};
+
+pub const API: &str = "api";
+pub const SCHEDULER: &str = "scheduler";
+pub const POSTGRES: &str = "postgres";
+pub const REDIS: &str = "redis";
+/// Tag on every system monitor, for filtering and notification routing
+pub const TAG: &str = "system";
+const INTERVAL: Seconds = Seconds::new(60);
+/// Schedulers touch their heartbeat every 30 seconds
+const SCHEDULER_GRACE_SECS: i64 = 60;
+
+/// A monitor the scheduler keeps for one part of the monitoring stack.
+#[derive(Debug, Clone)]
+pub struct SystemMonitor {
+    /// `system_key` of the monitor
+    pub key: &'static str,
+    pub request: CreateMonitorRequest,
+}
+
+/// The system monitors the config describes: the API's /health, the
+/// schedulers' heartbeat, and connect-and-query checks of Postgres and
+/// Redis. Passwords go to the monitors' credentials, never the endpoint.
+pub fn definitions(config: &Config) -> Result<Vec<SystemMonitor>> {
+    let api = CreateMonitorRequest::builder("Monitor API", health_url(&config.self_monitoring.api_url)?)
+        .expected_body_contains(r#""status":"healthy""#)
+        .interval(INTERVAL)
+        .tag(TAG)
+        .build()?;
+
+    let scheduler = CreateMonitorRequest::builder("Monitor scheduler", "heartbeat://monitor-scheduler")
+        .heartbeat_check(HeartbeatCheck {
+            grace_secs: Some(SCHEDULER_GRACE_SECS),
+        })
+        .interval(INTERVAL)
+        .tag(TAG)
+        .build()?;
+
+    let database = &config.database;
+    let mut dsn = databases::parse(&format!(
+        "postgres://{}:{}/{}",
+        database.host, database.port, database.database
+    ))?;
+    dsn.set_username(&database.username)
+        .map_err(|_| Error::validation("database.username cannot be used in a URL"))?;
+    let mut postgres = CreateMonitorRequest::builder("Monitor Postgres", dsn.to_string())
+        .database_check(DatabaseCheck::default())
+        .interval(INTERVAL)
+        .tag(TAG);
+    if !database.password.is_empty() {
+        postgres = postgres.credential(databases::PASSWORD, &database.password);
+    }
+    let postgres = postgres.build()?;
+
+    let mut url = databases::parse(&config.redis.url)?;
+    let password = url.password().map(str::to_string);
+    url.set_password(None)
+        .map_err(|_| Error::validation("redis.url cannot carry credentials"))?;
+    let mut redis = CreateMonitorRequest::builder("Monitor Redis", url.to_string())
+        .database_check(DatabaseCheck::default())
+        .interval(INTERVAL)
+        .tag(TAG);
+    if let Some(password) = password {
+        redis = redis.credential(databases::PASSWORD, password);
+    }
+    let redis = redis.build()?;
+
+    Ok(vec![
+        SystemMonitor { key: API, request: api },
+        SystemMonitor { key: SCHEDULER, request: scheduler },
+        SystemMonitor { key: POSTGRES, request: postgres },
+        SystemMonitor { key: REDIS, request: redis },
+    ])
+}
+
+fn health_url(api_url: &str) -> Result<String> {
+    let url = format!("{}/health", api_url.trim_end_matches('/'));
+    Url::parse(&url).map_err(|e| Error::validation(format!("Invalid self_monitoring.api_url: {}", e)))?;
+    Ok(url)
+}
+
+/// Creates the system monitors that are missing. Existing ones get their
+/// endpoint, credentials and check from the config again; interval,
+/// notification channels and the like stay as an admin left them. Several
+/// schedulers may run this at once.
+pub async fn ensure(db: &DatabasePool, config: &Config, keys: &KeyRing) -> Result<()> {
+    for monitor in definitions(config)? {
+        let request = &monitor.request;
+        let credentials = match &request.credentials {
+            Some(credentials) => Some(keys.encrypt(&serde_json::to_string(credentials)?)?),
+            None => None,
+        };
+        let database_check = request.database_check.as_ref().map(serde_json::to_value).transpose()?;
+        let heartbeat_check = request.heartbeat_check.as_ref().map(serde_json::to_value).transpose()?;
+        let created: bool = sqlx::query_scalar(
+            r#"
+            INSERT INTO monitors (id, name, endpoint, method, expected_status, interval, enabled, tags, credentials, expected_body_contains, database_check, heartbeat_check, system_key, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, true, $7, $8, $9, $10, $11, $12, NOW(), NOW())
+            ON CONFLICT (system_key) DO UPDATE SET
+                endpoint = EXCLUDED.endpoint,
+                credentials = EXCLUDED.credentials,
+                expected_body_contains = EXCLUDED.expected_body_contains,
+                database_check = EXCLUDED.database_check,
+                heartbeat_check = EXCLUDED.heartbeat_check,
+                updated_at = NOW()
+            RETURNING xmax = 0
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(&request.name)
+        .bind(&request.endpoint)
+        .bind(&request.method)
+        .bind(&request.expected_status)
+        .bind(request.interval)
+        .bind(&request.tags)
+        .bind(&credentials)
+        .bind(&request.expected_body_contains)
+        .bind(&database_check)
+        .bind(&heartbeat_check)
+        .bind(monitor.key)
+        .fetch_one(db)
+        .await?;
+        if created {
+            info!("Created system monitor {}", request.name);
+        }
+    }
+    Ok(())
+}
+
+/// Pings the scheduler heartbeat monitor, if there is one. It fails once no
+/// scheduler has done so for a while; with every scheduler down nothing is
+/// left to run that check, so that case needs an outside watcher.
+pub async fn report_scheduler_alive(db: &DatabasePool, at: DateTime<Utc>) -> Result<()> {
+    let monitor_id: Option<Uuid> = sqlx::query_scalar("SELECT id FROM monitors WHERE system_key = $1")
+        .bind(SCHEDULER)
+        .fetch_optional(db)
+        .await?;
+    if let Some(monitor_id) = monitor_id {
+        heartbeats::touch(db, monitor_id, at).await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,45 @@
+#[cfg(test)]
+mod self_monitoring_tests {
+    use crate::config::Config;
+    use crate::databases::PASSWORD;
+    use crate::self_monitoring::*;
+
+    fn config() -> Config {
+        let mut config = Config::from_env().unwrap();
+        config.self_monitoring.api_url = "http://monitor-api:8080/".to_string();
+        config.database.host = "db.internal".to_string();
+        config.database.port = 5433;
+        config.database.username = "monitor".to_string();
+        config.database.password = "s3cret".to_string();
+        config.database.database = "monitor".to_string();
+        config.redis.url = "redis://:hunter2@cache.internal:6379/0".to_string();
+        config
+    }
+
+    #[test]
+    fn system_monitors_cover_the_whole_stack() {
+        let monitors = definitions(&config()).unwrap();
+        let keys: Vec<_> = monitors.iter().map(|m| m.key).collect();
+        assert_eq!(keys, [API, SCHEDULER, POSTGRES, REDIS]);
+        assert!(monitors.iter().all(|m| m.request.tags == [TAG]));
+        assert!(monitors.iter().all(|m| m.request.validate().is_ok()));
+
+        let api = &monitors[0].request;
+        assert_eq!(api.endpoint, "http://monitor-api:8080/health");
+        assert!(api.expected_body_contains.is_some());
+        assert!(monitors[1].request.heartbeat_check.is_some());
+    }
+
+    #[test]
+    fn passwords_stay_out_of_endpoints() {
+        let monitors = definitions(&config()).unwrap();
+
+        let postgres = &monitors[2].request;
+        assert_eq!(postgres.endpoint, "postgres://monitor@db.internal:5433/monitor");
+        assert_eq!(postgres.credentials.as_ref().unwrap()[PASSWORD], "s3cret");
+
+        let redis = &monitors[3].request;
+        assert_eq!(redis.endpoint, "redis://cache.internal:6379/0");
+        assert_eq!(redis.credentials.as_ref().unwrap()[PASSWORD], "hunter2");
+    }
+}
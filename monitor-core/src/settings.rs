@@ -0,0 +1,189 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use std::collections::BTreeMap;
+use uuid::Uuid;
+use crate::{db::DatabasePool, durations::Seconds, error::Result, models::Monitor, Error};
+
+pub const GLOBAL_SCOPE: &str = "global";
+const TAG_SCOPE_PREFIX: &str = "tag:";
+
+/// Used when neither the monitor nor any default sets a timeout.
+pub const DEFAULT_TIMEOUT: Seconds = Seconds::new(30);
+
+/// Pause between attempts when the monitor does not set `retry_delay_ms`.
+pub const DEFAULT_RETRY_DELAY_MS: i32 = 2000;
+
+/// Inheritable monitor settings; `None` defers to the next level down.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, FromRow)]
+pub struct SettingsOverride {
+    pub timeout: Option<Seconds>,
+    /// Extra attempts before a failed check is recorded
+    pub retries: Option<i32>,
+    /// Webhook endpoints that receive results instead of the monitor's own
+    pub notification_channels: Option<Vec<Uuid>>,
+    pub script_profile: Option<String>,
+}
+
+impl SettingsOverride {
+    pub fn from_monitor(monitor: &Monitor) -> Self {
+        Self {
+            timeout: monitor.timeout,
+            retries: monitor.retries,
+            notification_channels: monitor.notification_channels.clone(),
+            script_profile: monitor.script_profile.clone(),
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.timeout.is_some_and(|t| !t.is_positive()) {
+            return Err(Error::validation("timeout must be positive"));
+        }
+        if self.retries.is_some_and(|r| !(0..=10).contains(&r)) {
+            return Err(Error::validation("retries must be between 0 and 10"));
+        }
+        Ok(())
+    }
+}
+
+/// Settings a check actually runs with, and the scope each one came from.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EffectiveSettings {
+    pub timeout: Seconds,
+    pub retries: i32,
+    pub notification_channels: Vec<Uuid>,
+    pub script_profile: String,
+    /// Setting name to `default`, `global`, `tag:<name>` or `monitor`
+    pub sources: BTreeMap<&'static str, String>,
+}
+
+impl EffectiveSettings {
+    /// Built-in values, used below the global scope.
+    pub fn builtin(script_profile: &str) -> Self {
+        let sources = ["timeout", "retries", "notification_channels", "script_profile"]
+            .into_iter()
+            .map(|name| (name, "default".to_string()))
+            .collect();
+        Self {
+            timeout: DEFAULT_TIMEOUT,
+            retries: 0,
+            notification_channels: Vec::new(),
+            script_profile: script_profile.to_string(),
+            sources,
+        }
+    }
+
+    fn apply(&mut self, layer: &SettingsOverride, source: &str) {
+        if let Some(timeout) = layer.timeout {
+            self.timeout = timeout;
+            self.sources.insert("timeout", source.to_string());
+        }
+        if let Some(retries) = layer.retries {
+            self.retries = retries;
+            self.sources.insert("retries", source.to_string());
+        }
+        if let Some(channels) = &layer.notification_channels {
+            self.notification_channels = channels.clone();
+            self.sources.insert("notification_channels", source.to_string());
+        }
+        if let Some(profile) = &layer.script_profile {
+            self.script_profile = profile.clone();
+            self.sources.insert("script_profile", source.to_string());
+        }
+    }
+}
+
+pub fn tag_scope(tag: &str) -> String {
+    format!("{}{}", TAG_SCOPE_PREFIX, tag)
+}
+
+/// Layers settings from lowest to highest precedence: built-in, global, tags,
+/// monitor. `tags` is in the monitor's tag order and earlier tags win.
+pub fn resolve(
+    builtin: EffectiveSettings,
+    global: Option<&SettingsOverride>,
+    tags: &[(String, SettingsOverride)],
+    monitor: &SettingsOverride,
+) -> EffectiveSettings {
+    let mut settings = builtin;
+    if let Some(global) = global {
+        settings.apply(global, GLOBAL_SCOPE);
+    }
+    for (tag, layer) in tags.iter().rev() {
+        settings.apply(layer, &tag_scope(tag));
+    }
+    settings.apply(monitor, "monitor");
+    settings
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct ScopedDefaults {
+    pub scope: String,
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    pub settings: SettingsOverride,
+}
+
+pub async fn list_defaults(db: &DatabasePool) -> Result<Vec<ScopedDefaults>> {
+    let rows = sqlx::query_as::<_, ScopedDefaults>(
+        "SELECT scope, timeout, retries, notification_channels, script_profile FROM settings_defaults ORDER BY scope",
+    )
+    .fetch_all(db)
+    .await?;
+    Ok(rows)
+}
+
+pub async fn set_defaults(db: &DatabasePool, scope: &str, settings: &SettingsOverride) -> Result<()> {
+    settings.validate()?;
+    sqlx::query(
+        r#"
+        INSERT INTO settings_defaults (scope, timeout, retries, notification_channels, script_profile, updated_at)
+        VALUES ($1, $2, $3, $4, $5, NOW())
+        ON CONFLICT (scope) DO UPDATE
+        SET timeout = EXCLUDED.timeout, retries = EXCLUDED.retries,
+            notification_channels = EXCLUDED.notification_channels,
+            script_profile = EXCLUDED.script_profile, updated_at = NOW()
+        "#,
+    )
+    .bind(scope)
+    .bind(settings.timeout)
+    .bind(settings.retries)
+    .bind(&settings.notification_channels)
+    .bind(&settings.script_profile)
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/// Returns whether the scope had defaults.
+pub async fn delete_defaults(db: &DatabasePool, scope: &str) -> Result<bool> {
+    let result = sqlx::query("DELETE FROM settings_defaults WHERE scope = $1")
+        .bind(scope)
+        .execute(db)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Resolves the settings a monitor's checks run with.
+pub async fn effective_for(db: &DatabasePool, monitor: &Monitor, builtin: EffectiveSettings) -> Result<EffectiveSettings> {
+    let scopes: Vec<String> = std::iter::once(GLOBAL_SCOPE.to_string())
+        .chain(monitor.tags.iter().map(|tag| tag_scope(tag)))
+        .collect();
+    let mut defaults: BTreeMap<String, SettingsOverride> = sqlx::query_as::<_, ScopedDefaults>(
+        "SELECT scope, timeout, retries, notification_channels, script_profile FROM settings_defaults WHERE scope = ANY($1)",
+    )
+    .bind(&scopes)
+    .fetch_all(db)
+    .await?
+    .into_iter()
+    .map(|row| (row.scope, row.settings))
+    .collect();
+
+    let global = defaults.remove(GLOBAL_SCOPE);
+    let tags: Vec<(String, SettingsOverride)> = monitor
+        .tags
+        .iter()
+        .filter_map(|tag| defaults.remove(&tag_scope(tag)).map(|layer| (tag.clone(), layer)))
+        .collect();
+
+    Ok(resolve(builtin, global.as_ref(), &tags, &SettingsOverride::from_monitor(monitor)))
+}
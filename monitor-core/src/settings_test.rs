@@ -0,0 +1,66 @@
+#[cfg(test)]
+mod settings_tests {
+    use crate::{durations::Seconds, settings::*};
+    use uuid::Uuid;
+
+    #[test]
+    fn test_builtin_defaults_apply_without_overrides() {
+        let settings = resolve(EffectiveSettings::builtin("default"), None, &[], &SettingsOverride::default());
+        assert_eq!(settings.timeout, DEFAULT_TIMEOUT);
+        assert_eq!(settings.retries, 0);
+        assert!(settings.notification_channels.is_empty());
+        assert_eq!(settings.script_profile, "default");
+        assert!(settings.sources.values().all(|source| source == "default"));
+    }
+
+    #[test]
+    fn test_precedence_global_then_tags_then_monitor() {
+        let pager = Uuid::new_v4();
+        let global = SettingsOverride {
+            timeout: Some(Seconds::new(20)),
+            retries: Some(1),
+            script_profile: Some("strict".to_string()),
+            ..Default::default()
+        };
+        let tags = vec![
+            (
+                "prod".to_string(),
+                SettingsOverride {
+                    retries: Some(3),
+                    notification_channels: Some(vec![pager]),
+                    ..Default::default()
+                },
+            ),
+            (
+                "internal".to_string(),
+                SettingsOverride {
+                    retries: Some(0),
+                    timeout: Some(Seconds::new(5)),
+                    ..Default::default()
+                },
+            ),
+        ];
+        let monitor = SettingsOverride {
+            timeout: Some(Seconds::new(10)),
+            ..Default::default()
+        };
+
+        let settings = resolve(EffectiveSettings::builtin("default"), Some(&global), &tags, &monitor);
+        assert_eq!(settings.timeout, Seconds::new(10));
+        assert_eq!(settings.sources["timeout"], "monitor");
+        // The first matching tag wins over later ones
+        assert_eq!(settings.retries, 3);
+        assert_eq!(settings.sources["retries"], "tag:prod");
+        assert_eq!(settings.notification_channels, vec![pager]);
+        assert_eq!(settings.script_profile, "strict");
+        assert_eq!(settings.sources["script_profile"], "global");
+    }
+
+    #[test]
+    fn test_validate_rejects_out_of_range_values() {
+        assert!(SettingsOverride { timeout: Some(Seconds::new(0)), ..Default::default() }.validate().is_err());
+        assert!(SettingsOverride { retries: Some(11), ..Default::default() }.validate().is_err());
+        let valid = SettingsOverride { retries: Some(2), timeout: Some(Seconds::new(15)), ..Default::default() };
+        assert!(valid.validate().is_ok());
+    }
+}
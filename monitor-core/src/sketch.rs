@@ -0,0 +1,77 @@
+use hdrhistogram::{
+    serialization::{Deserializer, Serializer, V2DeflateSerializer},
+    Histogram,
+};
+use crate::{error::Result, Error};
+
+/// Largest latency tracked precisely; slower responses are clamped to it.
+const MAX_LATENCY_MS: u64 = 3_600_000;
+const SIGNIFICANT_DIGITS: u8 = 2;
+
+/// Mergeable latency distribution (HdrHistogram, 1% precision) stored in rollups.
+#[derive(Debug, Clone)]
+pub struct LatencySketch {
+    histogram: Histogram<u64>,
+}
+
+impl Default for LatencySketch {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LatencySketch {
+    pub fn new() -> Self {
+        Self {
+            histogram: Histogram::new_with_bounds(1, MAX_LATENCY_MS, SIGNIFICANT_DIGITS)
+                .expect("static histogram bounds are valid"),
+        }
+    }
+
+    pub fn record(&mut self, latency_ms: i64) {
+        self.histogram.saturating_record(latency_ms.max(0) as u64);
+    }
+
+    pub fn merge(&mut self, other: &LatencySketch) {
+        // Both sides share the same bounds, so adding cannot fail
+        let _ = self.histogram.add(&other.histogram);
+    }
+
+    pub fn count(&self) -> u64 {
+        self.histogram.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.histogram.is_empty()
+    }
+
+    /// Latency at quantile `q` (0.0 - 1.0), or `None` without samples.
+    pub fn quantile(&self, q: f64) -> Option<f64> {
+        (!self.is_empty()).then(|| self.histogram.value_at_quantile(q) as f64)
+    }
+
+    pub fn max(&self) -> Option<f64> {
+        (!self.is_empty()).then(|| self.histogram.max() as f64)
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+        V2DeflateSerializer::new()
+            .serialize(&self.histogram, &mut bytes)
+            .map_err(|e| Error::internal(format!("Failed to serialize latency sketch: {:?}", e)))?;
+        Ok(bytes)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let stored: Histogram<u64> = Deserializer::new()
+            .deserialize(&mut std::io::Cursor::new(bytes))
+            .map_err(|e| Error::internal(format!("Corrupt latency sketch: {:?}", e)))?;
+
+        let mut sketch = Self::new();
+        sketch
+            .histogram
+            .add(&stored)
+            .map_err(|e| Error::internal(format!("Incompatible latency sketch: {:?}", e)))?;
+        Ok(sketch)
+    }
+}
@@ -0,0 +1,41 @@
+#[cfg(test)]
+mod sketch_tests {
+    use crate::sketch::*;
+
+    #[test]
+    fn test_merged_percentiles_match_combined_data() {
+        // A fast hour and a slow hour: averaging per-hour p95s would give ~505ms
+        let mut fast = LatencySketch::new();
+        let mut slow = LatencySketch::new();
+        for i in 0..1000 {
+            fast.record(10 + i % 5);
+        }
+        for i in 0..100 {
+            slow.record(1000 + i);
+        }
+
+        let mut merged = fast.clone();
+        merged.merge(&slow);
+
+        assert_eq!(merged.count(), 1100);
+        let p95 = merged.quantile(0.95).unwrap();
+        assert!((1040.0..=1060.0).contains(&p95), "p95 was {}", p95);
+        assert!(merged.quantile(0.5).unwrap() < 20.0);
+        assert!(merged.max().unwrap() >= 1099.0);
+    }
+
+    #[test]
+    fn test_serialization_roundtrip() {
+        let mut sketch = LatencySketch::new();
+        for latency in [0, 5, 120, 120, 4_000, 10_000_000] {
+            sketch.record(latency);
+        }
+
+        let restored = LatencySketch::from_bytes(&sketch.to_bytes().unwrap()).unwrap();
+        assert_eq!(restored.count(), 6);
+        assert_eq!(restored.quantile(0.5), sketch.quantile(0.5));
+        assert_eq!(restored.max(), sketch.max());
+        assert!(LatencySketch::new().quantile(0.95).is_none());
+        assert!(LatencySketch::from_bytes(b"garbage").is_err());
+    }
+}
@@ -0,0 +1,853 @@
+use aes::{
+    cipher::{generic_array::GenericArray, BlockEncrypt, KeyInit},
+    Aes128,
+};
+use hmac::{Hmac, Mac};
+use md5::Md5;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+use crate::{
+    error::Result,
+    openmetrics::{MetricAssertion, Sample},
+    Error,
+};
+
+pub const DEFAULT_PORT: u16 = 161;
+const MAX_OIDS: usize = 32;
+/// Largest message this side accepts, announced in v3 headers
+pub const MAX_MESSAGE_SIZE: usize = 65_507;
+/// RFC 3414 asks for passwords of at least eight characters
+const MIN_PASSWORD_LEN: usize = 8;
+
+/// Credential keys the check reads from the monitor's `credentials`.
+pub const COMMUNITY: &str = "community";
+pub const AUTH_PASSWORD: &str = "auth_password";
+pub const PRIV_PASSWORD: &str = "priv_password";
+/// Used by v2c checks without a community credential
+pub const DEFAULT_COMMUNITY: &str = "public";
+
+const INTEGER: u8 = 0x02;
+const OCTET_STRING: u8 = 0x04;
+const NULL: u8 = 0x05;
+const OBJECT_IDENTIFIER: u8 = 0x06;
+const SEQUENCE: u8 = 0x30;
+const IP_ADDRESS: u8 = 0x40;
+const COUNTER32: u8 = 0x41;
+const GAUGE32: u8 = 0x42;
+const TIMETICKS: u8 = 0x43;
+const COUNTER64: u8 = 0x46;
+const NO_SUCH_OBJECT: u8 = 0x80;
+const NO_SUCH_INSTANCE: u8 = 0x81;
+const END_OF_MIB_VIEW: u8 = 0x82;
+const GET_REQUEST: u8 = 0xA0;
+const RESPONSE: u8 = 0xA2;
+const REPORT: u8 = 0xA8;
+
+const FLAG_AUTH: u8 = 0x01;
+const FLAG_PRIV: u8 = 0x02;
+const FLAG_REPORTABLE: u8 = 0x04;
+/// The user-based security model
+const USM: i64 = 3;
+const AUTH_PARAMS_LEN: usize = 12;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SnmpVersion {
+    V2c,
+    V3,
+}
+
+/// HMAC-MD5-96 or HMAC-SHA-96.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuthProtocol {
+    Md5,
+    Sha,
+}
+
+/// AES-128 in CFB mode, as in RFC 3826.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PrivacyProtocol {
+    Aes,
+}
+
+/// An SNMP check run instead of the HTTP request: GETs the named OIDs from
+/// the agent at the monitor's `endpoint`, `snmp://host[:port]`, and holds the
+/// values to the assertions. The community string and v3 passwords come from
+/// the monitor's credentials.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnmpCheck {
+    pub version: SnmpVersion,
+    /// OIDs to poll by the name assertions use, e.g.
+    /// `"if_oper_status": "1.3.6.1.2.1.2.2.1.8.1"`
+    pub oids: BTreeMap<String, String>,
+    /// Conditions such as `if_oper_status == 1` or `cpu_idle > 10`
+    #[serde(default)]
+    pub assertions: Vec<MetricAssertion>,
+    /// v3 security name
+    pub username: Option<String>,
+    /// v3 only; without it messages are neither signed nor encrypted
+    pub auth_protocol: Option<AuthProtocol>,
+    /// v3 only, and needs `auth_protocol`
+    pub privacy_protocol: Option<PrivacyProtocol>,
+}
+
+impl SnmpCheck {
+    pub fn validate(&self, endpoint: &str) -> Result<()> {
+        agent_address(endpoint)?;
+        if self.oids.is_empty() || self.oids.len() > MAX_OIDS {
+            return Err(Error::validation(format!("oids must name between 1 and {} OIDs", MAX_OIDS)));
+        }
+        for (name, oid) in &self.oids {
+            if !valid_name(name) {
+                return Err(Error::validation(format!(
+                    "OID name '{}' must be letters, digits and underscores, not starting with a digit",
+                    name
+                )));
+            }
+            parse_oid(oid)?;
+        }
+        if let Some(assertion) = self
+            .assertions
+            .iter()
+            .find(|a| !a.labels.is_empty() || !self.oids.contains_key(&a.metric))
+        {
+            return Err(Error::validation(format!(
+                "assertion '{}' must use one of the names in oids, without labels",
+                assertion
+            )));
+        }
+        match self.version {
+            SnmpVersion::V2c
+                if self.username.is_some() || self.auth_protocol.is_some() || self.privacy_protocol.is_some() =>
+            {
+                Err(Error::validation("username, auth_protocol and privacy_protocol only apply to SNMPv3"))
+            }
+            SnmpVersion::V3 if self.username.as_deref().is_none_or(|name| name.trim().is_empty()) => {
+                Err(Error::validation("SNMPv3 checks need a username"))
+            }
+            SnmpVersion::V3 if self.privacy_protocol.is_some() && self.auth_protocol.is_none() => {
+                Err(Error::validation("privacy_protocol needs an auth_protocol"))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// The OIDs to request, in the order of their names.
+    pub fn object_ids(&self) -> Result<Vec<Vec<u32>>> {
+        self.oids.values().map(|oid| parse_oid(oid)).collect()
+    }
+
+    /// Names the agent's variable bindings; OIDs the check did not ask for are dropped.
+    pub fn named(&self, bindings: Vec<(Vec<u32>, SnmpValue)>) -> BTreeMap<String, SnmpValue> {
+        let names: HashMap<Vec<u32>, &String> = self
+            .oids
+            .iter()
+            .filter_map(|(name, oid)| Some((parse_oid(oid).ok()?, name)))
+            .collect();
+        bindings
+            .into_iter()
+            .filter_map(|(oid, value)| Some((names.get(&oid)?.to_string(), value)))
+            .collect()
+    }
+
+    /// `Err` carries the result's error message for the first assertion that
+    /// does not hold. Text values and missing objects never satisfy one.
+    pub fn evaluate(&self, values: &BTreeMap<String, SnmpValue>) -> std::result::Result<(), String> {
+        let samples: Vec<Sample> = values
+            .iter()
+            .filter_map(|(name, value)| match value {
+                SnmpValue::Number(value) => Some(Sample {
+                    name: name.clone(),
+                    labels: BTreeMap::new(),
+                    value: *value,
+                }),
+                _ => None,
+            })
+            .collect();
+        self.assertions.iter().try_for_each(|assertion| assertion.evaluate(&samples))
+    }
+}
+
+fn valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// `host:port` of an `snmp://` endpoint.
+pub fn agent_address(endpoint: &str) -> Result<String> {
+    let url = Url::parse(endpoint).map_err(|e| Error::validation(format!("Invalid SNMP endpoint: {}", e)))?;
+    match (url.scheme(), url.host_str()) {
+        ("snmp", Some(host)) => Ok(format!("{}:{}", host, url.port().unwrap_or(DEFAULT_PORT))),
+        _ => Err(Error::validation("snmp_check endpoints must be snmp://host[:port] URLs")),
+    }
+}
+
+/// Reads dotted notation such as `1.3.6.1.2.1.1.3.0`; a leading dot is allowed.
+pub fn parse_oid(oid: &str) -> Result<Vec<u32>> {
+    let invalid = || {
+        Error::validation(format!("Invalid OID '{}': expected dotted numbers such as 1.3.6.1.2.1.1.3.0", oid))
+    };
+    let arcs = oid
+        .strip_prefix('.')
+        .unwrap_or(oid)
+        .split('.')
+        .map(|arc| arc.parse::<u32>().map_err(|_| invalid()))
+        .collect::<Result<Vec<u32>>>()?;
+    if arcs.len() < 2 || arcs.len() > 128 || arcs[0] > 2 || (arcs[0] < 2 && arcs[1] >= 40) {
+        return Err(invalid());
+    }
+    Ok(arcs)
+}
+
+pub fn format_oid(arcs: &[u32]) -> String {
+    arcs.iter().map(u32::to_string).collect::<Vec<_>>().join(".")
+}
+
+/// A polled value as kept in the result body.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum SnmpValue {
+    /// Integers, counters, gauges and time ticks
+    Number(f64),
+    /// Octet strings, OIDs and IP addresses; binary strings are hex-encoded
+    Text(String),
+    /// The agent has no such object
+    Missing,
+}
+
+/// The authoritative engine of a v3 agent, learned by discovery.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Engine {
+    pub id: Vec<u8>,
+    pub boots: u32,
+    pub time: u32,
+}
+
+/// Builds the requests of one poll and reads the agent's answers. A v3
+/// session first sends the `discovery_request` and passes the report to
+/// `discover`.
+pub struct Session {
+    security: Security,
+    request_id: i32,
+    message_id: i32,
+}
+
+enum Security {
+    Community(String),
+    User(Box<User>),
+}
+
+struct User {
+    name: String,
+    auth: Option<(AuthProtocol, String)>,
+    privacy: Option<String>,
+    engine: Option<Engine>,
+    auth_key: Vec<u8>,
+    priv_key: Vec<u8>,
+}
+
+impl Session {
+    /// Reads the community or the v3 passwords from the monitor's credentials.
+    pub fn new(check: &SnmpCheck, credentials: &HashMap<String, String>) -> Result<Self> {
+        let security = match check.version {
+            SnmpVersion::V2c => Security::Community(
+                credentials.get(COMMUNITY).cloned().unwrap_or_else(|| DEFAULT_COMMUNITY.to_string()),
+            ),
+            SnmpVersion::V3 => {
+                let password = |key: &str| match credentials.get(key) {
+                    Some(password) if password.len() >= MIN_PASSWORD_LEN => Ok(password.clone()),
+                    _ => Err(Error::validation(format!(
+                        "this SNMPv3 check needs a {} of at least {} characters in the monitor's credentials",
+                        key, MIN_PASSWORD_LEN
+                    ))),
+                };
+                let auth = match check.auth_protocol {
+                    Some(protocol) => Some((protocol, password(AUTH_PASSWORD)?)),
+                    None => None,
+                };
+                let privacy = match check.privacy_protocol {
+                    Some(_) => Some(password(PRIV_PASSWORD)?),
+                    None => None,
+                };
+                Security::User(Box::new(User {
+                    name: check.username.clone().unwrap_or_default(),
+                    auth,
+                    privacy,
+                    engine: None,
+                    auth_key: Vec::new(),
+                    priv_key: Vec::new(),
+                }))
+            }
+        };
+        // Random starting ids keep a late answer to an earlier poll from matching
+        let seed = Uuid::new_v4().as_u128();
+        Ok(Self {
+            security,
+            request_id: (seed as i32) & i32::MAX,
+            message_id: ((seed >> 32) as i32) & i32::MAX,
+        })
+    }
+
+    pub fn needs_discovery(&self) -> bool {
+        matches!(&self.security, Security::User(user) if user.engine.is_none())
+    }
+
+    /// An unauthenticated v3 request the agent answers with a report naming its engine.
+    pub fn discovery_request(&mut self) -> Vec<u8> {
+        self.next_ids();
+        let scoped = sequence(SEQUENCE, &[octets(b""), octets(b""), get_pdu(self.request_id, &[])]);
+        let security = usm_parameters(&Engine::default(), "", &[], &[]);
+        v3_message(self.message_id, FLAG_REPORTABLE, security, scoped)
+    }
+
+    /// Learns the engine from the report to `discovery_request` and localizes the keys to it.
+    pub fn discover(&mut self, response: &[u8]) -> Result<()> {
+        let message = read_v3(response)?;
+        if message.id != self.message_id as i64 {
+            return Err(Error::internal("Unexpected SNMP message id in the discovery report"));
+        }
+        if message.engine.id.is_empty() {
+            return Err(Error::internal("The agent did not report its engine id"));
+        }
+        let Security::User(user) = &mut self.security else {
+            return Ok(());
+        };
+        if let Some((protocol, password)) = &user.auth {
+            user.auth_key = password_to_key(*protocol, password.as_bytes(), &message.engine.id);
+            if let Some(password) = &user.privacy {
+                // RFC 3826 localizes with the authentication hash and uses the first 16 bytes
+                user.priv_key = password_to_key(*protocol, password.as_bytes(), &message.engine.id);
+                user.priv_key.truncate(16);
+            }
+        }
+        user.engine = Some(message.engine);
+        Ok(())
+    }
+
+    /// A GetRequest for `oids`.
+    pub fn get_request(&mut self, oids: &[Vec<u32>]) -> Result<Vec<u8>> {
+        self.next_ids();
+        let pdu = get_pdu(self.request_id, oids);
+        let user = match &self.security {
+            Security::Community(community) => {
+                return Ok(sequence(SEQUENCE, &[integer(1), octets(community.as_bytes()), pdu]));
+            }
+            Security::User(user) => user,
+        };
+        let engine = user.engine.as_ref().ok_or_else(|| Error::internal("SNMPv3 engine not discovered"))?;
+
+        let scoped = sequence(SEQUENCE, &[octets(&engine.id), octets(b""), pdu]);
+        let mut flags = FLAG_REPORTABLE;
+        let (data, salt) = if user.privacy.is_some() {
+            flags |= FLAG_PRIV;
+            let salt = Uuid::new_v4().as_u128().to_be_bytes()[..8].to_vec();
+            let encrypted = aes_cfb(&user.priv_key, privacy_iv(engine, &salt)?, &scoped, true);
+            (octets(&encrypted), salt)
+        } else {
+            (scoped, Vec::new())
+        };
+        let Some((protocol, _)) = user.auth else {
+            let security = usm_parameters(engine, &user.name, &[], &[]);
+            return Ok(v3_message(self.message_id, flags, security, data));
+        };
+
+        // Signed with zeroed parameters, then built again with the digest in their place
+        flags |= FLAG_AUTH;
+        let build = |auth: &[u8]| {
+            v3_message(self.message_id, flags, usm_parameters(engine, &user.name, auth, &salt), data.clone())
+        };
+        let digest = hmac_96(protocol, &user.auth_key, &build(&[0; AUTH_PARAMS_LEN]));
+        Ok(build(&digest))
+    }
+
+    /// The variable bindings of the agent's answer to the last `get_request`.
+    pub fn read_response(&self, response: &[u8]) -> Result<Vec<(Vec<u32>, SnmpValue)>> {
+        let pdu = match &self.security {
+            Security::Community(_) => {
+                let mut message = Reader::new(Reader::new(response).expect(SEQUENCE)?);
+                if message.integer()? != 1 {
+                    return Err(Error::internal("Not an SNMPv2c message"));
+                }
+                message.octets()?;
+                let (tag, content) = message.next()?;
+                read_pdu(tag, content)?
+            }
+            Security::User(user) => user.read_response(response, self.message_id)?,
+        };
+        pdu.into_bindings(self.request_id)
+    }
+
+    fn next_ids(&mut self) {
+        self.request_id = self.request_id.wrapping_add(1) & i32::MAX;
+        self.message_id = self.message_id.wrapping_add(1) & i32::MAX;
+    }
+}
+
+impl User {
+    fn read_response(&self, response: &[u8], message_id: i32) -> Result<Pdu> {
+        let message = read_v3(response)?;
+        if message.id != message_id as i64 {
+            return Err(Error::internal("Unexpected SNMP message id"));
+        }
+        let authenticated = message.flags & FLAG_AUTH != 0;
+        if authenticated && let Some((protocol, _)) = self.auth {
+            // The digest covers the whole message with its own field zeroed
+            let offset = message.auth.as_ptr() as usize - response.as_ptr() as usize;
+            let mut zeroed = response.to_vec();
+            zeroed[offset..offset + message.auth.len()].fill(0);
+            if hmac_96(protocol, &self.auth_key, &zeroed) != message.auth {
+                return Err(Error::auth("The SNMP response failed authentication"));
+            }
+        }
+
+        let plaintext;
+        let scoped = if message.flags & FLAG_PRIV != 0 {
+            let (tag, content) = message.data;
+            if tag != OCTET_STRING || self.privacy.is_none() {
+                return Err(malformed());
+            }
+            plaintext = aes_cfb(&self.priv_key, privacy_iv(&message.engine, message.privacy)?, content, false);
+            Reader::new(&plaintext).expect(SEQUENCE)?
+        } else if message.data.0 == SEQUENCE {
+            message.data.1
+        } else {
+            return Err(malformed());
+        };
+        let mut scoped = Reader::new(scoped);
+        scoped.octets()?;
+        scoped.octets()?;
+        let (tag, content) = scoped.next()?;
+        let pdu = read_pdu(tag, content)?;
+        // Reports about a failed request are sent without authentication
+        if self.auth.is_some() && !authenticated && pdu.tag != REPORT {
+            return Err(Error::auth("The agent answered without authentication"));
+        }
+        Ok(pdu)
+    }
+}
+
+/// Key localization from RFC 3414 A.2: the password repeated to one megabyte
+/// is hashed, and the digest hashed again around the engine id.
+pub fn password_to_key(protocol: AuthProtocol, password: &[u8], engine_id: &[u8]) -> Vec<u8> {
+    const STREAM_LEN: usize = 1_048_576;
+    let mut stream = Vec::with_capacity(STREAM_LEN + password.len());
+    while stream.len() < STREAM_LEN && !password.is_empty() {
+        stream.extend_from_slice(password);
+    }
+    stream.truncate(STREAM_LEN);
+    let key = digest(protocol, &stream);
+    digest(protocol, &[key.as_slice(), engine_id, key.as_slice()].concat())
+}
+
+fn digest(protocol: AuthProtocol, data: &[u8]) -> Vec<u8> {
+    match protocol {
+        AuthProtocol::Md5 => Md5::digest(data).to_vec(),
+        AuthProtocol::Sha => Sha1::digest(data).to_vec(),
+    }
+}
+
+/// The first 12 bytes of the message's HMAC.
+fn hmac_96(protocol: AuthProtocol, key: &[u8], message: &[u8]) -> Vec<u8> {
+    let mut digest = match protocol {
+        AuthProtocol::Md5 => {
+            let mut mac = <Hmac<Md5> as Mac>::new_from_slice(key).expect("HMAC accepts keys of any length");
+            mac.update(message);
+            mac.finalize().into_bytes().to_vec()
+        }
+        AuthProtocol::Sha => {
+            let mut mac = <Hmac<Sha1> as Mac>::new_from_slice(key).expect("HMAC accepts keys of any length");
+            mac.update(message);
+            mac.finalize().into_bytes().to_vec()
+        }
+    };
+    digest.truncate(AUTH_PARAMS_LEN);
+    digest
+}
+
+/// Engine boots, engine time and the 8-byte salt sent as privacy parameters.
+fn privacy_iv(engine: &Engine, salt: &[u8]) -> Result<[u8; 16]> {
+    if salt.len() != 8 {
+        return Err(malformed());
+    }
+    let mut iv = [0; 16];
+    iv[..4].copy_from_slice(&engine.boots.to_be_bytes());
+    iv[4..8].copy_from_slice(&engine.time.to_be_bytes());
+    iv[8..].copy_from_slice(salt);
+    Ok(iv)
+}
+
+/// AES-128 in 128-bit cipher feedback mode, which needs no padding.
+pub fn aes_cfb(key: &[u8], iv: [u8; 16], data: &[u8], encrypt: bool) -> Vec<u8> {
+    let cipher = Aes128::new(GenericArray::from_slice(&key[..16]));
+    let mut feedback = iv;
+    let mut output = Vec::with_capacity(data.len());
+    for chunk in data.chunks(16) {
+        let mut keystream = GenericArray::from(feedback);
+        cipher.encrypt_block(&mut keystream);
+        let start = output.len();
+        output.extend(chunk.iter().zip(keystream.iter()).map(|(byte, key)| byte ^ key));
+        let ciphertext = if encrypt { &output[start..] } else { chunk };
+        feedback[..ciphertext.len()].copy_from_slice(ciphertext);
+    }
+    output
+}
+
+fn tlv(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut encoded = vec![tag];
+    if content.len() < 0x80 {
+        encoded.push(content.len() as u8);
+    } else {
+        let length = (content.len() as u32).to_be_bytes();
+        let skip = length.iter().take_while(|b| **b == 0).count();
+        encoded.push(0x80 | (length.len() - skip) as u8);
+        encoded.extend_from_slice(&length[skip..]);
+    }
+    encoded.extend_from_slice(content);
+    encoded
+}
+
+fn sequence(tag: u8, elements: &[Vec<u8>]) -> Vec<u8> {
+    tlv(tag, &elements.concat())
+}
+
+fn octets(value: &[u8]) -> Vec<u8> {
+    tlv(OCTET_STRING, value)
+}
+
+/// The shortest two's complement form.
+fn integer(value: i64) -> Vec<u8> {
+    let bytes = value.to_be_bytes();
+    let mut start = 0;
+    while start < bytes.len() - 1
+        && ((bytes[start] == 0 && bytes[start + 1] & 0x80 == 0)
+            || (bytes[start] == 0xFF && bytes[start + 1] & 0x80 != 0))
+    {
+        start += 1;
+    }
+    tlv(INTEGER, &bytes[start..])
+}
+
+pub fn encode_oid(arcs: &[u32]) -> Vec<u8> {
+    let mut content = Vec::new();
+    let mut push = |value: u64| {
+        let mut groups = vec![(value & 0x7F) as u8];
+        let mut rest = value >> 7;
+        while rest > 0 {
+            groups.push((rest & 0x7F) as u8 | 0x80);
+            rest >>= 7;
+        }
+        content.extend(groups.iter().rev());
+    };
+    // The first two arcs share one subidentifier
+    push(arcs[0] as u64 * 40 + arcs.get(1).copied().unwrap_or(0) as u64);
+    arcs.iter().skip(2).for_each(|arc| push(*arc as u64));
+    tlv(OBJECT_IDENTIFIER, &content)
+}
+
+fn decode_oid(content: &[u8]) -> Result<Vec<u32>> {
+    if content.last().is_none_or(|last| last & 0x80 != 0) {
+        return Err(malformed());
+    }
+    let mut arcs = Vec::new();
+    let mut value: u64 = 0;
+    for byte in content {
+        value = value << 7 | (byte & 0x7F) as u64;
+        if value > u32::MAX as u64 + 80 {
+            return Err(malformed());
+        }
+        if byte & 0x80 != 0 {
+            continue;
+        }
+        if arcs.is_empty() {
+            let first = (value / 40).min(2);
+            arcs.push(first as u32);
+            arcs.push(u32::try_from(value - first * 40).map_err(|_| malformed())?);
+        } else {
+            arcs.push(u32::try_from(value).map_err(|_| malformed())?);
+        }
+        value = 0;
+    }
+    Ok(arcs)
+}
+
+fn decode_signed(content: &[u8]) -> Result<i64> {
+    if content.is_empty() || content.len() > 8 {
+        return Err(malformed());
+    }
+    let sign = if content[0] & 0x80 != 0 { -1 } else { 0 };
+    Ok(content.iter().fold(sign, |value, byte| value << 8 | *byte as i64))
+}
+
+/// Counters may carry a leading zero byte to stay positive.
+fn decode_unsigned(content: &[u8]) -> Result<u64> {
+    let content = match content {
+        [0, rest @ ..] if !rest.is_empty() => rest,
+        _ => content,
+    };
+    if content.is_empty() || content.len() > 8 {
+        return Err(malformed());
+    }
+    Ok(content.iter().fold(0, |value, byte| value << 8 | *byte as u64))
+}
+
+fn decode_value(tag: u8, content: &[u8]) -> Result<SnmpValue> {
+    Ok(match tag {
+        INTEGER => SnmpValue::Number(decode_signed(content)? as f64),
+        COUNTER32 | GAUGE32 | TIMETICKS | COUNTER64 => SnmpValue::Number(decode_unsigned(content)? as f64),
+        OBJECT_IDENTIFIER => SnmpValue::Text(format_oid(&decode_oid(content)?)),
+        IP_ADDRESS if content.len() == 4 => {
+            SnmpValue::Text(content.iter().map(u8::to_string).collect::<Vec<_>>().join("."))
+        }
+        OCTET_STRING => match std::str::from_utf8(content) {
+            Ok(text) if !text.contains(|c: char| c.is_control() && !c.is_whitespace()) => {
+                SnmpValue::Text(text.to_string())
+            }
+            _ => SnmpValue::Text(hex::encode(content)),
+        },
+        NULL | NO_SUCH_OBJECT | NO_SUCH_INSTANCE | END_OF_MIB_VIEW => SnmpValue::Missing,
+        _ => SnmpValue::Text(hex::encode(content)),
+    })
+}
+
+fn malformed() -> Error {
+    Error::internal("Malformed SNMP message")
+}
+
+/// Walks the elements of one BER-encoded content.
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Tag and content of the next element.
+    fn next(&mut self) -> Result<(u8, &'a [u8])> {
+        let [tag, first, rest @ ..] = self.data else {
+            return Err(malformed());
+        };
+        let (length, rest) = if first & 0x80 == 0 {
+            (*first as usize, rest)
+        } else {
+            let count = (first & 0x7F) as usize;
+            if count == 0 || count > 4 || rest.len() < count {
+                return Err(malformed());
+            }
+            let length = rest[..count].iter().fold(0, |length, byte| length << 8 | *byte as usize);
+            (length, &rest[count..])
+        };
+        if rest.len() < length {
+            return Err(malformed());
+        }
+        let (content, rest) = rest.split_at(length);
+        self.data = rest;
+        Ok((*tag, content))
+    }
+
+    fn expect(&mut self, tag: u8) -> Result<&'a [u8]> {
+        match self.next()? {
+            (found, content) if found == tag => Ok(content),
+            _ => Err(malformed()),
+        }
+    }
+
+    fn integer(&mut self) -> Result<i64> {
+        decode_signed(self.expect(INTEGER)?)
+    }
+
+    fn octets(&mut self) -> Result<&'a [u8]> {
+        self.expect(OCTET_STRING)
+    }
+}
+
+fn get_pdu(request_id: i32, oids: &[Vec<u32>]) -> Vec<u8> {
+    let bindings: Vec<Vec<u8>> = oids
+        .iter()
+        .map(|oid| sequence(SEQUENCE, &[encode_oid(oid), tlv(NULL, &[])]))
+        .collect();
+    sequence(
+        GET_REQUEST,
+        &[integer(request_id as i64), integer(0), integer(0), sequence(SEQUENCE, &bindings)],
+    )
+}
+
+struct Pdu {
+    tag: u8,
+    request_id: i64,
+    error_status: i64,
+    error_index: i64,
+    bindings: Vec<(Vec<u32>, SnmpValue)>,
+}
+
+fn read_pdu(tag: u8, content: &[u8]) -> Result<Pdu> {
+    let mut fields = Reader::new(content);
+    let request_id = fields.integer()?;
+    let error_status = fields.integer()?;
+    let error_index = fields.integer()?;
+    let mut list = Reader::new(fields.expect(SEQUENCE)?);
+    let mut bindings = Vec::new();
+    while !list.is_empty() {
+        let mut binding = Reader::new(list.expect(SEQUENCE)?);
+        let oid = decode_oid(binding.expect(OBJECT_IDENTIFIER)?)?;
+        let (tag, content) = binding.next()?;
+        bindings.push((oid, decode_value(tag, content)?));
+    }
+    Ok(Pdu {
+        tag,
+        request_id,
+        error_status,
+        error_index,
+        bindings,
+    })
+}
+
+impl Pdu {
+    fn into_bindings(self, request_id: i32) -> Result<Vec<(Vec<u32>, SnmpValue)>> {
+        if self.tag == REPORT {
+            return Err(report_error(&self.bindings));
+        }
+        if self.tag != RESPONSE || self.request_id != request_id as i64 {
+            return Err(Error::internal("Unexpected SNMP response"));
+        }
+        if self.error_status != 0 {
+            return Err(Error::internal(format!(
+                "The agent answered {} for variable {}",
+                error_status_name(self.error_status),
+                self.error_index
+            )));
+        }
+        Ok(self.bindings)
+    }
+}
+
+fn error_status_name(status: i64) -> String {
+    const NAMES: [&str; 19] = [
+        "noError",
+        "tooBig",
+        "noSuchName",
+        "badValue",
+        "readOnly",
+        "genErr",
+        "noAccess",
+        "wrongType",
+        "wrongLength",
+        "wrongEncoding",
+        "wrongValue",
+        "noCreation",
+        "inconsistentValue",
+        "resourceUnavailable",
+        "commitFailed",
+        "undoFailed",
+        "authorizationError",
+        "notWritable",
+        "inconsistentName",
+    ];
+    usize::try_from(status)
+        .ok()
+        .and_then(|status| NAMES.get(status))
+        .map(|name| name.to_string())
+        .unwrap_or_else(|| format!("error {}", status))
+}
+
+/// Explains the usmStats counter a report carries instead of a response.
+fn report_error(bindings: &[(Vec<u32>, SnmpValue)]) -> Error {
+    const USM_STATS: [u32; 9] = [1, 3, 6, 1, 6, 3, 15, 1, 1];
+    let counter = bindings
+        .first()
+        .and_then(|(oid, _)| oid.strip_prefix(USM_STATS.as_slice()))
+        .and_then(|rest| rest.first().copied());
+    match counter {
+        Some(1) => Error::auth("The agent does not support the check's security level"),
+        Some(2) => Error::auth("The request was outside the agent's time window"),
+        Some(3) => Error::auth("The agent does not know the user"),
+        Some(4) => Error::auth("The agent does not know the engine id"),
+        Some(5) => Error::auth("The agent rejected the authentication password"),
+        Some(6) => Error::auth("The agent could not decrypt the request; check the privacy password"),
+        _ => {
+            let oid = bindings.first().map(|(oid, _)| format_oid(oid)).unwrap_or_default();
+            Error::internal(format!("The agent sent a report ({})", oid))
+        }
+    }
+}
+
+fn usm_parameters(engine: &Engine, username: &str, auth: &[u8], privacy: &[u8]) -> Vec<u8> {
+    octets(&sequence(
+        SEQUENCE,
+        &[
+            octets(&engine.id),
+            integer(engine.boots as i64),
+            integer(engine.time as i64),
+            octets(username.as_bytes()),
+            octets(auth),
+            octets(privacy),
+        ],
+    ))
+}
+
+fn v3_message(message_id: i32, flags: u8, security: Vec<u8>, data: Vec<u8>) -> Vec<u8> {
+    let header = sequence(
+        SEQUENCE,
+        &[
+            integer(message_id as i64),
+            integer(MAX_MESSAGE_SIZE as i64),
+            octets(&[flags]),
+            integer(USM),
+        ],
+    );
+    sequence(SEQUENCE, &[integer(3), header, security, data])
+}
+
+/// A v3 message with its security parameters read and its data left as is.
+struct Message<'a> {
+    id: i64,
+    flags: u8,
+    engine: Engine,
+    auth: &'a [u8],
+    privacy: &'a [u8],
+    data: (u8, &'a [u8]),
+}
+
+fn read_v3(data: &[u8]) -> Result<Message<'_>> {
+    let mut message = Reader::new(Reader::new(data).expect(SEQUENCE)?);
+    if message.integer()? != 3 {
+        return Err(Error::internal("Not an SNMPv3 message"));
+    }
+    let mut header = Reader::new(message.expect(SEQUENCE)?);
+    let id = header.integer()?;
+    header.integer()?;
+    let flags = *header.octets()?.first().ok_or_else(malformed)?;
+    if header.integer()? != USM {
+        return Err(Error::internal("The agent answered with another security model"));
+    }
+
+    let mut security = Reader::new(Reader::new(message.octets()?).expect(SEQUENCE)?);
+    let engine = Engine {
+        id: security.octets()?.to_vec(),
+        boots: u32::try_from(security.integer()?).map_err(|_| malformed())?,
+        time: u32::try_from(security.integer()?).map_err(|_| malformed())?,
+    };
+    security.octets()?;
+    let auth = security.octets()?;
+    let privacy = security.octets()?;
+    Ok(Message {
+        id,
+        flags,
+        engine,
+        auth,
+        privacy,
+        data: message.next()?,
+    })
+}
+
@@ -0,0 +1,166 @@
+#[cfg(test)]
+mod snmp_tests {
+    use crate::snmp::*;
+    use std::collections::{BTreeMap, HashMap};
+
+    fn check(value: serde_json::Value) -> SnmpCheck {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn interface_check() -> SnmpCheck {
+        check(serde_json::json!({
+            "version": "v2c",
+            "oids": { "if_oper_status": "1.3.6.1.2.1.2.2.1.8.1", "cpu_idle": ".1.3.6.1.4.1.2021.11.11.0" },
+            "assertions": ["if_oper_status == 1", "cpu_idle > 10"]
+        }))
+    }
+
+    #[test]
+    fn test_parse_oid() {
+        assert_eq!(parse_oid("1.3.6.1.2.1.1.3.0").unwrap(), vec![1, 3, 6, 1, 2, 1, 1, 3, 0]);
+        assert_eq!(parse_oid(".1.3.6").unwrap(), vec![1, 3, 6]);
+        assert_eq!(format_oid(&[1, 3, 6, 1]), "1.3.6.1");
+        for invalid in ["", "1", "1..3", "3.1", "1.40", "1.3.x", "1.3.6.4294967296"] {
+            assert!(parse_oid(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_encode_oid() {
+        assert_eq!(
+            encode_oid(&[1, 3, 6, 1, 2, 1, 1, 3, 0]),
+            vec![0x06, 0x08, 0x2B, 0x06, 0x01, 0x02, 0x01, 0x01, 0x03, 0x00]
+        );
+        // Arcs above 127 take several base-128 digits
+        assert_eq!(encode_oid(&[1, 3, 6, 1, 4, 1, 2021]), vec![0x06, 0x07, 0x2B, 0x06, 0x01, 0x04, 0x01, 0x8F, 0x65]);
+    }
+
+    #[test]
+    fn test_password_to_key_matches_rfc_3414() {
+        // RFC 3414 A.3.1 and A.3.2
+        let engine_id = hex::decode("000000000000000000000002").unwrap();
+        assert_eq!(
+            hex::encode(password_to_key(AuthProtocol::Md5, b"maplesyrup", &engine_id)),
+            "526f5eed9fcce26f8964c2930787d82b"
+        );
+        assert_eq!(
+            hex::encode(password_to_key(AuthProtocol::Sha, b"maplesyrup", &engine_id)),
+            "6695febc9288e36282235fc7151f128497b38f3f"
+        );
+    }
+
+    #[test]
+    fn test_aes_cfb_round_trip() {
+        let key = [7u8; 16];
+        let iv = [1u8; 16];
+        // Not a multiple of the block size; CFB needs no padding
+        let plaintext = b"a scoped PDU that spans three AES blocks".to_vec();
+        let ciphertext = aes_cfb(&key, iv, &plaintext, true);
+        assert_eq!(ciphertext.len(), plaintext.len());
+        assert_ne!(ciphertext, plaintext);
+        assert_eq!(aes_cfb(&key, iv, &ciphertext, false), plaintext);
+    }
+
+    #[test]
+    fn test_check_validation() {
+        let endpoint = "snmp://switch.example.com";
+        assert!(interface_check().validate(endpoint).is_ok());
+        assert_eq!(agent_address("snmp://switch.example.com").unwrap(), "switch.example.com:161");
+        assert_eq!(agent_address("snmp://10.0.0.1:1161").unwrap(), "10.0.0.1:1161");
+        assert!(interface_check().validate("http://switch.example.com").is_err());
+
+        let mut unknown_name = interface_check();
+        unknown_name.assertions.push("memory_free > 0".parse().unwrap());
+        assert!(unknown_name.validate(endpoint).is_err());
+
+        let mut bad_oid = interface_check();
+        bad_oid.oids.insert("uptime".to_string(), "sysUpTime.0".to_string());
+        assert!(bad_oid.validate(endpoint).is_err());
+
+        let mut v2c_user = interface_check();
+        v2c_user.username = Some("monitor".to_string());
+        assert!(v2c_user.validate(endpoint).is_err());
+
+        let v3 = |extra: serde_json::Value| {
+            let mut value = serde_json::json!({ "version": "v3", "oids": { "uptime": "1.3.6.1.2.1.1.3.0" } });
+            value.as_object_mut().unwrap().extend(extra.as_object().unwrap().clone());
+            check(value)
+        };
+        assert!(v3(serde_json::json!({})).validate(endpoint).is_err());
+        assert!(v3(serde_json::json!({ "username": "monitor", "auth_protocol": "sha", "privacy_protocol": "aes" }))
+            .validate(endpoint)
+            .is_ok());
+        assert!(v3(serde_json::json!({ "username": "monitor", "privacy_protocol": "aes" }))
+            .validate(endpoint)
+            .is_err());
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let check = interface_check();
+        let mut values = BTreeMap::from([
+            ("if_oper_status".to_string(), SnmpValue::Number(1.0)),
+            ("cpu_idle".to_string(), SnmpValue::Number(85.0)),
+        ]);
+        assert!(check.evaluate(&values).is_ok());
+
+        values.insert("if_oper_status".to_string(), SnmpValue::Number(2.0));
+        assert_eq!(check.evaluate(&values).unwrap_err(), "if_oper_status is 2, expected == 1");
+
+        values.insert("if_oper_status".to_string(), SnmpValue::Missing);
+        assert_eq!(check.evaluate(&values).unwrap_err(), "if_oper_status not found");
+        assert_eq!(serde_json::to_value(&values).unwrap()["if_oper_status"], serde_json::Value::Null);
+    }
+
+    #[test]
+    fn test_v2c_session_reads_its_response() {
+        let check = interface_check();
+        let credentials = HashMap::from([(COMMUNITY.to_string(), "s3cret".to_string())]);
+        let mut session = Session::new(&check, &credentials).unwrap();
+        assert!(!session.needs_discovery());
+        let mut request = session.get_request(&check.object_ids().unwrap()).unwrap();
+
+        // SEQUENCE, version 1, the community, then the GetRequest; turned
+        // into a response, its NULL values read as missing objects
+        assert_eq!(&request[2..13], b"\x02\x01\x01\x04\x06s3cret");
+        assert_eq!(request[13], 0xA0);
+        request[13] = 0xA2;
+        let values = check.named(session.read_response(&request).unwrap());
+        assert_eq!(values.len(), 2);
+        assert!(values.values().all(|value| *value == SnmpValue::Missing));
+
+        // A response to another poll's request is not taken for this one
+        let mut other = Session::new(&check, &credentials).unwrap();
+        other.get_request(&check.object_ids().unwrap()).unwrap();
+        assert!(other.read_response(&request).is_err());
+    }
+
+    #[test]
+    fn test_v3_session_needs_discovery_and_passwords() {
+        let check = check(serde_json::json!({
+            "version": "v3",
+            "oids": { "uptime": "1.3.6.1.2.1.1.3.0" },
+            "username": "monitor",
+            "auth_protocol": "sha",
+            "privacy_protocol": "aes"
+        }));
+        let short = HashMap::from([
+            (AUTH_PASSWORD.to_string(), "short".to_string()),
+            (PRIV_PASSWORD.to_string(), "privacy-password".to_string()),
+        ]);
+        assert!(Session::new(&check, &short).is_err());
+
+        let credentials = HashMap::from([
+            (AUTH_PASSWORD.to_string(), "authentication".to_string()),
+            (PRIV_PASSWORD.to_string(), "privacy-password".to_string()),
+        ]);
+        let mut session = Session::new(&check, &credentials).unwrap();
+        assert!(session.needs_discovery());
+        assert!(session.get_request(&check.object_ids().unwrap()).is_err());
+
+        // An answer without an engine id cannot complete discovery
+        let request = session.discovery_request();
+        assert!(session.discover(&request).is_err());
+        assert!(session.needs_discovery());
+    }
+}
@@ -0,0 +1,272 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use crate::{db::DatabasePool, error::Result, Error};
+
+pub const DEFAULT_FAILURE_THRESHOLD: i32 = 3;
+pub const DEFAULT_RECOVERY_THRESHOLD: i32 = 2;
+pub const DEFAULT_FLAP_THRESHOLD: i32 = 5;
+pub const DEFAULT_FLAP_WINDOW_SECS: i32 = 1800;
+const MAX_THRESHOLD: i32 = 100;
+const FLAP_WINDOW_RANGE: std::ops::RangeInclusive<i32> = 60..=86_400;
+
+/// A monitor's health: `Degraded` after its first failed check, `Down` once
+/// `failure_threshold` checks in a row have failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+pub enum HealthState {
+    Ok,
+    Degraded,
+    Down,
+}
+
+impl HealthState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HealthState::Ok => "ok",
+            HealthState::Degraded => "degraded",
+            HealthState::Down => "down",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Transition {
+    pub from: HealthState,
+    pub to: HealthState,
+}
+
+/// What a counted check means for notifications.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StateEvent {
+    /// The state changed while the monitor was not flapping
+    Changed(Transition),
+    /// Changes after this one are not notified until the monitor stabilizes
+    FlappingStarted { transitions: usize, window_secs: i32 },
+    /// A whole flap window passed without a change; holds the settled state
+    Stabilized(HealthState),
+}
+
+/// A `monitor_states` row.
+#[derive(Debug, Clone, PartialEq, Serialize, FromRow)]
+pub struct MonitorState {
+    pub monitor_id: Uuid,
+    pub state: HealthState,
+    /// Failed checks in a row that take the monitor down
+    pub failure_threshold: i32,
+    /// Successful checks in a row that bring it back to `ok`
+    pub recovery_threshold: i32,
+    pub consecutive_failures: i32,
+    pub consecutive_successes: i32,
+    /// State changes within `flap_window_secs` that make the monitor flapping
+    pub flap_threshold: i32,
+    pub flap_window_secs: i32,
+    /// Times of the state changes still inside the flap window
+    pub recent_transitions: Vec<DateTime<Utc>>,
+    pub flapping_since: Option<DateTime<Utc>>,
+    pub changed_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ThresholdsRequest {
+    pub failure_threshold: Option<i32>,
+    pub recovery_threshold: Option<i32>,
+    pub flap_threshold: Option<i32>,
+    pub flap_window_secs: Option<i32>,
+}
+
+impl MonitorState {
+    /// A monitor with no recorded checks yet.
+    pub fn new(monitor_id: Uuid, at: DateTime<Utc>) -> Self {
+        Self {
+            monitor_id,
+            state: HealthState::Ok,
+            failure_threshold: DEFAULT_FAILURE_THRESHOLD,
+            recovery_threshold: DEFAULT_RECOVERY_THRESHOLD,
+            consecutive_failures: 0,
+            consecutive_successes: 0,
+            flap_threshold: DEFAULT_FLAP_THRESHOLD,
+            flap_window_secs: DEFAULT_FLAP_WINDOW_SECS,
+            recent_transitions: Vec::new(),
+            flapping_since: None,
+            changed_at: at,
+            updated_at: at,
+        }
+    }
+
+    /// Counts one check and moves to the state it implies. A failure never
+    /// lowers the state, and any state but `ok` needs `recovery_threshold`
+    /// successes in a row to recover.
+    pub fn advance(&mut self, success: bool, at: DateTime<Utc>) -> Option<Transition> {
+        let next = if success {
+            self.consecutive_successes += 1;
+            self.consecutive_failures = 0;
+            if self.consecutive_successes >= self.recovery_threshold {
+                HealthState::Ok
+            } else {
+                self.state
+            }
+        } else {
+            self.consecutive_failures += 1;
+            self.consecutive_successes = 0;
+            if self.consecutive_failures >= self.failure_threshold {
+                HealthState::Down
+            } else if self.state == HealthState::Ok {
+                HealthState::Degraded
+            } else {
+                self.state
+            }
+        };
+        self.updated_at = at;
+        if next == self.state {
+            return None;
+        }
+        let transition = Transition {
+            from: self.state,
+            to: next,
+        };
+        self.state = next;
+        self.changed_at = at;
+        Some(transition)
+    }
+
+    pub fn is_flapping(&self) -> bool {
+        self.flapping_since.is_some()
+    }
+
+    /// Counts one check like `advance` and decides what to notify. A monitor
+    /// starts flapping when it changes state more than `flap_threshold` times
+    /// within the window, and stabilizes once a whole window has no changes.
+    pub fn observe(&mut self, success: bool, at: DateTime<Utc>) -> Option<StateEvent> {
+        let transition = self.advance(success, at);
+        if transition.is_some() {
+            self.recent_transitions.push(at);
+        }
+        let window_start = at - Duration::seconds(self.flap_window_secs as i64);
+        self.recent_transitions.retain(|t| *t > window_start);
+
+        if self.is_flapping() {
+            if self.recent_transitions.is_empty() {
+                self.flapping_since = None;
+                return Some(StateEvent::Stabilized(self.state));
+            }
+            return None;
+        }
+        if self.recent_transitions.len() > self.flap_threshold as usize {
+            self.flapping_since = Some(at);
+            return Some(StateEvent::FlappingStarted {
+                transitions: self.recent_transitions.len(),
+                window_secs: self.flap_window_secs,
+            });
+        }
+        transition.map(StateEvent::Changed)
+    }
+}
+
+pub fn validate_thresholds(request: &ThresholdsRequest) -> Result<()> {
+    for (name, value) in [
+        ("failure_threshold", request.failure_threshold),
+        ("recovery_threshold", request.recovery_threshold),
+        ("flap_threshold", request.flap_threshold),
+    ] {
+        if value.is_some_and(|v| !(1..=MAX_THRESHOLD).contains(&v)) {
+            return Err(Error::validation(format!("{} must be between 1 and {}", name, MAX_THRESHOLD)));
+        }
+    }
+    if request.flap_window_secs.is_some_and(|secs| !FLAP_WINDOW_RANGE.contains(&secs)) {
+        return Err(Error::validation(format!(
+            "flap_window_secs must be between {} and {}",
+            FLAP_WINDOW_RANGE.start(),
+            FLAP_WINDOW_RANGE.end()
+        )));
+    }
+    Ok(())
+}
+
+/// The stored state, or the initial one as of `now` when the monitor has not
+/// been checked.
+pub async fn get(db: &DatabasePool, monitor_id: Uuid, now: DateTime<Utc>) -> Result<MonitorState> {
+    let state = sqlx::query_as::<_, MonitorState>("SELECT * FROM monitor_states WHERE monitor_id = $1")
+        .bind(monitor_id)
+        .fetch_optional(db)
+        .await?;
+    Ok(state.unwrap_or_else(|| MonitorState::new(monitor_id, now)))
+}
+
+/// Omitted thresholds keep their current values.
+pub async fn set_thresholds(db: &DatabasePool, monitor_id: Uuid, request: &ThresholdsRequest) -> Result<MonitorState> {
+    validate_thresholds(request)?;
+    let state = sqlx::query_as::<_, MonitorState>(
+        r#"
+        INSERT INTO monitor_states (monitor_id, failure_threshold, recovery_threshold, flap_threshold, flap_window_secs)
+        VALUES ($1, COALESCE($2, $6), COALESCE($3, $7), COALESCE($4, $8), COALESCE($5, $9))
+        ON CONFLICT (monitor_id) DO UPDATE
+        SET failure_threshold = COALESCE($2, monitor_states.failure_threshold),
+            recovery_threshold = COALESCE($3, monitor_states.recovery_threshold),
+            flap_threshold = COALESCE($4, monitor_states.flap_threshold),
+            flap_window_secs = COALESCE($5, monitor_states.flap_window_secs),
+            updated_at = NOW()
+        RETURNING *
+        "#,
+    )
+    .bind(monitor_id)
+    .bind(request.failure_threshold)
+    .bind(request.recovery_threshold)
+    .bind(request.flap_threshold)
+    .bind(request.flap_window_secs)
+    .bind(DEFAULT_FAILURE_THRESHOLD)
+    .bind(DEFAULT_RECOVERY_THRESHOLD)
+    .bind(DEFAULT_FLAP_THRESHOLD)
+    .bind(DEFAULT_FLAP_WINDOW_SECS)
+    .fetch_one(db)
+    .await?;
+    Ok(state)
+}
+
+/// Counts a check result against the monitor's state. The row is locked so
+/// concurrent workers count every result once and only one of them sees a
+/// given event.
+pub async fn record(
+    db: &DatabasePool,
+    monitor_id: Uuid,
+    success: bool,
+    at: DateTime<Utc>,
+) -> Result<(MonitorState, Option<StateEvent>)> {
+    let mut tx = db.begin().await?;
+    sqlx::query(
+        "INSERT INTO monitor_states (monitor_id, changed_at) VALUES ($1, $2) ON CONFLICT (monitor_id) DO NOTHING",
+    )
+    .bind(monitor_id)
+    .bind(at)
+    .execute(&mut *tx)
+    .await?;
+    let mut state = sqlx::query_as::<_, MonitorState>("SELECT * FROM monitor_states WHERE monitor_id = $1 FOR UPDATE")
+        .bind(monitor_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+    let event = state.observe(success, at);
+    sqlx::query(
+        r#"
+        UPDATE monitor_states
+        SET state = $2, consecutive_failures = $3, consecutive_successes = $4, recent_transitions = $5,
+            flapping_since = $6, changed_at = $7, updated_at = $8
+        WHERE monitor_id = $1
+        "#,
+    )
+    .bind(monitor_id)
+    .bind(state.state)
+    .bind(state.consecutive_failures)
+    .bind(state.consecutive_successes)
+    .bind(&state.recent_transitions)
+    .bind(state.flapping_since)
+    .bind(state.changed_at)
+    .bind(state.updated_at)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    Ok((state, event))
+}
@@ -0,0 +1,187 @@
+#[cfg(test)]
+mod states_tests {
+    use crate::states::*;
+    use chrono::{Duration, TimeZone, Utc};
+    use uuid::Uuid;
+
+    fn run(state: &mut MonitorState, checks: &[bool]) -> Vec<Transition> {
+        let start = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        checks
+            .iter()
+            .enumerate()
+            .filter_map(|(i, success)| state.advance(*success, start + Duration::minutes(i as i64)))
+            .collect()
+    }
+
+    fn transition(from: HealthState, to: HealthState) -> Transition {
+        Transition { from, to }
+    }
+
+    #[test]
+    fn test_degraded_on_first_failure_and_down_at_threshold() {
+        let mut state = MonitorState::new(Uuid::new_v4(), Utc::now());
+        let transitions = run(&mut state, &[false, false, false, false]);
+
+        assert_eq!(
+            transitions,
+            vec![
+                transition(HealthState::Ok, HealthState::Degraded),
+                transition(HealthState::Degraded, HealthState::Down),
+            ]
+        );
+        assert_eq!(state.state, HealthState::Down);
+        assert_eq!(state.consecutive_failures, 4);
+    }
+
+    #[test]
+    fn test_recovery_needs_consecutive_successes() {
+        let mut state = MonitorState::new(Uuid::new_v4(), Utc::now());
+        run(&mut state, &[false, false, false]);
+
+        // One success is not enough, and a failure restarts the count
+        assert!(run(&mut state, &[true, false, true]).is_empty());
+        assert_eq!(state.state, HealthState::Down);
+
+        assert_eq!(run(&mut state, &[true]), vec![transition(HealthState::Down, HealthState::Ok)]);
+        assert_eq!(state.consecutive_successes, 2);
+    }
+
+    #[test]
+    fn test_degraded_recovers_without_going_down() {
+        let mut state = MonitorState::new(Uuid::new_v4(), Utc::now());
+        let transitions = run(&mut state, &[false, true, false, true, true, true]);
+
+        assert_eq!(
+            transitions,
+            vec![
+                transition(HealthState::Ok, HealthState::Degraded),
+                transition(HealthState::Degraded, HealthState::Ok),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_threshold_of_one_skips_degraded() {
+        let mut state = MonitorState {
+            failure_threshold: 1,
+            recovery_threshold: 1,
+            ..MonitorState::new(Uuid::new_v4(), Utc::now())
+        };
+        let transitions = run(&mut state, &[true, false, true]);
+
+        assert_eq!(
+            transitions,
+            vec![
+                transition(HealthState::Ok, HealthState::Down),
+                transition(HealthState::Down, HealthState::Ok),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_changed_at_only_moves_on_transitions() {
+        let created = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let mut state = MonitorState::new(Uuid::new_v4(), created);
+        run(&mut state, &[true, true]);
+        assert_eq!(state.changed_at, created);
+        assert!(state.updated_at > created);
+    }
+
+    #[test]
+    fn test_thresholds_are_bounded() {
+        let request = |failure, recovery| ThresholdsRequest {
+            failure_threshold: failure,
+            recovery_threshold: recovery,
+            flap_threshold: None,
+            flap_window_secs: None,
+        };
+        assert!(validate_thresholds(&request(Some(5), None)).is_ok());
+        assert!(validate_thresholds(&request(Some(0), None)).is_err());
+        assert!(validate_thresholds(&request(None, Some(101))).is_err());
+        assert!(validate_thresholds(&ThresholdsRequest {
+            flap_window_secs: Some(10),
+            ..request(None, None)
+        })
+        .is_err());
+    }
+
+    fn flappy() -> MonitorState {
+        MonitorState {
+            failure_threshold: 1,
+            recovery_threshold: 1,
+            flap_threshold: 3,
+            flap_window_secs: 600,
+            ..MonitorState::new(Uuid::new_v4(), Utc::now())
+        }
+    }
+
+    #[test]
+    fn test_flapping_suppresses_changes_until_a_quiet_window() {
+        let start = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let mut state = flappy();
+        let events: Vec<Option<StateEvent>> = [false, true, false, true, false, true]
+            .iter()
+            .enumerate()
+            .map(|(i, success)| state.observe(*success, start + Duration::minutes(i as i64)))
+            .collect();
+
+        assert_eq!(events[0], Some(StateEvent::Changed(transition(HealthState::Ok, HealthState::Down))));
+        assert_eq!(events[2], Some(StateEvent::Changed(transition(HealthState::Ok, HealthState::Down))));
+        assert_eq!(
+            events[3],
+            Some(StateEvent::FlappingStarted {
+                transitions: 4,
+                window_secs: 600
+            })
+        );
+        assert_eq!(events[4..], [None, None]);
+        assert!(state.is_flapping());
+
+        // Still inside the window of the last change
+        assert_eq!(state.observe(true, start + Duration::minutes(14)), None);
+        assert_eq!(
+            state.observe(true, start + Duration::minutes(16)),
+            Some(StateEvent::Stabilized(HealthState::Ok))
+        );
+        assert!(!state.is_flapping());
+        assert!(state.recent_transitions.is_empty());
+    }
+
+    #[test]
+    fn test_changes_spread_over_time_do_not_flap() {
+        let start = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let mut state = flappy();
+        for i in 0..10 {
+            let event = state.observe(i % 2 == 1, start + Duration::minutes(i * 5));
+            assert!(matches!(event, Some(StateEvent::Changed(_))));
+        }
+        assert_eq!(state.recent_transitions.len(), 2);
+    }
+
+    #[test]
+    fn test_two_weeks_of_minute_checks() {
+        use crate::clock::{Clock, ManualClock};
+
+        let start = Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap();
+        let clock = ManualClock::new(start);
+        let outage = start + Duration::days(3)..start + Duration::days(3) + Duration::minutes(10);
+        let flaky_hour = start + Duration::days(9)..start + Duration::days(9) + Duration::hours(1);
+        let mut state = MonitorState::new(Uuid::new_v4(), clock.now());
+        let mut events = Vec::new();
+        while clock.now() < start + Duration::weeks(2) {
+            let now = clock.advance(Duration::minutes(1));
+            let minute = (now - start).num_minutes();
+            let failing = outage.contains(&now) || (flaky_hour.contains(&now) && minute % 5 < 3);
+            events.extend(state.observe(!failing, now).map(|event| (now, event)));
+        }
+
+        let changes = |kind: fn(&StateEvent) -> bool| events.iter().filter(|(_, event)| kind(event)).count();
+        assert_eq!(changes(|e| matches!(e, StateEvent::FlappingStarted { .. })), 1);
+        assert_eq!(changes(|e| matches!(e, StateEvent::Stabilized(HealthState::Ok))), 1);
+        // Ok -> degraded -> down and back for the outage; the flaky hour goes quiet once flapping
+        assert_eq!(events[0], (outage.start, StateEvent::Changed(transition(HealthState::Ok, HealthState::Degraded))));
+        assert_eq!(events[2].1, StateEvent::Changed(transition(HealthState::Down, HealthState::Ok)));
+        assert_eq!(state.state, HealthState::Ok);
+        assert_eq!(state.updated_at, start + Duration::weeks(2));
+    }
+}
@@ -0,0 +1,235 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use crate::{
+    db::DatabasePool,
+    error::Result,
+    rollups::{self, HourlyAggregate, ROLLUP_SECS},
+    Error,
+};
+
+/// Upper bound on buckets per request so a tiny step over a long range cannot
+/// turn into a raw-row download.
+pub const MAX_BUCKETS: i64 = 2_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeseriesMetric {
+    /// Response time in milliseconds
+    Latency,
+    /// Share of successful checks, 0.0 - 1.0
+    Availability,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct TimeseriesPoint {
+    pub bucket: DateTime<Utc>,
+    pub count: i64,
+    pub avg: Option<f64>,
+    /// Only reported for latency
+    pub p95: Option<f64>,
+    /// Only reported for latency
+    pub max: Option<f64>,
+}
+
+/// Latency distribution over a time range, merged from hourly sketches.
+#[derive(Debug, Clone, Serialize)]
+pub struct LatencySummary {
+    pub count: i64,
+    pub availability: Option<f64>,
+    pub avg: Option<f64>,
+    pub p50: Option<f64>,
+    pub p95: Option<f64>,
+    pub p99: Option<f64>,
+    pub max: Option<f64>,
+}
+
+impl LatencySummary {
+    fn from_aggregate(aggregate: &HourlyAggregate) -> Self {
+        let count = aggregate.count;
+        let ratio = |value: i64| (count > 0).then(|| value as f64 / count as f64);
+        Self {
+            count,
+            availability: ratio(aggregate.success_count),
+            avg: ratio(aggregate.latency_sum),
+            p50: aggregate.latency.quantile(0.50),
+            p95: aggregate.latency.quantile(0.95),
+            p99: aggregate.latency.quantile(0.99),
+            max: aggregate.latency.max(),
+        }
+    }
+}
+
+/// One monitor's values on a shared bucket axis (`None` where it had no results).
+#[derive(Debug, Clone, Serialize)]
+pub struct AlignedSeries {
+    pub monitor_id: Uuid,
+    pub avg: Vec<Option<f64>>,
+    pub p95: Vec<Option<f64>>,
+}
+
+/// Puts several monitors' series on the union of their bucket timestamps so
+/// dashboards can plot them side by side.
+pub fn align_series(series: &[(Uuid, Vec<TimeseriesPoint>)]) -> (Vec<DateTime<Utc>>, Vec<AlignedSeries>) {
+    let buckets: Vec<DateTime<Utc>> = series
+        .iter()
+        .flat_map(|(_, points)| points.iter().map(|p| p.bucket))
+        .collect::<std::collections::BTreeSet<_>>()
+        .into_iter()
+        .collect();
+
+    let aligned = series
+        .iter()
+        .map(|(monitor_id, points)| {
+            let by_bucket: std::collections::HashMap<_, _> = points.iter().map(|p| (p.bucket, p)).collect();
+            AlignedSeries {
+                monitor_id: *monitor_id,
+                avg: buckets.iter().map(|b| by_bucket.get(b).and_then(|p| p.avg)).collect(),
+                p95: buckets.iter().map(|b| by_bucket.get(b).and_then(|p| p.p95)).collect(),
+            }
+        })
+        .collect();
+
+    (buckets, aligned)
+}
+
+/// Parses a bucket width such as `30s`, `5m`, `1h` or `1d` into seconds.
+pub fn parse_step(step: &str) -> Result<i64> {
+    let step = step.trim();
+    let split = step
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(|| Error::validation(format!("Step '{}' needs a unit (s, m, h, d)", step)))?;
+    let (amount, unit) = step.split_at(split);
+    let amount: i64 = amount
+        .parse()
+        .map_err(|_| Error::validation(format!("Invalid step: {}", step)))?;
+
+    let seconds = match unit {
+        "s" => amount,
+        "m" => amount * 60,
+        "h" => amount * 3_600,
+        "d" => amount * 86_400,
+        _ => return Err(Error::validation(format!("Unknown step unit '{}', use s, m, h or d", unit))),
+    };
+
+    if seconds <= 0 {
+        return Err(Error::validation("Step must be positive"));
+    }
+    Ok(seconds)
+}
+
+/// Buckets results for one monitor in SQL, aligned to multiples of `step_secs`
+/// since the Unix epoch. Empty buckets are omitted.
+pub async fn timeseries(
+    db: &DatabasePool,
+    monitor_id: Uuid,
+    metric: TimeseriesMetric,
+    step_secs: i64,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<TimeseriesPoint>> {
+    if to <= from {
+        return Err(Error::validation("'to' must be after 'from'"));
+    }
+    if (to - from).num_seconds() / step_secs > MAX_BUCKETS {
+        return Err(Error::validation(format!(
+            "Range and step would produce more than {} buckets; use a larger step",
+            MAX_BUCKETS
+        )));
+    }
+
+    // Hour-aligned steps are served from rollups so they keep working after raw
+    // results expire, with percentiles merged from the stored histograms
+    if step_secs % ROLLUP_SECS == 0 {
+        return rollup_timeseries(db, monitor_id, metric, step_secs, from, to).await;
+    }
+
+    let aggregates = match metric {
+        TimeseriesMetric::Latency => {
+            r#"AVG(response_time)::FLOAT8 AS avg,
+               PERCENTILE_CONT(0.95) WITHIN GROUP (ORDER BY response_time)::FLOAT8 AS p95,
+               MAX(response_time)::FLOAT8 AS max"#
+        }
+        TimeseriesMetric::Availability => {
+            r#"AVG(CASE WHEN status = 'success' THEN 1.0 ELSE 0.0 END)::FLOAT8 AS avg,
+               NULL::FLOAT8 AS p95,
+               NULL::FLOAT8 AS max"#
+        }
+    };
+
+    let points = sqlx::query_as::<_, TimeseriesPoint>(&format!(
+        r#"
+        SELECT TO_TIMESTAMP(FLOOR(EXTRACT(EPOCH FROM checked_at) / $2) * $2) AS bucket,
+               COUNT(*) AS count,
+               {aggregates}
+        FROM monitor_results
+        WHERE monitor_id = $1 AND checked_at >= $3 AND checked_at < $4
+        GROUP BY bucket
+        ORDER BY bucket
+        "#
+    ))
+    .bind(monitor_id)
+    .bind(step_secs as f64)
+    .bind(from)
+    .bind(to)
+    .fetch_all(db)
+    .await?;
+
+    Ok(points)
+}
+
+async fn rollup_timeseries(
+    db: &DatabasePool,
+    monitor_id: Uuid,
+    metric: TimeseriesMetric,
+    step_secs: i64,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<TimeseriesPoint>> {
+    let mut buckets: std::collections::BTreeMap<i64, HourlyAggregate> = Default::default();
+    for (hour, aggregate) in rollups::hourly_aggregates(db, monitor_id, from, to).await? {
+        let ts = hour.timestamp();
+        buckets
+            .entry(ts - ts.rem_euclid(step_secs))
+            .or_default()
+            .merge(&aggregate);
+    }
+
+    Ok(buckets
+        .into_iter()
+        .filter_map(|(ts, aggregate)| {
+            let summary = LatencySummary::from_aggregate(&aggregate);
+            let (avg, p95, max) = match metric {
+                TimeseriesMetric::Latency => (summary.avg, summary.p95, summary.max),
+                TimeseriesMetric::Availability => (summary.availability, None, None),
+            };
+            Some(TimeseriesPoint {
+                bucket: DateTime::from_timestamp(ts, 0)?,
+                count: summary.count,
+                avg,
+                p95,
+                max,
+            })
+        })
+        .collect())
+}
+
+/// Percentiles over `[from, to)` from merged hourly sketches; accurate over long
+/// windows where averaging per-hour values would not be.
+pub async fn latency_summary(
+    db: &DatabasePool,
+    monitor_id: Uuid,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<LatencySummary> {
+    if to <= from {
+        return Err(Error::validation("'to' must be after 'from'"));
+    }
+
+    let mut total = HourlyAggregate::default();
+    for aggregate in rollups::hourly_aggregates(db, monitor_id, from, to).await?.values() {
+        total.merge(aggregate);
+    }
+    Ok(LatencySummary::from_aggregate(&total))
+}
@@ -0,0 +1,44 @@
+#[cfg(test)]
+mod stats_tests {
+    use crate::stats::*;
+    use chrono::DateTime;
+    use uuid::Uuid;
+
+    fn point(ts: i64, avg: f64) -> TimeseriesPoint {
+        TimeseriesPoint {
+            bucket: DateTime::from_timestamp(ts, 0).unwrap(),
+            count: 1,
+            avg: Some(avg),
+            p95: Some(avg * 2.0),
+            max: Some(avg * 3.0),
+        }
+    }
+
+    #[test]
+    fn test_parse_step() {
+        assert_eq!(parse_step("30s").unwrap(), 30);
+        assert_eq!(parse_step("5m").unwrap(), 300);
+        assert_eq!(parse_step("1h").unwrap(), 3_600);
+        assert_eq!(parse_step("2d").unwrap(), 172_800);
+
+        assert!(parse_step("5").is_err());
+        assert!(parse_step("m").is_err());
+        assert!(parse_step("0s").is_err());
+        assert!(parse_step("5w").is_err());
+    }
+
+    #[test]
+    fn test_align_series_fills_gaps() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let (buckets, series) = align_series(&[
+            (a, vec![point(0, 10.0), point(600, 30.0)]),
+            (b, vec![point(300, 20.0), point(600, 40.0)]),
+        ]);
+
+        assert_eq!(buckets.iter().map(|b| b.timestamp()).collect::<Vec<_>>(), vec![0, 300, 600]);
+        assert_eq!(series[0].monitor_id, a);
+        assert_eq!(series[0].avg, vec![Some(10.0), None, Some(30.0)]);
+        assert_eq!(series[1].avg, vec![None, Some(20.0), Some(40.0)]);
+        assert_eq!(series[1].p95, vec![None, Some(40.0), Some(80.0)]);
+    }
+}
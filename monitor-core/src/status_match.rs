@@ -0,0 +1,153 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{
+    encode::IsNull,
+    error::BoxDynError,
+    postgres::{PgArgumentBuffer, PgTypeInfo, PgValueRef},
+    Decode, Encode, Postgres, Type,
+};
+use std::{fmt, str::FromStr};
+use crate::{error::Result, Error};
+
+/// Ranges and codes one expression may list
+const MAX_PARTS: usize = 32;
+
+/// Status codes a monitor accepts, stored as text in
+/// `monitors.expected_status`.
+///
+/// JSON takes a single code such as `200`, or a string listing codes and
+/// inclusive ranges such as `"200-299"` or `"200,301,302"`. A single code is
+/// returned as a number, anything else as the canonical string.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "StatusRepr", into = "StatusRepr")]
+pub struct ExpectedStatus(Vec<(i32, i32)>);
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum StatusRepr {
+    Code(i32),
+    Expression(String),
+}
+
+impl ExpectedStatus {
+    pub fn matches(&self, code: i32) -> bool {
+        self.0.iter().any(|(min, max)| (*min..=*max).contains(&code))
+    }
+
+    /// The code when exactly one is accepted.
+    pub fn single(&self) -> Option<i32> {
+        match self.0.as_slice() {
+            [(min, max)] if min == max => Some(*min),
+            _ => None,
+        }
+    }
+}
+
+impl Default for ExpectedStatus {
+    fn default() -> Self {
+        Self::from(200)
+    }
+}
+
+impl From<i32> for ExpectedStatus {
+    fn from(code: i32) -> Self {
+        Self(vec![(code, code)])
+    }
+}
+
+impl fmt::Display for ExpectedStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (min, max)) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(",")?;
+            }
+            if min == max {
+                write!(f, "{}", min)?;
+            } else {
+                write!(f, "{}-{}", min, max)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for ExpectedStatus {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        let invalid = |reason: &str| Error::validation(format!("Invalid expected_status '{}': {}", value, reason));
+        let ranges = parse_ranges(value).ok_or_else(|| invalid("expected codes or ranges such as 200-299,304"))?;
+        if ranges.len() > MAX_PARTS {
+            return Err(invalid(&format!("at most {} codes or ranges are allowed", MAX_PARTS)));
+        }
+        for (min, max) in &ranges {
+            if !(100..=599).contains(min) || !(100..=599).contains(max) {
+                return Err(invalid("status codes must be between 100 and 599"));
+            }
+            if min > max {
+                return Err(invalid("ranges must run from low to high"));
+            }
+        }
+        Ok(Self(ranges))
+    }
+}
+
+/// `None` unless every comma-separated part is a code or `low-high`.
+fn parse_ranges(value: &str) -> Option<Vec<(i32, i32)>> {
+    value
+        .split(',')
+        .map(|part| {
+            let part = part.trim();
+            match part.split_once('-') {
+                Some((min, max)) => Some((min.trim().parse().ok()?, max.trim().parse().ok()?)),
+                None => part.parse().ok().map(|code| (code, code)),
+            }
+        })
+        .collect()
+}
+
+impl TryFrom<StatusRepr> for ExpectedStatus {
+    type Error = Error;
+
+    fn try_from(repr: StatusRepr) -> Result<Self> {
+        match repr {
+            StatusRepr::Code(code) => code.to_string().parse(),
+            StatusRepr::Expression(expression) => expression.parse(),
+        }
+    }
+}
+
+impl From<ExpectedStatus> for StatusRepr {
+    fn from(status: ExpectedStatus) -> Self {
+        match status.single() {
+            Some(code) => Self::Code(code),
+            None => Self::Expression(status.to_string()),
+        }
+    }
+}
+
+impl Type<Postgres> for ExpectedStatus {
+    fn type_info() -> PgTypeInfo {
+        <String as Type<Postgres>>::type_info()
+    }
+
+    fn compatible(ty: &PgTypeInfo) -> bool {
+        <String as Type<Postgres>>::compatible(ty)
+    }
+}
+
+impl Encode<'_, Postgres> for ExpectedStatus {
+    fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> std::result::Result<IsNull, BoxDynError> {
+        <String as Encode<Postgres>>::encode(self.to_string(), buf)
+    }
+}
+
+/// Codes stored before expressions were validated may be out of range; they
+/// are kept as they are rather than failing the whole row.
+impl<'r> Decode<'r, Postgres> for ExpectedStatus {
+    fn decode(value: PgValueRef<'r>) -> std::result::Result<Self, BoxDynError> {
+        let expression = <String as Decode<Postgres>>::decode(value)?;
+        parse_ranges(&expression)
+            .map(Self)
+            .ok_or_else(|| format!("Invalid stored expected_status '{}'", expression).into())
+    }
+}
@@ -0,0 +1,48 @@
+#[cfg(test)]
+mod status_match_tests {
+    use crate::status_match::*;
+    use serde_json::json;
+
+    fn parse(expression: &str) -> ExpectedStatus {
+        expression.parse().unwrap()
+    }
+
+    #[test]
+    fn single_codes_ranges_and_lists_match() {
+        assert!(parse("200").matches(200));
+        assert!(!parse("200").matches(201));
+        assert!(parse("200-299").matches(204));
+        assert!(!parse("200-299").matches(301));
+        let list = parse("200, 301,302");
+        assert!(list.matches(301) && list.matches(302));
+        assert!(!list.matches(303));
+        assert!(parse("200-204,304").matches(304));
+    }
+
+    #[test]
+    fn invalid_expressions_are_rejected() {
+        for expression in ["", "abc", "200-", "299-200", "99", "600", "200,,201", "2xx"] {
+            assert!(expression.parse::<ExpectedStatus>().is_err(), "{}", expression);
+        }
+        let too_many = (100..140).map(|code| code.to_string()).collect::<Vec<_>>().join(",");
+        assert!(too_many.parse::<ExpectedStatus>().is_err());
+    }
+
+    #[test]
+    fn json_takes_a_number_or_an_expression() {
+        let single: ExpectedStatus = serde_json::from_value(json!(204)).unwrap();
+        assert_eq!(single, ExpectedStatus::from(204));
+        let range: ExpectedStatus = serde_json::from_value(json!("200-299")).unwrap();
+        assert!(range.matches(250));
+        assert!(serde_json::from_value::<ExpectedStatus>(json!(42)).is_err());
+        assert!(serde_json::from_value::<ExpectedStatus>(json!("ok")).is_err());
+    }
+
+    #[test]
+    fn single_codes_serialize_as_numbers_and_the_rest_canonically() {
+        assert_eq!(serde_json::to_value(ExpectedStatus::default()).unwrap(), json!(200));
+        assert_eq!(serde_json::to_value(parse(" 200 - 299 ,304")).unwrap(), json!("200-299,304"));
+        assert_eq!(parse("200-200").single(), Some(200));
+        assert_eq!(parse("200,201").single(), None);
+    }
+}
@@ -0,0 +1,164 @@
+use chrono::{DateTime, Utc};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use uuid::Uuid;
+use crate::{
+    aws::{self, CanonicalRequest, Credentials},
+    error::Result,
+    Error,
+};
+
+pub const DEFAULT_REGION: &str = "us-east-1";
+pub const DEFAULT_PROBE_PREFIX: &str = "monitor-probe/";
+const SERVICE: &str = "s3";
+
+/// Credential keys the check reads from the monitor's `credentials`.
+pub const ACCESS_KEY_ID: &str = "access_key_id";
+pub const SECRET_ACCESS_KEY: &str = "secret_access_key";
+pub const SESSION_TOKEN: &str = "session_token";
+
+/// An object storage check run instead of the HTTP request. The monitor's
+/// `endpoint` is a path-style bucket URL, e.g.
+/// `https://s3.eu-west-1.amazonaws.com/my-bucket` or `http://minio:9000/backups`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StorageCheck {
+    /// Signing region; S3-compatible stores usually accept the default
+    pub region: Option<String>,
+    #[serde(flatten)]
+    pub operation: StorageOperation,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StorageOperation {
+    /// Metadata of an existing object
+    Head { key: String },
+    /// Downloads an existing object
+    Get { key: String },
+    /// Writes a probe object under `prefix`, reads it back and deletes it
+    RoundTrip { prefix: Option<String> },
+}
+
+impl StorageCheck {
+    pub fn region(&self) -> &str {
+        self.region.as_deref().unwrap_or(DEFAULT_REGION)
+    }
+
+    pub fn validate(&self, endpoint: &str) -> Result<()> {
+        let url = Url::parse(endpoint).map_err(|e| Error::validation(format!("Invalid storage endpoint: {}", e)))?;
+        if !["http", "https"].contains(&url.scheme()) || url.host_str().is_none() {
+            return Err(Error::validation("storage_check endpoints must be http or https URLs"));
+        }
+        if url.path().trim_matches('/').is_empty() || url.query().is_some() {
+            return Err(Error::validation(
+                "storage_check endpoints must be path-style bucket URLs such as https://s3.amazonaws.com/bucket",
+            ));
+        }
+        if self.region.as_ref().is_some_and(|region| !valid_region(region)) {
+            return Err(Error::validation("region must be a name such as eu-west-1"));
+        }
+        match &self.operation {
+            StorageOperation::Head { key } | StorageOperation::Get { key } if !valid_key(key) => {
+                Err(Error::validation("key must be a non-empty object key without a leading '/'"))
+            }
+            StorageOperation::RoundTrip { prefix: Some(prefix) } if !prefix.is_empty() && !valid_key(prefix) => {
+                Err(Error::validation("prefix must not start with '/'"))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+fn valid_region(region: &str) -> bool {
+    !region.is_empty() && region.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+fn valid_key(key: &str) -> bool {
+    !key.is_empty() && !key.starts_with('/') && key.len() <= 1024
+}
+
+/// Access keys from the monitor's decrypted credentials.
+pub fn credentials(values: &HashMap<String, String>) -> Result<Credentials> {
+    match (values.get(ACCESS_KEY_ID), values.get(SECRET_ACCESS_KEY)) {
+        (Some(access_key_id), Some(secret_access_key)) => Ok(Credentials {
+            access_key_id: access_key_id.clone(),
+            secret_access_key: secret_access_key.clone(),
+            session_token: values.get(SESSION_TOKEN).cloned(),
+        }),
+        _ => Err(Error::validation(format!(
+            "storage checks need {} and {} in the monitor's credentials",
+            ACCESS_KEY_ID, SECRET_ACCESS_KEY
+        ))),
+    }
+}
+
+/// A fresh key for a round trip, so concurrent or failed runs never collide.
+pub fn probe_key(prefix: Option<&str>, monitor_id: Uuid) -> String {
+    format!("{}{}/{}", prefix.unwrap_or(DEFAULT_PROBE_PREFIX), monitor_id, Uuid::new_v4())
+}
+
+/// The object's URL under the bucket URL, with each key segment encoded the
+/// way SigV4 expects for S3.
+pub fn object_url(endpoint: &str, key: &str) -> Result<Url> {
+    let encoded: Vec<String> = key.split('/').map(aws::uri_encode).collect();
+    let url = format!("{}/{}", endpoint.trim_end_matches('/'), encoded.join("/"));
+    Url::parse(&url).map_err(|e| Error::validation(format!("Invalid storage endpoint: {}", e)))
+}
+
+/// Headers that sign a request for `url` with `payload` as its body,
+/// `authorization` included.
+pub fn signed_headers(
+    credentials: &Credentials,
+    region: &str,
+    method: &str,
+    url: &Url,
+    payload: &[u8],
+    at: DateTime<Utc>,
+) -> Result<Vec<(&'static str, String)>> {
+    let host = url.host_str().ok_or_else(|| Error::validation("Storage endpoint has no host"))?;
+    // Default ports are left out of the Host header, and so of the signature
+    let host = match url.port() {
+        Some(port) => format!("{}:{}", host, port),
+        None => host.to_string(),
+    };
+    let payload_hash = hex::encode(Sha256::digest(payload));
+    let mut headers = vec![("x-amz-content-sha256", payload_hash.clone())];
+    if let Some(token) = &credentials.session_token {
+        headers.push(("x-amz-security-token", token.clone()));
+    }
+
+    let request = CanonicalRequest {
+        method,
+        host: &host,
+        path: url.path(),
+        query: "",
+        headers: headers.iter().map(|(name, value)| (*name, value.as_str())).collect(),
+        payload_hash,
+    };
+    let authorization = aws::sign(credentials, SERVICE, region, &request, at);
+    headers.push(("x-amz-date", at.format("%Y%m%dT%H%M%SZ").to_string()));
+    headers.push(("authorization", authorization));
+    Ok(headers)
+}
+
+/// The `Code` and `Message` of an S3 error body, for the result's error message.
+pub fn error_message(status: u16, body: &str) -> String {
+    let detail = roxmltree::Document::parse(body).ok().and_then(|doc| {
+        let text = |name: &str| {
+            doc.descendants()
+                .find(|n| n.tag_name().name() == name)
+                .and_then(|n| n.text())
+                .map(str::to_string)
+        };
+        match (text("Code"), text("Message")) {
+            (Some(code), Some(message)) => Some(format!("{}: {}", code, message)),
+            (code, message) => code.or(message),
+        }
+    });
+    match detail {
+        Some(detail) => format!("Storage returned HTTP {} ({})", status, detail),
+        None => format!("Storage returned HTTP {}", status),
+    }
+}
@@ -0,0 +1,99 @@
+#[cfg(test)]
+mod storage_tests {
+    use crate::aws::{self, CanonicalRequest, Credentials};
+    use crate::storage::{self, *};
+    use chrono::{TimeZone, Utc};
+    use std::collections::HashMap;
+    use uuid::Uuid;
+
+    fn credentials() -> Credentials {
+        Credentials {
+            access_key_id: "AKIDEXAMPLE".to_string(),
+            secret_access_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".to_string(),
+            session_token: None,
+        }
+    }
+
+    #[test]
+    fn test_sign_matches_sigv4_test_suite() {
+        // "post-vanilla" from the AWS Signature Version 4 test suite
+        let request = CanonicalRequest {
+            method: "POST",
+            host: "example.amazonaws.com",
+            path: "/",
+            query: "",
+            headers: Vec::new(),
+            payload_hash: "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855".to_string(),
+        };
+        let at = Utc.with_ymd_and_hms(2015, 8, 30, 12, 36, 0).unwrap();
+        assert!(aws::sign(&credentials(), "service", "us-east-1", &request, at)
+            .ends_with("Signature=5da7c1a2acd57cee7505fc6676e4e544621c30862966e37dddb68e92efbe5d6b"));
+    }
+
+    #[test]
+    fn test_checks_parse_and_validate() {
+        let check: StorageCheck = serde_json::from_value(serde_json::json!({
+            "type": "head", "key": "health/ok.txt", "region": "eu-west-1"
+        }))
+        .unwrap();
+        assert_eq!(check.region(), "eu-west-1");
+        assert!(check.validate("https://s3.eu-west-1.amazonaws.com/my-bucket").is_ok());
+        assert!(check.validate("https://s3.eu-west-1.amazonaws.com/").is_err());
+        assert!(check.validate("ftp://s3.amazonaws.com/my-bucket").is_err());
+
+        let round_trip: StorageCheck = serde_json::from_value(serde_json::json!({ "type": "round_trip" })).unwrap();
+        assert_eq!(round_trip.operation, StorageOperation::RoundTrip { prefix: None });
+        assert_eq!(round_trip.region(), DEFAULT_REGION);
+        assert!(round_trip.validate("http://minio:9000/backups").is_ok());
+
+        let absolute = StorageCheck {
+            region: None,
+            operation: StorageOperation::Get { key: "/x".to_string() },
+        };
+        assert!(absolute.validate("http://minio:9000/backups").is_err());
+    }
+
+    #[test]
+    fn test_object_urls_encode_key_segments() {
+        let url = object_url("https://s3.amazonaws.com/bucket/", "reports/2024 Q1+final.csv").unwrap();
+        assert_eq!(url.as_str(), "https://s3.amazonaws.com/bucket/reports/2024%20Q1%2Bfinal.csv");
+
+        let key = probe_key(Some("probes/"), Uuid::nil());
+        assert!(key.starts_with("probes/00000000-0000-0000-0000-000000000000/"));
+    }
+
+    #[test]
+    fn test_signed_headers() {
+        let url = object_url("http://minio:9000/bucket", "probe").unwrap();
+        let at = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let headers: HashMap<_, _> = signed_headers(&credentials(), "us-east-1", "PUT", &url, b"hello", at)
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(
+            headers["x-amz-content-sha256"],
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+        );
+        assert_eq!(headers["x-amz-date"], "20240301T120000Z");
+        assert!(headers["authorization"].starts_with(
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20240301/us-east-1/s3/aws4_request, \
+             SignedHeaders=host;x-amz-content-sha256;x-amz-date, "
+        ));
+    }
+
+    #[test]
+    fn test_credentials_and_errors() {
+        let mut values = HashMap::new();
+        values.insert(ACCESS_KEY_ID.to_string(), "AKID".to_string());
+        assert!(storage::credentials(&values).is_err());
+        values.insert(SECRET_ACCESS_KEY.to_string(), "secret".to_string());
+        assert_eq!(storage::credentials(&values).unwrap().session_token, None);
+
+        let body = "<Error><Code>NoSuchKey</Code><Message>The specified key does not exist.</Message></Error>";
+        assert_eq!(
+            error_message(404, body),
+            "Storage returned HTTP 404 (NoSuchKey: The specified key does not exist.)"
+        );
+        assert_eq!(error_message(503, ""), "Storage returned HTTP 503");
+    }
+}
@@ -0,0 +1,329 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tokio::{sync::watch, task::JoinHandle};
+use tracing::{error, info, warn};
+use crate::{error::Result, metrics};
+
+const METRIC_TASK_RESTARTS: &str = "monitor_task_restarts_total";
+
+/// How long a task may take to return after shutdown is signalled before it
+/// is aborted
+pub const DEFAULT_GRACE: Duration = Duration::from_secs(10);
+
+/// Delay between restarts, doubling with each consecutive failure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Backoff {
+    pub initial: Duration,
+    pub max: Duration,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self::exponential(Duration::from_secs(1), Duration::from_secs(60))
+    }
+}
+
+impl Backoff {
+    pub const fn fixed(delay: Duration) -> Self {
+        Self { initial: delay, max: delay }
+    }
+
+    pub const fn exponential(initial: Duration, max: Duration) -> Self {
+        Self { initial, max }
+    }
+
+    /// Delay before the restart following `failures` earlier consecutive ones.
+    pub fn delay(&self, failures: u32) -> Duration {
+        self.initial.saturating_mul(2u32.saturating_pow(failures)).min(self.max)
+    }
+}
+
+/// What happens when a supervised task returns or panics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestartPolicy {
+    /// Run once
+    Never,
+    /// Restart after an error or a panic; returning `Ok` ends the task
+    OnFailure(Backoff),
+    /// Restart whenever the task ends
+    Always(Backoff),
+}
+
+impl RestartPolicy {
+    /// The backoff to restart with, or `None` when the task is done.
+    pub fn restart(&self, failed: bool) -> Option<Backoff> {
+        match *self {
+            RestartPolicy::Never => None,
+            RestartPolicy::OnFailure(backoff) => failed.then_some(backoff),
+            RestartPolicy::Always(backoff) => Some(backoff),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskStatus {
+    Running,
+    /// Waiting out the backoff before the next run
+    Restarting,
+    /// Returned and will not be restarted, or stopped for shutdown
+    Finished,
+    /// Failed and will not be restarted
+    Failed,
+}
+
+/// Reported per task on the API's /health.
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskHealth {
+    pub name: String,
+    pub status: TaskStatus,
+    pub restarts: u32,
+    pub last_error: Option<String>,
+    /// When the current or last run started
+    pub started_at: DateTime<Utc>,
+}
+
+/// Handed to every run of a task; long-running loops should return once it
+/// fires.
+#[derive(Debug, Clone)]
+pub struct ShutdownSignal(watch::Receiver<bool>);
+
+impl ShutdownSignal {
+    pub fn is_shutdown(&self) -> bool {
+        *self.0.borrow()
+    }
+
+    /// Resolves once shutdown starts, or when the supervisor is dropped.
+    pub async fn wait(&mut self) {
+        let _ = self.0.wait_for(|stopping| *stopping).await;
+    }
+}
+
+type ShutdownHook = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// Runs named background tasks for the lifetime of the process, restarting
+/// them by their [`RestartPolicy`] and stopping them on [`Supervisor::shutdown`].
+#[derive(Clone)]
+pub struct Supervisor {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    grace: Duration,
+    shutdown: watch::Sender<bool>,
+    tasks: Mutex<Vec<JoinHandle<()>>>,
+    health: Mutex<BTreeMap<String, TaskHealth>>,
+    hooks: Mutex<Vec<(String, ShutdownHook)>>,
+}
+
+impl std::fmt::Debug for Supervisor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let health = self.inner.health.lock().unwrap();
+        f.debug_struct("Supervisor")
+            .field("grace", &self.inner.grace)
+            .field("tasks", &health.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl Default for Supervisor {
+    fn default() -> Self {
+        Self::new(DEFAULT_GRACE)
+    }
+}
+
+impl Supervisor {
+    pub fn new(grace: Duration) -> Self {
+        let (shutdown, _) = watch::channel(false);
+        Self {
+            inner: Arc::new(Inner {
+                grace,
+                shutdown,
+                tasks: Mutex::new(Vec::new()),
+                health: Mutex::new(BTreeMap::new()),
+                hooks: Mutex::new(Vec::new()),
+            }),
+        }
+    }
+
+    /// Starts `task` under `name`, which should be unique. `task` is called
+    /// again for every restart, so anything that must be in place before the
+    /// first run, such as a subscription, can be set up before its future.
+    pub fn spawn<F, Fut>(&self, name: &str, policy: RestartPolicy, task: F)
+    where
+        F: FnMut(ShutdownSignal) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        if self.is_shutdown() {
+            warn!("Not starting task {}: shutting down", name);
+            return;
+        }
+        self.inner.health.lock().unwrap().insert(
+            name.to_string(),
+            TaskHealth {
+                name: name.to_string(),
+                status: TaskStatus::Running,
+                restarts: 0,
+                last_error: None,
+                started_at: Utc::now(),
+            },
+        );
+        let handle = tokio::spawn(supervise(self.inner.clone(), name.to_string(), policy, task));
+        self.inner.tasks.lock().unwrap().push(handle);
+    }
+
+    /// Runs `hook` during shutdown, after every task has stopped. Hooks run
+    /// one at a time in the order they were added.
+    pub fn on_shutdown(&self, name: &str, hook: impl Future<Output = ()> + Send + 'static) {
+        self.inner.hooks.lock().unwrap().push((name.to_string(), Box::pin(hook)));
+    }
+
+    pub fn signal(&self) -> ShutdownSignal {
+        ShutdownSignal(self.inner.shutdown.subscribe())
+    }
+
+    pub fn is_shutdown(&self) -> bool {
+        *self.inner.shutdown.borrow()
+    }
+
+    /// Every task, by name.
+    pub fn health(&self) -> Vec<TaskHealth> {
+        self.inner.health.lock().unwrap().values().cloned().collect()
+    }
+
+    /// False once a task has failed for good.
+    pub fn healthy(&self) -> bool {
+        self.inner.health.lock().unwrap().values().all(|task| task.status != TaskStatus::Failed)
+    }
+
+    /// Signals every task to stop, waits for them (aborting any that outlast
+    /// the grace period) and then runs the shutdown hooks. Later calls return
+    /// at once.
+    pub async fn shutdown(&self) {
+        if self.inner.shutdown.send_replace(true) {
+            return;
+        }
+        info!("Stopping background tasks");
+        let tasks = std::mem::take(&mut *self.inner.tasks.lock().unwrap());
+        for task in tasks {
+            let _ = task.await;
+        }
+        let hooks = std::mem::take(&mut *self.inner.hooks.lock().unwrap());
+        for (name, hook) in hooks {
+            info!("Running shutdown hook {}", name);
+            hook.await;
+        }
+    }
+}
+
+impl Inner {
+    fn update(&self, name: &str, apply: impl FnOnce(&mut TaskHealth)) {
+        if let Some(health) = self.health.lock().unwrap().get_mut(name) {
+            apply(health);
+        }
+    }
+
+    fn finish(&self, name: &str, error: Option<String>) {
+        self.update(name, |health| {
+            health.status = if error.is_some() { TaskStatus::Failed } else { TaskStatus::Finished };
+            if error.is_some() {
+                health.last_error = error;
+            }
+        });
+    }
+}
+
+async fn supervise<F, Fut>(inner: Arc<Inner>, name: String, policy: RestartPolicy, mut task: F)
+where
+    F: FnMut(ShutdownSignal) -> Fut + Send + 'static,
+    Fut: Future<Output = Result<()>> + Send + 'static,
+{
+    let mut signal = ShutdownSignal(inner.shutdown.subscribe());
+    let mut failures = 0;
+    loop {
+        let started = Instant::now();
+        // Each run gets its own task so a panic ends the run, not the supervisor
+        let mut run = tokio::spawn(task(signal.clone()));
+        let outcome = tokio::select! {
+            outcome = &mut run => outcome,
+            _ = signal.wait() => match tokio::time::timeout(inner.grace, &mut run).await {
+                Ok(outcome) => outcome,
+                Err(_) => {
+                    run.abort();
+                    warn!("Task {} did not stop within {:?} and was aborted", name, inner.grace);
+                    inner.finish(&name, None);
+                    return;
+                }
+            },
+        };
+        let error = match outcome {
+            Ok(Ok(())) => None,
+            Ok(Err(e)) => Some(e.to_string()),
+            Err(e) if e.is_panic() => Some(format!("panicked: {}", panic_message(e.into_panic()))),
+            Err(e) => Some(e.to_string()),
+        };
+
+        if signal.is_shutdown() {
+            inner.finish(&name, error);
+            return;
+        }
+        let Some(backoff) = policy.restart(error.is_some()) else {
+            match &error {
+                Some(e) => error!("Task {} failed and will not be restarted: {}", name, e),
+                None => info!("Task {} finished", name),
+            }
+            inner.finish(&name, error);
+            return;
+        };
+
+        // A run that outlasted the longest delay starts the backoff over
+        if started.elapsed() >= backoff.max {
+            failures = 0;
+        }
+        let delay = backoff.delay(failures);
+        failures = failures.saturating_add(1);
+        match &error {
+            Some(e) => warn!("Task {} failed, restarting in {:?}: {}", name, delay, e),
+            None => info!("Task {} returned, restarting in {:?}", name, delay),
+        }
+        let registry = metrics::global();
+        registry.describe(METRIC_TASK_RESTARTS, "Background task restarts");
+        registry.increment_counter(METRIC_TASK_RESTARTS, &[("task", name.as_str())]);
+        inner.update(&name, |health| {
+            health.status = TaskStatus::Restarting;
+            health.restarts += 1;
+            if error.is_some() {
+                health.last_error = error;
+            }
+        });
+
+        tokio::select! {
+            _ = tokio::time::sleep(delay) => {}
+            _ = signal.wait() => {
+                inner.finish(&name, None);
+                return;
+            }
+        }
+        inner.update(&name, |health| {
+            health.status = TaskStatus::Running;
+            health.started_at = Utc::now();
+        });
+    }
+}
+
+pub fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
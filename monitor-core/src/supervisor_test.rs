@@ -0,0 +1,115 @@
+#[cfg(test)]
+mod supervisor_tests {
+    use crate::supervisor::*;
+    use crate::Error;
+    use std::{
+        future::Future,
+        sync::{
+            atomic::{AtomicU32, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+
+    const QUICK: Backoff = Backoff::fixed(Duration::from_millis(1));
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap().block_on(future)
+    }
+
+    /// Polls `done` for up to five seconds; panics with backtraces can be slow.
+    async fn wait_until(done: impl Fn() -> bool) {
+        for _ in 0..500 {
+            if done() {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("timed out waiting for the supervisor");
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_maximum() {
+        let backoff = Backoff::exponential(Duration::from_secs(1), Duration::from_secs(10));
+        assert_eq!(backoff.delay(0), Duration::from_secs(1));
+        assert_eq!(backoff.delay(2), Duration::from_secs(4));
+        assert_eq!(backoff.delay(4), Duration::from_secs(10));
+        assert_eq!(backoff.delay(u32::MAX), Duration::from_secs(10));
+        assert_eq!(Backoff::fixed(Duration::from_secs(5)).delay(3), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn policies_decide_which_outcomes_restart() {
+        assert_eq!(RestartPolicy::Never.restart(true), None);
+        assert_eq!(RestartPolicy::OnFailure(QUICK).restart(false), None);
+        assert_eq!(RestartPolicy::OnFailure(QUICK).restart(true), Some(QUICK));
+        assert_eq!(RestartPolicy::Always(QUICK).restart(false), Some(QUICK));
+    }
+
+    #[test]
+    fn failing_tasks_restart_until_they_succeed() {
+        block_on(async {
+            let supervisor = Supervisor::default();
+            let runs = Arc::new(AtomicU32::new(0));
+            let counter = runs.clone();
+            supervisor.spawn("flaky", RestartPolicy::OnFailure(QUICK), move |_| {
+                let run = counter.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    match run {
+                        0 => Err(Error::internal("connection refused")),
+                        1 => panic!("lost the listener"),
+                        _ => Ok(()),
+                    }
+                }
+            });
+            wait_until(|| supervisor.health()[0].status == TaskStatus::Finished).await;
+
+            assert_eq!(runs.load(Ordering::SeqCst), 3);
+            let health = &supervisor.health()[0];
+            assert_eq!(health.status, TaskStatus::Finished);
+            assert_eq!(health.restarts, 2);
+            assert!(health.last_error.as_deref().unwrap().contains("lost the listener"));
+            assert!(supervisor.healthy());
+        });
+    }
+
+    #[test]
+    fn a_task_that_is_not_restarted_reports_its_failure() {
+        block_on(async {
+            let supervisor = Supervisor::default();
+            supervisor.spawn("once", RestartPolicy::Never, |_| async { Err(Error::internal("bad config")) });
+            wait_until(|| !supervisor.healthy()).await;
+
+            let health = &supervisor.health()[0];
+            assert_eq!(health.status, TaskStatus::Failed);
+            assert_eq!(health.restarts, 0);
+            assert!(!supervisor.healthy());
+        });
+    }
+
+    #[test]
+    fn shutdown_stops_tasks_then_runs_hooks() {
+        block_on(async {
+            let supervisor = Supervisor::new(Duration::from_millis(50));
+            let order = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+            let stopped = order.clone();
+            supervisor.spawn("cooperative", RestartPolicy::Always(QUICK), move |mut signal| {
+                let stopped = stopped.clone();
+                async move {
+                    signal.wait().await;
+                    stopped.lock().unwrap().push("task");
+                    Ok(())
+                }
+            });
+            supervisor.spawn("stubborn", RestartPolicy::Always(QUICK), |_| std::future::pending());
+            let hooked = order.clone();
+            supervisor.on_shutdown("release", async move { hooked.lock().unwrap().push("hook") });
+
+            supervisor.shutdown().await;
+            assert_eq!(*order.lock().unwrap(), ["task", "hook"]);
+            assert!(supervisor.health().iter().all(|task| task.status == TaskStatus::Finished));
+            assert!(supervisor.signal().is_shutdown());
+        });
+    }
+}
@@ -0,0 +1,67 @@
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use crate::{error::Result, Error};
+
+const MAX_PAYLOAD_LEN: usize = 4096;
+const MAX_BANNER_LEN: usize = 1024;
+/// Bytes read from the server while looking for the banner
+pub const MAX_READ_BYTES: usize = 4096;
+/// What a failure message quotes of the server's answer
+const MAX_QUOTED_CHARS: usize = 200;
+
+/// A TCP port check run instead of the HTTP request. The monitor's
+/// `endpoint` is `tcp://host:port`; connecting is enough to pass unless a
+/// banner is expected.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TcpCheck {
+    /// Sent once connected, e.g. `"PING\r\n"`
+    #[serde(default)]
+    pub payload: Option<String>,
+    /// Text the server must send, after the payload if there is one, e.g.
+    /// `SSH-2.0` or `+PONG`
+    #[serde(default)]
+    pub banner: Option<String>,
+}
+
+impl TcpCheck {
+    pub fn validate(&self, endpoint: &str) -> Result<()> {
+        target(endpoint)?;
+        if self.payload.as_ref().is_some_and(|payload| payload.is_empty() || payload.len() > MAX_PAYLOAD_LEN) {
+            return Err(Error::validation(format!("payload must be 1 to {} bytes", MAX_PAYLOAD_LEN)));
+        }
+        if self.banner.as_ref().is_some_and(|banner| banner.is_empty() || banner.len() > MAX_BANNER_LEN) {
+            return Err(Error::validation(format!("banner must be 1 to {} bytes", MAX_BANNER_LEN)));
+        }
+        Ok(())
+    }
+
+    /// Whether `received` holds the banner, if one is expected.
+    pub fn banner_found(&self, received: &[u8]) -> bool {
+        match &self.banner {
+            Some(banner) => received.windows(banner.len()).any(|window| window == banner.as_bytes()),
+            None => true,
+        }
+    }
+
+    /// `Err` carries the result's error message when the banner is missing.
+    pub fn evaluate(&self, received: &[u8]) -> std::result::Result<(), String> {
+        match &self.banner {
+            Some(banner) if !self.banner_found(received) => {
+                let received = String::from_utf8_lossy(received);
+                let text: String = received.trim_end().chars().take(MAX_QUOTED_CHARS).collect();
+                Err(format!("expected '{}' from the server, got '{}'", banner, text))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Host and port of a `tcp://host:port` endpoint; the port is required.
+pub fn target(endpoint: &str) -> Result<(String, u16)> {
+    let url = Url::parse(endpoint).map_err(|e| Error::validation(format!("Invalid TCP endpoint: {}", e)))?;
+    match (url.scheme(), url.host_str(), url.port()) {
+        // IPv6 hosts come bracketed
+        ("tcp", Some(host), Some(port)) => Ok((host.trim_matches(['[', ']']).to_string(), port)),
+        _ => Err(Error::validation("tcp_check endpoints must be tcp://host:port URLs")),
+    }
+}
@@ -0,0 +1,40 @@
+#[cfg(test)]
+mod tcp_tests {
+    use crate::tcp::*;
+
+    fn check(value: serde_json::Value) -> TcpCheck {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_target() {
+        assert_eq!(target("tcp://db.example.com:5432").unwrap(), ("db.example.com".to_string(), 5432));
+        assert_eq!(target("tcp://[::1]:22").unwrap(), ("::1".to_string(), 22));
+        assert!(target("tcp://db.example.com").is_err());
+        assert!(target("http://db.example.com:5432").is_err());
+    }
+
+    #[test]
+    fn test_validate() {
+        let endpoint = "tcp://cache.example.com:6379";
+        assert!(TcpCheck::default().validate(endpoint).is_ok());
+        assert!(check(serde_json::json!({ "payload": "PING\r\n", "banner": "+PONG" })).validate(endpoint).is_ok());
+        assert!(check(serde_json::json!({ "banner": "" })).validate(endpoint).is_err());
+        assert!(check(serde_json::json!({ "payload": "x".repeat(5000) })).validate(endpoint).is_err());
+    }
+
+    #[test]
+    fn test_banner() {
+        let ssh = check(serde_json::json!({ "banner": "SSH-2.0" }));
+        assert!(ssh.banner_found(b"SSH-2.0-OpenSSH_9.6\r\n"));
+        assert!(!ssh.banner_found(b"SSH-2"));
+        assert!(ssh.evaluate(b"SSH-2.0-OpenSSH_9.6\r\n").is_ok());
+        assert_eq!(
+            ssh.evaluate(b"220 mail.example.com ESMTP\r\n").unwrap_err(),
+            "expected 'SSH-2.0' from the server, got '220 mail.example.com ESMTP'"
+        );
+
+        // Without a banner, connecting is enough
+        assert!(TcpCheck::default().evaluate(b"").is_ok());
+    }
+}
@@ -0,0 +1,138 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use crate::{
+    db::DatabasePool,
+    error::Result,
+    models::{Monitor, UserRole},
+    Error,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Team {
+    pub id: Uuid,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateTeamRequest {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct TeamMember {
+    pub user_id: Uuid,
+    pub username: String,
+    pub added_at: DateTime<Utc>,
+}
+
+/// Admins may edit any monitor and viewers none. Others may edit monitors they
+/// own or that belong to one of their teams; monitors with neither an owner nor
+/// a team predate ownership and stay editable by everyone. System monitors are
+/// left to admins.
+pub fn can_edit(monitor: &Monitor, user_id: Uuid, role: UserRole, team_ids: &[Uuid]) -> bool {
+    match role {
+        UserRole::Admin => true,
+        UserRole::Viewer => false,
+        UserRole::Member if monitor.system_key.is_some() => false,
+        UserRole::Member => match (monitor.owner_id, monitor.team_id) {
+            (None, None) => true,
+            (owner, team) => owner == Some(user_id) || team.is_some_and(|t| team_ids.contains(&t)),
+        },
+    }
+}
+
+pub async fn create(db: &DatabasePool, request: &CreateTeamRequest) -> Result<Team> {
+    let name = request.name.trim();
+    if name.is_empty() {
+        return Err(Error::validation("Team name is required"));
+    }
+
+    let exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM teams WHERE name = $1)")
+        .bind(name)
+        .fetch_one(db)
+        .await?;
+    if exists {
+        return Err(Error::validation(format!("Team {} already exists", name)));
+    }
+
+    let team = sqlx::query_as::<_, Team>(
+        "INSERT INTO teams (id, name, created_at) VALUES ($1, $2, NOW()) RETURNING *",
+    )
+    .bind(Uuid::new_v4())
+    .bind(name)
+    .fetch_one(db)
+    .await?;
+    Ok(team)
+}
+
+/// All teams, or only the ones `user_id` belongs to.
+pub async fn list(db: &DatabasePool, member: Option<Uuid>) -> Result<Vec<Team>> {
+    let teams = sqlx::query_as::<_, Team>(
+        r#"
+        SELECT * FROM teams
+        WHERE $1::uuid IS NULL OR id IN (SELECT team_id FROM team_members WHERE user_id = $1)
+        ORDER BY name
+        "#,
+    )
+    .bind(member)
+    .fetch_all(db)
+    .await?;
+    Ok(teams)
+}
+
+/// Returns whether the team existed.
+pub async fn delete(db: &DatabasePool, team_id: Uuid) -> Result<bool> {
+    let result = sqlx::query("DELETE FROM teams WHERE id = $1")
+        .bind(team_id)
+        .execute(db)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn members(db: &DatabasePool, team_id: Uuid) -> Result<Vec<TeamMember>> {
+    let members = sqlx::query_as::<_, TeamMember>(
+        r#"
+        SELECT m.user_id, u.username, m.added_at
+        FROM team_members m
+        JOIN users u ON u.id = m.user_id
+        WHERE m.team_id = $1
+        ORDER BY u.username
+        "#,
+    )
+    .bind(team_id)
+    .fetch_all(db)
+    .await?;
+    Ok(members)
+}
+
+pub async fn add_member(db: &DatabasePool, team_id: Uuid, user_id: Uuid) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO team_members (team_id, user_id, added_at) VALUES ($1, $2, NOW()) ON CONFLICT DO NOTHING",
+    )
+    .bind(team_id)
+    .bind(user_id)
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/// Returns whether the user was a member.
+pub async fn remove_member(db: &DatabasePool, team_id: Uuid, user_id: Uuid) -> Result<bool> {
+    let result = sqlx::query("DELETE FROM team_members WHERE team_id = $1 AND user_id = $2")
+        .bind(team_id)
+        .bind(user_id)
+        .execute(db)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn team_ids_for(db: &DatabasePool, user_id: Uuid) -> Result<Vec<Uuid>> {
+    let ids = sqlx::query_scalar("SELECT team_id FROM team_members WHERE user_id = $1")
+        .bind(user_id)
+        .fetch_all(db)
+        .await?;
+    Ok(ids)
+}
@@ -0,0 +1,100 @@
+#[cfg(test)]
+mod teams_tests {
+    use crate::{
+        durations::Seconds,
+        http_method::HttpMethod,
+        models::{Monitor, UserRole},
+        teams::*,
+    };
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    fn monitor(owner_id: Option<Uuid>, team_id: Option<Uuid>) -> Monitor {
+        let now = Utc::now();
+        Monitor {
+            id: Uuid::new_v4(),
+            name: "api".to_string(),
+            endpoint: "https://example.com/health".to_string(),
+            method: HttpMethod::Get,
+            headers: None,
+            body: None,
+            expected_status: 200.into(),
+            timeout: None,
+            interval: Seconds::new(60),
+            script: None,
+            pre_request_script: None,
+            enabled: true,
+            paused_reason: None,
+            paused_by: None,
+            paused_at: None,
+            resume_at: None,
+            tags: vec![],
+            owner_id,
+            team_id,
+            system_key: None,
+            retries: None,
+            retry_delay_ms: None,
+            schedule_cron: None,
+            notification_channels: None,
+            script_profile: None,
+            credentials: None,
+            steps: None,
+            metric_assertions: None,
+            broker_check: None,
+            storage_check: None,
+            ntp_check: None,
+            snmp_check: None,
+            exec_check: None,
+            tcp_check: None,
+            dns_check: None,
+            tls_check: None,
+            websocket_check: None,
+            mail_check: None,
+            database_check: None,
+            heartbeat_check: None,
+            expected_body_contains: None,
+            expected_body_regex: None,
+            bypass_dns_cache: false,
+            connect_timeout_ms: None,
+            tls_timeout_ms: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn test_owner_and_team_members_can_edit() {
+        let owner = Uuid::new_v4();
+        let other = Uuid::new_v4();
+        let team = Uuid::new_v4();
+        let owned = monitor(Some(owner), Some(team));
+
+        assert!(can_edit(&owned, owner, UserRole::Member, &[]));
+        assert!(can_edit(&owned, other, UserRole::Member, &[team]));
+        assert!(!can_edit(&owned, other, UserRole::Member, &[Uuid::new_v4()]));
+        assert!(can_edit(&owned, other, UserRole::Admin, &[]));
+    }
+
+    #[test]
+    fn test_viewers_cannot_edit() {
+        let viewer = Uuid::new_v4();
+        assert!(!can_edit(&monitor(Some(viewer), None), viewer, UserRole::Viewer, &[]));
+        assert!(!can_edit(&monitor(None, None), viewer, UserRole::Viewer, &[]));
+    }
+
+    #[test]
+    fn test_unowned_monitors_stay_editable() {
+        let user = Uuid::new_v4();
+        assert!(can_edit(&monitor(None, None), user, UserRole::Member, &[]));
+        assert!(!can_edit(&monitor(None, Some(Uuid::new_v4())), user, UserRole::Member, &[]));
+    }
+
+    #[test]
+    fn test_system_monitors_need_an_admin() {
+        let user = Uuid::new_v4();
+        let mut system = monitor(None, None);
+        system.system_key = Some("api".to_string());
+        assert!(!can_edit(&system, user, UserRole::Member, &[]));
+        assert!(can_edit(&system, user, UserRole::Admin, &[]));
+    }
+}
@@ -0,0 +1,91 @@
+use reqwest::{Client, StatusCode};
+use serde_json::json;
+use std::time::Duration;
+use crate::{
+    alerts::{AlertNotification, AlertState},
+    error::Result,
+    Error,
+};
+
+const API_URL: &str = "https://api.telegram.org";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// Attempts per message when Telegram asks us to slow down
+const MAX_ATTEMPTS: usize = 3;
+/// Longer waits are not worth holding the alert dispatcher for
+const MAX_RETRY_AFTER_SECS: u64 = 30;
+
+/// Escapes the characters MarkdownV2 reserves outside of entities.
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "_*[]()~`>#+-=|{}.!\\".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// The alert as a MarkdownV2 message: a bold headline, the summary and
+/// whatever is known about the latest result.
+pub fn message(notification: &AlertNotification) -> String {
+    let marker = match notification.state {
+        AlertState::Firing => "🔴",
+        AlertState::Resolved => "✅",
+    };
+    let mut lines = vec![
+        format!("{} *{}*", marker, escape(&notification.subject())),
+        escape(&notification.summary),
+        escape(&notification.endpoint),
+    ];
+    if let Some(status) = &notification.status {
+        lines.push(format!("Status: {}", escape(status)));
+    }
+    if let Some(ms) = notification.response_time {
+        lines.push(format!("Response time: {} ms", ms));
+    }
+    if let Some(error) = &notification.error_message {
+        lines.push(format!("Error: _{}_", escape(error)));
+    }
+    lines.join("\n")
+}
+
+/// Seconds Telegram asks to wait, from a `429` response body.
+pub fn retry_after(body: &serde_json::Value) -> Option<u64> {
+    body.pointer("/parameters/retry_after").and_then(|v| v.as_u64())
+}
+
+/// Sends the message, waiting and retrying when rate limited.
+pub async fn send(client: &Client, bot_token: &str, chat_id: &str, notification: &AlertNotification) -> Result<()> {
+    let url = format!("{}/bot{}/sendMessage", API_URL, bot_token);
+    let payload = json!({
+        "chat_id": chat_id,
+        "text": message(notification),
+        "parse_mode": "MarkdownV2",
+        "disable_web_page_preview": true,
+    });
+
+    for attempt in 1..=MAX_ATTEMPTS {
+        let response = client
+            .post(&url)
+            .json(&payload)
+            .timeout(REQUEST_TIMEOUT)
+            .send()
+            .await
+            .map_err(|e| Error::internal(format!("Telegram request failed: {}", e.without_url())))?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(());
+        }
+        let body: serde_json::Value = response.json().await.unwrap_or_default();
+        if status == StatusCode::TOO_MANY_REQUESTS && attempt < MAX_ATTEMPTS {
+            let wait = retry_after(&body).unwrap_or(1).min(MAX_RETRY_AFTER_SECS);
+            tokio::time::sleep(Duration::from_secs(wait)).await;
+            continue;
+        }
+        let description = body.get("description").and_then(|d| d.as_str()).unwrap_or_default();
+        // The token is part of the URL; only the status and Telegram's description are reported
+        return Err(Error::internal(format!("Telegram rejected the message: HTTP {} {}", status, description)));
+    }
+    Err(Error::internal("Telegram kept rate limiting the message"))
+}
@@ -0,0 +1,122 @@
+use chrono::{DateTime, Utc};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use x509_parser::extensions::GeneralName;
+use crate::{error::Result, expirations, Error};
+
+const DEFAULT_TLS_PORT: u16 = 443;
+const MAX_MIN_DAYS: i64 = 3650;
+
+/// A certificate check run instead of the HTTP request. The monitor's
+/// `endpoint` is `tls://host[:port]` or an `https://` URL; port 443 is used
+/// when none is given.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TlsCheck {
+    /// Fail when the server's certificate expires in fewer days
+    #[serde(default = "default_min_days")]
+    pub min_days: i64,
+    /// Name sent for SNI and matched against the certificate, when it is
+    /// not the endpoint's host
+    #[serde(default)]
+    pub server_name: Option<String>,
+}
+
+fn default_min_days() -> i64 {
+    14
+}
+
+impl Default for TlsCheck {
+    fn default() -> Self {
+        Self {
+            min_days: default_min_days(),
+            server_name: None,
+        }
+    }
+}
+
+/// What a result records of each certificate in the chain.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CertificateInfo {
+    pub subject: String,
+    pub issuer: String,
+    /// DNS names and IP addresses from the subject alternative names
+    pub sans: Vec<String>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl TlsCheck {
+    pub fn validate(&self, endpoint: &str) -> Result<()> {
+        target(endpoint)?;
+        if !(0..=MAX_MIN_DAYS).contains(&self.min_days) {
+            return Err(Error::validation(format!("min_days must be between 0 and {}", MAX_MIN_DAYS)));
+        }
+        if self.server_name.as_ref().is_some_and(|name| name.trim().is_empty()) {
+            return Err(Error::validation("server_name must not be empty"));
+        }
+        Ok(())
+    }
+
+    /// `Err` carries the result's error message when the certificate
+    /// expires within `min_days`.
+    pub fn evaluate(&self, leaf: &CertificateInfo, now: DateTime<Utc>) -> std::result::Result<(), String> {
+        let days = expirations::days_remaining(leaf.expires_at, now);
+        if days >= self.min_days {
+            Ok(())
+        } else if days < 0 {
+            Err(format!("certificate for {} expired {} days ago", leaf.subject, -days))
+        } else {
+            Err(format!(
+                "certificate for {} expires in {} days, within the {}-day threshold",
+                leaf.subject, days, self.min_days
+            ))
+        }
+    }
+}
+
+/// Host and port of a `tls://` or `https://` endpoint.
+pub fn target(endpoint: &str) -> Result<(String, u16)> {
+    let url = Url::parse(endpoint).map_err(|e| Error::validation(format!("Invalid TLS endpoint: {}", e)))?;
+    match (url.scheme(), url.host_str()) {
+        // IPv6 hosts come bracketed
+        ("tls" | "https", Some(host)) if !host.is_empty() => {
+            Ok((host.trim_matches(['[', ']']).to_string(), url.port().unwrap_or(DEFAULT_TLS_PORT)))
+        }
+        _ => Err(Error::validation("tls_check endpoints must be tls://host[:port] or https:// URLs")),
+    }
+}
+
+/// Subject, issuer, alternative names and expiry of a DER certificate.
+pub fn certificate_info(der: &[u8]) -> Result<CertificateInfo> {
+    let (subject, expires_at) = expirations::parse_certificate(der)?;
+    let (_, cert) = x509_parser::parse_x509_certificate(der)
+        .map_err(|e| Error::validation(format!("Invalid certificate: {}", e)))?;
+    let issuer = cert
+        .issuer()
+        .iter_common_name()
+        .next()
+        .and_then(|cn| cn.as_str().ok())
+        .map(str::to_string)
+        .unwrap_or_else(|| cert.issuer().to_string());
+    let sans = match cert.subject_alternative_name() {
+        Ok(Some(extension)) => extension
+            .value
+            .general_names
+            .iter()
+            .filter_map(|name| match name {
+                GeneralName::DNSName(dns) => Some(dns.to_string()),
+                GeneralName::IPAddress([a, b, c, d]) => Some(std::net::Ipv4Addr::new(*a, *b, *c, *d).to_string()),
+                GeneralName::IPAddress(bytes) => {
+                    <[u8; 16]>::try_from(*bytes).ok().map(|ip| std::net::Ipv6Addr::from(ip).to_string())
+                }
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+    Ok(CertificateInfo {
+        subject,
+        issuer,
+        sans,
+        expires_at,
+    })
+}
@@ -0,0 +1,65 @@
+#[cfg(test)]
+mod tls_tests {
+    use crate::tls::*;
+    use base64::Engine;
+    use chrono::{TimeZone, Utc};
+
+    /// Self-signed for example.com, valid 2024-01-01 to 2034-01-01
+    const CERTIFICATE: &str = concat!(
+        "MIIB1jCCAXygAwIBAgIUa+AovvhWPwrDbqCxiyGwMv1yATwwCgYIKoZIzj0EAwIwKDEUMBIGA1UEAwwLZXhhbXBsZS5jb20x",
+        "EDAOBgNVBAoMB0V4YW1wbGUwHhcNMjQwMTAxMDAwMDAwWhcNMzQwMTAxMDAwMDAwWjAoMRQwEgYDVQQDDAtleGFtcGxlLmNv",
+        "bTEQMA4GA1UECgwHRXhhbXBsZTBZMBMGByqGSM49AgEGCCqGSM49AwEHA0IABMof0HyPd5yXfL/EFOa5FEXOS063PA74LM6o",
+        "b536dIAIycQ3Qk/l7SCOnRhXK9My//BFRNczme42WFCjRboBL62jgYMwgYAwHQYDVR0OBBYEFNFpEZRsx99oJPEgi2JmH04E",
+        "V2EuMB8GA1UdIwQYMBaAFNFpEZRsx99oJPEgi2JmH04EV2EuMA8GA1UdEwEB/wQFMAMBAf8wLQYDVR0RBCYwJIILZXhhbXBs",
+        "ZS5jb22CD3d3dy5leGFtcGxlLmNvbYcEwAACATAKBggqhkjOPQQDAgNIADBFAiBcAqiEb6XVIUQslwZQpZry5QHtfnUeHrww",
+        "vmX5hujP+gIhAID7V1f1V9JZQXZW/VcnEJA9uoDCCkMmzSXCj1t7ldfk",
+    );
+
+    fn certificate() -> CertificateInfo {
+        let der = base64::engine::general_purpose::STANDARD.decode(CERTIFICATE).unwrap();
+        certificate_info(&der).unwrap()
+    }
+
+    #[test]
+    fn test_target() {
+        assert_eq!(target("tls://example.com").unwrap(), ("example.com".to_string(), 443));
+        assert_eq!(target("tls://mail.example.com:993").unwrap(), ("mail.example.com".to_string(), 993));
+        assert_eq!(target("https://[2001:db8::1]:8443/health").unwrap(), ("2001:db8::1".to_string(), 8443));
+        assert!(target("tcp://example.com:443").is_err());
+    }
+
+    #[test]
+    fn test_validate() {
+        let endpoint = "tls://example.com";
+        assert!(TlsCheck::default().validate(endpoint).is_ok());
+        assert!(TlsCheck { min_days: -1, ..Default::default() }.validate(endpoint).is_err());
+        assert!(TlsCheck { server_name: Some(" ".to_string()), ..Default::default() }.validate(endpoint).is_err());
+        let check: TlsCheck = serde_json::from_value(serde_json::json!({})).unwrap();
+        assert_eq!(check.min_days, 14);
+    }
+
+    #[test]
+    fn test_certificate_info() {
+        let info = certificate();
+        assert_eq!(info.subject, "example.com");
+        assert_eq!(info.issuer, "example.com");
+        assert_eq!(info.sans, ["example.com", "www.example.com", "192.0.2.1"]);
+        assert_eq!(info.expires_at, Utc.with_ymd_and_hms(2034, 1, 1, 0, 0, 0).unwrap());
+        assert!(certificate_info(b"not a certificate").is_err());
+    }
+
+    #[test]
+    fn test_expiry_threshold() {
+        let info = certificate();
+        let check = TlsCheck::default();
+        assert!(check.evaluate(&info, Utc.with_ymd_and_hms(2033, 12, 1, 0, 0, 0).unwrap()).is_ok());
+        assert_eq!(
+            check.evaluate(&info, Utc.with_ymd_and_hms(2033, 12, 25, 0, 0, 0).unwrap()).unwrap_err(),
+            "certificate for example.com expires in 7 days, within the 14-day threshold"
+        );
+        assert_eq!(
+            check.evaluate(&info, Utc.with_ymd_and_hms(2034, 1, 3, 12, 0, 0).unwrap()).unwrap_err(),
+            "certificate for example.com expired 3 days ago"
+        );
+    }
+}
@@ -0,0 +1,181 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use crate::{assertions::Assertion, error::Result, Error};
+
+/// One request of a multi-step transaction monitor.
+///
+/// `url`, header values and `body` may reference variables captured by earlier
+/// steps as `{{name}}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransactionStep {
+    pub name: String,
+    pub method: String,
+    pub url: String,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    pub body: Option<String>,
+    pub expected_status: i32,
+    /// Variables to capture from the response: a JSON pointer into the body
+    /// (`/data/token`), a JSONPath (`$.data.items[0].id`), `header:<name>` or
+    /// `regex:<pattern>`, which takes the first capture group
+    #[serde(default)]
+    pub extract: BTreeMap<String, String>,
+    /// Checked after the status, before variables are extracted
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub assertions: Vec<Assertion>,
+}
+
+/// Where an `extract` variable comes from.
+#[derive(Debug, Clone)]
+enum Source<'a> {
+    Pointer(String),
+    Header(&'a str),
+    Regex(Regex),
+}
+
+impl<'a> Source<'a> {
+    fn parse(source: &'a str) -> std::result::Result<Self, String> {
+        if let Some(header) = source.strip_prefix("header:") {
+            return Ok(Source::Header(header));
+        }
+        if let Some(pattern) = source.strip_prefix("regex:") {
+            return Regex::new(pattern).map(Source::Regex).map_err(|e| format!("invalid regex: {}", e));
+        }
+        if source.starts_with('/') {
+            return Ok(Source::Pointer(source.to_string()));
+        }
+        if source.starts_with('$') {
+            return json_path_pointer(source).map(Source::Pointer);
+        }
+        Err("expected a JSON pointer, a JSONPath, header:<name> or regex:<pattern>".to_string())
+    }
+}
+
+pub fn parse_steps(value: &serde_json::Value) -> Result<Vec<TransactionStep>> {
+    let steps: Vec<TransactionStep> = serde_json::from_value(value.clone())?;
+    validate_steps(&steps)?;
+    Ok(steps)
+}
+
+pub fn validate_steps(steps: &[TransactionStep]) -> Result<()> {
+    if steps.is_empty() {
+        return Err(Error::validation("A transaction needs at least one step"));
+    }
+    for step in steps {
+        if step.url.trim().is_empty() {
+            return Err(Error::validation(format!("Step '{}' has no url", step.name)));
+        }
+        for (var, source) in &step.extract {
+            Source::parse(source).map_err(|e| {
+                Error::validation(format!("Step '{}' extracts '{}' from '{}': {}", step.name, var, source, e))
+            })?;
+        }
+    }
+    Ok(())
+}
+
+/// Replaces `{{name}}` with captured variables; unknown names are left as is.
+pub fn substitute(template: &str, vars: &HashMap<String, String>) -> String {
+    let mut out = template.to_string();
+    for (name, value) in vars {
+        out = out.replace(&format!("{{{{{}}}}}", name), value);
+    }
+    out
+}
+
+/// Captures the step's `extract` variables from a response. `headers` is keyed by
+/// lowercase header name.
+pub fn extract_variables(
+    step: &TransactionStep,
+    headers: &HashMap<String, String>,
+    body: &str,
+) -> Result<HashMap<String, String>> {
+    let sources = step
+        .extract
+        .iter()
+        .map(|(var, source)| Ok((var, source, Source::parse(source).map_err(Error::validation)?)))
+        .collect::<Result<Vec<_>>>()?;
+
+    let json = sources
+        .iter()
+        .any(|(_, _, source)| matches!(source, Source::Pointer(_)))
+        .then(|| serde_json::from_str::<serde_json::Value>(body))
+        .transpose()
+        .map_err(|_| Error::validation(format!("Step '{}' did not return JSON", step.name)))?;
+
+    let mut vars = HashMap::new();
+    for (var, text, source) in sources {
+        let value = match (source, &json) {
+            (Source::Header(header), _) => headers.get(&header.to_lowercase()).cloned(),
+            (Source::Pointer(pointer), Some(json)) => json.pointer(&pointer).map(|v| match v {
+                serde_json::Value::String(s) => s.clone(),
+                other => other.to_string(),
+            }),
+            (Source::Regex(regex), _) => regex
+                .captures(body)
+                .and_then(|captures| captures.get(1).or_else(|| captures.get(0)))
+                .map(|m| m.as_str().to_string()),
+            (Source::Pointer(_), None) => None,
+        };
+        let value = value.ok_or_else(|| {
+            Error::validation(format!("Step '{}' response has no {} for '{}'", step.name, text, var))
+        })?;
+        vars.insert(var.clone(), value);
+    }
+
+    Ok(vars)
+}
+
+/// The JSON pointer for a JSONPath that names a single value: `$`, then
+/// `.name`, `['name']` or `[index]` segments. Wildcards, slices, filters
+/// and recursive descent are not supported.
+pub fn json_path_pointer(path: &str) -> std::result::Result<String, String> {
+    let unsupported = || format!("unsupported JSONPath '{}'", path);
+    let mut rest = path.strip_prefix('$').ok_or_else(unsupported)?;
+    let mut pointer = String::new();
+    while !rest.is_empty() {
+        let segment;
+        if let Some(after) = rest.strip_prefix('.') {
+            let end = after.find(['.', '[']).unwrap_or(after.len());
+            segment = &after[..end];
+            if segment.is_empty() || segment == "*" {
+                return Err(unsupported());
+            }
+            rest = &after[end..];
+        } else if let Some(after) = rest.strip_prefix('[') {
+            let end = after.find(']').ok_or_else(unsupported)?;
+            let inner = after[..end].trim();
+            segment = match inner.chars().next() {
+                Some(quote @ ('\'' | '"')) if inner.len() >= 2 && inner.ends_with(quote) => &inner[1..inner.len() - 1],
+                _ if !inner.is_empty() && inner.chars().all(|c| c.is_ascii_digit()) => inner,
+                _ => return Err(unsupported()),
+            };
+            rest = &after[end + 1..];
+        } else {
+            return Err(unsupported());
+        }
+        pointer.push('/');
+        pointer.push_str(&segment.replace('~', "~0").replace('/', "~1"));
+    }
+    Ok(pointer)
+}
+
+/// Stores the `name=value` pair of each `Set-Cookie` header in the jar.
+pub fn merge_set_cookies<'a>(jar: &mut BTreeMap<String, String>, set_cookies: impl IntoIterator<Item = &'a str>) {
+    for set_cookie in set_cookies {
+        let pair = set_cookie.split(';').next().unwrap_or_default();
+        if let Some((name, value)) = pair.split_once('=') {
+            jar.insert(name.trim().to_string(), value.trim().to_string());
+        }
+    }
+}
+
+pub fn cookie_header(jar: &BTreeMap<String, String>) -> Option<String> {
+    (!jar.is_empty()).then(|| {
+        jar.iter()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect::<Vec<_>>()
+            .join("; ")
+    })
+}
@@ -0,0 +1,102 @@
+#[cfg(test)]
+mod transaction_tests {
+    use crate::transaction::*;
+    use std::collections::{BTreeMap, HashMap};
+
+    fn step(extract: &[(&str, &str)]) -> TransactionStep {
+        TransactionStep {
+            name: "login".to_string(),
+            method: "POST".to_string(),
+            url: "https://example.com/login".to_string(),
+            headers: BTreeMap::new(),
+            body: None,
+            expected_status: 200,
+            extract: extract.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+            assertions: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_substitute_known_variables_only() {
+        let vars = HashMap::from([("token".to_string(), "abc".to_string())]);
+        assert_eq!(substitute("Bearer {{token}}", &vars), "Bearer abc");
+        assert_eq!(substitute("{{missing}}/{{token}}", &vars), "{{missing}}/abc");
+    }
+
+    #[test]
+    fn test_extract_from_body_and_headers() {
+        let step = step(&[("token", "/data/token"), ("count", "/data/count"), ("csrf", "header:X-CSRF-Token")]);
+        let headers = HashMap::from([("x-csrf-token".to_string(), "c5rf".to_string())]);
+        let vars = extract_variables(&step, &headers, r#"{"data":{"token":"abc","count":3}}"#).unwrap();
+
+        assert_eq!(vars["token"], "abc");
+        assert_eq!(vars["count"], "3");
+        assert_eq!(vars["csrf"], "c5rf");
+
+        assert!(extract_variables(&step, &headers, "<html>").is_err());
+        assert!(extract_variables(&step, &HashMap::new(), r#"{"data":{"token":"abc","count":3}}"#).is_err());
+    }
+
+    #[test]
+    fn test_extract_with_json_path_and_regex() {
+        let step = step(&[
+            ("id", "$.data.items[1]['id']"),
+            ("order", "regex:Order #(\\d+)"),
+            ("word", "regex:[a-z]+"),
+        ]);
+        let body = r#"{"data":{"items":[{"id":1},{"id":"b-2"}]},"note":"Order #42 placed"}"#;
+        let vars = extract_variables(&step, &HashMap::new(), body).unwrap();
+
+        assert_eq!(vars["id"], "b-2");
+        assert_eq!(vars["order"], "42");
+        assert_eq!(vars["word"], "data");
+        assert!(extract_variables(&step, &HashMap::new(), r#"{"data":{"items":[]}}"#).is_err());
+    }
+
+    #[test]
+    fn test_json_path_pointer() {
+        assert_eq!(json_path_pointer("$").unwrap(), "");
+        assert_eq!(json_path_pointer("$.a.b[0]").unwrap(), "/a/b/0");
+        assert_eq!(json_path_pointer("$['a/b'][\"c~d\"]").unwrap(), "/a~1b/c~0d");
+        assert!(json_path_pointer("$..id").is_err());
+        assert!(json_path_pointer("$.items[*].id").is_err());
+        assert!(json_path_pointer("$.items[0:2]").is_err());
+    }
+
+    #[test]
+    fn test_validate_steps() {
+        assert!(validate_steps(&[]).is_err());
+        assert!(validate_steps(&[step(&[("token", "/token")])]).is_ok());
+        assert!(validate_steps(&[step(&[("token", "$.token"), ("csrf", "regex:csrf=(\\w+)")])]).is_ok());
+        assert!(validate_steps(&[step(&[("token", "token")])]).is_err());
+        assert!(validate_steps(&[step(&[("token", "regex:(")])]).is_err());
+        assert!(validate_steps(&[step(&[("token", "$..token")])]).is_err());
+    }
+
+    #[test]
+    fn test_step_assertions_are_optional() {
+        let cart: TransactionStep = serde_json::from_value(serde_json::json!({
+            "name": "cart",
+            "method": "GET",
+            "url": "https://example.com/cart",
+            "body": null,
+            "expected_status": 200,
+            "assertions": [{ "type": "body_contains", "value": "total" }],
+        }))
+        .unwrap();
+        assert_eq!(cart.assertions.len(), 1);
+
+        let plain = serde_json::to_value(step(&[])).unwrap();
+        assert!(plain.get("assertions").is_none());
+    }
+
+    #[test]
+    fn test_cookie_jar() {
+        let mut jar = BTreeMap::new();
+        assert_eq!(cookie_header(&jar), None);
+
+        merge_set_cookies(&mut jar, ["sid=1; Path=/; HttpOnly", "theme=dark"]);
+        merge_set_cookies(&mut jar, ["sid=2; Secure"]);
+        assert_eq!(cookie_header(&jar).as_deref(), Some("sid=2; theme=dark"));
+    }
+}
@@ -0,0 +1,71 @@
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use uuid::Uuid;
+use crate::{error::Result, Error};
+
+type HmacSha256 = Hmac<Sha256>;
+
+pub const SIGNATURE_HEADER: &str = "X-Monitor-Signature";
+
+/// Maximum age, in seconds, of a signed delivery before it is treated as a replay.
+pub const DEFAULT_TOLERANCE_SECS: i64 = 300;
+
+pub fn generate_secret() -> String {
+    format!("whsec_{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+fn mac(secret: &str, timestamp: i64, body: &[u8]) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    mac
+}
+
+/// Builds the `X-Monitor-Signature` value: `t=<unix timestamp>,v1=<hex hmac-sha256>`.
+///
+/// The MAC covers `"<timestamp>.<body>"`, so the timestamp cannot be altered
+/// without invalidating the signature.
+pub fn sign(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let signature = hex::encode(mac(secret, timestamp, body).finalize().into_bytes());
+    format!("t={},v1={}", timestamp, signature)
+}
+
+/// Verifies a signature header produced by [`sign`] for a received body.
+///
+/// Rejects malformed headers, signatures that do not match, and deliveries whose
+/// timestamp differs from `now` by more than `tolerance_secs`.
+pub fn verify(secret: &str, header: &str, body: &[u8], now: i64, tolerance_secs: i64) -> Result<()> {
+    let mut timestamp = None;
+    let mut signatures = Vec::new();
+
+    for part in header.split(',') {
+        match part.trim().split_once('=') {
+            Some(("t", value)) => timestamp = value.parse::<i64>().ok(),
+            Some(("v1", value)) => signatures.push(value),
+            _ => {}
+        }
+    }
+
+    let timestamp = timestamp.ok_or_else(|| Error::auth("Missing or invalid signature timestamp"))?;
+    if signatures.is_empty() {
+        return Err(Error::auth("Missing v1 signature"));
+    }
+
+    if (now - timestamp).abs() > tolerance_secs {
+        return Err(Error::auth("Signature timestamp outside of tolerance"));
+    }
+
+    let valid = signatures.iter().any(|signature| {
+        hex::decode(signature)
+            .map(|bytes| mac(secret, timestamp, body).verify_slice(&bytes).is_ok())
+            .unwrap_or(false)
+    });
+
+    if valid {
+        Ok(())
+    } else {
+        Err(Error::auth("Signature mismatch"))
+    }
+}
@@ -0,0 +1,38 @@
+#[cfg(test)]
+mod webhook_tests {
+    use crate::webhook::*;
+
+    const SECRET: &str = "whsec_test";
+    const BODY: &[u8] = br#"{"event":"monitor.result"}"#;
+
+    #[test]
+    fn test_sign_and_verify_roundtrip() {
+        let header = sign(SECRET, 1_700_000_000, BODY);
+        assert!(header.starts_with("t=1700000000,v1="));
+        assert!(verify(SECRET, &header, BODY, 1_700_000_010, DEFAULT_TOLERANCE_SECS).is_ok());
+    }
+
+    #[test]
+    fn test_verify_rejects_tampered_body_and_wrong_secret() {
+        let header = sign(SECRET, 1_700_000_000, BODY);
+        assert!(verify(SECRET, &header, b"{}", 1_700_000_000, DEFAULT_TOLERANCE_SECS).is_err());
+        assert!(verify("other", &header, BODY, 1_700_000_000, DEFAULT_TOLERANCE_SECS).is_err());
+    }
+
+    #[test]
+    fn test_verify_rejects_replayed_timestamp() {
+        let header = sign(SECRET, 1_700_000_000, BODY);
+        let later = 1_700_000_000 + DEFAULT_TOLERANCE_SECS + 1;
+        assert!(verify(SECRET, &header, BODY, later, DEFAULT_TOLERANCE_SECS).is_err());
+
+        // Rewriting the timestamp invalidates the signature
+        let forged = header.replace("t=1700000000", &format!("t={}", later));
+        assert!(verify(SECRET, &forged, BODY, later, DEFAULT_TOLERANCE_SECS).is_err());
+    }
+
+    #[test]
+    fn test_verify_rejects_malformed_header() {
+        assert!(verify(SECRET, "garbage", BODY, 0, DEFAULT_TOLERANCE_SECS).is_err());
+        assert!(verify(SECRET, "t=0", BODY, 0, DEFAULT_TOLERANCE_SECS).is_err());
+    }
+}
@@ -0,0 +1,239 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+use crate::{error::Result, headers::Headers, Error};
+
+const MAX_MESSAGE_LEN: usize = 4096;
+const MAX_EXPECTED_LEN: usize = 1024;
+/// Largest reply, over all its fragments, the check reads
+pub const MAX_REPLY_BYTES: usize = 64 * 1024;
+/// What a failure message quotes of the server's reply
+const MAX_QUOTED_CHARS: usize = 200;
+/// Appended to the client's key to derive `Sec-WebSocket-Accept` (RFC 6455)
+const ACCEPT_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+pub const OPCODE_CONTINUATION: u8 = 0x0;
+pub const OPCODE_TEXT: u8 = 0x1;
+pub const OPCODE_BINARY: u8 = 0x2;
+pub const OPCODE_CLOSE: u8 = 0x8;
+pub const OPCODE_PING: u8 = 0x9;
+pub const OPCODE_PONG: u8 = 0xA;
+
+/// A WebSocket check run instead of the HTTP request. The monitor's
+/// `endpoint` is a `ws://` or `wss://` URL; completing the handshake is
+/// enough to pass unless a message is sent or a reply expected.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WebSocketCheck {
+    /// Text message sent once connected, e.g. `{"type":"ping"}`
+    #[serde(default)]
+    pub message: Option<String>,
+    /// Text the first reply must contain. Without a `message` the first
+    /// message the server sends is checked
+    #[serde(default)]
+    pub expected_reply: Option<String>,
+    /// Sent as `Sec-WebSocket-Protocol`, for servers that require one
+    #[serde(default)]
+    pub subprotocol: Option<String>,
+}
+
+/// Where a `ws://` or `wss://` endpoint connects.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Target {
+    pub host: String,
+    pub port: u16,
+    /// `wss://`, which connects over TLS
+    pub secure: bool,
+    /// Path and query for the request line
+    pub resource: String,
+    /// Value of the `Host` header
+    pub authority: String,
+}
+
+/// One frame read from the server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    pub fin: bool,
+    pub opcode: u8,
+    pub payload: Vec<u8>,
+}
+
+impl WebSocketCheck {
+    pub fn validate(&self, endpoint: &str) -> Result<()> {
+        target(endpoint)?;
+        if self.message.as_ref().is_some_and(|message| message.is_empty() || message.len() > MAX_MESSAGE_LEN) {
+            return Err(Error::validation(format!("message must be 1 to {} bytes", MAX_MESSAGE_LEN)));
+        }
+        if self.expected_reply.as_ref().is_some_and(|reply| reply.is_empty() || reply.len() > MAX_EXPECTED_LEN) {
+            return Err(Error::validation(format!("expected_reply must be 1 to {} bytes", MAX_EXPECTED_LEN)));
+        }
+        if let Some(subprotocol) = &self.subprotocol
+            && (subprotocol.is_empty() || !subprotocol.chars().all(|c| c.is_ascii_graphic() && c != ','))
+        {
+            return Err(Error::validation("subprotocol must be a single protocol token"));
+        }
+        Ok(())
+    }
+
+    /// Whether the check waits for a message after the handshake.
+    pub fn waits_for_reply(&self) -> bool {
+        self.message.is_some() || self.expected_reply.is_some()
+    }
+
+    /// `Err` carries the result's error message when the reply lacks the
+    /// expected text.
+    pub fn evaluate(&self, reply: &[u8]) -> std::result::Result<(), String> {
+        match &self.expected_reply {
+            Some(expected) if !reply.windows(expected.len()).any(|window| window == expected.as_bytes()) => {
+                Err(format!("expected '{}' in the reply, got '{}'", expected, quote(reply)))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The start of a reply as it is quoted in results.
+pub fn quote(reply: &[u8]) -> String {
+    String::from_utf8_lossy(reply).trim_end().chars().take(MAX_QUOTED_CHARS).collect()
+}
+
+pub fn target(endpoint: &str) -> Result<Target> {
+    let url = Url::parse(endpoint).map_err(|e| Error::validation(format!("Invalid WebSocket endpoint: {}", e)))?;
+    let secure = match url.scheme() {
+        "ws" => false,
+        "wss" => true,
+        _ => return Err(Error::validation("websocket_check endpoints must be ws:// or wss:// URLs")),
+    };
+    let Some(host) = url.host_str() else {
+        return Err(Error::validation("websocket_check endpoints must name a host"));
+    };
+    let authority = match url.port() {
+        Some(port) => format!("{}:{}", host, port),
+        None => host.to_string(),
+    };
+    let resource = match url.query() {
+        Some(query) => format!("{}?{}", url.path(), query),
+        None => url.path().to_string(),
+    };
+    Ok(Target {
+        // IPv6 hosts come bracketed
+        host: host.trim_matches(['[', ']']).to_string(),
+        port: url.port_or_known_default().unwrap_or(if secure { 443 } else { 80 }),
+        secure,
+        resource,
+        authority,
+    })
+}
+
+/// The opening handshake for `target`, with the monitor's headers.
+pub fn handshake_request(target: &Target, key: &str, subprotocol: Option<&str>, headers: &Headers) -> String {
+    let mut request = format!(
+        "GET {} HTTP/1.1\r\nHost: {}\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+         Sec-WebSocket-Key: {}\r\nSec-WebSocket-Version: 13\r\n",
+        target.resource, target.authority, key
+    );
+    if let Some(subprotocol) = subprotocol {
+        request.push_str(&format!("Sec-WebSocket-Protocol: {}\r\n", subprotocol));
+    }
+    for (name, value) in headers {
+        request.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    request.push_str("\r\n");
+    request
+}
+
+/// The `Sec-WebSocket-Accept` a server must answer `key` with.
+pub fn accept_key(key: &str) -> String {
+    let mut hasher = Sha1::new();
+    hasher.update(key.as_bytes());
+    hasher.update(ACCEPT_GUID.as_bytes());
+    STANDARD.encode(hasher.finalize())
+}
+
+/// Status code on the first line of a response head.
+pub fn handshake_status(head: &str) -> Option<i32> {
+    head.lines().next()?.split_whitespace().nth(1)?.parse().ok()
+}
+
+/// `Err` carries the result's error message when the response head does not
+/// complete the handshake for `key`.
+pub fn verify_handshake(head: &str, key: &str) -> std::result::Result<(), String> {
+    let status_line = head.lines().next().unwrap_or_default();
+    if handshake_status(head) != Some(101) {
+        return Err(format!("expected 101 Switching Protocols, got '{}'", status_line));
+    }
+    let header = |name: &str| {
+        head.lines().skip(1).find_map(|line| {
+            let (header, value) = line.split_once(':')?;
+            header.trim().eq_ignore_ascii_case(name).then(|| value.trim())
+        })
+    };
+    if !header("upgrade").is_some_and(|value| value.eq_ignore_ascii_case("websocket")) {
+        return Err("the server did not upgrade the connection to a WebSocket".to_string());
+    }
+    if header("sec-websocket-accept") != Some(accept_key(key).as_str()) {
+        return Err("the server answered with the wrong Sec-WebSocket-Accept".to_string());
+    }
+    Ok(())
+}
+
+/// A single, final client frame. Client frames are always masked.
+pub fn encode_frame(opcode: u8, payload: &[u8], mask: [u8; 4]) -> Vec<u8> {
+    let mut frame = vec![0x80 | opcode];
+    match payload.len() {
+        len @ 0..=125 => frame.push(0x80 | len as u8),
+        len @ 126..=0xFFFF => {
+            frame.push(0x80 | 126);
+            frame.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        len => {
+            frame.push(0x80 | 127);
+            frame.extend_from_slice(&(len as u64).to_be_bytes());
+        }
+    }
+    frame.extend_from_slice(&mask);
+    frame.extend(payload.iter().enumerate().map(|(i, byte)| byte ^ mask[i % 4]));
+    frame
+}
+
+/// The first frame in `buf` and the bytes it took, or `None` until the
+/// whole frame has arrived. Frames over [`MAX_REPLY_BYTES`] are an error.
+pub fn decode_frame(buf: &[u8]) -> std::result::Result<Option<(Frame, usize)>, String> {
+    let [first, second, ..] = *buf else {
+        return Ok(None);
+    };
+    let (len, mut offset) = match second & 0x7F {
+        126 if buf.len() >= 4 => (u16::from_be_bytes([buf[2], buf[3]]) as u64, 4),
+        127 if buf.len() >= 10 => (u64::from_be_bytes(buf[2..10].try_into().unwrap()), 10),
+        126 | 127 => return Ok(None),
+        len => (len as u64, 2),
+    };
+    if len > MAX_REPLY_BYTES as u64 {
+        return Err(format!("the server sent a {} byte frame, more than {} bytes", len, MAX_REPLY_BYTES));
+    }
+    let mask = if second & 0x80 != 0 {
+        let Some(mask) = buf.get(offset..offset + 4) else {
+            return Ok(None);
+        };
+        offset += 4;
+        Some([mask[0], mask[1], mask[2], mask[3]])
+    } else {
+        None
+    };
+    let end = offset + len as usize;
+    let Some(payload) = buf.get(offset..end) else {
+        return Ok(None);
+    };
+    let payload = match mask {
+        Some(mask) => payload.iter().enumerate().map(|(i, byte)| byte ^ mask[i % 4]).collect(),
+        None => payload.to_vec(),
+    };
+    Ok(Some((
+        Frame {
+            fin: first & 0x80 != 0,
+            opcode: first & 0x0F,
+            payload,
+        },
+        end,
+    )))
+}
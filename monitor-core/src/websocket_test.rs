@@ -0,0 +1,104 @@
+#[cfg(test)]
+mod websocket_tests {
+    use crate::headers::Headers;
+    use crate::websocket::*;
+
+    #[test]
+    fn endpoints_must_be_websocket_urls() {
+        let secure = target("wss://stream.example.com/feed?token=abc").unwrap();
+        assert_eq!(secure.host, "stream.example.com");
+        assert_eq!(secure.port, 443);
+        assert!(secure.secure);
+        assert_eq!(secure.resource, "/feed?token=abc");
+        assert_eq!(secure.authority, "stream.example.com");
+
+        let local = target("ws://[::1]:8080").unwrap();
+        assert_eq!((local.host.as_str(), local.port, local.secure), ("::1", 8080, false));
+        assert_eq!(local.resource, "/");
+        assert_eq!(local.authority, "[::1]:8080");
+
+        assert!(WebSocketCheck::default().validate("https://example.com").is_err());
+        assert!(WebSocketCheck::default().validate("ws://example.com/socket").is_ok());
+    }
+
+    #[test]
+    fn messages_replies_and_subprotocols_are_validated() {
+        let check = |message: Option<&str>, expected: Option<&str>, subprotocol: Option<&str>| WebSocketCheck {
+            message: message.map(str::to_string),
+            expected_reply: expected.map(str::to_string),
+            subprotocol: subprotocol.map(str::to_string),
+        };
+        assert!(check(Some("ping"), Some("pong"), Some("graphql-ws")).validate("ws://h").is_ok());
+        assert!(check(Some(""), None, None).validate("ws://h").is_err());
+        assert!(check(None, Some(&"x".repeat(2000)), None).validate("ws://h").is_err());
+        assert!(check(None, None, Some("a, b")).validate("ws://h").is_err());
+        assert!(!check(None, None, None).waits_for_reply());
+        assert!(check(None, Some("hello"), None).waits_for_reply());
+    }
+
+    #[test]
+    fn handshake_uses_the_rfc_accept_key() {
+        // Example from RFC 6455 section 1.3
+        assert_eq!(accept_key("dGhlIHNhbXBsZSBub25jZQ=="), "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+
+        let chat = target("ws://example.com:8080/chat").unwrap();
+        let headers = Headers::from([("Authorization".to_string(), "Bearer t".to_string())]);
+        let request = handshake_request(&chat, "key", Some("chat"), &headers);
+        assert!(request.starts_with("GET /chat HTTP/1.1\r\nHost: example.com:8080\r\n"));
+        assert!(request.contains("Sec-WebSocket-Protocol: chat\r\n"));
+        assert!(request.ends_with("Authorization: Bearer t\r\n\r\n"));
+    }
+
+    #[test]
+    fn handshake_responses_are_verified() {
+        let key = "dGhlIHNhbXBsZSBub25jZQ==";
+        let accepted = "HTTP/1.1 101 Switching Protocols\r\nupgrade: WebSocket\r\nConnection: Upgrade\r\n\
+                        Sec-WebSocket-Accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo=\r\n\r\n";
+        assert!(verify_handshake(accepted, key).is_ok());
+        assert!(verify_handshake(accepted, "other").unwrap_err().contains("Sec-WebSocket-Accept"));
+
+        let refused = "HTTP/1.1 403 Forbidden\r\nContent-Length: 0\r\n\r\n";
+        assert_eq!(handshake_status(refused), Some(403));
+        assert!(verify_handshake(refused, key).unwrap_err().contains("403 Forbidden"));
+    }
+
+    #[test]
+    fn frames_round_trip_with_masking() {
+        let encoded = encode_frame(OPCODE_TEXT, b"Hello", [0x37, 0xfa, 0x21, 0x3d]);
+        // Masked "Hello" from RFC 6455 section 5.7
+        assert_eq!(encoded, [0x81, 0x85, 0x37, 0xfa, 0x21, 0x3d, 0x7f, 0x9f, 0x4d, 0x51, 0x58]);
+        let (frame, used) = decode_frame(&encoded).unwrap().unwrap();
+        assert_eq!((frame.fin, frame.opcode, used), (true, OPCODE_TEXT, 11));
+        assert_eq!(frame.payload, b"Hello");
+
+        let long = vec![b'a'; 300];
+        let (frame, _) = decode_frame(&encode_frame(OPCODE_BINARY, &long, [1, 2, 3, 4])).unwrap().unwrap();
+        assert_eq!(frame.payload, long);
+    }
+
+    #[test]
+    fn partial_and_oversized_frames() {
+        // Unmasked server frame "Hel" without FIN, as in RFC 6455 section 5.7
+        let fragment = [0x01, 0x03, 0x48, 0x65, 0x6c];
+        assert_eq!(decode_frame(&fragment[..3]).unwrap(), None);
+        let (frame, _) = decode_frame(&fragment).unwrap().unwrap();
+        assert!(!frame.fin);
+        assert_eq!(frame.payload, b"Hel");
+
+        let mut huge = vec![0x82, 127];
+        huge.extend_from_slice(&(MAX_REPLY_BYTES as u64 + 1).to_be_bytes());
+        assert!(decode_frame(&huge).is_err());
+    }
+
+    #[test]
+    fn replies_must_contain_the_expected_text() {
+        let check = WebSocketCheck {
+            expected_reply: Some("\"pong\"".to_string()),
+            ..Default::default()
+        };
+        assert!(check.evaluate(br#"{"type":"pong"}"#).is_ok());
+        let error = check.evaluate(br#"{"type":"error"}"#).unwrap_err();
+        assert!(error.contains(r#"got '{"type":"error"}'"#));
+        assert!(WebSocketCheck::default().evaluate(b"anything").is_ok());
+    }
+}
@@ -0,0 +1,66 @@
+use monitor_core::{
+    config::KubernetesDiscoveryConfig,
+    kubernetes::{Ingress, ObjectList, Service},
+    Error, Result,
+};
+use reqwest::{Certificate, Client};
+use serde::de::DeserializeOwned;
+use std::{env, fs};
+
+const SERVICE_ACCOUNT_DIR: &str = "/var/run/secrets/kubernetes.io/serviceaccount";
+
+/// Read-only access to the Kubernetes API: the in-cluster service account by
+/// default, or `cluster_url` (e.g. `kubectl proxy`) without credentials.
+pub struct Cluster {
+    client: Client,
+    base_url: String,
+    token: Option<String>,
+    namespace: Option<String>,
+}
+
+impl Cluster {
+    pub fn from_config(config: &KubernetesDiscoveryConfig) -> Result<Self> {
+        if let Some(url) = &config.cluster_url {
+            return Ok(Self {
+                client: Client::new(),
+                base_url: url.trim_end_matches('/').to_string(),
+                token: None,
+                namespace: config.namespace.clone(),
+            });
+        }
+
+        let host = env::var("KUBERNETES_SERVICE_HOST")
+            .map_err(|_| Error::validation("Not running in a cluster; set K8S_API_URL"))?;
+        let port = env::var("KUBERNETES_SERVICE_PORT").unwrap_or_else(|_| "443".to_string());
+        let token = fs::read_to_string(format!("{}/token", SERVICE_ACCOUNT_DIR))?;
+        let ca = Certificate::from_pem(&fs::read(format!("{}/ca.crt", SERVICE_ACCOUNT_DIR))?)?;
+
+        Ok(Self {
+            client: Client::builder().add_root_certificate(ca).build()?,
+            base_url: format!("https://{}:{}", host, port),
+            token: Some(token.trim().to_string()),
+            namespace: config.namespace.clone(),
+        })
+    }
+
+    pub async fn ingresses(&self) -> Result<Vec<Ingress>> {
+        self.list("apis/networking.k8s.io/v1", "ingresses").await
+    }
+
+    pub async fn services(&self) -> Result<Vec<Service>> {
+        self.list("api/v1", "services").await
+    }
+
+    async fn list<T: DeserializeOwned>(&self, group: &str, resource: &str) -> Result<Vec<T>> {
+        let url = match &self.namespace {
+            Some(namespace) => format!("{}/{}/namespaces/{}/{}", self.base_url, group, namespace, resource),
+            None => format!("{}/{}/{}", self.base_url, group, resource),
+        };
+        let mut request = self.client.get(url);
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+        let list: ObjectList<T> = request.send().await?.error_for_status()?.json().await?;
+        Ok(list.items)
+    }
+}
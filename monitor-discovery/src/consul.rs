@@ -0,0 +1,50 @@
+use monitor_core::{config::ConsulDiscoveryConfig, consul::CatalogService, Result};
+use reqwest::{Client, RequestBuilder};
+use std::collections::HashMap;
+
+/// Read-only access to the Consul catalog.
+pub struct Consul {
+    client: Client,
+    config: ConsulDiscoveryConfig,
+}
+
+impl Consul {
+    pub fn from_config(config: &ConsulDiscoveryConfig) -> Self {
+        Self {
+            client: Client::new(),
+            config: config.clone(),
+        }
+    }
+
+    /// Every instance of the services carrying the opt-in tag.
+    pub async fn instances(&self) -> Result<Vec<CatalogService>> {
+        let services: HashMap<String, Vec<String>> =
+            self.get("/v1/catalog/services").send().await?.error_for_status()?.json().await?;
+
+        let mut instances = Vec::new();
+        for (name, tags) in services {
+            if !tags.contains(&self.config.tag) {
+                continue;
+            }
+            let request = self
+                .get(&format!("/v1/catalog/service/{}", name))
+                .query(&[("tag", &self.config.tag)]);
+            let found: Vec<CatalogService> = request.send().await?.error_for_status()?.json().await?;
+            instances.extend(found);
+        }
+        Ok(instances)
+    }
+
+    fn get(&self, path: &str) -> RequestBuilder {
+        let mut request = self
+            .client
+            .get(format!("{}{}", self.config.address.trim_end_matches('/'), path));
+        if let Some(datacenter) = &self.config.datacenter {
+            request = request.query(&[("dc", datacenter)]);
+        }
+        if let Some(token) = &self.config.token {
+            request = request.header("X-Consul-Token", token);
+        }
+        request
+    }
+}
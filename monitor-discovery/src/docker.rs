@@ -0,0 +1,60 @@
+use monitor_core::{
+    config::DockerDiscoveryConfig,
+    docker::{self, Container},
+    Error, Result,
+};
+use reqwest::{Client, Url};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::UnixStream,
+};
+
+/// Read-only access to the Docker Engine API, over the local socket or a
+/// `tcp://` host.
+pub struct Docker {
+    host: String,
+}
+
+impl Docker {
+    pub fn from_config(config: &DockerDiscoveryConfig) -> Self {
+        Self {
+            host: config.host.trim_start_matches("unix://").to_string(),
+        }
+    }
+
+    /// Running containers labelled `monitor.yeheng.io/enabled=true`.
+    pub async fn containers(&self) -> Result<Vec<Container>> {
+        let url = Url::parse_with_params("http://docker/containers/json", &[("filters", docker::label_filter())])
+            .map_err(|e| Error::internal(e.to_string()))?;
+
+        let body = match self.host.strip_prefix("tcp://") {
+            Some(address) => {
+                let url = format!("http://{}{}?{}", address, url.path(), url.query().unwrap_or_default());
+                Client::new().get(url).send().await?.error_for_status()?.text().await?
+            }
+            None => self.get_over_socket(&format!("{}?{}", url.path(), url.query().unwrap_or_default())).await?,
+        };
+        Ok(serde_json::from_str(&body)?)
+    }
+
+    /// A plain HTTP/1.0 request, so the daemon closes the connection after an
+    /// unchunked body.
+    async fn get_over_socket(&self, path: &str) -> Result<String> {
+        let mut stream = UnixStream::connect(&self.host).await?;
+        stream
+            .write_all(format!("GET {} HTTP/1.0\r\nHost: docker\r\n\r\n", path).as_bytes())
+            .await?;
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response).await?;
+
+        let response = String::from_utf8_lossy(&response);
+        let (head, body) = response
+            .split_once("\r\n\r\n")
+            .ok_or_else(|| Error::internal("Malformed response from the Docker socket"))?;
+        let status = head.lines().next().unwrap_or_default();
+        if status.split_whitespace().nth(1) != Some("200") {
+            return Err(Error::internal(format!("Docker API returned {}: {}", status, body.trim())));
+        }
+        Ok(body.to_string())
+    }
+}
@@ -0,0 +1,123 @@
+use monitor_core::{
+    config::Config,
+    consul as catalog,
+    discovery::{Discovered, Source, SyncAction, CONSUL, DOCKER, KUBERNETES},
+    docker as containers, kubernetes, logging, Error, Result,
+};
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+use crate::{cluster::Cluster, consul::Consul, docker::Docker, monitors::MonitorApi};
+
+mod cluster;
+mod consul;
+mod docker;
+mod monitors;
+
+/// Keeps monitors in line with annotated Kubernetes Ingresses and Services,
+/// labelled Docker containers and tagged Consul services. Kubernetes and
+/// Docker objects opt in with `monitor.yeheng.io/enabled: "true"`; see
+/// `monitor_core::discovery` for the other labels and `monitor_core::consul`
+/// for Consul.
+#[tokio::main]
+async fn main() -> Result<()> {
+    logging::init_logging();
+
+    let config = Config::from_env()?;
+    logging::set_filter(&config.logging.level)?;
+    let settings = config.discovery;
+    let cluster = match settings.kubernetes.enabled {
+        true => Some(Cluster::from_config(&settings.kubernetes)?),
+        false => None,
+    };
+    let docker = settings.docker.enabled.then(|| Docker::from_config(&settings.docker));
+    let consul = settings.consul.enabled.then(|| Consul::from_config(&settings.consul));
+    if cluster.is_none() && docker.is_none() && consul.is_none() {
+        return Err(Error::validation(
+            "Nothing to discover; set DISCOVER_KUBERNETES, DISCOVER_DOCKER or DISCOVER_CONSUL",
+        ));
+    }
+    let api = MonitorApi::from_config(&settings)?;
+    if cluster.is_some() {
+        info!(
+            "Syncing monitors from Kubernetes ({}) every {}s",
+            settings.kubernetes.namespace.as_deref().unwrap_or("all namespaces"),
+            settings.interval_secs
+        );
+    }
+    if docker.is_some() {
+        info!("Syncing monitors from Docker at {} every {}s", settings.docker.host, settings.interval_secs);
+    }
+    if consul.is_some() {
+        info!(
+            "Syncing monitors from Consul services tagged '{}' every {}s",
+            settings.consul.tag, settings.interval_secs
+        );
+    }
+
+    let mut interval = tokio::time::interval(Duration::from_secs(settings.interval_secs.max(5)));
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {
+                if let Some(cluster) = &cluster {
+                    let found = discover_kubernetes(cluster).await;
+                    if let Err(e) = sync(KUBERNETES, found, &api).await {
+                        error!("Kubernetes sync failed: {}", e);
+                    }
+                }
+                if let Some(docker) = &docker {
+                    let found = docker
+                        .containers()
+                        .await
+                        .map(|found| containers::desired_monitors(&found, &settings.docker.published_host));
+                    if let Err(e) = sync(DOCKER, found, &api).await {
+                        error!("Docker sync failed: {}", e);
+                    }
+                }
+                if let Some(consul) = &consul {
+                    let found = consul
+                        .instances()
+                        .await
+                        .map(|found| catalog::desired_monitors(&found, &settings.consul));
+                    if let Err(e) = sync(CONSUL, found, &api).await {
+                        error!("Consul sync failed: {}", e);
+                    }
+                }
+            }
+            _ = tokio::signal::ctrl_c() => {
+                info!("Shutdown signal received");
+                return Ok(());
+            }
+        }
+    }
+}
+
+async fn discover_kubernetes(cluster: &Cluster) -> Result<Discovered> {
+    Ok(kubernetes::desired_monitors(&cluster.ingresses().await?, &cluster.services().await?))
+}
+
+/// Applies what one source asks for. A source that could not be listed is
+/// skipped entirely so its monitors are not paused.
+async fn sync(source: Source, found: Result<Discovered>, api: &MonitorApi) -> Result<()> {
+    let (desired, invalid) = found?;
+    for (object, e) in &invalid {
+        warn!("Ignoring labels of {}: {}", object, e);
+    }
+    let invalid: Vec<String> = invalid.into_iter().map(|(object, _)| object).collect();
+    let existing = api.list().await?;
+
+    for action in source.plan(&desired, &invalid, &existing) {
+        // One failed call should not hold up the rest of the sync
+        let outcome = match &action {
+            SyncAction::Create(want) => api.create(want).await,
+            SyncAction::Update(id, want) => api.update(*id, want).await,
+            SyncAction::Resume(id) => api.resume(*id).await,
+            SyncAction::Pause(id) => api.pause(*id, source.removed_reason).await,
+        };
+        match outcome {
+            Ok(()) => info!("Applied {:?}", action),
+            Err(e) => warn!("Failed to apply {:?}: {}", action, e),
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,86 @@
+use monitor_core::{
+    config::DiscoveryConfig,
+    discovery::DesiredMonitor,
+    models::Monitor,
+    models::{Page, MAX_PER_PAGE},
+    Error, Result,
+};
+use reqwest::{Client, RequestBuilder};
+use serde_json::json;
+use uuid::Uuid;
+
+/// The monitor API, called with a personal access token so changes are
+/// attributed and checked like any other client's.
+pub struct MonitorApi {
+    client: Client,
+    base_url: String,
+    token: String,
+}
+
+impl MonitorApi {
+    pub fn from_config(config: &DiscoveryConfig) -> Result<Self> {
+        let token = config
+            .api_token
+            .clone()
+            .ok_or_else(|| Error::validation("MONITOR_API_TOKEN is required"))?;
+        Ok(Self {
+            client: Client::new(),
+            base_url: config.api_url.trim_end_matches('/').to_string(),
+            token,
+        })
+    }
+
+    pub async fn list(&self) -> Result<Vec<Monitor>> {
+        let mut monitors = Vec::new();
+        for page in 1.. {
+            let request = self
+                .client
+                .get(self.url("/api/monitors"))
+                .query(&[("page", page), ("per_page", MAX_PER_PAGE)]);
+            let batch: Page<Monitor> = self.send(request).await?.json().await?;
+            let done = batch.items.len() < batch.per_page as usize;
+            monitors.extend(batch.items);
+            if done {
+                break;
+            }
+        }
+        Ok(monitors)
+    }
+
+    pub async fn create(&self, desired: &DesiredMonitor) -> Result<()> {
+        let request = self.client.post(self.url("/api/monitors")).json(&desired.create_request()?);
+        self.send(request).await?;
+        Ok(())
+    }
+
+    pub async fn update(&self, id: Uuid, desired: &DesiredMonitor) -> Result<()> {
+        let request = self
+            .client
+            .put(self.url(&format!("/api/monitors/{}", id)))
+            .json(&desired.update_request());
+        self.send(request).await?;
+        Ok(())
+    }
+
+    pub async fn pause(&self, id: Uuid, reason: &str) -> Result<()> {
+        let request = self
+            .client
+            .post(self.url(&format!("/api/monitors/{}/pause", id)))
+            .json(&json!({ "reason": reason }));
+        self.send(request).await?;
+        Ok(())
+    }
+
+    pub async fn resume(&self, id: Uuid) -> Result<()> {
+        self.send(self.client.post(self.url(&format!("/api/monitors/{}/resume", id)))).await?;
+        Ok(())
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    async fn send(&self, request: RequestBuilder) -> Result<reqwest::Response> {
+        Ok(request.bearer_auth(&self.token).send().await?.error_for_status()?)
+    }
+}
@@ -0,0 +1,226 @@
+//! Runs the API router and the scheduler in-process against real Postgres
+//! and Redis containers, for tests that cover whole flows.
+//!
+//! The tests need Docker and are ignored by default:
+//! `cargo test -p monitor-integration-tests -- --ignored`.
+
+use axum::{
+    body::{self, Body},
+    http::{header, Method, Request, StatusCode},
+    routing::get,
+    Router,
+};
+use monitor_api::{audit_log, live::ResultFeed, server::{self, AppState}};
+use monitor_core::{
+    auth::AuthService,
+    cache::create_redis_pool,
+    clock,
+    config::Config,
+    crypto::KeyRing,
+    db::{create_pool, run_migrations, DatabasePool},
+    events::EventBus,
+    faults::FaultInjector,
+    reload::LiveConfig,
+    supervisor::Supervisor,
+    Error, Result,
+};
+use monitor_scheduler::scheduler::MonitorScheduler;
+use serde_json::{json, Value};
+use std::{future::Future, sync::Arc, time::Duration};
+use testcontainers_modules::{
+    postgres::Postgres,
+    redis::Redis,
+    testcontainers::{runners::AsyncRunner, ContainerAsync, ImageExt},
+};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+/// Largest response body the tests read
+const MAX_BODY_BYTES: usize = 16 * 1024 * 1024;
+
+/// Postgres release the tests run against
+const POSTGRES_TAG: &str = "16-alpine";
+
+/// How often `eventually` retries
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Containers that stop when dropped.
+pub struct Services {
+    _postgres: ContainerAsync<Postgres>,
+    _redis: ContainerAsync<Redis>,
+}
+
+/// The API as a router with its background tasks, on a migrated database.
+pub struct TestEnv {
+    pub config: Config,
+    pub db: DatabasePool,
+    pub state: Arc<AppState>,
+    router: Router,
+    supervisor: Supervisor,
+    scheduler: Option<MonitorScheduler>,
+    _services: Option<Services>,
+}
+
+impl TestEnv {
+    /// Starts Postgres and Redis containers and connects to them.
+    pub async fn start() -> Result<Self> {
+        let postgres = Postgres::default().with_tag(POSTGRES_TAG).start().await.map_err(container_error)?;
+        let redis = Redis::default().start().await.map_err(container_error)?;
+
+        let mut config = Config::from_env().map_err(|e| Error::internal(e.to_string()))?;
+        config.database.host = postgres.get_host().await.map_err(container_error)?.to_string();
+        config.database.port = postgres.get_host_port_ipv4(5432).await.map_err(container_error)?;
+        config.database.username = "postgres".to_string();
+        config.database.password = "postgres".to_string();
+        config.database.database = "postgres".to_string();
+        let redis_host = redis.get_host().await.map_err(container_error)?;
+        let redis_port = redis.get_host_port_ipv4(6379).await.map_err(container_error)?;
+        config.redis.url = format!("redis://{}:{}", redis_host, redis_port);
+        // Stored secrets need a key, which the default JWT secret cannot provide
+        config.auth.jwt_secret = format!("integration-tests-{}", Uuid::new_v4());
+
+        let mut env = Self::connect(config).await?;
+        env._services = Some(Services {
+            _postgres: postgres,
+            _redis: redis,
+        });
+        Ok(env)
+    }
+
+    /// Migrates the database `config` names and builds the API on it, the
+    /// way the API binary does. Checks run at once and results spill to a
+    /// temporary file. Set `config.faults` to inject failures.
+    pub async fn connect(mut config: Config) -> Result<Self> {
+        config.scheduler.start_spread_secs = 0;
+        config.scheduler.jitter_percent = 0;
+        config.result_buffer.spill_path = std::env::temp_dir()
+            .join(format!("monitor-it-spill-{}.jsonl", Uuid::new_v4()))
+            .display()
+            .to_string();
+
+        let db = create_pool(&config.database).await?;
+        run_migrations(&db).await?;
+        let events = EventBus::default();
+        let supervisor = Supervisor::default();
+        let state = Arc::new(AppState {
+            db: db.clone(),
+            redis: create_redis_pool(&config.redis).await?,
+            auth: AuthService::new(config.auth.jwt_secret.clone(), config.auth.jwt_expiration),
+            keys: KeyRing::from_config(&config.encryption, &config.auth.jwt_secret)?,
+            config: LiveConfig::new(config.clone()),
+            results: ResultFeed::new(events.clone()),
+            events,
+            supervisor: supervisor.clone(),
+            clock: clock::system(),
+            faults: FaultInjector::from_config(&config),
+        });
+        state.results.spawn_listener(&supervisor, db.clone());
+        audit_log::spawn(&supervisor, db.clone(), &state.events);
+        let router = server::create_app(state.clone()).await;
+
+        Ok(Self {
+            config,
+            db,
+            state,
+            router,
+            supervisor,
+            scheduler: None,
+            _services: None,
+        })
+    }
+
+    /// Starts a scheduler queue worker on the same database.
+    pub async fn start_scheduler(&mut self) -> Result<()> {
+        let mut scheduler = MonitorScheduler::new(
+            self.db.clone(),
+            self.state.config.clone(),
+            self.state.keys.clone(),
+            Supervisor::default(),
+            self.state.clock.clone(),
+        )
+        .await?;
+        scheduler.start_queue_worker(None).await?;
+        self.scheduler = Some(scheduler);
+        Ok(())
+    }
+
+    /// Sends one request through the router and returns the status with the
+    /// JSON body, or `Null` when the body is empty or not JSON.
+    pub async fn request(
+        &self,
+        method: Method,
+        path: &str,
+        token: Option<&str>,
+        body: Option<Value>,
+    ) -> (StatusCode, Value) {
+        let mut request = Request::builder().method(method).uri(path);
+        if let Some(token) = token {
+            request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+        }
+        let request = match body {
+            Some(body) => request
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string())),
+            None => request.body(Body::empty()),
+        }
+        .expect("valid test request");
+
+        let response = self.router.clone().oneshot(request).await.expect("the router is infallible");
+        let status = response.status();
+        let bytes = body::to_bytes(response.into_body(), MAX_BODY_BYTES).await.expect("readable response body");
+        (status, serde_json::from_slice(&bytes).unwrap_or(Value::Null))
+    }
+
+    /// Registers a user and returns its session token.
+    pub async fn register(&self, username: &str) -> String {
+        let body = json!({
+            "username": username,
+            "email": format!("{}@example.com", username),
+            "password": "correct horse battery staple",
+        });
+        let (status, response) = self.request(Method::POST, "/api/auth/register", None, Some(body)).await;
+        assert_eq!(status, StatusCode::CREATED, "register failed: {}", response);
+        response["token"].as_str().expect("token in the register response").to_string()
+    }
+
+    /// Stops the scheduler and the API's background tasks.
+    pub async fn shutdown(mut self) {
+        if let Some(mut scheduler) = self.scheduler.take() {
+            let _ = scheduler.stop().await;
+        }
+        self.supervisor.shutdown().await;
+        let _ = std::fs::remove_file(&self.config.result_buffer.spill_path);
+    }
+}
+
+/// Serves `body` with `status` on a local port, as a target for checks.
+/// Returns the URL.
+pub async fn spawn_target(status: StatusCode, body: &'static str) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.expect("a free local port");
+    let address = listener.local_addr().expect("bound address");
+    let app = Router::new().route("/", get(move || async move { (status, body) }));
+    tokio::spawn(async move { axum::serve(listener, app).await });
+    format!("http://{}/", address)
+}
+
+/// Retries `attempt` until it returns `Some` or `timeout` passes.
+pub async fn eventually<T, F, Fut>(timeout: Duration, mut attempt: F) -> Option<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Option<T>>,
+{
+    let deadline = tokio::time::Instant::now() + timeout;
+    loop {
+        if let Some(value) = attempt().await {
+            return Some(value);
+        }
+        if tokio::time::Instant::now() >= deadline {
+            return None;
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+fn container_error(e: impl std::fmt::Display) -> Error {
+    Error::internal(format!("Failed to start container: {}", e))
+}
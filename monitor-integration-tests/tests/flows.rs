@@ -0,0 +1,265 @@
+use axum::http::{Method, StatusCode};
+use chrono::Utc;
+use monitor_core::{audit, models::{MonitorResult, UserRole}, repository};
+use monitor_integration_tests::{eventually, spawn_target, TestEnv};
+use monitor_scheduler::result_sink::ResultSink;
+use serde_json::{json, Value};
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Longest a check may take to show up in the API
+const RESULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+async fn create_monitor(env: &TestEnv, token: &str, endpoint: &str) -> String {
+    let body = json!({
+        "name": "Integration target",
+        "endpoint": endpoint,
+        "method": "GET",
+        "expected_status": 200,
+        "interval": 60,
+    });
+    let (status, monitor) = env.request(Method::POST, "/api/monitors", Some(token), Some(body)).await;
+    assert_eq!(status, StatusCode::OK, "create failed: {}", monitor);
+    monitor["id"].as_str().expect("monitor id").to_string()
+}
+
+/// The first stored result for the monitor, through the API.
+async fn first_result(env: &TestEnv, token: &str, monitor_id: &str) -> Option<Value> {
+    let path = format!("/api/monitors/{}/results", monitor_id);
+    eventually(RESULT_TIMEOUT, || async {
+        let (status, history) = env.request(Method::GET, &path, Some(token), None).await;
+        assert_eq!(status, StatusCode::OK, "results failed: {}", history);
+        history["items"].as_array().and_then(|items| items.first().cloned())
+    })
+    .await
+}
+
+#[tokio::test]
+#[ignore = "needs Docker"]
+async fn registered_user_sees_the_result_of_a_scheduled_check() {
+    let mut env = TestEnv::start().await.expect("test environment");
+    let token = env.register("alice").await;
+    let target = spawn_target(StatusCode::OK, "ok").await;
+    let monitor_id = create_monitor(&env, &token, &target).await;
+
+    env.start_scheduler().await.expect("scheduler");
+    let result = first_result(&env, &token, &monitor_id).await.expect("a check result");
+    assert_eq!(result["status"], "success");
+    assert_eq!(result["response_code"], 200);
+
+    env.shutdown().await;
+}
+
+#[tokio::test]
+#[ignore = "needs Docker"]
+async fn unexpected_status_is_recorded_as_a_failure() {
+    let mut env = TestEnv::start().await.expect("test environment");
+    let token = env.register("bob").await;
+    let target = spawn_target(StatusCode::SERVICE_UNAVAILABLE, "down").await;
+    let monitor_id = create_monitor(&env, &token, &target).await;
+
+    env.start_scheduler().await.expect("scheduler");
+    let result = first_result(&env, &token, &monitor_id).await.expect("a check result");
+    assert_eq!(result["status"], "failure");
+    assert_eq!(result["response_code"], 503);
+    assert_eq!(result["error_category"], "http_status");
+
+    env.shutdown().await;
+}
+
+#[tokio::test]
+#[ignore = "needs Docker"]
+async fn monitors_are_private_to_their_owner() {
+    let env = TestEnv::start().await.expect("test environment");
+    let (status, _) = env.request(Method::GET, "/api/monitors", None, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let owner = env.register("carol").await;
+    let other = env.register("dave").await;
+    let monitor_id = create_monitor(&env, &owner, "https://example.com/").await;
+    let listed = |page: Value| page["items"].as_array().into_iter().flatten().any(|m| m["id"] == monitor_id.as_str());
+
+    let (status, page) = env.request(Method::GET, "/api/monitors", Some(&owner), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(listed(page));
+    let (status, page) = env.request(Method::GET, "/api/monitors", Some(&other), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(!listed(page));
+
+    env.shutdown().await;
+}
+
+#[tokio::test]
+#[ignore = "needs Docker"]
+async fn dashboard_and_read_only_tokens_cannot_write_or_administer() {
+    let env = TestEnv::start().await.expect("test environment");
+    // The first account is the administrator
+    let admin = env.register("erin").await;
+    let member = env.register("frank").await;
+    let monitor_id = create_monitor(&env, &admin, "https://example.com/").await;
+
+    let body = json!({ "name": "Lobby", "monitor_ids": [monitor_id] });
+    let (status, created) = env.request(Method::POST, "/api/tokens/dashboard", Some(&admin), Some(body)).await;
+    assert_eq!(status, StatusCode::OK, "dashboard token failed: {}", created);
+    let dashboard = created["token"].as_str().expect("dashboard token").to_string();
+    let dashboard_id = created["details"]["id"].as_str().expect("dashboard token id").to_string();
+
+    let body = json!({ "name": "Read only", "scopes": ["read:monitors"] });
+    let (status, created) = env.request(Method::POST, "/api/tokens", Some(&member), Some(body)).await;
+    assert_eq!(status, StatusCode::OK, "personal token failed: {}", created);
+    let personal = created["token"].as_str().expect("personal token").to_string();
+    let personal_id = created["details"]["id"].as_str().expect("personal token id").to_string();
+
+    let status_path = format!("/api/monitors/{}/status", monitor_id);
+    let (status, _) = env.request(Method::GET, &status_path, Some(&dashboard), None).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = env.request(Method::GET, "/api/monitors", Some(&personal), None).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let monitor_body = json!({
+        "name": "Forbidden",
+        "endpoint": "https://example.com/",
+        "method": "GET",
+        "expected_status": 200,
+        "interval": 60,
+    });
+    let forbidden = [
+        (Method::GET, "/api/monitors".to_string(), None),
+        (Method::POST, "/api/monitors".to_string(), Some(monitor_body.clone())),
+        (Method::PUT, format!("/api/monitors/{}", monitor_id), Some(json!({ "name": "Renamed" }))),
+        (Method::POST, format!("/api/monitors/{}/pause", monitor_id), Some(json!({}))),
+        (Method::GET, "/api/webhooks".to_string(), None),
+        (Method::POST, "/api/webhooks".to_string(), Some(json!({ "url": "https://example.com/hook" }))),
+        (Method::GET, "/api/admin/config".to_string(), None),
+        (Method::DELETE, format!("/api/tokens/{}", dashboard_id), None),
+    ];
+    for (method, path, body) in forbidden {
+        let (status, response) = env.request(method.clone(), &path, Some(&dashboard), body).await;
+        assert_eq!(status, StatusCode::FORBIDDEN, "dashboard token on {} {}: {}", method, path, response);
+    }
+
+    let forbidden = [
+        (Method::POST, "/api/monitors".to_string(), Some(monitor_body)),
+        (Method::POST, "/api/webhooks".to_string(), Some(json!({ "url": "https://example.com/hook" }))),
+        (Method::GET, "/api/admin/config".to_string(), None),
+        (Method::GET, "/api/admin/audit-log".to_string(), None),
+        (Method::DELETE, format!("/api/tokens/{}", personal_id), None),
+    ];
+    for (method, path, body) in forbidden {
+        let (status, response) = env.request(method.clone(), &path, Some(&personal), body).await;
+        assert_eq!(status, StatusCode::FORBIDDEN, "personal token on {} {}: {}", method, path, response);
+    }
+
+    env.shutdown().await;
+}
+
+#[tokio::test]
+#[ignore = "needs Docker"]
+async fn purging_a_user_removes_their_audit_trail() {
+    let env = TestEnv::start().await.expect("test environment");
+    let admin = env.register("gina").await;
+    env.register("harry").await;
+    let user = repository::find_active_login(&env.db, "harry").await.expect("user lookup").expect("harry");
+    // Harry's edit of someone else's monitor stays in the log after his own monitors are gone
+    let monitor_id = create_monitor(&env, &admin, "https://example.com/").await;
+    let monitor_id = monitor_id.parse().expect("monitor id is a UUID");
+    audit::record(&env.db, Some(user.id), audit::ACTION_MONITOR_UPDATED, Some(monitor_id), json!({}))
+        .await
+        .expect("audit entry");
+
+    let path = format!("/api/admin/users/{}/data", user.id);
+    let dry_run = format!("{}?dry_run=true", path);
+    let (status, report) = env.request(Method::DELETE, &dry_run, Some(&admin), None).await;
+    assert_eq!(status, StatusCode::OK, "dry run failed: {}", report);
+    assert_eq!(report["rows"]["audit_log"], 1);
+
+    let (status, report) = env.request(Method::DELETE, &path, Some(&admin), None).await;
+    assert_eq!(status, StatusCode::OK, "purge failed: {}", report);
+    assert_eq!(report["rows"]["audit_log"], 1);
+    let remaining = audit::list(&env.db, Some(monitor_id), 100).await.expect("audit log");
+    assert!(remaining.iter().all(|entry| entry.action != audit::ACTION_MONITOR_UPDATED));
+
+    env.shutdown().await;
+}
+
+#[tokio::test]
+#[ignore = "needs Docker"]
+async fn only_one_of_simultaneous_first_registrations_becomes_admin() {
+    let env = TestEnv::start().await.expect("test environment");
+    tokio::join!(env.register("jack"), env.register("kate"), env.register("liam"), env.register("mona"));
+
+    let users = repository::list_users(&env.db).await.expect("users");
+    assert_eq!(users.len(), 4);
+    assert_eq!(users.iter().filter(|user| user.role == UserRole::Admin).count(), 1);
+
+    env.shutdown().await;
+}
+
+#[tokio::test]
+#[ignore = "needs Docker"]
+async fn team_filter_is_limited_to_the_callers_teams() {
+    let env = TestEnv::start().await.expect("test environment");
+    let admin = env.register("nora").await;
+    let outsider = env.register("oscar").await;
+    let (status, team) = env.request(Method::POST, "/api/teams", Some(&admin), Some(json!({ "name": "ops" }))).await;
+    assert!(status.is_success(), "team creation failed: {}", team);
+    let path = format!("/api/monitors?filter=team&team_id={}", team["id"].as_str().expect("team id"));
+
+    let (status, _) = env.request(Method::GET, &path, Some(&outsider), None).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = env.request(Method::GET, &path, Some(&admin), None).await;
+    assert_eq!(status, StatusCode::OK);
+
+    env.shutdown().await;
+}
+
+fn success_result(monitor_id: Uuid) -> MonitorResult {
+    MonitorResult {
+        id: Uuid::new_v4(),
+        monitor_id,
+        status: "success".to_string(),
+        response_time: 12,
+        response_code: Some(200),
+        response_body: None,
+        response_headers: None,
+        error_message: None,
+        error_category: None,
+        error_hint: None,
+        checked_at: Utc::now(),
+        clock_skew_ms: None,
+        schedule_lag_ms: None,
+        attempts: 1,
+    }
+}
+
+#[tokio::test]
+#[ignore = "needs Docker"]
+async fn a_rejected_trial_write_closes_the_result_breaker() {
+    let env = TestEnv::start().await.expect("test environment");
+    let token = env.register("iris").await;
+    let monitor_id = create_monitor(&env, &token, "https://example.com/").await;
+    let monitor_id = monitor_id.parse().expect("monitor id is a UUID");
+    let mut buffer = env.config.result_buffer.clone();
+    buffer.failure_threshold = 1;
+    buffer.open_secs = 0;
+    let sink = ResultSink::new(env.db.clone(), &buffer, None).await.expect("result sink");
+
+    // A missing table is not the result's fault, so this write opens the breaker
+    sqlx::query("ALTER TABLE monitor_results RENAME TO monitor_results_away").execute(&env.db).await.expect("rename");
+    sink.save(&success_result(monitor_id)).await.expect("spilled");
+    sqlx::query("ALTER TABLE monitor_results_away RENAME TO monitor_results").execute(&env.db).await.expect("rename");
+
+    // The half-open trial is for a monitor that does not exist; the database still answered
+    sink.save(&success_result(Uuid::new_v4())).await.expect("rejected");
+
+    let stored = success_result(monitor_id);
+    sink.save(&stored).await.expect("saved");
+    let rows: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM monitor_results WHERE id = $1")
+        .bind(stored.id)
+        .fetch_one(&env.db)
+        .await
+        .expect("count");
+    assert_eq!(rows, 1, "the write after a rejected trial was spilled instead of stored");
+
+    env.shutdown().await;
+}
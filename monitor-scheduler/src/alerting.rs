@@ -0,0 +1,406 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use monitor_core::{
+    alerts::{self, AlertNotification, AlertRule, AlertState, ChannelConfig, RESULT_ALERT_TYPES},
+    clock::SharedClock,
+    config::SmtpConfig,
+    crypto::KeyRing,
+    db::DatabasePool,
+    events::{Event, EventBus},
+    expirations::{self, EXPIRY_ALERT_TYPE},
+    maintenance,
+    models::{Monitor, MonitorResult},
+    pagerduty::{self, EventAction},
+    reload::LiveConfig,
+    repository, telegram, Error, Result,
+};
+use reqwest::Client;
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::{smtp, webhooks};
+
+const NOTIFY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Somewhere an alert notification can be sent.
+#[async_trait]
+pub trait NotificationChannel: Send + Sync {
+    async fn notify(&self, notification: &AlertNotification) -> Result<()>;
+
+    /// Used in logs and `NotificationSent` events.
+    fn describe(&self) -> String;
+}
+
+/// POSTs the notification as JSON.
+pub struct WebhookChannel {
+    client: Client,
+    url: String,
+}
+
+async fn post_json<T: serde::Serialize + ?Sized>(client: &Client, url: &str, body: &T) -> Result<()> {
+    let response = client.post(url).json(body).timeout(NOTIFY_TIMEOUT).send().await?;
+    if !response.status().is_success() {
+        return Err(Error::internal(format!("HTTP {}", response.status())));
+    }
+    Ok(())
+}
+
+#[async_trait]
+impl NotificationChannel for WebhookChannel {
+    async fn notify(&self, notification: &AlertNotification) -> Result<()> {
+        post_json(&self.client, &self.url, notification).await
+    }
+
+    fn describe(&self) -> String {
+        format!("webhook {}", self.url)
+    }
+}
+
+/// Posts a templated message to a Slack incoming webhook.
+pub struct SlackChannel {
+    client: Client,
+    webhook_url: String,
+    channel: Option<String>,
+    mentions: Vec<String>,
+    template: Option<String>,
+}
+
+#[async_trait]
+impl NotificationChannel for SlackChannel {
+    async fn notify(&self, notification: &AlertNotification) -> Result<()> {
+        let payload = alerts::slack_payload(
+            self.channel.as_deref(),
+            &self.mentions,
+            self.template.as_deref(),
+            notification,
+        );
+        post_json(&self.client, &self.webhook_url, &payload).await
+    }
+
+    fn describe(&self) -> String {
+        match &self.channel {
+            Some(channel) => format!("Slack {}", channel),
+            None => "Slack webhook".to_string(),
+        }
+    }
+}
+
+/// Posts a templated message to a Discord webhook.
+pub struct DiscordChannel {
+    client: Client,
+    webhook_url: String,
+    mentions: Vec<String>,
+    template: Option<String>,
+}
+
+#[async_trait]
+impl NotificationChannel for DiscordChannel {
+    async fn notify(&self, notification: &AlertNotification) -> Result<()> {
+        let payload = alerts::discord_payload(&self.mentions, self.template.as_deref(), notification);
+        post_json(&self.client, &self.webhook_url, &payload).await
+    }
+
+    fn describe(&self) -> String {
+        "Discord webhook".to_string()
+    }
+}
+
+/// Triggers a PagerDuty incident when the rule fires and resolves it when
+/// the rule resolves.
+pub struct PagerDutyChannel {
+    client: Client,
+    routing_key: String,
+    severity: String,
+}
+
+#[async_trait]
+impl NotificationChannel for PagerDutyChannel {
+    async fn notify(&self, notification: &AlertNotification) -> Result<()> {
+        let action = match notification.state {
+            AlertState::Firing => EventAction::Trigger,
+            AlertState::Resolved => EventAction::Resolve,
+        };
+        let event = pagerduty::event(&self.routing_key, action, &self.severity, notification);
+        pagerduty::enqueue(&self.client, &event).await
+    }
+
+    fn describe(&self) -> String {
+        "PagerDuty".to_string()
+    }
+}
+
+/// Sends a MarkdownV2 message through a Telegram bot.
+pub struct TelegramChannel {
+    client: Client,
+    bot_token: String,
+    chat_id: String,
+}
+
+#[async_trait]
+impl NotificationChannel for TelegramChannel {
+    async fn notify(&self, notification: &AlertNotification) -> Result<()> {
+        telegram::send(&self.client, &self.bot_token, &self.chat_id, notification).await
+    }
+
+    fn describe(&self) -> String {
+        format!("Telegram chat {}", self.chat_id)
+    }
+}
+
+/// Sends a plain text email through the configured SMTP server.
+pub struct EmailChannel {
+    smtp: SmtpConfig,
+    to: Vec<String>,
+}
+
+#[async_trait]
+impl NotificationChannel for EmailChannel {
+    async fn notify(&self, notification: &AlertNotification) -> Result<()> {
+        let message = alerts::email_message(&self.smtp.from, &self.to, notification);
+        smtp::send(&self.smtp, &self.to, &message).await
+    }
+
+    fn describe(&self) -> String {
+        format!("email to {}", self.to.join(", "))
+    }
+}
+
+/// Evaluates a monitor's alert rules and notifies their channels when a rule
+/// starts or stops firing.
+pub struct AlertManager {
+    db: DatabasePool,
+    client: Client,
+    keys: KeyRing,
+    config: LiveConfig,
+    events: EventBus,
+    clock: SharedClock,
+}
+
+impl AlertManager {
+    pub fn new(
+        db: DatabasePool,
+        client: Client,
+        keys: KeyRing,
+        config: LiveConfig,
+        events: EventBus,
+        clock: SharedClock,
+    ) -> Self {
+        Self {
+            db,
+            client,
+            keys,
+            config,
+            events,
+            clock,
+        }
+    }
+
+    /// Runs after `result` is saved. Failures are logged; they never fail the check.
+    pub async fn evaluate_result(&self, monitor: &Monitor, result: &MonitorResult) {
+        let rules = match self.rules(monitor, RESULT_ALERT_TYPES).await {
+            Ok(rules) => rules,
+            Err(e) => {
+                warn!("Failed to load alert rules for {}: {}", monitor.name, e);
+                return;
+            }
+        };
+        let Some(needed) = rules.iter().map(|rule| rule.condition.results_needed()).max() else {
+            return;
+        };
+
+        // The result sink may not have written `result` yet, so it is added
+        // here and anything stored at or after it is ignored
+        let mut recent = vec![result.clone()];
+        match repository::recent_results(&self.db, monitor.id, None, needed as i64 + 1).await {
+            Ok(stored) => recent.extend(
+                stored
+                    .into_iter()
+                    .filter(|r| r.id != result.id && r.checked_at < result.checked_at)
+                    .take(needed.saturating_sub(1)),
+            ),
+            Err(e) => {
+                warn!("Failed to load recent results for {}: {}", monitor.name, e);
+                return;
+            }
+        }
+
+        for rule in &rules {
+            let Some(breached) = rule.condition.evaluate(&recent, None) else {
+                continue;
+            };
+            let summary = rule.condition.describe(Some(result), None);
+            self.transition(monitor, rule, breached, summary, Some(result)).await;
+        }
+    }
+
+    /// Runs after the monitor's certificate and domain expiry are refreshed.
+    pub async fn evaluate_expiry(&self, monitor: &Monitor) -> Result<()> {
+        let rules = self.rules(monitor, &[EXPIRY_ALERT_TYPE]).await?;
+        if rules.is_empty() {
+            return Ok(());
+        }
+        let Some(expires_at) = expirations::soonest_expiry_for(&self.db, monitor.id).await? else {
+            return Ok(());
+        };
+        let days = expirations::days_remaining(expires_at, self.clock.now());
+        for rule in &rules {
+            if let Some(breached) = rule.condition.evaluate(&[], Some(days)) {
+                let summary = rule.condition.describe(None, Some(days));
+                self.transition(monitor, rule, breached, summary, None).await;
+            }
+        }
+        Ok(())
+    }
+
+    async fn rules(&self, monitor: &Monitor, types: &[&str]) -> Result<Vec<AlertRule>> {
+        let alerts = alerts::enabled_rules(&self.db, monitor.id, types).await?;
+        Ok(alerts
+            .iter()
+            .filter_map(|alert| match AlertRule::from_alert(alert) {
+                Ok(rule) => Some(rule),
+                Err(e) => {
+                    warn!("Ignoring invalid alert {} of {}: {}", alert.id, monitor.name, e);
+                    None
+                }
+            })
+            .collect())
+    }
+
+    /// Notifies only on a change of state. The conditional update makes sure
+    /// one worker sends it when several evaluate the same rule.
+    async fn transition(
+        &self,
+        monitor: &Monitor,
+        rule: &AlertRule,
+        breached: bool,
+        summary: String,
+        latest: Option<&MonitorResult>,
+    ) {
+        let now = self.clock.now();
+        // Alerts do not start firing during maintenance; a breach that outlasts
+        // the window fires on the first evaluation after it
+        if breached && !rule.firing && self.in_maintenance(monitor, now).await {
+            return;
+        }
+        let (changed, state, summary) = match (breached, rule.firing) {
+            (true, false) => (alerts::mark_firing(&self.db, rule.id, now).await, AlertState::Firing, summary),
+            (false, true) => (
+                alerts::mark_resolved(&self.db, rule.id).await,
+                AlertState::Resolved,
+                format!("{} alert resolved", rule.alert_type),
+            ),
+            _ => return,
+        };
+        match changed {
+            Ok(true) => {}
+            Ok(false) => return,
+            Err(e) => {
+                warn!("Failed to update alert {} of {}: {}", rule.id, monitor.name, e);
+                return;
+            }
+        }
+
+        info!("Alert {} of {} is {:?}: {}", rule.alert_type, monitor.name, state, summary);
+        if state == AlertState::Firing {
+            self.events.publish(Event::IncidentOpened {
+                monitor_id: monitor.id,
+                alert_id: rule.id,
+                alert_type: rule.alert_type.clone(),
+                summary: summary.clone(),
+            });
+        }
+        let notification = AlertNotification::new(rule, monitor, state, summary, latest, now);
+        self.notify(rule, &notification).await;
+    }
+
+    async fn in_maintenance(&self, monitor: &Monitor, at: DateTime<Utc>) -> bool {
+        match maintenance::in_maintenance(&self.db, monitor.id, &monitor.tags, at).await {
+            Ok(active) => active,
+            Err(e) => {
+                warn!("Failed to check maintenance windows for {}: {}", monitor.name, e);
+                false
+            }
+        }
+    }
+
+    async fn notify(&self, rule: &AlertRule, notification: &AlertNotification) {
+        if rule.channels.is_empty() {
+            let delivered = webhooks::deliver_alert(&self.db, &self.client, &self.keys, notification).await;
+            if let Err(e) = &delivered {
+                warn!("Failed to deliver alert {} to webhook endpoints: {}", rule.id, e);
+            }
+            self.sent(notification, "webhook endpoints".to_string(), delivered);
+            return;
+        }
+        for channel in self.channels(rule) {
+            let notified = channel.notify(notification).await;
+            if let Err(e) = &notified {
+                warn!("Failed to notify {} of alert {}: {}", channel.describe(), rule.id, e);
+            }
+            self.sent(notification, channel.describe(), notified);
+        }
+    }
+
+    fn sent(&self, notification: &AlertNotification, channel: String, outcome: Result<()>) {
+        self.events.publish(Event::NotificationSent {
+            monitor_id: notification.monitor_id,
+            channel,
+            error: outcome.err().map(|e| e.to_string()),
+        });
+    }
+
+    fn channels(&self, rule: &AlertRule) -> Vec<Box<dyn NotificationChannel>> {
+        let smtp = &self.config.current().alerting.smtp;
+        rule.channels
+            .iter()
+            .filter_map(|channel| -> Option<Box<dyn NotificationChannel>> {
+                match channel {
+                    ChannelConfig::Webhook { url } => Some(Box::new(WebhookChannel {
+                        client: self.client.clone(),
+                        url: url.clone(),
+                    })),
+                    ChannelConfig::Email { .. } if smtp.host.is_none() => {
+                        warn!("Alert {} has an email channel but SMTP_HOST is not set", rule.id);
+                        None
+                    }
+                    ChannelConfig::Email { to } => Some(Box::new(EmailChannel {
+                        smtp: smtp.clone(),
+                        to: to.clone(),
+                    })),
+                    ChannelConfig::Slack {
+                        webhook_url,
+                        channel,
+                        mentions,
+                        template,
+                    } => Some(Box::new(SlackChannel {
+                        client: self.client.clone(),
+                        webhook_url: webhook_url.clone(),
+                        channel: channel.clone(),
+                        mentions: mentions.clone(),
+                        template: template.clone(),
+                    })),
+                    ChannelConfig::Telegram { bot_token, chat_id } => Some(Box::new(TelegramChannel {
+                        client: self.client.clone(),
+                        bot_token: bot_token.clone(),
+                        chat_id: chat_id.clone(),
+                    })),
+                    ChannelConfig::PagerDuty { routing_key, severity } => Some(Box::new(PagerDutyChannel {
+                        client: self.client.clone(),
+                        routing_key: routing_key.clone(),
+                        severity: severity.clone().unwrap_or_else(|| pagerduty::DEFAULT_SEVERITY.to_string()),
+                    })),
+                    ChannelConfig::Discord {
+                        webhook_url,
+                        mentions,
+                        template,
+                    } => Some(Box::new(DiscordChannel {
+                        client: self.client.clone(),
+                        webhook_url: webhook_url.clone(),
+                        mentions: mentions.clone(),
+                        template: template.clone(),
+                    })),
+                }
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,266 @@
+use chrono::Utc;
+use monitor_core::{
+    brokers::{self, BrokerCheck, NATS_DEFAULT_PORT},
+    clock,
+    durations::Seconds,
+    failures::FailureCategory,
+    models::{Monitor, MonitorResult},
+    Error, Result,
+};
+use reqwest::Url;
+use serde_json::json;
+use std::{
+    collections::HashMap,
+    time::Instant,
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::TcpStream,
+};
+use uuid::Uuid;
+
+use crate::clients::{self, HostClients};
+
+/// Result status, category and message of a check that did not pass.
+pub type Failure = (&'static str, FailureCategory, String);
+
+/// What a passing check reports: the HTTP status, if any, and a JSON body.
+pub type Outcome = std::result::Result<(Option<i32>, serde_json::Value), (Option<i32>, Failure)>;
+
+/// Runs the monitor's broker check. The timeout covers connecting, the probe
+/// round trip and the admin API call.
+pub async fn run(
+    targets: &HostClients,
+    monitor: &Monitor,
+    check: &serde_json::Value,
+    credentials: &HashMap<String, String>,
+    timeout: Seconds,
+) -> MonitorResult {
+    let start_time = Instant::now();
+    let outcome = match serde_json::from_value::<BrokerCheck>(check.clone()) {
+        Ok(check) => with_timeout(timeout, execute(targets, monitor, &check, credentials)).await,
+        Err(e) => Err((None, ("error", FailureCategory::Other, format!("Invalid broker check: {}", e)))),
+    };
+    into_result(monitor, start_time, outcome)
+}
+
+/// Reports a check that did not finish within `timeout` as timed out.
+pub async fn with_timeout(timeout: Seconds, check: impl Future<Output = Outcome>) -> Outcome {
+    tokio::time::timeout(timeout.as_std(), check).await.unwrap_or_else(|_| {
+        Err((
+            None,
+            (
+                "timeout",
+                FailureCategory::RequestTimeout,
+                format!("No answer within {}", timeout),
+            ),
+        ))
+    })
+}
+
+/// The check result for an outcome; `response_time` runs from `start_time`.
+pub fn into_result(monitor: &Monitor, start_time: Instant, outcome: Outcome) -> MonitorResult {
+    let (status, response_code, response_body, failure) = match outcome {
+        Ok((code, body)) => ("success", code, Some(body.to_string()), None),
+        Err((code, (status, category, message))) => (status, code, None, Some((category, message))),
+    };
+    let (error_category, error_message) = failure
+        .map(|(category, message)| (category.as_str().to_string(), message))
+        .unzip();
+    MonitorResult {
+        id: Uuid::new_v4(),
+        monitor_id: monitor.id,
+        status: status.to_string(),
+        response_time: start_time.elapsed().as_millis() as i32,
+        response_code,
+        response_body,
+        response_headers: None,
+        error_message,
+        error_category,
+        error_hint: None,
+        checked_at: Utc::now(),
+        clock_skew_ms: clock::current_offset_ms(),
+        schedule_lag_ms: None,
+        attempts: 1,
+    }
+}
+
+async fn execute(
+    targets: &HostClients,
+    monitor: &Monitor,
+    check: &BrokerCheck,
+    credentials: &HashMap<String, String>,
+) -> Outcome {
+    match check {
+        BrokerCheck::KafkaConsumerLag {
+            cluster_id,
+            group,
+            max_lag,
+        } => kafka_lag(targets, monitor, cluster_id, group, *max_lag, credentials).await,
+        nats => {
+            let failed = |category, e: Error| (None, ("error", category, e.to_string()));
+            let url = Url::parse(&monitor.endpoint)
+                .map_err(|e| failed(FailureCategory::Other, Error::validation(e.to_string())))?;
+            let mut connection = NatsConnection::connect(&url)
+                .await
+                .map_err(|e| failed(FailureCategory::ConnectError, e))?;
+            nats_check(&mut connection, monitor, nats).await.map_err(|e| failed(FailureCategory::Other, e))?
+        }
+    }
+}
+
+/// Runs a NATS check on an open connection. The outer `Err` is a protocol
+/// or connection error, the inner one a check that did not pass.
+async fn nats_check(connection: &mut NatsConnection, monitor: &Monitor, check: &BrokerCheck) -> Result<Outcome> {
+    match check {
+        BrokerCheck::NatsProbe { subject } => {
+            let subject = brokers::probe_subject(subject.as_deref(), monitor.id);
+            let nonce = Uuid::new_v4().to_string();
+            connection.subscribe(&subject, "1").await?;
+            connection.publish(&subject, None, nonce.as_bytes()).await?;
+            let sent = Instant::now();
+            // Other publishers may share the subject; only our probe counts
+            while connection.next_message("1").await? != nonce.as_bytes() {}
+            Ok(Ok((None, json!({ "subject": subject, "round_trip_ms": sent.elapsed().as_millis() as u64 }))))
+        }
+        BrokerCheck::NatsConsumerLag {
+            stream,
+            consumer,
+            max_lag,
+        } => {
+            let inbox = format!("_INBOX.{}", Uuid::new_v4().simple());
+            connection.subscribe(&inbox, "1").await?;
+            connection
+                .publish(&brokers::consumer_info_subject(stream, consumer), Some(&inbox), b"")
+                .await?;
+            let reply = connection.next_message("1").await?;
+            let body: serde_json::Value = serde_json::from_slice(&reply)?;
+            let outcome = brokers::parse_consumer_lag(&body)
+                .and_then(|lag| brokers::check_lag(lag, *max_lag).map(|_| lag))
+                .map(|lag| (None, json!({ "stream": stream, "consumer": consumer, "lag": lag })))
+                .map_err(|message| (None, ("failure", FailureCategory::AssertionFailed, message)));
+            Ok(outcome)
+        }
+        BrokerCheck::KafkaConsumerLag { .. } => Err(Error::internal("not a NATS check")),
+    }
+}
+
+async fn kafka_lag(
+    targets: &HostClients,
+    monitor: &Monitor,
+    cluster_id: &str,
+    group: &str,
+    max_lag: u64,
+    credentials: &HashMap<String, String>,
+) -> Outcome {
+    let url = brokers::kafka_lag_url(&monitor.endpoint, cluster_id, group)
+        .map_err(|e| (None, ("error", FailureCategory::Other, e.to_string())))?;
+    let mut request = targets.for_url(url.as_str(), monitor).get(url);
+    for (name, value) in credentials {
+        request = request.header(name, value);
+    }
+    let response = request.send().await.map_err(|e| (None, clients::describe_error(&e)))?;
+    let code = response.status().as_u16() as i32;
+    if !response.status().is_success() {
+        let message = format!("REST Proxy returned HTTP {}", code);
+        return Err((Some(code), ("failure", FailureCategory::HttpStatus, message)));
+    }
+    let body: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| (Some(code), ("error", FailureCategory::Other, format!("Invalid lag summary: {}", e))))?;
+    let lag = brokers::parse_kafka_lag(&body)
+        .map_err(|message| (Some(code), ("error", FailureCategory::Other, message)))?;
+    brokers::check_lag(lag, max_lag)
+        .map_err(|message| (Some(code), ("failure", FailureCategory::AssertionFailed, message)))?;
+    Ok((Some(code), json!({ "cluster_id": cluster_id, "group": group, "lag": lag })))
+}
+
+/// Just enough of the NATS client protocol for a probe or a request-reply.
+struct NatsConnection {
+    stream: BufReader<TcpStream>,
+}
+
+impl NatsConnection {
+    async fn connect(url: &Url) -> Result<Self> {
+        let host = url.host_str().ok_or_else(|| Error::validation("NATS URL has no host"))?;
+        let tcp = TcpStream::connect((host, url.port().unwrap_or(NATS_DEFAULT_PORT))).await?;
+        let mut connection = Self {
+            stream: BufReader::new(tcp),
+        };
+
+        let line = connection.read_line().await?;
+        let info: serde_json::Value = line
+            .strip_prefix("INFO ")
+            .and_then(|info| serde_json::from_str(info).ok())
+            .ok_or_else(|| Error::internal(format!("Expected INFO from the server, got: {}", line)))?;
+        if info.get("tls_required").and_then(|v| v.as_bool()) == Some(true) {
+            return Err(Error::internal("The server requires TLS, which NATS checks do not support"));
+        }
+
+        let connect = format!("CONNECT {}\r\nPING\r\n", brokers::connect_options(url));
+        connection.write(connect.as_bytes()).await?;
+        // The PONG confirms the server accepted CONNECT
+        loop {
+            let line = connection.read_line().await?;
+            match line.as_str() {
+                "PONG" => return Ok(connection),
+                "PING" => connection.write(b"PONG\r\n").await?,
+                _ if line.starts_with("-ERR") => return Err(Error::auth(format!("NATS server: {}", line))),
+                _ => {}
+            }
+        }
+    }
+
+    async fn read_line(&mut self) -> Result<String> {
+        let mut line = String::new();
+        if self.stream.read_line(&mut line).await? == 0 {
+            return Err(Error::internal("The NATS server closed the connection"));
+        }
+        Ok(line.trim_end().to_string())
+    }
+
+    async fn write(&mut self, data: &[u8]) -> Result<()> {
+        self.stream.get_mut().write_all(data).await?;
+        Ok(())
+    }
+
+    async fn subscribe(&mut self, subject: &str, sid: &str) -> Result<()> {
+        self.write(format!("SUB {} {}\r\n", subject, sid).as_bytes()).await
+    }
+
+    async fn publish(&mut self, subject: &str, reply_to: Option<&str>, payload: &[u8]) -> Result<()> {
+        let mut message = match reply_to {
+            Some(reply_to) => format!("PUB {} {} {}\r\n", subject, reply_to, payload.len()),
+            None => format!("PUB {} {}\r\n", subject, payload.len()),
+        }
+        .into_bytes();
+        message.extend_from_slice(payload);
+        message.extend_from_slice(b"\r\n");
+        self.write(&message).await
+    }
+
+    /// Payload of the next message for `sid`, answering server pings meanwhile.
+    async fn next_message(&mut self, sid: &str) -> Result<Vec<u8>> {
+        loop {
+            let line = self.read_line().await?;
+            if line == "PING" {
+                self.write(b"PONG\r\n").await?;
+                continue;
+            }
+            if line.starts_with("-ERR") {
+                return Err(Error::internal(format!("NATS server: {}", line)));
+            }
+            let Some(header) = brokers::parse_message_header(&line) else {
+                continue;
+            };
+            // The payload is followed by CRLF
+            let mut payload = vec![0; header.size + 2];
+            self.stream.read_exact(&mut payload).await?;
+            payload.truncate(header.size);
+            if header.sid == sid {
+                return Ok(payload);
+            }
+        }
+    }
+}
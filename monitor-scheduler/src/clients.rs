@@ -0,0 +1,159 @@
+use monitor_core::{
+    config::{DnsConfig, HttpClientConfig},
+    failures::FailureCategory,
+    metrics,
+    models::Monitor,
+};
+use reqwest::{header::HeaderMap, Client, Url};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tracing::warn;
+
+use crate::dns::CheckResolver;
+
+const METRIC_HOSTS: &str = "monitor_scheduler_http_client_hosts";
+
+/// Monitors with the same origin share a client when these settings match too.
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct ClientKey {
+    origin: String,
+    https: bool,
+    bypass_dns_cache: bool,
+    connect_timeout: Duration,
+    tls_timeout: Duration,
+}
+
+/// One reqwest client per target origin, so each host gets its own connection
+/// pool and keep-alive connections are reused across checks instead of one
+/// busy or slow target holding up the shared pool. Clients for monitors that
+/// bypass the DNS cache or set their own connect limits are kept separately.
+#[derive(Clone)]
+pub struct HostClients {
+    clients: Arc<Mutex<HashMap<ClientKey, (Client, Instant)>>>,
+    config: HttpClientConfig,
+    resolver: CheckResolver,
+    /// Used for URLs without a host and when building a client fails
+    fallback: Client,
+}
+
+impl HostClients {
+    pub fn new(config: &HttpClientConfig, dns: &DnsConfig) -> Self {
+        metrics::global().describe(METRIC_HOSTS, "Target hosts with a pooled HTTP client");
+        Self {
+            clients: Arc::new(Mutex::new(HashMap::new())),
+            config: config.clone(),
+            resolver: CheckResolver::new(dns),
+            fallback: Client::new(),
+        }
+    }
+
+    pub fn resolver(&self) -> &CheckResolver {
+        &self.resolver
+    }
+
+    pub fn for_url(&self, url: &str, monitor: &Monitor) -> Client {
+        let Some((origin, https)) = origin(url) else {
+            return self.fallback.clone();
+        };
+        let key = ClientKey {
+            origin,
+            https,
+            bypass_dns_cache: monitor.bypass_dns_cache,
+            connect_timeout: phase_timeout(monitor.connect_timeout_ms, self.config.connect_timeout_ms),
+            tls_timeout: phase_timeout(monitor.tls_timeout_ms, self.config.tls_timeout_ms),
+        };
+
+        let mut clients = self.clients.lock().unwrap();
+        if let Some((client, used)) = clients.get_mut(&key) {
+            *used = Instant::now();
+            return client.clone();
+        }
+
+        let client = match self.build(&key) {
+            Ok(client) => client,
+            Err(e) => {
+                warn!("Failed to build HTTP client for {}: {}", key.origin, e);
+                return self.fallback.clone();
+            }
+        };
+        if clients.len() >= self.config.max_hosts.max(1)
+            && let Some(oldest) = clients.iter().min_by_key(|(_, (_, used))| *used).map(|(k, _)| k.clone())
+        {
+            clients.remove(&oldest);
+        }
+        clients.insert(key, (client.clone(), Instant::now()));
+        metrics::global().set_gauge(METRIC_HOSTS, &[], clients.len() as f64);
+        client
+    }
+
+    fn build(&self, key: &ClientKey) -> reqwest::Result<Client> {
+        let resolver = if key.bypass_dns_cache {
+            self.resolver.uncached()
+        } else {
+            self.resolver.clone()
+        };
+        // reqwest times TCP connect and TLS handshake as one connection phase,
+        // so an HTTPS connection gets both budgets; the request as a whole is
+        // still bounded by the monitor's `timeout`
+        let connect_timeout = if key.https {
+            key.connect_timeout + key.tls_timeout
+        } else {
+            key.connect_timeout
+        };
+        Client::builder()
+            .dns_resolver(Arc::new(resolver))
+            .connect_timeout(connect_timeout)
+            .pool_max_idle_per_host(self.config.pool_max_idle_per_host)
+            .pool_idle_timeout(Duration::from_secs(self.config.pool_idle_timeout_secs))
+            .tcp_keepalive(Duration::from_secs(self.config.tcp_keepalive_secs))
+            .tcp_nodelay(true)
+            .build()
+    }
+}
+
+/// `scheme://host:port`, with the scheme's default port filled in, and whether
+/// the connection uses TLS.
+fn origin(url: &str) -> Option<(String, bool)> {
+    let url = Url::parse(url).ok()?;
+    let host = url.host_str()?;
+    let origin = format!("{}://{}:{}", url.scheme(), host, url.port_or_known_default()?);
+    Some((origin, url.scheme() == "https"))
+}
+
+fn phase_timeout(monitor_ms: Option<i32>, default_ms: u64) -> Duration {
+    Duration::from_millis(monitor_ms.filter(|ms| *ms > 0).map(|ms| ms as u64).unwrap_or(default_ms))
+}
+
+/// Result status, failure category and message for a request that failed
+/// before a response. Connection-phase timeouts are reported as `timeout` like
+/// whole-request ones.
+pub fn describe_error(error: &reqwest::Error) -> (&'static str, FailureCategory, String) {
+    if error.is_redirect() {
+        ("error", FailureCategory::TooManyRedirects, error.to_string())
+    } else if error.is_connect() && error.is_timeout() {
+        ("timeout", FailureCategory::ConnectTimeout, format!("Connect timeout: {}", error))
+    } else if error.is_timeout() {
+        ("timeout", FailureCategory::RequestTimeout, format!("Request timeout: {}", error))
+    } else if error.is_connect() {
+        let mut chain = Vec::new();
+        let mut source: Option<&dyn std::error::Error> = Some(error);
+        while let Some(e) = source {
+            chain.push(e.to_string());
+            source = e.source();
+        }
+        ("error", FailureCategory::for_connect_error(&chain), chain.join(": "))
+    } else {
+        ("error", FailureCategory::Other, error.to_string())
+    }
+}
+
+/// Response headers as strings; values that are not valid UTF-8 are dropped.
+pub fn response_headers(headers: &HeaderMap) -> BTreeMap<String, String> {
+    headers
+        .iter()
+        .filter_map(|(name, value)| Some((name.as_str().to_string(), value.to_str().ok()?.to_string())))
+        .collect()
+}
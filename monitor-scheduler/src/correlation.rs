@@ -0,0 +1,102 @@
+use chrono::{Duration, Utc};
+use monitor_core::{
+    config::CorrelationConfig,
+    correlation::{self, DownMonitor, ANALYZER_LOCK_KEY},
+    crypto::KeyRing,
+    db::DatabasePool,
+    locks,
+    maintenance,
+    Result,
+};
+use reqwest::{Client, Url};
+use std::net::IpAddr;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::{dns::CheckResolver, webhooks};
+
+/// Opens a correlated incident when enough monitors sharing a tag or network
+/// block go down within the configured window, adds monitors that fail later
+/// to it, and resolves it once fewer than the threshold are still down. Each
+/// transition sends one notification instead of one per monitor.
+pub async fn analyze(
+    db: &DatabasePool,
+    client: &Client,
+    keys: &KeyRing,
+    resolver: &CheckResolver,
+    config: &CorrelationConfig,
+) -> Result<()> {
+    // Held until the end of the run; other scheduler processes skip this one
+    let Some(_lock) = locks::try_lock(db, ANALYZER_LOCK_KEY).await? else {
+        return Ok(());
+    };
+
+    let now = Utc::now();
+    let mut down = Vec::new();
+    for mut monitor in correlation::down_monitors(db).await? {
+        // Expected outages during provider maintenance are not incidents
+        if maintenance::in_maintenance(db, monitor.monitor_id, &monitor.tags, now).await? {
+            continue;
+        }
+        monitor.network = target_ip(resolver, &monitor.endpoint).await.map(correlation::network_block);
+        down.push(monitor);
+    }
+
+    let open = correlation::open_incidents(db).await?;
+    for incident in &open {
+        let members: Vec<&DownMonitor> = down.iter().filter(|m| m.belongs_to(&incident.kind, &incident.group_key)).collect();
+        if members.len() < config.min_monitors {
+            correlation::resolve(db, incident.id, now).await?;
+            info!("Correlated incident {} {} resolved", incident.kind, incident.group_key);
+            notify(db, client, keys, "incident.resolved", incident, &members).await;
+        } else {
+            let ids: Vec<Uuid> = members.iter().map(|m| m.monitor_id).collect();
+            correlation::touch(db, incident.id, &ids, now).await?;
+        }
+    }
+
+    let since = now - Duration::seconds(config.window_secs);
+    for group in correlation::correlate(&down, since, config.min_monitors) {
+        let covered = open.iter().any(|incident| {
+            (incident.kind == group.kind && incident.group_key == group.key)
+                || group.monitor_ids.iter().all(|id| incident.monitor_ids.contains(id))
+        });
+        if covered {
+            continue;
+        }
+
+        let incident = correlation::open(db, &group, now).await?;
+        let members: Vec<&DownMonitor> = down.iter().filter(|m| group.monitor_ids.contains(&m.monitor_id)).collect();
+        warn!(
+            "{} monitors sharing {} {} went down together, opened correlated incident {}",
+            members.len(),
+            group.kind,
+            group.key,
+            incident.id
+        );
+        notify(db, client, keys, "incident.correlated", &incident, &members).await;
+    }
+    Ok(())
+}
+
+async fn notify(
+    db: &DatabasePool,
+    client: &Client,
+    keys: &KeyRing,
+    event: &str,
+    incident: &correlation::CorrelatedIncident,
+    members: &[&DownMonitor],
+) {
+    if let Err(e) = webhooks::deliver_incident(db, client, keys, event, incident, members).await {
+        warn!("Failed to deliver {} for {}: {}", event, incident.id, e);
+    }
+}
+
+async fn target_ip(resolver: &CheckResolver, endpoint: &str) -> Option<IpAddr> {
+    let url = Url::parse(endpoint).ok()?;
+    let host = url.host_str()?.trim_start_matches('[').trim_end_matches(']');
+    match host.parse() {
+        Ok(ip) => Some(ip),
+        Err(_) => resolver.lookup(host).await,
+    }
+}
@@ -0,0 +1,170 @@
+use monitor_core::{
+    databases::{self, DatabaseCheck, Engine},
+    durations::Seconds,
+    failures::FailureCategory,
+    models::{Monitor, MonitorResult},
+};
+use redis::{ConnectionInfo, IntoConnectionInfo};
+use reqwest::Url;
+use serde_json::{json, Value};
+use sqlx::{
+    mysql::{MySql, MySqlConnectOptions},
+    postgres::{PgConnectOptions, Postgres},
+    database::HasStatementCache,
+    ConnectOptions, Connection, Database, Executor, IntoArguments,
+};
+use std::{collections::HashMap, str::FromStr, time::Instant};
+
+use crate::{
+    brokers::{self, Failure, Outcome},
+    tls::within,
+};
+
+type Step<T> = Result<T, (Option<i32>, Failure)>;
+
+/// Connects to the database server at the monitor's endpoint and runs the
+/// check's query, or `PING` on Redis. Connect and query times are kept in
+/// the result body.
+pub async fn run(
+    monitor: &Monitor,
+    check: &serde_json::Value,
+    credentials: &HashMap<String, String>,
+    timeout: Seconds,
+) -> MonitorResult {
+    let start_time = Instant::now();
+    let outcome = match serde_json::from_value::<DatabaseCheck>(check.clone()) {
+        Ok(check) => brokers::with_timeout(timeout, execute(monitor, &check, credentials)).await,
+        Err(e) => Err((None, ("error", FailureCategory::Other, format!("Invalid database check: {}", e)))),
+    };
+    brokers::into_result(monitor, start_time, outcome)
+}
+
+async fn execute(monitor: &Monitor, check: &DatabaseCheck, credentials: &HashMap<String, String>) -> Outcome {
+    let failed = |message: String| (None, ("error", FailureCategory::Other, message));
+    let url = databases::parse(&monitor.endpoint).map_err(|e| failed(e.to_string()))?;
+    let engine = databases::engine(&url).map_err(|e| failed(e.to_string()))?;
+    let (username, password) = databases::credentials(credentials);
+    let host = url.host_str().unwrap_or_default();
+    let server = match url.port() {
+        Some(port) => format!("{}:{}", host, port),
+        None => host.to_string(),
+    };
+
+    let (connect_ms, query_ms, mut body) = match engine {
+        Engine::Postgres => {
+            let mut options = PgConnectOptions::from_str(url.as_str())
+                .map_err(|e| failed(format!("Invalid database endpoint: {}", e)))?
+                .application_name("monitor")
+                .disable_statement_logging();
+            if let Some(username) = &username {
+                options = options.username(username);
+            }
+            if let Some(password) = &password {
+                options = options.password(password);
+            }
+            sql::<Postgres>(monitor, options, check.query(), &server).await?
+        }
+        Engine::Mysql => {
+            let mut options = MySqlConnectOptions::from_str(url.as_str())
+                .map_err(|e| failed(format!("Invalid database endpoint: {}", e)))?
+                .disable_statement_logging();
+            if let Some(username) = &username {
+                options = options.username(username);
+            }
+            if let Some(password) = &password {
+                options = options.password(password);
+            }
+            sql::<MySql>(monitor, options, check.query(), &server).await?
+        }
+        Engine::Redis => redis_ping(monitor, &url, username, password, &server).await?,
+    };
+    body["engine"] = json!(engine);
+    body["connect_ms"] = json!(connect_ms);
+    body["query_ms"] = json!(query_ms);
+    Ok((None, body))
+}
+
+/// Connects with `options` and runs `query`, returning the connect and
+/// query times and whether the query returned a row.
+async fn sql<DB>(
+    monitor: &Monitor,
+    options: <DB::Connection as Connection>::Options,
+    query: &str,
+    server: &str,
+) -> Step<(i64, i64, Value)>
+where
+    DB: Database + HasStatementCache,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+{
+    let started = Instant::now();
+    let mut connection = within(monitor.connect_timeout_ms, "Connect", options.connect())
+        .await?
+        .map_err(|e| connect_failure(server, e))?;
+    let connect_ms = started.elapsed().as_millis() as i64;
+
+    let started = Instant::now();
+    let row = sqlx::query(query).persistent(false).fetch_optional(&mut connection).await.map_err(|e| {
+        (None, ("failure", FailureCategory::Other, format!("the query failed: {}", e)))
+    })?;
+    let query_ms = started.elapsed().as_millis() as i64;
+    // Closing politely is best effort; the check already passed
+    let _ = connection.close().await;
+    Ok((connect_ms, query_ms, json!({ "returned_row": row.is_some() })))
+}
+
+fn connect_failure(server: &str, e: sqlx::Error) -> (Option<i32>, Failure) {
+    match e {
+        // The server answered but refused the login or the database
+        sqlx::Error::Database(e) => {
+            (None, ("failure", FailureCategory::Other, format!("{} refused the connection: {}", server, e)))
+        }
+        e => {
+            let message = format!("Could not connect to {}: {}", server, e);
+            (None, ("error", FailureCategory::for_connect_error(&[&message]), message))
+        }
+    }
+}
+
+async fn redis_ping(
+    monitor: &Monitor,
+    url: &Url,
+    username: Option<String>,
+    password: Option<String>,
+    server: &str,
+) -> Step<(i64, i64, Value)> {
+    let failed = |message: String| (None, ("error", FailureCategory::Other, message));
+    let mut info: ConnectionInfo = url
+        .as_str()
+        .into_connection_info()
+        .map_err(|e| failed(format!("Invalid database endpoint: {}", e)))?;
+    if username.is_some() {
+        info.redis.username = username;
+    }
+    if password.is_some() {
+        info.redis.password = password;
+    }
+    let client = redis::Client::open(info).map_err(|e| failed(format!("Invalid database endpoint: {}", e)))?;
+
+    let started = Instant::now();
+    let mut connection = within(monitor.connect_timeout_ms, "Connect", client.get_multiplexed_async_connection())
+        .await?
+        .map_err(|e| redis_failure(server, e))?;
+    let connect_ms = started.elapsed().as_millis() as i64;
+
+    let started = Instant::now();
+    let reply: String = redis::cmd("PING")
+        .query_async(&mut connection)
+        .await
+        .map_err(|e| redis_failure(server, e))?;
+    let query_ms = started.elapsed().as_millis() as i64;
+    Ok((connect_ms, query_ms, json!({ "reply": reply })))
+}
+
+fn redis_failure(server: &str, e: redis::RedisError) -> (Option<i32>, Failure) {
+    if e.kind() == redis::ErrorKind::AuthenticationFailed || e.code().is_some() {
+        return (None, ("failure", FailureCategory::Other, format!("{} refused the connection: {}", server, e)));
+    }
+    let message = format!("Could not connect to {}: {}", server, e);
+    (None, ("error", FailureCategory::for_connect_error(&[&message]), message))
+}
@@ -0,0 +1,197 @@
+use hickory_resolver::{
+    config::{LookupIpStrategy, NameServerConfigGroup, ResolverConfig, ResolverOpts},
+    error::ResolveErrorKind,
+    proto::rr::{RData, RecordType as QueryType},
+    system_conf, TokioAsyncResolver,
+};
+use monitor_core::{
+    config::DnsConfig,
+    dns::{self, DnsCache, DnsCheck, RecordType},
+    durations::Seconds,
+    failures::FailureCategory,
+    metrics,
+    models::{Monitor, MonitorResult},
+};
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use serde_json::json;
+use std::{
+    net::{IpAddr, SocketAddr},
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tracing::warn;
+
+use crate::brokers::{self, Outcome};
+
+const METRIC_LOOKUPS: &str = "monitor_scheduler_dns_lookups_total";
+const METRIC_RESOLUTION: &str = "monitor_scheduler_dns_resolution_seconds";
+
+/// Resolver for check requests. Answers are cached for their TTL and shared by
+/// every check on this worker; monitors that watch for DNS changes use an
+/// [`uncached`](Self::uncached) copy that always asks the name servers.
+#[derive(Clone)]
+pub struct CheckResolver {
+    resolver: Arc<TokioAsyncResolver>,
+    cache: Option<Arc<Mutex<DnsCache>>>,
+}
+
+impl CheckResolver {
+    pub fn new(config: &DnsConfig) -> Self {
+        let registry = metrics::global();
+        registry.describe(METRIC_LOOKUPS, "Host name lookups for checks by outcome (hit, miss, bypass, error)");
+        registry.describe(METRIC_RESOLUTION, "Time spent resolving host names that were not cached");
+
+        let (resolver_config, mut options) = system_conf::read_system_conf().unwrap_or_else(|e| {
+            warn!("Failed to read system DNS configuration, using defaults: {}", e);
+            (ResolverConfig::default(), ResolverOpts::default())
+        });
+        // Caching happens in `DnsCache` so hits can be counted and bypassed
+        options.cache_size = 0;
+        // Both families are needed for Happy Eyeballs; the default only asks
+        // for AAAA records when there are no A records
+        options.ip_strategy = LookupIpStrategy::Ipv4AndIpv6;
+
+        Self {
+            resolver: Arc::new(TokioAsyncResolver::tokio(resolver_config, options)),
+            cache: Some(Arc::new(Mutex::new(DnsCache::new(
+                config.cache_size,
+                Duration::from_secs(config.min_ttl_secs),
+                Duration::from_secs(config.max_ttl_secs),
+            )))),
+        }
+    }
+
+    pub fn uncached(&self) -> Self {
+        Self {
+            resolver: self.resolver.clone(),
+            cache: None,
+        }
+    }
+
+    /// First address for `host` through the same cache checks use, or `None`
+    /// when it does not resolve.
+    pub async fn lookup(&self, host: &str) -> Option<IpAddr> {
+        let name = Name::from_str(host).ok()?;
+        let mut addrs = self.resolve(name).await.ok()?;
+        addrs.next().map(|addr| addr.ip())
+    }
+}
+
+impl Resolve for CheckResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let resolver = self.clone();
+        Box::pin(async move {
+            let host = name.as_str();
+            let registry = metrics::global();
+            if let Some(cache) = &resolver.cache
+                && let Some(addrs) = cache.lock().unwrap().get(host, Instant::now())
+            {
+                registry.increment_counter(METRIC_LOOKUPS, &[("result", "hit")]);
+                return Ok(to_addrs(addrs));
+            }
+
+            let started = Instant::now();
+            let lookup = match resolver.resolver.lookup_ip(host).await {
+                Ok(lookup) => lookup,
+                Err(e) => {
+                    registry.increment_counter(METRIC_LOOKUPS, &[("result", "error")]);
+                    return Err(e.into());
+                }
+            };
+            registry.observe_histogram(METRIC_RESOLUTION, &[], started.elapsed().as_secs_f64());
+
+            let addrs: Vec<_> = lookup.iter().collect();
+            match &resolver.cache {
+                Some(cache) => {
+                    registry.increment_counter(METRIC_LOOKUPS, &[("result", "miss")]);
+                    let now = Instant::now();
+                    let ttl = lookup.valid_until().saturating_duration_since(now);
+                    cache.lock().unwrap().insert(host, addrs.clone(), ttl, now);
+                }
+                None => registry.increment_counter(METRIC_LOOKUPS, &[("result", "bypass")]),
+            }
+            Ok(to_addrs(addrs))
+        })
+    }
+}
+
+/// reqwest fills in the port from the URL. IPv6 addresses go first: the
+/// connector tries the first address family and races the other one after a
+/// short delay (Happy Eyeballs), so a slow IPv6 path falls back to IPv4
+/// instead of timing out.
+fn to_addrs(mut addrs: Vec<IpAddr>) -> Addrs {
+    addrs.sort_by_key(|ip| ip.is_ipv4());
+    Box::new(addrs.into_iter().map(|ip| SocketAddr::new(ip, 0)))
+}
+
+/// Looks up the monitor's name without any cache and holds the answer to the
+/// check's expectations. `response_time` is the resolution time.
+pub async fn run(monitor: &Monitor, check: &serde_json::Value, timeout: Seconds) -> MonitorResult {
+    let start_time = Instant::now();
+    let outcome = match serde_json::from_value::<DnsCheck>(check.clone()) {
+        Ok(check) => brokers::with_timeout(timeout, execute(monitor, &check)).await,
+        Err(e) => Err((None, ("error", FailureCategory::Other, format!("Invalid DNS check: {}", e)))),
+    };
+    brokers::into_result(monitor, start_time, outcome)
+}
+
+async fn execute(monitor: &Monitor, check: &DnsCheck) -> Outcome {
+    let invalid = |e: monitor_core::Error| (None, ("error", FailureCategory::Other, e.to_string()));
+    let name = dns::query_name(&monitor.endpoint).map_err(invalid)?;
+    let (config, mut options) = match check.resolver_addr().map_err(invalid)? {
+        Some(addr) => {
+            let servers = NameServerConfigGroup::from_ips_clear(&[addr.ip()], addr.port(), true);
+            (ResolverConfig::from_parts(None, vec![], servers), ResolverOpts::default())
+        }
+        None => system_conf::read_system_conf().unwrap_or_else(|e| {
+            warn!("Failed to read system DNS configuration, using defaults: {}", e);
+            (ResolverConfig::default(), ResolverOpts::default())
+        }),
+    };
+    options.cache_size = 0;
+    let resolver = TokioAsyncResolver::tokio(config, options);
+
+    let query_type = match check.record_type {
+        RecordType::A => QueryType::A,
+        RecordType::Aaaa => QueryType::AAAA,
+        RecordType::Cname => QueryType::CNAME,
+        RecordType::Mx => QueryType::MX,
+        RecordType::Txt => QueryType::TXT,
+    };
+    let lookup = resolver.lookup(name.as_str(), query_type).await.map_err(|e| match e.kind() {
+        ResolveErrorKind::NoRecordsFound { response_code, .. } => {
+            let message = format!("No {} records for {} ({})", check.record_type.as_str(), name, response_code);
+            (None, ("failure", FailureCategory::DnsError, message))
+        }
+        _ => (None, ("error", FailureCategory::DnsError, format!("Could not resolve {}: {}", name, e))),
+    })?;
+
+    // CNAME chains followed on the way come back too
+    let records: Vec<_> = lookup.records().iter().filter(|record| record.record_type() == query_type).collect();
+    let answers: Vec<String> = records.iter().filter_map(|record| record.data().and_then(format_rdata)).collect();
+    let ttl = records.iter().map(|record| record.ttl()).min().unwrap_or(0);
+    let body = json!({
+        "record_type": check.record_type.as_str(),
+        "answers": answers,
+        "ttl": ttl,
+    });
+    check
+        .evaluate(&answers, ttl)
+        .map_err(|message| (None, ("failure", FailureCategory::AssertionFailed, message)))?;
+    Ok((None, body))
+}
+
+/// Record values as `DnsCheck::evaluate` compares them: names without the
+/// trailing dot, MX records as `preference exchange` and TXT strings joined.
+fn format_rdata(data: &RData) -> Option<String> {
+    let name = |name: &hickory_resolver::Name| name.to_string().trim_end_matches('.').to_string();
+    match data {
+        RData::A(a) => Some(a.to_string()),
+        RData::AAAA(aaaa) => Some(aaaa.to_string()),
+        RData::CNAME(cname) => Some(name(cname)),
+        RData::MX(mx) => Some(format!("{} {}", mx.preference(), name(mx.exchange()))),
+        RData::TXT(txt) => Some(txt.iter().map(|part| String::from_utf8_lossy(part)).collect()),
+        _ => None,
+    }
+}
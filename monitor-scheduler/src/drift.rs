@@ -0,0 +1,50 @@
+use monitor_core::{
+    config::SchedulerConfig,
+    drift::{DriftAlert, DriftTracker},
+    metrics,
+    models::Monitor,
+};
+use std::sync::{Arc, Mutex};
+use tracing::{info, warn};
+
+const METRIC_LAG: &str = "monitor_scheduler_check_lag_seconds";
+const METRIC_LAG_MEDIAN: &str = "monitor_scheduler_check_lag_median_seconds";
+
+/// Check start lag across every monitor on this worker.
+#[derive(Clone)]
+pub struct ScheduleDrift {
+    tracker: Arc<Mutex<DriftTracker>>,
+}
+
+impl ScheduleDrift {
+    pub fn new(config: &SchedulerConfig) -> Self {
+        let registry = metrics::global();
+        registry.describe(METRIC_LAG, "Delay between a check's scheduled tick and its start");
+        registry.describe(METRIC_LAG_MEDIAN, "Median check start delay over the drift window");
+        registry.register_buckets(METRIC_LAG, &[0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 2.0, 5.0, 10.0, 30.0]);
+        Self {
+            tracker: Arc::new(Mutex::new(DriftTracker::new(config.drift_window, config.drift_warn_ms))),
+        }
+    }
+
+    pub fn observe(&self, monitor: &Monitor, lag_ms: i64) {
+        let registry = metrics::global();
+        registry.observe_histogram(METRIC_LAG, &[("monitor", monitor.name.as_str())], lag_ms as f64 / 1000.0);
+
+        let mut tracker = self.tracker.lock().unwrap();
+        let alert = tracker.record(lag_ms);
+        if let Some(median) = tracker.median_ms() {
+            registry.set_gauge(METRIC_LAG_MEDIAN, &[], median as f64 / 1000.0);
+        }
+        match alert {
+            Some(DriftAlert::Sustained { median_ms }) => warn!(
+                "Checks are starting {}ms late on average; the scheduler worker may be overloaded",
+                median_ms
+            ),
+            Some(DriftAlert::Recovered { median_ms }) => {
+                info!("Check start lag back to {}ms", median_ms)
+            }
+            None => {}
+        }
+    }
+}
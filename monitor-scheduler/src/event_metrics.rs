@@ -0,0 +1,31 @@
+use monitor_core::{
+    events::EventBus,
+    metrics,
+    supervisor::{Backoff, RestartPolicy, Supervisor},
+};
+use tokio::sync::broadcast::error::RecvError;
+
+const METRIC_EVENTS: &str = "monitor_scheduler_events_total";
+
+/// Counts every event published on the bus by type until shutdown.
+pub fn spawn(supervisor: &Supervisor, events: &EventBus) {
+    metrics::global().describe(METRIC_EVENTS, "Events published by checks, alerts and notifications");
+    let events = events.clone();
+    supervisor.spawn("event_metrics", RestartPolicy::OnFailure(Backoff::default()), move |mut shutdown| {
+        let mut receiver = events.subscribe();
+        async move {
+            loop {
+                let received = tokio::select! {
+                    received = receiver.recv() => received,
+                    _ = shutdown.wait() => return Ok(()),
+                };
+                match received {
+                    Ok(event) => metrics::global().increment_counter(METRIC_EVENTS, &[("type", event.kind())]),
+                    // Counting is best effort; skipped events are simply not counted
+                    Err(RecvError::Lagged(_)) => {}
+                    Err(RecvError::Closed) => return Ok(()),
+                }
+            }
+        }
+    });
+}
@@ -0,0 +1,64 @@
+use monitor_core::{
+    durations::Seconds,
+    exec::{self, ExecCheck, Verdict},
+    failures::FailureCategory,
+    models::{Monitor, MonitorResult},
+};
+use std::{process::Stdio, time::Instant};
+use tokio::{io::AsyncReadExt, process::Command};
+
+use crate::brokers::{self, Outcome};
+
+/// Runs the monitor's allow-listed command and maps its exit code and output
+/// to the result. The process is killed when the timeout ends the check.
+pub async fn run(monitor: &Monitor, check: &serde_json::Value, commands: &str, timeout: Seconds) -> MonitorResult {
+    let start_time = Instant::now();
+    let outcome = match serde_json::from_value::<ExecCheck>(check.clone()) {
+        Ok(check) => brokers::with_timeout(timeout, execute(monitor, &check, commands)).await,
+        Err(e) => Err((None, ("error", FailureCategory::Other, format!("Invalid exec check: {}", e)))),
+    };
+    brokers::into_result(monitor, start_time, outcome)
+}
+
+async fn execute(monitor: &Monitor, check: &ExecCheck, commands: &str) -> Outcome {
+    let failed = |message: String| (None, ("error", FailureCategory::Other, message));
+    let name = exec::command_name(&monitor.endpoint).map_err(|e| failed(e.to_string()))?;
+    let program = exec::resolve(commands, name).map_err(|e| failed(e.to_string()))?;
+
+    // No shell and none of the scheduler's environment besides PATH
+    let mut command = Command::new(&program);
+    command
+        .args(&check.args)
+        .env_clear()
+        .env("LANG", "C")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true);
+    if let Some(path) = std::env::var_os("PATH") {
+        command.env("PATH", path);
+    }
+    let mut child = command
+        .spawn()
+        .map_err(|e| failed(format!("Could not run {}: {}", program, e)))?;
+
+    let mut stdout = Vec::new();
+    if let Some(pipe) = child.stdout.take() {
+        // Dropping the pipe after the limit stops a chatty command instead of blocking it
+        pipe.take(exec::MAX_OUTPUT_BYTES as u64)
+            .read_to_end(&mut stdout)
+            .await
+            .map_err(|e| failed(format!("Could not read the output of {}: {}", program, e)))?;
+    }
+    let status = child
+        .wait()
+        .await
+        .map_err(|e| failed(format!("Could not wait for {}: {}", program, e)))?;
+
+    let (body, verdict) = check.evaluate(status.code(), &String::from_utf8_lossy(&stdout));
+    match verdict {
+        Verdict::Pass => Ok((None, body)),
+        Verdict::Fail(message) => Err((None, ("failure", FailureCategory::AssertionFailed, message))),
+        Verdict::Error(message) => Err((None, ("error", FailureCategory::Other, message))),
+    }
+}
@@ -0,0 +1,47 @@
+use chrono::{DateTime, Utc};
+use monitor_core::{
+    db::DatabasePool,
+    durations::Seconds,
+    failures::FailureCategory,
+    heartbeats::{self, HeartbeatCheck},
+    models::{Monitor, MonitorResult},
+};
+use serde_json::json;
+use std::time::Instant;
+
+use crate::brokers::{self, Outcome};
+
+/// Checks when the monitor's job last pinged. Nothing goes out: the check
+/// fails once no ping arrived within the interval plus the grace period.
+pub async fn run(
+    db: &DatabasePool,
+    monitor: &Monitor,
+    check: &serde_json::Value,
+    timeout: Seconds,
+    now: DateTime<Utc>,
+) -> MonitorResult {
+    let start_time = Instant::now();
+    let outcome = match serde_json::from_value::<HeartbeatCheck>(check.clone()) {
+        Ok(check) => brokers::with_timeout(timeout, execute(db, monitor, &check, now)).await,
+        Err(e) => Err((None, ("error", FailureCategory::Other, format!("Invalid heartbeat check: {}", e)))),
+    };
+    brokers::into_result(monitor, start_time, outcome)
+}
+
+async fn execute(db: &DatabasePool, monitor: &Monitor, check: &HeartbeatCheck, now: DateTime<Utc>) -> Outcome {
+    let heartbeat = heartbeats::get(db, monitor.id)
+        .await
+        .map_err(|e| (None, ("error", FailureCategory::Other, format!("Could not load heartbeat: {}", e))))?;
+    let last_ping = heartbeat.as_ref().and_then(|h| h.last_ping_at);
+    // Until a token is issued, the clock starts when the monitor was created
+    let since = heartbeat.as_ref().map(|h| h.created_at).unwrap_or(monitor.created_at);
+    let body = json!({
+        "last_ping_at": last_ping,
+        "pings": heartbeat.as_ref().map(|h| h.pings).unwrap_or(0),
+        "deadline": check.deadline(monitor.interval, last_ping, since),
+    });
+    check
+        .evaluate(monitor.interval, last_ping, since, now)
+        .map_err(|message| (None, ("failure", FailureCategory::AssertionFailed, message)))?;
+    Ok((None, body))
+}
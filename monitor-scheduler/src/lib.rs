@@ -0,0 +1,27 @@
+pub mod alerting;
+pub mod brokers;
+pub mod clients;
+pub mod correlation;
+pub mod databases;
+pub mod dns;
+pub mod drift;
+pub mod event_metrics;
+pub mod exec;
+pub mod heartbeats;
+pub mod locks;
+pub mod mail;
+pub mod ntp;
+pub mod pre_request;
+pub mod queue_listener;
+pub mod result_sink;
+pub mod scheduler;
+pub mod scripting;
+pub mod smtp;
+pub mod snmp;
+pub mod storage;
+pub mod tcp;
+pub mod tls;
+pub mod transactions;
+pub mod validation;
+pub mod webhooks;
+pub mod websocket;
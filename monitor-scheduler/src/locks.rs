@@ -0,0 +1,93 @@
+use chrono::{Duration, Utc};
+use monitor_core::{
+    db::DatabasePool,
+    locks::{self, CheckLease},
+    metrics,
+    models::Monitor,
+};
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+};
+use tracing::warn;
+use uuid::Uuid;
+
+const METRIC_SKIPPED: &str = "monitor_scheduler_checks_skipped_total";
+
+/// Per-monitor execution locks. A monitor runs at most once at a time on this
+/// worker, and a lease row in `monitor_check_leases` extends that across workers
+/// without holding a database connection for the length of the check.
+#[derive(Clone)]
+pub struct CheckLocks {
+    running: Arc<Mutex<HashSet<Uuid>>>,
+    /// Owner recorded on this worker's leases
+    worker: String,
+}
+
+/// Held for the duration of a check; releases both locks on drop.
+pub struct CheckGuard {
+    running: Arc<Mutex<HashSet<Uuid>>>,
+    monitor_id: Uuid,
+    lease: Option<(DatabasePool, CheckLease)>,
+}
+
+impl Drop for CheckGuard {
+    fn drop(&mut self) {
+        self.running.lock().unwrap().remove(&self.monitor_id);
+        // A lease left behind expires, and this worker's next run takes it over
+        if let Some((db, lease)) = self.lease.take() {
+            tokio::spawn(async move {
+                if let Err(e) = locks::release_check(&db, &lease).await {
+                    warn!("Failed to release check lease for {}: {}", lease.monitor_id, e);
+                }
+            });
+        }
+    }
+}
+
+impl CheckLocks {
+    pub fn new(worker: &str) -> Self {
+        metrics::global().describe(METRIC_SKIPPED, "Scheduled checks skipped because the previous run was still in progress");
+        Self {
+            running: Arc::default(),
+            worker: worker.to_string(),
+        }
+    }
+
+    /// `None` when the previous run of this monitor has not finished; the skipped
+    /// run is recorded before returning. The lease expires after `lease` if this
+    /// worker stops before releasing it.
+    pub async fn acquire(&self, db: &DatabasePool, monitor: &Monitor, lease: Duration) -> Option<CheckGuard> {
+        if !self.running.lock().unwrap().insert(monitor.id) {
+            skip(db, monitor, locks::SKIP_LOCAL).await;
+            return None;
+        }
+        let lease = match locks::claim_check(db, monitor.id, &self.worker, Utc::now() + lease).await {
+            Ok(Some(lease)) => Some((db.clone(), lease)),
+            Ok(None) => {
+                self.running.lock().unwrap().remove(&monitor.id);
+                skip(db, monitor, locks::SKIP_REMOTE).await;
+                return None;
+            }
+            // Without the database the result sink is buffering anyway; keep
+            // checking with only the local lock rather than going dark
+            Err(e) => {
+                warn!("Could not claim check lease for {}: {}", monitor.name, e);
+                None
+            }
+        };
+        Some(CheckGuard {
+            running: self.running.clone(),
+            monitor_id: monitor.id,
+            lease,
+        })
+    }
+}
+
+async fn skip(db: &DatabasePool, monitor: &Monitor, reason: &str) {
+    warn!("Skipping check for {}: previous run still in progress ({})", monitor.name, reason);
+    metrics::global().increment_counter(METRIC_SKIPPED, &[("monitor", monitor.name.as_str()), ("reason", reason)]);
+    if let Err(e) = locks::record_skipped(db, monitor.id, reason, Utc::now()).await {
+        warn!("Failed to record skipped run for {}: {}", monitor.name, e);
+    }
+}
@@ -0,0 +1,283 @@
+use monitor_core::{
+    durations::Seconds,
+    failures::FailureCategory,
+    mail::{self, MailCheck, Protocol, Target},
+    models::{Monitor, MonitorResult},
+};
+use serde_json::{json, Value};
+use std::{collections::HashMap, sync::Arc, time::Instant};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader},
+    net::TcpStream,
+};
+use tokio_rustls::{
+    client::TlsStream,
+    rustls::{self, pki_types::ServerName, ClientConfig, RootCertStore},
+    TlsConnector,
+};
+
+use crate::{
+    brokers::{self, Failure, Outcome},
+    tls::within,
+};
+
+type Step<T> = Result<T, (Option<i32>, Failure)>;
+
+/// Connects to the monitor's SMTP or IMAP server, reads the greeting and
+/// completes `EHLO` or `CAPABILITY`, upgrading with STARTTLS and logging in
+/// when the check asks to. Connect and banner times are kept in the result
+/// body.
+pub async fn run(
+    monitor: &Monitor,
+    check: &serde_json::Value,
+    credentials: &HashMap<String, String>,
+    timeout: Seconds,
+) -> MonitorResult {
+    let start_time = Instant::now();
+    let outcome = match serde_json::from_value::<MailCheck>(check.clone()) {
+        Ok(check) => brokers::with_timeout(timeout, execute(monitor, &check, credentials)).await,
+        Err(e) => Err((None, ("error", FailureCategory::Other, format!("Invalid mail check: {}", e)))),
+    };
+    brokers::into_result(monitor, start_time, outcome)
+}
+
+async fn execute(monitor: &Monitor, check: &MailCheck, credentials: &HashMap<String, String>) -> Outcome {
+    let failed = |message: String| (None, ("error", FailureCategory::Other, message));
+    let target = mail::target(&monitor.endpoint).map_err(|e| failed(e.to_string()))?;
+    let login = check
+        .authenticate
+        .then(|| mail::credentials(credentials))
+        .transpose()
+        .map_err(|e| failed(e.to_string()))?;
+
+    let started = Instant::now();
+    let tcp = within(monitor.connect_timeout_ms, "Connect", TcpStream::connect((target.host.as_str(), target.port)))
+        .await?
+        .map_err(|e| {
+            let message = format!("Could not connect to {}:{}: {}", target.host, target.port, e);
+            (None, ("error", FailureCategory::for_connect_error(&[&message]), message))
+        })?;
+    let mut body = json!({
+        "protocol": target.protocol,
+        "connect_ms": started.elapsed().as_millis() as i64,
+        "tls": target.implicit_tls || check.starttls,
+    });
+
+    if target.implicit_tls {
+        let mut stream = BufReader::new(handshake(monitor, &target, tcp).await?);
+        greet(&mut stream, &target, check, started, &mut body).await?;
+        return session(&mut stream, &target, check, login, started, body).await;
+    }
+    let mut stream = BufReader::new(tcp);
+    greet(&mut stream, &target, check, started, &mut body).await?;
+    if !check.starttls {
+        return session(&mut stream, &target, check, login, started, body).await;
+    }
+    start_tls(&mut stream, &target, check).await?;
+    let mut stream = BufReader::new(handshake(monitor, &target, stream.into_inner()).await?);
+    session(&mut stream, &target, check, login, started, body).await
+}
+
+/// Reads the greeting into `body` and checks it for the expected banner.
+async fn greet<S: AsyncRead + Unpin>(
+    stream: &mut BufReader<S>,
+    target: &Target,
+    check: &MailCheck,
+    started: Instant,
+    body: &mut Value,
+) -> Step<()> {
+    let (banner, accepted) = match target.protocol {
+        Protocol::Smtp => {
+            let (code, lines) = smtp_reply(stream).await?;
+            (lines.join("\n"), code == 220)
+        }
+        Protocol::Imap => {
+            let line = read_line(stream).await?;
+            let accepted = mail::imap_greeting_ok(&line);
+            (line, accepted)
+        }
+    };
+    body["banner_ms"] = json!(started.elapsed().as_millis() as i64);
+    body["banner"] = json!(mail::quote(&banner));
+    if !accepted {
+        let message = format!("the server refused the session: '{}'", mail::quote(&banner));
+        return Err((None, ("failure", FailureCategory::ConnectError, message)));
+    }
+    check
+        .evaluate(&banner)
+        .map_err(|message| (None, ("failure", FailureCategory::AssertionFailed, message)))
+}
+
+/// Asks a plain connection to switch to TLS, after checking the server
+/// offers it.
+async fn start_tls<S: AsyncRead + AsyncWrite + Unpin>(
+    stream: &mut BufReader<S>,
+    target: &Target,
+    check: &MailCheck,
+) -> Step<()> {
+    let offered = match target.protocol {
+        Protocol::Smtp => {
+            let lines = smtp_command(stream, &format!("EHLO {}", check.helo_name()), 250).await?;
+            mail::smtp_extensions(&lines).contains(&"STARTTLS".to_string())
+        }
+        Protocol::Imap => imap_capabilities(stream, "a1").await?.contains(&"STARTTLS".to_string()),
+    };
+    if !offered {
+        let message = "the server does not offer STARTTLS".to_string();
+        return Err((None, ("failure", FailureCategory::TlsError, message)));
+    }
+    match target.protocol {
+        Protocol::Smtp => smtp_command(stream, "STARTTLS", 220).await.map(drop),
+        Protocol::Imap => imap_command(stream, "a2", "STARTTLS").await.map(drop),
+    }
+}
+
+/// `EHLO` or `CAPABILITY` on the established, possibly encrypted,
+/// connection, then the login if asked for. Leaving politely is best effort.
+async fn session<S: AsyncRead + AsyncWrite + Unpin>(
+    stream: &mut BufReader<S>,
+    target: &Target,
+    check: &MailCheck,
+    login: Option<(String, String)>,
+    started: Instant,
+    mut body: Value,
+) -> Outcome {
+    let rejected = |message: String| (None, ("failure", FailureCategory::Other, message));
+    match target.protocol {
+        Protocol::Smtp => {
+            let lines = smtp_command(stream, &format!("EHLO {}", check.helo_name()), 250).await?;
+            body["extensions"] = json!(mail::smtp_extensions(&lines));
+            if let Some((username, password)) = &login {
+                let command = format!("AUTH PLAIN {}", mail::auth_plain(username, password));
+                smtp_command(stream, &command, 235)
+                    .await
+                    .map_err(|(_, (_, _, message))| rejected(format!("authentication failed: {}", message)))?;
+            }
+            body["handshake_ms"] = json!(started.elapsed().as_millis() as i64);
+            let _ = smtp_command(stream, "QUIT", 221).await;
+        }
+        Protocol::Imap => {
+            body["capabilities"] = json!(imap_capabilities(stream, "a3").await?);
+            if let Some((username, password)) = &login {
+                let command = format!("LOGIN {} {}", mail::imap_quote(username), mail::imap_quote(password));
+                imap_command(stream, "a4", &command)
+                    .await
+                    .map_err(|(_, (_, _, message))| rejected(format!("authentication failed: {}", message)))?;
+            }
+            body["handshake_ms"] = json!(started.elapsed().as_millis() as i64);
+            let _ = imap_command(stream, "a5", "LOGOUT").await;
+        }
+    }
+    body["authenticated"] = json!(login.is_some());
+    Ok((None, body))
+}
+
+async fn handshake(monitor: &Monitor, target: &Target, tcp: TcpStream) -> Step<TlsStream<TcpStream>> {
+    let failed = |message: String| (None, ("error", FailureCategory::Other, message));
+    let server_name = ServerName::try_from(target.host.clone())
+        .map_err(|_| failed(format!("Invalid server name: {}", target.host)))?;
+    let connector = connector().map_err(|e| failed(e.to_string()))?;
+    within(monitor.tls_timeout_ms, "TLS handshake", connector.connect(server_name, tcp))
+        .await?
+        .map_err(|e| {
+            let message = format!("TLS handshake with {} failed: {}", target.host, e);
+            (None, ("error", FailureCategory::TlsError, message))
+        })
+}
+
+/// Sends an SMTP command and returns the lines of a reply with `expected`.
+async fn smtp_command<S: AsyncRead + AsyncWrite + Unpin>(
+    stream: &mut BufReader<S>,
+    line: &str,
+    expected: u16,
+) -> Step<Vec<String>> {
+    write_line(stream, line).await?;
+    let (code, lines) = smtp_reply(stream).await?;
+    if code != expected {
+        let verb = line.split_whitespace().next().unwrap_or_default();
+        let message = format!("the server answered {} with '{}'", verb, mail::quote(&lines.join(" ")));
+        return Err((None, ("failure", FailureCategory::Other, message)));
+    }
+    Ok(lines)
+}
+
+/// Reads a possibly multi-line SMTP reply.
+async fn smtp_reply<S: AsyncRead + Unpin>(stream: &mut BufReader<S>) -> Step<(u16, Vec<String>)> {
+    let mut lines = Vec::new();
+    while lines.len() < mail::MAX_REPLY_LINES {
+        let line = read_line(stream).await?;
+        let Some(reply) = mail::reply_line(&line) else {
+            let message = format!("not an SMTP reply: '{}'", mail::quote(&line));
+            return Err((None, ("failure", FailureCategory::Other, message)));
+        };
+        lines.push(line.trim_end().to_string());
+        if !reply.more {
+            return Ok((reply.code, lines));
+        }
+    }
+    Err((None, ("failure", FailureCategory::Other, "the SMTP reply has too many lines".to_string())))
+}
+
+/// Sends a tagged IMAP command and returns the untagged lines before its
+/// completion, failing on `NO` or `BAD`.
+async fn imap_command<S: AsyncRead + AsyncWrite + Unpin>(
+    stream: &mut BufReader<S>,
+    tag: &str,
+    command: &str,
+) -> Step<Vec<String>> {
+    write_line(stream, &format!("{} {}", tag, command)).await?;
+    let mut untagged = Vec::new();
+    while untagged.len() < mail::MAX_REPLY_LINES {
+        let line = read_line(stream).await?;
+        match mail::imap_completion(&line, tag) {
+            Some(Ok(())) => return Ok(untagged),
+            Some(Err(text)) => {
+                let verb = command.split_whitespace().next().unwrap_or_default();
+                let message = format!("the server answered {} with '{}'", verb, text);
+                return Err((None, ("failure", FailureCategory::Other, message)));
+            }
+            None => untagged.push(line),
+        }
+    }
+    Err((None, ("failure", FailureCategory::Other, "the IMAP response has too many lines".to_string())))
+}
+
+async fn imap_capabilities<S: AsyncRead + AsyncWrite + Unpin>(
+    stream: &mut BufReader<S>,
+    tag: &str,
+) -> Step<Vec<String>> {
+    let untagged = imap_command(stream, tag, "CAPABILITY").await?;
+    Ok(untagged.iter().find_map(|line| mail::imap_capabilities(line)).unwrap_or_default())
+}
+
+async fn write_line<S: AsyncRead + AsyncWrite + Unpin>(stream: &mut BufReader<S>, line: &str) -> Step<()> {
+    stream.get_mut().write_all(format!("{}\r\n", line).as_bytes()).await.map_err(lost)
+}
+
+/// One line, up to [`mail::MAX_LINE_BYTES`].
+async fn read_line<S: AsyncRead + Unpin>(stream: &mut BufReader<S>) -> Step<String> {
+    let mut line = String::new();
+    let read = (&mut *stream).take(mail::MAX_LINE_BYTES as u64).read_line(&mut line).await.map_err(lost)?;
+    if read == 0 {
+        return Err(lost(std::io::ErrorKind::UnexpectedEof.into()));
+    }
+    if !line.ends_with('\n') {
+        let message = format!("the server sent a line longer than {} bytes", mail::MAX_LINE_BYTES);
+        return Err((None, ("failure", FailureCategory::Other, message)));
+    }
+    Ok(line)
+}
+
+fn lost(e: std::io::Error) -> (Option<i32>, Failure) {
+    (None, ("error", FailureCategory::ConnectError, format!("Connection to the mail server failed: {}", e)))
+}
+
+fn connector() -> Result<TlsConnector, rustls::Error> {
+    let mut roots = RootCertStore::empty();
+    roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+    let config = ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+        .with_safe_default_protocol_versions()?
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    Ok(TlsConnector::from(Arc::new(config)))
+}
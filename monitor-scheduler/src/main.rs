@@ -1,19 +1,41 @@
 use monitor_core::{
+    cache::create_redis_pool,
+    clock,
     config::Config,
+    crypto::KeyRing,
     db::{create_pool, run_migrations},
+    doctor, error_reporting,
     logging,
+    reload::{self, LiveConfig},
+    runtime,
+    supervisor::Supervisor,
     Result,
 };
+use monitor_scheduler::scheduler;
 use tracing::info;
 
-mod scheduler;
+fn main() -> Result<()> {
+    runtime::build("monitor-scheduler", &runtime::from_env())?.block_on(run())
+}
 
-#[tokio::main]
-async fn main() -> Result<()> {
+async fn run() -> Result<()> {
     logging::init_logging();
+
+    if doctor::requested() {
+        // The scheduler only uses Redis with ENABLE_REDIS_EVENTS
+        let options = doctor::DoctorOptions {
+            check_redis: false,
+            ..Default::default()
+        };
+        let report = doctor::run(&options).await;
+        report.print("monitor-scheduler");
+        std::process::exit(if report.passed() { 0 } else { 1 });
+    }
     
     let config = Config::from_env()?;
     info!("Starting Monitor Scheduler with config: {:?}", config);
+    logging::set_filter(&config.logging.level)?;
+    let _reporting = error_reporting::init(&config.error_reporting, "monitor-scheduler", env!("CARGO_PKG_VERSION"));
 
     let db_pool = create_pool(&config.database).await?;
     info!("Database connection established");
@@ -21,10 +43,19 @@ async fn main() -> Result<()> {
     run_migrations(&db_pool).await?;
     info!("Database migrations completed");
 
-    let mut scheduler = scheduler::MonitorScheduler::new(db_pool).await?;
+    let keys = KeyRing::from_config(&config.encryption, &config.auth.jwt_secret)?;
+    let live_config = LiveConfig::new(config.clone());
+    reload::spawn_watcher(live_config.clone())?;
+    let redis = if config.features.enable_redis_events {
+        Some(create_redis_pool(&config.redis).await?)
+    } else {
+        None
+    };
+    let mut scheduler =
+        scheduler::MonitorScheduler::new(db_pool, live_config, keys, Supervisor::default(), clock::system()).await?;
     
     scheduler.start().await?;
-    scheduler.load_and_schedule_monitors().await?;
+    scheduler.start_queue_worker(redis).await?;
     
     info!("Monitor scheduler is running. Press Ctrl+C to stop.");
     
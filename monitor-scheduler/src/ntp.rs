@@ -0,0 +1,35 @@
+use monitor_core::{
+    durations::Seconds,
+    failures::FailureCategory,
+    models::{Monitor, MonitorResult},
+    ntp::{self, NtpCheck},
+};
+use serde_json::json;
+use std::time::{Duration, Instant};
+
+use crate::brokers::{self, Outcome};
+
+/// Queries the monitor's NTP server and holds it to the check's bounds. The
+/// measured offset is kept in the result body.
+pub async fn run(monitor: &Monitor, check: &serde_json::Value, timeout: Seconds) -> MonitorResult {
+    let start_time = Instant::now();
+    let outcome = match serde_json::from_value::<NtpCheck>(check.clone()) {
+        Ok(check) => brokers::with_timeout(timeout, execute(monitor, &check, timeout)).await,
+        Err(e) => Err((None, ("error", FailureCategory::Other, format!("Invalid NTP check: {}", e)))),
+    };
+    brokers::into_result(monitor, start_time, outcome)
+}
+
+async fn execute(monitor: &Monitor, check: &NtpCheck, timeout: Seconds) -> Outcome {
+    let address = ntp::server_address(&monitor.endpoint)
+        .map_err(|e| (None, ("error", FailureCategory::Other, e.to_string())))?;
+    // The outer timeout goes first, so an unanswered query is reported as one
+    let sample = ntp::query(&address, timeout.as_std() + Duration::from_secs(1))
+        .await
+        .map_err(|e| (None, ("error", FailureCategory::ConnectError, e.to_string())))?;
+    let body = json!(sample);
+    check
+        .evaluate(&sample)
+        .map_err(|message| (None, ("failure", FailureCategory::AssertionFailed, message)))?;
+    Ok((None, body))
+}
@@ -0,0 +1,56 @@
+use monitor_core::{models::Monitor, Error, Result};
+use monitor_scripting::models::{HttpRequestSpec, PreRequestChanges, PreRequestContext};
+use reqwest::RequestBuilder;
+
+use crate::{clients::HostClients, scripting::Scripting};
+
+/// Builds the HTTP request, running the monitor's pre-request script first when
+/// it has one. `scripting` is `None` when scripting is disabled, in which case
+/// monitors with a pre-request script fail instead of sending an unsigned request.
+pub async fn build_request(
+    clients: &HostClients,
+    monitor: &Monitor,
+    mut spec: HttpRequestSpec,
+    scripting: Option<&Scripting>,
+) -> Result<RequestBuilder> {
+    let mut query = Vec::new();
+
+    if let Some(script) = &monitor.pre_request_script {
+        let scripting = scripting
+            .ok_or_else(|| Error::script_execution("Scripting is disabled, pre-request script not run"))?;
+        let changes = run_script(monitor, script, &spec, scripting).await?;
+        spec.headers.extend(changes.headers);
+        if changes.body.is_some() {
+            spec.body = changes.body;
+        }
+        query.extend(changes.query);
+    }
+
+    // Picked after the script ran, since it may have changed the URL
+    let mut request = clients.for_url(&spec.url, monitor).request(spec.method.parse().unwrap_or(reqwest::Method::GET), &spec.url);
+    if !query.is_empty() {
+        request = request.query(&query);
+    }
+    for (key, value) in &spec.headers {
+        request = request.header(key, value);
+    }
+    if let Some(body) = spec.body {
+        request = request.body(body);
+    }
+    Ok(request)
+}
+
+/// The script runs on an engine checked out from the scheduler's pool.
+async fn run_script(
+    monitor: &Monitor,
+    script: &str,
+    spec: &HttpRequestSpec,
+    scripting: &Scripting,
+) -> Result<PreRequestChanges> {
+    let context = PreRequestContext {
+        monitor: serde_json::to_value(monitor)?,
+        request: spec.clone(),
+    };
+    let engine = scripting.engine().await?;
+    engine.execute_pre_request_script(script, &context).await
+}
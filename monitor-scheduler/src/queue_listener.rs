@@ -0,0 +1,87 @@
+use monitor_core::{
+    cache::{RedisPool, MONITOR_CHANGES_CHANNEL},
+    db::DatabasePool,
+    faults::{self, FaultInjector, FaultPoint},
+    queue,
+    supervisor::{Backoff, RestartPolicy, ShutdownSignal, Supervisor},
+    Error, Result,
+};
+use sqlx::postgres::PgListener;
+use std::{sync::Arc, time::Duration};
+use tokio_stream::StreamExt;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Applies monitor changes to the check queue as they are committed,
+/// reconnecting when the connection drops. The periodic `queue::sync` still
+/// catches anything missed while disconnected.
+pub fn spawn(supervisor: &Supervisor, db: DatabasePool) {
+    let policy = RestartPolicy::Always(Backoff::fixed(RECONNECT_DELAY));
+    supervisor.spawn("monitor_change_listener", policy, move |shutdown| {
+        let db = db.clone();
+        async move { listen(&db, shutdown).await }
+    });
+}
+
+/// Like `spawn`, for the changes the API publishes on Redis.
+pub fn spawn_redis(supervisor: &Supervisor, db: DatabasePool, redis: RedisPool, faults: Option<Arc<FaultInjector>>) {
+    let policy = RestartPolicy::Always(Backoff::fixed(RECONNECT_DELAY));
+    supervisor.spawn("redis_change_subscriber", policy, move |shutdown| {
+        let db = db.clone();
+        let redis = redis.clone();
+        let faults = faults.clone();
+        async move {
+            faults::inject(faults.as_deref(), FaultPoint::Redis).await?;
+            subscribe(&db, &redis, shutdown).await
+        }
+    });
+}
+
+async fn listen(db: &DatabasePool, mut shutdown: ShutdownSignal) -> Result<()> {
+    let mut listener = PgListener::connect_with(db).await?;
+    listener.listen(queue::CHANGE_CHANNEL).await?;
+    info!("Listening for monitor changes");
+
+    loop {
+        let notification = tokio::select! {
+            notification = listener.recv() => notification?,
+            _ = shutdown.wait() => return Ok(()),
+        };
+        apply(db, notification.payload()).await;
+    }
+}
+
+async fn subscribe(db: &DatabasePool, redis: &RedisPool, mut shutdown: ShutdownSignal) -> Result<()> {
+    let mut pubsub = redis.get_async_pubsub().await?;
+    pubsub.subscribe(MONITOR_CHANGES_CHANNEL).await?;
+    info!("Subscribed to monitor changes on Redis");
+
+    let mut messages = pubsub.into_on_message();
+    loop {
+        let message = tokio::select! {
+            message = messages.next() => message,
+            _ = shutdown.wait() => return Ok(()),
+        };
+        let Some(message) = message else {
+            return Err(Error::internal("Redis closed the subscription"));
+        };
+        if let Ok(payload) = message.get_payload::<String>() {
+            apply(db, &payload).await;
+        }
+    }
+}
+
+/// Syncs the monitor whose id is `payload`. Postgres and Redis both announce
+/// API changes, and syncing twice is harmless.
+async fn apply(db: &DatabasePool, payload: &str) {
+    let Ok(monitor_id) = payload.parse::<Uuid>() else {
+        return;
+    };
+    match queue::sync_monitor(db, monitor_id).await {
+        Ok(0) => {}
+        Ok(_) => info!("Queued monitor {}", monitor_id),
+        Err(e) => warn!("Failed to sync monitor {} into the check queue: {}", monitor_id, e),
+    }
+}
@@ -0,0 +1,260 @@
+use monitor_core::{
+    circuit_breaker::CircuitBreaker,
+    config::ResultBufferConfig,
+    db::DatabasePool,
+    faults::{self, FaultInjector, FaultPoint},
+    metrics,
+    models::MonitorResult,
+    repository,
+    Error, Result,
+};
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tokio::{fs, io::AsyncWriteExt};
+use tracing::{info, warn};
+
+const METRIC_SPILLED: &str = "monitor_scheduler_results_spilled_total";
+const METRIC_DROPPED: &str = "monitor_scheduler_results_dropped_total";
+const METRIC_REPLAYED: &str = "monitor_scheduler_results_replayed_total";
+const METRIC_DUPLICATES: &str = "monitor_scheduler_results_duplicate_total";
+const METRIC_SPILL_SIZE: &str = "monitor_scheduler_spill_buffer_size";
+const METRIC_BREAKER_STATE: &str = "monitor_scheduler_db_breaker_state";
+/// Postgres takes at most 65535 bind parameters per statement, 13 per result
+const MAX_BATCH_SIZE: usize = 5000;
+
+/// Writes check results to Postgres behind a circuit breaker. While the breaker is
+/// open, results are appended to a bounded JSON-lines spill file and replayed once
+/// the database accepts writes again.
+pub struct ResultSink {
+    db: DatabasePool,
+    breaker: Mutex<CircuitBreaker>,
+    spill_path: PathBuf,
+    capacity: usize,
+    batch_size: usize,
+    /// Number of results in the spill file; the lock also serialises file access
+    spilled: tokio::sync::Mutex<usize>,
+    faults: Option<Arc<FaultInjector>>,
+}
+
+impl ResultSink {
+    pub async fn new(
+        db: DatabasePool,
+        config: &ResultBufferConfig,
+        faults: Option<Arc<FaultInjector>>,
+    ) -> Result<Self> {
+        let registry = metrics::global();
+        registry.describe(METRIC_SPILLED, "Results buffered locally because the database was unavailable");
+        registry.describe(METRIC_DROPPED, "Results lost because the spill buffer was full");
+        registry.describe(METRIC_REPLAYED, "Buffered results written to the database after recovery");
+        registry.describe(METRIC_DUPLICATES, "Buffered results the database already had when replayed");
+        registry.describe(METRIC_SPILL_SIZE, "Results currently waiting in the spill buffer");
+        registry.describe(METRIC_BREAKER_STATE, "Results database circuit breaker (0 closed, 1 open, 2 half-open)");
+
+        let spill_path = PathBuf::from(&config.spill_path);
+        // Results spilled before a restart are replayed like any others
+        let existing = match fs::read_to_string(&spill_path).await {
+            Ok(contents) => contents.lines().filter(|l| !l.trim().is_empty()).count(),
+            Err(_) => 0,
+        };
+        if existing > 0 {
+            info!("{} buffered results waiting in {}", existing, spill_path.display());
+        }
+        registry.set_gauge(METRIC_SPILL_SIZE, &[], existing as f64);
+
+        Ok(Self {
+            db,
+            breaker: Mutex::new(CircuitBreaker::new(
+                config.failure_threshold,
+                Duration::from_secs(config.open_secs),
+            )),
+            spill_path,
+            capacity: config.capacity,
+            batch_size: config.batch_size.clamp(1, MAX_BATCH_SIZE),
+            spilled: tokio::sync::Mutex::new(existing),
+            faults,
+        })
+    }
+
+    pub async fn save(&self, result: &MonitorResult) -> Result<()> {
+        if !self.allow_request() {
+            return self.spill(result).await;
+        }
+
+        match self.insert(result).await {
+            Ok(_) => {
+                self.record(true);
+                Ok(())
+            }
+            Err(e) if e.is_rejected_data() => {
+                self.reject(result, &e);
+                Ok(())
+            }
+            Err(e) => {
+                warn!("Failed to save result for monitor {}: {}", result.monitor_id, e);
+                self.record(false);
+                self.spill(result).await
+            }
+        }
+    }
+
+    /// Writes buffered results back to the database, keeping whatever could not be written.
+    pub async fn replay(&self) -> Result<()> {
+        let mut spilled = self.spilled.lock().await;
+        if *spilled == 0 || !self.allow_request() {
+            return Ok(());
+        }
+
+        let contents = fs::read_to_string(&self.spill_path).await.unwrap_or_default();
+        let mut pending: Vec<&str> = contents.lines().filter(|l| !l.trim().is_empty()).collect();
+        let mut replayed = 0;
+        let mut duplicates = 0;
+        let mut rejected = 0;
+
+        while !pending.is_empty() {
+            let taken = pending.len().min(self.batch_size);
+            // Positions within the batch's lines, to keep the unwritten ones on failure
+            let mut positions = Vec::with_capacity(taken);
+            let mut batch = Vec::with_capacity(taken);
+            for (position, line) in pending[..taken].iter().enumerate() {
+                match serde_json::from_str::<MonitorResult>(line) {
+                    Ok(result) => {
+                        positions.push(position);
+                        batch.push(result);
+                    }
+                    Err(e) => {
+                        warn!("Discarding unreadable buffered result: {}", e);
+                        metrics::global().increment_counter(METRIC_DROPPED, &[("reason", "corrupt")]);
+                    }
+                }
+            }
+            let written = match self.insert_batch(&batch).await {
+                Ok(inserted) => {
+                    replayed += inserted as usize;
+                    // Written before the failure that spilled them; the lost reply hid them
+                    duplicates += batch.len() - inserted as usize;
+                    taken
+                }
+                // One bad row fails the whole statement, so find it row by row
+                Err(e) if e.is_rejected_data() => {
+                    let mut written = taken;
+                    for (position, result) in positions.iter().zip(&batch) {
+                        match self.insert(result).await {
+                            Ok(true) => replayed += 1,
+                            Ok(false) => duplicates += 1,
+                            Err(e) if e.is_rejected_data() => {
+                                self.reject(result, &e);
+                                rejected += 1;
+                            }
+                            Err(e) => {
+                                warn!("Replay of buffered results paused: {}", e);
+                                self.record(false);
+                                written = *position;
+                                break;
+                            }
+                        }
+                    }
+                    written
+                }
+                Err(e) => {
+                    warn!("Replay of buffered results paused: {}", e);
+                    self.record(false);
+                    0
+                }
+            };
+            pending.drain(..written);
+            if written < taken {
+                break;
+            }
+        }
+
+        if pending.is_empty() {
+            self.record(true);
+            let _ = fs::remove_file(&self.spill_path).await;
+        } else {
+            let mut rest = pending.join("\n");
+            rest.push('\n');
+            fs::write(&self.spill_path, rest).await?;
+        }
+
+        *spilled = pending.len();
+        let registry = metrics::global();
+        registry.add_counter(METRIC_REPLAYED, &[], replayed as f64);
+        registry.add_counter(METRIC_DUPLICATES, &[], duplicates as f64);
+        registry.set_gauge(METRIC_SPILL_SIZE, &[], *spilled as f64);
+        if replayed + duplicates + rejected > 0 {
+            info!(
+                "Replayed {} buffered results, skipped {} already stored, dropped {} rejected, {} remaining",
+                replayed, duplicates, rejected, *spilled
+            );
+        }
+        Ok(())
+    }
+
+    async fn insert(&self, result: &MonitorResult) -> Result<bool> {
+        faults::inject(self.faults.as_deref(), FaultPoint::DbWrite).await?;
+        repository::insert_result(&self.db, result).await
+    }
+
+    async fn insert_batch(&self, results: &[MonitorResult]) -> Result<u64> {
+        faults::inject(self.faults.as_deref(), FaultPoint::DbWrite).await?;
+        repository::insert_results(&self.db, results).await
+    }
+
+    /// Drops a result the database will never accept, such as one for a deleted
+    /// monitor. The database answered, so this counts as a success for the
+    /// breaker; a rejected half-open trial closes it rather than leaving it stuck.
+    fn reject(&self, result: &MonitorResult, error: &Error) {
+        warn!("Dropping result {} for monitor {}: {}", result.id, result.monitor_id, error);
+        metrics::global().increment_counter(METRIC_DROPPED, &[("reason", "rejected")]);
+        self.record(true);
+    }
+
+    fn allow_request(&self) -> bool {
+        let mut breaker = self.breaker.lock().unwrap();
+        let allowed = breaker.allow_request(Instant::now());
+        metrics::global().set_gauge(METRIC_BREAKER_STATE, &[], breaker.state().as_gauge());
+        allowed
+    }
+
+    fn record(&self, success: bool) {
+        let mut breaker = self.breaker.lock().unwrap();
+        if success {
+            breaker.record_success();
+        } else {
+            breaker.record_failure(Instant::now());
+        }
+        metrics::global().set_gauge(METRIC_BREAKER_STATE, &[], breaker.state().as_gauge());
+    }
+
+    async fn spill(&self, result: &MonitorResult) -> Result<()> {
+        let registry = metrics::global();
+        let mut spilled = self.spilled.lock().await;
+        if *spilled >= self.capacity {
+            registry.increment_counter(METRIC_DROPPED, &[("reason", "buffer_full")]);
+            warn!("Spill buffer full, dropping result for monitor {}", result.monitor_id);
+            return Ok(());
+        }
+
+        if let Some(dir) = self.spill_path.parent()
+            && !dir.as_os_str().is_empty()
+        {
+            fs::create_dir_all(dir).await?;
+        }
+        let mut line = serde_json::to_string(result)?;
+        line.push('\n');
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.spill_path)
+            .await?;
+        file.write_all(line.as_bytes()).await?;
+
+        *spilled += 1;
+        registry.increment_counter(METRIC_SPILLED, &[]);
+        registry.set_gauge(METRIC_SPILL_SIZE, &[], *spilled as f64);
+        Ok(())
+    }
+}
@@ -1,124 +1,390 @@
 use monitor_core::{
-    models::{Monitor, MonitorResult},
+    config::{SchedulerConfig, ScriptingConfig},
+    reload::LiveConfig,
+    cache::RedisPool,
+    crypto::KeyRing,
+    models::{Monitor, MonitorResult, MonitorType},
     db::DatabasePool,
+    body_match, build_info::{self, BuildInfo}, clock::{self, SharedClock}, durations::Seconds, error_reporting, events::{Event, EventBus}, evidence::{self, IncidentEvidence}, expirations, failures::{self, FailureCategory, HintContext}, faults::{self, FaultInjector, FaultPoint}, logging, maintenance, metrics, openmetrics, pause, queue::{self, QueuedCheck}, random, redaction::{LiveRedactor, Redactor}, repository, retention, rollups, runtime_settings, secrets, self_monitoring,
+    settings::{self, EffectiveSettings},
+    states::{self, MonitorState, StateEvent},
+    supervisor::{self, Backoff, RestartPolicy, ShutdownSignal, Supervisor},
     Error, Result,
 };
+use monitor_scripting::{
+    models::{HttpRequestSpec, ValidationContext, ValidationResult},
+    pool::ScriptEnginePool,
+};
 use reqwest::Client;
-use sqlx::Row;
-use std::time::Instant;
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+use tokio::{sync::Semaphore, time::MissedTickBehavior};
 use tokio_cron_scheduler::{Job, JobScheduler};
 use tracing::{error, info, warn};
 use uuid::Uuid;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
+
+use crate::{alerting::AlertManager, brokers, clients::{self, HostClients}, correlation, databases, dns, drift::ScheduleDrift, event_metrics, exec, heartbeats, locks::CheckLocks, mail, ntp, pre_request, queue_listener, result_sink::ResultSink, scripting::Scripting, snmp, storage, tcp, tls, transactions, validation, webhooks, websocket};
+
+const METRIC_CHECK_CRASHES: &str = "monitor_scheduler_check_crashes_total";
+
+/// How often the worker looks for due checks
+const QUEUE_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
 
 pub struct MonitorScheduler {
     db: DatabasePool,
     http_client: Client,
     scheduler: JobScheduler,
+    config: LiveConfig,
+    keys: KeyRing,
+    results: Arc<ResultSink>,
+    runner: CheckRunner,
+    supervisor: Supervisor,
+    /// Read instead of `Utc::now()` so tests can control time
+    clock: SharedClock,
+}
+
+/// Everything a claimed check needs to run; cloned into each check task.
+#[derive(Clone)]
+struct CheckRunner {
+    db: DatabasePool,
+    client: Client,
+    /// Clients for checked targets; `client` is for webhooks
+    targets: HostClients,
+    keys: KeyRing,
+    results: Arc<ResultSink>,
+    config: LiveConfig,
+    locks: CheckLocks,
+    drift: ScheduleDrift,
+    alerts: Arc<AlertManager>,
+    /// Applied to every result before it is stored or notified
+    redactor: LiveRedactor,
+    events: EventBus,
+    clock: SharedClock,
+    /// Makes a share of checks, result writes and Redis calls fail, for resilience tests
+    faults: Option<Arc<FaultInjector>>,
+    /// Shared by all checks; sized once at startup
+    engines: Arc<ScriptEnginePool>,
+    /// Identifies this process's claims in `monitor_check_queue`
+    worker: String,
+}
+
+/// Per-run options passed from the job to the check.
+struct CheckContext {
+    deliver_webhooks: bool,
+    scripting: Option<Scripting>,
+    /// Allow-list of commands exec checks may run
+    exec_commands: String,
+    schedule_lag_ms: Option<i32>,
+    alerts: Arc<AlertManager>,
+    redactor: Arc<Redactor>,
+    events: EventBus,
+    clock: SharedClock,
+    faults: Option<Arc<FaultInjector>>,
 }
 
 impl MonitorScheduler {
-    pub async fn new(db: DatabasePool) -> Result<Self> {
+    pub async fn new(
+        db: DatabasePool,
+        config: LiveConfig,
+        keys: KeyRing,
+        supervisor: Supervisor,
+        clock: SharedClock,
+    ) -> Result<Self> {
         let http_client = Client::new();
         let scheduler = JobScheduler::new()
             .await
             .map_err(|e| Error::scheduler(e.to_string()))?;
+        let faults = FaultInjector::from_config(&config.current());
+        let results = Arc::new(ResultSink::new(db.clone(), &config.current().result_buffer, faults.clone()).await?);
+        let events = EventBus::default();
+        event_metrics::spawn(&supervisor, &events);
+        let worker = format!("scheduler-{}", Uuid::new_v4());
+        let runner = CheckRunner {
+            db: db.clone(),
+            client: http_client.clone(),
+            targets: HostClients::new(&config.current().http_client, &config.current().dns),
+            keys: keys.clone(),
+            results: results.clone(),
+            config: config.clone(),
+            locks: CheckLocks::new(&worker),
+            drift: ScheduleDrift::new(&config.current().scheduler),
+            alerts: Arc::new(AlertManager::new(
+                db.clone(),
+                http_client.clone(),
+                keys.clone(),
+                config.clone(),
+                events.clone(),
+                clock.clone(),
+            )),
+            redactor: LiveRedactor::default(),
+            events,
+            clock: clock.clone(),
+            faults,
+            engines: Arc::new(ScriptEnginePool::new(config.current().scripting.pool_size)),
+            worker,
+        };
         
         Ok(Self {
             db,
             http_client,
             scheduler,
+            config,
+            keys,
+            results,
+            runner,
+            supervisor,
+            clock,
         })
     }
 
     pub async fn start(&mut self) -> Result<()> {
         info!("Starting monitor scheduler");
+        let features = self.config.current().features.clone();
+        info!("Enabled features: {:?}", features);
+        if !features.enable_webhooks {
+            info!("Webhooks are disabled, results will not be delivered");
+        }
         
-        let job = Job::new_async("0/30 * * * * *", |_uuid, _l| {
+        let db = self.db.clone();
+        let clock = self.clock.clone();
+        let rollup_job = Job::new_async("0 5 * * * *", move |_uuid, _l| {
+            let db = db.clone();
+            let now = clock.now();
+            Box::pin(async move {
+                match rollups::rollup_completed_hours(&db, now).await {
+                    Ok(rows) => info!("Rolled up {} monitor-hours of results", rows),
+                    Err(e) => {
+                        error!("Result rollup failed: {}", e);
+                        error_reporting::capture_error(&e, "scheduler.rollups");
+                    }
+                }
+            })
+        })
+        .map_err(|e| Error::scheduler(e.to_string()))?;
+        self.scheduler.add(rollup_job).await
+            .map_err(|e| Error::scheduler(e.to_string()))?;
+
+        publish_clock_status(&self.db, &self.config).await;
+        publish_build_info(&self.db, &self.config).await;
+        if self.config.current().self_monitoring.enabled
+            && let Err(e) = self_monitoring::ensure(&self.db, &self.config.current(), &self.keys).await
+        {
+            warn!("Failed to create the system monitors: {}", e);
+        }
+        let db = self.db.clone();
+        let config = self.config.clone();
+        let clock_job = Job::new_async("0 */5 * * * *", move |_uuid, _l| {
+            let db = db.clone();
+            let config = config.clone();
+            Box::pin(async move {
+                publish_clock_status(&db, &config).await;
+                publish_build_info(&db, &config).await;
+            })
+        })
+        .map_err(|e| Error::scheduler(e.to_string()))?;
+        self.scheduler.add(clock_job).await
+            .map_err(|e| Error::scheduler(e.to_string()))?;
+
+        // Also applies the log filter override and redaction rules set through
+        // the API, replays results buffered while the database was unavailable,
+        // resumes paused monitors whose resume time has passed and queues new
+        // monitors
+        let db = self.db.clone();
+        let config = self.config.clone();
+        let results = self.results.clone();
+        let redactor = self.runner.redactor.clone();
+        let clock = self.clock.clone();
+        if let Err(e) = redactor.refresh(&db).await {
+            warn!("Failed to load result redaction rules: {}", e);
+        }
+        let applied_filter = Arc::new(Mutex::new(None::<String>));
+        let job = Job::new_async("0/30 * * * * *", move |_uuid, _l| {
+            let db = db.clone();
+            let config = config.clone();
+            let applied_filter = applied_filter.clone();
+            let results = results.clone();
+            let redactor = redactor.clone();
+            let clock = clock.clone();
             Box::pin(async move {
                 info!("Scheduler job triggered");
+                if let Err(e) = self_monitoring::report_scheduler_alive(&db, clock.now()).await {
+                    warn!("Failed to ping the scheduler heartbeat: {}", e);
+                }
+                if let Err(e) = sync_log_filter(&db, &config, &applied_filter).await {
+                    warn!("Failed to apply scheduler log filter: {}", e);
+                }
+                if let Err(e) = redactor.refresh(&db).await {
+                    warn!("Failed to reload result redaction rules: {}", e);
+                }
+                if let Err(e) = results.replay().await {
+                    warn!("Failed to replay buffered results: {}", e);
+                }
+                match pause::resume_due(&db, clock.now()).await {
+                    Ok(resumed) if !resumed.is_empty() => info!("Auto-resumed {} paused monitors", resumed.len()),
+                    Ok(_) => {}
+                    Err(e) => warn!("Failed to resume paused monitors: {}", e),
+                }
+                // Catches changes the listener missed while disconnected
+                match queue::sync(&db, config.current().scheduler.start_spread_secs).await {
+                    Ok(0) => {}
+                    Ok(added) => info!("Queued {} new monitors", added),
+                    Err(e) => warn!("Failed to sync the check queue: {}", e),
+                }
             })
         })
         .map_err(|e| Error::scheduler(e.to_string()))?;
         
         self.scheduler.add(job).await
             .map_err(|e| Error::scheduler(e.to_string()))?;
-        self.scheduler.start().await
+
+        let db = self.db.clone();
+        let config = self.config.clone();
+        let clock = self.clock.clone();
+        let retention_job = Job::new_async("0 0 3 * * *", move |_uuid, _l| {
+            let db = db.clone();
+            let cutoff = clock.now() - chrono::Duration::days(config.current().retention.result_days);
+            Box::pin(async move {
+                match retention::purge_expired_results(&db, cutoff, false).await {
+                    Ok(report) => info!("Retention removed {} expired results", report.total()),
+                    Err(e) => {
+                        error!("Result retention failed: {}", e);
+                        error_reporting::capture_error(&e, "scheduler.retention");
+                    }
+                }
+            })
+        })
+        .map_err(|e| Error::scheduler(e.to_string()))?;
+        self.scheduler.add(retention_job).await
             .map_err(|e| Error::scheduler(e.to_string()))?;
-        
-        info!("Monitor scheduler started successfully");
-        Ok(())
-    }
 
-    pub async fn load_and_schedule_monitors(&mut self) -> Result<()> {
-        let monitors = self.get_enabled_monitors().await?;
-        info!("Found {} enabled monitors", monitors.len());
-        
-        for monitor in monitors {
-            self.schedule_monitor(monitor).await?;
-        }
-        
-        Ok(())
-    }
+        // Move secrets encrypted with retired keys (or still in plaintext) onto the active key
+        let db = self.db.clone();
+        let keys = self.keys.clone();
+        let config = self.config.clone();
+        let rotation_job = Job::new_async("0 15 * * * *", move |_uuid, _l| {
+            let db = db.clone();
+            let keys = keys.clone();
+            let batch_size = config.current().encryption.rotation_batch_size;
+            Box::pin(async move {
+                match secrets::reencrypt_stale(&db, &keys, batch_size).await {
+                    Ok(0) => {}
+                    Ok(count) => info!("Re-encrypted {} secrets with key v{}", count, keys.active_version()),
+                    Err(e) => {
+                        error!("Secret re-encryption failed: {}", e);
+                        error_reporting::capture_error(&e, "scheduler.key_rotation");
+                    }
+                }
+            })
+        })
+        .map_err(|e| Error::scheduler(e.to_string()))?;
+        self.scheduler.add(rotation_job).await
+            .map_err(|e| Error::scheduler(e.to_string()))?;
 
-    async fn get_enabled_monitors(&self) -> Result<Vec<Monitor>> {
-        let rows = sqlx::query("SELECT * FROM monitors WHERE enabled = true")
-            .fetch_all(&self.db)
-            .await?;
-
-        let mut monitors = Vec::new();
-        for row in rows {
-            let monitor = Monitor {
-                id: row.get("id"),
-                name: row.get("name"),
-                endpoint: row.get("endpoint"),
-                method: row.get("method"),
-                headers: row.get("headers"),
-                body: row.get("body"),
-                expected_status: row.get("expected_status"),
-                timeout: row.get("timeout"),
-                interval: row.get("interval"),
-                script: row.get("script"),
-                enabled: row.get("enabled"),
-                created_at: row.get("created_at"),
-                updated_at: row.get("updated_at"),
-            };
-            monitors.push(monitor);
-        }
-        
-        Ok(monitors)
-    }
+        let db = self.db.clone();
+        let client = self.http_client.clone();
+        let alerts = self.runner.alerts.clone();
+        let expiry_job = Job::new_async("0 30 2 * * *", move |_uuid, _l| {
+            let db = db.clone();
+            let client = client.clone();
+            let alerts = alerts.clone();
+            Box::pin(async move {
+                if let Err(e) = refresh_expirations(&db, &client, &alerts).await {
+                    error!("Expiry refresh failed: {}", e);
+                    error_reporting::capture_error(&e, "scheduler.expirations");
+                }
+            })
+        })
+        .map_err(|e| Error::scheduler(e.to_string()))?;
+        self.scheduler.add(expiry_job).await
+            .map_err(|e| Error::scheduler(e.to_string()))?;
 
-    async fn schedule_monitor(&mut self, monitor: Monitor) -> Result<()> {
         let db = self.db.clone();
         let client = self.http_client.clone();
-        let monitor_name = monitor.name.clone();
-        let interval = monitor.interval;
-        
-        let cron_expression = format!("0/{} * * * * *", interval);
-        
-        let job = Job::new_async(&cron_expression, move |_uuid, _l| {
+        let clock = self.clock.clone();
+        let maintenance_job = Job::new_async("0 */15 * * * *", move |_uuid, _l| {
             let db = db.clone();
             let client = client.clone();
-            let monitor = monitor.clone();
-            
+            let now = clock.now();
             Box::pin(async move {
-                if let Err(e) = execute_monitor_check(&db, &client, &monitor).await {
-                    error!("Monitor check failed for {}: {}", monitor.name, e);
+                if let Err(e) = sync_maintenance(&db, &client, now).await {
+                    error!("Maintenance calendar sync failed: {}", e);
+                    error_reporting::capture_error(&e, "scheduler.maintenance");
                 }
             })
         })
         .map_err(|e| Error::scheduler(e.to_string()))?;
-        
-        self.scheduler.add(job).await
+        self.scheduler.add(maintenance_job).await
+            .map_err(|e| Error::scheduler(e.to_string()))?;
+
+        let db = self.db.clone();
+        let client = self.http_client.clone();
+        let keys = self.keys.clone();
+        let resolver = self.runner.targets.resolver().clone();
+        let config = self.config.clone();
+        let correlation_job = Job::new_async("0/15 * * * * *", move |_uuid, _l| {
+            let db = db.clone();
+            let client = client.clone();
+            let keys = keys.clone();
+            let resolver = resolver.clone();
+            let correlation = config.current().correlation.clone();
+            Box::pin(async move {
+                if !correlation.enabled {
+                    return;
+                }
+                if let Err(e) = correlation::analyze(&db, &client, &keys, &resolver, &correlation).await {
+                    warn!("Correlated outage analysis failed: {}", e);
+                }
+            })
+        })
+        .map_err(|e| Error::scheduler(e.to_string()))?;
+        self.scheduler.add(correlation_job).await
+            .map_err(|e| Error::scheduler(e.to_string()))?;
+
+        self.scheduler.start().await
             .map_err(|e| Error::scheduler(e.to_string()))?;
-        info!("Scheduled monitor: {} (interval: {}s)", monitor_name, interval);
         
+        info!("Monitor scheduler started successfully");
+        Ok(())
+    }
+
+    /// Queues enabled monitors and starts pulling due checks from the shared
+    /// queue. Several scheduler processes can run against the same database.
+    /// With `redis`, changes published by the API are applied as well.
+    pub async fn start_queue_worker(&mut self, redis: Option<RedisPool>) -> Result<()> {
+        let added = queue::sync(&self.db, self.config.current().scheduler.start_spread_secs).await?;
+        info!("Queued {} new monitors; worker {} is pulling due checks", added, self.runner.worker);
+
+        let runner = self.runner.clone();
+        let config = self.config.clone();
+        self.supervisor.spawn("check_dispatcher", RestartPolicy::Always(Backoff::default()), move |shutdown| {
+            let current = config.current();
+            dispatch(runner.clone(), current.scheduler.clone(), current.check_concurrency(), shutdown)
+        });
+        queue_listener::spawn(&self.supervisor, self.db.clone());
+        if let Some(redis) = redis {
+            queue_listener::spawn_redis(&self.supervisor, self.db.clone(), redis, self.runner.faults.clone());
+        }
+
+        // Once the dispatcher has stopped claiming, hand its claims to other workers
+        let db = self.db.clone();
+        let worker = self.runner.worker.clone();
+        self.supervisor.on_shutdown("release_claims", async move {
+            match queue::release_all(&db, &worker).await {
+                Ok(0) => {}
+                Ok(released) => info!("Released {} claimed checks", released),
+                Err(e) => warn!("Failed to release claimed checks: {}", e),
+            }
+        });
         Ok(())
     }
 
     pub async fn stop(&mut self) -> Result<()> {
         info!("Stopping monitor scheduler");
+        self.supervisor.shutdown().await;
         self.scheduler.shutdown().await
             .map_err(|e| Error::scheduler(e.to_string()))?;
         info!("Monitor scheduler stopped");
@@ -126,44 +392,542 @@ impl MonitorScheduler {
     }
 }
 
+/// Claims due checks while this worker has spare capacity and runs each on its
+/// own task, until shutdown.
+async fn dispatch(
+    runner: CheckRunner,
+    config: SchedulerConfig,
+    concurrency: usize,
+    mut shutdown: ShutdownSignal,
+) -> Result<()> {
+    if concurrency < config.max_concurrent_checks {
+        info!(
+            "Running at most {} checks at once; raise database.max_connections to allow {}",
+            concurrency, config.max_concurrent_checks
+        );
+    }
+    let capacity = Arc::new(Semaphore::new(concurrency));
+    let lease = chrono::Duration::seconds(config.claim_lease_secs);
+    let mut tick = tokio::time::interval(QUEUE_POLL_INTERVAL);
+    tick.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop {
+        tokio::select! {
+            _ = tick.tick() => {}
+            _ = shutdown.wait() => return Ok(()),
+        }
+        let available = capacity.available_permits();
+        if available == 0 {
+            continue;
+        }
+        let claimed = match queue::claim(&runner.db, &runner.worker, available as i64, lease).await {
+            Ok(claimed) => claimed,
+            Err(e) => {
+                warn!("Failed to claim due checks: {}", e);
+                continue;
+            }
+        };
+        for check in claimed {
+            let permit = capacity.clone().acquire_owned().await.expect("capacity semaphore is never closed");
+            let runner = runner.clone();
+            tokio::spawn(async move {
+                runner.run(check).await;
+                drop(permit);
+            });
+        }
+    }
+}
+
+impl CheckRunner {
+    async fn run(&self, check: QueuedCheck) {
+        let started = self.clock.now();
+        let monitor = match repository::get_monitor(&self.db, check.monitor_id).await {
+            Ok(Some(monitor)) => monitor,
+            // Deleting the monitor removed its queue row as well
+            Ok(None) => return,
+            Err(e) => {
+                // The claim expires after its lease and the check is retried
+                warn!("Failed to load monitor {}: {}", check.monitor_id, e);
+                return;
+            }
+        };
+
+        // A sync queues cron monitors to run at once; they wait for their first time instead
+        let on_schedule = monitor
+            .schedule_cron
+            .as_deref()
+            .is_none_or(|expression| queue::cron_matches(expression, check.due_at));
+        // Paused monitors stay queued until resumed; their ticks pass without a check
+        if monitor.enabled && on_schedule && !self.in_skipping_window(&monitor, started).await {
+            let lag_ms = check.lag_ms(started);
+            self.drift.observe(&monitor, lag_ms);
+            let lease = chrono::Duration::seconds(self.config.current().scheduler.claim_lease_secs);
+            if let Some(_guard) = self.locks.acquire(&self.db, &monitor, lease).await {
+                let config = self.config.current();
+                let context = CheckContext {
+                    deliver_webhooks: config.features.enable_webhooks,
+                    scripting: config.features.enable_scripting.then(|| Scripting {
+                        config: config.scripting.clone(),
+                        engines: self.engines.clone(),
+                    }),
+                    exec_commands: config.exec.commands.clone(),
+                    schedule_lag_ms: Some(lag_ms.min(i32::MAX as i64) as i32),
+                    alerts: self.alerts.clone(),
+                    redactor: self.redactor.current(),
+                    events: self.events.clone(),
+                    clock: self.clock.clone(),
+                    faults: self.faults.clone(),
+                };
+                run_isolated_check(
+                    self.db.clone(),
+                    self.client.clone(),
+                    self.targets.clone(),
+                    self.keys.clone(),
+                    self.results.clone(),
+                    monitor.clone(),
+                    context,
+                )
+                .await;
+            }
+        }
+
+        let now = self.clock.now();
+        let cron_due = monitor.schedule_cron.as_deref().and_then(|expression| queue::next_cron_due(expression, now));
+        let next_due = match cron_due {
+            // Cron schedules run at the times they name, without jitter
+            Some(next_due) => next_due,
+            None => {
+                let next_due = queue::next_due(check.due_at, monitor.interval, now);
+                let jitter_percent = self.config.current().scheduler.jitter_percent;
+                queue::jittered(next_due, monitor.interval, jitter_percent, random::uniform() * 2.0 - 1.0, now)
+            }
+        };
+        if let Err(e) = queue::complete(&self.db, monitor.id, &self.worker, next_due).await {
+            warn!("Failed to reschedule {}: {}", monitor.name, e);
+        }
+    }
+
+    /// Whether one of the monitor's maintenance windows skips this check,
+    /// which is then recorded as a skipped run.
+    async fn in_skipping_window(&self, monitor: &Monitor, at: DateTime<Utc>) -> bool {
+        let window = match maintenance::active_monitor_window(&self.db, monitor.id, at).await {
+            Ok(Some(window)) if window.skip_checks => window,
+            Ok(_) => return false,
+            Err(e) => {
+                warn!("Failed to check maintenance windows for {}: {}", monitor.name, e);
+                return false;
+            }
+        };
+        info!("Skipping check for {}: in maintenance window '{}'", monitor.name, window.summary);
+        let reason = monitor_core::locks::SKIP_MAINTENANCE;
+        if let Err(e) = monitor_core::locks::record_skipped(&self.db, monitor.id, reason, at).await {
+            warn!("Failed to record skipped run for {}: {}", monitor.name, e);
+        }
+        true
+    }
+}
+
+/// Runs a check on its own task so a panic cannot unwind into the job scheduler.
+/// A crashed check is recorded as a `crashed` result and the job stays scheduled.
+async fn run_isolated_check(
+    db: DatabasePool,
+    client: Client,
+    targets: HostClients,
+    keys: KeyRing,
+    results: Arc<ResultSink>,
+    monitor: Monitor,
+    context: CheckContext,
+) {
+    let task_db = db.clone();
+    let task_results = results.clone();
+    let task_monitor = monitor.clone();
+    let schedule_lag_ms = context.schedule_lag_ms;
+    let alerts = context.alerts.clone();
+    let redactor = context.redactor.clone();
+    let clock = context.clock.clone();
+    let handle = tokio::spawn(async move {
+        execute_monitor_check(&task_db, &client, &targets, &keys, &task_results, &task_monitor, &context).await
+    });
+
+    match handle.await {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => {
+            error!("Monitor check failed for {}: {}", monitor.name, e);
+            error_reporting::capture_error(&e, "scheduler.monitor_check");
+        }
+        Err(join_error) if join_error.is_panic() => {
+            let message = supervisor::panic_message(join_error.into_panic());
+            error!("Monitor check for {} crashed: {}", monitor.name, message);
+
+            let registry = metrics::global();
+            registry.describe(METRIC_CHECK_CRASHES, "Monitor checks that panicked");
+            registry.increment_counter(METRIC_CHECK_CRASHES, &[("monitor", monitor.name.as_str())]);
+            error_reporting::capture_message(
+                &format!("Monitor check for {} crashed: {}", monitor.name, message),
+                "scheduler.monitor_check",
+            );
+
+            let mut result = MonitorResult {
+                id: Uuid::new_v4(),
+                monitor_id: monitor.id,
+                status: "crashed".to_string(),
+                response_time: 0,
+                response_code: None,
+                response_body: None,
+                response_headers: None,
+                error_message: Some(format!("Check crashed: {}", message)),
+                error_category: Some(FailureCategory::Crashed.as_str().to_string()),
+                error_hint: None,
+                checked_at: clock.now(),
+                clock_skew_ms: clock::current_offset_ms(),
+                schedule_lag_ms,
+                attempts: 1,
+            };
+            redactor.apply(&mut result);
+            if let Err(e) = results.save(&result).await {
+                error!("Failed to record crashed check for {}: {}", monitor.name, e);
+                return;
+            }
+            let recorded = record_state(&db, &monitor, &result).await;
+            if !recorded.is_some_and(|(state, _)| state.is_flapping()) {
+                alerts.evaluate_result(&monitor, &result).await;
+            }
+        }
+        Err(join_error) => warn!("Monitor check for {} was cancelled: {}", monitor.name, join_error),
+    }
+}
+
+/// The result of a check the fault injector fails, or makes outlast the
+/// monitor's timeout. Other checks run after any stall.
+async fn injected_failure(context: &CheckContext, monitor: &Monitor, timeout: Seconds) -> Option<MonitorResult> {
+    let start_time = Instant::now();
+    let outcome = brokers::with_timeout(timeout, async {
+        faults::inject(context.faults.as_deref(), FaultPoint::Check)
+            .await
+            .map(|_| (None, serde_json::Value::Null))
+            .map_err(|_| {
+                let message = format!("Injected {} failure", FaultPoint::Check);
+                (None, ("error", FailureCategory::ConnectError, message))
+            })
+    })
+    .await;
+    outcome.is_err().then(|| brokers::into_result(monitor, start_time, outcome))
+}
+
 async fn execute_monitor_check(
     db: &DatabasePool,
     client: &Client,
+    targets: &HostClients,
+    keys: &KeyRing,
+    results: &ResultSink,
     monitor: &Monitor,
+    context: &CheckContext,
 ) -> Result<()> {
     info!("Executing monitor check: {}", monitor.name);
+
+    let credentials: HashMap<String, String> = match &monitor.credentials {
+        Some(credentials) => serde_json::from_str(&keys.decrypt(credentials)?)?,
+        None => HashMap::new(),
+    };
+
+    let scripting = context.scripting.as_ref();
+    let builtin_profile = scripting.map(|s| s.config.security_profile.as_str()).unwrap_or("default");
+    let settings = settings::effective_for(db, monitor, EffectiveSettings::builtin(builtin_profile)).await?;
+    let scripting = scripting.map(|scripting| Scripting {
+        config: ScriptingConfig {
+            security_profile: settings.script_profile.clone(),
+            ..scripting.config.clone()
+        },
+        engines: scripting.engines.clone(),
+    });
+
+    let mut attempt = 0;
+    let result = loop {
+        let check = monitor.check_config().unwrap_or(&serde_json::Value::Null);
+        let timeout = settings.timeout;
+        let result = if let Some(result) = injected_failure(context, monitor, timeout).await {
+            result
+        } else {
+            match monitor.monitor_type() {
+                MonitorType::Http | MonitorType::Metrics => {
+                    run_single_request(targets, monitor, &credentials, timeout, scripting.as_ref()).await
+                }
+                MonitorType::Transaction => {
+                    transactions::run(targets, monitor, check, &credentials, timeout, scripting.as_ref()).await?
+                }
+                MonitorType::Broker => brokers::run(targets, monitor, check, &credentials, timeout).await,
+                MonitorType::Storage => storage::run(targets, monitor, check, &credentials, timeout).await,
+                MonitorType::Ntp => ntp::run(monitor, check, timeout).await,
+                MonitorType::Snmp => snmp::run(monitor, check, &credentials, timeout).await,
+                MonitorType::Exec => exec::run(monitor, check, &context.exec_commands, timeout).await,
+                MonitorType::Tcp => tcp::run(monitor, check, timeout).await,
+                MonitorType::Dns => dns::run(monitor, check, timeout).await,
+                MonitorType::Tls => tls::run(monitor, check, timeout).await,
+                MonitorType::WebSocket => websocket::run(monitor, check, &credentials, timeout).await,
+                MonitorType::Mail => mail::run(monitor, check, &credentials, timeout).await,
+                MonitorType::Database => databases::run(monitor, check, &credentials, timeout).await,
+                MonitorType::Heartbeat => heartbeats::run(db, monitor, check, timeout, context.clock.now()).await,
+            }
+        };
+        if result.status == "success" || attempt >= settings.retries || !failures::is_transient(&result) {
+            break result;
+        }
+        attempt += 1;
+        info!(
+            "Monitor {} returned {}, retrying ({}/{})",
+            monitor.name, result.status, attempt, settings.retries
+        );
+        let delay = monitor.retry_delay_ms.unwrap_or(settings::DEFAULT_RETRY_DELAY_MS).max(0);
+        tokio::time::sleep(std::time::Duration::from_millis(delay as u64)).await;
+    };
+    let now = context.clock.now();
+    let mut result = MonitorResult {
+        schedule_lag_ms: context.schedule_lag_ms,
+        attempts: attempt + 1,
+        error_hint: failure_hint(db, monitor, &result, now).await,
+        // Checks stamp results with the wall clock; the scheduler's clock decides
+        checked_at: now,
+        ..result
+    };
+    // Before anything is stored or sent
+    context.redactor.apply(&mut result);
+
+    if result.status != "success" {
+        capture_evidence(db, monitor, &result).await;
+    }
+    results.save(&result).await?;
+    context.events.publish(Event::CheckCompleted { result: Arc::new(result.clone()) });
+    let recorded = record_state(db, monitor, &result).await;
+    if let Some((_, Some(event))) = &recorded {
+        context.events.publish(Event::StateChanged {
+            monitor_id: monitor.id,
+            event: *event,
+        });
+    }
+
+    if !alerts_suppressed(db, monitor, &result, now).await {
+        if context.deliver_webhooks
+            && let Some((_, Some(event))) = &recorded
+            && let Err(e) = webhooks::deliver_state(
+                db,
+                client,
+                keys,
+                monitor,
+                &result,
+                *event,
+                &settings.notification_channels,
+            )
+            .await
+        {
+            warn!("Failed to deliver state webhooks for {}: {}", monitor.name, e);
+        }
+        // Alert rules hold their state while the monitor flaps
+        if !recorded.is_some_and(|(state, _)| state.is_flapping()) {
+            context.alerts.evaluate_result(monitor, &result).await;
+        }
+    }
     
-    let start_time = Instant::now();
-    let mut request = client.request(
-        monitor.method.parse().unwrap_or(reqwest::Method::GET),
-        &monitor.endpoint,
-    );
+    if result.status != "success" {
+        warn!("Monitor {} failed: {:?}", monitor.name, result.error_message);
+    } else {
+        info!("Monitor {} succeeded in {}ms", monitor.name, result.response_time);
+    }
     
-    if let Some(headers) = &monitor.headers {
-        if let Ok(header_map) = serde_json::from_value::<std::collections::HashMap<String, String>>(headers.clone()) {
-            for (key, value) in header_map {
-                request = request.header(&key, &value);
+    Ok(())
+}
+
+/// Counts the result towards the monitor's health and returns the updated
+/// state with what to notify, if anything. Counting continues while alerts
+/// are suppressed.
+async fn record_state(
+    db: &DatabasePool,
+    monitor: &Monitor,
+    result: &MonitorResult,
+) -> Option<(MonitorState, Option<StateEvent>)> {
+    match states::record(db, monitor.id, result.status == "success", result.checked_at).await {
+        Ok((state, event)) => {
+            match event {
+                Some(StateEvent::Changed(transition)) => info!(
+                    "Monitor {} went from {} to {}",
+                    monitor.name,
+                    transition.from.as_str(),
+                    transition.to.as_str()
+                ),
+                Some(StateEvent::FlappingStarted { transitions, window_secs }) => warn!(
+                    "Monitor {} is flapping: {} state changes in {}s",
+                    monitor.name, transitions, window_secs
+                ),
+                Some(StateEvent::Stabilized(settled)) => {
+                    info!("Monitor {} stopped flapping and is {}", monitor.name, settled.as_str())
+                }
+                None => {}
             }
+            Some((state, event))
+        }
+        Err(e) => {
+            warn!("Failed to update the state of {}: {}", monitor.name, e);
+            None
+        }
+    }
+}
+
+/// Failures are not delivered while the monitor's provider is in a
+/// maintenance window, or when the monitor is part of an open correlated
+/// incident whose notification covers it.
+async fn alerts_suppressed(db: &DatabasePool, monitor: &Monitor, result: &MonitorResult, now: DateTime<Utc>) -> bool {
+    if result.status == "success" {
+        return false;
+    }
+    match maintenance::in_maintenance(db, monitor.id, &monitor.tags, now).await {
+        Ok(true) => return true,
+        Ok(false) => {}
+        Err(e) => warn!("Failed to check maintenance windows for {}: {}", monitor.name, e),
+    }
+    match monitor_core::correlation::is_correlated(db, monitor.id).await {
+        Ok(correlated) => correlated,
+        Err(e) => {
+            warn!("Failed to check correlated incidents for {}: {}", monitor.name, e);
+            false
         }
     }
+}
+
+/// Keeps the full response of the first failure after a success. Must run
+/// before the result is saved so the previous status is still the latest one.
+async fn capture_evidence(db: &DatabasePool, monitor: &Monitor, result: &MonitorResult) {
+    let previous = match evidence::previous_status(db, monitor.id).await {
+        Ok(previous) => previous,
+        Err(e) => {
+            warn!("Failed to load previous status for {}: {}", monitor.name, e);
+            return;
+        }
+    };
+    if let Some(previous) = previous.as_deref()
+        && evidence::is_first_failure(Some(previous), &result.status)
+        && let Err(e) = evidence::record(db, &IncidentEvidence::from_result(result, previous)).await
+    {
+        warn!("Failed to capture incident evidence for {}: {}", monitor.name, e);
+    }
+}
+
+/// Suggested cause for a failed result. The certificate expiry is only looked up
+/// for TLS failures.
+async fn failure_hint(
+    db: &DatabasePool,
+    monitor: &Monitor,
+    result: &MonitorResult,
+    now: DateTime<Utc>,
+) -> Option<String> {
+    let category = FailureCategory::parse(result.error_category.as_deref()?)?;
+    let certificate_expires_at = if category == FailureCategory::TlsError {
+        expirations::certificate_expiry_for(db, monitor.id).await.unwrap_or_else(|e| {
+            warn!("Failed to look up certificate expiry for {}: {}", monitor.name, e);
+            None
+        })
+    } else {
+        None
+    };
+    failures::hint(&HintContext {
+        category,
+        message: result.error_message.as_deref().unwrap_or_default(),
+        response_code: result.response_code,
+        certificate_expires_at,
+        now,
+    })
+}
+
+async fn run_single_request(
+    targets: &HostClients,
+    monitor: &Monitor,
+    credentials: &HashMap<String, String>,
+    timeout: Seconds,
+    scripting: Option<&Scripting>,
+) -> MonitorResult {
+    let mut spec = HttpRequestSpec {
+        method: monitor.method.to_string(),
+        url: monitor.endpoint.clone(),
+        headers: BTreeMap::new(),
+        body: monitor.body.clone(),
+    };
     
-    if let Some(body) = &monitor.body {
-        request = request.body(body.clone());
+    if let Some(headers) = &monitor.headers {
+        match monitor_core::headers::from_value(headers) {
+            Ok(headers) => spec.headers.extend(headers),
+            Err(e) => {
+                let failure = ("error", FailureCategory::Other, e.to_string());
+                return brokers::into_result(monitor, Instant::now(), Err((None, failure)));
+            }
+        }
     }
+    spec.headers.extend(credentials.clone());
+    if monitor.metric_assertions.is_some() && !spec.headers.keys().any(|name| name.eq_ignore_ascii_case("accept")) {
+        spec.headers.insert("Accept".to_string(), openmetrics::ACCEPT.to_string());
+    }
+
+    let request = match pre_request::build_request(targets, monitor, spec, scripting).await {
+        Ok(request) => request,
+        Err(e) => {
+            return MonitorResult {
+                id: Uuid::new_v4(),
+                monitor_id: monitor.id,
+                status: "error".to_string(),
+                response_time: 0,
+                response_code: None,
+                response_body: None,
+                response_headers: None,
+                error_message: Some(e.to_string()),
+                error_category: Some(FailureCategory::ScriptError.as_str().to_string()),
+                error_hint: None,
+                checked_at: Utc::now(),
+                clock_skew_ms: clock::current_offset_ms(),
+                schedule_lag_ms: None,
+                attempts: 1,
+            };
+        }
+    };
     
-    let result = match tokio::time::timeout(
-        std::time::Duration::from_secs(monitor.timeout as u64),
+    // Timed after the pre-request script so script time does not count as latency
+    let start_time = Instant::now();
+    match tokio::time::timeout(
+        timeout.as_std(),
         request.send(),
     ).await {
         Ok(Ok(response)) => {
             let response_time = start_time.elapsed().as_millis() as i32;
             let status_code = response.status().as_u16() as i32;
+            let response_headers = clients::response_headers(response.headers());
             let response_body = response.text().await.unwrap_or_default();
             
-            let status = if status_code == monitor.expected_status {
-                "success".to_string()
+            let builtin = if !monitor.expected_status.matches(status_code) {
+                Err((FailureCategory::HttpStatus, None))
+            } else if let Err(reason) = body_match::check(
+                &response_body,
+                monitor.expected_body_contains.as_deref(),
+                monitor.expected_body_regex.as_deref(),
+            ) {
+                Err((FailureCategory::AssertionFailed, Some(reason)))
+            } else if let Some(assertions) = &monitor.metric_assertions
+                && let Err(reason) = openmetrics::check(assertions, &response_body)
+            {
+                Err((FailureCategory::AssertionFailed, Some(reason)))
             } else {
-                "failure".to_string()
+                Ok(())
+            };
+            let verdict = match &monitor.script {
+                Some(script) => {
+                    let context = ValidationContext {
+                        status_code: status_code as u16,
+                        headers: response_headers.clone().into_iter().collect(),
+                        body: response_body.clone(),
+                        response_time: response_time.max(0) as u64,
+                    };
+                    script_verdict(validation::validate(monitor, script, context, scripting).await, builtin)
+                }
+                None => builtin.map_err(|(category, reason)| ("failure", category, reason)),
+            };
+            let (status, error_category, error_message) = match verdict {
+                Ok(()) => ("success".to_string(), None, None),
+                Err((status, category, reason)) => (status.to_string(), Some(category.as_str().to_string()), reason),
             };
             
             MonitorResult {
@@ -173,22 +937,35 @@ async fn execute_monitor_check(
                 response_time,
                 response_code: Some(status_code),
                 response_body: Some(response_body),
-                error_message: None,
+                response_headers: Some(response_headers),
+                error_message,
+                error_category,
+                error_hint: None,
                 checked_at: Utc::now(),
+                clock_skew_ms: clock::current_offset_ms(),
+                schedule_lag_ms: None,
+                attempts: 1,
             }
         },
         Ok(Err(e)) => {
             let response_time = start_time.elapsed().as_millis() as i32;
+            let (status, category, message) = clients::describe_error(&e);
             
             MonitorResult {
                 id: Uuid::new_v4(),
                 monitor_id: monitor.id,
-                status: "error".to_string(),
+                status: status.to_string(),
                 response_time,
                 response_code: None,
                 response_body: None,
-                error_message: Some(e.to_string()),
+                response_headers: None,
+                error_message: Some(message),
+                error_category: Some(category.as_str().to_string()),
+                error_hint: None,
                 checked_at: Utc::now(),
+                clock_skew_ms: clock::current_offset_ms(),
+                schedule_lag_ms: None,
+                attempts: 1,
             }
         },
         Err(_) => {
@@ -201,40 +978,144 @@ async fn execute_monitor_check(
                 response_time,
                 response_code: None,
                 response_body: None,
+                response_headers: None,
                 error_message: Some("Request timeout".to_string()),
+                error_category: Some(FailureCategory::RequestTimeout.as_str().to_string()),
+                error_hint: None,
                 checked_at: Utc::now(),
+                clock_skew_ms: clock::current_offset_ms(),
+                schedule_lag_ms: None,
+                attempts: 1,
             }
         }
+    }
+}
+
+/// Combines the validation script's outcome with the built-in status, body
+/// and metric comparisons. A `{ passed, message }` verdict replaces them; any
+/// other result must pass on top of them. Scripts that fail to run make the
+/// check an error.
+fn script_verdict(
+    validation: Result<ValidationResult>,
+    builtin: std::result::Result<(), (FailureCategory, Option<String>)>,
+) -> std::result::Result<(), (&'static str, FailureCategory, Option<String>)> {
+    let validation = match validation {
+        Ok(validation) if validation.is_script_error() => {
+            return Err(("error", FailureCategory::ScriptError, Some(validation.message)));
+        }
+        Ok(validation) => validation,
+        Err(e) => return Err(("error", FailureCategory::ScriptError, Some(e.to_string()))),
     };
-    
-    save_monitor_result(db, &result).await?;
-    
-    if result.status != "success" {
-        warn!("Monitor {} failed: {:?}", monitor.name, result.error_message);
-    } else {
-        info!("Monitor {} succeeded in {}ms", monitor.name, result.response_time);
+    let rejected = || Err(("failure", FailureCategory::AssertionFailed, Some(validation.message.clone())));
+    match builtin {
+        _ if validation.verdict && validation.passed => Ok(()),
+        _ if validation.verdict => rejected(),
+        Err((category, reason)) => Err(("failure", category, reason)),
+        Ok(()) if validation.passed => Ok(()),
+        Ok(()) => rejected(),
+    }
+}
+
+/// Refreshes the windows of every enabled maintenance calendar. A feed that
+/// cannot be fetched or parsed keeps its previous windows.
+async fn sync_maintenance(db: &DatabasePool, client: &Client, now: DateTime<Utc>) -> Result<()> {
+    let sources = maintenance::list_sources(db).await?;
+    for source in sources.iter().filter(|s| s.enabled) {
+        let body = match fetch_feed(client, &source.url).await {
+            Ok(body) => body,
+            Err(e) => {
+                warn!("Failed to fetch maintenance calendar {}: {}", source.name, e);
+                maintenance::record_sync_error(db, source.id, &e).await?;
+                continue;
+            }
+        };
+        match maintenance::parse_feed(&body) {
+            Ok(events) => {
+                maintenance::store_windows(db, source.id, &events, now).await?;
+                info!("Synced {} maintenance windows from {}", events.len(), source.name);
+            }
+            Err(e) => {
+                warn!("Failed to parse maintenance calendar {}: {}", source.name, e);
+                maintenance::record_sync_error(db, source.id, &e.to_string()).await?;
+            }
+        }
     }
-    
     Ok(())
 }
 
-async fn save_monitor_result(db: &DatabasePool, result: &MonitorResult) -> Result<()> {
-    sqlx::query(
-        r#"
-        INSERT INTO monitor_results (id, monitor_id, status, response_time, response_code, response_body, error_message, checked_at)
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
-        "#
-    )
-    .bind(result.id)
-    .bind(result.monitor_id)
-    .bind(&result.status)
-    .bind(result.response_time)
-    .bind(result.response_code)
-    .bind(&result.response_body)
-    .bind(&result.error_message)
-    .bind(result.checked_at)
-    .execute(db)
-    .await?;
-    
+async fn fetch_feed(client: &Client, url: &str) -> std::result::Result<String, String> {
+    let response = client
+        .get(url)
+        .timeout(std::time::Duration::from_secs(30))
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if !response.status().is_success() {
+        return Err(format!("HTTP {}", response.status()));
+    }
+    response.text().await.map_err(|e| e.to_string())
+}
+
+/// Records certificate and domain expiry for every enabled HTTPS monitor and
+/// evaluates their expiry alerts.
+async fn refresh_expirations(db: &DatabasePool, client: &Client, alerts: &AlertManager) -> Result<()> {
+    let monitors = repository::enabled_monitors(db).await?;
+
+    let mut updated = 0;
+    for monitor in &monitors {
+        updated += expirations::refresh_monitor(db, client, monitor).await?;
+        if let Err(e) = alerts.evaluate_expiry(monitor).await {
+            warn!("Failed to evaluate expiry alerts for {}: {}", monitor.name, e);
+        }
+    }
+    info!("Refreshed {} certificate/domain expirations", updated);
+    Ok(())
+}
+
+/// Measures clock skew and shares it with the API so it shows up in /health.
+async fn publish_clock_status(db: &DatabasePool, config: &LiveConfig) {
+    let status = match clock::check(db, &config.current().clock).await {
+        Ok(status) => status,
+        Err(e) => {
+            warn!("Clock skew check failed: {}", e);
+            return;
+        }
+    };
+
+    let published = match serde_json::to_string(&status) {
+        Ok(json) => runtime_settings::set(db, runtime_settings::SCHEDULER_CLOCK_STATUS, &json).await,
+        Err(e) => Err(e.into()),
+    };
+    if let Err(e) = published {
+        warn!("Failed to publish clock status: {}", e);
+    }
+}
+
+/// Tells the API which build this instance runs, for /api/version.
+async fn publish_build_info(db: &DatabasePool, config: &LiveConfig) {
+    let info = BuildInfo::current("scheduler", &config.current());
+    if let Err(e) = build_info::publish(db, &info).await {
+        warn!("Failed to publish build info: {}", e);
+    }
+}
+
+/// Applies `runtime_settings.scheduler.log_filter`, falling back to `logging.level`
+/// once the override is cleared. `applied` holds the override currently in effect.
+async fn sync_log_filter(
+    db: &DatabasePool,
+    config: &LiveConfig,
+    applied: &Mutex<Option<String>>,
+) -> Result<()> {
+    let wanted = runtime_settings::get(db, runtime_settings::SCHEDULER_LOG_FILTER).await?;
+    if *applied.lock().unwrap() == wanted {
+        return Ok(());
+    }
+
+    let filter = wanted
+        .clone()
+        .unwrap_or_else(|| config.current().logging.level.clone());
+    logging::set_filter(&filter)?;
+    info!("Scheduler log filter set to '{}'", filter);
+    *applied.lock().unwrap() = wanted;
     Ok(())
-}
\ No newline at end of file
+}
@@ -0,0 +1,27 @@
+use monitor_core::{config::ScriptingConfig, Error, Result};
+use monitor_scripting::{
+    models::SecurityConfig,
+    pool::{PooledEngine, ScriptEnginePool},
+};
+use std::{sync::Arc, time::Duration};
+
+/// Scripting settings for one check and the scheduler's engines its
+/// pre-request and validation scripts run on.
+#[derive(Clone)]
+pub struct Scripting {
+    pub config: ScriptingConfig,
+    pub engines: Arc<ScriptEnginePool>,
+}
+
+impl Scripting {
+    /// Checks out an engine with the configured timeout and security
+    /// profile, waiting while `scripting.pool_size` scripts are running.
+    pub async fn engine(&self) -> Result<PooledEngine<'_>> {
+        let profile = &self.config.security_profile;
+        let security_config = SecurityConfig::from_profile(profile)
+            .ok_or_else(|| Error::script_execution(format!("Unknown security profile: {}", profile)))?;
+        self.engines
+            .checkout(Duration::from_secs(self.config.timeout), security_config)
+            .await
+    }
+}
@@ -0,0 +1,112 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use monitor_core::{
+    config::{SmtpConfig, SmtpTls},
+    Error, Result,
+};
+use std::{sync::Arc, time::Duration};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader},
+    net::TcpStream,
+};
+use tokio_rustls::{
+    rustls::{self, pki_types::ServerName, ClientConfig, RootCertStore},
+    TlsConnector,
+};
+
+const SMTP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Sends one message, already formatted for `DATA`, through the configured
+/// server.
+pub async fn send(config: &SmtpConfig, to: &[String], message: &str) -> Result<()> {
+    let host = config
+        .host
+        .as_deref()
+        .ok_or_else(|| Error::validation("SMTP is not configured; set SMTP_HOST"))?;
+    tokio::time::timeout(SMTP_TIMEOUT, deliver(config, host, to, message))
+        .await
+        .map_err(|_| Error::internal(format!("SMTP to {} timed out", host)))?
+}
+
+async fn deliver(config: &SmtpConfig, host: &str, to: &[String], message: &str) -> Result<()> {
+    let tcp = TcpStream::connect((host, config.port)).await?;
+    match config.tls {
+        SmtpTls::Tls => {
+            let mut stream = BufReader::new(connector()?.connect(server_name(host)?, tcp).await?);
+            reply(&mut stream, 220).await?;
+            session(&mut stream, config, to, message).await
+        }
+        SmtpTls::StartTls => {
+            let mut stream = BufReader::new(tcp);
+            reply(&mut stream, 220).await?;
+            command(&mut stream, "EHLO monitor", 250).await?;
+            command(&mut stream, "STARTTLS", 220).await?;
+            let tls = connector()?.connect(server_name(host)?, stream.into_inner()).await?;
+            session(&mut BufReader::new(tls), config, to, message).await
+        }
+        SmtpTls::None => {
+            let mut stream = BufReader::new(tcp);
+            reply(&mut stream, 220).await?;
+            session(&mut stream, config, to, message).await
+        }
+    }
+}
+
+async fn session<S: AsyncRead + AsyncWrite + Unpin>(
+    stream: &mut BufReader<S>,
+    config: &SmtpConfig,
+    to: &[String],
+    message: &str,
+) -> Result<()> {
+    command(stream, "EHLO monitor", 250).await?;
+    if let (Some(username), Some(password)) = (&config.username, &config.password) {
+        let credentials = STANDARD.encode(format!("\0{}\0{}", username, password));
+        command(stream, &format!("AUTH PLAIN {}", credentials), 235).await?;
+    }
+    command(stream, &format!("MAIL FROM:<{}>", config.from), 250).await?;
+    for recipient in to {
+        command(stream, &format!("RCPT TO:<{}>", recipient), 250).await?;
+    }
+    command(stream, "DATA", 354).await?;
+    stream.get_mut().write_all(message.as_bytes()).await?;
+    reply(stream, 250).await?;
+    // The message is accepted; a failed goodbye does not matter
+    let _ = command(stream, "QUIT", 221).await;
+    Ok(())
+}
+
+async fn command<S: AsyncRead + AsyncWrite + Unpin>(stream: &mut BufReader<S>, line: &str, expected: u16) -> Result<()> {
+    stream.get_mut().write_all(format!("{}\r\n", line).as_bytes()).await?;
+    reply(stream, expected).await
+}
+
+/// Reads a possibly multi-line reply (`250-...` continues, `250 ...` ends).
+async fn reply<S: AsyncRead + Unpin>(stream: &mut BufReader<S>, expected: u16) -> Result<()> {
+    loop {
+        let mut line = String::new();
+        if stream.read_line(&mut line).await? == 0 {
+            return Err(Error::internal("SMTP server closed the connection"));
+        }
+        let code: u16 = line.get(..3).and_then(|code| code.parse().ok()).unwrap_or_default();
+        if code != expected {
+            return Err(Error::internal(format!("SMTP server replied: {}", line.trim_end())));
+        }
+        if line.as_bytes().get(3) != Some(&b'-') {
+            return Ok(());
+        }
+    }
+}
+
+fn connector() -> Result<TlsConnector> {
+    let mut roots = RootCertStore::empty();
+    roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+    let config = ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .map_err(|e| Error::internal(e.to_string()))?
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    Ok(TlsConnector::from(Arc::new(config)))
+}
+
+fn server_name(host: &str) -> Result<ServerName<'static>> {
+    ServerName::try_from(host.to_string()).map_err(|_| Error::validation(format!("Invalid SMTP host: {}", host)))
+}
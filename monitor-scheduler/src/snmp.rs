@@ -0,0 +1,66 @@
+use monitor_core::{
+    durations::Seconds,
+    failures::FailureCategory,
+    models::{Monitor, MonitorResult},
+    snmp::{self, Session, SnmpCheck},
+    Error,
+};
+use serde_json::json;
+use std::{collections::HashMap, time::Instant};
+use tokio::net::UdpSocket;
+
+use crate::brokers::{self, Failure, Outcome};
+
+/// Polls the monitor's SNMP agent and holds the values to the check's
+/// assertions. The polled values are kept in the result body.
+pub async fn run(
+    monitor: &Monitor,
+    check: &serde_json::Value,
+    credentials: &HashMap<String, String>,
+    timeout: Seconds,
+) -> MonitorResult {
+    let start_time = Instant::now();
+    let outcome = match serde_json::from_value::<SnmpCheck>(check.clone()) {
+        Ok(check) => brokers::with_timeout(timeout, execute(monitor, &check, credentials)).await,
+        Err(e) => Err((None, ("error", FailureCategory::Other, format!("Invalid SNMP check: {}", e)))),
+    };
+    brokers::into_result(monitor, start_time, outcome)
+}
+
+async fn execute(monitor: &Monitor, check: &SnmpCheck, credentials: &HashMap<String, String>) -> Outcome {
+    let failed = |category, e: Error| (None, ("error", category, e.to_string()));
+    let address = snmp::agent_address(&monitor.endpoint).map_err(|e| failed(FailureCategory::Other, e))?;
+    let oids = check.object_ids().map_err(|e| failed(FailureCategory::Other, e))?;
+    let mut session = Session::new(check, credentials).map_err(|e| failed(FailureCategory::Other, e))?;
+
+    let socket = UdpSocket::bind("0.0.0.0:0").await.map_err(|e| failed(FailureCategory::ConnectError, e.into()))?;
+    socket
+        .connect(&address)
+        .await
+        .map_err(|e| failed(FailureCategory::ConnectError, e.into()))?;
+    if session.needs_discovery() {
+        let report = exchange(&socket, &session.discovery_request()).await?;
+        session.discover(&report).map_err(|e| failed(FailureCategory::Other, e))?;
+    }
+    let request = session.get_request(&oids).map_err(|e| failed(FailureCategory::Other, e))?;
+    let response = exchange(&socket, &request).await?;
+    let bindings = session.read_response(&response).map_err(|e| failed(FailureCategory::Other, e))?;
+
+    let values = check.named(bindings);
+    let body = json!({ "values": values });
+    check
+        .evaluate(&values)
+        .map_err(|message| (None, ("failure", FailureCategory::AssertionFailed, message)))?;
+    Ok((None, body))
+}
+
+/// Sends one request and waits for the agent's datagram; the outer timeout
+/// bounds the wait.
+async fn exchange(socket: &UdpSocket, request: &[u8]) -> std::result::Result<Vec<u8>, (Option<i32>, Failure)> {
+    let failed = |e: std::io::Error| (None, ("error", FailureCategory::ConnectError, e.to_string()));
+    socket.send(request).await.map_err(failed)?;
+    let mut response = vec![0; snmp::MAX_MESSAGE_SIZE];
+    let len = socket.recv(&mut response).await.map_err(failed)?;
+    response.truncate(len);
+    Ok(response)
+}
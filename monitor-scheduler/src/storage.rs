@@ -0,0 +1,120 @@
+use chrono::Utc;
+use monitor_core::{
+    aws::Credentials,
+    durations::Seconds,
+    failures::FailureCategory,
+    models::{Monitor, MonitorResult},
+    storage::{self, StorageCheck, StorageOperation},
+};
+use reqwest::{Client, Method};
+use serde_json::json;
+use std::{collections::HashMap, time::Instant};
+use uuid::Uuid;
+
+use crate::{
+    brokers::{self, Failure, Outcome},
+    clients::{self, HostClients},
+};
+
+/// Runs the monitor's storage check. The timeout covers every request of a
+/// round trip together.
+pub async fn run(
+    targets: &HostClients,
+    monitor: &Monitor,
+    check: &serde_json::Value,
+    credentials: &HashMap<String, String>,
+    timeout: Seconds,
+) -> MonitorResult {
+    let start_time = Instant::now();
+    let outcome = match serde_json::from_value::<StorageCheck>(check.clone()) {
+        Ok(check) => brokers::with_timeout(timeout, execute(targets, monitor, &check, credentials)).await,
+        Err(e) => Err((None, ("error", FailureCategory::Other, format!("Invalid storage check: {}", e)))),
+    };
+    brokers::into_result(monitor, start_time, outcome)
+}
+
+async fn execute(
+    targets: &HostClients,
+    monitor: &Monitor,
+    check: &StorageCheck,
+    credentials: &HashMap<String, String>,
+) -> Outcome {
+    let credentials = storage::credentials(credentials)
+        .map_err(|e| (None, ("error", FailureCategory::Other, e.to_string())))?;
+    let bucket = Bucket {
+        client: targets.for_url(&monitor.endpoint, monitor),
+        endpoint: &monitor.endpoint,
+        credentials,
+        region: check.region(),
+    };
+
+    match &check.operation {
+        StorageOperation::Head { key } => {
+            let (code, _, ms) = bucket.send("head", Method::HEAD, key, Vec::new()).await?;
+            Ok((Some(code), json!({ "key": key, "phases": { "head_ms": ms } })))
+        }
+        StorageOperation::Get { key } => {
+            let (code, body, ms) = bucket.send("get", Method::GET, key, Vec::new()).await?;
+            Ok((Some(code), json!({ "key": key, "size": body.len(), "phases": { "get_ms": ms } })))
+        }
+        StorageOperation::RoundTrip { prefix } => {
+            let key = storage::probe_key(prefix.as_deref(), monitor.id);
+            let nonce = Uuid::new_v4().to_string();
+            let (_, _, put_ms) = bucket.send("put", Method::PUT, &key, nonce.clone().into_bytes()).await?;
+            let read = bucket.send("get", Method::GET, &key, Vec::new()).await;
+            // Deleted even when the read failed, so probes do not pile up
+            let (code, _, delete_ms) = bucket.send("delete", Method::DELETE, &key, Vec::new()).await?;
+            let (get_code, body, get_ms) = read?;
+            if body != nonce.as_bytes() {
+                let message = "the probe object read back differs from the one written".to_string();
+                return Err((Some(get_code), ("failure", FailureCategory::AssertionFailed, message)));
+            }
+            let phases = json!({ "put_ms": put_ms, "get_ms": get_ms, "delete_ms": delete_ms });
+            Ok((Some(code), json!({ "key": key, "phases": phases })))
+        }
+    }
+}
+
+struct Bucket<'a> {
+    client: Client,
+    endpoint: &'a str,
+    credentials: Credentials,
+    region: &'a str,
+}
+
+impl Bucket<'_> {
+    /// Status, body and milliseconds taken of a signed request. `phase`
+    /// prefixes the error message of a failed one.
+    async fn send(
+        &self,
+        phase: &str,
+        method: Method,
+        key: &str,
+        body: Vec<u8>,
+    ) -> std::result::Result<(i32, Vec<u8>, u64), (Option<i32>, Failure)> {
+        let in_phase = |(status, category, message): Failure| (status, category, format!("{}: {}", phase, message));
+        let url = storage::object_url(self.endpoint, key)
+            .map_err(|e| (None, in_phase(("error", FailureCategory::Other, e.to_string()))))?;
+        let headers = storage::signed_headers(&self.credentials, self.region, method.as_str(), &url, &body, Utc::now())
+            .map_err(|e| (None, in_phase(("error", FailureCategory::Other, e.to_string()))))?;
+
+        let started = Instant::now();
+        let mut request = self.client.request(method, url).body(body);
+        for (name, value) in headers {
+            request = request.header(name, value);
+        }
+        let response = request.send().await.map_err(|e| (None, in_phase(clients::describe_error(&e))))?;
+        let code = response.status().as_u16();
+        let success = response.status().is_success();
+        let bytes = response
+            .bytes()
+            .await
+            .map_err(|e| (Some(code as i32), in_phase(clients::describe_error(&e))))?;
+        let elapsed = started.elapsed().as_millis() as u64;
+        if !success {
+            let message = storage::error_message(code, &String::from_utf8_lossy(&bytes));
+            return Err((Some(code as i32), in_phase(("failure", FailureCategory::HttpStatus, message))));
+        }
+        Ok((code as i32, bytes.to_vec(), elapsed))
+    }
+}
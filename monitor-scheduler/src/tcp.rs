@@ -0,0 +1,69 @@
+use monitor_core::{
+    durations::Seconds,
+    failures::FailureCategory,
+    models::{Monitor, MonitorResult},
+    tcp::{self, TcpCheck},
+};
+use serde_json::json;
+use std::time::{Duration, Instant};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
+
+use crate::brokers::{self, Outcome};
+
+/// Connects to the monitor's port, sends the payload if there is one and
+/// waits for the banner if one is expected. The connect time is kept in the
+/// result body.
+pub async fn run(monitor: &Monitor, check: &serde_json::Value, timeout: Seconds) -> MonitorResult {
+    let start_time = Instant::now();
+    let outcome = match serde_json::from_value::<TcpCheck>(check.clone()) {
+        Ok(check) => brokers::with_timeout(timeout, execute(monitor, &check)).await,
+        Err(e) => Err((None, ("error", FailureCategory::Other, format!("Invalid TCP check: {}", e)))),
+    };
+    brokers::into_result(monitor, start_time, outcome)
+}
+
+async fn execute(monitor: &Monitor, check: &TcpCheck) -> Outcome {
+    let (host, port) = tcp::target(&monitor.endpoint)
+        .map_err(|e| (None, ("error", FailureCategory::Other, e.to_string())))?;
+    let connect_error = |e: std::io::Error| {
+        let message = format!("Could not connect to {}:{}: {}", host, port, e);
+        (None, ("error", FailureCategory::for_connect_error(&[&message]), message))
+    };
+
+    let started = Instant::now();
+    let connect = TcpStream::connect((host.as_str(), port));
+    let mut stream = match monitor.connect_timeout_ms {
+        Some(ms) => tokio::time::timeout(Duration::from_millis(ms as u64), connect).await.map_err(|_| {
+            let message = format!("Connect timeout after {}ms", ms);
+            (None, ("timeout", FailureCategory::ConnectTimeout, message))
+        })?,
+        None => connect.await,
+    }
+    .map_err(connect_error)?;
+    let connect_ms = started.elapsed().as_millis() as i64;
+
+    if let Some(payload) = &check.payload {
+        stream.write_all(payload.as_bytes()).await.map_err(connect_error)?;
+    }
+    let mut body = json!({ "connect_ms": connect_ms });
+    if check.banner.is_some() {
+        // The outer timeout ends a wait for a banner that never comes
+        let mut received = Vec::new();
+        let mut chunk = [0; 1024];
+        while !check.banner_found(&received) && received.len() < tcp::MAX_READ_BYTES {
+            match stream.read(&mut chunk).await.map_err(connect_error)? {
+                0 => break,
+                read => received.extend_from_slice(&chunk[..read]),
+            }
+        }
+        received.truncate(tcp::MAX_READ_BYTES);
+        body["received"] = json!(String::from_utf8_lossy(&received));
+        check
+            .evaluate(&received)
+            .map_err(|message| (None, ("failure", FailureCategory::AssertionFailed, message)))?;
+    }
+    Ok((None, body))
+}
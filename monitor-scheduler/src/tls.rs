@@ -0,0 +1,182 @@
+use chrono::Utc;
+use monitor_core::{
+    durations::Seconds,
+    expirations,
+    failures::FailureCategory,
+    models::{Monitor, MonitorResult},
+    tls::{self, TlsCheck},
+};
+use serde_json::json;
+use std::{
+    future::Future,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tokio::net::TcpStream;
+use tokio_rustls::{
+    rustls::{
+        self,
+        client::{
+            danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+            WebPkiServerVerifier,
+        },
+        crypto::ring,
+        pki_types::{CertificateDer, ServerName, UnixTime},
+        ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme,
+    },
+    TlsConnector,
+};
+
+use crate::brokers::{self, Failure, Outcome};
+
+/// Completes a handshake with the monitor's server and checks how long its
+/// certificate has left. The chain is kept in the result body.
+pub async fn run(monitor: &Monitor, check: &serde_json::Value, timeout: Seconds) -> MonitorResult {
+    let start_time = Instant::now();
+    let outcome = match serde_json::from_value::<TlsCheck>(check.clone()) {
+        Ok(check) => brokers::with_timeout(timeout, execute(monitor, &check)).await,
+        Err(e) => Err((None, ("error", FailureCategory::Other, format!("Invalid TLS check: {}", e)))),
+    };
+    brokers::into_result(monitor, start_time, outcome)
+}
+
+async fn execute(monitor: &Monitor, check: &TlsCheck) -> Outcome {
+    let failed = |status, category, message: String| (None, (status, category, message));
+    let (host, port) = tls::target(&monitor.endpoint)
+        .map_err(|e| failed("error", FailureCategory::Other, e.to_string()))?;
+    let name = check.server_name.clone().unwrap_or_else(|| host.clone());
+    let server_name = ServerName::try_from(name.clone())
+        .map_err(|_| failed("error", FailureCategory::Other, format!("Invalid server name: {}", name)))?;
+
+    let tcp = within(monitor.connect_timeout_ms, "Connect", TcpStream::connect((host.as_str(), port)))
+        .await?
+        .map_err(|e| {
+            let message = format!("Could not connect to {}:{}: {}", host, port, e);
+            failed("error", FailureCategory::for_connect_error(&[&message]), message)
+        })?;
+    let verifier = Arc::new(
+        RecordingVerifier::new().map_err(|e| failed("error", FailureCategory::Other, e.to_string()))?,
+    );
+    let config = ClientConfig::builder_with_provider(Arc::new(ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .map_err(|e| failed("error", FailureCategory::Other, e.to_string()))?
+        .dangerous()
+        .with_custom_certificate_verifier(verifier.clone())
+        .with_no_client_auth();
+    let handshake = TlsConnector::from(Arc::new(config)).connect(server_name, tcp);
+    let stream = within(monitor.tls_timeout_ms, "TLS handshake", handshake)
+        .await?
+        .map_err(|e| failed("error", FailureCategory::TlsError, format!("TLS handshake with {} failed: {}", name, e)))?;
+
+    let chain = stream
+        .get_ref()
+        .1
+        .peer_certificates()
+        .unwrap_or_default()
+        .iter()
+        .map(|der| tls::certificate_info(der))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| failed("error", FailureCategory::TlsError, e.to_string()))?;
+    let Some(leaf) = chain.first() else {
+        return Err(failed("error", FailureCategory::TlsError, format!("{} sent no certificate", name)));
+    };
+
+    let now = Utc::now();
+    check
+        .evaluate(leaf, now)
+        .map_err(|message| failed("failure", FailureCategory::AssertionFailed, message))?;
+    if let Some(error) = verifier.error() {
+        return Err(failed("failure", FailureCategory::TlsError, format!("certificate not trusted: {}", error)));
+    }
+    Ok((
+        None,
+        json!({
+            "days_remaining": expirations::days_remaining(leaf.expires_at, now),
+            "expires_at": leaf.expires_at,
+            "subject": leaf.subject,
+            "issuer": leaf.issuer,
+            "sans": leaf.sans,
+            "chain": chain,
+        }),
+    ))
+}
+
+/// Runs `phase` within `limit_ms` when the monitor sets one; the overall
+/// timeout still applies otherwise.
+pub async fn within<T>(
+    limit_ms: Option<i32>,
+    phase: &str,
+    future: impl Future<Output = T>,
+) -> Result<T, (Option<i32>, Failure)> {
+    match limit_ms {
+        Some(ms) => tokio::time::timeout(Duration::from_millis(ms as u64), future).await.map_err(|_| {
+            let message = format!("{} timeout after {}ms", phase, ms);
+            (None, ("timeout", FailureCategory::ConnectTimeout, message))
+        }),
+        None => Ok(future.await),
+    }
+}
+
+/// Verifies the chain against the web PKI roots but lets the handshake
+/// finish either way, so expired or untrusted certificates can still be
+/// read and reported.
+#[derive(Debug)]
+struct RecordingVerifier {
+    inner: Arc<WebPkiServerVerifier>,
+    error: Mutex<Option<rustls::Error>>,
+}
+
+impl RecordingVerifier {
+    fn new() -> Result<Self, rustls::client::VerifierBuilderError> {
+        let mut roots = RootCertStore::empty();
+        roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+        let inner = WebPkiServerVerifier::builder_with_provider(Arc::new(roots), Arc::new(ring::default_provider()))
+            .build()?;
+        Ok(Self {
+            inner,
+            error: Mutex::new(None),
+        })
+    }
+
+    fn error(&self) -> Option<rustls::Error> {
+        self.error.lock().unwrap().clone()
+    }
+}
+
+impl ServerCertVerifier for RecordingVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        if let Err(e) = self.inner.verify_server_cert(end_entity, intermediates, server_name, ocsp_response, now) {
+            *self.error.lock().unwrap() = Some(e);
+        }
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls12_signature(message, cert, dss)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls13_signature(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.inner.supported_verify_schemes()
+    }
+}
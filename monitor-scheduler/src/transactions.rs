@@ -0,0 +1,152 @@
+use chrono::Utc;
+use monitor_core::{
+    assertions, clock,
+    durations::Seconds,
+    failures::FailureCategory,
+    models::{Monitor, MonitorResult},
+    transaction::{self, TransactionStep},
+    Result,
+};
+use monitor_scripting::models::HttpRequestSpec;
+use reqwest::header::{COOKIE, SET_COOKIE};
+use std::{
+    collections::{BTreeMap, HashMap},
+    time::Instant,
+};
+use uuid::Uuid;
+
+use crate::{clients::{self, HostClients}, pre_request, scripting::Scripting};
+
+/// Runs a multi-step transaction monitor. Steps share variables and a cookie jar;
+/// the timeout covers the whole transaction and the first failing step
+/// ends it.
+pub async fn run(
+    clients: &HostClients,
+    monitor: &Monitor,
+    steps: &serde_json::Value,
+    credentials: &HashMap<String, String>,
+    timeout: Seconds,
+    scripting: Option<&Scripting>,
+) -> Result<MonitorResult> {
+    let steps = transaction::parse_steps(steps)?;
+    let start_time = Instant::now();
+    let deadline = timeout.as_std();
+
+    let mut vars: HashMap<String, String> = HashMap::new();
+    let mut cookies: BTreeMap<String, String> = BTreeMap::new();
+    let mut last: Option<(i32, BTreeMap<String, String>, String)> = None;
+
+    for (index, step) in steps.iter().enumerate() {
+        let label = format!("Step {} ({})", index + 1, step.name);
+        let step_start = Instant::now();
+        let spec = request_spec(step, &vars, &cookies, credentials);
+        let request = match pre_request::build_request(clients, monitor, spec, scripting).await {
+            Ok(request) => request,
+            Err(e) => {
+                let failure = (FailureCategory::ScriptError, format!("{}: {}", label, e));
+                return Ok(finish(monitor, start_time, "error", last, Some(failure)));
+            }
+        };
+
+        let response = match tokio::time::timeout(deadline.saturating_sub(start_time.elapsed()), request.send()).await {
+            Ok(Ok(response)) => response,
+            Ok(Err(e)) => {
+                let (status, category, message) = clients::describe_error(&e);
+                return Ok(finish(monitor, start_time, status, last, Some((category, format!("{}: {}", label, message)))));
+            }
+            Err(_) => {
+                let failure = (FailureCategory::RequestTimeout, format!("{}: Request timeout", label));
+                return Ok(finish(monitor, start_time, "timeout", last, Some(failure)));
+            }
+        };
+
+        let status_code = response.status().as_u16() as i32;
+        transaction::merge_set_cookies(
+            &mut cookies,
+            response.headers().get_all(SET_COOKIE).iter().filter_map(|v| v.to_str().ok()),
+        );
+        let response_headers = clients::response_headers(response.headers());
+        let headers: HashMap<String, String> = response_headers.clone().into_iter().collect();
+        let body = response.text().await.unwrap_or_default();
+        last = Some((status_code, response_headers, body));
+
+        if status_code != step.expected_status {
+            let error = format!("{}: expected status {}, got {}", label, step.expected_status, status_code);
+            return Ok(finish(monitor, start_time, "failure", last, Some((FailureCategory::HttpStatus, error))));
+        }
+
+        if !step.assertions.is_empty() {
+            // Assertions see this step's response, timed from its own start
+            let response = finish(monitor, step_start, "success", last.clone(), None);
+            if let Err(reason) = assertions::evaluate_all(&step.assertions, &response) {
+                let failure = (FailureCategory::AssertionFailed, format!("{}: {}", label, reason));
+                return Ok(finish(monitor, start_time, "failure", last, Some(failure)));
+            }
+        }
+
+        let body = last.as_ref().map(|(_, _, body)| body.as_str()).unwrap_or_default();
+        match transaction::extract_variables(step, &headers, body) {
+            Ok(extracted) => vars.extend(extracted),
+            Err(e) => {
+                let failure = (FailureCategory::AssertionFailed, format!("{}: {}", label, e));
+                return Ok(finish(monitor, start_time, "failure", last, Some(failure)));
+            }
+        }
+    }
+
+    Ok(finish(monitor, start_time, "success", last, None))
+}
+
+fn request_spec(
+    step: &TransactionStep,
+    vars: &HashMap<String, String>,
+    cookies: &BTreeMap<String, String>,
+    credentials: &HashMap<String, String>,
+) -> HttpRequestSpec {
+    let mut headers: BTreeMap<String, String> = step
+        .headers
+        .iter()
+        .map(|(key, value)| (key.clone(), transaction::substitute(value, vars)))
+        .collect();
+    headers.extend(credentials.clone());
+    if let Some(cookie) = transaction::cookie_header(cookies) {
+        headers.insert(COOKIE.to_string(), cookie);
+    }
+
+    HttpRequestSpec {
+        method: step.method.clone(),
+        url: transaction::substitute(&step.url, vars),
+        headers,
+        body: step.body.as_ref().map(|body| transaction::substitute(body, vars)),
+    }
+}
+
+fn finish(
+    monitor: &Monitor,
+    start_time: Instant,
+    status: &str,
+    last: Option<(i32, BTreeMap<String, String>, String)>,
+    failure: Option<(FailureCategory, String)>,
+) -> MonitorResult {
+    let (response_code, response_headers, response_body) = match last {
+        Some((code, headers, body)) => (Some(code), Some(headers), Some(body)),
+        None => (None, None, None),
+    };
+    let (error_category, error_message) = failure.map(|(category, message)| (category.as_str().to_string(), message)).unzip();
+    MonitorResult {
+        id: Uuid::new_v4(),
+        monitor_id: monitor.id,
+        status: status.to_string(),
+        response_time: start_time.elapsed().as_millis() as i32,
+        response_code,
+        response_body,
+        response_headers,
+        error_message,
+        error_category,
+        error_hint: None,
+        checked_at: Utc::now(),
+        clock_skew_ms: clock::current_offset_ms(),
+        schedule_lag_ms: None,
+        attempts: 1,
+    }
+}
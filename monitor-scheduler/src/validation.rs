@@ -0,0 +1,22 @@
+use monitor_core::{models::Monitor, Error, Result};
+use monitor_scripting::models::{ValidationContext, ValidationResult};
+
+use crate::scripting::Scripting;
+
+/// Runs the monitor's validation script against a response. `scripting` is
+/// `None` when scripting is disabled, in which case the check is an error
+/// rather than passing unvalidated.
+pub async fn validate(
+    monitor: &Monitor,
+    script: &str,
+    context: ValidationContext,
+    scripting: Option<&Scripting>,
+) -> Result<ValidationResult> {
+    let scripting =
+        scripting.ok_or_else(|| Error::script_execution("Scripting is disabled, validation script not run"))?;
+    let engine = scripting.engine().await?;
+    engine
+        .execute_validation_script(script, &context)
+        .await
+        .map_err(|e| Error::script_execution(format!("Validation script for {} failed: {}", monitor.name, e)))
+}
@@ -0,0 +1,195 @@
+use chrono::Utc;
+use monitor_core::{
+    alerts::{AlertNotification, AlertState},
+    correlation::{CorrelatedIncident, DownMonitor},
+    crypto::KeyRing,
+    db::DatabasePool,
+    models::{Monitor, MonitorResult, WebhookEndpoint},
+    states::StateEvent,
+    webhook::{self, SIGNATURE_HEADER},
+    Result,
+};
+use reqwest::Client;
+use serde_json::json;
+use std::time::Duration;
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Sent when the monitor's health changes or it starts or stops flapping,
+/// with the result that caused it.
+pub async fn deliver_state(
+    db: &DatabasePool,
+    client: &Client,
+    keys: &KeyRing,
+    monitor: &Monitor,
+    result: &MonitorResult,
+    event: StateEvent,
+    channels: &[Uuid],
+) -> Result<()> {
+    // Notification channels from the monitor's settings replace its own endpoints
+    let endpoints = if channels.is_empty() {
+        sqlx::query_as::<_, WebhookEndpoint>(
+            "SELECT * FROM webhook_endpoints WHERE enabled = true AND (monitor_id IS NULL OR monitor_id = $1)",
+        )
+        .bind(monitor.id)
+        .fetch_all(db)
+        .await?
+    } else {
+        sqlx::query_as::<_, WebhookEndpoint>(
+            "SELECT * FROM webhook_endpoints WHERE enabled = true AND id = ANY($1)",
+        )
+        .bind(channels)
+        .fetch_all(db)
+        .await?
+    };
+
+    if endpoints.is_empty() {
+        return Ok(());
+    }
+
+    let monitor_json = json!({
+        "id": monitor.id,
+        "name": monitor.name,
+        "endpoint": monitor.endpoint,
+    });
+    let (payload, subject) = match event {
+        StateEvent::Changed(transition) => (
+            json!({
+                "event": "monitor.state",
+                "monitor": monitor_json,
+                "from": transition.from,
+                "to": transition.to,
+                "result": result,
+            }),
+            format!("{} is {}", monitor.name, transition.to.as_str()),
+        ),
+        StateEvent::FlappingStarted { transitions, window_secs } => (
+            json!({
+                "event": "monitor.flapping",
+                "monitor": monitor_json,
+                "transitions": transitions,
+                "window_secs": window_secs,
+                "result": result,
+            }),
+            format!("{} is flapping", monitor.name),
+        ),
+        StateEvent::Stabilized(settled) => (
+            json!({
+                "event": "monitor.stable",
+                "monitor": monitor_json,
+                "state": settled,
+                "result": result,
+            }),
+            format!("{} is stable and {}", monitor.name, settled.as_str()),
+        ),
+    };
+
+    send(client, keys, endpoints, &serde_json::to_vec(&payload)?, &subject).await;
+    Ok(())
+}
+
+/// Sends one notification for a correlated incident to the endpoints that are
+/// not tied to a single monitor.
+pub async fn deliver_incident(
+    db: &DatabasePool,
+    client: &Client,
+    keys: &KeyRing,
+    event: &str,
+    incident: &CorrelatedIncident,
+    monitors: &[&DownMonitor],
+) -> Result<()> {
+    let endpoints = sqlx::query_as::<_, WebhookEndpoint>(
+        "SELECT * FROM webhook_endpoints WHERE enabled = true AND monitor_id IS NULL",
+    )
+    .fetch_all(db)
+    .await?;
+
+    if endpoints.is_empty() {
+        return Ok(());
+    }
+
+    let payload = serde_json::to_vec(&json!({
+        "event": event,
+        "incident": incident,
+        "monitors": monitors
+            .iter()
+            .map(|m| json!({ "id": m.monitor_id, "name": m.name, "endpoint": m.endpoint }))
+            .collect::<Vec<_>>(),
+    }))?;
+
+    let subject = format!("{} {}", incident.kind, incident.group_key);
+    send(client, keys, endpoints, &payload, &subject).await;
+    Ok(())
+}
+
+/// Alerts whose rule has no channels of its own go to the monitor's endpoints.
+pub async fn deliver_alert(
+    db: &DatabasePool,
+    client: &Client,
+    keys: &KeyRing,
+    notification: &AlertNotification,
+) -> Result<()> {
+    let endpoints = sqlx::query_as::<_, WebhookEndpoint>(
+        "SELECT * FROM webhook_endpoints WHERE enabled = true AND (monitor_id IS NULL OR monitor_id = $1)",
+    )
+    .bind(notification.monitor_id)
+    .fetch_all(db)
+    .await?;
+
+    if endpoints.is_empty() {
+        return Ok(());
+    }
+
+    let event = match notification.state {
+        AlertState::Firing => "alert.firing",
+        AlertState::Resolved => "alert.resolved",
+    };
+    let payload = serde_json::to_vec(&json!({
+        "event": event,
+        "alert": notification,
+    }))?;
+
+    send(client, keys, endpoints, &payload, &notification.subject()).await;
+    Ok(())
+}
+
+async fn send(client: &Client, keys: &KeyRing, endpoints: Vec<WebhookEndpoint>, payload: &[u8], subject: &str) {
+    for endpoint in endpoints {
+        let secret = match keys.decrypt(&endpoint.secret) {
+            Ok(secret) => secret,
+            Err(e) => {
+                warn!("Skipping webhook {}: {}", endpoint.url, e);
+                continue;
+            }
+        };
+        let signature = webhook::sign(&secret, Utc::now().timestamp(), payload);
+
+        let response = client
+            .post(&endpoint.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(SIGNATURE_HEADER, signature)
+            .timeout(DELIVERY_TIMEOUT)
+            .body(payload.to_vec())
+            .send()
+            .await;
+
+        match response {
+            Ok(response) if response.status().is_success() => {
+                debug!("Delivered webhook for {} to {}", subject, endpoint.url);
+            }
+            Ok(response) => {
+                warn!(
+                    "Webhook {} rejected notification for {}: HTTP {}",
+                    endpoint.url,
+                    subject,
+                    response.status()
+                );
+            }
+            Err(e) => {
+                warn!("Webhook delivery to {} failed: {}", endpoint.url, e);
+            }
+        }
+    }
+}
@@ -0,0 +1,196 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use monitor_core::{
+    durations::Seconds,
+    failures::FailureCategory,
+    headers::{self, Headers},
+    models::{Monitor, MonitorResult},
+    websocket::{self, Target, WebSocketCheck},
+};
+use serde_json::json;
+use std::{collections::HashMap, sync::Arc, time::Instant};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    net::TcpStream,
+};
+use tokio_rustls::{
+    rustls::{self, pki_types::ServerName, ClientConfig, RootCertStore},
+    TlsConnector,
+};
+use uuid::Uuid;
+
+use crate::{
+    brokers::{self, Outcome},
+    tls::within,
+};
+
+/// Longest handshake response head the check reads
+const MAX_HEAD_BYTES: usize = 16 * 1024;
+
+/// Opens a WebSocket to the monitor's endpoint, sends the message if there
+/// is one and checks the first reply. Handshake and echo times are kept in
+/// the result body.
+pub async fn run(
+    monitor: &Monitor,
+    check: &serde_json::Value,
+    credentials: &HashMap<String, String>,
+    timeout: Seconds,
+) -> MonitorResult {
+    let start_time = Instant::now();
+    let outcome = match serde_json::from_value::<WebSocketCheck>(check.clone()) {
+        Ok(check) => brokers::with_timeout(timeout, execute(monitor, &check, credentials)).await,
+        Err(e) => Err((None, ("error", FailureCategory::Other, format!("Invalid WebSocket check: {}", e)))),
+    };
+    brokers::into_result(monitor, start_time, outcome)
+}
+
+async fn execute(monitor: &Monitor, check: &WebSocketCheck, credentials: &HashMap<String, String>) -> Outcome {
+    let failed = |status, category, message: String| (None, (status, category, message));
+    let target = websocket::target(&monitor.endpoint)
+        .map_err(|e| failed("error", FailureCategory::Other, e.to_string()))?;
+    let mut headers = match &monitor.headers {
+        Some(value) => headers::from_value(value).map_err(|e| failed("error", FailureCategory::Other, e.to_string()))?,
+        None => Headers::new(),
+    };
+    headers.extend(credentials.iter().map(|(name, value)| (name.clone(), value.clone())));
+
+    let started = Instant::now();
+    let tcp = within(monitor.connect_timeout_ms, "Connect", TcpStream::connect((target.host.as_str(), target.port)))
+        .await?
+        .map_err(|e| {
+            let message = format!("Could not connect to {}:{}: {}", target.host, target.port, e);
+            failed("error", FailureCategory::for_connect_error(&[&message]), message)
+        })?;
+    if !target.secure {
+        return exchange(tcp, &target, check, &headers, started).await;
+    }
+
+    let server_name = ServerName::try_from(target.host.clone())
+        .map_err(|_| failed("error", FailureCategory::Other, format!("Invalid server name: {}", target.host)))?;
+    let connector = connector().map_err(|e| failed("error", FailureCategory::Other, e.to_string()))?;
+    let stream = within(monitor.tls_timeout_ms, "TLS handshake", connector.connect(server_name, tcp))
+        .await?
+        .map_err(|e| {
+            let message = format!("TLS handshake with {} failed: {}", target.host, e);
+            failed("error", FailureCategory::TlsError, message)
+        })?;
+    exchange(stream, &target, check, &headers, started).await
+}
+
+async fn exchange<S: AsyncRead + AsyncWrite + Unpin>(
+    mut stream: S,
+    target: &Target,
+    check: &WebSocketCheck,
+    headers: &Headers,
+    started: Instant,
+) -> Outcome {
+    let closed = |e: std::io::Error| {
+        let message = format!("Connection to {} failed: {}", target.authority, e);
+        (None, ("error", FailureCategory::ConnectError, message))
+    };
+    let key = STANDARD.encode(Uuid::new_v4().as_bytes());
+    let request = websocket::handshake_request(target, &key, check.subprotocol.as_deref(), headers);
+    stream.write_all(request.as_bytes()).await.map_err(closed)?;
+
+    let (head, mut buffer) = read_head(&mut stream).await.map_err(closed)?;
+    let status = websocket::handshake_status(&head);
+    if let Err(message) = websocket::verify_handshake(&head, &key) {
+        return Err(match status {
+            Some(code) if code != 101 => (Some(code), ("failure", FailureCategory::HttpStatus, message)),
+            _ => (status, ("error", FailureCategory::Other, message)),
+        });
+    }
+    let mut body = json!({ "handshake_ms": started.elapsed().as_millis() as i64 });
+
+    if check.waits_for_reply() {
+        let sent = Instant::now();
+        if let Some(message) = &check.message {
+            let frame = websocket::encode_frame(websocket::OPCODE_TEXT, message.as_bytes(), mask());
+            stream.write_all(&frame).await.map_err(closed)?;
+        }
+        // The outer timeout ends a wait for a reply that never comes
+        let reply = read_message(&mut stream, &mut buffer)
+            .await
+            .map_err(|message| (status, ("error", FailureCategory::ConnectError, message)))?;
+        body["echo_ms"] = json!(sent.elapsed().as_millis() as i64);
+        body["reply"] = json!(websocket::quote(&reply));
+        check
+            .evaluate(&reply)
+            .map_err(|message| (status, ("failure", FailureCategory::AssertionFailed, message)))?;
+    }
+
+    // Closing politely is best effort; the check already passed
+    let close = websocket::encode_frame(websocket::OPCODE_CLOSE, &1000u16.to_be_bytes(), mask());
+    let _ = stream.write_all(&close).await;
+    Ok((status, body))
+}
+
+/// The response head up to the blank line, and whatever followed it.
+async fn read_head<S: AsyncRead + Unpin>(stream: &mut S) -> std::io::Result<(String, Vec<u8>)> {
+    let mut received = Vec::new();
+    let mut chunk = [0; 1024];
+    loop {
+        if let Some(end) = received.windows(4).position(|window| window == b"\r\n\r\n") {
+            let rest = received.split_off(end + 4);
+            return Ok((String::from_utf8_lossy(&received).into_owned(), rest));
+        }
+        if received.len() > MAX_HEAD_BYTES {
+            return Err(std::io::Error::other("the handshake response is too long"));
+        }
+        match stream.read(&mut chunk).await? {
+            0 => return Err(std::io::ErrorKind::UnexpectedEof.into()),
+            read => received.extend_from_slice(&chunk[..read]),
+        }
+    }
+}
+
+/// The first text or binary message, joined from its fragments. Pings are
+/// answered while waiting.
+async fn read_message<S: AsyncRead + AsyncWrite + Unpin>(
+    stream: &mut S,
+    buffer: &mut Vec<u8>,
+) -> Result<Vec<u8>, String> {
+    let mut message = Vec::new();
+    let mut chunk = [0; 4096];
+    loop {
+        let Some((frame, used)) = websocket::decode_frame(buffer)? else {
+            match stream.read(&mut chunk).await.map_err(|e| e.to_string())? {
+                0 => return Err("the server closed the connection before replying".to_string()),
+                read => buffer.extend_from_slice(&chunk[..read]),
+            }
+            continue;
+        };
+        buffer.drain(..used);
+        match frame.opcode {
+            websocket::OPCODE_TEXT | websocket::OPCODE_BINARY | websocket::OPCODE_CONTINUATION => {
+                message.extend_from_slice(&frame.payload);
+                if message.len() > websocket::MAX_REPLY_BYTES {
+                    return Err(format!("the reply is longer than {} bytes", websocket::MAX_REPLY_BYTES));
+                }
+                if frame.fin {
+                    return Ok(message);
+                }
+            }
+            websocket::OPCODE_PING => {
+                let pong = websocket::encode_frame(websocket::OPCODE_PONG, &frame.payload, mask());
+                stream.write_all(&pong).await.map_err(|e| e.to_string())?;
+            }
+            websocket::OPCODE_CLOSE => return Err("the server closed the connection before replying".to_string()),
+            _ => {}
+        }
+    }
+}
+
+fn mask() -> [u8; 4] {
+    let bytes = Uuid::new_v4().into_bytes();
+    [bytes[12], bytes[13], bytes[14], bytes[15]]
+}
+
+fn connector() -> Result<TlsConnector, rustls::Error> {
+    let mut roots = RootCertStore::empty();
+    roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+    let config = ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+        .with_safe_default_protocol_versions()?
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    Ok(TlsConnector::from(Arc::new(config)))
+}
@@ -1,12 +1,22 @@
-use monitor_core::{Error, Result};
+use monitor_core::{Error, Result, error_reporting, metrics};
 /// 引擎核心模块
 ///
 /// 提供JavaScript脚本执行环境，支持脚本验证、超时控制和错误处理
 use rquickjs::{Context, Runtime, Value as JsValue, Ctx};
 use serde_json::{Value, json};
+use std::sync::Once;
 use std::time::{Duration, Instant};
 
-use crate::models::{ScriptResult, SecurityConfig, ValidationContext, ValidationResult};
+use crate::models::{
+    HelperFunction, PreRequestChanges, PreRequestContext, ScriptCapabilities, ScriptLimits,
+    ScriptResult, SecurityConfig, ValidationContext, ValidationResult,
+};
+
+/// 注入到每个脚本上下文中的工具函数源码
+const UTILITY_FUNCTIONS: &str = include_str!("utility_functions.js");
+
+/// 脚本上下文中可用的宿主对象
+const HOST_OBJECTS: &[&str] = &["context", "console", "performance"];
 
 /// JavaScript脚本执行引擎
 ///
@@ -23,8 +33,12 @@ use crate::models::{ScriptResult, SecurityConfig, ValidationContext, ValidationR
 ///
 /// # 示例
 /// ```
+/// # use monitor_scripting::engine::ScriptEngine;
+/// # use serde_json::json;
+/// # async fn example() {
 /// let engine = ScriptEngine::new().unwrap();
 /// let result = engine.execute_script("1 + 1", &json!({})).await;
+/// # }
 /// ```
 pub struct ScriptEngine {
     /// JavaScript运行时实例
@@ -124,7 +138,7 @@ impl ScriptEngine {
         let ctx = Context::full(&self.runtime)
             .map_err(|e| Error::script_execution(format!("Failed to create context: {}", e)))?;
 
-        let result: Result<ScriptResult> = ctx.with(|ctx| {
+        let mut result: Result<ScriptResult> = ctx.with(|ctx| {
             // Set up the context with monitor data
             let global = ctx.globals();
 
@@ -136,6 +150,9 @@ impl ScriptEngine {
                 )));
             }
 
+            // 注册摘要、签名等原生宿主函数
+            crate::host_functions::register(&ctx)?;
+
             // Add context data
             if let Ok(context_str) = serde_json::to_string(context_data) {
                 let _ = ctx.eval::<(), _>(format!("const context = {}", context_str));
@@ -165,12 +182,16 @@ impl ScriptEngine {
                         result: Some(result_value),
                         error: None,
                         execution_time_ms: execution_time.as_millis() as u64,
-                        memory_usage: None, // Could be enhanced with memory tracking
+                        memory_usage: None,
                     })
                 }
                 Err(e) => {
                     let execution_time = start_time.elapsed();
-                    let error_details = self.extract_detailed_error(&e, script);
+                    let thrown = matches!(e, rquickjs::Error::Exception).then(|| ctx.catch());
+                    let error_details = thrown
+                        .as_ref()
+                        .and_then(|thrown| self.describe_exception(thrown))
+                        .unwrap_or_else(|| self.extract_detailed_error(&e, script));
                     Ok(ScriptResult {
                         success: false,
                         result: None,
@@ -182,9 +203,84 @@ impl ScriptEngine {
             }
         });
 
+        // 运行时内存统计需要在上下文锁释放后读取
+        if let Ok(script_result) = result.as_mut() {
+            script_result.memory_usage = self.get_memory_usage().map(|m| m as u64);
+        }
+
+        match &result {
+            Ok(script_result) => self.record_metrics(script_result, start_time.elapsed()),
+            Err(e) => {
+                self.record_metrics_for_engine_error(start_time.elapsed());
+                error_reporting::capture_message(&format!("Script engine error: {}", e), "scripting.engine");
+            }
+        }
+
         result.map_err(|e| Error::script_execution(format!("Script execution failed: {}", e)))
     }
 
+    /// 将脚本执行结果上报到共享指标模块
+    ///
+    /// # 参数
+    /// * `script_result` - 脚本执行结果
+    /// * `elapsed` - 实际执行耗时
+    ///
+    /// # 实现逻辑
+    /// 1. 按安全配置档案统计执行次数和耗时分布
+    /// 2. 失败时按错误类型分类计数，超时单独计数
+    /// 3. 记录运行时内存使用峰值
+    fn record_metrics(&self, script_result: &ScriptResult, elapsed: Duration) {
+        describe_metrics();
+        let registry = metrics::global();
+        let profile = self.security_config.profile.as_str();
+        let labels = [("profile", profile)];
+
+        registry.increment_counter(METRIC_EXECUTIONS, &labels);
+        registry.observe_histogram(METRIC_DURATION, &labels, elapsed.as_secs_f64());
+
+        if let Some(memory) = script_result.memory_usage {
+            registry.set_gauge_max(METRIC_MEMORY_PEAK, &labels, memory as f64);
+        }
+
+        if !script_result.success {
+            let error_type = script_result
+                .error
+                .as_ref()
+                .map(|e| self.classify_error(e, elapsed))
+                .unwrap_or("unknown");
+            registry.increment_counter(
+                METRIC_FAILURES,
+                &[("profile", profile), ("error_type", error_type)],
+            );
+            if error_type == "timeout" {
+                registry.increment_counter(METRIC_TIMEOUTS, &labels);
+            }
+        }
+    }
+
+    /// 记录引擎自身错误（如上下文创建、安全策略加载失败）的指标
+    fn record_metrics_for_engine_error(&self, elapsed: Duration) {
+        describe_metrics();
+        let registry = metrics::global();
+        let profile = self.security_config.profile.as_str();
+
+        registry.increment_counter(METRIC_EXECUTIONS, &[("profile", profile)]);
+        registry.observe_histogram(METRIC_DURATION, &[("profile", profile)], elapsed.as_secs_f64());
+        registry.increment_counter(
+            METRIC_FAILURES,
+            &[("profile", profile), ("error_type", "engine_error")],
+        );
+    }
+
+    /// 根据错误详情和执行耗时确定错误类型标签
+    fn classify_error<'a>(&self, error: &'a Value, elapsed: Duration) -> &'a str {
+        let message = error.get("message").and_then(|v| v.as_str()).unwrap_or("");
+        if elapsed >= self.timeout || message.to_lowercase().contains("timeout") {
+            return "timeout";
+        }
+        error.get("type").and_then(|v| v.as_str()).unwrap_or("unknown")
+    }
+
     /// 创建带有元数据的脚本包装器，用于增强错误报告和超时处理
     ///
     /// # 参数
@@ -224,9 +320,61 @@ impl ScriptEngine {
     /// # 实现逻辑
     /// 从外部文件加载工具函数
     fn get_utility_functions(&self) -> String {
-        // Load utility functions from an external file
-        let utility_script = include_str!("utility_functions.js");
-        utility_script.to_string()
+        UTILITY_FUNCTIONS.to_string()
+    }
+
+    /// 获取当前引擎的脚本能力描述
+    ///
+    /// # 返回值
+    /// 返回基于当前安全配置和超时时间生成的ScriptCapabilities
+    pub fn capabilities(&self) -> ScriptCapabilities {
+        Self::describe_capabilities(&self.security_config, self.timeout)
+    }
+
+    /// 根据安全配置和超时时间生成脚本能力描述，无需创建运行时
+    ///
+    /// # 参数
+    /// * `security_config` - 安全配置
+    /// * `timeout` - 脚本执行的最大允许时间
+    ///
+    /// # 实现逻辑
+    /// 1. 从工具函数源码中解析顶层函数声明及其JSDoc说明
+    /// 2. 剔除被安全策略禁用的函数
+    /// 3. 汇总限制和禁用列表
+    pub fn describe_capabilities(
+        security_config: &SecurityConfig,
+        timeout: Duration,
+    ) -> ScriptCapabilities {
+        let helper_functions = parse_helper_functions(UTILITY_FUNCTIONS)
+            .into_iter()
+            .filter(|f| !security_config.denied_functions.contains(&f.name))
+            .collect();
+
+        let mut denied_functions: Vec<String> =
+            security_config.denied_functions.iter().cloned().collect();
+        denied_functions.sort();
+        let mut denied_properties: Vec<String> =
+            security_config.denied_properties.iter().cloned().collect();
+        denied_properties.sort();
+
+        ScriptCapabilities {
+            profile: security_config.profile.clone(),
+            helper_functions,
+            host_objects: HOST_OBJECTS.iter().map(|o| o.to_string()).collect(),
+            limits: ScriptLimits {
+                memory_limit_bytes: security_config.memory_limit,
+                stack_size_bytes: security_config.stack_size,
+                timeout_ms: timeout.as_millis() as u64,
+                max_loop_iterations: security_config.max_loop_iterations,
+                max_recursion_depth: security_config.max_recursion_depth,
+            },
+            denied_functions,
+            denied_properties,
+            eval_disabled: security_config.disable_eval,
+            function_constructor_disabled: security_config.disable_function_constructor,
+            modules_disabled: security_config.disable_modules,
+            strict_mode: security_config.enable_strict_mode,
+        }
     }
 
     /// 提取详细的错误信息
@@ -268,6 +416,33 @@ impl ScriptEngine {
         }
     }
 
+    /// 读取脚本抛出的错误对象
+    ///
+    /// # 参数
+    /// * `thrown` - 通过 `ctx.catch()` 取得的异常值
+    ///
+    /// # 返回值
+    /// 返回包含错误名称（如 `AssertionError`）和消息的JSON对象，
+    /// 抛出的不是带消息的对象时返回None
+    fn describe_exception(&self, thrown: &JsValue) -> Option<Value> {
+        let object = thrown.as_object()?;
+        let message: String = object.get("message").ok()?;
+        let name: String = object.get("name").unwrap_or_else(|_| "Error".to_string());
+        let error_type = match name.as_str() {
+            "SyntaxError" => "syntax_error",
+            "ReferenceError" => "reference_error",
+            "TypeError" => "type_error",
+            // Interrupts (timeouts), stack overflows and out-of-memory
+            "InternalError" => "runtime_error",
+            _ => "exception",
+        };
+        Some(json!({
+            "type": error_type,
+            "name": name,
+            "message": message,
+        }))
+    }
+
     /// 解析错误消息并生成详细的错误信息
     ///
     /// # 参数
@@ -349,7 +524,7 @@ impl ScriptEngine {
                 json!({
                     "line": line_num,
                     "content": line,
-                    "is_error": highlight.map_or(false, |h| h == line_num - 1)
+                    "is_error": highlight.is_some_and(|h| h == line_num - 1)
                 })
             })
             .collect();
@@ -385,7 +560,7 @@ impl ScriptEngine {
                 (function() {{
                     const originalFunc = globalThis['{}'];
                     globalThis['{}'] = function() {{
-                        throw new Error('{}');
+                        throw new Error("{}");
                     }};
                     // 也尝试在window对象上禁用（如果存在）
                     if (typeof window !== 'undefined') {{
@@ -529,9 +704,8 @@ impl ScriptEngine {
     /// # 注意
     /// 这个功能依赖于QuickJS的内存统计功能
     pub fn get_memory_usage(&self) -> Option<usize> {
-        // QuickJS的rquickjs绑定可能不直接暴露内存使用情况
-        // 这里返回None，但可以在未来版本中实现
-        None
+        let usage = self.runtime.memory_usage();
+        usize::try_from(usage.memory_used_size).ok()
     }
 
     /// 执行验证脚本
@@ -546,7 +720,8 @@ impl ScriptEngine {
     /// # 实现逻辑
     /// 1. 将响应数据序列化为JSON
     /// 2. 执行验证脚本
-    /// 3. 根据执行结果生成验证结果
+    /// 3. 根据执行结果生成验证结果：返回 `{ passed, message }` 对象时以其为明确判定，
+    ///    否则未抛出异常且返回值为真即通过
     pub async fn execute_validation_script(
         &self,
         script: &str,
@@ -557,43 +732,173 @@ impl ScriptEngine {
 
         let script_result = self.execute_script(script, &context_json).await?;
 
-        let (passed, message) = if script_result.success {
-            // For validation scripts, we consider it passed if:
-            // 1. No exception was thrown
-            // 2. The result is truthy (if it's a boolean/value)
-            let result_is_truthy = script_result
-                .result
-                .clone()
-                .map(|v| match v {
-                    Value::Bool(b) => b,
-                    Value::Null => false,
-                    Value::Number(n) => n.as_f64().unwrap_or(0.0) != 0.0,
-                    Value::String(s) => !s.is_empty(),
-                    Value::Array(a) => !a.is_empty(),
-                    Value::Object(_) => true,
-                })
-                .unwrap_or(true);
-
-            (result_is_truthy, "Validation passed".to_string())
+        let (passed, message, verdict) = if script_result.success {
+            match script_result.result.as_ref() {
+                // `{ passed, message }` is an explicit verdict
+                Some(Value::Object(object)) if let Some(passed) = object.get("passed").and_then(Value::as_bool) => {
+                    let message = object
+                        .get("message")
+                        .and_then(Value::as_str)
+                        .map(str::to_string)
+                        .unwrap_or_else(|| if passed { "Validation passed" } else { "Validation failed" }.to_string());
+                    (passed, message, true)
+                }
+                // Otherwise it passed if no exception was thrown and the
+                // result, if any, is truthy
+                result => {
+                    let result_is_truthy = result
+                        .map(|v| match v {
+                            Value::Bool(b) => *b,
+                            Value::Null => false,
+                            Value::Number(n) => n.as_f64().unwrap_or(0.0) != 0.0,
+                            Value::String(s) => !s.is_empty(),
+                            Value::Array(a) => !a.is_empty(),
+                            Value::Object(_) => true,
+                        })
+                        .unwrap_or(true);
+                    let message = match result {
+                        _ if result_is_truthy => "Validation passed".to_string(),
+                        Some(value) => format!("Validation script returned {}", value),
+                        None => "Validation failed".to_string(),
+                    };
+                    (result_is_truthy, message, false)
+                }
+            }
         } else {
-            let error_message = script_result
-                .error
-                .as_ref()
+            let error = script_result.error.as_ref();
+            let message = error
                 .and_then(|e| e.get("message"))
                 .and_then(|v| v.as_str())
-                .unwrap_or("Script execution failed")
-                .to_string();
-            (false, error_message)
+                .unwrap_or("Script execution failed");
+            let message = match error.and_then(|e| e.get("name")).and_then(|v| v.as_str()) {
+                Some(name) => format!("{}: {}", name, message),
+                None => message.to_string(),
+            };
+            (false, message, false)
         };
 
         Ok(ValidationResult {
             passed,
             message,
+            verdict,
             details: script_result.result,
             error_details: script_result.error,
             execution_time_ms: script_result.execution_time_ms,
         })
     }
+
+    /// 执行预请求脚本
+    ///
+    /// # 参数
+    /// * `script` - 预请求脚本代码，可返回 `{ headers, query, body }` 对象
+    /// * `request_context` - 监控配置和即将发送的请求
+    ///
+    /// # 返回值
+    /// 返回脚本要求对请求做出的修改；脚本没有返回值时不做修改
+    ///
+    /// # 实现逻辑
+    /// 1. 将监控配置和请求序列化为上下文数据
+    /// 2. 执行脚本，执行失败时返回错误
+    /// 3. 解析返回对象中的请求头、查询参数和请求体
+    pub async fn execute_pre_request_script(
+        &self,
+        script: &str,
+        request_context: &PreRequestContext,
+    ) -> Result<PreRequestChanges> {
+        let context_json = serde_json::to_value(request_context)
+            .map_err(|e| Error::script_execution(format!("Failed to serialize context: {}", e)))?;
+
+        let script_result = self.execute_script(script, &context_json).await?;
+        if !script_result.success {
+            let message = script_result
+                .error
+                .as_ref()
+                .and_then(|e| e.get("message"))
+                .and_then(|v| v.as_str())
+                .unwrap_or("Script execution failed");
+            return Err(Error::script_execution(format!("Pre-request script failed: {}", message)));
+        }
+
+        PreRequestChanges::from_script_result(script_result.result.as_ref())
+    }
+}
+
+/// 从工具函数源码中解析顶层函数声明
+///
+/// # 参数
+/// * `source` - 工具函数JavaScript源码
+///
+/// # 返回值
+/// 返回按声明顺序排列的函数描述列表
+///
+/// # 实现逻辑
+/// 1. 跟踪最近一个JSDoc块的首行说明
+/// 2. 遇到行首的`function name(params)`时生成描述
+fn parse_helper_functions(source: &str) -> Vec<HelperFunction> {
+    let mut helpers = Vec::new();
+    let mut pending_description: Option<String> = None;
+    let mut in_doc = false;
+
+    for line in source.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("/**") {
+            in_doc = true;
+            pending_description = None;
+            continue;
+        }
+        if in_doc {
+            if trimmed.starts_with("*/") {
+                in_doc = false;
+            } else if pending_description.is_none() {
+                let text = trimmed.trim_start_matches('*').trim();
+                if !text.is_empty() && !text.starts_with('@') {
+                    pending_description = Some(text.to_string());
+                }
+            }
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix("function ")
+            && let Some((name, rest)) = rest.split_once('(')
+            && let Some((params, _)) = rest.split_once(')')
+        {
+            helpers.push(HelperFunction {
+                name: name.trim().to_string(),
+                params: params
+                    .split(',')
+                    .map(|p| p.trim().to_string())
+                    .filter(|p| !p.is_empty())
+                    .collect(),
+                description: pending_description.take().unwrap_or_default(),
+            });
+        }
+    }
+
+    helpers
+}
+
+/// 脚本执行总次数指标名称
+pub const METRIC_EXECUTIONS: &str = "monitor_script_executions_total";
+/// 脚本执行失败次数指标名称（按错误类型区分）
+pub const METRIC_FAILURES: &str = "monitor_script_failures_total";
+/// 脚本执行超时次数指标名称
+pub const METRIC_TIMEOUTS: &str = "monitor_script_timeouts_total";
+/// 脚本执行耗时分布指标名称
+pub const METRIC_DURATION: &str = "monitor_script_execution_duration_seconds";
+/// 脚本运行时内存峰值指标名称
+pub const METRIC_MEMORY_PEAK: &str = "monitor_script_memory_peak_bytes";
+
+/// 注册脚本相关指标的说明文本（只执行一次）
+fn describe_metrics() {
+    static DESCRIBE: Once = Once::new();
+    DESCRIBE.call_once(|| {
+        let registry = metrics::global();
+        registry.describe(METRIC_EXECUTIONS, "Total number of script executions");
+        registry.describe(METRIC_FAILURES, "Failed script executions by error type");
+        registry.describe(METRIC_TIMEOUTS, "Script executions that exceeded their timeout");
+        registry.describe(METRIC_DURATION, "Script execution duration in seconds");
+        registry.describe(METRIC_MEMORY_PEAK, "Peak QuickJS runtime memory usage in bytes");
+    });
 }
 
 /// 将JavaScript值转换为Rust的serde_json::Value
@@ -658,37 +963,36 @@ fn js_value_to_serde_value(value: &JsValue) -> Result<Value> {
         let mut map = serde_json::Map::new();
 
         // Check for special object types
-        if let Ok(constructor) = obj.get::<_, JsValue>("constructor") {
-            if let Some(name) = constructor
+        if let Ok(constructor) = obj.get::<_, JsValue>("constructor")
+            && let Some(name) = constructor
                 .as_object()
                 .and_then(|c| c.get::<_, String>("name").ok())
-            {
-                match name.as_str() {
-                    "Date" => {
-                        return Ok(json!({
-                            "__type": "Date",
-                            "timestamp": "date_object"
-                        }));
-                    }
-                    "RegExp" => {
-                        return Ok(json!({
-                            "__type": "RegExp",
-                            "source": "regex_pattern"
-                        }));
-                    }
-                    "Error" => {
-                        let message = obj.get::<_, String>("message").unwrap_or_default();
-                        let name = obj
-                            .get::<_, String>("name")
-                            .unwrap_or_else(|_| "Error".to_string());
-                        return Ok(json!({
-                            "__type": "Error",
-                            "name": name,
-                            "message": message
-                        }));
-                    }
-                    _ => {}
+        {
+            match name.as_str() {
+                "Date" => {
+                    return Ok(json!({
+                        "__type": "Date",
+                        "timestamp": "date_object"
+                    }));
+                }
+                "RegExp" => {
+                    return Ok(json!({
+                        "__type": "RegExp",
+                        "source": "regex_pattern"
+                    }));
+                }
+                "Error" => {
+                    let message = obj.get::<_, String>("message").unwrap_or_default();
+                    let name = obj
+                        .get::<_, String>("name")
+                        .unwrap_or_else(|_| "Error".to_string());
+                    return Ok(json!({
+                        "__type": "Error",
+                        "name": name,
+                        "message": message
+                    }));
                 }
+                _ => {}
             }
         }
 
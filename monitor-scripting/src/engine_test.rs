@@ -1,6 +1,10 @@
 #[cfg(test)]
 mod engine_tests {
-    use crate::{engine::*, models::ValidationContext};
+    use crate::{
+        engine::*,
+        models::{HttpRequestSpec, PreRequestChanges, PreRequestContext, SecurityConfig, ValidationContext},
+    };
+    use monitor_core::metrics;
     use std::{collections::HashMap, time::Duration};
 
     #[tokio::test]
@@ -219,4 +223,167 @@ mod engine_tests {
         assert!(!result.passed);
         // Since we're returning false for status 500, validation should fail
     }
+
+    #[tokio::test]
+    async fn test_validation_verdicts_and_errors() {
+        let engine = ScriptEngine::new().unwrap();
+        let context = ValidationContext {
+            status_code: 503,
+            headers: HashMap::new(),
+            body: r#"{"maintenance": true}"#.to_string(),
+            response_time: 40,
+        };
+        let validate = |script: &'static str| {
+            let engine = &engine;
+            let context = &context;
+            async move { engine.execute_validation_script(script, context).await.unwrap() }
+        };
+
+        let verdict = validate("({ passed: JSON.parse(context.body).maintenance, message: 'in maintenance' })").await;
+        assert!(verdict.passed && verdict.verdict);
+        assert_eq!(verdict.message, "in maintenance");
+
+        let falsy = validate("context.status_code === 200").await;
+        assert!(!falsy.passed && !falsy.verdict && !falsy.is_script_error());
+        assert_eq!(falsy.message, "Validation script returned false");
+
+        let rejected = validate("assert(context.status_code === 200, 'Status code should be 200')").await;
+        assert!(!rejected.passed && !rejected.is_script_error());
+        assert_eq!(rejected.message, "AssertionError: Status code should be 200");
+
+        let broken = validate("context.body.missing.field").await;
+        assert!(!broken.passed && broken.is_script_error());
+        assert!(broken.message.starts_with("TypeError"), "{}", broken.message);
+    }
+
+    #[tokio::test]
+    async fn test_execution_metrics_recorded_per_profile() {
+        let security_config = SecurityConfig {
+            profile: "metrics-test".to_string(),
+            ..SecurityConfig::default()
+        };
+        let engine = ScriptEngine::with_security_config(security_config).unwrap();
+        let context = serde_json::json!({});
+
+        engine.execute_script("1 + 1", &context).await.unwrap();
+        engine
+            .execute_script("null.someMethod()", &context)
+            .await
+            .unwrap();
+
+        let registry = metrics::global();
+        let labels = [("profile", "metrics-test")];
+        assert_eq!(registry.counter_value(METRIC_EXECUTIONS, &labels), 2.0);
+        assert_eq!(registry.histogram_count(METRIC_DURATION, &labels), 2);
+        assert!(registry.gauge_value(METRIC_MEMORY_PEAK, &labels).unwrap() > 0.0);
+
+        let failures: f64 = ["exception", "type_error", "runtime_error"]
+            .iter()
+            .map(|t| {
+                registry.counter_value(
+                    METRIC_FAILURES,
+                    &[("profile", "metrics-test"), ("error_type", t)],
+                )
+            })
+            .sum();
+        assert_eq!(failures, 1.0);
+        assert!(registry.render().contains("monitor_script_executions_total{profile=\"metrics-test\"} 2"));
+    }
+
+    #[test]
+    fn test_capabilities_reflect_security_profile() {
+        let strict = SecurityConfig::strict();
+        let capabilities = ScriptEngine::describe_capabilities(&strict, Duration::from_secs(5));
+
+        assert_eq!(capabilities.profile, "strict");
+        assert_eq!(capabilities.limits.memory_limit_bytes, strict.memory_limit);
+        assert_eq!(capabilities.limits.timeout_ms, 5000);
+        assert!(capabilities.denied_functions.contains(&"eval".to_string()));
+        assert!(capabilities.host_objects.contains(&"context".to_string()));
+
+        let assert_status = capabilities
+            .helper_functions
+            .iter()
+            .find(|f| f.name == "assertStatus")
+            .expect("assertStatus should be listed");
+        assert_eq!(assert_status.params, vec!["statusCode", "expected", "message"]);
+        assert!(!assert_status.description.is_empty());
+
+        let engine = ScriptEngine::new().unwrap();
+        assert_eq!(engine.capabilities().profile, "default");
+    }
+
+    fn pre_request_context() -> PreRequestContext {
+        PreRequestContext {
+            monitor: serde_json::json!({ "name": "orders", "tags": ["prod"] }),
+            request: HttpRequestSpec {
+                method: "POST".to_string(),
+                url: "https://example.com/orders".to_string(),
+                headers: Default::default(),
+                body: Some("{\"id\":1}".to_string()),
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hmac_and_jwt_helpers() {
+        let engine = ScriptEngine::new().unwrap();
+        let result = engine
+            .execute_script("hmacSha256('key', 'The quick brown fox jumps over the lazy dog')", &serde_json::json!({}))
+            .await
+            .unwrap();
+        assert_eq!(
+            result.result,
+            Some(serde_json::json!("f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8"))
+        );
+
+        let result = engine
+            .execute_script("signJwt({ sub: 'monitor', iat: 0 }, 'secret')", &serde_json::json!({}))
+            .await
+            .unwrap();
+        let token = result.result.unwrap();
+        let parts: Vec<&str> = token.as_str().unwrap().split('.').collect();
+        assert_eq!(parts.len(), 3);
+        assert_eq!(parts[0], "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9");
+        assert_eq!(
+            parts[2],
+            crate::host_functions::hmac_sha256("secret", &format!("{}.{}", parts[0], parts[1]), "base64url")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pre_request_script_changes() {
+        let engine = ScriptEngine::new().unwrap();
+        let script = r#"
+            const signature = hmacSha256('secret', context.request.body);
+            return {
+                headers: { 'X-Signature': signature, 'X-Attempt': 1 },
+                query: { monitor: context.monitor.name },
+            };
+        "#;
+
+        let changes = engine
+            .execute_pre_request_script(script, &pre_request_context())
+            .await
+            .unwrap();
+        assert_eq!(
+            changes.headers["X-Signature"],
+            crate::host_functions::hmac_sha256("secret", "{\"id\":1}", "hex")
+        );
+        assert_eq!(changes.headers["X-Attempt"], "1");
+        assert_eq!(changes.query["monitor"], "orders");
+        assert_eq!(changes.body, None);
+    }
+
+    #[tokio::test]
+    async fn test_pre_request_script_errors() {
+        let engine = ScriptEngine::new().unwrap();
+        let context = pre_request_context();
+
+        let unchanged = engine.execute_pre_request_script("undefined", &context).await.unwrap();
+        assert_eq!(unchanged, PreRequestChanges::default());
+
+        assert!(engine.execute_pre_request_script("'not an object'", &context).await.is_err());
+        assert!(engine.execute_pre_request_script("throw new Error('boom')", &context).await.is_err());
+    }
 }
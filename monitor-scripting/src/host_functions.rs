@@ -0,0 +1,74 @@
+/// 宿主函数模块
+///
+/// 为脚本提供QuickJS本身缺少的摘要、签名和编码能力，
+/// 由utility_functions.js中的工具函数包装后供脚本使用
+use base64::{
+    Engine,
+    engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD},
+};
+use hmac::{Hmac, Mac};
+use monitor_core::{Error, Result};
+use rquickjs::{Ctx, Function, function::Opt};
+use sha2::{Digest, Sha256};
+
+/// 按指定编码输出字节
+///
+/// # 参数
+/// * `bytes` - 原始字节
+/// * `encoding` - `hex`（默认）、`base64` 或 `base64url`（无填充）
+pub fn encode(bytes: &[u8], encoding: &str) -> String {
+    match encoding {
+        "base64" => STANDARD.encode(bytes),
+        "base64url" => URL_SAFE_NO_PAD.encode(bytes),
+        _ => hex::encode(bytes),
+    }
+}
+
+/// 计算SHA-256摘要
+pub fn sha256(message: &str, encoding: &str) -> String {
+    encode(&Sha256::digest(message.as_bytes()), encoding)
+}
+
+/// 计算HMAC-SHA256签名
+pub fn hmac_sha256(key: &str, message: &str, encoding: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(key.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(message.as_bytes());
+    encode(&mac.finalize().into_bytes(), encoding)
+}
+
+/// 将原生函数注册到脚本上下文的全局对象
+///
+/// # 参数
+/// * `ctx` - JavaScript执行上下文
+///
+/// # 实现逻辑
+/// 1. 为每个原生函数创建rquickjs函数对象
+/// 2. 以双下划线前缀名称挂载到全局对象，避免与用户脚本冲突
+pub fn register(ctx: &Ctx<'_>) -> Result<()> {
+    let global = ctx.globals();
+    let map_err = |e: rquickjs::Error| Error::script_execution(format!("Failed to register host functions: {}", e));
+
+    let sha = Function::new(ctx.clone(), |message: String, encoding: Opt<String>| {
+        sha256(&message, encoding.0.as_deref().unwrap_or("hex"))
+    })
+    .map_err(map_err)?;
+    global.set("__sha256", sha).map_err(map_err)?;
+
+    let hmac = Function::new(
+        ctx.clone(),
+        |key: String, message: String, encoding: Opt<String>| {
+            hmac_sha256(&key, &message, encoding.0.as_deref().unwrap_or("hex"))
+        },
+    )
+    .map_err(map_err)?;
+    global.set("__hmacSha256", hmac).map_err(map_err)?;
+
+    let base64 = Function::new(ctx.clone(), |text: String, encoding: Opt<String>| {
+        encode(text.as_bytes(), encoding.0.as_deref().unwrap_or("base64"))
+    })
+    .map_err(map_err)?;
+    global.set("__base64Encode", base64).map_err(map_err)?;
+
+    Ok(())
+}
@@ -1,6 +1,11 @@
 pub mod engine;
+pub mod host_functions;
 pub mod models;
+pub mod pool;
 
 
 #[cfg(test)]
 pub mod engine_test;
+
+#[cfg(test)]
+pub mod pool_test;
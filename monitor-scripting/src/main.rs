@@ -4,6 +4,7 @@ use std::collections::HashMap;
 use tracing::info;
 
 pub mod engine;
+pub mod host_functions;
 pub mod models;
 
 #[tokio::main]
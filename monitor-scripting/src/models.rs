@@ -24,18 +24,163 @@ pub struct ValidationContext {
     pub response_time: u64,
 }
 
+/// 预请求脚本可以修改的HTTP请求
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct HttpRequestSpec {
+    pub method: String,
+    pub url: String,
+    pub headers: std::collections::BTreeMap<String, String>,
+    pub body: Option<String>,
+}
+
+/// 预请求脚本的上下文数据
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct PreRequestContext {
+    /// 监控配置（不含凭据）
+    pub monitor: Value,
+    /// 即将发送的请求
+    pub request: HttpRequestSpec,
+}
+
+/// 预请求脚本对请求的修改
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PreRequestChanges {
+    /// 新增或覆盖的请求头
+    pub headers: std::collections::BTreeMap<String, String>,
+    /// 追加的查询参数
+    pub query: std::collections::BTreeMap<String, String>,
+    /// 替换后的请求体
+    pub body: Option<String>,
+}
+
+impl PreRequestChanges {
+    /// 从脚本返回值解析请求修改
+    ///
+    /// # 参数
+    /// * `result` - 脚本返回值；`undefined` 或 `null` 表示不做修改
+    ///
+    /// # 实现逻辑
+    /// 1. 返回值必须是对象，否则返回校验错误
+    /// 2. 请求头和查询参数中的非字符串值转换为其JSON文本
+    pub fn from_script_result(result: Option<&Value>) -> monitor_core::Result<Self> {
+        let object = match result {
+            None | Some(Value::Null) => return Ok(Self::default()),
+            Some(Value::Object(map)) if map.get("__type").and_then(Value::as_str) == Some("undefined") => {
+                return Ok(Self::default());
+            }
+            Some(Value::Object(map)) => map,
+            Some(other) => {
+                return Err(monitor_core::Error::validation(format!(
+                    "Pre-request script must return an object, got {}",
+                    other
+                )));
+            }
+        };
+
+        let string_map = |key: &str| -> std::collections::BTreeMap<String, String> {
+            object
+                .get(key)
+                .and_then(Value::as_object)
+                .map(|map| {
+                    map.iter()
+                        .map(|(k, v)| (k.clone(), value_to_string(v)))
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+
+        Ok(Self {
+            headers: string_map("headers"),
+            query: string_map("query"),
+            body: object.get("body").filter(|v| !v.is_null()).map(value_to_string),
+        })
+    }
+}
+
+/// JavaScript数字均为浮点数，整数值按整数格式输出
+fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Number(n) => match n.as_f64() {
+            Some(f) if f.fract() == 0.0 && f.abs() < 9_007_199_254_740_992.0 => (f as i64).to_string(),
+            _ => n.to_string(),
+        },
+        other => other.to_string(),
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ValidationResult {
     pub passed: bool,
     pub message: String,
+    /// The script returned `{ passed, message }`, which decides the check on
+    /// its own instead of adding to the status code comparison
+    pub verdict: bool,
     pub details: Option<Value>,
     pub error_details: Option<Value>,
     pub execution_time_ms: u64,
 }
 
+impl ValidationResult {
+    /// The script itself failed to run (syntax, reference or type errors,
+    /// timeouts) rather than rejecting the response. Thrown assertions such
+    /// as `assert()` count as rejections.
+    pub fn is_script_error(&self) -> bool {
+        self.error_details
+            .as_ref()
+            .and_then(|e| e.get("type"))
+            .and_then(|v| v.as_str())
+            .is_some_and(|error_type| error_type != "exception")
+    }
+}
+
+/// 脚本可用的工具函数描述
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct HelperFunction {
+    /// 函数名称
+    pub name: String,
+    /// 参数列表（保留默认值写法）
+    pub params: Vec<String>,
+    /// 函数说明（取自JSDoc首行）
+    pub description: String,
+}
+
+/// 脚本执行限制
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ScriptLimits {
+    pub memory_limit_bytes: usize,
+    pub stack_size_bytes: usize,
+    pub timeout_ms: u64,
+    pub max_loop_iterations: Option<u64>,
+    pub max_recursion_depth: Option<u32>,
+}
+
+/// 脚本运行环境能力描述，由实际引擎配置生成
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ScriptCapabilities {
+    /// 安全配置档案名称
+    pub profile: String,
+    /// 可用的工具函数
+    pub helper_functions: Vec<HelperFunction>,
+    /// 注入的宿主对象
+    pub host_objects: Vec<String>,
+    /// 执行限制
+    pub limits: ScriptLimits,
+    /// 被禁用的全局函数
+    pub denied_functions: Vec<String>,
+    /// 被禁用的对象属性
+    pub denied_properties: Vec<String>,
+    pub eval_disabled: bool,
+    pub function_constructor_disabled: bool,
+    pub modules_disabled: bool,
+    pub strict_mode: bool,
+}
+
 /// 安全配置结构体
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct SecurityConfig {
+    /// 安全配置档案名称（用于指标标签）
+    pub profile: String,
     /// 内存限制（字节）
     pub memory_limit: usize,
     /// 栈大小限制（字节）
@@ -88,6 +233,7 @@ impl Default for SecurityConfig {
         denied_properties.insert("prototype".to_string());
 
         Self {
+            profile: "default".to_string(),
             memory_limit: DEFAULT_MEMORY_LIMIT,
             stack_size: DEFAULT_STACK_SIZE,
             denied_functions,
@@ -112,6 +258,7 @@ impl SecurityConfig {
         denied_properties.insert("__proto__".to_string());
 
         Self {
+            profile: "permissive".to_string(),
             memory_limit: DEFAULT_MEMORY_LIMIT * 4, // 32MB
             stack_size: DEFAULT_STACK_SIZE * 4,     // 2MB
             denied_functions: HashSet::new(),
@@ -169,6 +316,7 @@ impl SecurityConfig {
         denied_properties.insert("arguments".to_string());
 
         Self {
+            profile: "strict".to_string(),
             memory_limit: DEFAULT_MEMORY_LIMIT / 2, // 4MB
             stack_size: DEFAULT_STACK_SIZE / 2,     // 256KB
             denied_functions,
@@ -184,6 +332,22 @@ impl SecurityConfig {
         }
     }
 
+    /// 根据档案名称创建安全配置
+    ///
+    /// # 参数
+    /// * `profile` - 档案名称（default、permissive、strict）
+    ///
+    /// # 返回值
+    /// 未知档案名称返回None
+    pub fn from_profile(profile: &str) -> Option<Self> {
+        match profile {
+            "default" => Some(Self::default()),
+            "permissive" => Some(Self::permissive()),
+            "strict" => Some(Self::strict()),
+            _ => None,
+        }
+    }
+
     /// 添加禁用函数
     pub fn deny_function(&mut self, function_name: &str) -> &mut Self {
         self.denied_functions.insert(function_name.to_string());
@@ -0,0 +1,215 @@
+/// 脚本引擎池模块
+///
+/// QuickJS运行时不能跨线程使用，池中的每个引擎都固定在自己的线程上，
+/// 借出后脚本通过通道交给该线程执行。池的大小同时限制了并发执行的脚本数
+/// 和运行时占用的内存总量
+use monitor_core::{Error, Result};
+use std::{
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        Mutex,
+        atomic::{AtomicBool, Ordering},
+        mpsc,
+    },
+    thread,
+    time::Duration,
+};
+use tokio::{
+    runtime::Handle,
+    sync::{Semaphore, SemaphorePermit, oneshot},
+};
+use tracing::warn;
+
+use crate::{
+    engine::ScriptEngine,
+    models::{PreRequestChanges, PreRequestContext, SecurityConfig, ValidationContext, ValidationResult},
+};
+
+/// 在引擎线程上执行的任务；引擎创建失败时收到错误
+type Job = Box<dyn FnOnce(Result<&ScriptEngine>) + Send>;
+
+/// 引擎的创建参数，与上一个任务不同时引擎线程会重建引擎
+#[derive(Clone, PartialEq)]
+struct EngineSettings {
+    timeout: Duration,
+    security_config: SecurityConfig,
+}
+
+/// 持有一个引擎的线程
+struct Worker {
+    jobs: mpsc::Sender<(EngineSettings, Job)>,
+}
+
+impl Worker {
+    fn spawn() -> Result<Self> {
+        let (jobs, queue) = mpsc::channel::<(EngineSettings, Job)>();
+        thread::Builder::new()
+            .name("script-engine".to_string())
+            .spawn(move || {
+                let mut engine: Option<(EngineSettings, ScriptEngine)> = None;
+                for (settings, job) in queue {
+                    if engine.as_ref().is_none_or(|(current, _)| *current != settings) {
+                        engine = None;
+                        match ScriptEngine::with_config(settings.timeout, settings.security_config.clone()) {
+                            Ok(created) => engine = Some((settings, created)),
+                            Err(e) => {
+                                job(Err(e));
+                                continue;
+                            }
+                        }
+                    }
+                    let Some((_, current)) = engine.as_ref() else { continue };
+                    // 发生panic的引擎状态不可信，下一个任务使用新引擎
+                    if panic::catch_unwind(AssertUnwindSafe(|| job(Ok(current)))).is_err() {
+                        warn!("Script engine panicked, replacing it");
+                        engine = None;
+                    }
+                }
+            })
+            .map_err(|e| Error::script_execution(format!("Failed to start script engine thread: {}", e)))?;
+        Ok(Self { jobs })
+    }
+}
+
+/// 可供多个异步任务共享的脚本引擎池
+///
+/// 引擎在首次借出时才创建，之后一直保留并在借出之间复用；
+/// 所有引擎都被借出时，`checkout` 会等待其他任务归还
+///
+/// # 示例
+/// ```
+/// # use monitor_scripting::{models::{SecurityConfig, ValidationContext}, pool::ScriptEnginePool};
+/// # use std::time::Duration;
+/// # async fn example(context: ValidationContext) -> monitor_core::Result<()> {
+/// let pool = ScriptEnginePool::new(4);
+/// let engine = pool.checkout(Duration::from_secs(5), SecurityConfig::default()).await?;
+/// let result = engine.execute_validation_script("context.status_code === 200", &context).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct ScriptEnginePool {
+    /// 已创建且未被借出的引擎
+    idle: Mutex<Vec<Worker>>,
+    permits: Semaphore,
+    size: usize,
+}
+
+impl ScriptEnginePool {
+    /// 创建最多包含 `size` 个引擎的池，`size` 为0时按1处理
+    pub fn new(size: usize) -> Self {
+        let size = size.max(1);
+        Self {
+            idle: Mutex::new(Vec::with_capacity(size)),
+            permits: Semaphore::new(size),
+            size,
+        }
+    }
+
+    /// 池中引擎的上限
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// 当前可以立即借出的引擎数
+    pub fn available(&self) -> usize {
+        self.permits.available_permits()
+    }
+
+    /// 借出一个使用指定超时时间和安全配置的引擎
+    ///
+    /// # 参数
+    /// * `timeout` - 脚本执行的最大允许时间
+    /// * `security_config` - 安全配置，与引擎上次使用的配置不同时会重建引擎
+    ///
+    /// # 返回值
+    /// 返回借出的引擎，释放时自动归还到池中
+    pub async fn checkout(&self, timeout: Duration, security_config: SecurityConfig) -> Result<PooledEngine<'_>> {
+        let permit = self
+            .permits
+            .acquire()
+            .await
+            .map_err(|_| Error::script_execution("Script engine pool is closed"))?;
+        let idle = self.idle.lock().unwrap().pop();
+        let worker = match idle {
+            Some(worker) => worker,
+            None => Worker::spawn()?,
+        };
+        Ok(PooledEngine {
+            pool: self,
+            worker: Some(worker),
+            settings: EngineSettings {
+                timeout,
+                security_config,
+            },
+            broken: AtomicBool::new(false),
+            _permit: permit,
+        })
+    }
+}
+
+/// 从 `ScriptEnginePool` 借出的引擎，释放时归还
+pub struct PooledEngine<'a> {
+    pool: &'a ScriptEnginePool,
+    worker: Option<Worker>,
+    settings: EngineSettings,
+    /// 引擎线程已经退出，不再归还到池中
+    broken: AtomicBool,
+    _permit: SemaphorePermit<'a>,
+}
+
+impl PooledEngine<'_> {
+    /// 执行验证脚本，参见 `ScriptEngine::execute_validation_script`
+    pub async fn execute_validation_script(
+        &self,
+        script: &str,
+        response_data: &ValidationContext,
+    ) -> Result<ValidationResult> {
+        let script = script.to_string();
+        let response_data = response_data.clone();
+        let handle = Handle::current();
+        self.run(move |engine| handle.block_on(engine.execute_validation_script(&script, &response_data)))
+            .await
+    }
+
+    /// 执行预请求脚本，参见 `ScriptEngine::execute_pre_request_script`
+    pub async fn execute_pre_request_script(
+        &self,
+        script: &str,
+        request_context: &PreRequestContext,
+    ) -> Result<PreRequestChanges> {
+        let script = script.to_string();
+        let request_context = request_context.clone();
+        let handle = Handle::current();
+        self.run(move |engine| handle.block_on(engine.execute_pre_request_script(&script, &request_context)))
+            .await
+    }
+
+    /// 在引擎线程上执行 `job` 并等待结果
+    async fn run<T: Send + 'static>(
+        &self,
+        job: impl FnOnce(&ScriptEngine) -> Result<T> + Send + 'static,
+    ) -> Result<T> {
+        let (sender, receiver) = oneshot::channel();
+        let job: Job = Box::new(move |engine: Result<&ScriptEngine>| {
+            let _ = sender.send(engine.and_then(job));
+        });
+        let worker = self.worker.as_ref().expect("engine is checked out");
+        if worker.jobs.send((self.settings.clone(), job)).is_err() {
+            self.broken.store(true, Ordering::Relaxed);
+            return Err(Error::script_execution("Script engine thread has stopped"));
+        }
+        receiver
+            .await
+            .map_err(|_| Error::script_execution("Script engine panicked while running the script"))?
+    }
+}
+
+impl Drop for PooledEngine<'_> {
+    fn drop(&mut self) {
+        if let Some(worker) = self.worker.take()
+            && !self.broken.load(Ordering::Relaxed)
+        {
+            self.pool.idle.lock().unwrap().push(worker);
+        }
+    }
+}
@@ -0,0 +1,72 @@
+#[cfg(test)]
+mod pool_tests {
+    use crate::{
+        models::{SecurityConfig, ValidationContext},
+        pool::*,
+    };
+    use std::{collections::HashMap, sync::Arc, time::Duration};
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    fn context(status_code: u16) -> ValidationContext {
+        ValidationContext {
+            status_code,
+            headers: HashMap::new(),
+            body: String::new(),
+            response_time: 10,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_engines_are_returned_and_reused() {
+        let pool = ScriptEnginePool::new(2);
+        for status_code in [200, 500] {
+            let engine = pool.checkout(TIMEOUT, SecurityConfig::default()).await.unwrap();
+            assert_eq!(pool.available(), 1);
+            let result = engine
+                .execute_validation_script("context.status_code === 200", &context(status_code))
+                .await
+                .unwrap();
+            assert_eq!(result.passed, status_code == 200);
+        }
+        assert_eq!(pool.available(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_checkout_waits_for_a_free_engine() {
+        let pool = ScriptEnginePool::new(1);
+        let engine = pool.checkout(TIMEOUT, SecurityConfig::default()).await.unwrap();
+        let waiting = tokio::time::timeout(
+            Duration::from_millis(50),
+            pool.checkout(TIMEOUT, SecurityConfig::default()),
+        )
+        .await;
+        assert!(waiting.is_err());
+
+        drop(engine);
+        let engine = pool.checkout(TIMEOUT, SecurityConfig::strict()).await.unwrap();
+        let result = engine.execute_validation_script("true", &context(200)).await.unwrap();
+        assert!(result.passed);
+    }
+
+    #[tokio::test]
+    async fn test_scripts_run_concurrently_within_the_pool_size() {
+        let pool = Arc::new(ScriptEnginePool::new(4));
+        let mut tasks = Vec::new();
+        for i in 0..16u16 {
+            let pool = pool.clone();
+            tasks.push(tokio::spawn(async move {
+                let engine = pool.checkout(TIMEOUT, SecurityConfig::default()).await.unwrap();
+                engine
+                    .execute_validation_script("context.status_code % 2 === 0", &context(200 + i))
+                    .await
+                    .unwrap()
+                    .passed
+            }));
+        }
+        for (i, task) in tasks.into_iter().enumerate() {
+            assert_eq!(task.await.unwrap(), i.is_multiple_of(2));
+        }
+        assert_eq!(pool.available(), pool.size());
+    }
+}
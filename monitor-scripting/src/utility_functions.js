@@ -251,3 +251,52 @@ function time(label) {
     },
   };
 }
+
+/**
+ * 计算SHA-256摘要
+ * @param {string} message - 要计算摘要的文本
+ * @param {string} encoding - 输出编码：hex（默认）、base64 或 base64url
+ * 输出：返回编码后的摘要字符串
+ * 逻辑：调用宿主提供的原生实现
+ */
+function sha256(message, encoding = "hex") {
+  return __sha256(String(message), encoding);
+}
+
+/**
+ * 计算HMAC-SHA256签名
+ * @param {string} key - 签名密钥
+ * @param {string} message - 要签名的文本
+ * @param {string} encoding - 输出编码：hex（默认）、base64 或 base64url
+ * 输出：返回编码后的签名字符串
+ * 逻辑：调用宿主提供的原生实现
+ */
+function hmacSha256(key, message, encoding = "hex") {
+  return __hmacSha256(String(key), String(message), encoding);
+}
+
+/**
+ * Base64编码文本
+ * @param {string} text - 要编码的文本（按UTF-8处理）
+ * @param {boolean} urlSafe - 是否使用无填充的URL安全字母表
+ * 输出：返回Base64字符串
+ * 逻辑：调用宿主提供的原生实现
+ */
+function base64Encode(text, urlSafe = false) {
+  return __base64Encode(String(text), urlSafe ? "base64url" : "base64");
+}
+
+/**
+ * 生成HS256签名的JWT
+ * @param {object} payload - JWT载荷，未提供iat时自动填入当前时间
+ * @param {string} secret - HMAC签名密钥
+ * 输出：返回紧凑格式的JWT字符串
+ * 逻辑：分别对头部和载荷进行base64url编码，再对两者拼接结果进行HMAC-SHA256签名
+ */
+function signJwt(payload, secret) {
+  const claims = Object.assign({ iat: Math.floor(Date.now() / 1000) }, payload);
+  const header = base64Encode(JSON.stringify({ alg: "HS256", typ: "JWT" }), true);
+  const body = base64Encode(JSON.stringify(claims), true);
+  const signature = hmacSha256(secret, `${header}.${body}`, "base64url");
+  return `${header}.${body}.${signature}`;
+}